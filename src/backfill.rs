@@ -0,0 +1,353 @@
+//! Historical Backfill
+//!
+//! Walks a long date range chunk by chunk through the regular fetch
+//! pipeline, saving progress after every chunk so a restart resumes it.
+
+use crate::config::technical;
+use crate::error::AppError;
+use crate::models::admin::{BackfillRequest, BackfillState, BackfillStatus};
+use crate::scheduler::{FetchWindow, SchedulerContext, run_fetch};
+use crate::services::{RunStatus, parse_rfc3339_utc};
+use chrono::{DateTime, Duration, Utc};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, instrument, warn};
+
+/// Chunk size when the request does not set one
+const DEFAULT_CHUNK_MINUTES: i64 = 60;
+/// Images per camera and chunk when the request does not set a limit
+const DEFAULT_CHUNK_LIMIT: u32 = 1000;
+
+/// Backfill job state shared with the admin endpoints
+pub struct Backfill {
+    path: PathBuf,
+    status: RwLock<Option<BackfillStatus>>,
+    pause_requested: AtomicBool,
+    /// Cancels the chunk in flight when a pause is requested
+    cancel: Mutex<CancellationToken>,
+}
+
+impl Backfill {
+    /// Load the saved job from `path` (a missing file means no job)
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AppError> {
+        let path = path.as_ref().to_path_buf();
+
+        let status = match std::fs::read_to_string(&path) {
+            Ok(content) => Some(serde_json::from_str(&content).map_err(|e| {
+                AppError::Config(format!(
+                    "Failed to parse backfill state {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                return Err(AppError::Io(format!(
+                    "Failed to read backfill state {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+        };
+
+        Ok(Self {
+            path,
+            status: RwLock::new(status),
+            pause_requested: AtomicBool::new(false),
+            cancel: Mutex::new(CancellationToken::new()),
+        })
+    }
+
+    pub fn status(&self) -> Option<BackfillStatus> {
+        self.status
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Whether the saved job was running when the process stopped
+    pub fn interrupted(&self) -> bool {
+        self.status()
+            .is_some_and(|s| s.state == BackfillState::Running)
+    }
+
+    /// Replace any finished or stopped job with a new one; returns `false`
+    /// if a job is running
+    pub fn try_start(&self, job: BackfillStatus) -> Result<bool, AppError> {
+        {
+            let mut status = self.status.write().unwrap_or_else(|e| e.into_inner());
+            if status
+                .as_ref()
+                .is_some_and(|s| s.state == BackfillState::Running)
+            {
+                return Ok(false);
+            }
+            *status = Some(job);
+        }
+        self.reset_pause();
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Mark a paused or failed job as running again; returns `false` if
+    /// there is nothing to resume
+    pub fn try_resume(&self) -> Result<bool, AppError> {
+        let resumed = self.update(|status| {
+            if matches!(status.state, BackfillState::Paused | BackfillState::Failed) {
+                status.state = BackfillState::Running;
+                status.last_error = None;
+                true
+            } else {
+                false
+            }
+        });
+        if resumed == Some(true) {
+            self.reset_pause();
+            self.save()?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Stop the running job, abandoning the chunk in flight (it is fetched
+    /// again on resume); returns `false` if no job is running
+    pub fn request_pause(&self) -> bool {
+        let running = self
+            .status()
+            .is_some_and(|s| s.state == BackfillState::Running);
+        if running {
+            self.pause_requested.store(true, Ordering::SeqCst);
+            self.cancel_token().cancel();
+        }
+        running
+    }
+
+    fn cancel_token(&self) -> CancellationToken {
+        self.cancel
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn reset_pause(&self) {
+        self.pause_requested.store(false, Ordering::SeqCst);
+        *self.cancel.lock().unwrap_or_else(|e| e.into_inner()) = CancellationToken::new();
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut BackfillStatus) -> T) -> Option<T> {
+        let mut status = self.status.write().unwrap_or_else(|e| e.into_inner());
+        status.as_mut().map(|status| {
+            let result = f(status);
+            status.updated_at = Utc::now().to_rfc3339();
+            result
+        })
+    }
+
+    /// Write the job to disk, atomically replacing the previous state
+    fn save(&self) -> Result<(), AppError> {
+        let Some(status) = self.status() else {
+            return Ok(());
+        };
+        let content = serde_json::to_string_pretty(&status)
+            .map_err(|e| AppError::Io(format!("Failed to serialize backfill state: {}", e)))?;
+
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, content)
+            .and_then(|_| std::fs::rename(&tmp, &self.path))
+            .map_err(|e| {
+                AppError::Io(format!(
+                    "Failed to write backfill state {}: {}",
+                    self.path.display(),
+                    e
+                ))
+            })
+    }
+
+    fn save_or_warn(&self) {
+        if let Err(e) = self.save() {
+            warn!(error = %e, "Backfill progress not saved");
+        }
+    }
+
+    fn finish(&self, state: BackfillState) {
+        self.update(|status| status.state = state);
+        self.save_or_warn();
+    }
+
+    /// Fetch chunks from the cursor until the range is done, the job is
+    /// paused, or a chunk keeps failing. Call `try_start` or `try_resume` first.
+    #[instrument(skip_all)]
+    pub async fn run(&self, ctx: &SchedulerContext) {
+        let cancel = self.cancel_token();
+        let mut attempts = 0;
+
+        loop {
+            let Some(job) = self.status() else { return };
+            if job.state != BackfillState::Running {
+                return;
+            }
+            if self.pause_requested.swap(false, Ordering::SeqCst) {
+                info!(id = %job.id, chunks_done = job.chunks_done, "Backfill paused");
+                self.finish(BackfillState::Paused);
+                return;
+            }
+
+            let (cursor, end) = match (
+                parse_rfc3339_utc(&job.cursor),
+                parse_rfc3339_utc(&job.end_date),
+            ) {
+                (Ok(cursor), Ok(end)) => (cursor, end),
+                (Err(e), _) | (_, Err(e)) => {
+                    error!(error = %e, "Backfill state is corrupt");
+                    self.update(|status| status.last_error = Some(e.to_string()));
+                    self.finish(BackfillState::Failed);
+                    return;
+                }
+            };
+            if cursor >= end {
+                info!(id = %job.id, inserted = job.inserted, "Backfill completed");
+                self.finish(BackfillState::Completed);
+                return;
+            }
+
+            if ctx.maintenance.is_enabled() {
+                info!("Maintenance mode active, backfill waiting");
+                retry_delay(&cancel).await;
+                continue;
+            }
+
+            let chunk_end = (cursor + Duration::minutes(job.chunk_minutes)).min(end);
+            let window = FetchWindow::between(cursor, chunk_end, job.cctv_id.clone(), job.limit);
+            let tally = run_fetch(ctx, &window, &cancel, None).await;
+
+            match tally.status() {
+                // Paused mid-chunk; the next iteration records it
+                RunStatus::Cancelled => continue,
+                RunStatus::Failed => {
+                    attempts += 1;
+                    let message = tally.first_error().unwrap_or("Chunk failed").to_string();
+                    warn!(
+                        chunk_start = %window.date_start,
+                        attempt = attempts,
+                        error = %message,
+                        "Backfill chunk failed"
+                    );
+                    self.update(|status| status.last_error = Some(message));
+                    if attempts >= technical::BACKFILL_CHUNK_ATTEMPTS {
+                        self.finish(BackfillState::Failed);
+                        return;
+                    }
+                    self.save_or_warn();
+                    retry_delay(&cancel).await;
+                    continue;
+                }
+                _ => {}
+            }
+
+            attempts = 0;
+            self.update(|status| {
+                status.cursor = chunk_end.to_rfc3339();
+                status.chunks_done += 1;
+                status.images_fetched += tally.fetched;
+                status.skipped += tally.skipped;
+                status.inserted += tally.inserted;
+                status.failed += tally.failed;
+                status.last_error = tally.first_error().map(str::to_string);
+            });
+            self.save_or_warn();
+        }
+    }
+}
+
+/// Wait before retrying, returning early on pause
+async fn retry_delay(cancel: &CancellationToken) {
+    let delay = std::time::Duration::from_secs(technical::BACKFILL_RETRY_DELAY_SECS);
+    cancel.run_until_cancelled(tokio::time::sleep(delay)).await;
+}
+
+/// Validate a request and build the job for it
+pub fn new_job(request: &BackfillRequest) -> Result<BackfillStatus, AppError> {
+    let start = parse_rfc3339_utc(&request.start_date)?;
+    let end = parse_rfc3339_utc(&request.end_date)?;
+    if start >= end {
+        return Err(AppError::InvalidRequest(
+            "start_date must be before end_date".to_string(),
+        ));
+    }
+
+    let chunk_minutes = request.chunk_minutes.unwrap_or(DEFAULT_CHUNK_MINUTES);
+    if !(1..=24 * 60).contains(&chunk_minutes) {
+        return Err(AppError::InvalidRequest(
+            "chunk_minutes must be between 1 and 1440".to_string(),
+        ));
+    }
+    let limit = request.limit.unwrap_or(DEFAULT_CHUNK_LIMIT);
+    if limit == 0 {
+        return Err(AppError::InvalidRequest("limit must be >= 1".to_string()));
+    }
+
+    let now = Utc::now().to_rfc3339();
+    Ok(BackfillStatus {
+        id: format!("{:016x}", rand::random::<u64>()),
+        state: BackfillState::Running,
+        start_date: start.to_rfc3339(),
+        end_date: end.to_rfc3339(),
+        chunk_minutes,
+        cctv_id: request
+            .cctv_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(str::to_string),
+        limit,
+        cursor: start.to_rfc3339(),
+        chunks_done: 0,
+        chunks_total: chunk_count(start, end, chunk_minutes),
+        images_fetched: 0,
+        skipped: 0,
+        inserted: 0,
+        failed: 0,
+        started_at: now.clone(),
+        updated_at: now,
+        last_error: None,
+    })
+}
+
+/// Number of chunks of `chunk_minutes` needed to cover `start..end`
+fn chunk_count(start: DateTime<Utc>, end: DateTime<Utc>, chunk_minutes: i64) -> u64 {
+    let chunk_secs = chunk_minutes * 60;
+    ((end - start).num_seconds() + chunk_secs - 1).div_euclid(chunk_secs) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(chunk_minutes: Option<i64>) -> BackfillRequest {
+        BackfillRequest {
+            start_date: "2025-01-01T00:00:00+07:00".to_string(),
+            end_date: "2025-01-01T05:30:00+07:00".to_string(),
+            chunk_minutes,
+            cctv_id: Some(" ".to_string()),
+            limit: None,
+        }
+    }
+
+    #[test]
+    fn test_new_job() {
+        let job = new_job(&request(None)).unwrap();
+        assert_eq!(job.chunks_total, 6);
+        assert_eq!(job.cursor, "2024-12-31T17:00:00+00:00");
+        assert_eq!(job.limit, DEFAULT_CHUNK_LIMIT);
+        assert_eq!(job.cctv_id, None);
+
+        assert_eq!(new_job(&request(Some(30))).unwrap().chunks_total, 11);
+        assert!(new_job(&request(Some(0))).is_err());
+
+        let mut reversed = request(None);
+        reversed.end_date = reversed.start_date.clone();
+        assert!(new_job(&reversed).is_err());
+    }
+}
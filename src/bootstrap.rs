@@ -0,0 +1,157 @@
+//! Bootstrap Command
+//!
+//! One-shot setup for a brand new deployment: `rust-cctv bootstrap [--backfill-minutes N]`.
+
+use crate::config::{Config, TunablesHandle};
+use crate::error::AppError;
+use crate::migrations;
+use crate::scheduler::{SchedulerContext, run_fetch_window};
+use crate::services::{
+    CameraRegistry, CollectionSettings, EmbeddingModels, ShardRouter, VehicleTypes,
+    ensure_collection_exists, verify_embedding_dimension,
+};
+use qdrant_client::Qdrant;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+/// Options for the bootstrap command
+#[derive(Debug, Default)]
+pub struct BootstrapOptions {
+    /// Run a backfill of this many minutes once setup succeeds
+    pub backfill_minutes: Option<i64>,
+}
+
+impl BootstrapOptions {
+    /// Parse options from the arguments following `bootstrap`
+    pub fn from_args(args: &[String]) -> Result<Self, AppError> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--backfill-minutes" => {
+                    let value = args.next().ok_or_else(|| {
+                        AppError::Config("--backfill-minutes requires a value".to_string())
+                    })?;
+                    let minutes = value.parse::<i64>().map_err(|e| {
+                        AppError::Config(format!("Invalid --backfill-minutes '{}': {}", value, e))
+                    })?;
+                    options.backfill_minutes = Some(minutes);
+                }
+                other => {
+                    return Err(AppError::Config(format!(
+                        "Unknown bootstrap option: {}",
+                        other
+                    )));
+                }
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Run every bootstrap step, stopping at the first failure
+pub async fn run(
+    qdrant: Arc<Qdrant>,
+    http_client: reqwest::Client,
+    config: Config,
+    options: BootstrapOptions,
+) -> Result<(), AppError> {
+    info!("Bootstrapping deployment");
+
+    // 1. Validate that the AI services produce vectors of the expected size
+    let router = ShardRouter::new(&config.collection_name, config.collection_shards);
+    let embedding_models = Arc::new(EmbeddingModels::load(&config, router.collections())?);
+    for model in embedding_models.models() {
+        verify_embedding_dimension(&http_client, &model.ai_service_url, model.vector_size).await?;
+        info!(model = %model.name, dimension = model.vector_size, "AI service dimension OK");
+    }
+
+    // 2. Collection and payload indexes, sized for each collection's model
+    for collection in router.collections() {
+        let settings = CollectionSettings {
+            vector_size: embedding_models.for_collection(collection).vector_size,
+            ..CollectionSettings::new(&config)
+        };
+        ensure_collection_exists(&qdrant, collection, &settings).await?;
+        let applied = migrations::run_pending(&qdrant, collection).await?;
+        info!(
+            collection = %collection,
+            migrations_applied = applied.len(),
+            "Collection ready"
+        );
+    }
+
+    // 3. Register upstream cameras
+    let tunables = TunablesHandle::new(config.tunables());
+    let vehicle_types = Arc::new(VehicleTypes::load(&config.vehicle_types_path)?);
+    let ctx = SchedulerContext::new(
+        qdrant,
+        http_client,
+        config.clone(),
+        tunables,
+        Arc::default(),
+        Arc::default(),
+        Arc::default(),
+    )
+    .with_vehicle_types(vehicle_types)
+    .with_embedding_models(embedding_models);
+    let cctv_ids = ctx.cctv_service.list_cctv().await?;
+
+    let mut registry = CameraRegistry::load(&config.camera_registry_path, &config.cameras)?;
+    let added = registry.register(cctv_ids);
+    registry.save()?;
+    info!(
+        added,
+        total = registry.cameras().len(),
+        path = %config.camera_registry_path,
+        "Camera registry updated"
+    );
+
+    // 4. Optional backfill
+    if let Some(minutes) = options.backfill_minutes {
+        info!(minutes, "Backfilling");
+        // Ctrl-C stops the in-flight batches instead of waiting for the window
+        let cancel = CancellationToken::new();
+        let on_interrupt = cancel.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                on_interrupt.cancel();
+            }
+        });
+        let tally = run_fetch_window(&ctx, minutes, &cancel).await;
+        info!(
+            status = ?tally.status(),
+            fetched = tally.fetched,
+            inserted = tally.inserted,
+            failed = tally.failed,
+            "Backfill finished"
+        );
+    }
+
+    info!("Bootstrap completed");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bootstrap_options() {
+        let args = vec!["--backfill-minutes".to_string(), "60".to_string()];
+        let options = BootstrapOptions::from_args(&args).unwrap();
+        assert_eq!(options.backfill_minutes, Some(60));
+
+        assert!(
+            BootstrapOptions::from_args(&[])
+                .unwrap()
+                .backfill_minutes
+                .is_none()
+        );
+        assert!(BootstrapOptions::from_args(&["--backfill-minutes".to_string()]).is_err());
+        assert!(BootstrapOptions::from_args(&["--force".to_string()]).is_err());
+    }
+}
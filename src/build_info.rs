@@ -0,0 +1,21 @@
+//! Build Information
+//!
+//! Compile-time metadata about this binary.
+
+use chrono::DateTime;
+
+/// Crate version from Cargo.toml
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Git commit the binary was built from (`unknown` outside a checkout)
+pub const GIT_SHA: &str = env!("BUILD_GIT_SHA");
+
+/// Build time in RFC 3339 format
+pub fn build_time() -> String {
+    env!("BUILD_TIMESTAMP")
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_default()
+}
@@ -0,0 +1,783 @@
+//! HTTP Client
+//!
+//! Typed functions for every endpoint of the service, for Rust services
+//! that call it instead of hand-writing reqwest requests.
+
+mod types;
+
+pub use types::*;
+
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::fmt;
+
+/// Paths covered by `CctvSearchClient`, as listed in the OpenAPI document
+pub const ENDPOINTS: &[&str] = &[
+    "/search",
+    "/search_by_image",
+    "/recommend",
+    "/search/correlated",
+    "/sessions",
+    "/sessions/{session_id}",
+    "/sessions/{session_id}/feedback",
+    "/cases",
+    "/cases/{case_id}",
+    "/cases/{case_id}/items",
+    "/alerts",
+    "/alerts/{alert_id}",
+    "/insert_image",
+    "/insert_images",
+    "/ingest/batch",
+    "/images",
+    "/vehicle_types",
+    "/ws",
+    "/events",
+    "/admin/reload",
+    "/admin/maintenance",
+    "/admin/retag",
+    "/admin/hybrid/calibrate",
+    "/admin/eval/sets",
+    "/admin/eval/sets/{set_id}",
+    "/admin/eval/sets/{set_id}/run",
+    "/admin/flush",
+    "/admin/collection",
+    "/admin/shards",
+    "/admin/shards/rebalance",
+    "/admin/integrity",
+    "/admin/integrity/verify",
+    "/admin/dead_letters",
+    "/admin/snapshots",
+    "/admin/snapshots/restore",
+    "/admin/snapshots/verification",
+    "/admin/backfill",
+    "/admin/backfill/pause",
+    "/admin/backfill/resume",
+    "/healthz",
+    "/readyz",
+    "/status",
+    "/scheduler/runs",
+    "/scheduler/runs/{run_id}",
+    "/scheduler/trigger",
+    "/embed/text",
+    "/embed/image",
+    "/version",
+    "/metrics",
+];
+
+/// Failure of a client call
+#[derive(Debug)]
+pub enum ClientError {
+    /// The request could not be sent or the response could not be decoded
+    Http(reqwest::Error),
+    /// The service answered with an error status
+    Status {
+        status: StatusCode,
+        /// `code` of the service's `ErrorResponse`, e.g. `validation_failed`
+        code: Option<String>,
+        message: String,
+        /// `details` of the `ErrorResponse`, e.g. the invalid fields
+        details: Option<serde_json::Value>,
+        /// Set on `503` responses from an open circuit breaker
+        retry_after_secs: Option<u64>,
+    },
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Http(e) => write!(f, "HTTP error: {}", e),
+            ClientError::Status {
+                status, message, ..
+            } => write!(f, "{}: {}", status, message),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(e: reqwest::Error) -> Self {
+        ClientError::Http(e)
+    }
+}
+
+/// Client for one deployment of the service
+#[derive(Debug, Clone)]
+pub struct CctvSearchClient {
+    base_url: String,
+    /// Base URL of the admin, maintenance and metrics endpoints
+    admin_base_url: String,
+    /// Sent as `X-API-Key` on every request
+    api_key: Option<String>,
+    /// Sent as `Authorization: Bearer` on every request
+    bearer_token: Option<String>,
+    /// Sent as `X-Tenant-ID` on every request
+    tenant: Option<String>,
+    http: reqwest::Client,
+}
+
+impl CctvSearchClient {
+    /// Client for the service at `base_url`, e.g. `http://localhost:8080`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_client(base_url, reqwest::Client::new())
+    }
+
+    /// Client reusing an existing reqwest client (timeouts, proxies, ...)
+    pub fn with_client(base_url: impl Into<String>, http: reqwest::Client) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        Self {
+            admin_base_url: base_url.clone(),
+            base_url,
+            api_key: None,
+            bearer_token: None,
+            tenant: None,
+            http,
+        }
+    }
+
+    /// Send admin, maintenance and metrics calls to `admin_base_url`, for
+    /// deployments with `ADMIN_PORT` set, e.g. `http://localhost:9090`
+    pub fn with_admin_url(mut self, admin_base_url: impl Into<String>) -> Self {
+        self.admin_base_url = admin_base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Authenticate with `api_key`, for deployments with `API_KEYS` set
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Authenticate with a JWT, for deployments with `JWT_SECRET` or
+    /// `JWT_JWKS_URL` set
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.bearer_token = Some(token.into());
+        self
+    }
+
+    /// Act for `tenant`, for deployments with `TENANTS` set whose tokens have
+    /// no `tenant` claim
+    pub fn with_tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(tenant.into());
+        self
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.authenticated(
+            self.http
+                .request(method, format!("{}{}", self.base_url, path)),
+        )
+    }
+
+    fn admin_request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.authenticated(
+            self.http
+                .request(method, format!("{}{}", self.admin_base_url, path)),
+        )
+    }
+
+    fn authenticated(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(key) = &self.api_key {
+            request = request.header("X-API-Key", key);
+        }
+        if let Some(token) = &self.bearer_token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(tenant) = &self.tenant {
+            request = request.header("X-Tenant-ID", tenant);
+        }
+        request
+    }
+
+    // -------------------------------------------------------------------------
+    // Search
+    // -------------------------------------------------------------------------
+
+    /// `POST /search`
+    pub async fn search(&self, request: &SearchRequest) -> Result<Vec<SearchResult>, ClientError> {
+        json(self.request(Method::POST, "/search").json(request)).await
+    }
+
+    /// `POST /search` grouped by camera, with up to `group_size` hits per camera
+    pub async fn search_grouped(
+        &self,
+        request: &SearchRequest,
+        group_size: u32,
+    ) -> Result<Vec<CameraGroup>, ClientError> {
+        let mut body = serde_json::to_value(request).unwrap_or_default();
+        body["group_by_camera"] = true.into();
+        body["group_size"] = group_size.into();
+        json(self.request(Method::POST, "/search").json(&body)).await
+    }
+
+    /// `POST /search` in debug mode, comparing candidate search settings
+    pub async fn search_debug(
+        &self,
+        request: &SearchRequest,
+    ) -> Result<SearchDebugResponse, ClientError> {
+        let mut body = serde_json::to_value(request).unwrap_or_default();
+        body["debug"] = true.into();
+        json(self.request(Method::POST, "/search").json(&body)).await
+    }
+
+    /// `POST /search_by_image`
+    pub async fn search_by_image(
+        &self,
+        request: &SearchByImageRequest,
+    ) -> Result<Vec<SearchResult>, ClientError> {
+        json(self.request(Method::POST, "/search_by_image").json(request)).await
+    }
+
+    /// `POST /recommend`
+    pub async fn recommend(
+        &self,
+        request: &RecommendRequest,
+    ) -> Result<Vec<SearchResult>, ClientError> {
+        json(self.request(Method::POST, "/recommend").json(request)).await
+    }
+
+    /// `POST /search/correlated`, best pairs first
+    pub async fn search_correlated(
+        &self,
+        request: &CorrelatedSearchRequest,
+    ) -> Result<Vec<CorrelatedMatch>, ClientError> {
+        json(
+            self.request(Method::POST, "/search/correlated")
+                .json(request),
+        )
+        .await
+    }
+
+    /// `POST /sessions`
+    pub async fn create_session(&self) -> Result<SessionState, ClientError> {
+        json(self.request(Method::POST, "/sessions")).await
+    }
+
+    /// `GET /sessions/{session_id}`
+    pub async fn get_session(&self, session_id: &str) -> Result<SessionState, ClientError> {
+        json(self.request(Method::GET, &format!("/sessions/{}", session_id))).await
+    }
+
+    /// `POST /sessions/{session_id}/feedback`
+    pub async fn add_session_feedback(
+        &self,
+        session_id: &str,
+        feedback: &SessionFeedback,
+    ) -> Result<SessionState, ClientError> {
+        let path = format!("/sessions/{}/feedback", session_id);
+        json(self.request(Method::POST, &path).json(feedback)).await
+    }
+
+    /// `DELETE /sessions/{session_id}`
+    pub async fn delete_session(&self, session_id: &str) -> Result<(), ClientError> {
+        let path = format!("/sessions/{}", session_id);
+        checked(self.request(Method::DELETE, &path)).await?;
+        Ok(())
+    }
+
+    /// `POST /cases`
+    pub async fn create_case(&self, request: &CreateCaseRequest) -> Result<Case, ClientError> {
+        json(self.request(Method::POST, "/cases").json(request)).await
+    }
+
+    /// `GET /cases`, most recently updated first
+    pub async fn list_cases(&self) -> Result<Vec<CaseSummary>, ClientError> {
+        json(self.request(Method::GET, "/cases")).await
+    }
+
+    /// `GET /cases/{case_id}`
+    pub async fn get_case(&self, case_id: &str) -> Result<Case, ClientError> {
+        json(self.request(Method::GET, &format!("/cases/{}", case_id))).await
+    }
+
+    /// `POST /cases/{case_id}/items`
+    pub async fn attach_case_item(
+        &self,
+        case_id: &str,
+        request: &AttachCaseItemRequest,
+    ) -> Result<CaseItem, ClientError> {
+        let path = format!("/cases/{}/items", case_id);
+        json(self.request(Method::POST, &path).json(request)).await
+    }
+
+    /// `POST /alerts`
+    pub async fn create_alert(&self, request: &CreateAlertRequest) -> Result<Alert, ClientError> {
+        json(self.request(Method::POST, "/alerts").json(request)).await
+    }
+
+    /// `GET /alerts`, oldest first
+    pub async fn list_alerts(&self) -> Result<Vec<Alert>, ClientError> {
+        json(self.request(Method::GET, "/alerts")).await
+    }
+
+    /// `GET /alerts/{alert_id}`
+    pub async fn get_alert(&self, alert_id: &str) -> Result<Alert, ClientError> {
+        json(self.request(Method::GET, &format!("/alerts/{}", alert_id))).await
+    }
+
+    /// `DELETE /alerts/{alert_id}`
+    pub async fn delete_alert(&self, alert_id: &str) -> Result<(), ClientError> {
+        let path = format!("/alerts/{}", alert_id);
+        checked(self.request(Method::DELETE, &path)).await?;
+        Ok(())
+    }
+
+    /// `GET /images`, one page of stored images; pass the returned
+    /// `next_page_token` as `query.page_token` for the next page
+    pub async fn list_images(&self, query: &ListImagesQuery) -> Result<ImagePage, ClientError> {
+        json(self.request(Method::GET, "/images").query(query)).await
+    }
+
+    /// `GET /vehicle_types`, labels of the `vehicle_type` and `yolo_id` codes
+    pub async fn vehicle_types(&self) -> Result<VehicleTypeMapping, ClientError> {
+        json(self.request(Method::GET, "/vehicle_types")).await
+    }
+
+    /// `GET /events`, scheduler runs, stored points passing `query` and
+    /// alert matches as they happen; read them with `EventStream::next`
+    pub async fn events(&self, query: &EventsQuery) -> Result<EventStream, ClientError> {
+        let response = checked(self.request(Method::GET, "/events").query(query)).await?;
+        Ok(EventStream {
+            response,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// URL of the `GET /ws` WebSocket, for a WebSocket client; each message
+    /// is an `IngestNotification`
+    pub fn live_ingest_url(&self) -> String {
+        match self.base_url.split_once("://") {
+            Some(("https", host)) => format!("wss://{}/ws", host),
+            Some((_, host)) => format!("ws://{}/ws", host),
+            None => format!("ws://{}/ws", self.base_url),
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Insertion
+    // -------------------------------------------------------------------------
+
+    /// `POST /insert_image`; `verify` overrides the server's `VERIFY_UPSERTS`
+    pub async fn insert_image(
+        &self,
+        image: &CctvImageData,
+        verify: Option<bool>,
+    ) -> Result<InsertImageResponse, ClientError> {
+        let request = self.request(Method::POST, "/insert_image").json(image);
+        json(with_verify(request, verify)).await
+    }
+
+    /// `POST /insert_images`; `verify` overrides the server's `VERIFY_UPSERTS`
+    pub async fn insert_images(
+        &self,
+        images: &[CctvImageData],
+        verify: Option<bool>,
+    ) -> Result<BatchInsertResponse, ClientError> {
+        let request = self.request(Method::POST, "/insert_images").json(images);
+        json(with_verify(request, verify)).await
+    }
+
+    /// `POST /ingest/batch` as edge device `device_id`, authenticated with
+    /// its own key instead of the client's API key or token
+    pub async fn ingest_batch(
+        &self,
+        device_id: &str,
+        device_key: &str,
+        batch: &EdgeIngestBatch,
+    ) -> Result<EdgeIngestAck, ClientError> {
+        let request = self
+            .http
+            .post(format!("{}/ingest/batch", self.base_url))
+            .header("X-Device-Id", device_id)
+            .header("X-Device-Key", device_key)
+            .json(batch);
+        json(request).await
+    }
+
+    // -------------------------------------------------------------------------
+    // Admin
+    // -------------------------------------------------------------------------
+
+    /// `POST /admin/reload`
+    pub async fn reload_config(&self) -> Result<Tunables, ClientError> {
+        json(self.admin_request(Method::POST, "/admin/reload")).await
+    }
+
+    /// `POST /admin/maintenance`
+    pub async fn set_maintenance(
+        &self,
+        request: &MaintenanceRequest,
+    ) -> Result<MaintenanceStatus, ClientError> {
+        json(
+            self.admin_request(Method::POST, "/admin/maintenance")
+                .json(request),
+        )
+        .await
+    }
+
+    /// `DELETE /images`
+    pub async fn delete_images(
+        &self,
+        query: &DeleteImagesQuery,
+    ) -> Result<DeleteImagesResponse, ClientError> {
+        json(self.admin_request(Method::DELETE, "/images").query(query)).await
+    }
+
+    /// `POST /admin/retag`
+    pub async fn retag_images(&self, request: &RetagRequest) -> Result<RetagResponse, ClientError> {
+        json(
+            self.admin_request(Method::POST, "/admin/retag")
+                .json(request),
+        )
+        .await
+    }
+
+    /// `POST /admin/hybrid/calibrate`
+    pub async fn calibrate_hybrid(
+        &self,
+        request: &HybridCalibrationRequest,
+    ) -> Result<HybridCalibrationResponse, ClientError> {
+        json(
+            self.admin_request(Method::POST, "/admin/hybrid/calibrate")
+                .json(request),
+        )
+        .await
+    }
+
+    /// `POST /admin/eval/sets`
+    pub async fn create_eval_set(
+        &self,
+        request: &CreateEvalSetRequest,
+    ) -> Result<EvalSet, ClientError> {
+        json(
+            self.admin_request(Method::POST, "/admin/eval/sets")
+                .json(request),
+        )
+        .await
+    }
+
+    /// `GET /admin/eval/sets`
+    pub async fn list_eval_sets(&self) -> Result<Vec<EvalSetSummary>, ClientError> {
+        json(self.admin_request(Method::GET, "/admin/eval/sets")).await
+    }
+
+    /// `GET /admin/eval/sets/{set_id}`
+    pub async fn get_eval_set(&self, set_id: &str) -> Result<EvalSet, ClientError> {
+        let path = format!("/admin/eval/sets/{}", set_id);
+        json(self.admin_request(Method::GET, &path)).await
+    }
+
+    /// `DELETE /admin/eval/sets/{set_id}`
+    pub async fn delete_eval_set(&self, set_id: &str) -> Result<(), ClientError> {
+        let path = format!("/admin/eval/sets/{}", set_id);
+        checked(self.admin_request(Method::DELETE, &path)).await?;
+        Ok(())
+    }
+
+    /// `POST /admin/eval/sets/{set_id}/run`
+    pub async fn run_eval_set(
+        &self,
+        set_id: &str,
+        request: &EvalRunRequest,
+    ) -> Result<EvalRunReport, ClientError> {
+        let path = format!("/admin/eval/sets/{}/run", set_id);
+        json(self.admin_request(Method::POST, &path).json(request)).await
+    }
+
+    /// `POST /admin/flush`; returns once every earlier write is applied
+    pub async fn flush_writes(&self) -> Result<FlushResponse, ClientError> {
+        json(self.admin_request(Method::POST, "/admin/flush")).await
+    }
+
+    /// `GET /admin/collection`, one entry per (shard) collection
+    pub async fn collection_info(&self) -> Result<Vec<CollectionStats>, ClientError> {
+        json(self.admin_request(Method::GET, "/admin/collection")).await
+    }
+
+    /// `GET /admin/shards`
+    pub async fn shard_status(&self) -> Result<ShardStatus, ClientError> {
+        json(self.admin_request(Method::GET, "/admin/shards")).await
+    }
+
+    /// `POST /admin/shards/rebalance`
+    ///
+    /// A rebalance that is already running is not an error: the current
+    /// status is returned either way.
+    pub async fn rebalance_shards(&self) -> Result<ShardStatus, ClientError> {
+        let response = self
+            .admin_request(Method::POST, "/admin/shards/rebalance")
+            .send()
+            .await?;
+        if response.status() == StatusCode::CONFLICT {
+            return Ok(response.json().await?);
+        }
+        Ok(error_for_status(response).await?.json().await?)
+    }
+
+    /// `POST /admin/integrity/verify`; poll `integrity_status` for the report
+    ///
+    /// A verification already running fails with status `409`.
+    pub async fn verify_integrity(
+        &self,
+        request: &IntegrityVerifyRequest,
+    ) -> Result<IntegrityStatus, ClientError> {
+        json(
+            self.admin_request(Method::POST, "/admin/integrity/verify")
+                .json(request),
+        )
+        .await
+    }
+
+    /// `GET /admin/integrity`
+    pub async fn integrity_status(&self) -> Result<IntegrityStatus, ClientError> {
+        json(self.admin_request(Method::GET, "/admin/integrity")).await
+    }
+
+    /// `GET /admin/dead_letters`, the newest `limit` entries oldest first
+    pub async fn dead_letters(&self, limit: Option<usize>) -> Result<Vec<DeadLetter>, ClientError> {
+        let mut request = self.admin_request(Method::GET, "/admin/dead_letters");
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)]);
+        }
+        json(request).await
+    }
+
+    /// `POST /admin/snapshots`, one new snapshot per collection
+    pub async fn create_snapshots(&self) -> Result<Vec<SnapshotInfo>, ClientError> {
+        json(self.admin_request(Method::POST, "/admin/snapshots")).await
+    }
+
+    /// `GET /admin/snapshots`, newest first
+    pub async fn snapshot_list(&self) -> Result<Vec<SnapshotInfo>, ClientError> {
+        json(self.admin_request(Method::GET, "/admin/snapshots")).await
+    }
+
+    /// `POST /admin/snapshots/restore`; returns once the collection is replaced
+    pub async fn restore_snapshot(
+        &self,
+        request: &SnapshotRestoreRequest,
+    ) -> Result<SnapshotRestoreResponse, ClientError> {
+        json(
+            self.admin_request(Method::POST, "/admin/snapshots/restore")
+                .json(request),
+        )
+        .await
+    }
+
+    /// `GET /admin/snapshots/verification`
+    pub async fn backup_verification(&self) -> Result<BackupVerificationStatus, ClientError> {
+        json(self.admin_request(Method::GET, "/admin/snapshots/verification")).await
+    }
+
+    /// `POST /admin/backfill`; poll `backfill_status` for progress
+    ///
+    /// A backfill already running fails with status `409`.
+    pub async fn start_backfill(
+        &self,
+        request: &BackfillRequest,
+    ) -> Result<BackfillStatus, ClientError> {
+        json(
+            self.admin_request(Method::POST, "/admin/backfill")
+                .json(request),
+        )
+        .await
+    }
+
+    /// `GET /admin/backfill`; `404` until a backfill has been started
+    pub async fn backfill_status(&self) -> Result<BackfillStatus, ClientError> {
+        json(self.admin_request(Method::GET, "/admin/backfill")).await
+    }
+
+    /// `POST /admin/backfill/pause`; cancels the chunk in flight
+    pub async fn pause_backfill(&self) -> Result<BackfillStatus, ClientError> {
+        json(self.admin_request(Method::POST, "/admin/backfill/pause")).await
+    }
+
+    /// `POST /admin/backfill/resume`
+    pub async fn resume_backfill(&self) -> Result<BackfillStatus, ClientError> {
+        json(self.admin_request(Method::POST, "/admin/backfill/resume")).await
+    }
+
+    // -------------------------------------------------------------------------
+    // Tools
+    // -------------------------------------------------------------------------
+
+    /// `POST /embed/text`
+    pub async fn embed_text(&self, text: &str) -> Result<EmbeddingResponse, ClientError> {
+        json(
+            self.request(Method::POST, "/embed/text")
+                .json(&serde_json::json!({ "text": text })),
+        )
+        .await
+    }
+
+    /// `POST /embed/image`
+    pub async fn embed_image(
+        &self,
+        request: &EmbedImageRequest,
+    ) -> Result<EmbeddingResponse, ClientError> {
+        json(self.request(Method::POST, "/embed/image").json(request)).await
+    }
+
+    // -------------------------------------------------------------------------
+    // System
+    // -------------------------------------------------------------------------
+
+    /// `GET /healthz`
+    pub async fn healthz(&self) -> Result<HealthResponse, ClientError> {
+        json(self.request(Method::GET, "/healthz")).await
+    }
+
+    /// `GET /readyz`; a `503` still returns the dependency report
+    pub async fn readyz(&self) -> Result<HealthResponse, ClientError> {
+        let response = self.request(Method::GET, "/readyz").send().await?;
+        if response.status() == StatusCode::SERVICE_UNAVAILABLE {
+            return Ok(response.json().await?);
+        }
+        Ok(error_for_status(response).await?.json().await?)
+    }
+
+    /// `GET /status`
+    pub async fn status(&self) -> Result<ServiceStatus, ClientError> {
+        json(self.request(Method::GET, "/status")).await
+    }
+
+    /// `GET /scheduler/runs`, newest first; `limit` defaults to 20 on the server
+    pub async fn scheduler_runs(
+        &self,
+        limit: Option<usize>,
+    ) -> Result<SchedulerRunsResponse, ClientError> {
+        let mut request = self.request(Method::GET, "/scheduler/runs");
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)]);
+        }
+        json(request).await
+    }
+
+    /// `GET /scheduler/runs/{run_id}`
+    pub async fn scheduler_run(&self, run_id: &str) -> Result<SchedulerRun, ClientError> {
+        json(self.request(Method::GET, &format!("/scheduler/runs/{}", run_id))).await
+    }
+
+    /// `POST /scheduler/trigger`; poll `scheduler_run` with the returned job ID
+    ///
+    /// A manual run already in progress fails with status `409`.
+    pub async fn trigger_fetch(
+        &self,
+        request: &TriggerFetchRequest,
+    ) -> Result<TriggerFetchResponse, ClientError> {
+        json(
+            self.admin_request(Method::POST, "/scheduler/trigger")
+                .json(request),
+        )
+        .await
+    }
+
+    /// `GET /version`
+    pub async fn version(&self) -> Result<VersionInfo, ClientError> {
+        json(self.request(Method::GET, "/version")).await
+    }
+
+    /// `GET /metrics` in Prometheus text format
+    pub async fn metrics(&self) -> Result<String, ClientError> {
+        Ok(checked(self.admin_request(Method::GET, "/metrics"))
+            .await?
+            .text()
+            .await?)
+    }
+
+    /// `GET /openapi.json`
+    pub async fn openapi(&self) -> Result<serde_json::Value, ClientError> {
+        json(self.request(Method::GET, "/openapi.json")).await
+    }
+}
+
+/// Open `GET /events` stream
+#[derive(Debug)]
+pub struct EventStream {
+    response: Response,
+    /// Received bytes not yet parsed into an event
+    buffer: Vec<u8>,
+}
+
+impl EventStream {
+    /// Next event, or `None` once the service closed the stream
+    ///
+    /// Keep-alive comments and events this client doesn't know are skipped.
+    pub async fn next(&mut self) -> Result<Option<LiveEvent>, ClientError> {
+        loop {
+            while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+                let block: Vec<u8> = self.buffer.drain(..end + 2).collect();
+                if let Some(event) = parse_event(&String::from_utf8_lossy(&block)) {
+                    return Ok(Some(event));
+                }
+            }
+            match self.response.chunk().await? {
+                Some(chunk) => self.buffer.extend_from_slice(&chunk),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Event of one Server-Sent Events block
+fn parse_event(block: &str) -> Option<LiveEvent> {
+    let mut name = "message";
+    let mut data = String::new();
+    for line in block.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            name = value.trim();
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push_str(value.trim_start());
+        }
+    }
+    match name {
+        "run" => serde_json::from_str(&data).ok().map(LiveEvent::Run),
+        "match" => serde_json::from_str(&data).ok().map(LiveEvent::Match),
+        "alert" => serde_json::from_str(&data).ok().map(LiveEvent::Alert),
+        _ => None,
+    }
+}
+
+fn with_verify(request: RequestBuilder, verify: Option<bool>) -> RequestBuilder {
+    match verify {
+        Some(verify) => request.query(&[("verify", verify)]),
+        None => request,
+    }
+}
+
+/// Send `request` and decode a successful JSON response
+async fn json<T: DeserializeOwned>(request: RequestBuilder) -> Result<T, ClientError> {
+    Ok(checked(request).await?.json().await?)
+}
+
+/// Send `request`, turning error statuses into `ClientError::Status`
+async fn checked(request: RequestBuilder) -> Result<Response, ClientError> {
+    error_for_status(request.send().await?).await
+}
+
+async fn error_for_status(response: Response) -> Result<Response, ClientError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let retry_after_secs = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok());
+    // Errors are `ErrorResponse` JSON; a proxy in between may answer with text
+    let body = response.text().await.unwrap_or_default();
+    let (code, message, details) = match serde_json::from_str::<ErrorResponse>(&body) {
+        Ok(error) => (Some(error.code), error.message, error.details),
+        Err(_) => (None, body, None),
+    };
+    Err(ClientError::Status {
+        status,
+        code,
+        message,
+        details,
+        retry_after_secs,
+    })
+}
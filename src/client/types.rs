@@ -0,0 +1,1174 @@
+//! Client Types
+//!
+//! Wire-format request/response structures of the HTTP API, mirroring the
+//! server models in both directions.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+/// Body of every error response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub code: String,
+    pub message: String,
+    #[serde(default)]
+    pub details: Option<Value>,
+}
+
+// =============================================================================
+// Search
+// =============================================================================
+
+/// Body of `POST /search`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchRequest {
+    pub query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<f32>,
+    /// RFC 3339
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    /// RFC 3339
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    /// Instead of `start_date`/`end_date`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_hours: Option<u64>,
+    /// Instead of `start_date`/`end_date`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_days: Option<u64>,
+    /// ISO 8601 duration, e.g. `PT24H`, instead of `start_date`/`end_date`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_ids: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vehicle_classes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_confidence: Option<f32>,
+    /// Exact-match conditions on `extra.*` payload keys
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<Map<String, Value>>,
+    /// `minimal`, `standard` or `full` (default: `standard`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fanout_chunks: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_params: Option<SearchParamsRequest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recency_half_life_hours: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Best hit per camera within this many seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collapse_window_s: Option<u64>,
+    /// `query` is a weighted prompt expression
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub arithmetic: bool,
+    /// Combine vector similarity with a text match on `filename` and `caption`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hybrid: bool,
+    /// `rrf`, `dbsf`, `weighted` or `filter`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hybrid_fusion: Option<String>,
+    /// Weight of vector similarity in `weighted` fusion, 0 to 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hybrid_alpha: Option<f32>,
+    /// `image` (default) or `caption`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector_space: Option<String>,
+}
+
+/// Per-request Qdrant search parameters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchParamsRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hnsw_ef: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exact: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_only: Option<bool>,
+    /// `all`, `majority`, `quorum` or a node count
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consistency: Option<String>,
+}
+
+/// Body of `POST /search_by_image`; set exactly one of `image_path` and `image_base64`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchByImageRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_base64: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_ids: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vehicle_classes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_confidence: Option<f32>,
+    /// Exact-match conditions on `extra.*` payload keys
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<Map<String, Value>>,
+    /// `minimal`, `standard` or `full` (default: `standard`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recency_half_life_hours: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Best hit per camera within this many seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collapse_window_s: Option<u64>,
+}
+
+/// Body of `POST /recommend`; point IDs to find similar images for
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecommendRequest {
+    pub positive: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub negative: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_ids: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vehicle_classes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_confidence: Option<f32>,
+    /// Exact-match conditions on `extra.*` payload keys
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<Map<String, Value>>,
+    /// `minimal`, `standard` or `full` (default: `standard`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Body of `POST /search/correlated`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CorrelatedSearchRequest {
+    pub query: String,
+    /// At least two cameras
+    pub camera_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub within_minutes: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    /// `minimal`, `standard` or `full` (default: `standard`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Sightings on two cameras, `first` being the earlier one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorrelatedMatch {
+    pub score: f32,
+    pub gap_seconds: i64,
+    pub first: SearchResult,
+    pub second: SearchResult,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResult {
+    /// Omitted with `detail: minimal`
+    #[serde(default)]
+    pub filename: Option<String>,
+    pub id: String,
+    pub score: f32,
+    pub datetime: String,
+    pub camera_id: String,
+    /// Registry name of the camera, if set
+    #[serde(default)]
+    pub camera_name: Option<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub lat: Option<f64>,
+    #[serde(default)]
+    pub lon: Option<f64>,
+    pub file_path: String,
+    #[serde(default)]
+    pub frame: Option<i64>,
+    #[serde(default)]
+    pub vehicle_class: Option<String>,
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    #[serde(default)]
+    pub vehicle_type_label: Option<String>,
+    #[serde(default)]
+    pub yolo_label: Option<String>,
+    #[serde(default)]
+    pub caption: Option<String>,
+    /// With `detail: full`
+    #[serde(default)]
+    pub payload: Option<Map<String, Value>>,
+    /// With `detail: full`
+    #[serde(default)]
+    pub score_breakdown: Option<ScoreBreakdown>,
+}
+
+/// How the score of a result was computed, with `detail: full`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    pub base_score: f32,
+    pub recency_factor: f32,
+}
+
+/// One candidate setting measured by a debug search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchParamTrial {
+    pub hnsw_ef: Option<u64>,
+    pub limit: u64,
+    pub latency_ms: u64,
+    pub overlap: f32,
+}
+
+/// Best hits of one camera, from `search_grouped`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraGroup {
+    pub camera_id: String,
+    pub hits: Vec<SearchResult>,
+}
+
+/// Query of `GET /images`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListImagesQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vehicle_class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredImage {
+    pub id: String,
+    pub payload: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImagePage {
+    pub images: Vec<StoredImage>,
+    pub next_page_token: Option<String>,
+}
+
+/// Message of the `GET /ws` WebSocket, one per stored point
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IngestNotification {
+    pub point_id: u64,
+    pub camera_id: String,
+    pub filename: String,
+    pub datetime: String,
+    pub vehicle_class: Option<String>,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+/// Query of `GET /events`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventsQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vehicle_class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert_id: Option<String>,
+}
+
+/// Event of the `GET /events` stream
+#[derive(Debug, Clone)]
+pub enum LiveEvent {
+    /// A scheduler run started, progressed or finished
+    Run(SchedulerRun),
+    /// A stored point passed the stream's filters
+    Match(IngestNotification),
+    /// Newly stored points matched a saved search
+    Alert(AlertNotification),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VehicleTypeLabel {
+    pub code: u32,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VehicleTypeMapping {
+    #[serde(default)]
+    pub vehicle_types: Vec<VehicleTypeLabel>,
+    #[serde(default)]
+    pub yolo_ids: Vec<VehicleTypeLabel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchDebugResponse {
+    pub results: Vec<SearchResult>,
+    pub reference_latency_ms: u64,
+    pub trials: Vec<SearchParamTrial>,
+}
+
+// =============================================================================
+// Sessions
+// =============================================================================
+
+/// Body of `POST /sessions/{session_id}/feedback`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionFeedback {
+    pub positive: Vec<String>,
+    pub negative: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionState {
+    pub session_id: String,
+    pub positive: Vec<String>,
+    pub negative: Vec<String>,
+}
+
+// =============================================================================
+// Cases
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseItemKind {
+    Search,
+    Bookmark,
+    Incident,
+    Export,
+}
+
+/// Body of `POST /cases`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateCaseRequest {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Body of `POST /cases/{case_id}/items`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachCaseItemRequest {
+    pub kind: CaseItemKind,
+    pub reference: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseItem {
+    pub item_id: u64,
+    pub kind: CaseItemKind,
+    pub reference: String,
+    pub note: Option<String>,
+    pub details: Option<Value>,
+    /// RFC 3339
+    pub added_at: String,
+    pub added_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Case {
+    pub case_id: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// RFC 3339
+    pub created_at: String,
+    pub created_by: Option<String>,
+    /// RFC 3339
+    pub updated_at: String,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    pub items: Vec<CaseItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseSummary {
+    pub case_id: String,
+    pub title: String,
+    pub created_at: String,
+    pub created_by: Option<String>,
+    pub updated_at: String,
+    pub items: usize,
+}
+
+/// Body of `POST /alerts`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateAlertRequest {
+    pub name: String,
+    pub query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_ids: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vehicle_classes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_confidence: Option<f32>,
+    pub min_score: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub alert_id: String,
+    pub name: String,
+    pub query: String,
+    pub camera_ids: Option<Vec<String>>,
+    pub vehicle_classes: Option<Vec<String>>,
+    pub min_confidence: Option<f32>,
+    pub min_score: f32,
+    pub webhook_url: Option<String>,
+    /// Only returned by `create_alert`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_secret: Option<String>,
+    /// RFC 3339
+    pub created_at: String,
+    pub created_by: Option<String>,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertHit {
+    pub point_id: u64,
+    pub camera_id: String,
+    pub filename: String,
+    pub datetime: String,
+    pub vehicle_class: Option<String>,
+    pub score: f32,
+}
+
+/// Points of one stored batch matching a saved search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertNotification {
+    pub alert_id: String,
+    pub name: String,
+    pub query: String,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    pub hits: Vec<AlertHit>,
+}
+
+// =============================================================================
+// Insertion
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiLabel {
+    pub class_name: String,
+    pub confidence: f32,
+}
+
+/// CCTV image metadata as accepted by the insert endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CctvImageData {
+    pub id: u32,
+    pub cctv_id: String,
+    pub date: String,
+    pub time: String,
+    pub frame: u32,
+    pub vehicle_type: u32,
+    pub yolo_id: u32,
+    pub filename: String,
+    pub file_path: String,
+    pub ai_label: Option<AiLabel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    #[serde(rename = "createdAt", default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    /// Precomputed image embedding; the service embeds images sent without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+    /// Fields without a dedicated payload key, stored under `extra`
+    #[serde(flatten, default)]
+    pub extra: Map<String, Value>,
+}
+
+/// Response of `POST /insert_image`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InsertImageResponse {
+    pub status: String,
+    pub point_id: u64,
+    #[serde(rename = "type")]
+    pub response_type: String,
+    pub embedding: Vec<f32>,
+    #[serde(default)]
+    pub verification_mismatches: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchInsertFailure {
+    pub id: u64,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchInsertResponse {
+    pub inserted: Vec<u64>,
+    pub failed: Vec<BatchInsertFailure>,
+    #[serde(default)]
+    pub verification_mismatches: Option<Vec<String>>,
+}
+
+/// Batch of embedded images sent to `POST /ingest/batch`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeIngestBatch {
+    /// Start time of the sending agent (Unix seconds)
+    pub epoch: u64,
+    /// Position of the batch in its epoch, from 1
+    pub sequence: u64,
+    pub points: Vec<CctvImageData>,
+}
+
+/// Inclusive range of batch sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequenceRange {
+    pub from: u64,
+    pub to: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IngestAckStatus {
+    Accepted,
+    Duplicate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeIngestAck {
+    pub device_id: String,
+    pub epoch: u64,
+    pub sequence: u64,
+    pub status: IngestAckStatus,
+    pub inserted: Vec<u64>,
+    pub failed: Vec<BatchInsertFailure>,
+    pub missing: Vec<SequenceRange>,
+}
+
+// =============================================================================
+// Admin
+// =============================================================================
+
+/// Runtime-tunable configuration returned by `POST /admin/reload`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tunables {
+    pub fetch_limit: u32,
+    pub fetch_days_range: i64,
+    pub fetch_every_time: i64,
+    #[serde(default)]
+    pub fetch_window_align: String,
+    pub search_hnsw_ef: Option<u64>,
+    pub search_exact: bool,
+    pub search_indexed_only: Option<bool>,
+    pub search_read_consistency: Option<String>,
+    pub search_fanout_chunks: u32,
+    #[serde(default)]
+    pub search_hybrid_fusion: String,
+    #[serde(default)]
+    pub search_hybrid_alpha: f32,
+    #[serde(default)]
+    pub shadow_collection: Option<String>,
+    #[serde(default)]
+    pub shadow_sample_rate: f64,
+    #[serde(default)]
+    pub shadow_ai_service_url: Option<String>,
+    pub search_recency_half_life_hours: Option<f64>,
+    #[serde(default)]
+    pub search_ai_fallback: bool,
+    pub verify_upserts: bool,
+    pub insert_upsert_wait: bool,
+    pub insert_write_ordering: Option<String>,
+    pub bulk_upsert_wait: bool,
+    pub bulk_write_ordering: Option<String>,
+    pub upsert_batch_size: usize,
+    #[serde(default)]
+    pub hash_images: bool,
+    #[serde(default)]
+    pub ingest_busy_hours: Option<String>,
+    #[serde(default)]
+    pub ingest_busy_points_per_sec: Option<u32>,
+    #[serde(default)]
+    pub ingest_off_peak_points_per_sec: Option<u32>,
+    pub log_level: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MaintenanceRequest {
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+    pub message: Option<String>,
+    pub allowlist: Vec<String>,
+}
+
+/// Query of `DELETE /images`; at least one filter is required
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeleteImagesQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteImagesResponse {
+    pub deleted: u64,
+    pub dry_run: bool,
+}
+
+/// Body of `POST /admin/retag`; at least one filter is required
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetagRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    pub set: Map<String, Value>,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetagResponse {
+    pub matched: u64,
+    pub dry_run: bool,
+    pub rebalance_required: bool,
+}
+
+/// Evaluation query of a hybrid calibration or evaluation set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LabeledQuery {
+    pub query: String,
+    pub relevant_ids: Vec<u64>,
+}
+
+/// Body of `POST /admin/hybrid/calibrate`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HybridCalibrationRequest {
+    pub queries: Vec<LabeledQuery>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alphas: Option<Vec<f32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlphaTrial {
+    pub alpha: f32,
+    pub recall: f64,
+    pub mrr: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HybridCalibrationResponse {
+    pub best_alpha: f32,
+    pub current_alpha: f32,
+    pub queries: usize,
+    pub top_k: u64,
+    pub trials: Vec<AlphaTrial>,
+}
+
+/// Body of `POST /admin/eval/sets`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateEvalSetRequest {
+    pub name: String,
+    pub queries: Vec<LabeledQuery>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalSet {
+    pub set_id: String,
+    pub name: String,
+    pub queries: Vec<LabeledQuery>,
+    pub created_at: String,
+    pub created_by: Option<String>,
+    #[serde(default)]
+    pub runs: Vec<EvalRunSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalSetSummary {
+    pub set_id: String,
+    pub name: String,
+    pub queries: usize,
+    pub created_at: String,
+    pub created_by: Option<String>,
+    pub last_run: Option<EvalRunSummary>,
+}
+
+/// Body of `POST /admin/eval/sets/{set_id}/run`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EvalRunRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u64>,
+    /// `/search` request fields applied to every query
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<Map<String, Value>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub mean_ms: f64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalRunSummary {
+    pub started_at: String,
+    pub top_k: u64,
+    pub embedding_models: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search: Option<Map<String, Value>>,
+    pub recall: f64,
+    pub mrr: f64,
+    pub latency: LatencySummary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalQueryResult {
+    pub query: String,
+    pub recall: f64,
+    pub reciprocal_rank: f64,
+    pub latency_ms: u64,
+    pub missed_ids: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalRunReport {
+    pub set_id: String,
+    #[serde(flatten)]
+    pub summary: EvalRunSummary,
+    pub results: Vec<EvalQueryResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlushResponse {
+    pub collections: Vec<String>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayloadIndexInfo {
+    pub field: String,
+    pub data_type: String,
+    pub points: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorConfigInfo {
+    pub size: u64,
+    pub distance: String,
+    pub on_disk: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionStats {
+    pub collection: String,
+    pub status: String,
+    pub optimizer_ok: bool,
+    pub optimizer_error: Option<String>,
+    pub points_count: Option<u64>,
+    pub indexed_vectors_count: Option<u64>,
+    pub segments_count: u64,
+    pub vectors: Option<VectorConfigInfo>,
+    pub shard_number: u32,
+    pub replication_factor: u32,
+    pub payload_indexes: Vec<PayloadIndexInfo>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub collection: String,
+    pub name: String,
+    pub created_at: Option<String>,
+    pub size_bytes: u64,
+    pub checksum: Option<String>,
+}
+
+/// Body of `POST /admin/snapshots/restore`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotRestoreRequest {
+    pub snapshot: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotRestoreResponse {
+    pub collection: String,
+    pub snapshot: String,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotCheck {
+    pub collection: String,
+    pub snapshot: Option<String>,
+    pub restored_points: u64,
+    pub live_points: u64,
+    pub probes: u32,
+    pub probes_found: u32,
+    pub passed: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupVerificationStatus {
+    pub running: bool,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub passed: Option<bool>,
+    pub checks: Vec<SnapshotCheck>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RebalanceStatus {
+    pub running: bool,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub scanned: u64,
+    pub moved: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardStatus {
+    pub collections: Vec<String>,
+    pub rebalance: RebalanceStatus,
+}
+
+/// Body of `POST /admin/integrity/verify`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntegrityVerifyRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_size: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityMismatch {
+    pub id: String,
+    pub collection: String,
+    pub camera_id: String,
+    pub file_path: String,
+    pub stored_sha256: String,
+    pub actual_sha256: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntegrityStatus {
+    pub running: bool,
+    pub sample_size: u32,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub checked: u64,
+    pub matched: u64,
+    pub mismatched: u64,
+    pub unreadable: u64,
+    pub mismatches: Vec<IntegrityMismatch>,
+    pub error: Option<String>,
+}
+
+/// Image rejected at ingest for a defective embedding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub id: u64,
+    pub cctv_id: String,
+    pub filename: String,
+    pub file_path: String,
+    pub source: String,
+    pub reason: String,
+    pub at: String,
+}
+
+/// Body of `POST /admin/backfill`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackfillRequest {
+    /// RFC 3339
+    pub start_date: String,
+    /// RFC 3339
+    pub end_date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_minutes: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cctv_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackfillState {
+    Running,
+    Paused,
+    Failed,
+    Completed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillStatus {
+    pub id: String,
+    pub state: BackfillState,
+    pub start_date: String,
+    pub end_date: String,
+    pub chunk_minutes: i64,
+    pub cctv_id: Option<String>,
+    pub limit: u32,
+    /// Start of the next chunk to fetch (RFC 3339)
+    pub cursor: String,
+    pub chunks_done: u64,
+    pub chunks_total: u64,
+    pub images_fetched: u64,
+    #[serde(default)]
+    pub skipped: u64,
+    pub inserted: u64,
+    pub failed: u64,
+    pub started_at: String,
+    pub updated_at: String,
+    pub last_error: Option<String>,
+}
+
+// =============================================================================
+// Tools
+// =============================================================================
+
+/// Image to embed; set exactly one of the two fields
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmbedImageRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_base64: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingResponse {
+    pub embedding: Vec<f32>,
+    pub dimensions: usize,
+    pub model: String,
+    pub cached: bool,
+}
+
+// =============================================================================
+// System
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyHealth {
+    pub name: String,
+    pub healthy: bool,
+    pub latency_ms: u64,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthResponse {
+    /// `ok` or `degraded`
+    pub status: String,
+    pub dependencies: Vec<DependencyHealth>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dependency {
+    AiService,
+    CctvApi,
+    Qdrant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitStatus {
+    pub dependency: Dependency,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub retry_after_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceStatus {
+    pub circuit_breakers_enabled: bool,
+    pub circuits: Vec<CircuitStatus>,
+    #[serde(default)]
+    pub ai_rate_limit: Option<AiRateLimitStatus>,
+    #[serde(default)]
+    pub ai_dispatch: Option<AiDispatchStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiDispatchStatus {
+    pub max_in_flight: usize,
+    pub in_flight: usize,
+    pub waiting_interactive: usize,
+    pub waiting_bulk: usize,
+    pub bulk_every: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiRateLimitStatus {
+    pub requests_per_sec: f64,
+    pub burst: u32,
+    pub interactive_reserve: u32,
+    pub available_tokens: f64,
+    pub throttled_interactive: u64,
+    pub throttled_bulk: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Running,
+    Completed,
+    Partial,
+    Failed,
+    Skipped,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunTrigger {
+    Scheduled,
+    Manual,
+}
+
+/// One fetch run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerRun {
+    pub id: String,
+    pub trigger: RunTrigger,
+    pub status: RunStatus,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    /// Bangkok local time
+    pub date_start: String,
+    /// Bangkok local time
+    pub date_stop: String,
+    pub cctv_id: Option<String>,
+    pub images_fetched: u64,
+    pub skipped: u64,
+    pub inserted: u64,
+    pub failed: u64,
+    #[serde(default)]
+    pub retried: u64,
+    pub error_count: usize,
+    pub errors: Vec<String>,
+    #[serde(default)]
+    pub failures: Vec<ImageFailure>,
+}
+
+/// Image a fetch run could not embed or store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageFailure {
+    pub id: u32,
+    pub cctv_id: String,
+    pub filename: String,
+    pub file_path: String,
+    pub error: String,
+    pub attempts: u32,
+    /// `false` if the image was rejected outright rather than retried
+    pub transient: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerRunsResponse {
+    pub scheduler_enabled: bool,
+    pub runs: Vec<SchedulerRun>,
+}
+
+/// Body of `POST /scheduler/trigger`; unset fields use the scheduler defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TriggerFetchRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cctv_id: Option<String>,
+    /// RFC 3339
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    /// RFC 3339
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    /// Overwrite images that are already stored
+    pub reingest: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerFetchResponse {
+    pub job_id: String,
+    pub status_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingModelInfo {
+    pub name: String,
+    pub vector_size: usize,
+    #[serde(default)]
+    pub pinned: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub version: String,
+    pub git_sha: String,
+    pub build_time: String,
+    pub features: Vec<String>,
+    pub embedding_model: EmbeddingModelInfo,
+}
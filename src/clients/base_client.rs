@@ -28,7 +28,7 @@ impl BaseApiClient {
             .json::<GetTokenResponse>()
             .await?;
 
-        Ok(resp.Data.access_token)
+        Ok(resp.data.access_token)
     }
 }
 
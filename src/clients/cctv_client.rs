@@ -47,14 +47,23 @@ impl CctvApi {
         }
     }
 
+    /// Request a new token without replacing the cached one
+    pub async fn check_token(&self) -> Result<(), Error> {
+        self.base_client
+            .get_token(&self.token_request)
+            .await
+            .map(|_| ())
+            .map_err(std::io::Error::other)
+    }
+
     async fn get_or_refresh_token(&self) -> Result<String, Error> {
         let mut token_guard = self.token.lock().await;
 
         // Check if we have a valid token that hasn't expired
-        if let Some((ref token, expiry)) = *token_guard {
-            if SystemTime::now() < expiry {
-                return Ok(token.clone());
-            }
+        if let Some((ref token, expiry)) = *token_guard
+            && SystemTime::now() < expiry
+        {
+            return Ok(token.clone());
         }
 
         // Token is expired or doesn't exist, fetch a new one
@@ -62,7 +71,7 @@ impl CctvApi {
             .base_client
             .get_token(&self.token_request)
             .await
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+            .map_err(std::io::Error::other)?;
 
         // Set expiry to 2 hours from now (with 5 min buffer)
         let expiry = SystemTime::now() + Duration::from_secs(2 * 60 * 60 - (5 * 60));
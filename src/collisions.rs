@@ -0,0 +1,172 @@
+//! Find-Collisions Command
+//!
+//! Reports points already overwritten by another camera's image with the same
+//! upstream ID: `rust-cctv find-collisions [--minutes N] [--limit N]`.
+
+use crate::config::{Config, TunablesHandle};
+use crate::error::AppError;
+use crate::models::search::CctvImageData;
+use crate::scheduler::{FetchWindow, SchedulerContext, fetch_window_images};
+use crate::services::{RunTally, extract_string, shared_ids};
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::GetPointsBuilder;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Options for the find-collisions command
+#[derive(Debug)]
+pub struct CollisionOptions {
+    /// Upstream window to scan, in minutes
+    pub minutes: i64,
+    /// Images fetched per camera
+    pub limit: u32,
+}
+
+impl Default for CollisionOptions {
+    fn default() -> Self {
+        Self {
+            minutes: 24 * 60,
+            limit: 1000,
+        }
+    }
+}
+
+impl CollisionOptions {
+    /// Parse options from the arguments following `find-collisions`
+    pub fn from_args(args: &[String]) -> Result<Self, AppError> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| AppError::Config(format!("{} requires a value", arg)));
+            match arg.as_str() {
+                "--minutes" => options.minutes = parse_value(arg, value?)?,
+                "--limit" => options.limit = parse_value(arg, value?)?,
+                other => {
+                    return Err(AppError::Config(format!(
+                        "Unknown find-collisions option: {}",
+                        other
+                    )));
+                }
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, AppError>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| AppError::Config(format!("Invalid {} '{}': {}", arg, value, e)))
+}
+
+/// Compare upstream images sharing an ID with what Qdrant stores for that ID
+///
+/// An image is reported when another camera's image now occupies its point.
+pub async fn run(
+    qdrant: Arc<Qdrant>,
+    http_client: reqwest::Client,
+    config: Config,
+    options: CollisionOptions,
+) -> Result<(), AppError> {
+    info!(
+        minutes = options.minutes,
+        limit = options.limit,
+        "Scanning for point ID collisions"
+    );
+
+    let tunables = TunablesHandle::new(config.tunables());
+    let ctx = SchedulerContext::new(
+        qdrant,
+        http_client,
+        config,
+        tunables,
+        Arc::default(),
+        Arc::default(),
+        Arc::default(),
+    );
+
+    let window = FetchWindow::last_minutes(options.minutes, options.limit);
+    let images = fetch_window_images(&ctx, &window, &mut RunTally::default()).await;
+    let shared = shared_ids(&images);
+
+    let mut overwritten = 0;
+    for (id, images) in &shared {
+        // Only cameras routed to the same collection can overwrite each other
+        let mut by_collection: Vec<(&str, Vec<&CctvImageData>)> = Vec::new();
+        for image in images {
+            let collection = ctx.router.collection_for(&image.cctv_id);
+            match by_collection.iter_mut().find(|(c, _)| *c == collection) {
+                Some((_, images)) => images.push(image),
+                None => by_collection.push((collection, vec![image])),
+            }
+        }
+
+        for (collection, images) in by_collection {
+            if images.iter().all(|i| i.cctv_id == images[0].cctv_id) {
+                continue;
+            }
+
+            let response = ctx
+                .qdrant
+                .get_points(
+                    GetPointsBuilder::new(collection, vec![(*id).into()]).with_payload(true),
+                )
+                .await
+                .map_err(|e| {
+                    AppError::Qdrant(format!(
+                        "Failed to read point {} from '{}': {}",
+                        id, collection, e
+                    ))
+                })?;
+            // Not stored yet, so nothing was overwritten
+            let Some(stored) = response.result.first() else {
+                continue;
+            };
+            let stored_camera_id = extract_string(&stored.payload, "camera_id");
+
+            for image in images.iter().filter(|i| i.cctv_id != stored_camera_id) {
+                overwritten += 1;
+                warn!(
+                    id,
+                    collection,
+                    camera_id = %image.cctv_id,
+                    filename = %image.filename,
+                    stored_camera_id = %stored_camera_id,
+                    "Image overwritten by another camera"
+                );
+            }
+        }
+    }
+
+    info!(
+        images = images.len(),
+        shared_ids = shared.len(),
+        overwritten,
+        "Collision scan completed"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_collision_options() {
+        let args = vec!["--minutes".to_string(), "60".to_string()];
+        let options = CollisionOptions::from_args(&args).unwrap();
+        assert_eq!(options.minutes, 60);
+        assert_eq!(options.limit, 1000);
+
+        assert!(CollisionOptions::from_args(&["--limit".to_string()]).is_err());
+        assert!(CollisionOptions::from_args(&["--limit".to_string(), "-1".to_string()]).is_err());
+        assert!(CollisionOptions::from_args(&["--force".to_string(), "1".to_string()]).is_err());
+    }
+}
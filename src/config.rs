@@ -2,96 +2,1421 @@
 //!
 //! Centralized configuration loading with sensible defaults.
 
+use crate::config_file::ConfigFile;
+use crate::error::AppError;
+use crate::logging::{self, LogFormat};
+use crate::models::cctv::CameraEntry;
+use crate::services::{PayloadLimits, SloTarget, UrlRewriteRule, VectorLayout};
+use chrono_tz::Tz;
+use qdrant_client::qdrant::Distance;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::env;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::{info, warn};
+use utoipa::ToSchema;
 
 /// Default application constants
 pub mod defaults {
+    /// Read when it exists and `CONFIG_FILE` is unset
+    pub const CONFIG_FILE: &str = "cctv-backend.toml";
     pub const QDRANT_URL: &str = "http://localhost:6334";
+    pub const QDRANT_TIMEOUT_SECS: u64 = 5;
+    pub const QDRANT_CONNECT_TIMEOUT_SECS: u64 = 5;
+    pub const QDRANT_KEEP_ALIVE: bool = true;
+    pub const QDRANT_SNAPSHOTS_PATH: &str = "/qdrant/snapshots";
     pub const AI_SERVICE_URL: &str = "http://localhost:5090";
+    pub const EMBEDDING_MODEL: &str = "unspecified";
     pub const COLLECTION_NAME: &str = "nt-cctv-vehicles";
+    pub const COLLECTION_SHARDS: u32 = 1;
+    pub const VECTOR_SIZE: usize = 1152;
+    pub const DISTANCE_METRIC: &str = "cosine";
+    pub const VECTOR_LAYOUT: &str = "single";
     pub const CCTV_API_URL: &str = "https://ntvideo.totbb.net";
     pub const CCTV_AUTHORIZE_CODE: &str = "your_authorize_code_here";
     pub const CCTV_USER_AUTH: &str = "your_user_auth_here";
     pub const CCTV_CLIENT_ID: &str = "rust-cctv-client";
+    pub const CCTV_SOURCE_TIMEZONE: &str = "Asia/Bangkok";
+    pub const CAMERA_REGISTRY_PATH: &str = "cameras.json";
+    pub const VEHICLE_TYPES_PATH: &str = "vehicle_types.json";
+    pub const QUERY_IMAGE_DIR: &str = "query_images";
+    pub const BACKFILL_STATE_PATH: &str = "backfill_state.json";
+    pub const DEAD_LETTER_PATH: &str = "dead_letters.jsonl";
+    pub const CASES_PATH: &str = "cases.json";
+    pub const EVAL_SETS_PATH: &str = "eval_sets.json";
+    pub const ALERTS_PATH: &str = "alerts.json";
     pub const SERVER_PORT: u16 = 8080;
+    pub const MAINTENANCE_ALLOWLIST: &str = "/admin/reload";
     pub const FETCH_LIMIT: u32 = 20;
     pub const FETCH_DAYS_RANGE: i64 = 2;
     pub const FETCH_EVERY_TIME: i64 = 1;
+    pub const FETCH_WINDOW_ALIGN: &str = "none";
+    pub const SEARCH_EXACT: bool = false;
+    pub const SEARCH_AI_FALLBACK: bool = false;
+    pub const SEARCH_FANOUT_CHUNKS: u32 = 1;
+    pub const SEARCH_HYBRID_FUSION: &str = "rrf";
+    pub const SEARCH_HYBRID_ALPHA: f32 = 0.5;
+    pub const SHADOW_SAMPLE_RATE: f64 = 0.0;
+    pub const VERIFY_UPSERTS: bool = false;
+    pub const INSERT_UPSERT_WAIT: bool = true;
+    pub const BULK_UPSERT_WAIT: bool = false;
+    pub const UPSERT_BATCH_SIZE: usize = 100;
+    pub const HASH_IMAGES: bool = true;
+    /// About 4.6 KB per 1152-dimensional embedding
+    pub const EMBEDDING_CACHE_ENTRIES: usize = 1024;
+    pub const EMBEDDING_CACHE_TTL_SECS: u64 = 300;
+    pub const PAYLOAD_MAX_BYTES: usize = 65536;
+    pub const PAYLOAD_MAX_TEXT_BYTES: usize = 8192;
+    pub const PAYLOAD_OVERSIZE_POLICY: &str = "truncate";
+    pub const SLO_TARGETS: &str = "/search:800:0.95";
+    pub const SLO_WINDOW_MINUTES: u64 = 60;
+    pub const SLO_BURN_RATE_ALERT: f64 = 2.0;
+    pub const WEBHOOK_MAX_RETRIES: u32 = 3;
+    pub const WEBHOOK_RETRY_BACKOFF_MS: u64 = 1000;
+    pub const LOG_LEVEL: &str = "info";
+    pub const CIRCUIT_FAILURE_THRESHOLD: u32 = 5;
+    pub const CIRCUIT_OPEN_SECS: u64 = 30;
+    pub const SHUTDOWN_TIMEOUT_SECS: u64 = 30;
+    pub const AI_RATE_LIMIT_BURST: u32 = 10;
+    pub const AI_BULK_EVERY: u32 = 4;
+    pub const CLIENT_RATE_LIMIT_BURST: u32 = 20;
+    pub const CLIENT_RATE_LIMIT_PATHS: &str = "/search,/insert_image";
+    pub const CORS_ALLOWED_METHODS: &str = "GET,POST,PUT,DELETE";
+    pub const CORS_ALLOWED_HEADERS: &str = "Content-Type,Authorization,X-API-Key";
 }
 
 /// Technical constants (should not be changed without model retraining)
 pub mod technical {
-    /// Vector embedding size - must match AI model output
-    pub const VECTOR_SIZE: usize = 1152;
+    /// How often the `JWT_JWKS_URL` key set is re-fetched to pick up rotated keys
+    pub const JWKS_REFRESH_SECS: u64 = 3600;
+    /// Upper bound on parallel sub-searches for a single fan-out query
+    pub const MAX_FANOUT_CHUNKS: u32 = 32;
+    /// Largest image `/embed/image` reads from `EMBED_IMAGE_ROOT`
+    pub const MAX_EMBED_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+    /// Upper bound on images accepted by a single batch insert
+    pub const MAX_INSERT_BATCH: usize = 500;
+    /// Candidates fetched per requested result when re-ranking by recency
+    pub const RECENCY_CANDIDATE_FACTOR: u64 = 4;
+    /// Candidates fetched per requested result when collapsing bursts of frames
+    pub const COLLAPSE_CANDIDATE_FACTOR: u64 = 5;
+    /// Clients tracked by the per-client rate limit before idle ones are dropped
+    pub const MAX_RATE_LIMITED_CLIENTS: usize = 10_000;
+    /// How long browsers may cache a CORS preflight response
+    pub const CORS_MAX_AGE_SECS: u64 = 600;
+    /// Fraction of the AI rate limit burst held back for interactive calls
+    pub const AI_RATE_INTERACTIVE_RESERVE: f64 = 0.5;
+    /// `Retry-After` of manual fetch runs refused during shutdown
+    pub const SHUTDOWN_RETRY_AFTER_SECS: u64 = 30;
+    /// Worker threads of the admin server when `ADMIN_PORT` is set
+    pub const ADMIN_SERVER_WORKERS: usize = 2;
+    /// Per-dependency timeout for `/healthz` and `/readyz` checks
+    pub const HEALTH_CHECK_TIMEOUT_SECS: u64 = 3;
+    /// Timeout of one webhook delivery attempt
+    pub const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+    /// Scheduled fetch runs kept for `/scheduler/runs`
+    pub const SCHEDULER_RUN_HISTORY: usize = 50;
+    /// Images sent to the AI service per embedding call during a fetch run
+    pub const EMBEDDING_BATCH_SIZE: usize = 100;
+    /// Embedding batches of a fetch run processed at the same time
+    pub const EMBEDDING_CONCURRENCY: usize = 2;
+    /// Embedding attempts per image in a fetch run before it counts as failed
+    pub const EMBEDDING_ATTEMPTS: u32 = 3;
+    /// Wait before the first embedding retry, doubled for each further retry
+    pub const EMBEDDING_RETRY_DELAY_SECS: u64 = 2;
+    /// Uploads of an embedded batch by the edge ingest agent before it is dropped
+    #[cfg(feature = "edge-ingest")]
+    pub const EDGE_FORWARD_ATTEMPTS: u32 = 3;
+    /// Wait before the first upload retry, doubled for each further retry
+    #[cfg(feature = "edge-ingest")]
+    pub const EDGE_FORWARD_RETRY_DELAY_SECS: u64 = 5;
+    /// Attempts per backfill chunk before the job is marked failed
+    pub const BACKFILL_CHUNK_ATTEMPTS: u32 = 3;
+    /// Wait between backfill chunk attempts, and while in maintenance mode
+    pub const BACKFILL_RETRY_DELAY_SECS: u64 = 30;
+    /// Upper bound on prompts in one arithmetic search query
+    pub const MAX_QUERY_PROMPTS: usize = 8;
+    /// Shadow searches running at once; further sampled searches are not shadowed
+    pub const MAX_SHADOW_IN_FLIGHT: usize = 4;
+    /// Upper bound on `top_k` of a search request
+    pub const MAX_TOP_K: u64 = 1000;
+    /// Upper bound on hits per camera in grouped searches
+    pub const MAX_GROUP_SIZE: u32 = 10;
+    /// Upper bound on evaluation queries of a hybrid weight calibration
+    pub const MAX_CALIBRATION_QUERIES: usize = 200;
+    /// Default cutoff of recall and reciprocal rank in a hybrid calibration
+    pub const CALIBRATION_TOP_K: u64 = 10;
+    /// Upper bound on labeled queries of an evaluation set
+    pub const MAX_EVAL_QUERIES: usize = 500;
+    /// Runs kept in the history of an evaluation set
+    pub const MAX_EVAL_RUNS: usize = 20;
+    /// Default and maximum time between paired sightings of a correlated search
+    pub const CORRELATION_WINDOW_MINUTES: u32 = 30;
+    pub const MAX_CORRELATION_WINDOW_MINUTES: u32 = 1440;
+    /// Best hits of each camera considered for pairing in a correlated search
+    pub const CORRELATION_CANDIDATES_PER_CAMERA: u64 = 50;
+    /// Timeout for downloading an image to hash its content
+    pub const IMAGE_HASH_TIMEOUT_SECS: u64 = 10;
+    /// Images downloaded and hashed at the same time
+    pub const IMAGE_HASH_CONCURRENCY: usize = 8;
+    /// Default and maximum points sampled per collection by an integrity check
+    pub const INTEGRITY_SAMPLE_SIZE: u32 = 100;
+    pub const MAX_INTEGRITY_SAMPLE_SIZE: u32 = 1000;
+    /// Mismatches listed in the integrity check status (all are counted)
+    pub const MAX_REPORTED_MISMATCHES: usize = 100;
+    /// Embeddings with a smaller norm are rejected as all-zero
+    pub const ZERO_EMBEDDING_NORM: f32 = 1e-6;
+    /// Embeddings with a smaller norm are logged and counted as suspicious
+    pub const LOW_EMBEDDING_NORM: f32 = 1e-2;
+    /// Default and maximum dead letters returned by `/admin/dead_letters`
+    pub const DEAD_LETTERS_LIMIT: usize = 100;
+    pub const MAX_DEAD_LETTERS_LIMIT: usize = 1000;
+    /// Default and maximum images per page of `GET /images`
+    pub const EXPORT_PAGE_SIZE: u32 = 100;
+    pub const MAX_EXPORT_PAGE_SIZE: u32 = 1000;
+    /// Suffix of the scratch collection a snapshot is restored into for verification
+    pub const BACKUP_VERIFY_SUFFIX: &str = "_backup_verify";
+    /// Points searched for by their own vector in a restored snapshot
+    pub const BACKUP_VERIFY_PROBES: u32 = 5;
+    /// Hits a probe must appear in; duplicate images tie for the first place
+    pub const BACKUP_VERIFY_PROBE_TOP_K: u64 = 5;
 }
 
 /// Application configuration loaded from environment
 #[derive(Clone)]
 pub struct Config {
     pub qdrant_url: String,
-    pub qdrant_api_key: String,
+    /// Unset or empty disables API-key authentication
+    pub qdrant_api_key: Option<String>,
+    /// PEM file with the CA that signed the Qdrant TLS certificate (`https://` only)
+    pub qdrant_ca_cert: Option<String>,
+    pub qdrant_timeout_secs: u64,
+    pub qdrant_connect_timeout_secs: u64,
+    /// Send gRPC keep-alive pings on idle connections
+    pub qdrant_keep_alive: bool,
+    /// Qdrant REST URL, needed to restore snapshots (`None` = restore disabled)
+    pub qdrant_rest_url: Option<String>,
+    /// Snapshot directory as seen by the Qdrant server
+    pub qdrant_snapshots_path: String,
+    /// Hours between restore checks of the newest snapshots (`None` = disabled)
+    pub backup_verify_every_hours: Option<u64>,
     pub ai_service_url: String,
+    pub embedding_model: String,
+    /// JSON file pinning collections to other embedding models and AI
+    /// services (`None` = every collection uses `embedding_model`)
+    pub embedding_models_path: Option<String>,
+    /// AI service path generating captions at ingest (`None` = no captioning)
+    pub caption_endpoint: Option<String>,
+    /// Embeddings kept by the query and `/embed/*` cache (0 = no caching)
+    pub embedding_cache_entries: usize,
+    /// How long a cached embedding is served
+    pub embedding_cache_ttl_secs: u64,
     pub collection_name: String,
+    /// Number of collections images are spread over by camera hash (1 = unsharded)
+    pub collection_shards: u32,
+    /// Embedding dimension; must match the AI service output and the collection
+    pub vector_size: usize,
+    pub distance_metric: Distance,
+    /// One unnamed image vector, or named image and caption vectors
+    pub vector_layout: VectorLayout,
+    /// Qdrant shards of each new collection (`None` = Qdrant default)
+    pub qdrant_shard_number: Option<u32>,
+    /// Copies of each shard across the cluster (`None` = Qdrant default)
+    pub qdrant_replication_factor: Option<u32>,
+    /// Replicas that must acknowledge a write (`None` = Qdrant default)
+    pub qdrant_write_consistency_factor: Option<u32>,
+    /// Keep vectors on disk instead of in RAM (`None` = Qdrant default)
+    pub qdrant_on_disk_vectors: Option<bool>,
+    /// Keep payloads on disk instead of in RAM (`None` = Qdrant default)
+    pub qdrant_on_disk_payload: Option<bool>,
     pub cctv_api_url: String,
     pub cctv_authorize_code: String,
     pub cctv_user_auth: String,
     pub cctv_client_id: String,
+    /// Timezone of the local `date`/`time` the cameras report, unless the
+    /// camera registry sets one for a camera
+    pub cctv_source_timezone: Tz,
+    pub camera_registry_path: String,
+    /// Central API the edge ingest agent forwards embedded images to
+    #[cfg(feature = "edge-ingest")]
+    pub edge_forward_url: Option<String>,
+    /// Sent as `X-API-Key` to `edge_forward_url`
+    #[cfg(feature = "edge-ingest")]
+    pub edge_forward_api_key: Option<String>,
+    /// ID and key the agent sends batches to `/ingest/batch` with
+    #[cfg(feature = "edge-ingest")]
+    pub edge_device_id: Option<String>,
+    #[cfg(feature = "edge-ingest")]
+    pub edge_device_key: Option<String>,
+    /// JSON mapping of `vehicle_type` and `yolo_id` codes to labels
+    pub vehicle_types_path: String,
+    /// Where base64 search-by-image uploads are written for the AI service
+    pub query_image_dir: String,
+    /// Directory `/embed/image` reads `image_path`s from; paths are refused without it
+    pub embed_image_root: Option<String>,
+    /// Where backfill progress is saved so a restart can resume it
+    pub backfill_state_path: String,
+    /// Where images rejected for a defective embedding are appended
+    pub dead_letter_path: String,
+    /// Where investigator cases are saved
+    pub cases_path: String,
+    /// Where labeled evaluation sets and their runs are saved
+    pub eval_sets_path: String,
+    /// Where saved search alerts are saved
+    pub alerts_path: String,
     pub server_port: u16,
+    /// Port serving the admin, maintenance and metrics endpoints instead of
+    /// `server_port` (`None` = served on `server_port`)
+    pub admin_port: Option<u16>,
+    /// Port of the gRPC API (`None` = not served)
+    pub grpc_port: Option<u16>,
+    /// How long shutdown waits for open connections to drain, and then for
+    /// fetch runs in flight to finish
+    pub shutdown_timeout_secs: u64,
+    pub maintenance_allowlist: Vec<String>,
+    /// Keys accepted in the `X-API-Key` header (empty = no authentication)
+    pub api_keys: Vec<String>,
+    /// File with further keys, one per line
+    pub api_keys_file: Option<String>,
+    /// `<device_id>:<key>` entries of the edge devices allowed on
+    /// `/ingest/batch`
+    pub edge_device_keys: Vec<String>,
+    /// HS256 secret bearer tokens are signed with
+    pub jwt_secret: Option<String>,
+    /// JWKS endpoint publishing the keys bearer tokens are signed with
+    pub jwt_jwks_url: Option<String>,
+    /// Required `iss` claim (`None` = not checked)
+    pub jwt_issuer: Option<String>,
+    /// Required `aud` claim (`None` = not checked)
+    pub jwt_audience: Option<String>,
+    /// Tenant IDs requests are confined to (empty = no tenancy)
+    pub tenants: Vec<String>,
+    pub disabled_features: Vec<String>,
     pub fetch_limit: u32,
     pub fetch_days_range: i64,
     pub fetch_every_time: i64,
+    /// Boundaries of scheduled fetch windows: `none`, `minute`, `hour` or `day`
+    pub fetch_window_align: String,
+    pub search_hnsw_ef: Option<u64>,
+    pub search_exact: bool,
+    pub search_indexed_only: Option<bool>,
+    pub search_read_consistency: Option<String>,
+    pub search_fanout_chunks: u32,
+    /// Default fusion of hybrid searches: `rrf`, `dbsf`, `weighted` or `filter`
+    pub search_hybrid_fusion: String,
+    /// Default weight of vector similarity against keyword matches in
+    /// weighted hybrid fusion
+    pub search_hybrid_alpha: f32,
+    /// Collection that sampled searches are replayed against (`None` = no shadowing)
+    pub shadow_collection: Option<String>,
+    /// Fraction of eligible searches replayed against `shadow_collection`
+    pub shadow_sample_rate: f64,
+    /// AI service re-embedding shadowed queries (`None` = reuse the live vector)
+    pub shadow_ai_service_url: Option<String>,
+    /// Default half-life for the search recency boost (`None` = no boost)
+    pub search_recency_half_life_hours: Option<f64>,
+    /// Answer `/search` with the newest matching images while the AI service
+    /// circuit is open, instead of failing
+    pub search_ai_fallback: bool,
+    /// Read points back after every upsert and compare payloads
+    pub verify_upserts: bool,
+    /// Wait for `/insert_image(s)` upserts to be applied before responding
+    pub insert_upsert_wait: bool,
+    /// Write ordering of `/insert_image(s)` upserts (`None` = Qdrant default)
+    pub insert_write_ordering: Option<String>,
+    /// Wait for fetch run and backfill upserts to be applied
+    pub bulk_upsert_wait: bool,
+    /// Write ordering of fetch run and backfill upserts (`None` = Qdrant default)
+    pub bulk_write_ordering: Option<String>,
+    /// Points per upsert call in fetch runs and backfills
+    pub upsert_batch_size: usize,
+    /// Store the SHA-256 of readable images at ingest
+    pub hash_images: bool,
+    /// Size limits of ingested payloads and how oversized text is handled
+    pub payload_limits: PayloadLimits,
+    /// Local hours, e.g. `07-20`, during which bulk upserts are limited to
+    /// `ingest_busy_points_per_sec` (`None` = no busy hours)
+    pub ingest_busy_hours: Option<String>,
+    /// Points per second upserted by fetch runs and backfills during busy
+    /// hours (`None` = unlimited)
+    pub ingest_busy_points_per_sec: Option<u32>,
+    /// Points per second upserted outside busy hours (`None` = unlimited)
+    pub ingest_off_peak_points_per_sec: Option<u32>,
+    pub slo_targets: Vec<SloTarget>,
+    pub slo_window_minutes: u64,
+    /// Burn rate at which an alert is sent to `slo_alert_webhook`
+    pub slo_burn_rate_alert: f64,
+    pub slo_alert_webhook: Option<String>,
+    /// URL receiving run summaries and alert matches (`None` = no webhook)
+    pub webhook_url: Option<String>,
+    /// Key of the HMAC-SHA256 signature of webhook deliveries (`None` = unsigned)
+    pub webhook_secret: Option<String>,
+    /// Hosts alert webhooks may reach on private addresses
+    pub webhook_allowed_hosts: Vec<String>,
+    /// Attempts after the first for deliveries failing with 5xx, 429 or no response
+    pub webhook_max_retries: u32,
+    /// Delay before the first retry, doubled for each further one
+    pub webhook_retry_backoff_ms: u64,
+    /// Level or `EnvFilter` directives, e.g. `info,rust_cctv=debug`
+    pub log_level: String,
+    pub log_format: LogFormat,
+    /// Consecutive failures that open a dependency's circuit breaker
+    pub circuit_failure_threshold: u32,
+    /// How long an open breaker rejects calls before letting one through
+    pub circuit_open_secs: u64,
+    /// Sustained AI service calls per second (`None` = unlimited)
+    pub ai_rate_limit_rps: Option<f64>,
+    /// AI service calls allowed at once after an idle period
+    pub ai_rate_limit_burst: u32,
+    /// AI service calls in flight at once; more are queued by priority (`None` = unlimited)
+    pub ai_max_in_flight: Option<usize>,
+    /// Queued interactive calls admitted in a row before a waiting bulk call
+    pub ai_bulk_every: u32,
+    /// Sustained requests per second per client (`None` = unlimited)
+    pub client_rate_limit_rps: Option<f64>,
+    /// Requests a client may send at once after an idle period
+    pub client_rate_limit_burst: u32,
+    /// Paths the per-client rate limit applies to
+    pub client_rate_limit_paths: Vec<String>,
+    /// Browser origins allowed to call the public API, or `*` (empty = CORS disabled)
+    pub cors_allowed_origins: Vec<String>,
+    /// Methods allowed in cross-origin requests
+    pub cors_allowed_methods: Vec<String>,
+    /// Request headers allowed in cross-origin requests
+    pub cors_allowed_headers: Vec<String>,
+    /// Rewrites of stored image URLs in responses, first match wins
+    pub image_url_rewrites: Vec<UrlRewriteRule>,
+    /// Configuration file the settings were read from, if any
+    pub config_file: Option<String>,
+    /// Settings of the configuration file that no variable was read from
+    pub config_file_unused: Vec<String>,
+    /// Cameras of the configuration file, over those of the registry
+    pub cameras: Vec<CameraEntry>,
 }
 
 impl Config {
-    /// Load configuration from environment variables with defaults
-    pub fn from_env() -> Result<Self, String> {
+    /// Load configuration from the configuration file, environment variables
+    /// and defaults, in that order of precedence
+    pub fn from_env() -> Result<Self, AppError> {
+        Self::from_layers(&|key| env::var(key).ok())
+    }
+
+    /// Re-load configuration, letting values in `.env` override the process environment
+    pub fn reload_from_env_file() -> Result<Self, AppError> {
+        // The iterator API reads `.env` without mutating the process environment
+        #[allow(deprecated)]
+        let file_vars: HashMap<String, String> = match dotenv::from_path_iter(".env") {
+            Ok(iter) => iter
+                .collect::<Result<_, _>>()
+                .map_err(|e| AppError::Config(format!("Failed to read .env: {}", e)))?,
+            Err(_) => HashMap::new(),
+        };
+
+        Self::from_layers(&|key| file_vars.get(key).cloned().or_else(|| env::var(key).ok()))
+    }
+
+    /// Load configuration from the file named by `CONFIG_FILE` in `lookup`
+    /// (or `cctv-backend.toml`) over `lookup` over the defaults
+    fn from_layers(lookup: &dyn Fn(&str) -> Option<String>) -> Result<Self, AppError> {
+        let file = ConfigFile::load(lookup("CONFIG_FILE").as_deref())?;
+        let mut config = Self::from_lookup(&|key| file.get(key).or_else(|| lookup(key)))?;
+        config.config_file = file.path().map(str::to_string);
+        config.config_file_unused = file.unused();
+        config.cameras = file.cameras().to_vec();
+        Ok(config)
+    }
+
+    /// Load configuration from a key lookup function with defaults
+    pub(crate) fn from_lookup(lookup: &dyn Fn(&str) -> Option<String>) -> Result<Self, AppError> {
+        let search_read_consistency: Option<String> =
+            Self::parse_env_opt(lookup, "SEARCH_READ_CONSISTENCY")?;
+        if let Some(value) = &search_read_consistency {
+            crate::services::parse_read_consistency(value)
+                .map_err(|e| AppError::Config(e.to_string()))?;
+        }
+        let fetch_window_align: String = Self::parse_env(
+            lookup,
+            "FETCH_WINDOW_ALIGN",
+            defaults::FETCH_WINDOW_ALIGN.to_string(),
+        )?;
+        crate::scheduler::parse_window_align(&fetch_window_align)
+            .map_err(|e| AppError::Config(e.to_string()))?;
+        let search_hybrid_fusion: String = Self::parse_env(
+            lookup,
+            "SEARCH_HYBRID_FUSION",
+            defaults::SEARCH_HYBRID_FUSION.to_string(),
+        )?;
+        crate::services::parse_hybrid_fusion(&search_hybrid_fusion)
+            .map_err(|e| AppError::Config(e.to_string()))?;
+        let insert_write_ordering: Option<String> =
+            Self::parse_env_opt(lookup, "INSERT_WRITE_ORDERING")?;
+        let bulk_write_ordering: Option<String> =
+            Self::parse_env_opt(lookup, "BULK_WRITE_ORDERING")?;
+        for value in insert_write_ordering.iter().chain(&bulk_write_ordering) {
+            crate::services::parse_write_ordering(value)
+                .map_err(|e| AppError::Config(e.to_string()))?;
+        }
+
+        let qdrant_url = lookup("QDRANT_URL").unwrap_or_else(|| defaults::QDRANT_URL.to_string());
+        let qdrant_ca_cert: Option<String> = Self::parse_env_opt(lookup, "QDRANT_CA_CERT")?;
+        if !qdrant_url.starts_with("http://") && !qdrant_url.starts_with("https://") {
+            return Err(AppError::Config(format!(
+                "QDRANT_URL must start with http:// or https:// (got '{}')",
+                qdrant_url
+            )));
+        }
+        if qdrant_ca_cert.is_some() && !qdrant_url.starts_with("https://") {
+            return Err(AppError::Config(
+                "QDRANT_CA_CERT requires an https:// QDRANT_URL".to_string(),
+            ));
+        }
+        let qdrant_rest_url: Option<String> = Self::parse_env_opt(lookup, "QDRANT_REST_URL")?;
+        if let Some(url) = &qdrant_rest_url
+            && !url.starts_with("http://")
+            && !url.starts_with("https://")
+        {
+            return Err(AppError::Config(format!(
+                "QDRANT_REST_URL must start with http:// or https:// (got '{}')",
+                url
+            )));
+        }
+        let backup_verify_every_hours: Option<u64> =
+            Self::parse_env_opt(lookup, "BACKUP_VERIFY_EVERY_HOURS")?;
+        if backup_verify_every_hours == Some(0) {
+            return Err(AppError::Config(
+                "BACKUP_VERIFY_EVERY_HOURS must be at least 1".to_string(),
+            ));
+        }
+        if backup_verify_every_hours.is_some() && qdrant_rest_url.is_none() {
+            return Err(AppError::Config(
+                "BACKUP_VERIFY_EVERY_HOURS requires QDRANT_REST_URL".to_string(),
+            ));
+        }
+
+        let log_level = lookup("LOG_LEVEL").unwrap_or_else(|| defaults::LOG_LEVEL.to_string());
+        logging::parse_filter(&log_level)?;
+
+        let search_recency_half_life_hours = crate::services::resolve_half_life(
+            Self::parse_env_opt(lookup, "SEARCH_RECENCY_HALF_LIFE_HOURS")?,
+            None,
+        )
+        .map_err(|_| AppError::Config("SEARCH_RECENCY_HALF_LIFE_HOURS must be >= 0".to_string()))?;
+
+        let search_hybrid_alpha: f32 =
+            Self::parse_env(lookup, "SEARCH_HYBRID_ALPHA", defaults::SEARCH_HYBRID_ALPHA)?;
+        if !(0.0..=1.0).contains(&search_hybrid_alpha) {
+            return Err(AppError::Config(
+                "SEARCH_HYBRID_ALPHA must be between 0 and 1".to_string(),
+            ));
+        }
+
+        let shadow_sample_rate: f64 =
+            Self::parse_env(lookup, "SHADOW_SAMPLE_RATE", defaults::SHADOW_SAMPLE_RATE)?;
+        if !(0.0..=1.0).contains(&shadow_sample_rate) {
+            return Err(AppError::Config(
+                "SHADOW_SAMPLE_RATE must be between 0 and 1".to_string(),
+            ));
+        }
+
+        let circuit_failure_threshold = Self::parse_env(
+            lookup,
+            "CIRCUIT_FAILURE_THRESHOLD",
+            defaults::CIRCUIT_FAILURE_THRESHOLD,
+        )?;
+        if circuit_failure_threshold == 0 {
+            return Err(AppError::Config(
+                "CIRCUIT_FAILURE_THRESHOLD must be at least 1".to_string(),
+            ));
+        }
+
+        let vector_size = Self::parse_env(lookup, "VECTOR_SIZE", defaults::VECTOR_SIZE)?;
+        if vector_size == 0 {
+            return Err(AppError::Config(
+                "VECTOR_SIZE must be at least 1".to_string(),
+            ));
+        }
+        let distance_metric = crate::services::parse_distance(&Self::parse_env(
+            lookup,
+            "DISTANCE_METRIC",
+            defaults::DISTANCE_METRIC.to_string(),
+        )?)
+        .map_err(|e| AppError::Config(e.to_string()))?;
+        let vector_layout = crate::services::parse_vector_layout(&Self::parse_env(
+            lookup,
+            "VECTOR_LAYOUT",
+            defaults::VECTOR_LAYOUT.to_string(),
+        )?)
+        .map_err(|e| AppError::Config(e.to_string()))?;
+        let caption_endpoint: Option<String> = Self::parse_env_opt(lookup, "CAPTION_ENDPOINT")?;
+        if let Some(endpoint) = &caption_endpoint
+            && !endpoint.starts_with('/')
+        {
+            return Err(AppError::Config(format!(
+                "CAPTION_ENDPOINT must be a path on the AI service, e.g. /caption, got '{}'",
+                endpoint
+            )));
+        }
+
+        let qdrant_shard_number: Option<u32> = Self::parse_env_opt(lookup, "QDRANT_SHARD_NUMBER")?;
+        let qdrant_replication_factor: Option<u32> =
+            Self::parse_env_opt(lookup, "QDRANT_REPLICATION_FACTOR")?;
+        let qdrant_write_consistency_factor: Option<u32> =
+            Self::parse_env_opt(lookup, "QDRANT_WRITE_CONSISTENCY_FACTOR")?;
+        for (name, value) in [
+            ("QDRANT_SHARD_NUMBER", qdrant_shard_number),
+            ("QDRANT_REPLICATION_FACTOR", qdrant_replication_factor),
+            (
+                "QDRANT_WRITE_CONSISTENCY_FACTOR",
+                qdrant_write_consistency_factor,
+            ),
+        ] {
+            if value == Some(0) {
+                return Err(AppError::Config(format!("{} must be at least 1", name)));
+            }
+        }
+        if qdrant_write_consistency_factor > Some(qdrant_replication_factor.unwrap_or(1)) {
+            return Err(AppError::Config(
+                "QDRANT_WRITE_CONSISTENCY_FACTOR must not exceed QDRANT_REPLICATION_FACTOR"
+                    .to_string(),
+            ));
+        }
+
+        let ai_rate_limit_rps: Option<f64> = Self::parse_env_opt(lookup, "AI_RATE_LIMIT_RPS")?;
+        if ai_rate_limit_rps.is_some_and(|rps| !(rps > 0.0 && rps.is_finite())) {
+            return Err(AppError::Config(
+                "AI_RATE_LIMIT_RPS must be a positive number".to_string(),
+            ));
+        }
+        let ai_rate_limit_burst =
+            Self::parse_env(lookup, "AI_RATE_LIMIT_BURST", defaults::AI_RATE_LIMIT_BURST)?;
+        if ai_rate_limit_burst == 0 {
+            return Err(AppError::Config(
+                "AI_RATE_LIMIT_BURST must be at least 1".to_string(),
+            ));
+        }
+
+        let ai_max_in_flight: Option<usize> = Self::parse_env_opt(lookup, "AI_MAX_IN_FLIGHT")?;
+        if ai_max_in_flight == Some(0) {
+            return Err(AppError::Config(
+                "AI_MAX_IN_FLIGHT must be at least 1".to_string(),
+            ));
+        }
+        let ai_bulk_every = Self::parse_env(lookup, "AI_BULK_EVERY", defaults::AI_BULK_EVERY)?;
+        if ai_bulk_every == 0 {
+            return Err(AppError::Config(
+                "AI_BULK_EVERY must be at least 1".to_string(),
+            ));
+        }
+
+        let client_rate_limit_rps: Option<f64> =
+            Self::parse_env_opt(lookup, "CLIENT_RATE_LIMIT_RPS")?;
+        if client_rate_limit_rps.is_some_and(|rps| !(rps > 0.0 && rps.is_finite())) {
+            return Err(AppError::Config(
+                "CLIENT_RATE_LIMIT_RPS must be a positive number".to_string(),
+            ));
+        }
+        let client_rate_limit_burst = Self::parse_env(
+            lookup,
+            "CLIENT_RATE_LIMIT_BURST",
+            defaults::CLIENT_RATE_LIMIT_BURST,
+        )?;
+        if client_rate_limit_burst == 0 {
+            return Err(AppError::Config(
+                "CLIENT_RATE_LIMIT_BURST must be at least 1".to_string(),
+            ));
+        }
+
+        let cors_allowed_origins =
+            Self::parse_list(&lookup("CORS_ALLOWED_ORIGINS").unwrap_or_default());
+        let valid_origin = |origin: &str| {
+            origin == "*"
+                || (origin.starts_with("http://") || origin.starts_with("https://"))
+                    && !origin.ends_with('/')
+        };
+        if let Some(origin) = cors_allowed_origins.iter().find(|o| !valid_origin(o)) {
+            return Err(AppError::Config(format!(
+                "CORS_ALLOWED_ORIGINS entry '{}' must be '*' or a scheme and host without a trailing slash, e.g. https://dashboard.example.com",
+                origin
+            )));
+        }
+
+        let upsert_batch_size =
+            Self::parse_env(lookup, "UPSERT_BATCH_SIZE", defaults::UPSERT_BATCH_SIZE)?;
+        if upsert_batch_size == 0 {
+            return Err(AppError::Config(
+                "UPSERT_BATCH_SIZE must be at least 1".to_string(),
+            ));
+        }
+
+        let ingest_busy_hours: Option<String> = Self::parse_env_opt(lookup, "INGEST_BUSY_HOURS")?;
+        if let Some(hours) = &ingest_busy_hours {
+            crate::services::parse_busy_hours(hours)
+                .map_err(|e| AppError::Config(e.to_string()))?;
+        }
+        let ingest_busy_points_per_sec: Option<u32> =
+            Self::parse_env_opt(lookup, "INGEST_BUSY_POINTS_PER_SEC")?;
+        let ingest_off_peak_points_per_sec: Option<u32> =
+            Self::parse_env_opt(lookup, "INGEST_OFF_PEAK_POINTS_PER_SEC")?;
+        for (name, value) in [
+            ("INGEST_BUSY_POINTS_PER_SEC", ingest_busy_points_per_sec),
+            (
+                "INGEST_OFF_PEAK_POINTS_PER_SEC",
+                ingest_off_peak_points_per_sec,
+            ),
+        ] {
+            if value == Some(0) {
+                return Err(AppError::Config(format!("{} must be at least 1", name)));
+            }
+        }
+        if ingest_busy_points_per_sec.is_some() && ingest_busy_hours.is_none() {
+            return Err(AppError::Config(
+                "INGEST_BUSY_POINTS_PER_SEC requires INGEST_BUSY_HOURS".to_string(),
+            ));
+        }
+
+        let webhook_url: Option<String> = Self::parse_env_opt(lookup, "WEBHOOK_URL")?;
+        if let Some(url) = &webhook_url
+            && !url.starts_with("http://")
+            && !url.starts_with("https://")
+        {
+            return Err(AppError::Config(format!(
+                "WEBHOOK_URL must be an http(s) URL: {}",
+                url
+            )));
+        }
+
+        let payload_limits = PayloadLimits {
+            max_bytes: Self::parse_env(lookup, "PAYLOAD_MAX_BYTES", defaults::PAYLOAD_MAX_BYTES)?,
+            max_text_bytes: Self::parse_env(
+                lookup,
+                "PAYLOAD_MAX_TEXT_BYTES",
+                defaults::PAYLOAD_MAX_TEXT_BYTES,
+            )?,
+            policy: crate::services::parse_oversize_policy(&Self::parse_env(
+                lookup,
+                "PAYLOAD_OVERSIZE_POLICY",
+                defaults::PAYLOAD_OVERSIZE_POLICY.to_string(),
+            )?)
+            .map_err(|e| AppError::Config(e.to_string()))?,
+        };
+        if payload_limits.max_text_bytes == 0
+            || payload_limits.max_bytes < payload_limits.max_text_bytes
+        {
+            return Err(AppError::Config(
+                "PAYLOAD_MAX_TEXT_BYTES must be at least 1 and at most PAYLOAD_MAX_BYTES"
+                    .to_string(),
+            ));
+        }
+
+        let server_port = Self::parse_env(lookup, "SERVER_PORT", defaults::SERVER_PORT)?;
+        let admin_port: Option<u16> = Self::parse_env_opt(lookup, "ADMIN_PORT")?;
+        if admin_port == Some(server_port) {
+            return Err(AppError::Config(
+                "ADMIN_PORT must differ from SERVER_PORT".to_string(),
+            ));
+        }
+        let grpc_port: Option<u16> = Self::parse_env_opt(lookup, "GRPC_PORT")?;
+        if grpc_port.is_some_and(|port| port == server_port || admin_port == Some(port)) {
+            return Err(AppError::Config(
+                "GRPC_PORT must differ from SERVER_PORT and ADMIN_PORT".to_string(),
+            ));
+        }
+        let shutdown_timeout_secs = Self::parse_env(
+            lookup,
+            "SHUTDOWN_TIMEOUT_SECS",
+            defaults::SHUTDOWN_TIMEOUT_SECS,
+        )?;
+
+        #[cfg(feature = "edge-ingest")]
+        let (edge_device_id, edge_device_key): (Option<String>, Option<String>) = (
+            Self::parse_env_opt(lookup, "EDGE_DEVICE_ID")?,
+            Self::parse_env_opt(lookup, "EDGE_DEVICE_KEY")?,
+        );
+        #[cfg(feature = "edge-ingest")]
+        if edge_device_id.is_some() != edge_device_key.is_some() {
+            return Err(AppError::Config(
+                "Set both or neither of EDGE_DEVICE_ID and EDGE_DEVICE_KEY".to_string(),
+            ));
+        }
+
+        let jwt_secret: Option<String> = Self::parse_env_opt(lookup, "JWT_SECRET")?;
+        let jwt_jwks_url: Option<String> = Self::parse_env_opt(lookup, "JWT_JWKS_URL")?;
+        if jwt_secret.is_some() && jwt_jwks_url.is_some() {
+            return Err(AppError::Config(
+                "Set only one of JWT_SECRET and JWT_JWKS_URL".to_string(),
+            ));
+        }
+
+        let slo_targets = Self::parse_list(
+            &lookup("SLO_TARGETS").unwrap_or_else(|| defaults::SLO_TARGETS.to_string()),
+        )
+        .iter()
+        .map(|target| target.parse())
+        .collect::<Result<Vec<SloTarget>, String>>()
+        .map_err(AppError::Config)?;
+
+        let cctv_source_timezone = lookup("CCTV_SOURCE_TIMEZONE")
+            .unwrap_or_else(|| defaults::CCTV_SOURCE_TIMEZONE.to_string())
+            .parse::<Tz>()
+            .map_err(|e| AppError::Config(format!("Invalid CCTV_SOURCE_TIMEZONE: {}", e)))?;
+
+        let image_url_rewrites =
+            Self::parse_list(&lookup("IMAGE_URL_REWRITES").unwrap_or_default())
+                .iter()
+                .map(|rule| rule.parse())
+                .collect::<Result<Vec<UrlRewriteRule>, String>>()
+                .map_err(AppError::Config)?;
+
         Ok(Self {
-            qdrant_url: env::var("QDRANT_URL").unwrap_or_else(|_| defaults::QDRANT_URL.to_string()),
-            qdrant_api_key: env::var("QDRANT_API_KEY")
-                .unwrap_or_else(|_| "your_api_key_here".to_string()),
-            ai_service_url: env::var("AI_SERVICE_URL")
-                .unwrap_or_else(|_| defaults::AI_SERVICE_URL.to_string()),
-            collection_name: env::var("COLLECTION_NAME")
-                .unwrap_or_else(|_| defaults::COLLECTION_NAME.to_string()),
-            cctv_api_url: env::var("CCTV_API_URL")
-                .unwrap_or_else(|_| defaults::CCTV_API_URL.to_string()),
-            cctv_authorize_code: env::var("CCTV_AUTHORIZE_CODE")
-                .unwrap_or_else(|_| defaults::CCTV_AUTHORIZE_CODE.to_string()),
-            cctv_user_auth: env::var("CCTV_USER_AUTH")
-                .unwrap_or_else(|_| defaults::CCTV_USER_AUTH.to_string()),
-            cctv_client_id: env::var("CCTV_CLIENT_ID")
-                .unwrap_or_else(|_| defaults::CCTV_CLIENT_ID.to_string()),
-            server_port: Self::parse_env("SERVER_PORT", defaults::SERVER_PORT)?,
-            fetch_limit: Self::parse_env("FETCH_LIMIT", defaults::FETCH_LIMIT)?,
-            fetch_days_range: Self::parse_env("FETCH_DAYS_RANGE", defaults::FETCH_DAYS_RANGE)?,
-            fetch_every_time: Self::parse_env("FETCH_EVERY_TIME", defaults::FETCH_EVERY_TIME)?,
+            qdrant_url,
+            qdrant_api_key: Self::parse_env_opt(lookup, "QDRANT_API_KEY")?,
+            qdrant_ca_cert,
+            qdrant_timeout_secs: Self::parse_env(
+                lookup,
+                "QDRANT_TIMEOUT_SECS",
+                defaults::QDRANT_TIMEOUT_SECS,
+            )?,
+            qdrant_connect_timeout_secs: Self::parse_env(
+                lookup,
+                "QDRANT_CONNECT_TIMEOUT_SECS",
+                defaults::QDRANT_CONNECT_TIMEOUT_SECS,
+            )?,
+            qdrant_keep_alive: Self::parse_env(
+                lookup,
+                "QDRANT_KEEP_ALIVE",
+                defaults::QDRANT_KEEP_ALIVE,
+            )?,
+            qdrant_rest_url,
+            backup_verify_every_hours,
+            qdrant_snapshots_path: lookup("QDRANT_SNAPSHOTS_PATH")
+                .unwrap_or_else(|| defaults::QDRANT_SNAPSHOTS_PATH.to_string()),
+            ai_service_url: lookup("AI_SERVICE_URL")
+                .unwrap_or_else(|| defaults::AI_SERVICE_URL.to_string()),
+            embedding_model: lookup("EMBEDDING_MODEL")
+                .unwrap_or_else(|| defaults::EMBEDDING_MODEL.to_string()),
+            embedding_models_path: Self::parse_env_opt(lookup, "EMBEDDING_MODELS_PATH")?,
+            caption_endpoint,
+            embedding_cache_entries: Self::parse_env(
+                lookup,
+                "EMBEDDING_CACHE_ENTRIES",
+                defaults::EMBEDDING_CACHE_ENTRIES,
+            )?,
+            embedding_cache_ttl_secs: Self::parse_env(
+                lookup,
+                "EMBEDDING_CACHE_TTL_SECS",
+                defaults::EMBEDDING_CACHE_TTL_SECS,
+            )?,
+            collection_name: lookup("COLLECTION_NAME")
+                .unwrap_or_else(|| defaults::COLLECTION_NAME.to_string()),
+            collection_shards: Self::parse_env(
+                lookup,
+                "COLLECTION_SHARDS",
+                defaults::COLLECTION_SHARDS,
+            )?,
+            vector_size,
+            distance_metric,
+            vector_layout,
+            qdrant_shard_number,
+            qdrant_replication_factor,
+            qdrant_write_consistency_factor,
+            qdrant_on_disk_vectors: Self::parse_env_opt(lookup, "QDRANT_ON_DISK_VECTORS")?,
+            qdrant_on_disk_payload: Self::parse_env_opt(lookup, "QDRANT_ON_DISK_PAYLOAD")?,
+            cctv_api_url: lookup("CCTV_API_URL")
+                .unwrap_or_else(|| defaults::CCTV_API_URL.to_string()),
+            cctv_authorize_code: lookup("CCTV_AUTHORIZE_CODE")
+                .unwrap_or_else(|| defaults::CCTV_AUTHORIZE_CODE.to_string()),
+            cctv_user_auth: lookup("CCTV_USER_AUTH")
+                .unwrap_or_else(|| defaults::CCTV_USER_AUTH.to_string()),
+            cctv_client_id: lookup("CCTV_CLIENT_ID")
+                .unwrap_or_else(|| defaults::CCTV_CLIENT_ID.to_string()),
+            cctv_source_timezone,
+            camera_registry_path: lookup("CAMERA_REGISTRY_PATH")
+                .unwrap_or_else(|| defaults::CAMERA_REGISTRY_PATH.to_string()),
+            #[cfg(feature = "edge-ingest")]
+            edge_forward_url: Self::parse_env_opt(lookup, "EDGE_FORWARD_URL")?,
+            #[cfg(feature = "edge-ingest")]
+            edge_forward_api_key: Self::parse_env_opt(lookup, "EDGE_FORWARD_API_KEY")?,
+            #[cfg(feature = "edge-ingest")]
+            edge_device_id,
+            #[cfg(feature = "edge-ingest")]
+            edge_device_key,
+            vehicle_types_path: lookup("VEHICLE_TYPES_PATH")
+                .unwrap_or_else(|| defaults::VEHICLE_TYPES_PATH.to_string()),
+            query_image_dir: lookup("QUERY_IMAGE_DIR")
+                .unwrap_or_else(|| defaults::QUERY_IMAGE_DIR.to_string()),
+            embed_image_root: Self::parse_env_opt(lookup, "EMBED_IMAGE_ROOT")?,
+            backfill_state_path: lookup("BACKFILL_STATE_PATH")
+                .unwrap_or_else(|| defaults::BACKFILL_STATE_PATH.to_string()),
+            dead_letter_path: lookup("DEAD_LETTER_PATH")
+                .unwrap_or_else(|| defaults::DEAD_LETTER_PATH.to_string()),
+            cases_path: lookup("CASES_PATH").unwrap_or_else(|| defaults::CASES_PATH.to_string()),
+            eval_sets_path: lookup("EVAL_SETS_PATH")
+                .unwrap_or_else(|| defaults::EVAL_SETS_PATH.to_string()),
+            alerts_path: lookup("ALERTS_PATH").unwrap_or_else(|| defaults::ALERTS_PATH.to_string()),
+            server_port,
+            admin_port,
+            grpc_port,
+            shutdown_timeout_secs,
+            maintenance_allowlist: Self::parse_list(
+                &lookup("MAINTENANCE_ALLOWLIST")
+                    .unwrap_or_else(|| defaults::MAINTENANCE_ALLOWLIST.to_string()),
+            ),
+            api_keys: Self::parse_list(&lookup("API_KEYS").unwrap_or_default()),
+            api_keys_file: Self::parse_env_opt(lookup, "API_KEYS_FILE")?,
+            edge_device_keys: Self::parse_list(&lookup("EDGE_DEVICE_KEYS").unwrap_or_default()),
+            tenants: Self::parse_list(&lookup("TENANTS").unwrap_or_default()),
+            jwt_secret,
+            jwt_jwks_url,
+            jwt_issuer: Self::parse_env_opt(lookup, "JWT_ISSUER")?,
+            jwt_audience: Self::parse_env_opt(lookup, "JWT_AUDIENCE")?,
+            disabled_features: Self::parse_list(&lookup("DISABLED_FEATURES").unwrap_or_default()),
+            fetch_limit: Self::parse_env(lookup, "FETCH_LIMIT", defaults::FETCH_LIMIT)?,
+            fetch_days_range: Self::parse_env(
+                lookup,
+                "FETCH_DAYS_RANGE",
+                defaults::FETCH_DAYS_RANGE,
+            )?,
+            fetch_every_time: Self::parse_env(
+                lookup,
+                "FETCH_EVERY_TIME",
+                defaults::FETCH_EVERY_TIME,
+            )?,
+            fetch_window_align,
+            search_hnsw_ef: Self::parse_env_opt(lookup, "SEARCH_HNSW_EF")?,
+            search_exact: Self::parse_env(lookup, "SEARCH_EXACT", defaults::SEARCH_EXACT)?,
+            search_indexed_only: Self::parse_env_opt(lookup, "SEARCH_INDEXED_ONLY")?,
+            search_read_consistency,
+            search_fanout_chunks: Self::parse_env(
+                lookup,
+                "SEARCH_FANOUT_CHUNKS",
+                defaults::SEARCH_FANOUT_CHUNKS,
+            )?,
+            search_hybrid_fusion,
+            search_hybrid_alpha,
+            shadow_collection: Self::parse_env_opt(lookup, "SHADOW_COLLECTION")?,
+            shadow_sample_rate,
+            shadow_ai_service_url: Self::parse_env_opt(lookup, "SHADOW_AI_SERVICE_URL")?,
+            search_recency_half_life_hours,
+            search_ai_fallback: Self::parse_env(
+                lookup,
+                "SEARCH_AI_FALLBACK",
+                defaults::SEARCH_AI_FALLBACK,
+            )?,
+            verify_upserts: Self::parse_env(lookup, "VERIFY_UPSERTS", defaults::VERIFY_UPSERTS)?,
+            insert_upsert_wait: Self::parse_env(
+                lookup,
+                "INSERT_UPSERT_WAIT",
+                defaults::INSERT_UPSERT_WAIT,
+            )?,
+            insert_write_ordering,
+            bulk_upsert_wait: Self::parse_env(
+                lookup,
+                "BULK_UPSERT_WAIT",
+                defaults::BULK_UPSERT_WAIT,
+            )?,
+            bulk_write_ordering,
+            upsert_batch_size,
+            hash_images: Self::parse_env(lookup, "HASH_IMAGES", defaults::HASH_IMAGES)?,
+            payload_limits,
+            ingest_busy_hours,
+            ingest_busy_points_per_sec,
+            ingest_off_peak_points_per_sec,
+            slo_targets,
+            slo_window_minutes: Self::parse_env(
+                lookup,
+                "SLO_WINDOW_MINUTES",
+                defaults::SLO_WINDOW_MINUTES,
+            )?,
+            slo_burn_rate_alert: Self::parse_env(
+                lookup,
+                "SLO_BURN_RATE_ALERT",
+                defaults::SLO_BURN_RATE_ALERT,
+            )?,
+            slo_alert_webhook: Self::parse_env_opt(lookup, "SLO_ALERT_WEBHOOK")?,
+            webhook_url,
+            webhook_secret: Self::parse_env_opt(lookup, "WEBHOOK_SECRET")?,
+            webhook_allowed_hosts: Self::parse_list(
+                &lookup("WEBHOOK_ALLOWED_HOSTS").unwrap_or_default(),
+            ),
+            webhook_max_retries: Self::parse_env(
+                lookup,
+                "WEBHOOK_MAX_RETRIES",
+                defaults::WEBHOOK_MAX_RETRIES,
+            )?,
+            webhook_retry_backoff_ms: Self::parse_env(
+                lookup,
+                "WEBHOOK_RETRY_BACKOFF_MS",
+                defaults::WEBHOOK_RETRY_BACKOFF_MS,
+            )?,
+            log_level,
+            log_format: Self::parse_env(lookup, "LOG_FORMAT", LogFormat::Text)?,
+            circuit_failure_threshold,
+            circuit_open_secs: Self::parse_env(
+                lookup,
+                "CIRCUIT_OPEN_SECS",
+                defaults::CIRCUIT_OPEN_SECS,
+            )?,
+            ai_rate_limit_rps,
+            ai_rate_limit_burst,
+            ai_max_in_flight,
+            ai_bulk_every,
+            client_rate_limit_rps,
+            client_rate_limit_burst,
+            client_rate_limit_paths: Self::parse_list(
+                &lookup("CLIENT_RATE_LIMIT_PATHS")
+                    .unwrap_or_else(|| defaults::CLIENT_RATE_LIMIT_PATHS.to_string()),
+            ),
+            cors_allowed_origins,
+            cors_allowed_methods: Self::parse_list(
+                &lookup("CORS_ALLOWED_METHODS")
+                    .unwrap_or_else(|| defaults::CORS_ALLOWED_METHODS.to_string()),
+            )
+            .into_iter()
+            .map(|method| method.to_uppercase())
+            .collect(),
+            cors_allowed_headers: Self::parse_list(
+                &lookup("CORS_ALLOWED_HEADERS")
+                    .unwrap_or_else(|| defaults::CORS_ALLOWED_HEADERS.to_string()),
+            ),
+            image_url_rewrites,
+            config_file: None,
+            config_file_unused: Vec::new(),
+            cameras: Vec::new(),
         })
     }
 
     /// Helper function to parse environment variables with type conversion
-    fn parse_env<T: std::str::FromStr>(key: &str, default: T) -> Result<T, String>
+    fn parse_env<T: std::str::FromStr>(
+        lookup: &dyn Fn(&str) -> Option<String>,
+        key: &str,
+        default: T,
+    ) -> Result<T, AppError>
     where
         T::Err: std::fmt::Display,
     {
-        match env::var(key) {
-            Ok(val) => val
-                .parse::<T>()
-                .map_err(|e| format!("Failed to parse {}: {} (value: '{}')", key, e, val)),
-            Err(_) => Ok(default),
+        match lookup(key) {
+            Some(val) => val.parse::<T>().map_err(|e| {
+                AppError::Config(format!("Failed to parse {}: {} (value: '{}')", key, e, val))
+            }),
+            None => Ok(default),
+        }
+    }
+
+    /// Helper function to parse optional environment variables (unset or empty = None)
+    fn parse_env_opt<T: std::str::FromStr>(
+        lookup: &dyn Fn(&str) -> Option<String>,
+        key: &str,
+    ) -> Result<Option<T>, AppError>
+    where
+        T::Err: std::fmt::Display,
+    {
+        match lookup(key) {
+            Some(val) if !val.is_empty() => val.parse::<T>().map(Some).map_err(|e| {
+                AppError::Config(format!("Failed to parse {}: {} (value: '{}')", key, e, val))
+            }),
+            _ => Ok(None),
+        }
+    }
+
+    /// Helper function to split a comma-separated list, dropping empty items
+    fn parse_list(value: &str) -> Vec<String> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Settings that can be changed at runtime without a restart
+    pub fn tunables(&self) -> Tunables {
+        Tunables {
+            fetch_limit: self.fetch_limit,
+            fetch_days_range: self.fetch_days_range,
+            fetch_every_time: self.fetch_every_time,
+            fetch_window_align: self.fetch_window_align.clone(),
+            search_hnsw_ef: self.search_hnsw_ef,
+            search_exact: self.search_exact,
+            search_indexed_only: self.search_indexed_only,
+            search_read_consistency: self.search_read_consistency.clone(),
+            search_fanout_chunks: self.search_fanout_chunks,
+            search_hybrid_fusion: self.search_hybrid_fusion.clone(),
+            search_hybrid_alpha: self.search_hybrid_alpha,
+            shadow_collection: self.shadow_collection.clone(),
+            shadow_sample_rate: self.shadow_sample_rate,
+            shadow_ai_service_url: self.shadow_ai_service_url.clone(),
+            search_recency_half_life_hours: self.search_recency_half_life_hours,
+            search_ai_fallback: self.search_ai_fallback,
+            verify_upserts: self.verify_upserts,
+            insert_upsert_wait: self.insert_upsert_wait,
+            insert_write_ordering: self.insert_write_ordering.clone(),
+            bulk_upsert_wait: self.bulk_upsert_wait,
+            bulk_write_ordering: self.bulk_write_ordering.clone(),
+            upsert_batch_size: self.upsert_batch_size,
+            hash_images: self.hash_images,
+            ingest_busy_hours: self.ingest_busy_hours.clone(),
+            ingest_busy_points_per_sec: self.ingest_busy_points_per_sec,
+            ingest_off_peak_points_per_sec: self.ingest_off_peak_points_per_sec,
+            log_level: self.log_level.clone(),
+        }
+    }
+
+    /// Log configuration summary
+    pub fn log_summary(&self) {
+        info!("Starting CCTV Search Backend");
+        if let Some(path) = &self.config_file {
+            info!(path = %path, "Config file");
+        }
+        for setting in &self.config_file_unused {
+            warn!(setting = %setting, "Config file setting not used by this build");
+        }
+        info!(
+            port = self.server_port,
+            admin_port = self.admin_port.unwrap_or(self.server_port),
+            grpc_port = ?self.grpc_port,
+            shutdown_timeout_secs = self.shutdown_timeout_secs,
+            api_keys = self.api_keys.len(),
+            api_keys_file = self.api_keys_file.as_deref().unwrap_or("none"),
+            edge_devices = self.edge_device_keys.len(),
+            "Server"
+        );
+        let jwt_keys = match (&self.jwt_secret, &self.jwt_jwks_url) {
+            (Some(_), _) => "secret",
+            (None, Some(url)) => url.as_str(),
+            (None, None) => "none",
+        };
+        info!(
+            keys = jwt_keys,
+            issuer = self.jwt_issuer.as_deref().unwrap_or("any"),
+            audience = self.jwt_audience.as_deref().unwrap_or("any"),
+            "JWT"
+        );
+        if !self.tenants.is_empty() {
+            info!(tenants = ?self.tenants, "Tenancy");
+        }
+        info!(
+            url = %self.qdrant_url,
+            tls = self.qdrant_url.starts_with("https://"),
+            ca = self.qdrant_ca_cert.as_deref().unwrap_or("system"),
+            auth = if self.qdrant_api_key.is_some() {
+                "api-key"
+            } else {
+                "none"
+            },
+            timeout_secs = self.qdrant_timeout_secs,
+            rest_url = self.qdrant_rest_url.as_deref().unwrap_or("none"),
+            snapshots_path = %self.qdrant_snapshots_path,
+            backup_verify_every_hours = ?self.backup_verify_every_hours,
+            "Qdrant"
+        );
+        info!(
+            url = %self.ai_service_url,
+            model = %self.embedding_model,
+            models_path = self.embedding_models_path.as_deref().unwrap_or("none"),
+            caption_endpoint = self.caption_endpoint.as_deref().unwrap_or("none"),
+            cache_entries = self.embedding_cache_entries,
+            cache_ttl_secs = self.embedding_cache_ttl_secs,
+            "AI service"
+        );
+        info!(
+            collection = %self.collection_name,
+            shards = self.collection_shards,
+            vector_size = self.vector_size,
+            distance = self.distance_metric.as_str_name(),
+            vector_layout = self.vector_layout.as_str(),
+            qdrant_shards = ?self.qdrant_shard_number,
+            replication_factor = ?self.qdrant_replication_factor,
+            write_consistency_factor = ?self.qdrant_write_consistency_factor,
+            on_disk_vectors = ?self.qdrant_on_disk_vectors,
+            on_disk_payload = ?self.qdrant_on_disk_payload,
+            "Collection"
+        );
+        info!(
+            url = %self.cctv_api_url,
+            source_timezone = %self.cctv_source_timezone,
+            "CCTV API"
+        );
+        info!(
+            limit = self.fetch_limit,
+            range_days = self.fetch_days_range,
+            every_minutes = self.fetch_every_time,
+            align = %self.fetch_window_align,
+            "Fetch"
+        );
+        info!(
+            hnsw_ef = ?self.search_hnsw_ef,
+            exact = self.search_exact,
+            indexed_only = ?self.search_indexed_only,
+            consistency = self.search_read_consistency.as_deref().unwrap_or("default"),
+            fanout_chunks = self.search_fanout_chunks,
+            hybrid_fusion = %self.search_hybrid_fusion,
+            hybrid_alpha = self.search_hybrid_alpha,
+            recency_half_life_hours = ?self.search_recency_half_life_hours,
+            ai_fallback = self.search_ai_fallback,
+            verify_upserts = self.verify_upserts,
+            "Search"
+        );
+        if let Some(collection) = &self.shadow_collection {
+            info!(
+                collection = %collection,
+                sample_rate = self.shadow_sample_rate,
+                ai_service = self.shadow_ai_service_url.as_deref().unwrap_or("live"),
+                "Shadow search"
+            );
+        }
+        info!(
+            insert_wait = self.insert_upsert_wait,
+            insert_ordering = self.insert_write_ordering.as_deref().unwrap_or("default"),
+            bulk_wait = self.bulk_upsert_wait,
+            bulk_ordering = self.bulk_write_ordering.as_deref().unwrap_or("default"),
+            batch_size = self.upsert_batch_size,
+            hash_images = self.hash_images,
+            payload_max_bytes = self.payload_limits.max_bytes,
+            payload_max_text_bytes = self.payload_limits.max_text_bytes,
+            payload_oversize_policy = ?self.payload_limits.policy,
+            "Upserts"
+        );
+        if let Some(hours) = &self.ingest_busy_hours {
+            info!(
+                busy_hours = %hours,
+                busy_points_per_sec = ?self.ingest_busy_points_per_sec,
+                off_peak_points_per_sec = ?self.ingest_off_peak_points_per_sec,
+                "Ingest throttle"
+            );
+        } else if let Some(rate) = self.ingest_off_peak_points_per_sec {
+            info!(points_per_sec = rate, "Ingest throttle");
+        }
+        if let Some(url) = &self.webhook_url {
+            info!(
+                url = %url,
+                signed = self.webhook_secret.is_some(),
+                max_retries = self.webhook_max_retries,
+                retry_backoff_ms = self.webhook_retry_backoff_ms,
+                "Webhook"
+            );
+        }
+        info!(
+            failure_threshold = self.circuit_failure_threshold,
+            open_secs = self.circuit_open_secs,
+            "Circuit breakers"
+        );
+        match self.ai_rate_limit_rps {
+            Some(rps) => info!(
+                rps,
+                burst = self.ai_rate_limit_burst,
+                "AI service rate limit"
+            ),
+            None => info!("AI service rate limit disabled"),
+        }
+        match self.ai_max_in_flight {
+            Some(max_in_flight) => info!(
+                max_in_flight,
+                bulk_every = self.ai_bulk_every,
+                "AI service dispatch queue"
+            ),
+            None => info!("AI service dispatch queue disabled"),
+        }
+        match self.client_rate_limit_rps {
+            Some(rps) => info!(
+                rps,
+                burst = self.client_rate_limit_burst,
+                paths = ?self.client_rate_limit_paths,
+                "Per-client rate limit"
+            ),
+            None => info!("Per-client rate limit disabled"),
+        }
+        if self.cors_allowed_origins.is_empty() {
+            info!("CORS disabled");
+        } else {
+            info!(
+                origins = ?self.cors_allowed_origins,
+                methods = ?self.cors_allowed_methods,
+                headers = ?self.cors_allowed_headers,
+                "CORS"
+            );
+        }
+        if !self.image_url_rewrites.is_empty() {
+            info!(rules = self.image_url_rewrites.len(), "Image URL rewrites");
         }
+        for slo in &self.slo_targets {
+            info!(
+                path = %slo.path,
+                threshold_ms = slo.threshold_ms,
+                objective = slo.objective,
+                window_minutes = self.slo_window_minutes,
+                "SLO"
+            );
+        }
+    }
+}
+
+/// Runtime-tunable subset of the configuration
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Tunables {
+    pub fetch_limit: u32,
+    pub fetch_days_range: i64,
+    pub fetch_every_time: i64,
+    /// Boundaries of scheduled fetch windows: `none`, `minute`, `hour` or `day`
+    pub fetch_window_align: String,
+    pub search_hnsw_ef: Option<u64>,
+    pub search_exact: bool,
+    pub search_indexed_only: Option<bool>,
+    pub search_read_consistency: Option<String>,
+    pub search_fanout_chunks: u32,
+    pub search_hybrid_fusion: String,
+    pub search_hybrid_alpha: f32,
+    pub shadow_collection: Option<String>,
+    pub shadow_sample_rate: f64,
+    pub shadow_ai_service_url: Option<String>,
+    pub search_recency_half_life_hours: Option<f64>,
+    pub search_ai_fallback: bool,
+    pub verify_upserts: bool,
+    pub insert_upsert_wait: bool,
+    pub insert_write_ordering: Option<String>,
+    pub bulk_upsert_wait: bool,
+    pub bulk_write_ordering: Option<String>,
+    pub upsert_batch_size: usize,
+    pub hash_images: bool,
+    pub ingest_busy_hours: Option<String>,
+    pub ingest_busy_points_per_sec: Option<u32>,
+    pub ingest_off_peak_points_per_sec: Option<u32>,
+    pub log_level: String,
+}
+
+/// Shared, watchable handle to the current tunables
+///
+/// Cloned into the HTTP workers and the scheduler; a reload is visible to
+/// every holder and wakes up subscribers.
+#[derive(Clone)]
+pub struct TunablesHandle {
+    sender: Arc<watch::Sender<Tunables>>,
+}
+
+impl TunablesHandle {
+    pub fn new(tunables: Tunables) -> Self {
+        Self {
+            sender: Arc::new(watch::Sender::new(tunables)),
+        }
+    }
+
+    /// Snapshot of the current tunables
+    pub fn current(&self) -> Tunables {
+        self.sender.borrow().clone()
     }
 
-    /// Print configuration summary
-    pub fn print_summary(&self) {
-        println!("========================================");
-        println!("🚀 Starting CCTV Search Backend");
-        println!("   -> Server Port : {}", self.server_port);
-        println!("   -> Qdrant URL  : {}", self.qdrant_url);
-        println!("   -> AI Service  : {}", self.ai_service_url);
-        println!("   -> Collection  : {}", self.collection_name);
-        println!("   -> Fetch Limit : {} images", self.fetch_limit);
-        println!("   -> Fetch Range : {} days", self.fetch_days_range);
-        println!("   -> Fetch Every : {} minutes", self.fetch_every_time);
-        println!("========================================");
+    /// Receiver notified whenever the tunables change
+    pub fn subscribe(&self) -> watch::Receiver<Tunables> {
+        self.sender.subscribe()
+    }
+
+    /// Re-read the configuration and publish the new tunables
+    pub fn reload(&self) -> Result<Tunables, AppError> {
+        let tunables = Config::reload_from_env_file()?.tunables();
+        self.sender.send_replace(tunables.clone());
+        Ok(tunables)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_lookup_overrides_defaults() {
+        let vars: HashMap<&str, &str> =
+            HashMap::from([("FETCH_LIMIT", "50"), ("SEARCH_EXACT", "true")]);
+        let config = Config::from_lookup(&|key| vars.get(key).map(|v| v.to_string())).unwrap();
+
+        assert_eq!(config.fetch_limit, 50);
+        assert!(config.search_exact);
+        assert_eq!(config.server_port, defaults::SERVER_PORT);
+
+        let invalid = HashMap::from([("SEARCH_READ_CONSISTENCY", "eventual")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("FETCH_WINDOW_ALIGN", "week")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("SEARCH_HYBRID_FUSION", "sum")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("SEARCH_HYBRID_ALPHA", "1.5")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("SHADOW_SAMPLE_RATE", "1.5")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("ADMIN_PORT", "8080")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("ADMIN_PORT", "9090"), ("GRPC_PORT", "9090")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        #[cfg(feature = "edge-ingest")]
+        {
+            let invalid = HashMap::from([("EDGE_DEVICE_ID", "gate_a")]);
+            assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        }
+        let invalid = HashMap::from([("QDRANT_WRITE_CONSISTENCY_FACTOR", "2")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("QDRANT_REST_URL", "localhost:6333")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("BACKUP_VERIFY_EVERY_HOURS", "24")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("VECTOR_LAYOUT", "multi")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("PAYLOAD_OVERSIZE_POLICY", "drop")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("PAYLOAD_MAX_BYTES", "1024")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("INGEST_BUSY_HOURS", "7am-8pm")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("INGEST_BUSY_POINTS_PER_SEC", "200")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("CAPTION_ENDPOINT", "caption")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("WEBHOOK_URL", "hooks.example.com/cctv")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("DISTANCE_METRIC", "hamming")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("AI_RATE_LIMIT_RPS", "0")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("AI_MAX_IN_FLIGHT", "0")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("CLIENT_RATE_LIMIT_RPS", "-1")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("CORS_ALLOWED_ORIGINS", "dashboard.example.com")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("CCTV_SOURCE_TIMEZONE", "Asia/Atlantis")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([("IMAGE_URL_REWRITES", "http://10.0.0.5/images/")]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+        let invalid = HashMap::from([
+            ("JWT_SECRET", "s3cret"),
+            ("JWT_JWKS_URL", "https://sso.example/jwks.json"),
+        ]);
+        assert!(Config::from_lookup(&|key| invalid.get(key).map(|v| v.to_string())).is_err());
+    }
+
+    #[test]
+    fn test_qdrant_transport_settings() {
+        let load = |vars: HashMap<&str, &str>| {
+            Config::from_lookup(&|key| vars.get(key).map(|v| v.to_string()))
+        };
+
+        let config = load(HashMap::from([("QDRANT_API_KEY", "")])).unwrap();
+        assert!(config.qdrant_api_key.is_none());
+
+        let config = load(HashMap::from([
+            ("QDRANT_URL", "https://qdrant.internal:6334"),
+            ("QDRANT_CA_CERT", "/etc/ssl/qdrant-ca.pem"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            config.qdrant_ca_cert.as_deref(),
+            Some("/etc/ssl/qdrant-ca.pem")
+        );
+
+        assert!(
+            load(HashMap::from([(
+                "QDRANT_CA_CERT",
+                "/etc/ssl/qdrant-ca.pem"
+            )]))
+            .is_err()
+        );
+        assert!(load(HashMap::from([("QDRANT_URL", "qdrant:6334")])).is_err());
     }
 }
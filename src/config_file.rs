@@ -0,0 +1,580 @@
+//! Configuration File
+//!
+//! Settings can also be given in `cctv-backend.toml`, or the TOML or YAML
+//! file named by `CONFIG_FILE`, grouped in sections. Each key sets one
+//! environment variable, e.g. `url` in `[qdrant]` sets `QDRANT_URL`, so the
+//! file layers over the environment with the same parsing and validation.
+//! Unknown keys and values of the wrong type are rejected.
+
+use crate::config::defaults;
+use crate::error::AppError;
+use crate::models::cctv::CameraEntry;
+use serde::Deserialize;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Value of a file setting as the environment variable would hold it
+trait Setting {
+    fn to_var(&self) -> String;
+}
+
+impl Setting for String {
+    fn to_var(&self) -> String {
+        self.clone()
+    }
+}
+
+impl Setting for bool {
+    fn to_var(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Setting for u64 {
+    fn to_var(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Setting for i64 {
+    fn to_var(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Setting for f64 {
+    fn to_var(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// A list setting, as an array or already comma-separated
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum StringList {
+    Items(Vec<String>),
+    Joined(String),
+}
+
+impl Setting for StringList {
+    fn to_var(&self) -> String {
+        match self {
+            StringList::Items(items) => items.join(","),
+            StringList::Joined(joined) => joined.clone(),
+        }
+    }
+}
+
+/// Struct of optional settings, each setting the environment variable after
+/// `=>`, and of the nested sections
+macro_rules! settings {
+    (
+        $(#[$doc:meta])*
+        struct $name:ident {
+            $($field:ident: $ty:ty => $var:literal,)*
+        }
+        $(sections { $($section:ident: $section_ty:ty,)* })?
+    ) => {
+        $(#[$doc])*
+        #[derive(Debug, Default, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct $name {
+            $($field: Option<$ty>,)*
+            $($(
+                #[serde(default)]
+                $section: $section_ty,
+            )*)?
+        }
+
+        impl $name {
+            /// Environment variables set, with their values
+            fn vars(&self) -> Vec<(&'static str, String)> {
+                #[allow(unused_mut)]
+                let mut vars = Vec::new();
+                $(
+                    if let Some(value) = &self.$field {
+                        vars.push(($var, value.to_var()));
+                    }
+                )*
+                $($(vars.extend(self.$section.vars());)*)?
+                vars
+            }
+        }
+    };
+}
+
+settings! {
+    /// Top level of the file
+    struct FileSettings {
+        collection_name: String => "COLLECTION_NAME",
+        collection_shards: u64 => "COLLECTION_SHARDS",
+        vector_size: u64 => "VECTOR_SIZE",
+        distance_metric: String => "DISTANCE_METRIC",
+        vector_layout: String => "VECTOR_LAYOUT",
+        tenants: StringList => "TENANTS",
+        disabled_features: StringList => "DISABLED_FEATURES",
+        log_level: String => "LOG_LEVEL",
+        log_format: String => "LOG_FORMAT",
+        server_port: u64 => "SERVER_PORT",
+        admin_port: u64 => "ADMIN_PORT",
+        grpc_port: u64 => "GRPC_PORT",
+        shutdown_timeout_secs: u64 => "SHUTDOWN_TIMEOUT_SECS",
+        maintenance_allowlist: StringList => "MAINTENANCE_ALLOWLIST",
+        api_keys: StringList => "API_KEYS",
+        api_keys_file: String => "API_KEYS_FILE",
+        vehicle_types_path: String => "VEHICLE_TYPES_PATH",
+        query_image_dir: String => "QUERY_IMAGE_DIR",
+        embed_image_root: String => "EMBED_IMAGE_ROOT",
+        backfill_state_path: String => "BACKFILL_STATE_PATH",
+        dead_letter_path: String => "DEAD_LETTER_PATH",
+        cases_path: String => "CASES_PATH",
+        eval_sets_path: String => "EVAL_SETS_PATH",
+        alerts_path: String => "ALERTS_PATH",
+        backup_verify_every_hours: u64 => "BACKUP_VERIFY_EVERY_HOURS",
+        verify_upserts: bool => "VERIFY_UPSERTS",
+        hash_images: bool => "HASH_IMAGES",
+        upsert_batch_size: u64 => "UPSERT_BATCH_SIZE",
+        insert_upsert_wait: bool => "INSERT_UPSERT_WAIT",
+        insert_write_ordering: String => "INSERT_WRITE_ORDERING",
+        bulk_upsert_wait: bool => "BULK_UPSERT_WAIT",
+        bulk_write_ordering: String => "BULK_WRITE_ORDERING",
+        image_url_rewrites: StringList => "IMAGE_URL_REWRITES",
+    }
+    sections {
+        qdrant: QdrantSettings,
+        ai_service: AiServiceSettings,
+        embedding_cache: EmbeddingCacheSettings,
+        circuit: CircuitSettings,
+        cctv: CctvSettings,
+        scheduler: SchedulerSettings,
+        cameras: CamerasSettings,
+        search: SearchSettings,
+        shadow: ShadowSettings,
+        ingest: IngestSettings,
+        payload: PayloadSettings,
+        jwt: JwtSettings,
+        cors: CorsSettings,
+        client_rate_limit: ClientRateLimitSettings,
+        slo: SloSettings,
+        webhook: WebhookSettings,
+        edge: EdgeSettings,
+    }
+}
+
+settings! {
+    /// `[qdrant]`
+    struct QdrantSettings {
+        url: String => "QDRANT_URL",
+        rest_url: String => "QDRANT_REST_URL",
+        api_key: String => "QDRANT_API_KEY",
+        ca_cert: String => "QDRANT_CA_CERT",
+        timeout_secs: u64 => "QDRANT_TIMEOUT_SECS",
+        connect_timeout_secs: u64 => "QDRANT_CONNECT_TIMEOUT_SECS",
+        keep_alive: bool => "QDRANT_KEEP_ALIVE",
+        snapshots_path: String => "QDRANT_SNAPSHOTS_PATH",
+        shard_number: u64 => "QDRANT_SHARD_NUMBER",
+        replication_factor: u64 => "QDRANT_REPLICATION_FACTOR",
+        write_consistency_factor: u64 => "QDRANT_WRITE_CONSISTENCY_FACTOR",
+        on_disk_vectors: bool => "QDRANT_ON_DISK_VECTORS",
+        on_disk_payload: bool => "QDRANT_ON_DISK_PAYLOAD",
+    }
+}
+
+settings! {
+    /// `[ai_service]`
+    struct AiServiceSettings {
+        url: String => "AI_SERVICE_URL",
+        embedding_model: String => "EMBEDDING_MODEL",
+        embedding_models_path: String => "EMBEDDING_MODELS_PATH",
+        caption_endpoint: String => "CAPTION_ENDPOINT",
+        rate_limit_rps: f64 => "AI_RATE_LIMIT_RPS",
+        rate_limit_burst: u64 => "AI_RATE_LIMIT_BURST",
+        max_in_flight: u64 => "AI_MAX_IN_FLIGHT",
+        bulk_every: u64 => "AI_BULK_EVERY",
+    }
+}
+
+settings! {
+    /// `[embedding_cache]`
+    struct EmbeddingCacheSettings {
+        entries: u64 => "EMBEDDING_CACHE_ENTRIES",
+        ttl_secs: u64 => "EMBEDDING_CACHE_TTL_SECS",
+    }
+}
+
+settings! {
+    /// `[circuit]`
+    struct CircuitSettings {
+        failure_threshold: u64 => "CIRCUIT_FAILURE_THRESHOLD",
+        open_secs: u64 => "CIRCUIT_OPEN_SECS",
+    }
+}
+
+settings! {
+    /// `[cctv]`
+    struct CctvSettings {
+        api_url: String => "CCTV_API_URL",
+        authorize_code: String => "CCTV_AUTHORIZE_CODE",
+        user_auth: String => "CCTV_USER_AUTH",
+        client_id: String => "CCTV_CLIENT_ID",
+        source_timezone: String => "CCTV_SOURCE_TIMEZONE",
+    }
+}
+
+settings! {
+    /// `[scheduler]`
+    struct SchedulerSettings {
+        limit: u64 => "FETCH_LIMIT",
+        days_range: i64 => "FETCH_DAYS_RANGE",
+        every_time: i64 => "FETCH_EVERY_TIME",
+        window_align: String => "FETCH_WINDOW_ALIGN",
+    }
+}
+
+settings! {
+    /// `[search]`
+    struct SearchSettings {
+        hnsw_ef: u64 => "SEARCH_HNSW_EF",
+        exact: bool => "SEARCH_EXACT",
+        indexed_only: bool => "SEARCH_INDEXED_ONLY",
+        read_consistency: String => "SEARCH_READ_CONSISTENCY",
+        fanout_chunks: u64 => "SEARCH_FANOUT_CHUNKS",
+        hybrid_fusion: String => "SEARCH_HYBRID_FUSION",
+        hybrid_alpha: f64 => "SEARCH_HYBRID_ALPHA",
+        recency_half_life_hours: f64 => "SEARCH_RECENCY_HALF_LIFE_HOURS",
+        ai_fallback: bool => "SEARCH_AI_FALLBACK",
+    }
+}
+
+settings! {
+    /// `[shadow]`
+    struct ShadowSettings {
+        collection: String => "SHADOW_COLLECTION",
+        sample_rate: f64 => "SHADOW_SAMPLE_RATE",
+        ai_service_url: String => "SHADOW_AI_SERVICE_URL",
+    }
+}
+
+settings! {
+    /// `[ingest]`
+    struct IngestSettings {
+        busy_hours: String => "INGEST_BUSY_HOURS",
+        busy_points_per_sec: u64 => "INGEST_BUSY_POINTS_PER_SEC",
+        off_peak_points_per_sec: u64 => "INGEST_OFF_PEAK_POINTS_PER_SEC",
+    }
+}
+
+settings! {
+    /// `[payload]`
+    struct PayloadSettings {
+        max_bytes: u64 => "PAYLOAD_MAX_BYTES",
+        max_text_bytes: u64 => "PAYLOAD_MAX_TEXT_BYTES",
+        oversize_policy: String => "PAYLOAD_OVERSIZE_POLICY",
+    }
+}
+
+settings! {
+    /// `[jwt]`
+    struct JwtSettings {
+        secret: String => "JWT_SECRET",
+        jwks_url: String => "JWT_JWKS_URL",
+        issuer: String => "JWT_ISSUER",
+        audience: String => "JWT_AUDIENCE",
+    }
+}
+
+settings! {
+    /// `[cors]`
+    struct CorsSettings {
+        allowed_origins: StringList => "CORS_ALLOWED_ORIGINS",
+        allowed_methods: StringList => "CORS_ALLOWED_METHODS",
+        allowed_headers: StringList => "CORS_ALLOWED_HEADERS",
+    }
+}
+
+settings! {
+    /// `[client_rate_limit]`
+    struct ClientRateLimitSettings {
+        rps: f64 => "CLIENT_RATE_LIMIT_RPS",
+        burst: u64 => "CLIENT_RATE_LIMIT_BURST",
+        paths: StringList => "CLIENT_RATE_LIMIT_PATHS",
+    }
+}
+
+settings! {
+    /// `[slo]`
+    struct SloSettings {
+        targets: StringList => "SLO_TARGETS",
+        window_minutes: u64 => "SLO_WINDOW_MINUTES",
+        burn_rate_alert: f64 => "SLO_BURN_RATE_ALERT",
+        alert_webhook: String => "SLO_ALERT_WEBHOOK",
+    }
+}
+
+settings! {
+    /// `[webhook]`
+    struct WebhookSettings {
+        url: String => "WEBHOOK_URL",
+        secret: String => "WEBHOOK_SECRET",
+        allowed_hosts: StringList => "WEBHOOK_ALLOWED_HOSTS",
+        max_retries: u64 => "WEBHOOK_MAX_RETRIES",
+        retry_backoff_ms: u64 => "WEBHOOK_RETRY_BACKOFF_MS",
+    }
+}
+
+settings! {
+    /// `[edge]`
+    struct EdgeSettings {
+        forward_url: String => "EDGE_FORWARD_URL",
+        forward_api_key: String => "EDGE_FORWARD_API_KEY",
+        device_id: String => "EDGE_DEVICE_ID",
+        device_key: String => "EDGE_DEVICE_KEY",
+        device_keys: StringList => "EDGE_DEVICE_KEYS",
+    }
+}
+
+/// `[cameras]`: the registry path and a `[cameras.<cctv_id>]` table per camera
+#[derive(Debug, Default, Deserialize)]
+struct CamerasSettings {
+    registry_path: Option<String>,
+    /// Every other key is a camera; a misspelled key fails as a camera table
+    #[serde(flatten)]
+    cameras: BTreeMap<String, CameraSettings>,
+}
+
+impl CamerasSettings {
+    fn vars(&self) -> Vec<(&'static str, String)> {
+        self.registry_path
+            .iter()
+            .map(|path| ("CAMERA_REGISTRY_PATH", path.clone()))
+            .collect()
+    }
+
+    fn entries(self) -> Vec<CameraEntry> {
+        self.cameras
+            .into_iter()
+            .map(|(cctv_id, camera)| CameraEntry {
+                cctv_id,
+                enabled: camera.enabled.unwrap_or(true),
+                name: camera.name,
+                location: camera.location,
+                lat: camera.lat,
+                lon: camera.lon,
+                timezone: camera.timezone,
+                tenant_id: camera.tenant_id,
+            })
+            .collect()
+    }
+}
+
+/// `[cameras.<cctv_id>]`, the fields of a camera registry entry
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraSettings {
+    enabled: Option<bool>,
+    name: Option<String>,
+    location: Option<String>,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    timezone: Option<String>,
+    tenant_id: Option<String>,
+}
+
+/// Environment variable set by the file
+#[derive(Debug, Clone)]
+struct Var {
+    name: &'static str,
+    value: String,
+    used: Cell<bool>,
+}
+
+/// Settings read from a configuration file; empty without one
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFile {
+    path: Option<String>,
+    vars: Vec<Var>,
+    cameras: Vec<CameraEntry>,
+}
+
+impl ConfigFile {
+    /// Read `path`, or `cctv-backend.toml` if no path is given and it exists
+    ///
+    /// Files ending in `.yaml` or `.yml` are read as YAML, others as TOML.
+    pub fn load(path: Option<&str>) -> Result<Self, AppError> {
+        let path = match path.map(str::trim).filter(|path| !path.is_empty()) {
+            Some(path) => path,
+            None if Path::new(defaults::CONFIG_FILE).exists() => defaults::CONFIG_FILE,
+            None => return Ok(Self::default()),
+        };
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| AppError::Config(format!("Failed to read config file {}: {}", path, e)))?;
+        let yaml = path.ends_with(".yaml") || path.ends_with(".yml");
+        let file = Self::parse(&text, yaml)
+            .map_err(|e| AppError::Config(format!("Invalid config file {}: {}", path, e)))?;
+        Ok(Self {
+            path: Some(path.to_string()),
+            ..file
+        })
+    }
+
+    /// Settings of a TOML or YAML document
+    fn parse(text: &str, yaml: bool) -> Result<Self, String> {
+        let mut settings: FileSettings = if yaml {
+            // An empty YAML document is null rather than a map
+            if text.trim().is_empty() {
+                FileSettings::default()
+            } else {
+                serde_yaml::from_str(text).map_err(|e| e.to_string())?
+            }
+        } else {
+            toml::from_str(text).map_err(|e| e.to_string())?
+        };
+        let vars = settings
+            .vars()
+            .into_iter()
+            .map(|(name, value)| Var {
+                name,
+                value,
+                used: Cell::new(false),
+            })
+            .collect();
+        Ok(Self {
+            path: None,
+            vars,
+            cameras: std::mem::take(&mut settings.cameras).entries(),
+        })
+    }
+
+    /// File the settings were read from
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Value of the environment variable `name` given in the file
+    pub fn get(&self, name: &str) -> Option<String> {
+        let var = self.vars.iter().find(|var| var.name == name)?;
+        var.used.set(true);
+        Some(var.value.clone())
+    }
+
+    /// Cameras of the `[cameras.<cctv_id>]` tables
+    pub fn cameras(&self) -> &[CameraEntry] {
+        &self.cameras
+    }
+
+    /// Variables set by the file that were not read so far, i.e. settings
+    /// of features left out of this build
+    pub fn unused(&self) -> Vec<String> {
+        self.vars
+            .iter()
+            .filter(|var| !var.used.get())
+            .map(|var| var.name.to_string())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_toml() {
+        let toml = ConfigFile::parse(
+            r#"
+collection_name = "nt-cctv-vehicles"  # the live collection
+tenants = [
+  "site-a", # north
+  "site-b",
+]
+cors = { allowed_origins = ["https://dashboard.example.com"] }
+
+[qdrant]
+url = "http://qdrant:6334"
+timeout_secs = 10
+
+[ai_service]
+url = 'http://ai:5090'
+rate_limit_rps = 2_000.5
+embedding_model = "siglip-so400m"
+
+[scheduler]
+limit = 50
+
+[cameras]
+registry_path = "cameras.json"
+cctv02 = { name = "Back gate", enabled = false }
+
+[cameras.cctv01]
+name = "Main gate"
+location = """
+Rama IV Road,
+northbound"""
+lat = 13.72
+timezone = "Asia/Bangkok"
+"#,
+            false,
+        )
+        .unwrap();
+        assert_eq!(toml.get("COLLECTION_NAME").unwrap(), "nt-cctv-vehicles");
+        assert_eq!(toml.get("TENANTS").unwrap(), "site-a,site-b");
+        assert_eq!(
+            toml.get("CORS_ALLOWED_ORIGINS").unwrap(),
+            "https://dashboard.example.com"
+        );
+        assert_eq!(toml.get("QDRANT_URL").unwrap(), "http://qdrant:6334");
+        assert_eq!(toml.get("QDRANT_TIMEOUT_SECS").unwrap(), "10");
+        assert_eq!(toml.get("AI_SERVICE_URL").unwrap(), "http://ai:5090");
+        assert_eq!(toml.get("AI_RATE_LIMIT_RPS").unwrap(), "2000.5");
+        assert_eq!(toml.get("EMBEDDING_MODEL").unwrap(), "siglip-so400m");
+        assert_eq!(toml.get("CAMERA_REGISTRY_PATH").unwrap(), "cameras.json");
+        assert_eq!(toml.get("CCTV_API_URL"), None);
+        assert_eq!(toml.unused(), ["FETCH_LIMIT"]);
+
+        let cameras = toml.cameras();
+        assert_eq!(cameras.len(), 2);
+        assert_eq!(cameras[0].cctv_id, "cctv01");
+        assert_eq!(
+            cameras[0].location.as_deref(),
+            Some("Rama IV Road,\nnorthbound")
+        );
+        assert_eq!(cameras[0].lat, Some(13.72));
+        assert!(cameras[0].enabled);
+        assert_eq!(cameras[1].name.as_deref(), Some("Back gate"));
+        assert!(!cameras[1].enabled);
+
+        assert!(ConfigFile::parse("[qdrant]\nurl = 1", false).is_err());
+        assert!(ConfigFile::parse("[qdrant]\nurl = \"a\"\nurl = \"b\"", false).is_err());
+        assert!(ConfigFile::parse("[qdrant]\nulr = \"http://qdrant:6334\"", false).is_err());
+        assert!(ConfigFile::parse("colection_name = \"x\"", false).is_err());
+        assert!(ConfigFile::parse("[cameras.cctv01]\nnmae = \"Gate\"", false).is_err());
+        assert!(ConfigFile::parse("url = \"http://qdrant", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_yaml() {
+        let yaml = ConfigFile::parse(
+            "collection_name: nt-cctv-vehicles\n\
+             tenants: [site-a, 'site-b']\n\
+             qdrant: {url: \"http://qdrant:6334\", timeout_secs: 10}  # grpc\n\
+             scheduler:\n  limit: 50\n  every_time: 5\n\
+             cors:\n  allowed_methods:\n    - GET\n    - POST\n\
+             cameras:\n  registry_path: cameras.json\n  cctv01: \
+               {name: Main gate, tenant_id: site-a}\n",
+            true,
+        )
+        .unwrap();
+        assert_eq!(yaml.get("COLLECTION_NAME").unwrap(), "nt-cctv-vehicles");
+        assert_eq!(yaml.get("TENANTS").unwrap(), "site-a,site-b");
+        assert_eq!(yaml.get("QDRANT_URL").unwrap(), "http://qdrant:6334");
+        assert_eq!(yaml.get("QDRANT_TIMEOUT_SECS").unwrap(), "10");
+        assert_eq!(yaml.get("FETCH_EVERY_TIME").unwrap(), "5");
+        assert_eq!(yaml.get("CORS_ALLOWED_METHODS").unwrap(), "GET,POST");
+        assert_eq!(yaml.get("CAMERA_REGISTRY_PATH").unwrap(), "cameras.json");
+        assert_eq!(yaml.cameras()[0].tenant_id.as_deref(), Some("site-a"));
+
+        assert!(ConfigFile::parse("", true).unwrap().vars.is_empty());
+        assert!(ConfigFile::parse("qdrant:\n  tls:\n    ca: x", true).is_err());
+        assert!(ConfigFile::parse("scheduler: {limit: fifty}", true).is_err());
+    }
+}
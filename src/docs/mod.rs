@@ -1,26 +1,328 @@
-use crate::models::search::{AiLabel, CctvImageData, SearchRequest, SearchResult};
-use utoipa::OpenApi;
+use crate::config::Tunables;
+use crate::error::{ErrorResponse, FieldError};
+use crate::models::admin::{
+    BackfillRequest, BackfillState, BackfillStatus, BackupVerificationStatus, CollectionStats,
+    DeadLetter, DeleteImagesResponse, FlushResponse, IntegrityMismatch, IntegrityStatus,
+    IntegrityVerifyRequest, MaintenanceRequest, MaintenanceStatus, PayloadIndexInfo,
+    RebalanceStatus, RetagRequest, RetagResponse, ShardStatus, SnapshotCheck, SnapshotInfo,
+    SnapshotRestoreRequest, SnapshotRestoreResponse, VectorConfigInfo,
+};
+use crate::models::alert::{Alert, AlertHit, AlertNotification, CreateAlertRequest};
+use crate::models::case::{
+    AttachCaseItemRequest, Case, CaseItem, CaseItemKind, CaseSummary, CreateCaseRequest,
+};
+use crate::models::cctv::{VehicleTypeLabel, VehicleTypeMapping};
+use crate::models::embed::{EmbedImageRequest, EmbedTextRequest, EmbeddingResponse};
+use crate::models::evaluation::{
+    CreateEvalSetRequest, EvalQueryResult, EvalRunReport, EvalRunRequest, EvalRunSummary, EvalSet,
+    EvalSetSummary, LatencySummary,
+};
+use crate::models::export::{ImagePage, StoredImage};
+use crate::models::ingest::{EdgeIngestAck, EdgeIngestBatch, IngestAckStatus, SequenceRange};
+use crate::models::scheduler::{SchedulerRunsResponse, TriggerFetchRequest, TriggerFetchResponse};
+use crate::models::search::{
+    AiLabel, AlphaTrial, BatchInsertFailure, BatchInsertResponse, CameraGroup, CctvImageData,
+    CorrelatedMatch, CorrelatedSearchRequest, HybridCalibrationRequest, HybridCalibrationResponse,
+    LabeledQuery, RecommendRequest, ResultDetail, ScoreBreakdown, SearchByImageRequest,
+    SearchDebugResponse, SearchParamTrial, SearchParamsRequest, SearchRequest, SearchResult,
+    VectorSpace,
+};
+use crate::models::session::{SessionFeedback, SessionState};
+use crate::models::system::{
+    DependencyHealth, EmbeddingModelInfo, HealthResponse, ServiceStatus, VersionInfo,
+};
+use crate::services::{
+    AiDispatchStatus, AiRateLimitStatus, CircuitState, CircuitStatus, Dependency, ImageFailure,
+    RunStatus, RunTrigger, SchedulerRun,
+};
+use utoipa::openapi::security::{
+    ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme,
+};
+use utoipa::{Modify, OpenApi};
 
 // Re-export SwaggerUi for use in main.rs
+#[cfg(feature = "swagger-ui")]
 pub use utoipa_swagger_ui::SwaggerUi;
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
         crate::handlers::search_vehicles,
+        crate::handlers::search_by_image,
+        crate::handlers::recommend,
+        crate::handlers::search_correlated,
+        crate::handlers::create_session,
+        crate::handlers::get_session,
+        crate::handlers::add_session_feedback,
+        crate::handlers::delete_session,
+        crate::handlers::create_case,
+        crate::handlers::list_cases,
+        crate::handlers::get_case,
+        crate::handlers::attach_case_item,
+        crate::handlers::create_alert,
+        crate::handlers::list_alerts,
+        crate::handlers::get_alert,
+        crate::handlers::delete_alert,
         crate::handlers::insert_image,
+        crate::handlers::insert_images,
+        crate::handlers::ingest_batch,
+        crate::handlers::live_ingest,
+        crate::handlers::live_events,
+        crate::handlers::list_images,
+        crate::handlers::vehicle_types,
+        crate::handlers::reload_config,
+        crate::handlers::set_maintenance,
+        crate::handlers::delete_images,
+        crate::handlers::retag_images,
+        crate::handlers::calibrate_hybrid,
+        crate::handlers::create_eval_set,
+        crate::handlers::list_eval_sets,
+        crate::handlers::get_eval_set,
+        crate::handlers::delete_eval_set,
+        crate::handlers::run_eval_set,
+        crate::handlers::flush_writes,
+        crate::handlers::collection_info,
+        crate::handlers::shard_status,
+        crate::handlers::rebalance_shards,
+        crate::handlers::verify_integrity,
+        crate::handlers::integrity_status,
+        crate::handlers::dead_letters,
+        crate::handlers::create_snapshots,
+        crate::handlers::snapshot_list,
+        crate::handlers::restore_snapshot,
+        crate::handlers::backup_verification,
+        crate::handlers::start_backfill,
+        crate::handlers::backfill_status,
+        crate::handlers::pause_backfill,
+        crate::handlers::resume_backfill,
+        crate::handlers::healthz,
+        crate::handlers::readyz,
+        crate::handlers::service_status,
+        crate::handlers::scheduler_runs,
+        crate::handlers::scheduler_run,
+        crate::handlers::trigger_fetch,
+        crate::handlers::embed_text,
+        crate::handlers::embed_image,
+        crate::handlers::version,
+        crate::handlers::metrics,
     ),
     components(
         schemas(
             SearchRequest,
+            VectorSpace,
+            SearchByImageRequest,
+            RecommendRequest,
+            CorrelatedSearchRequest,
+            CorrelatedMatch,
+            SearchParamsRequest,
             SearchResult,
+            ResultDetail,
+            ScoreBreakdown,
+            CameraGroup,
+            SearchDebugResponse,
+            SearchParamTrial,
+            HybridCalibrationRequest,
+            LabeledQuery,
+            HybridCalibrationResponse,
+            AlphaTrial,
+            CreateEvalSetRequest,
+            EvalSet,
+            EvalSetSummary,
+            EvalRunRequest,
+            EvalRunSummary,
+            EvalRunReport,
+            EvalQueryResult,
+            LatencySummary,
+            SessionFeedback,
+            SessionState,
+            CreateCaseRequest,
+            AttachCaseItemRequest,
+            CaseItemKind,
+            CaseItem,
+            Case,
+            CaseSummary,
+            CreateAlertRequest,
+            Alert,
+            AlertHit,
+            AlertNotification,
             CctvImageData,
-            AiLabel
+            BatchInsertResponse,
+            BatchInsertFailure,
+            EdgeIngestBatch,
+            EdgeIngestAck,
+            IngestAckStatus,
+            SequenceRange,
+            AiLabel,
+            StoredImage,
+            ImagePage,
+            VehicleTypeMapping,
+            VehicleTypeLabel,
+            Tunables,
+            MaintenanceRequest,
+            MaintenanceStatus,
+            DeleteImagesResponse,
+            RetagRequest,
+            RetagResponse,
+            FlushResponse,
+            CollectionStats,
+            VectorConfigInfo,
+            PayloadIndexInfo,
+            ShardStatus,
+            RebalanceStatus,
+            IntegrityVerifyRequest,
+            IntegrityStatus,
+            IntegrityMismatch,
+            DeadLetter,
+            SnapshotInfo,
+            SnapshotRestoreRequest,
+            SnapshotRestoreResponse,
+            SnapshotCheck,
+            BackupVerificationStatus,
+            BackfillRequest,
+            BackfillStatus,
+            BackfillState,
+            VersionInfo,
+            EmbeddingModelInfo,
+            HealthResponse,
+            DependencyHealth,
+            ServiceStatus,
+            CircuitStatus,
+            AiRateLimitStatus,
+            AiDispatchStatus,
+            CircuitState,
+            Dependency,
+            SchedulerRunsResponse,
+            SchedulerRun,
+            ImageFailure,
+            RunStatus,
+            RunTrigger,
+            TriggerFetchRequest,
+            TriggerFetchResponse,
+            EmbedTextRequest,
+            EmbedImageRequest,
+            EmbeddingResponse,
+            ErrorResponse,
+            FieldError
         )
     ),
+    modifiers(&ApiKeyAuth),
     tags(
-        (name = "Search API", description = "Vehicle search endpoints"),
-        (name = "Insertion API", description = "Image insertion endpoints")
+        (name = "Search API", description = "Vehicle search endpoints / ค้นหารถจากข้อความหรือภาพ"),
+        (name = "Insertion API", description = "Image insertion endpoints / นำเข้าภาพจากกล้อง"),
+        (name = "Cases API", description = "Investigator cases / แฟ้มคดีของผู้สืบสวน"),
+        (name = "Alerts API", description = "Saved search alerts / แจ้งเตือนเมื่อพบภาพตรงกับคำค้นที่บันทึกไว้"),
+        (name = "Admin API", description = "Operational endpoints / งานดูแลระบบ"),
+        (name = "System API", description = "Service metadata endpoints / สถานะและข้อมูลบริการ"),
+        (name = "Tools API", description = "Embedding passthrough for offline tools / สร้าง embedding สำหรับเครื่องมือออฟไลน์")
     )
 )]
 pub struct ApiDoc;
+
+/// `X-API-Key` header and JWT bearer token, each required on every endpoint
+/// when configured
+struct ApiKeyAuth;
+
+impl Modify for ApiKeyAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "X-API-Key",
+                "Required with API_KEYS or API_KEYS_FILE set, except on /healthz and /readyz",
+            ))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some(
+                        "Required with JWT_SECRET or JWT_JWKS_URL set, except on /healthz and /readyz. \
+                         The `roles` claim grants `reader` (search, sessions, cases, exports), `writer` \
+                         (also inserts) or `admin` (also admin, scheduler trigger and deletion)",
+                    ))
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "device_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "X-Device-Key",
+                "Key of the edge device named in X-Device-Id, from EDGE_DEVICE_KEYS; only on \
+                 /ingest/batch, which takes no API key or bearer token",
+            ))),
+        );
+        openapi.security = Some(vec![
+            SecurityRequirement::new("api_key", Vec::<String>::new()),
+            SecurityRequirement::new("bearer", Vec::<String>::new()),
+        ]);
+    }
+}
+
+/// Dev-only endpoints, documented only in builds that include them
+#[cfg(feature = "chaos")]
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        crate::handlers::get_chaos,
+        crate::handlers::set_chaos_settings,
+        crate::handlers::clear_chaos,
+    ),
+    components(schemas(
+        crate::models::chaos::ChaosRequest,
+        crate::models::chaos::ChaosSettings,
+        crate::models::chaos::ChaosStatus,
+        crate::services::ChaosTarget
+    )),
+    tags((name = "Dev API", description = "Fault injection for testing / จำลองความผิดพลาดเพื่อทดสอบ"))
+)]
+pub struct ChaosApiDoc;
+
+/// OpenAPI document for every endpoint compiled into this build
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    #[allow(unused_mut)]
+    let mut doc = ApiDoc::openapi();
+    #[cfg(feature = "chaos")]
+    doc.merge(ChaosApiDoc::openapi());
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utoipa::openapi::RefOr;
+
+    #[test]
+    fn test_error_responses_have_a_json_body() {
+        let doc = openapi();
+        for (path, item) in &doc.paths.paths {
+            for operation in item.operations.values() {
+                for (status, response) in &operation.responses.responses {
+                    let RefOr::T(response) = response else {
+                        continue;
+                    };
+                    if status.as_str() >= "400" {
+                        assert!(
+                            response.content.contains_key("application/json"),
+                            "{} {} has no JSON ErrorResponse body",
+                            path,
+                            status
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn test_client_covers_every_endpoint() {
+        let doc = ApiDoc::openapi();
+        let documented: Vec<&str> = doc.paths.paths.keys().map(String::as_str).collect();
+        let mut covered = rust_cctv::client::ENDPOINTS.to_vec();
+        covered.sort_unstable();
+        assert_eq!(documented, covered);
+    }
+}
@@ -0,0 +1,316 @@
+//! Edge Ingest Agent
+//!
+//! Ingest-only loop for roadside gateways, built with
+//! `--no-default-features --features edge-ingest`: every fetch interval it
+//! reads new images from the CCTV source, embeds them with the local AI
+//! service and forwards them in batches to the central API, which stores
+//! the embeddings without calling its own AI service. With a device ID and
+//! key set, batches go to `POST /ingest/batch` with sequence numbers, so the
+//! central API acknowledges each one and reports those that never arrived.
+
+use crate::clients::cctv_client::CctvApi;
+use crate::config::{Config, technical};
+use crate::error::AppError;
+use crate::models::search::{CctvImageData, CctvMetadataRequest};
+use crate::scheduler::{FetchWindow, WindowAlign, parse_window_align};
+use crate::services::cctv_service::CctvService;
+use crate::services::{
+    AiPriority, CameraRegistry, RequestMetrics, get_image_embedding, validate_embedding,
+};
+use rust_cctv::client::{
+    self, BatchInsertResponse, CctvSearchClient, ClientError, EdgeIngestBatch, IngestAckStatus,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
+
+/// CCTV source, embedder and central API of the agent
+struct EdgeAgent {
+    config: Config,
+    http_client: reqwest::Client,
+    cctv_service: CctvService<CctvApi>,
+    central: CctvSearchClient,
+    metrics: RequestMetrics,
+    /// Start time of the agent (Unix seconds), the epoch of its batches
+    epoch: u64,
+    /// Sequence of the last batch sent to `/ingest/batch`
+    sequence: AtomicU64,
+}
+
+/// Run the agent until SIGINT or SIGTERM; a fetch in flight is finished first
+pub async fn run(config: Config) -> Result<(), AppError> {
+    let forward_url = config.edge_forward_url.clone().ok_or_else(|| {
+        AppError::Config("EDGE_FORWARD_URL is required by the edge ingest agent".to_string())
+    })?;
+    let http_client = reqwest::Client::new();
+    let mut central = CctvSearchClient::with_client(&forward_url, http_client.clone());
+    if let Some(api_key) = &config.edge_forward_api_key {
+        central = central.with_api_key(api_key);
+    }
+    let cctv_service = CctvService::new(CctvApi::new(
+        config.cctv_api_url.clone(),
+        config.cctv_authorize_code.clone(),
+        config.cctv_user_auth.clone(),
+        config.cctv_client_id.clone(),
+    ));
+    info!(
+        forward_url = %forward_url,
+        ai_service = %config.ai_service_url,
+        every_minutes = config.fetch_every_time,
+        device_id = ?config.edge_device_id,
+        "Edge ingest agent started"
+    );
+    let epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let agent = EdgeAgent {
+        config,
+        http_client,
+        cctv_service,
+        central,
+        metrics: RequestMetrics::default(),
+        epoch,
+        sequence: AtomicU64::new(0),
+    };
+
+    let every = Duration::from_secs(agent.config.fetch_every_time as u64 * 60);
+    let mut ticks = tokio::time::interval(every);
+    let shutdown = crate::shutdown_signal();
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = ticks.tick() => {}
+        }
+        agent.forward_window().await;
+    }
+    info!("Edge ingest agent stopped");
+    Ok(())
+}
+
+impl EdgeAgent {
+    /// Fetch, embed and forward the images of the last fetch window
+    async fn forward_window(&self) {
+        let align =
+            parse_window_align(&self.config.fetch_window_align).unwrap_or(WindowAlign::None);
+        let window =
+            FetchWindow::aligned(self.config.fetch_every_time, align, self.config.fetch_limit);
+        let images = self.fetch_images(&window).await;
+        info!(
+            date_start = %window.date_start,
+            date_stop = %window.date_stop,
+            images = images.len(),
+            "Fetched edge window"
+        );
+
+        let (mut inserted, mut failed) = (0, 0);
+        for batch in images.chunks(technical::EMBEDDING_BATCH_SIZE) {
+            match self.forward_batch(batch).await {
+                Ok(response) => {
+                    for failure in &response.failed {
+                        warn!(id = failure.id, error = %failure.error, "Central API rejected image");
+                    }
+                    inserted += response.inserted.len();
+                    failed += batch.len() - response.inserted.len();
+                }
+                Err(e) => {
+                    error!(images = batch.len(), error = %e, "Failed to forward batch");
+                    failed += batch.len();
+                }
+            }
+        }
+        info!(inserted, failed, "Edge window forwarded");
+    }
+
+    /// Up to `window.limit` images of every enabled camera in `window`
+    async fn fetch_images(&self, window: &FetchWindow) -> Vec<CctvImageData> {
+        let cctv_ids = match self.cctv_service.list_cctv().await {
+            Ok(ids) => ids,
+            Err(e) => {
+                error!(error = %e, "Failed to get CCTV list");
+                return Vec::new();
+            }
+        };
+        let registry =
+            CameraRegistry::load(&self.config.camera_registry_path, &self.config.cameras)
+                .inspect_err(|e| warn!(error = %e, "Camera registry unavailable"))
+                .ok();
+
+        let mut images = Vec::new();
+        for cctv_id in cctv_ids {
+            if registry.as_ref().is_some_and(|r| !r.is_enabled(&cctv_id)) {
+                continue;
+            }
+            let request = CctvMetadataRequest {
+                cctv_id: cctv_id.clone(),
+                date_start: window.date_start.clone(),
+                date_stop: window.date_stop.clone(),
+                limit: window.limit,
+            };
+            match self.cctv_service.fetch_train_data(&request).await {
+                Ok(fetched) => images.extend(fetched),
+                Err(e) => error!(cctv_id = %cctv_id, error = %e, "Failed to fetch training data"),
+            }
+        }
+        images
+    }
+
+    /// Embed `images` and send those with a valid embedding to the central API
+    ///
+    /// The upload is retried with a doubling delay, under the same sequence
+    /// number when sent as a device; images the AI service can't embed are
+    /// logged and left out.
+    async fn forward_batch(
+        &self,
+        images: &[CctvImageData],
+    ) -> Result<BatchInsertResponse, AppError> {
+        let paths = images.iter().map(|i| i.file_path.clone()).collect();
+        let embedded = get_image_embedding(
+            &self.http_client,
+            &self.config.ai_service_url,
+            paths,
+            AiPriority::Bulk,
+        )
+        .await?;
+        if embedded.results.len() != images.len() {
+            return Err(AppError::AiService(format!(
+                "AI service returned {} results for {} images",
+                embedded.results.len(),
+                images.len()
+            )));
+        }
+
+        let mut batch = Vec::with_capacity(images.len());
+        for (image, result) in images.iter().zip(embedded.results) {
+            let Some(vector) = result.embedding else {
+                let error = result
+                    .error
+                    .unwrap_or_else(|| "No embedding returned".to_string());
+                warn!(filename = %image.filename, error = %error, "Image not embedded");
+                continue;
+            };
+            let vector_size = self.config.vector_size;
+            if validate_embedding(&vector, vector_size, "edge", &image.filename, &self.metrics)
+                .is_ok()
+            {
+                batch.push(forwarded_image(image, vector));
+            }
+        }
+        if batch.is_empty() {
+            return Ok(BatchInsertResponse {
+                inserted: Vec::new(),
+                failed: Vec::new(),
+                verification_mismatches: None,
+            });
+        }
+
+        let batch = EdgeIngestBatch {
+            epoch: self.epoch,
+            sequence: self.sequence.fetch_add(1, Ordering::Relaxed) + 1,
+            points: batch,
+        };
+        let mut delay = Duration::from_secs(technical::EDGE_FORWARD_RETRY_DELAY_SECS);
+        let mut attempt = 1;
+        loop {
+            match self.upload(&batch).await {
+                Ok(response) => return Ok(response),
+                Err(e) if attempt < technical::EDGE_FORWARD_ATTEMPTS => {
+                    warn!(attempt, error = %e, "Forwarding batch failed, retrying");
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    return Err(AppError::Io(format!(
+                        "Central API refused the batch: {}",
+                        e
+                    )));
+                }
+            }
+        }
+    }
+
+    /// Send `batch` to `/ingest/batch` as the configured device, or to
+    /// `/insert_images` without one
+    async fn upload(&self, batch: &EdgeIngestBatch) -> Result<BatchInsertResponse, ClientError> {
+        let (Some(device_id), Some(device_key)) =
+            (&self.config.edge_device_id, &self.config.edge_device_key)
+        else {
+            return self.central.insert_images(&batch.points, None).await;
+        };
+        let ack = self
+            .central
+            .ingest_batch(device_id, device_key, batch)
+            .await?;
+        if let Some(oldest) = ack.missing.first() {
+            warn!(
+                sequence = ack.sequence,
+                missing_from = oldest.from,
+                gaps = ack.missing.len(),
+                "Central API is missing earlier batches"
+            );
+        }
+        // A duplicate was stored by an attempt whose acknowledgement got lost
+        let inserted = match ack.status {
+            IngestAckStatus::Accepted => ack.inserted,
+            IngestAckStatus::Duplicate => batch.points.iter().map(|p| p.id as u64).collect(),
+        };
+        Ok(BatchInsertResponse {
+            inserted,
+            failed: ack.failed,
+            verification_mismatches: None,
+        })
+    }
+}
+
+/// Insert request of `image` carrying its `embedding`
+fn forwarded_image(image: &CctvImageData, embedding: Vec<f32>) -> client::CctvImageData {
+    client::CctvImageData {
+        id: image.id,
+        cctv_id: image.cctv_id.clone(),
+        date: image.date.clone(),
+        time: image.time.clone(),
+        frame: image.frame,
+        vehicle_type: image.vehicle_type,
+        yolo_id: image.yolo_id,
+        filename: image.filename.clone(),
+        file_path: image.file_path.clone(),
+        ai_label: image.ai_label.as_ref().map(|label| client::AiLabel {
+            class_name: label.class_name.clone(),
+            confidence: label.confidence,
+        }),
+        caption: image.caption.clone(),
+        created_at: image.created_at.clone(),
+        embedding: Some(embedding),
+        extra: image.extra.clone().into_iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forwarded_image() {
+        let image: CctvImageData = serde_json::from_value(serde_json::json!({
+            "id": 12345,
+            "cctv_id": "cctv01",
+            "date": "2025-10-08",
+            "time": "06:32:00",
+            "frame": 123,
+            "vehicle_type": 2,
+            "yolo_id": 5,
+            "filename": "cctv01_2025-10-08_06-32_123.jpg",
+            "file_path": "/data/images/cctv01_2025-10-08_06-32_123.jpg",
+            "ai_label": { "class_name": "pickup", "confidence": 0.5 },
+            "lane": 2
+        }))
+        .unwrap();
+        let body = serde_json::to_value(forwarded_image(&image, vec![0.5, 0.25])).unwrap();
+        assert_eq!(body["embedding"], serde_json::json!([0.5, 0.25]));
+        assert_eq!(body["ai_label"]["class_name"], "pickup");
+        assert_eq!(body["lane"], 2);
+        assert!(body.get("caption").is_none());
+    }
+}
@@ -0,0 +1,319 @@
+//! Application Errors
+//!
+//! Error type shared by services, handlers and background jobs. The variant
+//! records which dependency or input failed and decides the HTTP status.
+
+#[cfg(feature = "server")]
+use actix_web::http::StatusCode;
+#[cfg(feature = "server")]
+use actix_web::http::header::{RETRY_AFTER, WWW_AUTHENTICATE};
+#[cfg(feature = "server")]
+use actix_web::{HttpResponse, ResponseError};
+use serde::Serialize;
+use std::fmt;
+use utoipa::ToSchema;
+
+/// One invalid field of a request body
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FieldError {
+    /// Name of the field, e.g. `top_k`
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Body of every error response
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "code": "validation_failed",
+    "message": "Request validation failed",
+    "details": { "fields": [{ "field": "top_k", "message": "must be between 1 and 1000" }] }
+}))]
+pub struct ErrorResponse {
+    /// Stable error code to match on, e.g. `validation_failed` or `unavailable`
+    pub code: String,
+    pub message: String,
+    /// `fields` of `validation_failed`, `retry_after_secs` of `unavailable`
+    /// and `rate_limited`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<serde_json::Value>,
+}
+
+impl From<&AppError> for ErrorResponse {
+    fn from(e: &AppError) -> Self {
+        let details = match e {
+            AppError::Validation(errors) => Some(serde_json::json!({ "fields": errors })),
+            AppError::Unavailable {
+                retry_after_secs, ..
+            }
+            | AppError::RateLimited {
+                retry_after_secs, ..
+            } => Some(serde_json::json!({ "retry_after_secs": retry_after_secs })),
+            _ => None,
+        };
+        Self {
+            code: e.code().to_string(),
+            message: e.to_string(),
+            details,
+        }
+    }
+}
+
+/// Categorized application error carrying a human-readable message
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    /// The AI embedding service failed or returned an unusable response
+    AiService(String),
+    /// A Qdrant request failed
+    Qdrant(String),
+    /// The CCTV metadata API failed
+    CctvApi(String),
+    /// A client-supplied value (date, consistency, image, ...) could not be parsed
+    Parse(String),
+    /// The request is well-formed but not acceptable
+    InvalidRequest(String),
+    /// Fields of the request body failed validation
+    Validation(Vec<FieldError>),
+    /// The request was processed but produced data that cannot be stored,
+    /// e.g. an embedding of the wrong dimension
+    Unprocessable(String),
+    /// The referenced resource (session, image, ...) does not exist
+    NotFound(String),
+    /// No valid credentials were presented
+    Unauthorized(String),
+    /// The credentials don't grant the role the endpoint requires
+    Forbidden(String),
+    /// Invalid or unreadable configuration
+    Config(String),
+    /// Local file system failure
+    Io(String),
+    /// A dependency is temporarily refused (circuit open); retry after the delay
+    Unavailable {
+        message: String,
+        retry_after_secs: u64,
+    },
+    /// The client sent too many requests; retry after the delay
+    RateLimited {
+        message: String,
+        retry_after_secs: u64,
+    },
+}
+
+impl AppError {
+    /// Message without the category
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::AiService(m)
+            | AppError::Qdrant(m)
+            | AppError::CctvApi(m)
+            | AppError::Parse(m)
+            | AppError::InvalidRequest(m)
+            | AppError::Unprocessable(m)
+            | AppError::NotFound(m)
+            | AppError::Unauthorized(m)
+            | AppError::Forbidden(m)
+            | AppError::Config(m)
+            | AppError::Io(m)
+            | AppError::Unavailable { message: m, .. }
+            | AppError::RateLimited { message: m, .. } => m,
+            AppError::Validation(_) => "Request validation failed",
+        }
+    }
+
+    /// Stable code of the error category, sent in `ErrorResponse`
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::AiService(_) => "ai_service_error",
+            AppError::Qdrant(_) => "qdrant_error",
+            AppError::CctvApi(_) => "cctv_api_error",
+            AppError::Parse(_) => "parse_error",
+            AppError::InvalidRequest(_) => "invalid_request",
+            AppError::Validation(_) => "validation_failed",
+            AppError::Unprocessable(_) => "unprocessable",
+            AppError::NotFound(_) => "not_found",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::Config(_) => "config_error",
+            AppError::Io(_) => "io_error",
+            AppError::Unavailable { .. } => "unavailable",
+            AppError::RateLimited { .. } => "rate_limited",
+        }
+    }
+
+    /// Whether the error means a dependency misbehaved, as opposed to a bad
+    /// request or a fast rejection
+    pub fn is_dependency_failure(&self) -> bool {
+        matches!(
+            self,
+            AppError::AiService(_) | AppError::Qdrant(_) | AppError::CctvApi(_)
+        )
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())?;
+        if let AppError::Validation(errors) = self {
+            for (i, error) in errors.iter().enumerate() {
+                let separator = if i == 0 { ": " } else { "; " };
+                write!(f, "{}{} {}", separator, error.field, error.message)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+#[cfg(feature = "server")]
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::Parse(_) | AppError::InvalidRequest(_) | AppError::Validation(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::Unprocessable(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::AiService(_) | AppError::Qdrant(_) | AppError::CctvApi(_) => {
+                StatusCode::BAD_GATEWAY
+            }
+            AppError::Config(_) | AppError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Unavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status_code());
+        if let AppError::Unavailable {
+            retry_after_secs, ..
+        }
+        | AppError::RateLimited {
+            retry_after_secs, ..
+        } = self
+        {
+            response.insert_header((RETRY_AFTER, retry_after_secs.to_string()));
+        }
+        if let AppError::Unauthorized(_) = self {
+            response.insert_header((WWW_AUTHENTICATE, "Bearer"));
+        }
+        response.json(ErrorResponse::from(self))
+    }
+}
+
+/// gRPC status of the error, mirroring `ResponseError::status_code`
+#[cfg(feature = "grpc")]
+impl From<AppError> for tonic::Status {
+    fn from(e: AppError) -> Self {
+        let code = match &e {
+            AppError::Parse(_) | AppError::InvalidRequest(_) | AppError::Validation(_) => {
+                tonic::Code::InvalidArgument
+            }
+            AppError::NotFound(_) => tonic::Code::NotFound,
+            AppError::Unauthorized(_) => tonic::Code::Unauthenticated,
+            AppError::Forbidden(_) => tonic::Code::PermissionDenied,
+            AppError::Unprocessable(_) => tonic::Code::FailedPrecondition,
+            AppError::AiService(_)
+            | AppError::Qdrant(_)
+            | AppError::CctvApi(_)
+            | AppError::Unavailable { .. } => tonic::Code::Unavailable,
+            AppError::Config(_) | AppError::Io(_) => tonic::Code::Internal,
+            AppError::RateLimited { .. } => tonic::Code::ResourceExhausted,
+        };
+        let mut status = tonic::Status::new(code, e.to_string());
+        if let AppError::Unavailable {
+            retry_after_secs, ..
+        }
+        | AppError::RateLimited {
+            retry_after_secs, ..
+        } = e
+        {
+            status
+                .metadata_mut()
+                .insert("retry-after", retry_after_secs.into());
+        }
+        status
+    }
+}
+
+impl From<qdrant_client::QdrantError> for AppError {
+    fn from(e: qdrant_client::QdrantError) -> Self {
+        AppError::Qdrant(format!("Qdrant error: {}", e))
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Io(e.to_string())
+    }
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_mapping() {
+        let status = |e: AppError| e.status_code();
+        assert_eq!(
+            status(AppError::Parse("bad date".into())),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(AppError::AiService("down".into())),
+            StatusCode::BAD_GATEWAY
+        );
+        assert_eq!(
+            status(AppError::Unprocessable("wrong dimension".into())),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            status(AppError::Forbidden("admin only".into())),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(AppError::Config("missing".into())),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(AppError::Qdrant("timeout".into()).to_string(), "timeout");
+
+        let invalid = AppError::Validation(vec![
+            FieldError::new("query", "must not be empty"),
+            FieldError::new("top_k", "must be between 1 and 1000"),
+        ]);
+        assert_eq!(status(invalid.clone()), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            invalid.to_string(),
+            "Request validation failed: query must not be empty; top_k must be between 1 and 1000"
+        );
+
+        let response = AppError::Unavailable {
+            message: "open".into(),
+            retry_after_secs: 12,
+        }
+        .error_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers().get(RETRY_AFTER).unwrap(), "12");
+
+        let body = serde_json::to_value(ErrorResponse::from(&invalid)).unwrap();
+        assert_eq!(body["code"], "validation_failed");
+        assert_eq!(body["details"]["fields"][1]["field"], "top_k");
+        let body =
+            serde_json::to_value(ErrorResponse::from(&AppError::NotFound("gone".into()))).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"code": "not_found", "message": "gone"})
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Feature Registry
+//!
+//! Single place that decides which optional subsystems are active. A feature
+//! is enabled when it is compiled in (Cargo feature) and not switched off at
+//! runtime via `DISABLED_FEATURES`.
+
+use crate::error::AppError;
+
+/// Optional subsystems wired at startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    /// Background CCTV fetch scheduler
+    Scheduler,
+    /// `/admin/*` endpoints, `DELETE /images` and `POST /scheduler/trigger`
+    AdminApi,
+    /// Swagger UI at `/swagger-ui/` (Cargo feature `swagger-ui`)
+    SwaggerUi,
+    /// Dev-only `/dev/chaos` fault injection endpoints (Cargo feature `chaos`)
+    Chaos,
+    /// Fail fast with 503 while a dependency keeps failing
+    CircuitBreaker,
+    /// `/embed/*` embedding passthrough for offline tools
+    Tools,
+    /// gRPC API on `GRPC_PORT` (Cargo feature `grpc`)
+    Grpc,
+}
+
+impl Feature {
+    /// All known features
+    pub const ALL: &'static [Feature] = &[
+        Feature::Scheduler,
+        Feature::AdminApi,
+        Feature::SwaggerUi,
+        Feature::Chaos,
+        Feature::CircuitBreaker,
+        Feature::Tools,
+        Feature::Grpc,
+    ];
+
+    /// Name used in `DISABLED_FEATURES` and `/version`
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::Scheduler => "scheduler",
+            Feature::AdminApi => "admin-api",
+            Feature::SwaggerUi => "swagger-ui",
+            Feature::Chaos => "chaos",
+            Feature::CircuitBreaker => "circuit-breaker",
+            Feature::Tools => "tools",
+            Feature::Grpc => "grpc",
+        }
+    }
+
+    /// Whether the feature is part of this build
+    pub fn compiled(self) -> bool {
+        match self {
+            Feature::Scheduler | Feature::AdminApi | Feature::CircuitBreaker | Feature::Tools => {
+                true
+            }
+            Feature::SwaggerUi => cfg!(feature = "swagger-ui"),
+            Feature::Chaos => cfg!(feature = "chaos"),
+            Feature::Grpc => cfg!(feature = "grpc"),
+        }
+    }
+}
+
+/// Resolved set of enabled features for this process
+#[derive(Debug, Clone)]
+pub struct FeatureRegistry {
+    enabled: Vec<Feature>,
+}
+
+impl FeatureRegistry {
+    /// Resolve features from the build and the runtime disable list
+    ///
+    /// Unknown names in `disabled` are reported as an error so typos don't
+    /// silently leave a feature on.
+    pub fn new(disabled: &[String]) -> Result<Self, AppError> {
+        if let Some(unknown) = disabled
+            .iter()
+            .find(|name| !Feature::ALL.iter().any(|f| f.name() == name.as_str()))
+        {
+            return Err(AppError::Config(format!(
+                "Unknown feature in DISABLED_FEATURES: {}",
+                unknown
+            )));
+        }
+
+        let enabled = Feature::ALL
+            .iter()
+            .copied()
+            .filter(|f| f.compiled() && !disabled.iter().any(|name| name == f.name()))
+            .collect();
+
+        Ok(Self { enabled })
+    }
+
+    #[inline]
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        self.enabled.contains(&feature)
+    }
+
+    /// Names of all enabled features
+    pub fn enabled_names(&self) -> Vec<String> {
+        self.enabled.iter().map(|f| f.name().to_string()).collect()
+    }
+
+    /// Log which features are active
+    pub fn log_summary(&self) {
+        for feature in Feature::ALL {
+            let status = if self.is_enabled(*feature) {
+                "on"
+            } else if feature.compiled() {
+                "off (runtime)"
+            } else {
+                "off (not compiled)"
+            };
+            tracing::info!(feature = feature.name(), status, "Feature");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_disable() {
+        let registry = FeatureRegistry::new(&["scheduler".to_string()]).unwrap();
+        assert!(!registry.is_enabled(Feature::Scheduler));
+        assert!(registry.is_enabled(Feature::AdminApi));
+        assert_eq!(
+            registry.is_enabled(Feature::SwaggerUi),
+            Feature::SwaggerUi.compiled()
+        );
+    }
+
+    #[test]
+    fn test_unknown_feature_is_rejected() {
+        assert!(FeatureRegistry::new(&["kafka".to_string()]).is_err());
+    }
+}
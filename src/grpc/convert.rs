@@ -0,0 +1,131 @@
+//! Message Conversions
+//!
+//! Mapping between the protobuf messages and the JSON models the handlers
+//! share. Enum fields are strings with the JSON spellings; an empty string
+//! is the JSON default.
+
+use super::messages as pb;
+use crate::error::AppError;
+use crate::models::search::{
+    AiLabel, BatchInsertResponse, CctvImageData, SearchRequest, SearchResult,
+};
+use serde::de::DeserializeOwned;
+
+/// JSON enum value `value` of `field`, or its default when empty
+fn parse_enum<T: DeserializeOwned + Default>(field: &str, value: &str) -> Result<T, AppError> {
+    if value.is_empty() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| AppError::Parse(format!("Invalid {} '{}'", field, value)))
+}
+
+impl TryFrom<pb::SearchRequest> for SearchRequest {
+    type Error = AppError;
+
+    fn try_from(request: pb::SearchRequest) -> Result<Self, AppError> {
+        Ok(SearchRequest {
+            query: request.query,
+            top_k: request.top_k,
+            min_score: request.min_score,
+            start_date: request.start_date,
+            end_date: request.end_date,
+            last_hours: request.last_hours,
+            last_days: request.last_days,
+            last: request.last,
+            camera_ids: Some(request.camera_ids).filter(|ids| !ids.is_empty()),
+            vehicle_classes: Some(request.vehicle_classes).filter(|c| !c.is_empty()),
+            min_confidence: request.min_confidence,
+            detail: parse_enum("detail", &request.detail)?,
+            fanout_chunks: request.fanout_chunks,
+            recency_half_life_hours: request.recency_half_life_hours,
+            session_id: request.session_id,
+            collapse_window_s: request.collapse_window_s,
+            arithmetic: request.arithmetic,
+            hybrid: request.hybrid,
+            hybrid_fusion: request.hybrid_fusion,
+            hybrid_alpha: request.hybrid_alpha,
+            vector_space: parse_enum("vector_space", &request.vector_space)?,
+            ..Default::default()
+        })
+    }
+}
+
+impl From<SearchResult> for pb::SearchResult {
+    fn from(result: SearchResult) -> Self {
+        pb::SearchResult {
+            filename: result.filename,
+            id: result.id,
+            score: result.score,
+            datetime: result.datetime,
+            camera_id: result.camera_id,
+            camera_name: result.camera_name,
+            location: result.location,
+            lat: result.lat,
+            lon: result.lon,
+            file_path: result.file_path,
+            frame: result.frame,
+            vehicle_class: result.vehicle_class,
+            confidence: result.confidence,
+            vehicle_type_label: result.vehicle_type_label,
+            yolo_label: result.yolo_label,
+            caption: result.caption,
+            payload_json: result
+                .payload
+                .map(|payload| serde_json::Value::Object(payload).to_string()),
+            score_breakdown: result.score_breakdown.map(|b| pb::ScoreBreakdown {
+                base_score: b.base_score,
+                recency_factor: b.recency_factor,
+            }),
+        }
+    }
+}
+
+impl TryFrom<pb::CctvImage> for CctvImageData {
+    type Error = AppError;
+
+    fn try_from(image: pb::CctvImage) -> Result<Self, AppError> {
+        let extra = match image.extra_json.as_deref() {
+            Some(json) => serde_json::from_str(json)
+                .map_err(|e| AppError::Parse(format!("extra_json of image {}: {}", image.id, e)))?,
+            None => Default::default(),
+        };
+        Ok(CctvImageData {
+            id: image.id,
+            cctv_id: image.cctv_id,
+            date: image.date,
+            time: image.time,
+            frame: image.frame,
+            vehicle_type: image.vehicle_type,
+            yolo_id: image.yolo_id,
+            filename: image.filename,
+            file_path: image.file_path,
+            ai_label: image.ai_label.map(|label| AiLabel {
+                class_name: label.class_name,
+                confidence: label.confidence,
+            }),
+            caption: image.caption,
+            created_at: image.created_at,
+            embedding: Some(image.embedding).filter(|e| !e.is_empty()),
+            extra,
+        })
+    }
+}
+
+impl From<BatchInsertResponse> for pb::BatchInsertResponse {
+    fn from(response: BatchInsertResponse) -> Self {
+        pb::BatchInsertResponse {
+            inserted: response.inserted,
+            failed: response
+                .failed
+                .into_iter()
+                .map(|f| pb::BatchInsertFailure {
+                    id: f.id,
+                    error: f.error,
+                })
+                .collect(),
+            verified: response.verification_mismatches.is_some(),
+            verification_mismatches: response.verification_mismatches.unwrap_or_default(),
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! gRPC Messages
+//!
+//! Prost messages of `proto/cctv_search.proto`. The build has no `protoc`,
+//! so they are written out here; a test checks every message against the
+//! fields declared in the proto.
+
+/// `cctv.v1.SearchRequest`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SearchRequest {
+    #[prost(string, tag = "1")]
+    pub query: String,
+    #[prost(uint64, optional, tag = "2")]
+    pub top_k: Option<u64>,
+    #[prost(float, optional, tag = "3")]
+    pub min_score: Option<f32>,
+    #[prost(string, optional, tag = "4")]
+    pub start_date: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub end_date: Option<String>,
+    #[prost(uint64, optional, tag = "6")]
+    pub last_hours: Option<u64>,
+    #[prost(uint64, optional, tag = "7")]
+    pub last_days: Option<u64>,
+    #[prost(string, optional, tag = "8")]
+    pub last: Option<String>,
+    #[prost(string, repeated, tag = "9")]
+    pub camera_ids: Vec<String>,
+    #[prost(string, repeated, tag = "10")]
+    pub vehicle_classes: Vec<String>,
+    #[prost(float, optional, tag = "11")]
+    pub min_confidence: Option<f32>,
+    #[prost(string, tag = "12")]
+    pub detail: String,
+    #[prost(uint32, optional, tag = "13")]
+    pub fanout_chunks: Option<u32>,
+    #[prost(double, optional, tag = "14")]
+    pub recency_half_life_hours: Option<f64>,
+    #[prost(string, optional, tag = "15")]
+    pub session_id: Option<String>,
+    #[prost(uint64, optional, tag = "16")]
+    pub collapse_window_s: Option<u64>,
+    #[prost(bool, tag = "17")]
+    pub arithmetic: bool,
+    #[prost(bool, tag = "18")]
+    pub hybrid: bool,
+    #[prost(string, optional, tag = "19")]
+    pub hybrid_fusion: Option<String>,
+    #[prost(string, tag = "20")]
+    pub vector_space: String,
+    #[prost(float, optional, tag = "21")]
+    pub hybrid_alpha: Option<f32>,
+}
+
+/// `cctv.v1.SearchResponse`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SearchResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub results: Vec<SearchResult>,
+    #[prost(bool, tag = "2")]
+    pub degraded: bool,
+}
+
+/// `cctv.v1.SearchResult`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SearchResult {
+    #[prost(string, optional, tag = "1")]
+    pub filename: Option<String>,
+    #[prost(string, tag = "2")]
+    pub id: String,
+    #[prost(float, tag = "3")]
+    pub score: f32,
+    #[prost(string, tag = "4")]
+    pub datetime: String,
+    #[prost(string, tag = "5")]
+    pub camera_id: String,
+    #[prost(string, optional, tag = "6")]
+    pub camera_name: Option<String>,
+    #[prost(string, optional, tag = "7")]
+    pub location: Option<String>,
+    #[prost(double, optional, tag = "8")]
+    pub lat: Option<f64>,
+    #[prost(double, optional, tag = "9")]
+    pub lon: Option<f64>,
+    #[prost(string, tag = "10")]
+    pub file_path: String,
+    #[prost(int64, optional, tag = "11")]
+    pub frame: Option<i64>,
+    #[prost(string, optional, tag = "12")]
+    pub vehicle_class: Option<String>,
+    #[prost(float, optional, tag = "13")]
+    pub confidence: Option<f32>,
+    #[prost(string, optional, tag = "14")]
+    pub vehicle_type_label: Option<String>,
+    #[prost(string, optional, tag = "15")]
+    pub yolo_label: Option<String>,
+    #[prost(string, optional, tag = "16")]
+    pub caption: Option<String>,
+    #[prost(string, optional, tag = "17")]
+    pub payload_json: Option<String>,
+    #[prost(message, optional, tag = "18")]
+    pub score_breakdown: Option<ScoreBreakdown>,
+}
+
+/// `cctv.v1.ScoreBreakdown`
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct ScoreBreakdown {
+    #[prost(float, tag = "1")]
+    pub base_score: f32,
+    #[prost(float, tag = "2")]
+    pub recency_factor: f32,
+}
+
+/// `cctv.v1.AiLabel`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AiLabel {
+    #[prost(string, tag = "1")]
+    pub class_name: String,
+    #[prost(float, tag = "2")]
+    pub confidence: f32,
+}
+
+/// `cctv.v1.CctvImage`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CctvImage {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+    #[prost(string, tag = "2")]
+    pub cctv_id: String,
+    #[prost(string, tag = "3")]
+    pub date: String,
+    #[prost(string, tag = "4")]
+    pub time: String,
+    #[prost(uint32, tag = "5")]
+    pub frame: u32,
+    #[prost(uint32, tag = "6")]
+    pub vehicle_type: u32,
+    #[prost(uint32, tag = "7")]
+    pub yolo_id: u32,
+    #[prost(string, tag = "8")]
+    pub filename: String,
+    #[prost(string, tag = "9")]
+    pub file_path: String,
+    #[prost(message, optional, tag = "10")]
+    pub ai_label: Option<AiLabel>,
+    #[prost(string, optional, tag = "11")]
+    pub caption: Option<String>,
+    #[prost(string, optional, tag = "12")]
+    pub created_at: Option<String>,
+    #[prost(float, repeated, tag = "13")]
+    pub embedding: Vec<f32>,
+    #[prost(string, optional, tag = "14")]
+    pub extra_json: Option<String>,
+}
+
+/// `cctv.v1.InsertImageRequest`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct InsertImageRequest {
+    #[prost(message, optional, tag = "1")]
+    pub image: Option<CctvImage>,
+    #[prost(bool, optional, tag = "2")]
+    pub verify: Option<bool>,
+}
+
+/// `cctv.v1.InsertImageResponse`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct InsertImageResponse {
+    #[prost(uint64, tag = "1")]
+    pub point_id: u64,
+    #[prost(float, repeated, tag = "2")]
+    pub embedding: Vec<f32>,
+    #[prost(bool, tag = "3")]
+    pub verified: bool,
+    #[prost(string, repeated, tag = "4")]
+    pub verification_mismatches: Vec<String>,
+}
+
+/// `cctv.v1.BatchInsertRequest`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BatchInsertRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub images: Vec<CctvImage>,
+    #[prost(bool, optional, tag = "2")]
+    pub verify: Option<bool>,
+}
+
+/// `cctv.v1.BatchInsertFailure`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BatchInsertFailure {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(string, tag = "2")]
+    pub error: String,
+}
+
+/// `cctv.v1.BatchInsertResponse`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BatchInsertResponse {
+    #[prost(uint64, repeated, tag = "1")]
+    pub inserted: Vec<u64>,
+    #[prost(message, repeated, tag = "2")]
+    pub failed: Vec<BatchInsertFailure>,
+    #[prost(bool, tag = "3")]
+    pub verified: bool,
+    #[prost(string, repeated, tag = "4")]
+    pub verification_mismatches: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::encoding::{self, WireType};
+    use std::collections::BTreeMap;
+    use std::fmt::Debug;
+
+    const PROTO: &str = include_str!("../../proto/cctv_search.proto");
+
+    /// A field declaration: label (`optional`, `repeated` or empty), type,
+    /// name and tag
+    struct ProtoField {
+        label: &'static str,
+        kind: &'static str,
+        name: &'static str,
+        tag: u32,
+    }
+
+    /// Fields of each message declared in the proto
+    fn proto_messages() -> BTreeMap<&'static str, Vec<ProtoField>> {
+        let mut messages = BTreeMap::new();
+        let mut current = None;
+        for line in PROTO.lines() {
+            let line = line.split("//").next().unwrap().trim();
+            if let Some(name) = line.strip_prefix("message ") {
+                current = Some(name.trim_end_matches('{').trim());
+                continue;
+            }
+            if line == "}" {
+                current = None;
+            }
+            let (Some(message), Some(declaration)) = (current, line.strip_suffix(';')) else {
+                continue;
+            };
+            let words: Vec<&str> = declaration.split_whitespace().collect();
+            let (label, words) = match words[0] {
+                "optional" | "repeated" => (words[0], &words[1..]),
+                _ => ("", &words[..]),
+            };
+            let [kind, name, "=", tag] = words else {
+                panic!("Unexpected field in {}: {}", message, line);
+            };
+            let field = ProtoField {
+                label,
+                kind,
+                name,
+                tag: tag.parse().unwrap(),
+            };
+            messages.entry(message).or_insert_with(Vec::new).push(field);
+        }
+        messages
+    }
+
+    /// `field` set to a value other than its default, encoded as the proto
+    /// declares it
+    fn encode_field(field: &ProtoField, buf: &mut Vec<u8>) {
+        let tag = field.tag;
+        let packed = field.label == "repeated";
+        match field.kind {
+            "string" => encoding::string::encode(tag, &"x".to_string(), buf),
+            "bool" => encoding::bool::encode(tag, &true, buf),
+            "uint32" if packed => encoding::uint32::encode_packed(tag, &[7], buf),
+            "uint32" => encoding::uint32::encode(tag, &7, buf),
+            "uint64" if packed => encoding::uint64::encode_packed(tag, &[7], buf),
+            "uint64" => encoding::uint64::encode(tag, &7, buf),
+            "int64" => encoding::int64::encode(tag, &-7, buf),
+            "float" if packed => encoding::float::encode_packed(tag, &[0.5], buf),
+            "float" => encoding::float::encode(tag, &0.5, buf),
+            "double" => encoding::double::encode(tag, &0.5, buf),
+            // An empty nested message
+            _ => {
+                encoding::encode_key(tag, WireType::LengthDelimited, buf);
+                encoding::encode_varint(0, buf);
+            }
+        }
+    }
+
+    /// `M` has exactly the fields of `name` in the proto, with its tags and
+    /// wire types: each field survives a decode and encode, which would drop
+    /// a tag `M` doesn't know
+    fn check<M: prost::Message + Default + Debug>(
+        messages: &mut BTreeMap<&str, Vec<ProtoField>>,
+        name: &str,
+    ) {
+        let fields = messages
+            .remove(name)
+            .unwrap_or_else(|| panic!("{} is not in the proto", name));
+        // The derived Debug lists every field; defaults hold no nested braces
+        let debug = format!("{:?}", M::default());
+        let declared: Vec<&str> = debug
+            .trim_start_matches(name)
+            .trim_matches([' ', '{', '}'])
+            .split(", ")
+            .map(|field| field.split(':').next().unwrap())
+            .collect();
+        let expected: Vec<&str> = fields.iter().map(|field| field.name).collect();
+        assert_eq!(declared, expected, "fields of {}", name);
+
+        for field in &fields {
+            let mut encoded = Vec::new();
+            encode_field(field, &mut encoded);
+            let message = M::decode(encoded.as_slice()).unwrap();
+            assert_eq!(
+                message.encode_to_vec(),
+                encoded,
+                "{}.{} = {}",
+                name,
+                field.name,
+                field.tag
+            );
+        }
+    }
+
+    #[test]
+    fn test_messages_match_proto() {
+        let mut messages = proto_messages();
+        check::<SearchRequest>(&mut messages, "SearchRequest");
+        check::<SearchResponse>(&mut messages, "SearchResponse");
+        check::<SearchResult>(&mut messages, "SearchResult");
+        check::<ScoreBreakdown>(&mut messages, "ScoreBreakdown");
+        check::<AiLabel>(&mut messages, "AiLabel");
+        check::<CctvImage>(&mut messages, "CctvImage");
+        check::<InsertImageRequest>(&mut messages, "InsertImageRequest");
+        check::<InsertImageResponse>(&mut messages, "InsertImageResponse");
+        check::<BatchInsertRequest>(&mut messages, "BatchInsertRequest");
+        check::<BatchInsertFailure>(&mut messages, "BatchInsertFailure");
+        check::<BatchInsertResponse>(&mut messages, "BatchInsertResponse");
+        let unchecked: Vec<&str> = messages.into_keys().collect();
+        assert!(unchecked.is_empty(), "no struct for {:?}", unchecked);
+    }
+}
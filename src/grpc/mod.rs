@@ -0,0 +1,378 @@
+//! gRPC API
+//!
+//! The `cctv.v1.CctvSearch` service of `proto/cctv_search.proto`, served on
+//! `GRPC_PORT` next to the REST API. Each RPC runs the same code as its
+//! REST endpoint and goes through the same API key, JWT role, tenant,
+//! maintenance and per-client rate limit checks, read from the request
+//! metadata.
+
+mod convert;
+mod messages;
+
+pub use messages::*;
+
+use crate::error::AppError;
+use crate::handlers::{AppState, SearchOutcome, insert_batch, insert_one, run_search, validated};
+use crate::middleware::{Role, check_role, client_key, rate_limited};
+use crate::models::search::{CctvImageData, InsertOptions};
+use crate::services::{bound_tenant, resolve_tenant};
+use actix_web::ResponseError;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::codegen::{Body, BoxFuture, Service, StdError, http};
+use tonic::metadata::MetadataMap;
+use tonic::server::{Grpc, NamedService};
+
+/// Fully qualified name of the service
+pub const SERVICE_NAME: &str = "cctv.v1.CctvSearch";
+
+/// `cctv.v1.CctvSearch` over the shared application state
+#[derive(Clone)]
+pub struct CctvSearchService {
+    state: Arc<AppState>,
+}
+
+impl CctvSearchService {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+
+    /// Decode `req`, run `rpc` for the caller's tenant once it holds `role`
+    /// and is within the rate limit of the REST endpoint `rest_path`, and
+    /// encode its result; the call is recorded in `/metrics` and the SLOs
+    /// under its path
+    fn unary<B, Req, Res, F, Fut>(
+        &self,
+        req: http::Request<B>,
+        rest_path: &'static str,
+        role: Role,
+        rpc: F,
+    ) -> BoxFuture<http::Response<BoxBody>, Infallible>
+    where
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+        Req: prost::Message + Default + Send + 'static,
+        Res: prost::Message + Send + 'static,
+        F: FnOnce(Arc<AppState>, Req, Option<String>) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = Result<Res, AppError>> + Send + 'static,
+    {
+        let state = self.state.clone();
+        let path = req.uri().path().to_string();
+        let handler = Rpc(move |request: tonic::Request<Req>| {
+            let (state, path, rpc) = (state.clone(), path.clone(), rpc.clone());
+            async move {
+                let started = Instant::now();
+                let peer = request.remote_addr().map(|addr| addr.ip());
+                let admitted = authorize(&state, request.metadata(), role).and_then(|tenant| {
+                    rate_limit(&state, rest_path, request.metadata(), peer)?;
+                    Ok(tenant)
+                });
+                let result = match admitted {
+                    Ok(tenant) => rpc(state.clone(), request.into_inner(), tenant).await,
+                    Err(e) => Err(e),
+                };
+                let elapsed_ms = started.elapsed().as_millis() as u64;
+                let status = result
+                    .as_ref()
+                    .map_or_else(|e| e.status_code(), |_| actix_web::http::StatusCode::OK);
+                state.metrics.record(&path, status.as_u16(), elapsed_ms);
+                state
+                    .slo
+                    .record(&path, elapsed_ms, !status.is_server_error());
+                result
+                    .map(tonic::Response::new)
+                    .map_err(tonic::Status::from)
+            }
+        });
+        Box::pin(async move { Ok(Grpc::new(ProstCodec::default()).unary(handler, req).await) })
+    }
+}
+
+impl<B> Service<http::Request<B>> for CctvSearchService
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        match req.uri().path() {
+            "/cctv.v1.CctvSearch/Search" => self.unary(req, "/search", Role::Reader, search),
+            "/cctv.v1.CctvSearch/InsertImage" => {
+                self.unary(req, "/insert_image", Role::Writer, insert_image)
+            }
+            "/cctv.v1.CctvSearch/BatchInsert" => {
+                self.unary(req, "/insert_images", Role::Writer, batch_insert)
+            }
+            path => {
+                let status = tonic::Status::unimplemented(format!("No RPC {}", path));
+                Box::pin(async move { Ok(status.into_http()) })
+            }
+        }
+    }
+}
+
+impl NamedService for CctvSearchService {
+    const NAME: &'static str = SERVICE_NAME;
+}
+
+/// Unary handler from a closure
+#[derive(Clone)]
+struct Rpc<F>(F);
+
+impl<Req, Res, F, Fut> Service<tonic::Request<Req>> for Rpc<F>
+where
+    F: FnMut(tonic::Request<Req>) -> Fut,
+    Fut: Future<Output = Result<tonic::Response<Res>, tonic::Status>>,
+{
+    type Response = tonic::Response<Res>;
+    type Error = tonic::Status;
+    type Future = Fut;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), tonic::Status>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: tonic::Request<Req>) -> Fut {
+        (self.0)(request)
+    }
+}
+
+/// Apply the REST guards to a call: `x-api-key` while API keys are
+/// configured, an `authorization: Bearer` token granting `role` while JWT
+/// authentication is enabled, and maintenance mode; returns the tenant the
+/// call is confined to, from the token, the API key or the `x-tenant-id`
+/// metadata
+fn authorize(
+    state: &AppState,
+    metadata: &MetadataMap,
+    role: Role,
+) -> Result<Option<String>, AppError> {
+    if state.api_keys.is_enabled() {
+        let key = metadata.get("x-api-key");
+        if !key.is_some_and(|key| state.api_keys.accepts(key.as_bytes())) {
+            return Err(AppError::Unauthorized(
+                "Missing or invalid x-api-key metadata".to_string(),
+            ));
+        }
+    }
+    let claims = if state.jwt.is_enabled() {
+        let token = metadata
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| {
+                AppError::Unauthorized("Missing authorization: Bearer metadata".to_string())
+            })?;
+        let claims = state.jwt.verify(token.trim())?;
+        check_role(claims.role(), role)?;
+        Some(claims)
+    } else {
+        None
+    };
+    let key_tenant = metadata
+        .get("x-api-key")
+        .and_then(|key| state.api_keys.tenant_of(key.as_bytes()));
+    let bound = bound_tenant(
+        claims.as_ref().and_then(|claims| claims.tenant.as_deref()),
+        key_tenant,
+    )?;
+    // Without any authentication any caller may name its tenant
+    let header_trusted = match &claims {
+        Some(claims) => claims.role() == Some(Role::Admin),
+        None => !state.api_keys.is_enabled(),
+    };
+    let requested = metadata
+        .get("x-tenant-id")
+        .and_then(|value| value.to_str().ok());
+    let tenant = resolve_tenant(
+        &state.scheduler.config.tenants,
+        bound,
+        requested,
+        header_trusted,
+    )?;
+    if let Some(message) = state.maintenance.message() {
+        return Err(AppError::Unavailable {
+            message,
+            retry_after_secs: 120,
+        });
+    }
+    Ok(tenant)
+}
+
+/// Take a token of the caller when `CLIENT_RATE_LIMIT_PATHS` lists
+/// `rest_path`; a gRPC client shares its bucket with its REST calls
+fn rate_limit(
+    state: &AppState,
+    rest_path: &str,
+    metadata: &MetadataMap,
+    peer: Option<IpAddr>,
+) -> Result<(), AppError> {
+    let Some(limiter) = state
+        .client_rate_limiter
+        .as_ref()
+        .filter(|limiter| limiter.applies_to(rest_path))
+    else {
+        return Ok(());
+    };
+    let api_key = metadata.get("x-api-key").map(|key| key.as_bytes());
+    limiter
+        .try_acquire(&client_key(state, api_key, peer), Instant::now())
+        .map_err(rate_limited)
+}
+
+async fn search(
+    state: Arc<AppState>,
+    request: SearchRequest,
+    tenant: Option<String>,
+) -> Result<SearchResponse, AppError> {
+    let request = validated(request.try_into()?)?;
+    let (results, degraded) = match run_search(&state, &request, tenant.as_deref()).await? {
+        SearchOutcome::Results(results) => (results, false),
+        SearchOutcome::Browsed(results) => (results, true),
+        SearchOutcome::Groups(_) | SearchOutcome::Debug(_) => {
+            return Err(AppError::InvalidRequest(
+                "Grouped and debug searches are only served by POST /search".to_string(),
+            ));
+        }
+    };
+    Ok(SearchResponse {
+        results: results.into_iter().map(Into::into).collect(),
+        degraded,
+    })
+}
+
+async fn insert_image(
+    state: Arc<AppState>,
+    request: InsertImageRequest,
+    tenant: Option<String>,
+) -> Result<InsertImageResponse, AppError> {
+    let image: CctvImageData = request
+        .image
+        .ok_or_else(|| AppError::InvalidRequest("No image provided".to_string()))?
+        .try_into()?;
+    let options = InsertOptions {
+        verify: request.verify,
+    };
+    let inserted = insert_one(&state, &image, &options, tenant.as_deref()).await?;
+    Ok(InsertImageResponse {
+        point_id: inserted.point_id,
+        embedding: inserted.embedding,
+        verified: inserted.verification_mismatches.is_some(),
+        verification_mismatches: inserted.verification_mismatches.unwrap_or_default(),
+    })
+}
+
+async fn batch_insert(
+    state: Arc<AppState>,
+    request: BatchInsertRequest,
+    tenant: Option<String>,
+) -> Result<BatchInsertResponse, AppError> {
+    let images = request
+        .images
+        .into_iter()
+        .map(CctvImageData::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    let options = InsertOptions {
+        verify: request.verify,
+    };
+    Ok(insert_batch(&state, images, &options, tenant.as_deref())
+        .await?
+        .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::search::{ResultDetail, SearchRequest as JsonSearchRequest};
+
+    #[test]
+    fn test_conversions() {
+        let request = SearchRequest {
+            query: "white pickup".to_string(),
+            top_k: Some(5),
+            camera_ids: vec!["cctv01".to_string()],
+            detail: "full".to_string(),
+            ..Default::default()
+        };
+        let request = JsonSearchRequest::try_from(request).unwrap();
+        assert_eq!(request.detail, ResultDetail::Full);
+        assert_eq!(request.camera_ids, Some(vec!["cctv01".to_string()]));
+        assert_eq!(request.vehicle_classes, None);
+
+        let invalid = SearchRequest {
+            vector_space: "audio".to_string(),
+            ..Default::default()
+        };
+        assert!(JsonSearchRequest::try_from(invalid).is_err());
+
+        let image = CctvImage {
+            id: 12345,
+            cctv_id: "cctv01".to_string(),
+            extra_json: Some(r#"{"lane": 2}"#.to_string()),
+            ..Default::default()
+        };
+        let image = CctvImageData::try_from(image).unwrap();
+        assert_eq!(image.extra["lane"], 2);
+        assert_eq!(image.embedding, None);
+    }
+
+    #[actix_web::test]
+    async fn test_client_rate_limit() {
+        let state = AppState::for_tests(&[
+            ("CLIENT_RATE_LIMIT_RPS", "1"),
+            ("CLIENT_RATE_LIMIT_BURST", "1"),
+            ("CLIENT_RATE_LIMIT_PATHS", "/search"),
+        ])
+        .await;
+        let metadata = MetadataMap::new();
+        let peer = Some(IpAddr::from([10, 0, 0, 1]));
+
+        assert!(rate_limit(&state, "/search", &metadata, peer).is_ok());
+        let limited = rate_limit(&state, "/search", &metadata, peer).unwrap_err();
+        assert_eq!(
+            tonic::Status::from(limited).code(),
+            tonic::Code::ResourceExhausted
+        );
+
+        // Other clients and RPCs whose endpoint isn't limited still get through
+        let other = Some(IpAddr::from([10, 0, 0, 2]));
+        assert!(rate_limit(&state, "/search", &metadata, other).is_ok());
+        assert!(rate_limit(&state, "/insert_image", &metadata, peer).is_ok());
+    }
+
+    #[test]
+    fn test_status_codes() {
+        let code = |e: AppError| tonic::Status::from(e).code();
+        assert_eq!(
+            code(AppError::Parse("bad date".into())),
+            tonic::Code::InvalidArgument
+        );
+        assert_eq!(
+            code(AppError::Forbidden("writer only".into())),
+            tonic::Code::PermissionDenied
+        );
+        assert_eq!(
+            code(AppError::Qdrant("timeout".into())),
+            tonic::Code::Unavailable
+        );
+        let status = tonic::Status::from(AppError::RateLimited {
+            message: "slow down".into(),
+            retry_after_secs: 3,
+        });
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        assert_eq!(status.metadata().get("retry-after").unwrap(), "3");
+    }
+}
@@ -0,0 +1,210 @@
+//! Admin Handlers
+//!
+//! Operational endpoints for running deployments.
+
+use super::AppState;
+use super::etag::json_with_etag;
+use crate::config::technical;
+use crate::error::AppError;
+use crate::middleware::{Admin, Authorized, MAINTENANCE_PATH};
+use crate::models::admin::{
+    DeadLettersQuery, FlushResponse, MaintenanceRequest, MaintenanceStatus, ShardStatus,
+};
+use crate::services::{Dependency, collection_stats, flush_collection, guarded};
+use actix_web::{HttpRequest, HttpResponse, Responder, get, post, web};
+use tracing::info;
+
+/// Handler for reloading runtime tunables from the environment and `.env`
+#[utoipa::path(
+    post,
+    path = "/admin/reload",
+    responses(
+        (status = 200, description = "Tunables reloaded", body = Tunables),
+        (status = 500, description = "Configuration could not be loaded", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[post("/admin/reload")]
+pub async fn reload_config(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let tunables = state.tunables.reload()?;
+    info!("Configuration reloaded via /admin/reload");
+    Ok(HttpResponse::Ok().json(tunables))
+}
+
+/// Handler for switching maintenance mode on or off
+#[utoipa::path(
+    post,
+    path = "/admin/maintenance",
+    request_body = MaintenanceRequest,
+    responses(
+        (status = 200, description = "Maintenance mode updated", body = MaintenanceStatus)
+    ),
+    tag = "Admin API"
+)]
+#[post("/admin/maintenance")]
+pub async fn set_maintenance(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+    payload: web::Json<MaintenanceRequest>,
+) -> impl Responder {
+    let payload = payload.into_inner();
+    if payload.enabled {
+        state.maintenance.enable(payload.message);
+        info!("Maintenance mode enabled");
+    } else {
+        state.maintenance.disable();
+        info!("Maintenance mode disabled");
+    }
+
+    let message = state.maintenance.message();
+    let mut allowlist = vec![MAINTENANCE_PATH.to_string()];
+    allowlist.extend(state.maintenance_allowlist.iter().cloned());
+
+    HttpResponse::Ok().json(MaintenanceStatus {
+        enabled: message.is_some(),
+        message,
+        allowlist,
+    })
+}
+
+/// Handler waiting until every write accepted so far is applied
+#[utoipa::path(
+    post,
+    path = "/admin/flush",
+    responses(
+        (status = 200, description = "Pending writes applied", body = FlushResponse),
+        (status = 502, description = "Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[post("/admin/flush")]
+pub async fn flush_writes(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let started = std::time::Instant::now();
+    for collection in state.router.collections() {
+        guarded(
+            Dependency::Qdrant,
+            flush_collection(&state.qdrant, collection),
+        )
+        .await?;
+    }
+
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    info!(elapsed_ms, "Pending writes flushed");
+    Ok(HttpResponse::Ok().json(FlushResponse {
+        collections: state.router.collections().to_vec(),
+        elapsed_ms,
+    }))
+}
+
+/// Handler reporting point counts, indexes and optimizer state of every collection
+#[utoipa::path(
+    get,
+    path = "/admin/collection",
+    responses(
+        (status = 200, description = "Stats of each (shard) collection", body = [CollectionStats]),
+        (status = 502, description = "Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[get("/admin/collection")]
+pub async fn collection_info(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let mut stats = Vec::new();
+    for collection in state.router.collections() {
+        stats.push(
+            guarded(
+                Dependency::Qdrant,
+                collection_stats(&state.qdrant, collection),
+            )
+            .await?,
+        );
+    }
+    Ok(HttpResponse::Ok().json(stats))
+}
+
+fn shard_status_of(state: &AppState) -> ShardStatus {
+    ShardStatus {
+        collections: state.router.collections().to_vec(),
+        rebalance: state.rebalancer.status(),
+    }
+}
+
+/// Handler reporting the shard layout and rebalancing progress
+#[utoipa::path(
+    get,
+    path = "/admin/shards",
+    responses(
+        (status = 200, description = "Shard collections and rebalancing status", body = ShardStatus),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag")
+    ),
+    tag = "Admin API"
+)]
+#[get("/admin/shards")]
+pub async fn shard_status(
+    _: Authorized<Admin>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    json_with_etag(&req, &shard_status_of(&state))
+}
+
+/// Handler starting a background job that moves points to their shard
+#[utoipa::path(
+    post,
+    path = "/admin/shards/rebalance",
+    responses(
+        (status = 202, description = "Rebalancing started", body = ShardStatus),
+        (status = 409, description = "Rebalancing already running", body = ShardStatus)
+    ),
+    tag = "Admin API"
+)]
+#[post("/admin/shards/rebalance")]
+pub async fn rebalance_shards(_: Authorized<Admin>, state: web::Data<AppState>) -> impl Responder {
+    if !state.rebalancer.try_start() {
+        return HttpResponse::Conflict().json(shard_status_of(&state));
+    }
+
+    info!("Shard rebalancing started");
+    let qdrant = state.qdrant.clone();
+    let router = state.router.clone();
+    let rebalancer = state.rebalancer.clone();
+    tokio::spawn(async move { rebalancer.run(&qdrant, &router).await });
+
+    HttpResponse::Accepted().json(shard_status_of(&state))
+}
+
+/// Handler listing the newest images rejected for a defective embedding
+#[utoipa::path(
+    get,
+    path = "/admin/dead_letters",
+    params(DeadLettersQuery),
+    responses(
+        (status = 200, description = "Dead letters, oldest first", body = [DeadLetter]),
+        (status = 400, description = "Invalid limit", body = ErrorResponse),
+        (status = 500, description = "Failed to read the dead-letter file", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[get("/admin/dead_letters")]
+pub async fn dead_letters(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+    query: web::Query<DeadLettersQuery>,
+) -> Result<HttpResponse, AppError> {
+    let limit = query.limit.unwrap_or(technical::DEAD_LETTERS_LIMIT);
+    if !(1..=technical::MAX_DEAD_LETTERS_LIMIT).contains(&limit) {
+        return Err(AppError::InvalidRequest(format!(
+            "limit must be between 1 and {}",
+            technical::MAX_DEAD_LETTERS_LIMIT
+        )));
+    }
+    Ok(HttpResponse::Ok().json(state.scheduler.dead_letters.recent(limit)?))
+}
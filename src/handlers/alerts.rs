@@ -0,0 +1,158 @@
+//! Alert Handlers
+//!
+//! Registering saved searches that are run against every batch the
+//! scheduler stores, listing and removing them.
+
+use super::AppState;
+use super::validation::ValidatedJson;
+use crate::error::{AppError, FieldError};
+use crate::middleware::{Authorized, Reader, Tenant, Writer, token_subject};
+use crate::models::alert::CreateAlertRequest;
+use crate::services::{AiPriority, alert_target_error, validate_embedding};
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+use std::collections::BTreeMap;
+use tracing::info;
+
+/// Handler registering a saved search
+///
+/// The query is embedded once by every embedding model in use, so the
+/// alert can be searched in collections pinned to another model. The
+/// webhook may only target a private address if its host is in
+/// `WEBHOOK_ALLOWED_HOSTS`; the secret signing its deliveries is only
+/// returned here.
+#[utoipa::path(
+    post,
+    path = "/alerts",
+    request_body = CreateAlertRequest,
+    responses(
+        (status = 201, description = "Alert registered", body = Alert),
+        (status = 400, description = "Invalid name, query, filters or webhook URL", body = ErrorResponse),
+        (status = 422, description = "The AI service returned an embedding of the wrong dimension", body = ErrorResponse),
+        (status = 500, description = "Alerts file could not be written", body = ErrorResponse),
+        (status = 502, description = "AI service failure", body = ErrorResponse)
+    ),
+    tag = "Alerts API"
+)]
+#[post("/alerts")]
+pub async fn create_alert(
+    _: Authorized<Writer>,
+    tenant: Tenant,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    request: ValidatedJson<CreateAlertRequest>,
+) -> Result<HttpResponse, AppError> {
+    if let Some(url) = &request.webhook_url
+        && let Some(reason) = alert_target_error(url, &state.scheduler.config.webhook_allowed_hosts)
+    {
+        return Err(AppError::Validation(vec![FieldError::new(
+            "webhook_url",
+            reason,
+        )]));
+    }
+    let mut vectors = BTreeMap::new();
+    for model in state.scheduler.embedding_models.models() {
+        let (vector, _) = state
+            .embedding_cache
+            .text_embedding(
+                &state.http_client,
+                model,
+                &request.query,
+                AiPriority::Interactive,
+                &state.metrics,
+            )
+            .await?;
+        validate_embedding(
+            &vector,
+            model.vector_size,
+            "search",
+            &request.query,
+            &state.metrics,
+        )?;
+        vectors.insert(model.name.clone(), vector);
+    }
+
+    let alert =
+        state
+            .scheduler
+            .alerts
+            .create(&request, token_subject(&req), tenant.id(), vectors)?;
+    info!(
+        target: "audit",
+        action = "create_alert",
+        alert_id = %alert.alert_id,
+        webhook = alert.webhook_url.is_some(),
+        user = alert.created_by.as_deref().unwrap_or("anonymous"),
+        "Alert registered"
+    );
+    Ok(HttpResponse::Created().json(alert))
+}
+
+/// Handler listing every alert of the tenant, oldest first
+#[utoipa::path(
+    get,
+    path = "/alerts",
+    responses(
+        (status = 200, description = "Registered alerts", body = Vec<Alert>)
+    ),
+    tag = "Alerts API"
+)]
+#[get("/alerts")]
+pub async fn list_alerts(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    state: web::Data<AppState>,
+) -> HttpResponse {
+    HttpResponse::Ok().json(state.scheduler.alerts.list(tenant.id()))
+}
+
+/// Handler returning one alert
+#[utoipa::path(
+    get,
+    path = "/alerts/{alert_id}",
+    params(("alert_id" = String, Path, description = "Alert ID")),
+    responses(
+        (status = 200, description = "Alert", body = Alert),
+        (status = 404, description = "Unknown alert", body = ErrorResponse)
+    ),
+    tag = "Alerts API"
+)]
+#[get("/alerts/{alert_id}")]
+pub async fn get_alert(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    state: web::Data<AppState>,
+    alert_id: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    Ok(HttpResponse::Ok().json(state.scheduler.alerts.get(&alert_id, tenant.id())?))
+}
+
+/// Handler removing an alert
+#[utoipa::path(
+    delete,
+    path = "/alerts/{alert_id}",
+    params(("alert_id" = String, Path, description = "Alert ID")),
+    responses(
+        (status = 204, description = "Alert removed"),
+        (status = 404, description = "Unknown alert", body = ErrorResponse),
+        (status = 500, description = "Alerts file could not be written", body = ErrorResponse)
+    ),
+    tag = "Alerts API"
+)]
+#[delete("/alerts/{alert_id}")]
+pub async fn delete_alert(
+    _: Authorized<Writer>,
+    tenant: Tenant,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    alert_id: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    state.scheduler.alerts.delete(&alert_id, tenant.id())?;
+    info!(
+        target: "audit",
+        action = "delete_alert",
+        alert_id = %alert_id,
+        user = token_subject(&req).as_deref().unwrap_or("anonymous"),
+        "Alert removed"
+    );
+    Ok(HttpResponse::NoContent().finish())
+}
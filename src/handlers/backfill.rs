@@ -0,0 +1,119 @@
+//! Backfill Handlers
+//!
+//! Start, inspect, pause and resume the historical backfill job.
+
+use super::AppState;
+use crate::backfill::new_job;
+use crate::error::AppError;
+use crate::middleware::{Admin, Authorized};
+use crate::models::admin::BackfillRequest;
+use actix_web::{HttpResponse, get, post, web};
+use tracing::{Instrument, info, info_span};
+
+/// Handler starting a backfill of a historical date range
+#[utoipa::path(
+    post,
+    path = "/admin/backfill",
+    request_body = BackfillRequest,
+    responses(
+        (status = 202, description = "Backfill started in the background", body = BackfillStatus),
+        (status = 400, description = "Invalid date range, chunk size or limit", body = ErrorResponse),
+        (status = 409, description = "A backfill is already running", body = BackfillStatus)
+    ),
+    tag = "Admin API"
+)]
+#[post("/admin/backfill")]
+pub async fn start_backfill(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+    request: web::Json<BackfillRequest>,
+) -> Result<HttpResponse, AppError> {
+    let job = new_job(&request)?;
+    if !state.backfill.try_start(job.clone())? {
+        return Ok(HttpResponse::Conflict().json(state.backfill.status()));
+    }
+
+    info!(
+        target: "audit",
+        action = "start_backfill",
+        backfill_id = %job.id,
+        cctv_id = job.cctv_id.as_deref().unwrap_or("all"),
+        start_date = %job.start_date,
+        end_date = %job.end_date,
+        chunks = job.chunks_total,
+        "Backfill started"
+    );
+
+    spawn_worker(&state);
+    Ok(HttpResponse::Accepted().json(job))
+}
+
+/// Handler returning the progress of the current or last backfill
+#[utoipa::path(
+    get,
+    path = "/admin/backfill",
+    responses(
+        (status = 200, description = "Backfill progress", body = BackfillStatus),
+        (status = 404, description = "No backfill has been started", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[get("/admin/backfill")]
+pub async fn backfill_status(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let status = state
+        .backfill
+        .status()
+        .ok_or_else(|| AppError::NotFound("No backfill has been started".to_string()))?;
+    Ok(HttpResponse::Ok().json(status))
+}
+
+/// Handler pausing the running backfill, cancelling the chunk in flight
+#[utoipa::path(
+    post,
+    path = "/admin/backfill/pause",
+    responses(
+        (status = 202, description = "Pause requested", body = BackfillStatus),
+        (status = 409, description = "No backfill is running", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[post("/admin/backfill/pause")]
+pub async fn pause_backfill(_: Authorized<Admin>, state: web::Data<AppState>) -> HttpResponse {
+    if !state.backfill.request_pause() {
+        return HttpResponse::Conflict().json(state.backfill.status());
+    }
+    info!(target: "audit", action = "pause_backfill", "Backfill pause requested");
+    HttpResponse::Accepted().json(state.backfill.status())
+}
+
+/// Handler resuming a paused or failed backfill from its cursor
+#[utoipa::path(
+    post,
+    path = "/admin/backfill/resume",
+    responses(
+        (status = 202, description = "Backfill resumed", body = BackfillStatus),
+        (status = 409, description = "No paused or failed backfill", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[post("/admin/backfill/resume")]
+pub async fn resume_backfill(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    if !state.backfill.try_resume()? {
+        return Ok(HttpResponse::Conflict().json(state.backfill.status()));
+    }
+    info!(target: "audit", action = "resume_backfill", "Backfill resumed");
+    spawn_worker(&state);
+    Ok(HttpResponse::Accepted().json(state.backfill.status()))
+}
+
+fn spawn_worker(state: &AppState) {
+    let backfill = state.backfill.clone();
+    let ctx = state.scheduler.clone();
+    tokio::spawn(async move { backfill.run(&ctx).await }.instrument(info_span!("backfill")));
+}
@@ -0,0 +1,126 @@
+//! Case Handlers
+//!
+//! Opening investigator cases and attaching searches, bookmarks, incidents
+//! and exports to them, attributed to the caller's token subject.
+
+use super::AppState;
+use super::etag::json_with_etag;
+use super::validation::ValidatedJson;
+use crate::error::AppError;
+use crate::middleware::{Authorized, Reader, Tenant, token_subject};
+use crate::models::case::{AttachCaseItemRequest, CreateCaseRequest};
+use actix_web::{HttpRequest, HttpResponse, get, post, web};
+use tracing::info;
+
+/// Handler opening a case
+#[utoipa::path(
+    post,
+    path = "/cases",
+    request_body = CreateCaseRequest,
+    responses(
+        (status = 201, description = "Case opened", body = Case),
+        (status = 400, description = "Empty title", body = ErrorResponse),
+        (status = 500, description = "Cases file could not be written", body = ErrorResponse)
+    ),
+    tag = "Cases API"
+)]
+#[post("/cases")]
+pub async fn create_case(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    request: ValidatedJson<CreateCaseRequest>,
+) -> Result<HttpResponse, AppError> {
+    let case = state
+        .cases
+        .create(&request, token_subject(&req), tenant.id())?;
+    info!(
+        target: "audit",
+        action = "create_case",
+        case_id = %case.case_id,
+        user = case.created_by.as_deref().unwrap_or("anonymous"),
+        "Case opened"
+    );
+    Ok(HttpResponse::Created().json(case))
+}
+
+/// Handler listing every case of the tenant, most recently updated first
+#[utoipa::path(
+    get,
+    path = "/cases",
+    responses(
+        (status = 200, description = "Cases without their items", body = Vec<CaseSummary>)
+    ),
+    tag = "Cases API"
+)]
+#[get("/cases")]
+pub async fn list_cases(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    state: web::Data<AppState>,
+) -> HttpResponse {
+    HttpResponse::Ok().json(state.cases.list(tenant.id()))
+}
+
+/// Handler returning a case with everything attached to it
+#[utoipa::path(
+    get,
+    path = "/cases/{case_id}",
+    params(("case_id" = String, Path, description = "Case ID")),
+    responses(
+        (status = 200, description = "Case and its items, oldest first", body = Case),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        (status = 404, description = "Unknown case", body = ErrorResponse)
+    ),
+    tag = "Cases API"
+)]
+#[get("/cases/{case_id}")]
+pub async fn get_case(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    case_id: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let case = state.cases.get(&case_id, tenant.id())?;
+    Ok(json_with_etag(&req, &case))
+}
+
+/// Handler attaching a search, bookmark, incident or export to a case
+#[utoipa::path(
+    post,
+    path = "/cases/{case_id}/items",
+    params(("case_id" = String, Path, description = "Case ID")),
+    request_body = AttachCaseItemRequest,
+    responses(
+        (status = 201, description = "Item attached", body = CaseItem),
+        (status = 400, description = "Empty reference or unknown kind", body = ErrorResponse),
+        (status = 404, description = "Unknown case", body = ErrorResponse),
+        (status = 500, description = "Cases file could not be written", body = ErrorResponse)
+    ),
+    tag = "Cases API"
+)]
+#[post("/cases/{case_id}/items")]
+pub async fn attach_case_item(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    case_id: web::Path<String>,
+    request: ValidatedJson<AttachCaseItemRequest>,
+) -> Result<HttpResponse, AppError> {
+    let item = state
+        .cases
+        .attach(&case_id, &request, token_subject(&req), tenant.id())?;
+    info!(
+        target: "audit",
+        action = "attach_case_item",
+        case_id = %case_id,
+        item_id = item.item_id,
+        kind = ?item.kind,
+        user = item.added_by.as_deref().unwrap_or("anonymous"),
+        "Item attached to case"
+    );
+    Ok(HttpResponse::Created().json(item))
+}
@@ -0,0 +1,80 @@
+//! Chaos Handlers
+//!
+//! Dev-only endpoints to inject latency and failures into dependency calls.
+
+use crate::error::AppError;
+use crate::middleware::{Admin, Authorized};
+use crate::models::chaos::{ChaosRequest, ChaosSettings, ChaosStatus};
+use crate::services::{ChaosTarget, chaos_settings, set_chaos};
+use actix_web::{HttpResponse, Responder, delete, get, post, web};
+use tracing::warn;
+
+fn chaos_status() -> ChaosStatus {
+    ChaosStatus {
+        ai_service: chaos_settings(ChaosTarget::AiService),
+        qdrant: chaos_settings(ChaosTarget::Qdrant),
+    }
+}
+
+/// Handler returning the chaos currently configured
+#[utoipa::path(
+    get,
+    path = "/dev/chaos",
+    responses(
+        (status = 200, description = "Current chaos settings", body = ChaosStatus)
+    ),
+    tag = "Dev API"
+)]
+#[get("/dev/chaos")]
+pub async fn get_chaos(_: Authorized<Admin>) -> impl Responder {
+    HttpResponse::Ok().json(chaos_status())
+}
+
+/// Handler configuring latency/failure injection for one dependency
+#[utoipa::path(
+    post,
+    path = "/dev/chaos",
+    request_body = ChaosRequest,
+    responses(
+        (status = 200, description = "Chaos settings updated", body = ChaosStatus),
+        (status = 400, description = "Invalid failure rate", body = ErrorResponse)
+    ),
+    tag = "Dev API"
+)]
+#[post("/dev/chaos")]
+pub async fn set_chaos_settings(
+    _: Authorized<Admin>,
+    payload: web::Json<ChaosRequest>,
+) -> Result<HttpResponse, AppError> {
+    if !(0.0..=1.0).contains(&payload.settings.failure_rate) {
+        return Err(AppError::InvalidRequest(
+            "failure_rate must be between 0.0 and 1.0".to_string(),
+        ));
+    }
+
+    set_chaos(payload.target, payload.settings);
+    warn!(
+        target = ?payload.target,
+        latency_ms = payload.settings.latency_ms,
+        failure_rate = payload.settings.failure_rate,
+        "Chaos injection configured"
+    );
+    Ok(HttpResponse::Ok().json(chaos_status()))
+}
+
+/// Handler removing all injected chaos
+#[utoipa::path(
+    delete,
+    path = "/dev/chaos",
+    responses(
+        (status = 200, description = "Chaos cleared", body = ChaosStatus)
+    ),
+    tag = "Dev API"
+)]
+#[delete("/dev/chaos")]
+pub async fn clear_chaos(_: Authorized<Admin>) -> impl Responder {
+    set_chaos(ChaosTarget::AiService, ChaosSettings::NONE);
+    set_chaos(ChaosTarget::Qdrant, ChaosSettings::NONE);
+    warn!("Chaos injection cleared");
+    HttpResponse::Ok().json(chaos_status())
+}
@@ -0,0 +1,104 @@
+//! Deletion Handlers
+//!
+//! Purging stored images by camera and datetime range.
+
+use super::AppState;
+use crate::error::AppError;
+use crate::middleware::{Admin, Authorized};
+use crate::models::admin::{DeleteImagesQuery, DeleteImagesResponse};
+use crate::services::{ChaosTarget, build_image_filter, inject};
+use actix_web::{HttpResponse, delete, web};
+use qdrant_client::qdrant::{CountPointsBuilder, DeletePointsBuilder, Filter};
+use tracing::{info, instrument};
+
+/// Handler deleting all images matching a camera and/or datetime range
+#[utoipa::path(
+    delete,
+    path = "/images",
+    params(DeleteImagesQuery),
+    responses(
+        (status = 200, description = "Matching images deleted (or counted with `dry_run`)", body = DeleteImagesResponse),
+        (status = 400, description = "No filter given or invalid date", body = ErrorResponse),
+        (status = 502, description = "Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[delete("/images")]
+pub async fn delete_images(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+    query: web::Query<DeleteImagesQuery>,
+) -> Result<HttpResponse, AppError> {
+    let camera_ids = query.camera_id.clone().map(|id| vec![id]);
+    let filter = build_image_filter(
+        query.start_date.as_deref(),
+        query.end_date.as_deref(),
+        camera_ids.as_deref(),
+    )?
+    // Refuse to wipe the whole collection by accident
+    .ok_or_else(|| {
+        AppError::InvalidRequest(
+            "At least one of camera_id, start_date or end_date is required".to_string(),
+        )
+    })?;
+
+    inject(ChaosTarget::Qdrant).await?;
+
+    let mut deleted = 0;
+    for collection_name in state.router.collections_for(camera_ids.as_deref()) {
+        deleted += delete_matching(&state, &collection_name, &filter, query.dry_run).await?;
+    }
+
+    if !query.dry_run {
+        info!(
+            deleted,
+            camera_id = query.camera_id.as_deref().unwrap_or("any"),
+            start_date = query.start_date.as_deref().unwrap_or("-"),
+            end_date = query.end_date.as_deref().unwrap_or("-"),
+            "Deleted images"
+        );
+    }
+    Ok(HttpResponse::Ok().json(DeleteImagesResponse {
+        deleted,
+        dry_run: query.dry_run,
+    }))
+}
+
+/// Count the points in one collection matching `filter` and delete them
+/// unless `dry_run` is set
+#[instrument(skip(state, filter))]
+async fn delete_matching(
+    state: &AppState,
+    collection_name: &str,
+    filter: &Filter,
+    dry_run: bool,
+) -> Result<u64, AppError> {
+    let matched = state
+        .qdrant
+        .count(
+            CountPointsBuilder::new(collection_name)
+                .filter(filter.clone())
+                .exact(true),
+        )
+        .await
+        .map_err(|e| AppError::Qdrant(format!("Qdrant count error: {}", e)))?
+        .result
+        .map(|r| r.count)
+        .unwrap_or_default();
+
+    if dry_run || matched == 0 {
+        return Ok(matched);
+    }
+
+    state
+        .qdrant
+        .delete_points(
+            DeletePointsBuilder::new(collection_name)
+                .points(filter.clone())
+                .wait(true),
+        )
+        .await
+        .map_err(|e| AppError::Qdrant(format!("Qdrant delete error: {}", e)))?;
+
+    Ok(matched)
+}
@@ -0,0 +1,212 @@
+//! Embedding Handlers
+//!
+//! Embeddings for offline tools, through the same circuit breaker and cache
+//! as the service itself.
+
+use super::AppState;
+use crate::config::technical;
+use crate::error::AppError;
+use crate::middleware::{Admin, Authorized};
+use crate::models::embed::{EmbedImageRequest, EmbedTextRequest, EmbeddingResponse};
+use crate::services::{
+    AiPriority, LocalImage, QueryImage, decode_base64_image, get_image_embedding, image_cache_key,
+};
+use actix_web::{HttpResponse, post, web};
+
+/// Handler returning the embedding of arbitrary text
+#[utoipa::path(
+    post,
+    path = "/embed/text",
+    request_body(content = EmbedTextRequest, examples(
+        ("English" = (value = json!({ "text": "white pickup truck" }))),
+        ("Thai" = (summary = "รถกระบะสีขาว = white pickup truck", value = json!({ "text": "รถกระบะสีขาว" })))
+    )),
+    responses(
+        (status = 200, description = "Text embedding", body = EmbeddingResponse),
+        (status = 400, description = "Empty text", body = ErrorResponse),
+        (status = 403, description = "The admin role is required", body = ErrorResponse),
+        (status = 502, description = "AI service failure", body = ErrorResponse),
+        (status = 503, description = "AI service circuit open", body = ErrorResponse,
+            headers(("Retry-After" = u64, description = "Seconds until the AI service is tried again")))
+    ),
+    tag = "Tools API"
+)]
+#[post("/embed/text")]
+pub async fn embed_text(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+    request: web::Json<EmbedTextRequest>,
+) -> Result<HttpResponse, AppError> {
+    if request.text.trim().is_empty() {
+        return Err(AppError::InvalidRequest(
+            "text must not be empty".to_string(),
+        ));
+    }
+
+    let (embedding, cached) = state
+        .embedding_cache
+        .text_embedding(
+            &state.http_client,
+            state.scheduler.embedding_models.default_model(),
+            &request.text,
+            AiPriority::Bulk,
+            &state.metrics,
+        )
+        .await?;
+
+    Ok(embedding_response(&state, embedding, cached))
+}
+
+/// Handler returning the embedding of an image, cached by image content
+#[utoipa::path(
+    post,
+    path = "/embed/image",
+    request_body(content = EmbedImageRequest, examples(
+        ("Local path" = (summary = "Relative to EMBED_IMAGE_ROOT", value = json!({ "image_path": "cctv01/2025-10-08/cctv01_06-32_123.jpg" }))),
+        ("Upload" = (value = json!({ "image_base64": "data:image/jpeg;base64,/9j/4AAQSkZJRgABAQ..." })))
+    )),
+    responses(
+        (status = 200, description = "Image embedding", body = EmbeddingResponse),
+        (status = 400, description = "Missing or invalid image, or the AI service could not embed it", body = ErrorResponse),
+        (status = 403, description = "The admin role is required, or image_path is outside EMBED_IMAGE_ROOT", body = ErrorResponse),
+        (status = 500, description = "Failed to store the uploaded image", body = ErrorResponse),
+        (status = 502, description = "AI service failure", body = ErrorResponse),
+        (status = 503, description = "AI service circuit open", body = ErrorResponse,
+            headers(("Retry-After" = u64, description = "Seconds until the AI service is tried again")))
+    ),
+    tag = "Tools API"
+)]
+#[post("/embed/image")]
+pub async fn embed_image(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+    request: web::Json<EmbedImageRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Paths are only read from under the image root, so a token can't probe
+    // or embed arbitrary files on the server
+    let (local_path, content) = match (&request.image_path, &request.image_base64) {
+        (Some(path), None) => {
+            let root = state.embed_image_root.as_deref().ok_or_else(|| {
+                AppError::InvalidRequest(
+                    "image_path requires EMBED_IMAGE_ROOT; send image_base64 instead".to_string(),
+                )
+            })?;
+            let image = LocalImage::read(root, path, technical::MAX_EMBED_IMAGE_BYTES).await?;
+            (Some(image.path), image.content)
+        }
+        (None, Some(encoded)) => (None, decode_base64_image(encoded)?),
+        _ => {
+            return Err(AppError::InvalidRequest(
+                "Exactly one of image_path and image_base64 is required".to_string(),
+            ));
+        }
+    };
+
+    let key = image_cache_key(&content);
+    if let Some(embedding) = state.embedding_cache.get(&key) {
+        return Ok(embedding_response(&state, embedding, true));
+    }
+
+    // Uploads are written where the AI service can read them and removed
+    // when `upload` goes out of scope
+    let (image_path, _upload) = match local_path {
+        Some(path) => (path.to_string_lossy().into_owned(), None),
+        None => {
+            let upload = QueryImage::save_bytes(&state.query_image_dir, &content).await?;
+            (upload.path(), Some(upload))
+        }
+    };
+
+    let result = get_image_embedding(
+        &state.http_client,
+        &state.ai_service_url,
+        vec![image_path],
+        AiPriority::Bulk,
+    )
+    .await?
+    .results
+    .into_iter()
+    .next()
+    .ok_or_else(|| AppError::AiService("No results returned from AI service".to_string()))?;
+    let embedding = match (result.embedding, result.error) {
+        (Some(embedding), _) => embedding,
+        (None, Some(error)) => {
+            return Err(AppError::InvalidRequest(format!(
+                "AI Image Service error: {}",
+                error
+            )));
+        }
+        (None, None) => {
+            return Err(AppError::AiService(
+                "No embedding returned from AI service".to_string(),
+            ));
+        }
+    };
+
+    state.embedding_cache.insert(key, embedding.clone());
+    Ok(embedding_response(&state, embedding, false))
+}
+
+fn embedding_response(state: &AppState, embedding: Vec<f32>, cached: bool) -> HttpResponse {
+    HttpResponse::Ok().json(EmbeddingResponse {
+        dimensions: embedding.len(),
+        embedding,
+        model: state.embedding_model.clone(),
+        cached,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::jwt_guard;
+    use actix_web::http::StatusCode;
+    use actix_web::middleware::from_fn;
+    use actix_web::{App, test};
+    use jsonwebtoken::{EncodingKey, Header, encode};
+
+    #[actix_web::test]
+    async fn test_embed_requires_admin() {
+        let state = AppState::for_tests(&[("JWT_SECRET", "s3cret")]).await;
+        let app = test::init_service(
+            App::new()
+                .app_data(state)
+                .wrap(from_fn(jwt_guard))
+                .service(embed_text)
+                .service(embed_image),
+        )
+        .await;
+        let exp = chrono::Utc::now().timestamp() + 600;
+        let token = |role: &str| {
+            let claims = serde_json::json!({ "sub": "tool", "exp": exp, "roles": [role] });
+            let key = EncodingKey::from_secret(b"s3cret");
+            format!(
+                "Bearer {}",
+                encode(&Header::default(), &claims, &key).unwrap()
+            )
+        };
+        let call = |path: &str, role: &str, body: serde_json::Value| {
+            let request = test::TestRequest::post()
+                .uri(path)
+                .insert_header(("Authorization", token(role)))
+                .set_json(body)
+                .to_request();
+            test::call_service(&app, request)
+        };
+
+        let text = serde_json::json!({ "text": " " });
+        let image = serde_json::json!({});
+        for role in ["reader", "writer"] {
+            let response = call("/embed/text", role, text.clone()).await;
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+            let response = call("/embed/image", role, image.clone()).await;
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        }
+
+        // Admins get past the role check to the request validation
+        let response = call("/embed/text", "admin", text).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = call("/embed/image", "admin", image).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,63 @@
+//! Conditional Responses
+//!
+//! Content-hash ETags for read endpoints polled by dashboards, answering
+//! `304 Not Modified` when the client's copy is current.
+
+use crate::services::sha256_hex;
+use actix_web::http::header::{ETAG, IF_NONE_MATCH};
+use actix_web::{HttpRequest, HttpResponse};
+use serde::Serialize;
+
+/// Serialize `body` as JSON with an ETag, or answer 304 if `If-None-Match` matches
+pub fn json_with_etag<T: Serialize>(req: &HttpRequest, body: &T) -> HttpResponse {
+    let json = match serde_json::to_vec(body) {
+        Ok(json) => json,
+        Err(e) => return HttpResponse::InternalServerError().body(e.to_string()),
+    };
+    let etag = content_etag(&json);
+
+    let if_none_match = req
+        .headers()
+        .get(IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok());
+    if if_none_match.is_some_and(|header| etag_matches(header, &etag)) {
+        return HttpResponse::NotModified()
+            .insert_header((ETAG, etag))
+            .finish();
+    }
+
+    HttpResponse::Ok()
+        .insert_header((ETAG, etag))
+        .content_type("application/json")
+        .body(json)
+}
+
+/// Strong ETag from the SHA-256 of the body, identical across replicas
+fn content_etag(content: &[u8]) -> String {
+    format!("\"{}\"", sha256_hex(content))
+}
+
+/// Whether an `If-None-Match` header matches `etag` (weak comparison, as
+/// RFC 9110 requires for this header)
+fn etag_matches(header: &str, etag: &str) -> bool {
+    header.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.trim_start_matches("W/") == etag.trim_start_matches("W/")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = content_etag(b"{\"running\":false}");
+        assert_eq!(etag, content_etag(b"{\"running\":false}"));
+        assert_ne!(etag, content_etag(b"{\"running\":true}"));
+
+        assert!(etag_matches(&etag, &etag));
+        assert!(etag_matches(&format!("\"other\", W/{}", etag), &etag));
+        assert!(etag_matches("*", &etag));
+        assert!(!etag_matches("\"other\"", &etag));
+    }
+}
@@ -0,0 +1,267 @@
+//! Evaluation Handlers
+//!
+//! Uploading labeled query sets and running them through `/search` with the
+//! current configuration, to measure whether a model or ranking change
+//! helps before it is rolled out.
+
+use super::{AppState, SearchOutcome, ValidatedJson, run_search, validated};
+use crate::error::AppError;
+use crate::middleware::{Admin, Authorized, token_subject};
+use crate::models::evaluation::{
+    CreateEvalSetRequest, EvalQueryResult, EvalRunReport, EvalRunRequest, EvalRunSummary,
+};
+use crate::models::search::{LabeledQuery, SearchRequest};
+use crate::services::{latency_summary, ranking_metrics};
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+use std::collections::HashSet;
+use std::time::Instant;
+use tracing::info;
+
+/// Default cutoff of recall and reciprocal rank of an evaluation run
+const DEFAULT_EVAL_TOP_K: u64 = 10;
+
+/// Handler storing a labeled query set
+#[utoipa::path(
+    post,
+    path = "/admin/eval/sets",
+    request_body = CreateEvalSetRequest,
+    responses(
+        (status = 201, description = "Evaluation set stored", body = EvalSet),
+        (status = 400, description = "Bad request; `validation_failed` lists the invalid fields in `details`", body = ErrorResponse),
+        (status = 500, description = "Evaluation sets file could not be written", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[post("/admin/eval/sets")]
+pub async fn create_eval_set(
+    _: Authorized<Admin>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    request: ValidatedJson<CreateEvalSetRequest>,
+) -> Result<HttpResponse, AppError> {
+    let set = state.eval_sets.create(&request, token_subject(&req))?;
+    info!(
+        target: "audit",
+        action = "create_eval_set",
+        set_id = %set.set_id,
+        queries = set.queries.len(),
+        user = set.created_by.as_deref().unwrap_or("anonymous"),
+        "Evaluation set stored"
+    );
+    Ok(HttpResponse::Created().json(set))
+}
+
+/// Handler listing every evaluation set with its most recent run
+#[utoipa::path(
+    get,
+    path = "/admin/eval/sets",
+    responses(
+        (status = 200, description = "Evaluation sets, oldest first", body = Vec<EvalSetSummary>)
+    ),
+    tag = "Admin API"
+)]
+#[get("/admin/eval/sets")]
+pub async fn list_eval_sets(_: Authorized<Admin>, state: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok().json(state.eval_sets.list())
+}
+
+/// Handler returning an evaluation set with its queries and run history
+#[utoipa::path(
+    get,
+    path = "/admin/eval/sets/{set_id}",
+    params(("set_id" = String, Path, description = "Evaluation set ID")),
+    responses(
+        (status = 200, description = "Evaluation set", body = EvalSet),
+        (status = 404, description = "Unknown evaluation set", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[get("/admin/eval/sets/{set_id}")]
+pub async fn get_eval_set(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+    set_id: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    Ok(HttpResponse::Ok().json(state.eval_sets.get(&set_id)?))
+}
+
+/// Handler removing an evaluation set and its run history
+#[utoipa::path(
+    delete,
+    path = "/admin/eval/sets/{set_id}",
+    params(("set_id" = String, Path, description = "Evaluation set ID")),
+    responses(
+        (status = 204, description = "Evaluation set removed"),
+        (status = 404, description = "Unknown evaluation set", body = ErrorResponse),
+        (status = 500, description = "Evaluation sets file could not be written", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[delete("/admin/eval/sets/{set_id}")]
+pub async fn delete_eval_set(
+    _: Authorized<Admin>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    set_id: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    state.eval_sets.delete(&set_id)?;
+    info!(
+        target: "audit",
+        action = "delete_eval_set",
+        set_id = %set_id,
+        user = token_subject(&req).as_deref().unwrap_or("anonymous"),
+        "Evaluation set removed"
+    );
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Handler running every query of an evaluation set through `/search`
+///
+/// Queries run one after another, over every tenant, so their latencies
+/// aren't skewed by each other. The aggregate is added to the run history
+/// of the set.
+#[utoipa::path(
+    post,
+    path = "/admin/eval/sets/{set_id}/run",
+    params(("set_id" = String, Path, description = "Evaluation set ID")),
+    request_body = EvalRunRequest,
+    responses(
+        (status = 200, description = "Recall@k, MRR and latency of the set", body = EvalRunReport),
+        (status = 400, description = "Bad request; `validation_failed` lists the invalid fields in `details`", body = ErrorResponse),
+        (status = 404, description = "Unknown evaluation set", body = ErrorResponse),
+        (status = 502, description = "AI service or Qdrant failure, or the AI service is down and searches only browse", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[post("/admin/eval/sets/{set_id}/run")]
+pub async fn run_eval_set(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+    set_id: web::Path<String>,
+    request: ValidatedJson<EvalRunRequest>,
+) -> Result<HttpResponse, AppError> {
+    let set = state.eval_sets.get(&set_id)?;
+    let top_k = request.top_k.unwrap_or(DEFAULT_EVAL_TOP_K);
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let embedding_models = state
+        .scheduler
+        .embedding_models
+        .models()
+        .into_iter()
+        .map(|model| model.name.clone())
+        .collect();
+
+    let mut results = Vec::with_capacity(set.queries.len());
+    let mut latencies = Vec::with_capacity(set.queries.len());
+    for query in &set.queries {
+        let search = eval_search_request(request.search.as_ref(), query, top_k)?;
+        let started = Instant::now();
+        let hits = match run_search(&state, &search, None).await? {
+            SearchOutcome::Results(hits) => hits,
+            SearchOutcome::Browsed(_) => {
+                return Err(AppError::AiService(
+                    "AI service unavailable; searches only browse the newest images".to_string(),
+                ));
+            }
+            // Grouped and debug searches are rejected by validation
+            SearchOutcome::Groups(_) | SearchOutcome::Debug(_) => {
+                return Err(AppError::InvalidRequest(
+                    "Evaluation runs need ranked search results".to_string(),
+                ));
+            }
+        };
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let returned: HashSet<u64> = hits.iter().filter_map(|hit| hit.id.parse().ok()).collect();
+        let (recall, reciprocal_rank) = ranking_metrics(
+            hits.iter().map(|hit| {
+                hit.id
+                    .parse()
+                    .is_ok_and(|id| query.relevant_ids.contains(&id))
+            }),
+            query.relevant_ids.len(),
+        );
+        latencies.push(latency_ms);
+        results.push(EvalQueryResult {
+            query: query.query.clone(),
+            recall,
+            reciprocal_rank,
+            latency_ms,
+            missed_ids: query
+                .relevant_ids
+                .iter()
+                .filter(|id| !returned.contains(id))
+                .copied()
+                .collect(),
+        });
+    }
+
+    let count = results.len().max(1) as f64;
+    let summary = EvalRunSummary {
+        started_at,
+        top_k,
+        embedding_models,
+        search: request.search.clone(),
+        recall: results.iter().map(|r| r.recall).sum::<f64>() / count,
+        mrr: results.iter().map(|r| r.reciprocal_rank).sum::<f64>() / count,
+        latency: latency_summary(&latencies),
+    };
+    state.eval_sets.record_run(&set.set_id, summary.clone())?;
+    info!(
+        set_id = %set.set_id,
+        queries = results.len(),
+        top_k,
+        recall = summary.recall,
+        mrr = summary.mrr,
+        p95_ms = summary.latency.p95_ms,
+        "Evaluation run completed"
+    );
+    Ok(HttpResponse::Ok().json(EvalRunReport {
+        set_id: set.set_id,
+        summary,
+        results,
+    }))
+}
+
+/// `/search` request of one labeled query: the run's search options with
+/// the query and cutoff set
+fn eval_search_request(
+    search: Option<&serde_json::Map<String, serde_json::Value>>,
+    query: &LabeledQuery,
+    top_k: u64,
+) -> Result<SearchRequest, AppError> {
+    let mut body = search.cloned().unwrap_or_default();
+    body.insert("query".to_string(), query.query.clone().into());
+    body.insert("top_k".to_string(), top_k.into());
+    let request = serde_json::from_value(serde_json::Value::Object(body))
+        .map_err(|e| AppError::InvalidRequest(format!("Invalid search options: {}", e)))?;
+    validated(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eval_search_request() {
+        let query = LabeledQuery {
+            query: "white pickup truck".to_string(),
+            relevant_ids: vec![3],
+        };
+        let search = serde_json::json!({"hybrid": true, "camera_ids": ["cctv01"]});
+        let request = eval_search_request(search.as_object(), &query, 20).unwrap();
+        assert_eq!(request.query, "white pickup truck");
+        assert_eq!(request.top_k, Some(20));
+        assert!(request.hybrid);
+        assert_eq!(
+            request.camera_ids.as_deref(),
+            Some(&["cctv01".to_string()][..])
+        );
+
+        let search = serde_json::json!({"min_score": "high"});
+        assert!(matches!(
+            eval_search_request(search.as_object(), &query, 20),
+            Err(AppError::InvalidRequest(_))
+        ));
+    }
+}
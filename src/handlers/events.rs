@@ -0,0 +1,240 @@
+//! Live Events Handler
+//!
+//! `GET /events` streams scheduler run progress, newly stored points and
+//! saved search matches as Server-Sent Events, which browsers read with
+//! `EventSource` instead of a WebSocket.
+
+use super::AppState;
+use crate::middleware::{Authorized, Reader, Tenant};
+use crate::models::alert::AlertNotification;
+use crate::models::events::EventsQuery;
+use crate::services::{IngestNotification, SchedulerRun};
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::http::header;
+use actix_web::web::Bytes;
+use actix_web::{HttpResponse, get, web};
+use serde::Serialize;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// Comment sent on idle streams, so proxies keep them open and hung-up
+/// clients are noticed
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Handler streaming scheduler runs and stored points as Server-Sent Events
+///
+/// A `run` event carries a `SchedulerRun` whenever a scheduled or manual run
+/// starts, completes a batch or finishes; a `match` event carries an
+/// `IngestNotification` for each stored point passing the filters; an
+/// `alert` event carries the `AlertNotification` of a saved search.
+/// While tenancy is enabled, only the points and alerts of the request's
+/// tenant are streamed, and no `run` events, which span every tenant.
+#[utoipa::path(
+    get,
+    path = "/events",
+    params(EventsQuery),
+    responses(
+        (status = 200, description = "Event stream of `run`, `match` and `alert` events", content_type = "text/event-stream")
+    ),
+    tag = "Insertion API"
+)]
+#[get("/events")]
+pub async fn live_events(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    query: web::Query<EventsQuery>,
+    state: web::Data<AppState>,
+) -> HttpResponse {
+    let filter = query.into_inner();
+    let mut runs = state.scheduler.run_history.subscribe();
+    let mut points = state.scheduler.ingest_feed.subscribe();
+    let mut alerts = state.scheduler.alerts.subscribe();
+    info!(
+        camera_id = ?filter.camera_id,
+        vehicle_class = ?filter.vehicle_class,
+        alert_id = ?filter.alert_id,
+        "Live event subscriber connected"
+    );
+
+    let closing = state.scheduler.ingest_feed.closing();
+    let (events, body) = mpsc::channel(16);
+    actix_web::rt::spawn(async move {
+        let mut keep_alive =
+            tokio::time::interval_at(Instant::now() + KEEP_ALIVE_INTERVAL, KEEP_ALIVE_INTERVAL);
+        loop {
+            let event = tokio::select! {
+                // Ending the stream on shutdown lets the connection drain
+                _ = closing.cancelled() => break,
+                _ = keep_alive.tick() => Some(Bytes::from_static(b": keep-alive\n\n")),
+                received = runs.recv() => match received {
+                    Ok(run) if tenant.id().is_none() && run_matches(&filter, &run) => {
+                        sse_event("run", &run)
+                    }
+                    Ok(_) => None,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "Live event subscriber fell behind on runs");
+                        None
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                received = points.recv() => match received {
+                    Ok(point)
+                        if point.visible_to(tenant.id()) && point_matches(&filter, &point) =>
+                    {
+                        sse_event("match", &point)
+                    }
+                    Ok(_) => None,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "Live event subscriber fell behind on stored points");
+                        None
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                received = alerts.recv() => match received {
+                    Ok(alert)
+                        if tenant.id().is_none_or(|id| alert.tenant_id.as_deref() == Some(id))
+                            && alert_matches(&filter, &alert) =>
+                    {
+                        sse_event("alert", &alert)
+                    }
+                    Ok(_) => None,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "Live event subscriber fell behind on alerts");
+                        None
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            };
+            // Sending fails once the client has hung up and the body is dropped
+            if let Some(event) = event
+                && events.send(event).await.is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        // Disables response buffering in nginx
+        .insert_header(("X-Accel-Buffering", "no"))
+        .body(ChannelBody(body))
+}
+
+/// Streaming response body, fed by the subscriber task of the connection
+struct ChannelBody(mpsc::Receiver<Bytes>);
+
+impl MessageBody for ChannelBody {
+    type Error = Infallible;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Infallible>>> {
+        self.get_mut().0.poll_recv(cx).map(|frame| frame.map(Ok))
+    }
+}
+
+/// Whether `run` fetches the filtered camera (runs of every camera always do)
+fn run_matches(filter: &EventsQuery, run: &SchedulerRun) -> bool {
+    match (&filter.camera_id, &run.cctv_id) {
+        (Some(camera_id), Some(cctv_id)) => camera_id == cctv_id,
+        _ => true,
+    }
+}
+
+/// Whether a stored point passes the camera and vehicle class filters
+fn point_matches(filter: &EventsQuery, point: &IngestNotification) -> bool {
+    filter
+        .camera_id
+        .as_ref()
+        .is_none_or(|camera_id| *camera_id == point.camera_id)
+        && filter
+            .vehicle_class
+            .as_ref()
+            .is_none_or(|class| point.vehicle_class.as_ref() == Some(class))
+}
+
+/// Whether an alert notification is of the filtered saved search
+fn alert_matches(filter: &EventsQuery, alert: &AlertNotification) -> bool {
+    filter
+        .alert_id
+        .as_ref()
+        .is_none_or(|alert_id| *alert_id == alert.alert_id)
+}
+
+/// Server-Sent Event named `name` with `data` as its JSON line
+fn sse_event(name: &str, data: &impl Serialize) -> Option<Bytes> {
+    let json = serde_json::to_string(data).ok()?;
+    Some(format!("event: {}\ndata: {}\n\n", name, json).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::RunTrigger;
+
+    #[test]
+    fn test_filters_and_encoding() {
+        let filter = EventsQuery {
+            camera_id: Some("cctv01".to_string()),
+            vehicle_class: Some("pickup".to_string()),
+            alert_id: Some("4f1c2a".to_string()),
+        };
+        let point = |camera_id: &str, class: Option<&str>| IngestNotification {
+            point_id: 12345,
+            camera_id: camera_id.to_string(),
+            filename: "cctv01_2025-10-08_06-32_123.jpg".to_string(),
+            datetime: "2025-10-07T23:32:00Z".to_string(),
+            vehicle_class: class.map(str::to_string),
+            tenant_id: None,
+        };
+        assert!(point_matches(&filter, &point("cctv01", Some("pickup"))));
+        assert!(!point_matches(&filter, &point("cctv01", None)));
+        assert!(!point_matches(&filter, &point("cctv02", Some("pickup"))));
+        assert!(point_matches(
+            &EventsQuery::default(),
+            &point("cctv02", None)
+        ));
+
+        let run = |cctv_id: Option<&str>| {
+            SchedulerRun::start(
+                RunTrigger::Manual,
+                "a".to_string(),
+                "b".to_string(),
+                cctv_id.map(str::to_string),
+            )
+        };
+        assert!(run_matches(&filter, &run(None)));
+        assert!(run_matches(&filter, &run(Some("cctv01"))));
+        assert!(!run_matches(&filter, &run(Some("cctv02"))));
+
+        let alert = |alert_id: &str| AlertNotification {
+            alert_id: alert_id.to_string(),
+            name: "Red pickups".to_string(),
+            query: "red pickup truck".to_string(),
+            tenant_id: None,
+            hits: Vec::new(),
+        };
+        assert!(alert_matches(&filter, &alert("4f1c2a")));
+        assert!(!alert_matches(&filter, &alert("9b0e11")));
+
+        let event = sse_event("match", &point("cctv01", None)).unwrap();
+        let text = std::str::from_utf8(&event).unwrap();
+        assert!(text.starts_with("event: match\ndata: {\"point_id\":12345,"));
+        assert!(text.ends_with("}\n\n"));
+        // The JSON fits on one data line
+        assert_eq!(text.matches('\n').count(), 3);
+    }
+}
@@ -0,0 +1,99 @@
+//! Export Handlers
+//!
+//! Paged listing of stored image metadata for analytics tools, without vector
+//! search.
+
+use super::AppState;
+use crate::config::technical;
+use crate::error::AppError;
+use crate::middleware::{Authorized, Reader, Tenant};
+use crate::models::export::{ImagePage, ListImagesQuery, StoredImage};
+use crate::services::{
+    ChaosTarget, Dependency, PageToken, build_image_filter, guarded, inject, label_conditions,
+    point_id_to_string, scroll_images, with_conditions, with_tenant,
+};
+use actix_web::{HttpResponse, get, web};
+
+/// Handler listing stored images matching a camera, datetime range and/or
+/// vehicle class, one page at a time
+#[utoipa::path(
+    get,
+    path = "/images",
+    params(ListImagesQuery),
+    responses(
+        (status = 200, description = "Page of stored images", body = ImagePage),
+        (status = 400, description = "Invalid date, limit or page token", body = ErrorResponse),
+        (status = 502, description = "Qdrant failure", body = ErrorResponse),
+        (status = 503, description = "Qdrant circuit breaker open", body = ErrorResponse)
+    ),
+    tag = "Search API"
+)]
+#[get("/images")]
+pub async fn list_images(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    state: web::Data<AppState>,
+    query: web::Query<ListImagesQuery>,
+) -> Result<HttpResponse, AppError> {
+    let limit = query.limit.unwrap_or(technical::EXPORT_PAGE_SIZE);
+    if !(1..=technical::MAX_EXPORT_PAGE_SIZE).contains(&limit) {
+        return Err(AppError::InvalidRequest(format!(
+            "limit must be between 1 and {}",
+            technical::MAX_EXPORT_PAGE_SIZE
+        )));
+    }
+    let token = query
+        .page_token
+        .as_deref()
+        .map(PageToken::decode)
+        .transpose()?;
+
+    let camera_ids = query.camera_id.clone().map(|id| vec![id]);
+    let vehicle_classes = query.vehicle_class.clone().map(|class| vec![class]);
+    let filter = with_tenant(
+        with_conditions(
+            build_image_filter(
+                query.start_date.as_deref(),
+                query.end_date.as_deref(),
+                camera_ids.as_deref(),
+            )?,
+            label_conditions(vehicle_classes.as_deref(), None)?,
+        ),
+        tenant.id(),
+    );
+    // The page token indexes into this list, which is stable for a given filter
+    let collections = state.router.collections_for(camera_ids.as_deref());
+
+    let (points, next) = guarded(Dependency::Qdrant, async {
+        inject(ChaosTarget::Qdrant).await?;
+        scroll_images(&state.qdrant, &collections, filter, limit, token).await
+    })
+    .await?;
+
+    let images = points
+        .into_iter()
+        .map(|point| StoredImage {
+            id: point
+                .id
+                .as_ref()
+                .map(point_id_to_string)
+                .unwrap_or_default(),
+            payload: point
+                .payload
+                .into_iter()
+                .map(|(key, value)| match (key.as_str(), value.into_json()) {
+                    ("image", serde_json::Value::String(url)) => {
+                        let url = state.url_rewriter.rewrite(&url).into_owned();
+                        (key, url.into())
+                    }
+                    (_, value) => (key, value),
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ImagePage {
+        images,
+        next_page_token: next.map(|token| token.encode()),
+    }))
+}
@@ -0,0 +1,113 @@
+//! Edge Ingest Handler
+//!
+//! Batches of embedded images sent by edge devices, acknowledged once
+//! stored so devices can retry until they get an acknowledgement.
+
+use super::{AppState, insert_batch};
+use crate::error::AppError;
+use crate::middleware::EdgeDevice;
+use crate::models::ingest::{EdgeIngestAck, EdgeIngestBatch, IngestAckStatus};
+use crate::models::search::{BatchInsertFailure, InsertOptions};
+use crate::services::SequenceCheck;
+use actix_web::{HttpResponse, post, web};
+use tracing::{info, warn};
+
+/// Key under `extra` recording the device that sent an image
+pub const EDGE_DEVICE_KEY: &str = "edge_device";
+
+/// Handler for a batch of embedded images from an edge device
+///
+/// A batch is only acknowledged once stored; resending an acknowledged
+/// sequence returns `duplicate` without storing it again.
+#[utoipa::path(
+    post,
+    path = "/ingest/batch",
+    request_body = EdgeIngestBatch,
+    params(
+        ("X-Device-Id" = String, Header, description = "ID of the sending device, as listed in EDGE_DEVICE_KEYS"),
+        ("X-Device-Key" = String, Header, description = "Key of the sending device")
+    ),
+    responses(
+        (status = 200, description = "Batch stored, or already stored by an earlier attempt", body = EdgeIngestAck),
+        (status = 400, description = "Empty or oversized batch, or an epoch older than the device's current one", body = ErrorResponse),
+        (status = 401, description = "Unknown device or wrong key", body = ErrorResponse),
+        (status = 502, description = "Qdrant failure; retry the batch", body = ErrorResponse)
+    ),
+    security(("device_key" = [])),
+    tag = "Insertion API"
+)]
+#[post("/ingest/batch")]
+pub async fn ingest_batch(
+    EdgeDevice(device_id): EdgeDevice,
+    state: web::Data<AppState>,
+    payload: web::Json<EdgeIngestBatch>,
+) -> Result<HttpResponse, AppError> {
+    let batch = payload.into_inner();
+    if batch.sequence == 0 {
+        return Err(AppError::InvalidRequest("Sequences start at 1".to_string()));
+    }
+    if batch.points.is_empty() {
+        return Err(AppError::InvalidRequest("No points provided".to_string()));
+    }
+
+    let sequences = &state.ingest_sequences;
+    let check = sequences.check(&device_id, batch.epoch, batch.sequence)?;
+    let (status, inserted, failed) = if check == SequenceCheck::Duplicate {
+        state.metrics.record_edge_duplicate();
+        info!(
+            device_id = %device_id,
+            sequence = batch.sequence,
+            "Edge batch already stored"
+        );
+        (IngestAckStatus::Duplicate, Vec::new(), Vec::new())
+    } else {
+        // Edge devices embed their own images; the AI service is not called
+        let (mut embedded, unembedded): (Vec<_>, Vec<_>) = batch
+            .points
+            .into_iter()
+            .partition(|image| image.embedding.is_some());
+        let mut failed: Vec<BatchInsertFailure> = unembedded
+            .iter()
+            .map(|image| BatchInsertFailure {
+                id: image.id as u64,
+                error: "Sent without an embedding".to_string(),
+            })
+            .collect();
+        for image in &mut embedded {
+            image
+                .extra
+                .insert(EDGE_DEVICE_KEY.to_string(), device_id.clone().into());
+        }
+        let mut inserted = Vec::new();
+        if !embedded.is_empty() {
+            let response =
+                insert_batch(&state, embedded, &InsertOptions { verify: None }, None).await?;
+            inserted = response.inserted;
+            failed.extend(response.failed);
+        }
+
+        if let Some(gap) = sequences.acknowledge(&device_id, batch.epoch, batch.sequence) {
+            state
+                .metrics
+                .record_edge_sequence_gap(gap.to - gap.from + 1);
+            warn!(
+                device_id = %device_id,
+                epoch = batch.epoch,
+                from = gap.from,
+                to = gap.to,
+                "Edge batches missing"
+            );
+        }
+        (IngestAckStatus::Accepted, inserted, failed)
+    };
+
+    Ok(HttpResponse::Ok().json(EdgeIngestAck {
+        missing: sequences.missing(&device_id),
+        device_id,
+        epoch: batch.epoch,
+        sequence: batch.sequence,
+        status,
+        inserted,
+        failed,
+    }))
+}
@@ -0,0 +1,551 @@
+//! Insertion Handlers
+//!
+//! Embedding and storing images sent by clients.
+
+use super::AppState;
+use crate::config::technical;
+use crate::error::AppError;
+use crate::middleware::{Authorized, Tenant, Writer};
+use crate::models::search::{
+    BatchInsertFailure, BatchInsertResponse, CctvImageData, InsertOptions,
+};
+use crate::services::{
+    AiPriority, CAPTION_TERMS_FIELD, CONTENT_HASH_FIELD, ChaosTarget, Dependency, EXTRA_FIELD,
+    EmbeddingModel, IngestPath, PayloadBuilder, TENANT_FIELD, UpsertSettings,
+    VEHICLE_TYPE_LABEL_FIELD, YOLO_LABEL_FIELD, api_datetime_to_rfc3339, caption_embedding,
+    caption_terms, detect_id_collisions, generate_captions, get_image_embedding, guarded,
+    hash_images, image_caption, image_tenant, inject, validate_embedding, verify_upsert,
+};
+use actix_web::{HttpResponse, post, web};
+use qdrant_client::qdrant::{PointStruct, UpsertPoints, Vectors};
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+/// Build the Qdrant point for an image, its embeddings, caption, content hash
+/// and tenant
+///
+/// The image `date`/`time` are local to the camera's source timezone and
+/// stored in UTC.
+fn image_point(
+    state: &AppState,
+    image: &CctvImageData,
+    vectors: Vectors,
+    caption: Option<&str>,
+    content_sha256: Option<&String>,
+    tenant: Option<&str>,
+) -> Result<PointStruct, AppError> {
+    let ctx = &state.scheduler;
+    let timezone = ctx
+        .cameras
+        .timezone(&image.cctv_id, ctx.config.cctv_source_timezone);
+    let datetime_rfc3339 = api_datetime_to_rfc3339(&image.date, &image.time, timezone)?;
+
+    // Auto-generate createdAt if not provided
+    let created_at = image
+        .created_at
+        .clone()
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+    // Build payload using the builder pattern
+    let mut payload_builder = PayloadBuilder::new()
+        .string("image", &image.file_path)
+        .string("filename", &image.filename)
+        .string("camera_id", &image.cctv_id)
+        .string("datetime", &datetime_rfc3339)
+        .integer("frame", image.frame as i64)
+        .integer("vehicle_type", image.vehicle_type as i64)
+        .integer("yolo_id", image.yolo_id as i64)
+        .string_opt(
+            VEHICLE_TYPE_LABEL_FIELD,
+            ctx.vehicle_types.vehicle_type_label(image.vehicle_type),
+        )
+        .string_opt(
+            YOLO_LABEL_FIELD,
+            ctx.vehicle_types.yolo_label(image.yolo_id),
+        )
+        .string("created_at", &created_at)
+        .string_opt("caption", caption)
+        .string_opt(CAPTION_TERMS_FIELD, caption.and_then(caption_terms))
+        .string_opt(CONTENT_HASH_FIELD, content_sha256)
+        .string_opt(TENANT_FIELD, tenant)
+        .object(EXTRA_FIELD, &image.extra);
+
+    // Add AI label if present
+    if let Some(ref ai_label) = image.ai_label {
+        payload_builder = payload_builder
+            .string("vehicle_class", &ai_label.class_name)
+            .double("confidence", ai_label.confidence as f64);
+    }
+
+    let mut payload = payload_builder.build();
+    ctx.config
+        .payload_limits
+        .enforce(&mut payload, &image.filename, &state.metrics)?;
+
+    // Use the API's image ID as point ID
+    Ok(PointStruct::new(image.id as u64, vectors, payload))
+}
+
+/// Embedding of the image at `file_path` from the AI service serving `model`
+async fn embed_image(
+    state: &AppState,
+    model: &EmbeddingModel,
+    file_path: &str,
+) -> Result<Vec<f32>, AppError> {
+    let batch_result = get_image_embedding(
+        &state.http_client,
+        &model.ai_service_url,
+        vec![file_path.to_string()],
+        AiPriority::Bulk,
+    )
+    .await?;
+
+    // Extract the first result
+    let result =
+        batch_result.results.into_iter().next().ok_or_else(|| {
+            AppError::AiService("No results returned from AI service".to_string())
+        })?;
+
+    // Check for errors in the result
+    if let Some(error) = result.error {
+        return Err(AppError::AiService(format!(
+            "AI Image Service error: {}",
+            error
+        )));
+    }
+
+    result
+        .embedding
+        .ok_or_else(|| AppError::AiService("No embedding returned from AI service".to_string()))
+}
+
+/// Image and, in the named layout, caption vectors of an image, the caption
+/// embedded by `model`
+async fn image_vectors(
+    state: &AppState,
+    model: &EmbeddingModel,
+    vector: Vec<f32>,
+    caption: Option<&str>,
+) -> Vectors {
+    let caption = caption_embedding(
+        state.vector_layout,
+        &state.http_client,
+        &model.ai_service_url,
+        caption,
+        model.vector_size,
+        &state.metrics,
+    )
+    .await;
+    state.vector_layout.point_vectors(vector, caption)
+}
+
+/// Generated captions of the `images` sent without one, keyed by path
+async fn image_captions(state: &AppState, images: &[&CctvImageData]) -> HashMap<String, String> {
+    generate_captions(
+        &state.http_client,
+        &state.ai_service_url,
+        state.scheduler.config.caption_endpoint.as_deref(),
+        images,
+    )
+    .await
+}
+
+/// Content hashes of the readable `images` when `HASH_IMAGES` is on, keyed by path
+async fn content_hashes(state: &AppState, images: &[CctvImageData]) -> HashMap<String, String> {
+    if !state.tunables.current().hash_images {
+        return HashMap::new();
+    }
+    let paths: Vec<String> = images.iter().map(|i| i.file_path.clone()).collect();
+    hash_images(&state.http_client, &paths).await
+}
+
+/// Record an image rejected for a defective embedding
+fn dead_letter(state: &AppState, image: &CctvImageData, error: &AppError) {
+    state
+        .scheduler
+        .dead_letters
+        .push(image, "insert", &error.to_string(), &state.metrics);
+}
+
+/// Read upserted points back when requested, or when `VERIFY_UPSERTS` is on
+///
+/// Returns `None` if verification did not run; a failed read-back is
+/// reported as a single mismatch.
+async fn verify_if_requested(
+    state: &AppState,
+    options: &InsertOptions,
+    collection_name: &str,
+    points: &[PointStruct],
+) -> Option<Vec<String>> {
+    if !verify_requested(state, options) {
+        return None;
+    }
+
+    Some(
+        verify_upsert(&state.qdrant, collection_name, points, &state.metrics)
+            .await
+            .unwrap_or_else(|e| vec![e.to_string()]),
+    )
+}
+
+fn verify_requested(state: &AppState, options: &InsertOptions) -> bool {
+    options
+        .verify
+        .unwrap_or_else(|| state.tunables.current().verify_upserts)
+}
+
+/// Warn about points that would overwrite another camera's image
+///
+/// A failed lookup is logged and does not block the insert.
+async fn check_id_collisions(state: &AppState, collection_name: &str, points: &[PointStruct]) {
+    if let Err(e) =
+        detect_id_collisions(&state.qdrant, collection_name, points, &state.metrics).await
+    {
+        warn!(error = %e, "Skipped ID collision check");
+    }
+}
+
+/// Upsert `points` through the Qdrant circuit breaker, with the
+/// interactive `wait`/ordering settings
+async fn upsert_points(
+    state: &AppState,
+    options: &InsertOptions,
+    collection_name: &str,
+    points: Vec<PointStruct>,
+) -> Result<(), AppError> {
+    let settings = UpsertSettings::for_path(
+        &state.tunables.current(),
+        IngestPath::Interactive,
+        verify_requested(state, options),
+    );
+    guarded(Dependency::Qdrant, async {
+        inject(ChaosTarget::Qdrant).await?;
+        check_id_collisions(state, collection_name, &points).await;
+        state
+            .qdrant
+            .upsert_points(UpsertPoints {
+                collection_name: collection_name.to_string(),
+                wait: Some(settings.wait),
+                points: points.clone(),
+                ordering: settings.ordering,
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| AppError::Qdrant(format!("Qdrant upsert error: {}", e)))?;
+        state.scheduler.ingest_feed.publish(&points);
+        Ok(())
+    })
+    .await
+}
+
+/// Handler for inserting a new image with metadata
+#[utoipa::path(
+    post,
+    path = "/insert_image",
+    request_body(content = CctvImageData, examples(
+        ("Image URL" = (summary = "Image served over HTTP", value = json!({
+            "id": 12345,
+            "cctv_id": "cctv01",
+            "date": "2025-10-08",
+            "time": "06:32:00",
+            "frame": 123,
+            "vehicle_type": 2,
+            "yolo_id": 5,
+            "filename": "cctv01_2025-10-08_06-32_123.jpg",
+            "file_path": "https://example.com/images/cctv01_2025-10-08_06-32_123.jpg",
+            "ai_label": { "class_name": "pickup", "confidence": 0.95 }
+        }))),
+        ("Local path" = (summary = "Image file readable by the AI service", value = json!({
+            "id": 12346,
+            "cctv_id": "cctv02",
+            "date": "2025-10-08",
+            "time": "06:33:10",
+            "frame": 7,
+            "vehicle_type": 1,
+            "yolo_id": 2,
+            "filename": "cctv02_2025-10-08_06-33_7.jpg",
+            "file_path": "/data/images/cctv02_2025-10-08_06-33_7.jpg",
+            "createdAt": "2025-10-08T06:33:12Z"
+        })))
+    )),
+    params(InsertOptions),
+    responses(
+        (status = 200, description = "Image inserted successfully", body = Value),
+        (status = 422, description = "The AI service returned an embedding of the wrong dimension", body = ErrorResponse),
+        (status = 429, description = "Per-client rate limit exceeded, retry after `Retry-After` seconds", body = ErrorResponse),
+        (status = 502, description = "AI service or Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Insertion API"
+)]
+#[post("/insert_image")]
+pub async fn insert_image(
+    _: Authorized<Writer>,
+    tenant: Tenant,
+    state: web::Data<AppState>,
+    payload: web::Json<CctvImageData>,
+    options: web::Query<InsertOptions>,
+) -> Result<HttpResponse, AppError> {
+    let inserted = insert_one(&state, &payload, &options, tenant.id()).await?;
+    let mut response = serde_json::json!({
+        "status": "ok",
+        "point_id": inserted.point_id,
+        "type": "image_embedding",
+        "embedding": inserted.embedding,
+    });
+    if let Some(mismatches) = inserted.verification_mismatches {
+        response["verification_mismatches"] = serde_json::json!(mismatches);
+    }
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// An image stored by `insert_one`
+pub struct InsertedImage {
+    pub point_id: u64,
+    pub embedding: Vec<f32>,
+    /// Set when the upsert was read back
+    pub verification_mismatches: Option<Vec<String>>,
+}
+
+/// Embed and store one image, shared by `POST /insert_image` and the gRPC API
+///
+/// The image is stored for the tenant its camera is registered to, else for
+/// the request's `tenant`.
+pub async fn insert_one(
+    state: &AppState,
+    image: &CctvImageData,
+    options: &InsertOptions,
+    tenant: Option<&str>,
+) -> Result<InsertedImage, AppError> {
+    let tenant = image_tenant(
+        &image.cctv_id,
+        state.scheduler.cameras.tenant(&image.cctv_id),
+        tenant,
+    )?;
+
+    // Images sent with an embedding skip the AI service
+    let collection_name = state.router.collection_for(&image.cctv_id);
+    let model = state
+        .scheduler
+        .embedding_models
+        .for_collection(collection_name);
+    let vector = match image.embedding.clone() {
+        Some(vector) => vector,
+        None => embed_image(state, model, &image.file_path).await?,
+    };
+    if let Err(e) = validate_embedding(
+        &vector,
+        model.vector_size,
+        "ingest",
+        &image.filename,
+        &state.metrics,
+    ) {
+        dead_letter(state, image, &e);
+        return Err(e);
+    }
+
+    // Use the API's image ID as point ID
+    let point_id: u64 = image.id as u64;
+    let hashes = content_hashes(state, std::slice::from_ref(image)).await;
+    let captions = image_captions(state, &[image]).await;
+    let caption = image_caption(image, &captions);
+    let vectors = image_vectors(state, model, vector.clone(), caption).await;
+    let hash = hashes.get(&image.file_path);
+    let point = image_point(state, image, vectors, caption, hash, tenant.as_deref())
+        .inspect_err(|e| dead_letter(state, image, e))?;
+
+    // Upsert to Qdrant
+    upsert_points(state, options, collection_name, vec![point.clone()]).await?;
+
+    Ok(InsertedImage {
+        point_id,
+        embedding: vector,
+        verification_mismatches: verify_if_requested(state, options, collection_name, &[point])
+            .await,
+    })
+}
+
+/// Handler for inserting many images with one embedding call and one upsert
+///
+/// Images the AI service cannot embed are reported in `failed`; the rest
+/// are still inserted.
+#[utoipa::path(
+    post,
+    path = "/insert_images",
+    request_body = [CctvImageData],
+    params(InsertOptions),
+    responses(
+        (status = 200, description = "Batch processed", body = BatchInsertResponse),
+        (status = 400, description = "Empty or oversized batch", body = ErrorResponse),
+        (status = 502, description = "AI service or Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Insertion API"
+)]
+#[post("/insert_images")]
+pub async fn insert_images(
+    _: Authorized<Writer>,
+    tenant: Tenant,
+    state: web::Data<AppState>,
+    payload: web::Json<Vec<CctvImageData>>,
+    options: web::Query<InsertOptions>,
+) -> Result<HttpResponse, AppError> {
+    let response = insert_batch(&state, payload.into_inner(), &options, tenant.id()).await?;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Embed and store a batch of images, shared by `POST /insert_images` and
+/// the gRPC API
+///
+/// Images are stored like in `insert_one`; a batch with an image of a camera
+/// registered to another tenant is rejected as a whole.
+pub async fn insert_batch(
+    state: &AppState,
+    images: Vec<CctvImageData>,
+    options: &InsertOptions,
+    tenant: Option<&str>,
+) -> Result<BatchInsertResponse, AppError> {
+    if images.is_empty() {
+        return Err(AppError::InvalidRequest("No images provided".to_string()));
+    }
+    if images.len() > technical::MAX_INSERT_BATCH {
+        return Err(AppError::InvalidRequest(format!(
+            "Too many images: {} (max {})",
+            images.len(),
+            technical::MAX_INSERT_BATCH
+        )));
+    }
+    let tenants = images
+        .iter()
+        .map(|image| {
+            let camera_tenant = state.scheduler.cameras.tenant(&image.cctv_id);
+            image_tenant(&image.cctv_id, camera_tenant, tenant)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Embed the images sent without an embedding, one AI service call per
+    // model of their collections
+    let models = &state.scheduler.embedding_models;
+    let mut by_model: Vec<(&EmbeddingModel, Vec<usize>)> = Vec::new();
+    for (index, image) in images.iter().enumerate() {
+        if image.embedding.is_some() {
+            continue;
+        }
+        let model = models.for_collection(state.router.collection_for(&image.cctv_id));
+        match by_model.iter_mut().find(|(m, _)| *m == model) {
+            Some((_, indexes)) => indexes.push(index),
+            None => by_model.push((model, vec![index])),
+        }
+    }
+    let mut embedded = HashMap::new();
+    for (model, indexes) in by_model {
+        let image_paths = indexes
+            .iter()
+            .map(|&i| images[i].file_path.clone())
+            .collect();
+        let batch_result = get_image_embedding(
+            &state.http_client,
+            &model.ai_service_url,
+            image_paths,
+            AiPriority::Bulk,
+        )
+        .await?;
+
+        // Results are returned in request order
+        if batch_result.results.len() != indexes.len() {
+            return Err(AppError::AiService(format!(
+                "AI service returned {} results for {} images",
+                batch_result.results.len(),
+                indexes.len()
+            )));
+        }
+        embedded.extend(indexes.into_iter().zip(batch_result.results));
+    }
+
+    let hashes = content_hashes(state, &images).await;
+    let captions = image_captions(state, &images.iter().collect::<Vec<_>>()).await;
+
+    // Points grouped by the (shard) collection of their camera
+    let mut batches: Vec<(&str, Vec<PointStruct>)> = Vec::new();
+    let mut inserted = Vec::with_capacity(images.len());
+    let mut failed = Vec::new();
+    for (index, image) in images.iter().enumerate() {
+        let collection_name = state.router.collection_for(&image.cctv_id);
+        let model = state
+            .scheduler
+            .embedding_models
+            .for_collection(collection_name);
+        let (embedding, error) = match &image.embedding {
+            Some(vector) => (Some(vector.clone()), None),
+            None => embedded
+                .remove(&index)
+                .map(|result| (result.embedding, result.error))
+                .unwrap_or_default(),
+        };
+        match (embedding, error) {
+            (Some(vector), _) => {
+                if let Err(e) = validate_embedding(
+                    &vector,
+                    model.vector_size,
+                    "ingest",
+                    &image.filename,
+                    &state.metrics,
+                ) {
+                    dead_letter(state, image, &e);
+                    failed.push(BatchInsertFailure {
+                        id: image.id as u64,
+                        error: e.to_string(),
+                    });
+                    continue;
+                }
+                let caption = image_caption(image, &captions);
+                let model = models.for_collection(collection_name);
+                let vectors = image_vectors(state, model, vector, caption).await;
+                let hash = hashes.get(&image.file_path);
+                let tenant = tenants[index].as_deref();
+                let point = match image_point(state, image, vectors, caption, hash, tenant) {
+                    Ok(point) => point,
+                    Err(e) => {
+                        dead_letter(state, image, &e);
+                        failed.push(BatchInsertFailure {
+                            id: image.id as u64,
+                            error: e.to_string(),
+                        });
+                        continue;
+                    }
+                };
+                match batches.iter_mut().find(|(c, _)| *c == collection_name) {
+                    Some((_, points)) => points.push(point),
+                    None => batches.push((collection_name, vec![point])),
+                }
+                inserted.push(image.id as u64);
+            }
+            (None, error) => failed.push(BatchInsertFailure {
+                id: image.id as u64,
+                error: error.unwrap_or_else(|| "No embedding returned".to_string()),
+            }),
+        }
+    }
+
+    let mut verification_mismatches: Option<Vec<String>> = None;
+    for (collection_name, points) in batches {
+        upsert_points(state, options, collection_name, points.clone()).await?;
+
+        if let Some(mismatches) =
+            verify_if_requested(state, options, collection_name, &points).await
+        {
+            verification_mismatches
+                .get_or_insert_with(Vec::new)
+                .extend(mismatches);
+        }
+    }
+
+    info!(
+        inserted = inserted.len(),
+        failed = failed.len(),
+        "Batch insert finished"
+    );
+    Ok(BatchInsertResponse {
+        inserted,
+        failed,
+        verification_mismatches,
+    })
+}
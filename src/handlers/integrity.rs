@@ -0,0 +1,70 @@
+//! Integrity Handlers
+//!
+//! Re-hash a sample of stored images and report those whose content changed
+//! since ingest.
+
+use super::AppState;
+use crate::config::technical;
+use crate::error::AppError;
+use crate::middleware::{Admin, Authorized};
+use crate::models::admin::IntegrityVerifyRequest;
+use actix_web::{HttpResponse, get, post, web};
+use tracing::{Instrument, info, info_span};
+
+/// Handler starting a background verification of stored image hashes
+#[utoipa::path(
+    post,
+    path = "/admin/integrity/verify",
+    request_body = IntegrityVerifyRequest,
+    responses(
+        (status = 202, description = "Verification started", body = IntegrityStatus),
+        (status = 400, description = "Invalid sample size", body = ErrorResponse),
+        (status = 409, description = "Verification already running", body = IntegrityStatus)
+    ),
+    tag = "Admin API"
+)]
+#[post("/admin/integrity/verify")]
+pub async fn verify_integrity(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+    request: web::Json<IntegrityVerifyRequest>,
+) -> Result<HttpResponse, AppError> {
+    let sample_size = request
+        .sample_size
+        .unwrap_or(technical::INTEGRITY_SAMPLE_SIZE);
+    if !(1..=technical::MAX_INTEGRITY_SAMPLE_SIZE).contains(&sample_size) {
+        return Err(AppError::InvalidRequest(format!(
+            "sample_size must be between 1 and {}",
+            technical::MAX_INTEGRITY_SAMPLE_SIZE
+        )));
+    }
+    if !state.integrity.try_start(sample_size) {
+        return Ok(HttpResponse::Conflict().json(state.integrity.status()));
+    }
+
+    info!(target: "audit", action = "verify_integrity", sample_size, "Integrity verification started");
+    let integrity = state.integrity.clone();
+    let qdrant = state.qdrant.clone();
+    let router = state.router.clone();
+    let client = state.http_client.clone();
+    tokio::spawn(
+        async move { integrity.run(&qdrant, &router, &client).await }
+            .instrument(info_span!("integrity")),
+    );
+
+    Ok(HttpResponse::Accepted().json(state.integrity.status()))
+}
+
+/// Handler reporting the progress and mismatches of the last verification
+#[utoipa::path(
+    get,
+    path = "/admin/integrity",
+    responses(
+        (status = 200, description = "Verification progress and mismatches", body = IntegrityStatus)
+    ),
+    tag = "Admin API"
+)]
+#[get("/admin/integrity")]
+pub async fn integrity_status(_: Authorized<Admin>, state: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok().json(state.integrity.status())
+}
@@ -0,0 +1,231 @@
+//! Live Ingest Handler
+//!
+//! `GET /ws` upgrades to a WebSocket that pushes a JSON notification for
+//! every newly stored point, so dashboards update without polling. The
+//! stream is one-way: pings are answered and a close is echoed, other
+//! messages from the client are ignored.
+
+use super::AppState;
+use crate::error::AppError;
+use crate::middleware::{Authorized, Reader, Tenant};
+use crate::services::IngestNotification;
+use actix_web::{HttpRequest, HttpResponse, get, web};
+use actix_ws::{CloseCode, Message, MessageStream, Session};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// Keeps idle connections open through proxies and detects dead clients
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Handler streaming newly ingested images over a WebSocket
+///
+/// Each text message is an `IngestNotification` (`point_id`, `camera_id`,
+/// `filename`, `datetime`, `vehicle_class`), only of the request's tenant
+/// while tenancy is enabled.
+#[utoipa::path(
+    get,
+    path = "/ws",
+    responses(
+        (status = 101, description = "Switched to a WebSocket pushing one JSON message per stored point"),
+        (status = 400, description = "Not a WebSocket upgrade request", body = ErrorResponse)
+    ),
+    tag = "Insertion API"
+)]
+#[get("/ws")]
+pub async fn live_ingest(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    req: HttpRequest,
+    payload: web::Payload,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let (response, session, messages) = actix_ws::handle(&req, payload)
+        .map_err(|e| AppError::InvalidRequest(format!("Expected a WebSocket upgrade: {}", e)))?;
+    let feed = &state.scheduler.ingest_feed;
+    let notifications = feed.subscribe();
+    info!(
+        subscribers = feed.subscribers(),
+        "Live ingest subscriber connected"
+    );
+
+    actix_web::rt::spawn(stream_notifications(
+        session,
+        messages,
+        notifications,
+        feed.closing(),
+        tenant,
+    ));
+    Ok(response)
+}
+
+/// Push the notifications `tenant` may see until the client or the server
+/// closes the connection
+async fn stream_notifications(
+    mut session: Session,
+    mut messages: MessageStream,
+    mut notifications: broadcast::Receiver<IngestNotification>,
+    closing: CancellationToken,
+    tenant: Tenant,
+) {
+    let mut pings = tokio::time::interval_at(Instant::now() + PING_INTERVAL, PING_INTERVAL);
+    // A failed send means the client is gone and there is nothing to close
+    let reason = loop {
+        tokio::select! {
+            _ = closing.cancelled() => break Some(CloseCode::Away.into()),
+            _ = pings.tick() => {
+                if session.ping(b"").await.is_err() {
+                    return;
+                }
+            }
+            message = messages.recv() => match message {
+                Some(Ok(Message::Ping(data))) => {
+                    if session.pong(&data).await.is_err() {
+                        return;
+                    }
+                }
+                // Echoing the client's close completes the closing handshake
+                Some(Ok(Message::Close(reason))) => break reason,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    debug!(error = %e, "Live ingest subscriber sent an invalid frame");
+                    break Some(CloseCode::Protocol.into());
+                }
+                None => return,
+            },
+            received = notifications.recv() => match received {
+                Ok(notification) if !notification.visible_to(tenant.id()) => {}
+                Ok(notification) => {
+                    let Ok(json) = serde_json::to_string(&notification) else {
+                        continue;
+                    };
+                    if session.text(json).await.is_err() {
+                        return;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "Live ingest subscriber fell behind");
+                }
+                Err(RecvError::Closed) => break Some(CloseCode::Away.into()),
+            },
+        }
+    };
+    let _ = session.close(reason).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{IngestFeed, PayloadBuilder};
+    use qdrant_client::qdrant::PointStruct;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    /// Server streaming `feed` on `/ws`, without authorization or tenancy
+    fn serve(feed: Arc<IngestFeed>) -> SocketAddr {
+        let server = actix_web::HttpServer::new(move || {
+            let feed = feed.clone();
+            actix_web::App::new().route(
+                "/ws",
+                web::get().to(move |req: HttpRequest, payload: web::Payload| {
+                    let feed = feed.clone();
+                    async move {
+                        let (response, session, messages) = actix_ws::handle(&req, payload)?;
+                        let notifications = feed.subscribe();
+                        let stream = stream_notifications(
+                            session,
+                            messages,
+                            notifications,
+                            feed.closing(),
+                            Tenant(None),
+                        );
+                        actix_web::rt::spawn(stream);
+                        Ok::<_, actix_web::Error>(response)
+                    }
+                }),
+            )
+        })
+        .workers(1)
+        .disable_signals()
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let addr = server.addrs()[0];
+        actix_web::rt::spawn(server.run());
+        addr
+    }
+
+    async fn connect(addr: SocketAddr) -> TcpStream {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(
+                b"GET /ws HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+                  Connection: Upgrade\r\nSec-WebSocket-Version: 13\r\n\
+                  Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        assert!(head.starts_with(b"HTTP/1.1 101"));
+        stream
+    }
+
+    /// Masked client frame of less than 126 bytes
+    fn client_frame(opcode: u8, data: &[u8]) -> Vec<u8> {
+        let mask = [0x37, 0xfa, 0x21, 0x3d];
+        let mut frame = vec![0x80 | opcode, 0x80 | data.len() as u8];
+        frame.extend_from_slice(&mask);
+        frame.extend(data.iter().zip(mask.iter().cycle()).map(|(b, m)| b ^ m));
+        frame
+    }
+
+    /// Opcode and payload of the next server frame
+    async fn read_frame(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let opcode = stream.read_u8().await.unwrap() & 0x0f;
+        let len = match stream.read_u8().await.unwrap() {
+            126 => stream.read_u16().await.unwrap() as usize,
+            127 => stream.read_u64().await.unwrap() as usize,
+            len => len as usize,
+        };
+        let mut data = vec![0; len];
+        stream.read_exact(&mut data).await.unwrap();
+        (opcode, data)
+    }
+
+    #[actix_web::test]
+    async fn test_live_ingest_frames() {
+        let feed = Arc::new(IngestFeed::default());
+        let addr = serve(feed.clone());
+
+        // Pongs are ignored, pings answered and a close echoed
+        let mut client = connect(addr).await;
+        for _ in 0..100 {
+            client.write_all(&client_frame(0xA, b"")).await.unwrap();
+        }
+        client.write_all(&client_frame(0x9, b"hi")).await.unwrap();
+        assert_eq!(read_frame(&mut client).await, (0xA, b"hi".to_vec()));
+        let normal = 1000u16.to_be_bytes().to_vec();
+        client.write_all(&client_frame(0x8, &normal)).await.unwrap();
+        assert_eq!(read_frame(&mut client).await, (0x8, normal));
+
+        // Stored points are pushed, and shutdown closes as going away
+        let mut client = connect(addr).await;
+        let payload = PayloadBuilder::new().string("camera_id", "cctv01").build();
+        feed.publish(&[PointStruct::new(7, vec![0.5], payload)]);
+        let (opcode, json) = read_frame(&mut client).await;
+        assert_eq!(opcode, 0x1);
+        let notification: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(notification["point_id"], 7);
+        assert_eq!(notification["camera_id"], "cctv01");
+        feed.close();
+        let going_away = 1001u16.to_be_bytes().to_vec();
+        assert_eq!(read_frame(&mut client).await, (0x8, going_away));
+    }
+}
@@ -0,0 +1,221 @@
+//! HTTP Request Handlers
+//!
+//! Handlers for the REST API endpoints.
+
+mod admin;
+mod alerts;
+mod backfill;
+mod cases;
+#[cfg(feature = "chaos")]
+mod chaos;
+mod delete;
+mod embed;
+mod etag;
+mod evaluation;
+mod events;
+mod export;
+mod ingest;
+mod insert;
+mod integrity;
+mod live;
+mod retag;
+mod scheduler;
+mod search;
+mod sessions;
+mod snapshots;
+mod system;
+mod validation;
+mod vehicle_types;
+
+pub use admin::*;
+pub use alerts::*;
+pub use backfill::*;
+pub use cases::*;
+#[cfg(feature = "chaos")]
+pub use chaos::*;
+pub use delete::*;
+pub use embed::*;
+pub use evaluation::*;
+pub use events::*;
+pub use export::*;
+pub use ingest::*;
+pub use insert::*;
+pub use integrity::*;
+pub use live::*;
+pub use retag::*;
+pub use scheduler::*;
+pub use search::*;
+pub use sessions::*;
+pub use snapshots::*;
+pub use system::*;
+pub use validation::*;
+pub use vehicle_types::*;
+
+use crate::backfill::Backfill;
+use crate::clients::cctv_client::CctvApi;
+use crate::config::TunablesHandle;
+use crate::features::FeatureRegistry;
+use crate::middleware::{ApiKeys, ClientRateLimiter, CorsPolicy, DeviceKeys, JwtAuth};
+use crate::scheduler::SchedulerContext;
+use crate::services::{
+    BackupVerifier, CameraDirectory, CaseStore, EmbeddingCache, EvalSetStore, IngestSequences,
+    IntegrityVerifier, MaintenanceMode, RequestMetrics, SearchSessions, ShadowSearch,
+    ShardRebalancer, ShardRouter, SloTracker, SnapshotRestorer, UrlRewriter, VectorLayout,
+};
+use qdrant_client::Qdrant;
+use std::sync::Arc;
+
+/// Application state shared across all web workers
+pub struct AppState {
+    pub qdrant: Arc<Qdrant>,
+    pub http_client: reqwest::Client,
+    pub ai_service_url: String,
+    /// CCTV API client, used to probe the token endpoint
+    pub cctv_api: CctvApi,
+    /// Maps cameras to their (shard) collection
+    pub router: Arc<ShardRouter>,
+    /// Background shard rebalancing job
+    pub rebalancer: Arc<ShardRebalancer>,
+    /// Background content hash verification job
+    pub integrity: Arc<IntegrityVerifier>,
+    /// REST client restoring collection snapshots
+    pub snapshots: Arc<SnapshotRestorer>,
+    /// Periodic restore check of the newest snapshots
+    pub backup_verifier: Arc<BackupVerifier>,
+    /// Resumable historical backfill job
+    pub backfill: Arc<Backfill>,
+    /// Directory for base64 search-by-image uploads
+    pub query_image_dir: String,
+    /// Directory `/embed/image` may read `image_path`s from
+    pub embed_image_root: Option<String>,
+    /// Identity of the configured embedding model
+    pub embedding_model: String,
+    /// Dimension of the stored embeddings
+    pub vector_size: usize,
+    /// Whether points hold named image and caption vectors
+    pub vector_layout: VectorLayout,
+    /// Runtime-tunable settings, reloadable without a restart
+    pub tunables: TunablesHandle,
+    /// Maintenance switch shared with the scheduler
+    pub maintenance: Arc<MaintenanceMode>,
+    /// Paths still served while in maintenance mode
+    pub maintenance_allowlist: Vec<String>,
+    /// Keys accepted by the API key guard
+    pub api_keys: Arc<ApiKeys>,
+    /// Bearer token validation for the JWT guard and role checks
+    pub jwt: Arc<JwtAuth>,
+    /// Keys of the edge devices allowed on `/ingest/batch`
+    pub device_keys: Arc<DeviceKeys>,
+    /// Batch sequences received from each edge device
+    pub ingest_sequences: Arc<IngestSequences>,
+    /// Per-client token buckets on the expensive endpoints, if configured
+    pub client_rate_limiter: Option<Arc<ClientRateLimiter>>,
+    /// Origins allowed to call the public API from a browser
+    pub cors: Arc<CorsPolicy>,
+    /// Maps stored image URLs to ones clients can load
+    pub url_rewriter: Arc<UrlRewriter>,
+    /// Camera names and locations returned with search results
+    pub cameras: Arc<CameraDirectory>,
+    /// Optional subsystems enabled for this process
+    pub features: Arc<FeatureRegistry>,
+    /// Per-endpoint request counters for `/metrics`
+    pub metrics: Arc<RequestMetrics>,
+    /// Latency SLOs evaluated over a rolling window
+    pub slo: Arc<SloTracker>,
+    /// Search sessions holding result feedback
+    pub sessions: Arc<SearchSessions>,
+    /// Investigator cases, saved to `CASES_PATH`
+    pub cases: Arc<CaseStore>,
+    /// Labeled evaluation sets, saved to `EVAL_SETS_PATH`
+    pub eval_sets: Arc<EvalSetStore>,
+    /// Embeddings served by `/embed/*`
+    pub embedding_cache: Arc<EmbeddingCache>,
+    /// Background replays of sampled searches against the shadow collection
+    pub shadow: Arc<ShadowSearch>,
+    /// Shared resources for manual fetch runs, including the run history
+    pub scheduler: SchedulerContext,
+}
+
+#[cfg(test)]
+impl AppState {
+    /// State configured by `vars` over the defaults, for requests against
+    /// single handlers; Qdrant and the AI service are not contacted up front
+    pub(crate) async fn for_tests(vars: &[(&str, &str)]) -> actix_web::web::Data<Self> {
+        use crate::config::Config;
+        use crate::services::{MaintenanceMode, SchedulerRunHistory, build_qdrant_client};
+
+        let lookup = |key: &str| {
+            vars.iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| value.to_string())
+        };
+        let config = Config::from_lookup(&lookup).unwrap();
+        let qdrant = Arc::new(build_qdrant_client(&config).unwrap());
+        let http_client = reqwest::Client::new();
+        let tunables = TunablesHandle::new(config.tunables());
+        let maintenance = Arc::new(MaintenanceMode::default());
+        let request_metrics = Arc::new(RequestMetrics::default());
+        let scheduler = SchedulerContext::new(
+            qdrant.clone(),
+            http_client.clone(),
+            config.clone(),
+            tunables.clone(),
+            maintenance.clone(),
+            request_metrics.clone(),
+            Arc::new(SchedulerRunHistory::default()),
+        );
+        let cctv_api = CctvApi::new(
+            config.cctv_api_url.clone(),
+            config.cctv_authorize_code.clone(),
+            config.cctv_user_auth.clone(),
+            config.cctv_client_id.clone(),
+        );
+
+        actix_web::web::Data::new(Self {
+            qdrant,
+            ai_service_url: config.ai_service_url.clone(),
+            cctv_api,
+            router: scheduler.router.clone(),
+            rebalancer: Arc::default(),
+            integrity: Arc::default(),
+            snapshots: Arc::new(SnapshotRestorer::new(&config).unwrap()),
+            backup_verifier: Arc::default(),
+            backfill: Arc::new(Backfill::load(&config.backfill_state_path).unwrap()),
+            query_image_dir: config.query_image_dir.clone(),
+            embed_image_root: config.embed_image_root.clone(),
+            embedding_model: config.embedding_model.clone(),
+            vector_size: config.vector_size,
+            vector_layout: config.vector_layout,
+            tunables,
+            maintenance,
+            maintenance_allowlist: config.maintenance_allowlist.clone(),
+            api_keys: Arc::new(ApiKeys::load(&config).unwrap()),
+            jwt: Arc::new(JwtAuth::load(&config, &http_client).await.unwrap()),
+            device_keys: Arc::new(DeviceKeys::load(&config).unwrap()),
+            ingest_sequences: Arc::default(),
+            client_rate_limiter: config.client_rate_limit_rps.map(|rps| {
+                Arc::new(ClientRateLimiter::new(
+                    rps,
+                    config.client_rate_limit_burst,
+                    config.client_rate_limit_paths.clone(),
+                ))
+            }),
+            cors: Arc::new(CorsPolicy::new(&config)),
+            url_rewriter: Arc::new(UrlRewriter::new(config.image_url_rewrites.clone())),
+            cameras: scheduler.cameras.clone(),
+            features: Arc::new(FeatureRegistry::new(&[]).unwrap()),
+            metrics: request_metrics,
+            slo: Arc::new(SloTracker::new(
+                config.slo_targets.clone(),
+                config.slo_window_minutes,
+            )),
+            sessions: Arc::default(),
+            cases: Arc::new(CaseStore::load(&config.cases_path).unwrap()),
+            eval_sets: Arc::new(EvalSetStore::load(&config.eval_sets_path).unwrap()),
+            embedding_cache: Arc::new(EmbeddingCache::new(0, std::time::Duration::ZERO)),
+            shadow: Arc::default(),
+            http_client,
+            scheduler,
+        })
+    }
+}
@@ -0,0 +1,174 @@
+//! Re-tagging Handlers
+//!
+//! Bulk payload patches on stored images, e.g. assigning a camera group or
+//! fixing a wrong `camera_id`.
+
+use super::AppState;
+use crate::error::AppError;
+use crate::middleware::{Admin, Authorized};
+use crate::models::admin::{RetagRequest, RetagResponse};
+use crate::services::{ChaosTarget, build_image_filter, inject};
+use actix_web::{HttpResponse, post, web};
+use qdrant_client::Payload;
+use qdrant_client::qdrant::{CountPointsBuilder, Filter, SetPayloadPointsBuilder};
+use serde_json::{Map, Value};
+use tracing::{info, instrument};
+
+/// Handler setting payload fields on all images matching a camera and/or datetime range
+#[utoipa::path(
+    post,
+    path = "/admin/retag",
+    request_body = RetagRequest,
+    responses(
+        (status = 200, description = "Matching images patched (or counted with `dry_run`)", body = RetagResponse),
+        (status = 400, description = "No filter given, empty patch or invalid date", body = ErrorResponse),
+        (status = 502, description = "Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[post("/admin/retag")]
+pub async fn retag_images(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+    request: web::Json<RetagRequest>,
+) -> Result<HttpResponse, AppError> {
+    validate_patch(&request.set)?;
+
+    let camera_ids = request.camera_id.clone().map(|id| vec![id]);
+    let filter = build_image_filter(
+        request.start_date.as_deref(),
+        request.end_date.as_deref(),
+        camera_ids.as_deref(),
+    )?
+    // Refuse to patch the whole collection by accident
+    .ok_or_else(|| {
+        AppError::InvalidRequest(
+            "At least one of camera_id, start_date or end_date is required".to_string(),
+        )
+    })?;
+
+    inject(ChaosTarget::Qdrant).await?;
+
+    let new_camera_id = request.set.get("camera_id").and_then(Value::as_str);
+    let mut matched = 0;
+    let mut rebalance_required = false;
+    for collection_name in state.router.collections_for(camera_ids.as_deref()) {
+        let patched = retag_matching(
+            &state,
+            &collection_name,
+            &filter,
+            &request.set,
+            request.dry_run,
+        )
+        .await?;
+        matched += patched;
+
+        if patched > 0
+            && new_camera_id.is_some_and(|id| state.router.collection_for(id) != collection_name)
+        {
+            rebalance_required = true;
+        }
+    }
+
+    if !request.dry_run {
+        info!(
+            target: "audit",
+            action = "retag",
+            matched,
+            camera_id = request.camera_id.as_deref().unwrap_or("any"),
+            start_date = request.start_date.as_deref().unwrap_or("-"),
+            end_date = request.end_date.as_deref().unwrap_or("-"),
+            set = %serde_json::to_string(&request.set).unwrap_or_default(),
+            "Re-tagged images"
+        );
+    }
+    Ok(HttpResponse::Ok().json(RetagResponse {
+        matched,
+        dry_run: request.dry_run,
+        rebalance_required,
+    }))
+}
+
+/// Reject patches that are empty or would break camera routing
+fn validate_patch(set: &Map<String, Value>) -> Result<(), AppError> {
+    if set.is_empty() {
+        return Err(AppError::InvalidRequest(
+            "set must contain at least one field".to_string(),
+        ));
+    }
+    if set.keys().any(|key| key.trim().is_empty()) {
+        return Err(AppError::InvalidRequest(
+            "Payload field names must not be empty".to_string(),
+        ));
+    }
+    if set
+        .get("camera_id")
+        .is_some_and(|id| id.as_str().is_none_or(|id| id.trim().is_empty()))
+    {
+        return Err(AppError::InvalidRequest(
+            "camera_id must be a non-empty string".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Count the points in one collection matching `filter` and patch them
+/// unless `dry_run` is set
+#[instrument(skip(state, filter, set))]
+async fn retag_matching(
+    state: &AppState,
+    collection_name: &str,
+    filter: &Filter,
+    set: &Map<String, Value>,
+    dry_run: bool,
+) -> Result<u64, AppError> {
+    let matched = state
+        .qdrant
+        .count(
+            CountPointsBuilder::new(collection_name)
+                .filter(filter.clone())
+                .exact(true),
+        )
+        .await
+        .map_err(|e| AppError::Qdrant(format!("Qdrant count error: {}", e)))?
+        .result
+        .map(|r| r.count)
+        .unwrap_or_default();
+
+    if dry_run || matched == 0 {
+        return Ok(matched);
+    }
+
+    state
+        .qdrant
+        .set_payload(
+            SetPayloadPointsBuilder::new(collection_name, Payload::from(set.clone()))
+                .points_selector(filter.clone())
+                .wait(true),
+        )
+        .await
+        .map_err(|e| AppError::Qdrant(format!("Qdrant set payload error: {}", e)))?;
+
+    Ok(matched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn patch(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn test_validate_patch() {
+        assert!(validate_patch(&patch(json!({ "camera_group": "north" }))).is_ok());
+        assert!(validate_patch(&patch(json!({ "camera_id": "cctv02" }))).is_ok());
+
+        assert!(validate_patch(&Map::new()).is_err());
+        assert!(validate_patch(&patch(json!({ " ": 1 }))).is_err());
+        assert!(validate_patch(&patch(json!({ "camera_id": 7 }))).is_err());
+        assert!(validate_patch(&patch(json!({ "camera_id": "" }))).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! Scheduler Handlers
+//!
+//! Fetch run history and on-demand fetches for backfills and re-ingestion.
+
+use super::AppState;
+use super::etag::json_with_etag;
+use crate::config::technical;
+use crate::error::AppError;
+use crate::features::Feature;
+use crate::middleware::{Admin, Authorized, Reader};
+use crate::models::scheduler::{
+    SchedulerRunsQuery, SchedulerRunsResponse, TriggerFetchRequest, TriggerFetchResponse,
+};
+use crate::scheduler::{FetchWindow, execute_run, start_run};
+use crate::services::{RunTrigger, parse_rfc3339_utc};
+use actix_web::{HttpRequest, HttpResponse, Responder, get, post, web};
+use chrono::{Duration, Utc};
+use tracing::{Instrument, info, info_span};
+
+/// Handler listing the outcomes of recent fetch runs
+#[utoipa::path(
+    get,
+    path = "/scheduler/runs",
+    params(SchedulerRunsQuery),
+    responses(
+        (status = 200, description = "Recent runs, newest first", body = SchedulerRunsResponse),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag")
+    ),
+    tag = "System API"
+)]
+#[get("/scheduler/runs")]
+pub async fn scheduler_runs(
+    _: Authorized<Reader>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<SchedulerRunsQuery>,
+) -> impl Responder {
+    json_with_etag(
+        &req,
+        &SchedulerRunsResponse {
+            scheduler_enabled: state.features.is_enabled(Feature::Scheduler),
+            runs: state
+                .scheduler
+                .run_history
+                .recent(query.limit.unwrap_or(20)),
+        },
+    )
+}
+
+/// Handler returning one fetch run, e.g. a manually triggered job
+#[utoipa::path(
+    get,
+    path = "/scheduler/runs/{run_id}",
+    params(("run_id" = String, Path, description = "Run (job) ID")),
+    responses(
+        (status = 200, description = "The run", body = SchedulerRun),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        (status = 404, description = "Unknown run, or too old to be kept", body = ErrorResponse)
+    ),
+    tag = "System API"
+)]
+#[get("/scheduler/runs/{run_id}")]
+pub async fn scheduler_run(
+    _: Authorized<Reader>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    run_id: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let run = state
+        .scheduler
+        .run_history
+        .get(&run_id)
+        .ok_or_else(|| AppError::NotFound(format!("Unknown run: {}", run_id)))?;
+    Ok(json_with_etag(&req, &run))
+}
+
+/// Handler starting a fetch run now, optionally for one camera or a past window
+#[utoipa::path(
+    post,
+    path = "/scheduler/trigger",
+    request_body = TriggerFetchRequest,
+    responses(
+        (status = 202, description = "Run started in the background", body = TriggerFetchResponse),
+        (status = 400, description = "Invalid date range or limit", body = ErrorResponse),
+        (status = 409, description = "A manual run is already in progress", body = SchedulerRun),
+        (status = 503, description = "The service is shutting down", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[post("/scheduler/trigger")]
+pub async fn trigger_fetch(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+    request: web::Json<TriggerFetchRequest>,
+) -> Result<HttpResponse, AppError> {
+    if state.scheduler.gate.is_closed() {
+        return Err(AppError::Unavailable {
+            message: "Shutting down, no new fetch runs are started".to_string(),
+            retry_after_secs: technical::SHUTDOWN_RETRY_AFTER_SECS,
+        });
+    }
+    let window = fetch_window(&request, &state)?;
+    let run = start_run(&window, RunTrigger::Manual);
+    if let Some(running) = state.scheduler.run_history.record_exclusive(run.clone()) {
+        return Ok(HttpResponse::Conflict().json(running));
+    }
+
+    info!(
+        target: "audit",
+        action = "trigger_fetch",
+        run_id = %run.id,
+        cctv_id = window.cctv_id.as_deref().unwrap_or("all"),
+        date_start = %window.date_start,
+        date_stop = %window.date_stop,
+        limit = window.limit,
+        reingest = window.reingest,
+        "Manual fetch triggered"
+    );
+
+    let response = TriggerFetchResponse {
+        job_id: run.id.clone(),
+        status_url: format!("/scheduler/runs/{}", run.id),
+    };
+    let ctx = state.scheduler.clone();
+    tokio::spawn(
+        async move { execute_run(&ctx, run, &window).await }
+            .instrument(info_span!("scheduler_run")),
+    );
+    Ok(HttpResponse::Accepted().json(response))
+}
+
+/// Resolve the request overrides against the current tunables
+fn fetch_window(request: &TriggerFetchRequest, state: &AppState) -> Result<FetchWindow, AppError> {
+    let tunables = state.tunables.current();
+
+    let stop = match request.end_date.as_deref() {
+        Some(end) => parse_rfc3339_utc(end)?,
+        None => Utc::now(),
+    };
+    let start = match request.start_date.as_deref() {
+        Some(start) => parse_rfc3339_utc(start)?,
+        None => stop - Duration::minutes(tunables.fetch_every_time),
+    };
+    if start >= stop {
+        return Err(AppError::InvalidRequest(
+            "start_date must be before end_date".to_string(),
+        ));
+    }
+
+    let limit = request.limit.unwrap_or(tunables.fetch_limit);
+    if limit == 0 {
+        return Err(AppError::InvalidRequest("limit must be >= 1".to_string()));
+    }
+
+    let cctv_id = request
+        .cctv_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string);
+
+    Ok(FetchWindow {
+        reingest: request.reingest,
+        ..FetchWindow::between(start, stop, cctv_id, limit)
+    })
+}
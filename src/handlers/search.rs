@@ -0,0 +1,1403 @@
+//! Search Handlers
+//!
+//! Text-to-image and image-to-image vehicle search, and sightings of one
+//! query correlated across cameras.
+
+use super::{AppState, ValidatedJson};
+use crate::config::{Tunables, technical};
+use crate::error::AppError;
+use crate::middleware::{Admin, Authorized, Reader, Tenant};
+use crate::models::cctv::CameraEntry;
+use crate::models::search::{
+    CameraGroup, CorrelatedMatch, CorrelatedSearchRequest, HybridCalibrationRequest,
+    HybridCalibrationResponse, RecommendRequest, ResultDetail, ScoreBreakdown,
+    SearchByImageRequest, SearchDebugResponse, SearchParamsRequest, SearchRequest, SearchResult,
+    VectorSpace,
+};
+use crate::services::{
+    AiPriority, ChaosTarget, Dependency, EmbeddingModel, HybridFusion, LabeledCandidates,
+    LocalImage, QueryImage, SessionExamples, ShadowTarget, UrlRewriter, VEHICLE_TYPE_LABEL_FIELD,
+    YOLO_LABEL_FIELD, apply_recency_boost, best_trial, browse_latest, build_image_filter,
+    circuit_breakers, collapse_bursts, combine_vectors, correlate_hits, default_alphas,
+    extra_conditions, extract_double, extract_integer, extract_string, fanout_search,
+    fetch_examples, get_image_embedding, group_id_to_string, group_search, guarded,
+    hybrid_candidates, hybrid_search, inject, label_conditions, merge_by_rank, parse_hybrid_fusion,
+    parse_prompt_expression, parse_read_consistency, parse_rfc3339_utc, point_id_to_string,
+    query_terms, recommend_fanout, resolve_half_life, simulate_search_params, split_datetime_range,
+    sweep_alphas, text_match_condition, validate_embedding, weighted_fusion, with_conditions,
+    with_tenant,
+};
+use actix_web::{HttpResponse, post, web};
+use qdrant_client::qdrant::{
+    Condition, Filter, PointGroup, ScoredPoint, SearchParams, SearchPoints,
+};
+use std::collections::HashMap;
+use std::time::Instant;
+use tracing::{error, info, warn};
+
+/// Set on `/search` responses served without the AI service
+pub const DEGRADED_SEARCH_HEADER: &str = "X-Search-Degraded";
+
+/// Map Qdrant scored points to API search results with the requested fields
+///
+/// `base_scores` holds the scores of boosted points before the recency boost;
+/// image URLs are rewritten by `urls`, and hits of registered `cameras` get
+/// their name and location.
+fn to_search_results(
+    points: Vec<ScoredPoint>,
+    detail: ResultDetail,
+    base_scores: &HashMap<String, f32>,
+    urls: &UrlRewriter,
+    cameras: &HashMap<String, CameraEntry>,
+) -> Vec<SearchResult> {
+    points
+        .into_iter()
+        .map(|point| {
+            let id = point
+                .id
+                .as_ref()
+                .map(point_id_to_string)
+                .unwrap_or_default();
+            let mut result = SearchResult {
+                filename: None,
+                id,
+                score: point.score,
+                datetime: extract_string(&point.payload, "datetime"),
+                camera_id: extract_string(&point.payload, "camera_id"),
+                camera_name: None,
+                location: None,
+                lat: None,
+                lon: None,
+                file_path: urls
+                    .rewrite(&extract_string(&point.payload, "image"))
+                    .into_owned(),
+                frame: None,
+                vehicle_class: None,
+                confidence: None,
+                vehicle_type_label: None,
+                yolo_label: None,
+                caption: None,
+                payload: None,
+                score_breakdown: None,
+            };
+            if detail == ResultDetail::Minimal {
+                return result;
+            }
+
+            if let Some(camera) = cameras.get(&result.camera_id) {
+                result.camera_name = camera.name.clone();
+                result.location = camera.location.clone();
+                result.lat = camera.lat;
+                result.lon = camera.lon;
+            }
+            result.filename = Some(extract_string(&point.payload, "filename"));
+            result.frame = extract_integer(&point.payload, "frame");
+            result.vehicle_class = Some(extract_string(&point.payload, "vehicle_class"))
+                .filter(|class| !class.is_empty());
+            result.confidence = extract_double(&point.payload, "confidence").map(|c| c as f32);
+            result.vehicle_type_label =
+                Some(extract_string(&point.payload, VEHICLE_TYPE_LABEL_FIELD))
+                    .filter(|label| !label.is_empty());
+            result.yolo_label = Some(extract_string(&point.payload, YOLO_LABEL_FIELD))
+                .filter(|label| !label.is_empty());
+            result.caption = Some(extract_string(&point.payload, "caption"))
+                .filter(|caption| !caption.is_empty());
+
+            if detail == ResultDetail::Full {
+                let base_score = base_scores.get(&result.id).copied().unwrap_or(point.score);
+                result.score_breakdown = Some(ScoreBreakdown {
+                    base_score,
+                    recency_factor: if base_score == 0.0 {
+                        1.0
+                    } else {
+                        point.score / base_score
+                    },
+                });
+                let mut payload: serde_json::Map<_, _> = point
+                    .payload
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into_json()))
+                    .collect();
+                payload.insert("image".to_string(), result.file_path.clone().into());
+                result.payload = Some(payload);
+            }
+            result
+        })
+        .collect()
+}
+
+/// Map Qdrant point groups to per-camera API results
+fn to_camera_groups(
+    groups: Vec<PointGroup>,
+    detail: ResultDetail,
+    urls: &UrlRewriter,
+    cameras: &HashMap<String, CameraEntry>,
+) -> Vec<CameraGroup> {
+    groups
+        .into_iter()
+        .map(|group| CameraGroup {
+            camera_id: group
+                .id
+                .as_ref()
+                .map(group_id_to_string)
+                .unwrap_or_default(),
+            hits: to_search_results(group.hits, detail, &HashMap::new(), urls, cameras),
+        })
+        .collect()
+}
+
+/// Embedding of a search query by `model`, combining weighted prompts in arithmetic mode
+async fn query_vector(
+    state: &AppState,
+    model: &EmbeddingModel,
+    payload: &SearchRequest,
+) -> Result<Vec<f32>, AppError> {
+    if !payload.arithmetic {
+        return text_embedding(state, model, &payload.query).await;
+    }
+
+    let prompts = parse_prompt_expression(&payload.query)?;
+    let mut weighted = Vec::with_capacity(prompts.len());
+    for prompt in prompts {
+        let vector = text_embedding(state, model, &prompt.text).await?;
+        weighted.push((prompt.weight, vector));
+    }
+    combine_vectors(&weighted)
+}
+
+/// Embedding of a query text, cached so repeated dashboard queries skip the AI service
+async fn text_embedding(
+    state: &AppState,
+    model: &EmbeddingModel,
+    text: &str,
+) -> Result<Vec<f32>, AppError> {
+    let (vector, _) = state
+        .embedding_cache
+        .text_embedding(
+            &state.http_client,
+            model,
+            text,
+            AiPriority::Interactive,
+            &state.metrics,
+        )
+        .await?;
+    Ok(vector)
+}
+
+/// Handler for searching vehicles with optional datetime filtering
+#[utoipa::path(
+    post,
+    path = "/search",
+    request_body(content = SearchRequest, examples(
+        ("English" = (summary = "Text query", value = json!({
+            "query": "white pickup truck",
+            "top_k": 5,
+            "start_date": "2025-10-08T00:00:00+07:00",
+            "end_date": "2025-10-08T23:59:59+07:00",
+            "camera_ids": ["cctv01"]
+        }))),
+        ("Thai" = (summary = "Thai text query (รถกระบะสีขาว = white pickup truck)", value = json!({
+            "query": "รถกระบะสีขาว",
+            "top_k": 5,
+            "vehicle_classes": ["pickup"],
+            "min_confidence": 0.8
+        }))),
+        ("Hybrid" = (summary = "Vector search fused with a filename match", value = json!({
+            "query": "cctv01 truck",
+            "hybrid": true,
+            "hybrid_fusion": "rrf"
+        }))),
+        ("Arithmetic" = (summary = "Weighted prompts", value = json!({
+            "query": "+1.0 \"pickup truck\" -0.5 \"delivery van\"",
+            "arithmetic": true
+        })))
+    )),
+    responses(
+        (status = 200, description = "Search completed successfully (a SearchDebugResponse when `debug` is set, [CameraGroup] when `group_by_camera` is set)", body = [SearchResult],
+            headers(("X-Search-Degraded" = String, description = "`browse` when the AI service was unavailable and the newest matching images were returned unranked"))),
+        (status = 400, description = "Bad request; `validation_failed` lists the invalid fields in `details`", body = ErrorResponse),
+        (status = 422, description = "The AI service returned an embedding of the wrong dimension", body = ErrorResponse),
+        (status = 429, description = "Per-client rate limit exceeded, retry after `Retry-After` seconds", body = ErrorResponse),
+        (status = 502, description = "AI service or Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Search API"
+)]
+#[post("/search")]
+pub async fn search_vehicles(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    state: web::Data<AppState>,
+    payload: ValidatedJson<SearchRequest>,
+) -> Result<HttpResponse, AppError> {
+    Ok(match run_search(&state, &payload, tenant.id()).await? {
+        SearchOutcome::Results(results) => HttpResponse::Ok().json(results),
+        SearchOutcome::Browsed(results) => HttpResponse::Ok()
+            .insert_header((DEGRADED_SEARCH_HEADER, "browse"))
+            .json(results),
+        SearchOutcome::Groups(groups) => HttpResponse::Ok().json(groups),
+        SearchOutcome::Debug(report) => HttpResponse::Ok().json(report),
+    })
+}
+
+/// Answer of a text search, by mode
+pub enum SearchOutcome {
+    Results(Vec<SearchResult>),
+    /// The newest matching images, unranked, while the AI service is down
+    Browsed(Vec<SearchResult>),
+    Groups(Vec<CameraGroup>),
+    Debug(SearchDebugResponse),
+}
+
+/// Run a validated text search, shared by `POST /search` and the gRPC API,
+/// over the images of `tenant` while tenancy is enabled
+pub async fn run_search(
+    state: &AppState,
+    payload: &SearchRequest,
+    tenant: Option<&str>,
+) -> Result<SearchOutcome, AppError> {
+    // Log search request
+    let start_time = chrono::Utc::now();
+    let datetime_range = match (&payload.start_date, &payload.end_date) {
+        (None, None) => "all time".to_string(),
+        (Some(s), None) => format!("from {}", s),
+        (None, Some(e)) => format!("to {}", e),
+        (Some(s), Some(e)) => format!("{} to {}", s, e),
+    };
+    let top_k = payload.top_k.unwrap_or(5);
+    info!(
+        query = %payload.query,
+        range = %datetime_range,
+        top_k,
+        "Search request"
+    );
+
+    // Debug mode compares raw Qdrant results and groups are ranked by
+    // Qdrant, so neither is boosted nor collapsed
+    let half_life = resolve_half_life(
+        payload.recency_half_life_hours,
+        state.tunables.current().search_recency_half_life_hours,
+    )?
+    .filter(|_| !payload.debug && !payload.group_by_camera);
+    let collapse_window = payload
+        .collapse_window_s
+        .filter(|&secs| secs > 0 && !payload.debug && !payload.group_by_camera);
+    let examples = session_examples(state, payload.session_id.as_deref(), tenant)?;
+    if payload.debug && examples.is_some() {
+        return Err(AppError::InvalidRequest(
+            "Debug mode cannot be combined with session feedback".to_string(),
+        ));
+    }
+    // Session examples are image embeddings
+    let vector_name = state.vector_layout.using(payload.vector_space)?;
+    if examples.is_some() && payload.vector_space != VectorSpace::Image {
+        return Err(AppError::InvalidRequest(
+            "Session feedback only steers image searches".to_string(),
+        ));
+    }
+    let hybrid = hybrid_text(state, payload)?;
+    let fused = hybrid
+        .as_ref()
+        .filter(|(fusion, _)| *fusion != HybridFusion::Filter);
+    if fused.is_some() && (payload.debug || examples.is_some()) {
+        return Err(AppError::InvalidRequest(
+            "Fused hybrid search cannot be combined with debug mode or session feedback; \
+             use hybrid_fusion \"filter\""
+                .to_string(),
+        ));
+    }
+    let group_size = payload.group_size.unwrap_or(1);
+    if payload.group_by_camera {
+        if payload.debug || examples.is_some() || fused.is_some() {
+            return Err(AppError::InvalidRequest(
+                "Grouped search cannot be combined with debug mode, session feedback or fused \
+                 hybrid search"
+                    .to_string(),
+            ));
+        }
+        if !(1..=technical::MAX_GROUP_SIZE).contains(&group_size) {
+            return Err(AppError::InvalidRequest(format!(
+                "group_size must be between 1 and {}",
+                technical::MAX_GROUP_SIZE
+            )));
+        }
+    }
+
+    // Build search request; filter mode only keeps images matching the text
+    let mut conditions =
+        label_conditions(payload.vehicle_classes.as_deref(), payload.min_confidence)?;
+    conditions.extend(extra_conditions(payload.filters.as_ref())?);
+    if let Some((HybridFusion::Filter, text)) = &hybrid {
+        conditions.push(text.clone());
+    }
+    let filter = with_tenant(
+        with_conditions(
+            build_image_filter(
+                payload.start_date.as_deref(),
+                payload.end_date.as_deref(),
+                payload.camera_ids.as_deref(),
+            )?,
+            conditions,
+        ),
+        tenant,
+    );
+
+    let tunables = state.tunables.current();
+    if tunables.search_ai_fallback
+        && !payload.debug
+        && !payload.group_by_camera
+        && ai_circuit_open()
+    {
+        // Fused hybrid searches still match their keywords while browsing
+        let filter = match &hybrid {
+            Some((fusion, text)) if *fusion != HybridFusion::Filter => {
+                with_conditions(filter, vec![text.clone()])
+            }
+            _ => filter,
+        };
+        return browse_fallback(state, payload, filter, top_k).await;
+    }
+
+    // Sharded deployments only search the shards holding the requested cameras
+    let collections = state.router.collections_for(payload.camera_ids.as_deref());
+
+    // Collections pinned to other models are searched with their own query
+    // embedding and merged by rank, as scores of two models aren't comparable
+    let models = state.scheduler.embedding_models.partition(&collections);
+    let single_model_only =
+        payload.group_by_camera || payload.debug || examples.is_some() || fused.is_some();
+    if models.len() > 1 && single_model_only {
+        return Err(AppError::InvalidRequest(
+            "Grouped, debug, session feedback and fused hybrid searches need collections of \
+             one embedding model; restrict camera_ids to cameras of one model"
+                .to_string(),
+        ));
+    }
+
+    // Get text embeddings from AI service
+    let mut model_vectors = Vec::with_capacity(models.len());
+    for (model, model_collections) in models {
+        let vector = query_vector(state, model, payload).await?;
+        validate_embedding(
+            &vector,
+            model.vector_size,
+            "search",
+            &payload.query,
+            &state.metrics,
+        )?;
+        model_vectors.push((vector, model_collections));
+    }
+    let vector = model_vectors[0].0.clone();
+
+    let read_consistency = match payload
+        .search_params
+        .as_ref()
+        .and_then(|p| p.consistency.as_deref())
+    {
+        Some(value) => Some(parse_read_consistency(value)?),
+        None => match tunables.search_read_consistency.as_deref() {
+            // Validated when the configuration was loaded
+            Some(value) => parse_read_consistency(value).ok(),
+            None => None,
+        },
+    };
+
+    let search_points = SearchPoints {
+        collection_name: collections[0].clone(),
+        vector,
+        vector_name,
+        limit: candidate_limit(top_k, half_life, collapse_window),
+        with_payload: Some(true.into()),
+        filter,
+        score_threshold: payload.min_score,
+        params: Some(merge_search_params(
+            default_search_params(&tunables),
+            payload.search_params.as_ref(),
+        )),
+        read_consistency,
+        ..Default::default()
+    };
+
+    // Grouped mode: best hits of each camera, ranked by their top hit
+    if payload.group_by_camera {
+        let groups = guarded(Dependency::Qdrant, async {
+            inject(ChaosTarget::Qdrant).await?;
+            group_search(
+                state.qdrant.clone(),
+                &search_points,
+                &collections,
+                group_size,
+            )
+            .await
+        })
+        .await?;
+        let elapsed_ms = chrono::Utc::now()
+            .signed_duration_since(start_time)
+            .num_milliseconds();
+        info!(
+            groups = groups.len(),
+            elapsed_ms, "Grouped search completed"
+        );
+        return Ok(SearchOutcome::Groups(to_camera_groups(
+            groups,
+            payload.detail,
+            &state.url_rewriter,
+            &state.cameras.cameras(),
+        )));
+    }
+
+    // Debug mode: compare candidate search settings against an exact search
+    if payload.debug {
+        if collections.len() > 1 {
+            return Err(AppError::InvalidRequest(
+                "Debug mode needs a single shard; restrict camera_ids to one shard".to_string(),
+            ));
+        }
+        let report = simulate_search_params(&state.qdrant, &search_points).await?;
+        info!(
+            trials = report.trials.len(),
+            exact_latency_ms = report.reference_latency_ms,
+            "Search debug run"
+        );
+        return Ok(SearchOutcome::Debug(SearchDebugResponse {
+            results: to_search_results(
+                report.reference,
+                payload.detail,
+                &HashMap::new(),
+                &state.url_rewriter,
+                &state.cameras.cameras(),
+            ),
+            reference_latency_ms: report.reference_latency_ms,
+            trials: report.trials,
+        }));
+    }
+
+    // Wide ranges: search sub-ranges in parallel and merge by score
+    let fanout_chunks = payload
+        .fanout_chunks
+        .unwrap_or(tunables.search_fanout_chunks)
+        .min(technical::MAX_FANOUT_CHUNKS);
+    let ranges = match (fanout_chunks, &payload.start_date, &payload.end_date) {
+        (chunks, Some(start), Some(end)) if chunks > 1 => {
+            // Dates were already validated by the filter builder above
+            split_datetime_range(parse_rfc3339_utc(start)?, parse_rfc3339_utc(end)?, chunks)
+        }
+        _ => Vec::new(),
+    };
+
+    // Replay a sample of plain searches against the shadow collection
+    let shadow = shadow_target(&tunables, payload, examples.is_some() || fused.is_some())
+        .filter(|_| model_vectors.len() == 1)
+        .map(|target| (target, search_points.clone()));
+    let search_started = std::time::Instant::now();
+
+    let result = guarded(Dependency::Qdrant, async {
+        inject(ChaosTarget::Qdrant).await?;
+        if let Some((fusion, text)) = fused {
+            info!(shards = collections.len(), fusion = ?fusion, "Hybrid search");
+            let Some(fusion) = fusion.qdrant_fusion() else {
+                let candidates =
+                    hybrid_candidates(state.qdrant.clone(), &search_points, &collections, text)
+                        .await?;
+                let alpha = payload.hybrid_alpha.unwrap_or(tunables.search_hybrid_alpha);
+                return Ok(weighted_fusion(
+                    candidates,
+                    &query_terms(&payload.query),
+                    alpha,
+                    search_points.limit as usize,
+                ));
+            };
+            return hybrid_search(
+                state.qdrant.clone(),
+                &search_points,
+                &collections,
+                text,
+                fusion,
+            )
+            .await;
+        }
+        if let Some(examples) = &examples {
+            return recommend_fanout(state.qdrant.clone(), &search_points, &collections, examples)
+                .await;
+        }
+        if model_vectors.len() > 1 {
+            info!(models = model_vectors.len(), "Multi-model search");
+            let mut partials = Vec::with_capacity(model_vectors.len());
+            for (vector, model_collections) in &model_vectors {
+                let model_points = SearchPoints {
+                    collection_name: model_collections[0].clone(),
+                    vector: vector.clone(),
+                    ..search_points.clone()
+                };
+                partials.push(
+                    fanout_search(
+                        state.qdrant.clone(),
+                        &model_points,
+                        model_collections,
+                        &ranges,
+                    )
+                    .await?,
+                );
+            }
+            return Ok(merge_by_rank(partials, search_points.limit as usize));
+        }
+        match (collections.len(), ranges.len()) {
+            (1, 0) => state
+                .qdrant
+                .search_points(search_points)
+                .await
+                .map(|response| response.result)
+                .map_err(|e| AppError::Qdrant(format!("Qdrant search error: {}", e))),
+            (shards, sub_ranges) => {
+                info!(shards, sub_ranges = sub_ranges.max(1), "Search fan-out");
+                fanout_search(state.qdrant.clone(), &search_points, &collections, &ranges).await
+            }
+        }
+    })
+    .await;
+
+    // Compared before the recency boost, which the shadow search does not apply
+    if let (Some((target, base)), Ok(points)) = (shadow, &result) {
+        state.shadow.spawn(
+            state.qdrant.clone(),
+            state.http_client.clone(),
+            state.metrics.clone(),
+            target,
+            payload.query.clone(),
+            base,
+            points,
+            search_started.elapsed().as_millis() as i64,
+        );
+    }
+
+    // Map results
+    match result.map(|points| rerank(points, half_life, collapse_window, top_k)) {
+        Ok((points, base_scores)) => {
+            let hit_count = points.len();
+            let elapsed_ms = start_time
+                .signed_duration_since(chrono::Utc::now())
+                .num_milliseconds()
+                .abs();
+            info!(results = hit_count, elapsed_ms, "Search completed");
+
+            Ok(SearchOutcome::Results(to_search_results(
+                points,
+                payload.detail,
+                &base_scores,
+                &state.url_rewriter,
+                &state.cameras.cameras(),
+            )))
+        }
+        Err(e) => {
+            let elapsed_ms = start_time
+                .signed_duration_since(chrono::Utc::now())
+                .num_milliseconds()
+                .abs();
+            error!(elapsed_ms, error = %e, "Search failed");
+            Err(e)
+        }
+    }
+}
+
+/// Whether calls to the AI service are currently rejected by its breaker
+fn ai_circuit_open() -> bool {
+    circuit_breakers().is_some_and(|breakers| {
+        breakers
+            .check(Dependency::AiService, Instant::now())
+            .is_err()
+    })
+}
+
+/// Degraded search while the AI service is down: the newest images
+/// matching the filters, flagged with `DEGRADED_SEARCH_HEADER` on `/search`
+async fn browse_fallback(
+    state: &AppState,
+    payload: &SearchRequest,
+    filter: Option<Filter>,
+    top_k: u64,
+) -> Result<SearchOutcome, AppError> {
+    let collections = state.router.collections_for(payload.camera_ids.as_deref());
+    let points = guarded(Dependency::Qdrant, async {
+        inject(ChaosTarget::Qdrant).await?;
+        browse_latest(&state.qdrant, &collections, filter, top_k as u32).await
+    })
+    .await?;
+    warn!(
+        results = points.len(),
+        "AI service circuit open, answered search with the newest matching images"
+    );
+
+    Ok(SearchOutcome::Browsed(to_search_results(
+        points,
+        payload.detail,
+        &HashMap::new(),
+        &state.url_rewriter,
+        &state.cameras.cameras(),
+    )))
+}
+
+/// Handler for finding vehicles similar to a given image
+#[utoipa::path(
+    post,
+    path = "/search_by_image",
+    request_body(content = SearchByImageRequest, examples(
+        ("Local path" = (summary = "Relative to EMBED_IMAGE_ROOT", value = json!({
+            "image_path": "cctv01/2025-10-08/cctv01_06-32_123.jpg",
+            "top_k": 5,
+            "camera_ids": ["cctv02"]
+        }))),
+        ("Upload" = (summary = "Base64 upload", value = json!({
+            "image_base64": "data:image/jpeg;base64,/9j/4AAQSkZJRgABAQ...",
+            "top_k": 5
+        })))
+    )),
+    responses(
+        (status = 200, description = "Search completed successfully", body = [SearchResult]),
+        (status = 400, description = "Invalid fields, missing or invalid image, or image_path without EMBED_IMAGE_ROOT", body = ErrorResponse),
+        (status = 403, description = "image_path is outside EMBED_IMAGE_ROOT", body = ErrorResponse),
+        (status = 500, description = "Failed to store the uploaded image", body = ErrorResponse),
+        (status = 422, description = "The AI service returned an embedding of the wrong dimension", body = ErrorResponse),
+        (status = 502, description = "AI service or Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Search API"
+)]
+#[post("/search_by_image")]
+pub async fn search_by_image(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    state: web::Data<AppState>,
+    payload: ValidatedJson<SearchByImageRequest>,
+) -> Result<HttpResponse, AppError> {
+    let start_time = chrono::Utc::now();
+
+    // Paths are only read from under the image root, so a reader can't make
+    // the AI service read arbitrary files or URLs. Base64 uploads are written
+    // to a file the AI service can read; it is removed when `upload` goes out
+    // of scope
+    let (image_path, upload) = match (&payload.image_path, &payload.image_base64) {
+        (Some(path), None) => {
+            let root = state.embed_image_root.as_deref().ok_or_else(|| {
+                AppError::InvalidRequest(
+                    "image_path requires EMBED_IMAGE_ROOT; send image_base64 instead".to_string(),
+                )
+            })?;
+            let image = LocalImage::read(root, path, technical::MAX_EMBED_IMAGE_BYTES).await?;
+            (image.path.to_string_lossy().into_owned(), None)
+        }
+        (None, Some(encoded)) => {
+            let upload = QueryImage::save(&state.query_image_dir, encoded).await?;
+            (upload.path(), Some(upload))
+        }
+        _ => {
+            return Err(AppError::InvalidRequest(
+                "Exactly one of image_path and image_base64 is required".to_string(),
+            ));
+        }
+    };
+    info!(
+        image = if upload.is_some() {
+            "<base64>"
+        } else {
+            &image_path
+        },
+        top_k = payload.top_k.unwrap_or(5),
+        "Image search request"
+    );
+
+    let filter = with_tenant(
+        with_conditions(
+            build_image_filter(
+                payload.start_date.as_deref(),
+                payload.end_date.as_deref(),
+                payload.camera_ids.as_deref(),
+            )?,
+            [
+                label_conditions(payload.vehicle_classes.as_deref(), payload.min_confidence)?,
+                extra_conditions(payload.filters.as_ref())?,
+            ]
+            .concat(),
+        ),
+        tenant.id(),
+    );
+    let top_k = payload.top_k.unwrap_or(5);
+    let half_life = resolve_half_life(
+        payload.recency_half_life_hours,
+        state.tunables.current().search_recency_half_life_hours,
+    )?;
+    let collapse_window = payload.collapse_window_s.filter(|&secs| secs > 0);
+    let examples = session_examples(&state, payload.session_id.as_deref(), tenant.id())?;
+    let collections = state.router.collections_for(payload.camera_ids.as_deref());
+    let model = state
+        .scheduler
+        .embedding_models
+        .single_model(&collections)?;
+
+    // Get image embedding from AI service
+    let batch_result = get_image_embedding(
+        &state.http_client,
+        &model.ai_service_url,
+        vec![image_path],
+        AiPriority::Interactive,
+    )
+    .await?;
+    let vector = match batch_result.results.into_iter().next() {
+        Some(result) => match (result.embedding, result.error) {
+            (Some(v), _) => v,
+            (None, Some(error)) => {
+                return Err(AppError::InvalidRequest(format!(
+                    "AI Image Service error: {}",
+                    error
+                )));
+            }
+            (None, None) => {
+                return Err(AppError::AiService(
+                    "No embedding returned from AI service".to_string(),
+                ));
+            }
+        },
+        None => {
+            return Err(AppError::AiService(
+                "No results returned from AI service".to_string(),
+            ));
+        }
+    };
+    validate_embedding(
+        &vector,
+        model.vector_size,
+        "search",
+        "query image",
+        &state.metrics,
+    )?;
+
+    let tunables = state.tunables.current();
+    let search_points = SearchPoints {
+        collection_name: collections[0].clone(),
+        vector,
+        vector_name: state.vector_layout.using(VectorSpace::Image)?,
+        limit: candidate_limit(top_k, half_life, collapse_window),
+        with_payload: Some(true.into()),
+        filter,
+        score_threshold: payload.min_score,
+        params: Some(default_search_params(&tunables)),
+        read_consistency: tunables
+            .search_read_consistency
+            .as_deref()
+            .and_then(|value| parse_read_consistency(value).ok()),
+        ..Default::default()
+    };
+
+    let elapsed_ms = || {
+        chrono::Utc::now()
+            .signed_duration_since(start_time)
+            .num_milliseconds()
+    };
+    let result = guarded(Dependency::Qdrant, async {
+        inject(ChaosTarget::Qdrant).await?;
+        if let Some(examples) = &examples {
+            recommend_fanout(state.qdrant.clone(), &search_points, &collections, examples).await
+        } else if collections.len() > 1 {
+            fanout_search(state.qdrant.clone(), &search_points, &collections, &[]).await
+        } else {
+            state
+                .qdrant
+                .search_points(search_points)
+                .await
+                .map(|response| response.result)
+                .map_err(|e| AppError::Qdrant(format!("Qdrant search error: {}", e)))
+        }
+    })
+    .await;
+
+    match result.map(|points| rerank(points, half_life, collapse_window, top_k)) {
+        Ok((points, base_scores)) => {
+            info!(
+                results = points.len(),
+                elapsed_ms = elapsed_ms(),
+                "Image search completed"
+            );
+            Ok(HttpResponse::Ok().json(to_search_results(
+                points,
+                payload.detail,
+                &base_scores,
+                &state.url_rewriter,
+                &state.cameras.cameras(),
+            )))
+        }
+        Err(e) => {
+            error!(elapsed_ms = elapsed_ms(), error = %e, "Image search failed");
+            Err(e)
+        }
+    }
+}
+
+/// Handler for finding images similar to stored images
+#[utoipa::path(
+    post,
+    path = "/recommend",
+    request_body(content = RecommendRequest, example = json!({
+        "positive": ["12345", "12377"],
+        "negative": ["12350"],
+        "top_k": 10,
+        "start_date": "2025-10-08T00:00:00+07:00"
+    })),
+    responses(
+        (status = 200, description = "Similar images, excluding the given ones", body = [SearchResult]),
+        (status = 400, description = "No positive ID, too many IDs, top_k out of range or invalid date", body = ErrorResponse),
+        (status = 404, description = "Unknown image ID", body = ErrorResponse),
+        (status = 502, description = "Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Search API"
+)]
+#[post("/recommend")]
+pub async fn recommend(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    state: web::Data<AppState>,
+    payload: ValidatedJson<RecommendRequest>,
+) -> Result<HttpResponse, AppError> {
+    let start_time = chrono::Utc::now();
+    let top_k = payload.top_k.unwrap_or(5);
+    info!(
+        positive = payload.positive.len(),
+        negative = payload.negative.len(),
+        top_k,
+        "Recommend request"
+    );
+
+    let filter = with_tenant(
+        with_conditions(
+            build_image_filter(
+                payload.start_date.as_deref(),
+                payload.end_date.as_deref(),
+                payload.camera_ids.as_deref(),
+            )?,
+            [
+                label_conditions(payload.vehicle_classes.as_deref(), payload.min_confidence)?,
+                extra_conditions(payload.filters.as_ref())?,
+            ]
+            .concat(),
+        ),
+        tenant.id(),
+    );
+
+    // Examples may be stored in any shard; hits only come from the requested cameras
+    let all_collections = state.router.collections();
+    let vector_name = state.vector_layout.image_vector();
+    let mut examples = SessionExamples::default();
+    examples.add(
+        fetch_examples(
+            &state.qdrant,
+            all_collections,
+            vector_name,
+            &payload.positive,
+            tenant.id(),
+        )
+        .await?,
+        fetch_examples(
+            &state.qdrant,
+            all_collections,
+            vector_name,
+            &payload.negative,
+            tenant.id(),
+        )
+        .await?,
+    );
+
+    let tunables = state.tunables.current();
+    let collections = state.router.collections_for(payload.camera_ids.as_deref());
+    // No query vector: the examples alone define the recommendation
+    let search_points = SearchPoints {
+        collection_name: collections[0].clone(),
+        vector_name: state.vector_layout.using(VectorSpace::Image)?,
+        limit: top_k,
+        with_payload: Some(true.into()),
+        filter,
+        score_threshold: payload.min_score,
+        params: Some(default_search_params(&tunables)),
+        read_consistency: tunables
+            .search_read_consistency
+            .as_deref()
+            .and_then(|value| parse_read_consistency(value).ok()),
+        ..Default::default()
+    };
+
+    let result = guarded(Dependency::Qdrant, async {
+        inject(ChaosTarget::Qdrant).await?;
+        recommend_fanout(
+            state.qdrant.clone(),
+            &search_points,
+            &collections,
+            &examples,
+        )
+        .await
+    })
+    .await;
+
+    let elapsed_ms = chrono::Utc::now()
+        .signed_duration_since(start_time)
+        .num_milliseconds();
+    match result {
+        Ok(points) => {
+            info!(results = points.len(), elapsed_ms, "Recommend completed");
+            Ok(HttpResponse::Ok().json(to_search_results(
+                points,
+                payload.detail,
+                &HashMap::new(),
+                &state.url_rewriter,
+                &state.cameras.cameras(),
+            )))
+        }
+        Err(e) => {
+            error!(elapsed_ms, error = %e, "Recommend failed");
+            Err(e)
+        }
+    }
+}
+
+/// Handler for sightings of one query on several cameras within a time window
+#[utoipa::path(
+    post,
+    path = "/search/correlated",
+    request_body(content = CorrelatedSearchRequest, example = json!({
+        "query": "red sedan",
+        "camera_ids": ["checkpoint_north", "checkpoint_south"],
+        "within_minutes": 20,
+        "top_k": 5,
+        "start_date": "2025-10-08T00:00:00+07:00"
+    })),
+    responses(
+        (status = 200, description = "Pairs of sightings on different cameras, best first", body = [CorrelatedMatch]),
+        (status = 400, description = "Bad request; `validation_failed` lists the invalid fields in `details`", body = ErrorResponse),
+        (status = 422, description = "The AI service returned an embedding of the wrong dimension", body = ErrorResponse),
+        (status = 502, description = "AI service or Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Search API"
+)]
+#[post("/search/correlated")]
+pub async fn search_correlated(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    state: web::Data<AppState>,
+    payload: ValidatedJson<CorrelatedSearchRequest>,
+) -> Result<HttpResponse, AppError> {
+    let start_time = chrono::Utc::now();
+    let top_k = payload.top_k.unwrap_or(5);
+    let within_minutes = payload
+        .within_minutes
+        .unwrap_or(technical::CORRELATION_WINDOW_MINUTES);
+    info!(
+        query = %payload.query,
+        cameras = payload.camera_ids.len(),
+        within_minutes,
+        top_k,
+        "Correlated search request"
+    );
+
+    // Each camera is searched on its own, so a busy camera can't crowd out the
+    // others, with the query embedded by the model of its collection
+    let tunables = state.tunables.current();
+    let vector_name = state.vector_layout.using(VectorSpace::Image)?;
+    let mut hits = Vec::new();
+    for camera_id in &payload.camera_ids {
+        let camera = std::slice::from_ref(camera_id);
+        let collection_name = state.router.collection_for(camera_id);
+        let model = state
+            .scheduler
+            .embedding_models
+            .for_collection(collection_name);
+        let vector = text_embedding(&state, model, &payload.query).await?;
+        validate_embedding(
+            &vector,
+            model.vector_size,
+            "search",
+            &payload.query,
+            &state.metrics,
+        )?;
+        let search_points = SearchPoints {
+            collection_name: collection_name.to_string(),
+            vector,
+            vector_name: vector_name.clone(),
+            limit: technical::CORRELATION_CANDIDATES_PER_CAMERA,
+            with_payload: Some(true.into()),
+            filter: with_tenant(
+                build_image_filter(
+                    payload.start_date.as_deref(),
+                    payload.end_date.as_deref(),
+                    Some(camera),
+                )?,
+                tenant.id(),
+            ),
+            score_threshold: payload.min_score,
+            params: Some(default_search_params(&tunables)),
+            read_consistency: tunables
+                .search_read_consistency
+                .as_deref()
+                .and_then(|value| parse_read_consistency(value).ok()),
+            ..Default::default()
+        };
+        let collections = [search_points.collection_name.clone()];
+        let points = guarded(Dependency::Qdrant, async {
+            inject(ChaosTarget::Qdrant).await?;
+            fanout_search(state.qdrant.clone(), &search_points, &collections, &[]).await
+        })
+        .await?;
+        hits.extend(points);
+    }
+
+    let pairs = correlate_hits(hits, within_minutes as i64 * 60, top_k as usize);
+    let cameras = state.cameras.cameras();
+    let matches: Vec<CorrelatedMatch> = pairs
+        .into_iter()
+        .filter_map(|pair| {
+            let mut results = to_search_results(
+                vec![pair.first, pair.second],
+                payload.detail,
+                &HashMap::new(),
+                &state.url_rewriter,
+                &cameras,
+            )
+            .into_iter();
+            Some(CorrelatedMatch {
+                score: pair.score,
+                gap_seconds: pair.gap_seconds,
+                first: results.next()?,
+                second: results.next()?,
+            })
+        })
+        .collect();
+
+    let elapsed_ms = chrono::Utc::now()
+        .signed_duration_since(start_time)
+        .num_milliseconds();
+    info!(
+        pairs = matches.len(),
+        elapsed_ms, "Correlated search completed"
+    );
+    Ok(HttpResponse::Ok().json(matches))
+}
+
+/// Handler sweeping the weight of weighted hybrid fusion against a labeled
+/// evaluation set
+///
+/// The hybrid candidates of each query are fetched once, as a weighted
+/// `/search` with `top_k` would fetch them, and re-ranked for every alpha.
+#[utoipa::path(
+    post,
+    path = "/admin/hybrid/calibrate",
+    request_body = HybridCalibrationRequest,
+    responses(
+        (status = 200, description = "Recall and MRR per alpha, and the best alpha", body = HybridCalibrationResponse),
+        (status = 400, description = "Bad request; `validation_failed` lists the invalid fields in `details`", body = ErrorResponse),
+        (status = 422, description = "The AI service returned an embedding of the wrong dimension", body = ErrorResponse),
+        (status = 502, description = "AI service or Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[post("/admin/hybrid/calibrate")]
+pub async fn calibrate_hybrid(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+    request: ValidatedJson<HybridCalibrationRequest>,
+) -> Result<HttpResponse, AppError> {
+    let top_k = request.top_k.unwrap_or(technical::CALIBRATION_TOP_K);
+    let alphas = request.alphas.clone().unwrap_or_else(default_alphas);
+    let collections = state.router.collections_for(request.camera_ids.as_deref());
+    let model = state
+        .scheduler
+        .embedding_models
+        .single_model(&collections)?;
+    let tunables = state.tunables.current();
+    let base = SearchPoints {
+        collection_name: collections[0].clone(),
+        vector_name: state.vector_layout.using(VectorSpace::Image)?,
+        limit: top_k,
+        with_payload: Some(true.into()),
+        filter: build_image_filter(None, None, request.camera_ids.as_deref())?,
+        params: Some(default_search_params(&tunables)),
+        ..Default::default()
+    };
+
+    let mut labeled = Vec::with_capacity(request.queries.len());
+    for query in &request.queries {
+        let text = text_match_condition(&query.query).ok_or_else(|| {
+            AppError::InvalidRequest(format!("Query {:?} has no words to match", query.query))
+        })?;
+        let vector = text_embedding(&state, model, &query.query).await?;
+        validate_embedding(
+            &vector,
+            model.vector_size,
+            "search",
+            &query.query,
+            &state.metrics,
+        )?;
+        let search_points = SearchPoints {
+            vector,
+            ..base.clone()
+        };
+        let candidates = guarded(Dependency::Qdrant, async {
+            inject(ChaosTarget::Qdrant).await?;
+            hybrid_candidates(state.qdrant.clone(), &search_points, &collections, &text).await
+        })
+        .await?;
+        labeled.push(LabeledCandidates {
+            candidates,
+            terms: query_terms(&query.query),
+            relevant: query.relevant_ids.iter().copied().collect(),
+        });
+    }
+
+    let trials = sweep_alphas(&labeled, &alphas, top_k as usize);
+    let best = best_trial(&trials)
+        .cloned()
+        .ok_or_else(|| AppError::InvalidRequest("alphas must not be empty".to_string()))?;
+    info!(
+        queries = labeled.len(),
+        top_k,
+        best_alpha = best.alpha,
+        mrr = best.mrr,
+        recall = best.recall,
+        "Hybrid calibration completed"
+    );
+    Ok(HttpResponse::Ok().json(HybridCalibrationResponse {
+        best_alpha: best.alpha,
+        current_alpha: tunables.search_hybrid_alpha,
+        queries: labeled.len(),
+        top_k,
+        trials,
+    }))
+}
+
+/// Feedback examples of the request's session of `tenant`, if it has any
+fn session_examples(
+    state: &AppState,
+    session_id: Option<&str>,
+    tenant: Option<&str>,
+) -> Result<Option<SessionExamples>, AppError> {
+    match session_id {
+        Some(id) => Ok(Some(state.sessions.examples(id, tenant)?).filter(|e| !e.is_empty())),
+        None => Ok(None),
+    }
+}
+
+/// Fusion and text condition of a hybrid search request, if `hybrid` is set
+fn hybrid_text(
+    state: &AppState,
+    payload: &SearchRequest,
+) -> Result<Option<(HybridFusion, Condition)>, AppError> {
+    if !payload.hybrid {
+        return Ok(None);
+    }
+    if payload.arithmetic {
+        return Err(AppError::InvalidRequest(
+            "Hybrid search cannot be combined with arithmetic mode".to_string(),
+        ));
+    }
+
+    let fusion = match payload.hybrid_fusion.as_deref() {
+        Some(value) => parse_hybrid_fusion(value)?,
+        None if payload.hybrid_alpha.is_some() => HybridFusion::Weighted,
+        // Validated when the configuration was loaded
+        None => parse_hybrid_fusion(&state.tunables.current().search_hybrid_fusion)?,
+    };
+    if payload.hybrid_alpha.is_some() && fusion != HybridFusion::Weighted {
+        return Err(AppError::InvalidRequest(
+            "hybrid_alpha only applies to hybrid_fusion \"weighted\"".to_string(),
+        ));
+    }
+    let text = text_match_condition(&payload.query).ok_or_else(|| {
+        AppError::InvalidRequest("Hybrid search needs a non-empty query".to_string())
+    })?;
+    Ok(Some((fusion, text)))
+}
+
+/// Shadow target for this search, if shadowing is configured and the search
+/// is sampled
+///
+/// Searches steered by session examples or hybrid fusion are not shadowed,
+/// nor are arithmetic queries that the shadow AI service would re-embed.
+fn shadow_target(
+    tunables: &Tunables,
+    payload: &SearchRequest,
+    steered: bool,
+) -> Option<ShadowTarget> {
+    let collection = tunables.shadow_collection.clone()?;
+    if steered || (payload.arithmetic && tunables.shadow_ai_service_url.is_some()) {
+        return None;
+    }
+    if rand::random::<f64>() >= tunables.shadow_sample_rate {
+        return None;
+    }
+    Some(ShadowTarget {
+        collection,
+        ai_service_url: tunables.shadow_ai_service_url.clone(),
+    })
+}
+
+/// Qdrant limit for `top_k` results; a recency boost or burst collapsing
+/// re-ranks a larger pool
+fn candidate_limit(top_k: u64, half_life: Option<f64>, collapse_window: Option<u64>) -> u64 {
+    let factor = match (half_life, collapse_window) {
+        (_, Some(_)) => technical::COLLAPSE_CANDIDATE_FACTOR,
+        (Some(_), None) => technical::RECENCY_CANDIDATE_FACTOR,
+        (None, None) => 1,
+    };
+    top_k.saturating_mul(factor)
+}
+
+/// Apply the recency boost and burst collapsing, if any, and cut the
+/// candidates back to `top_k`
+///
+/// Also returns the scores of the candidates before the boost, by point ID.
+fn rerank(
+    points: Vec<ScoredPoint>,
+    half_life: Option<f64>,
+    collapse_window: Option<u64>,
+    top_k: u64,
+) -> (Vec<ScoredPoint>, HashMap<String, f32>) {
+    let (mut points, base_scores) = match half_life {
+        Some(hours) => {
+            let base_scores = points
+                .iter()
+                .filter_map(|p| Some((point_id_to_string(p.id.as_ref()?), p.score)))
+                .collect();
+            let boosted = apply_recency_boost(points, hours, chrono::Utc::now(), usize::MAX);
+            (boosted, base_scores)
+        }
+        None => (points, HashMap::new()),
+    };
+    if let Some(window_secs) = collapse_window {
+        points = collapse_bursts(points, window_secs);
+    }
+    points.truncate(top_k as usize);
+    (points, base_scores)
+}
+
+/// Search parameters from the configured tunables
+fn default_search_params(tunables: &Tunables) -> SearchParams {
+    SearchParams {
+        hnsw_ef: tunables.search_hnsw_ef,
+        exact: Some(tunables.search_exact),
+        indexed_only: tunables.search_indexed_only,
+        ..Default::default()
+    }
+}
+
+/// Overlay per-request search parameters on the configured defaults
+fn merge_search_params(
+    defaults: SearchParams,
+    requested: Option<&SearchParamsRequest>,
+) -> SearchParams {
+    let Some(requested) = requested else {
+        return defaults;
+    };
+
+    SearchParams {
+        hnsw_ef: requested.hnsw_ef.or(defaults.hnsw_ef),
+        exact: requested.exact.or(defaults.exact),
+        indexed_only: requested.indexed_only.or(defaults.indexed_only),
+        ..defaults
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::PayloadBuilder;
+
+    #[test]
+    fn test_result_detail_levels() {
+        let hit = || ScoredPoint {
+            id: Some(7.into()),
+            score: 0.4,
+            payload: PayloadBuilder::new()
+                .string("filename", "a.jpg")
+                .string("camera_id", "cctv01")
+                .integer("frame", 3)
+                .build(),
+            ..Default::default()
+        };
+        let base_scores = HashMap::from([("7".to_string(), 0.8)]);
+        let urls = UrlRewriter::default();
+        let cameras = HashMap::from([(
+            "cctv01".to_string(),
+            CameraEntry {
+                cctv_id: "cctv01".to_string(),
+                enabled: true,
+                name: Some("Main gate".to_string()),
+                location: None,
+                lat: Some(13.75),
+                lon: Some(100.5),
+                timezone: None,
+                tenant_id: None,
+            },
+        )]);
+
+        let minimal = to_search_results(
+            vec![hit()],
+            ResultDetail::Minimal,
+            &base_scores,
+            &urls,
+            &cameras,
+        );
+        assert_eq!(minimal[0].camera_id, "cctv01");
+        assert!(minimal[0].filename.is_none() && minimal[0].frame.is_none());
+        assert!(minimal[0].camera_name.is_none());
+
+        let standard = to_search_results(
+            vec![hit()],
+            ResultDetail::Standard,
+            &base_scores,
+            &urls,
+            &cameras,
+        );
+        assert_eq!(standard[0].frame, Some(3));
+        assert_eq!(standard[0].camera_name.as_deref(), Some("Main gate"));
+        assert_eq!(standard[0].lat, Some(13.75));
+        assert!(standard[0].payload.is_none() && standard[0].score_breakdown.is_none());
+
+        let full = to_search_results(
+            vec![hit()],
+            ResultDetail::Full,
+            &base_scores,
+            &urls,
+            &cameras,
+        );
+        let breakdown = full[0].score_breakdown.as_ref().unwrap();
+        assert_eq!(breakdown.base_score, 0.8);
+        assert_eq!(breakdown.recency_factor, 0.5);
+        assert_eq!(full[0].payload.as_ref().unwrap()["frame"], 3);
+    }
+
+    #[actix_web::test]
+    async fn test_search_by_image_confines_paths() {
+        use actix_web::http::StatusCode;
+        use actix_web::{App, test};
+
+        let base = std::env::temp_dir().join(format!("images-{:016x}", rand::random::<u64>()));
+        let root = base.join("root");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(base.join("secret"), b"secret").unwrap();
+        let root_str = root.to_string_lossy();
+
+        let status = |state: web::Data<AppState>, body: serde_json::Value| async move {
+            let app = test::init_service(App::new().app_data(state).service(search_by_image)).await;
+            let request = test::TestRequest::post()
+                .uri("/search_by_image")
+                .set_json(body)
+                .to_request();
+            test::call_service(&app, request).await.status()
+        };
+
+        let unrooted = AppState::for_tests(&[]).await;
+        let rooted = AppState::for_tests(&[("EMBED_IMAGE_ROOT", &root_str)]).await;
+        let path = serde_json::json!({ "image_path": "frame.jpg" });
+        assert_eq!(status(unrooted, path).await, StatusCode::BAD_REQUEST);
+        let escape = serde_json::json!({ "image_path": "../secret" });
+        assert_eq!(status(rooted.clone(), escape).await, StatusCode::FORBIDDEN);
+        let url = serde_json::json!({ "image_path": "http://169.254.169.254/latest" });
+        assert_eq!(status(rooted.clone(), url).await, StatusCode::BAD_REQUEST);
+        let top_k = serde_json::json!({ "image_base64": "AAAA", "top_k": 100000 });
+        assert_eq!(status(rooted, top_k).await, StatusCode::BAD_REQUEST);
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -0,0 +1,148 @@
+//! Search Session Handlers
+//!
+//! Creating sessions and recording result feedback that later searches in
+//! the session use as recommend examples.
+
+use super::AppState;
+use super::etag::json_with_etag;
+use crate::error::AppError;
+use crate::middleware::{Authorized, Reader, Tenant};
+use crate::models::session::{SessionFeedback, SessionState};
+use crate::services::{MAX_SESSION_EXAMPLES, SessionExamples, fetch_examples};
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+use tracing::info;
+
+fn session_state(session_id: &str, examples: &SessionExamples) -> SessionState {
+    SessionState {
+        session_id: session_id.to_string(),
+        positive: examples.positive_ids(),
+        negative: examples.negative_ids(),
+    }
+}
+
+/// Handler starting a search session of the tenant
+#[utoipa::path(
+    post,
+    path = "/sessions",
+    responses(
+        (status = 200, description = "Session created", body = SessionState)
+    ),
+    tag = "Search API"
+)]
+#[post("/sessions")]
+pub async fn create_session(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    state: web::Data<AppState>,
+) -> HttpResponse {
+    let session_id = state.sessions.create(tenant.id());
+    info!(session_id = %session_id, "Search session created");
+    HttpResponse::Ok().json(session_state(&session_id, &SessionExamples::default()))
+}
+
+/// Handler returning the examples collected in a session
+#[utoipa::path(
+    get,
+    path = "/sessions/{session_id}",
+    params(("session_id" = String, Path, description = "Session ID")),
+    responses(
+        (status = 200, description = "Session examples", body = SessionState),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        (status = 404, description = "Unknown or expired session", body = ErrorResponse)
+    ),
+    tag = "Search API"
+)]
+#[get("/sessions/{session_id}")]
+pub async fn get_session(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    session_id: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let examples = state.sessions.examples(&session_id, tenant.id())?;
+    Ok(json_with_etag(&req, &session_state(&session_id, &examples)))
+}
+
+/// Handler marking search results as positive or negative examples
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/feedback",
+    params(("session_id" = String, Path, description = "Session ID")),
+    request_body = SessionFeedback,
+    responses(
+        (status = 200, description = "Examples recorded", body = SessionState),
+        (status = 400, description = "Too many examples", body = ErrorResponse),
+        (status = 404, description = "Unknown or expired session, or unknown image ID", body = ErrorResponse),
+        (status = 502, description = "Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Search API"
+)]
+#[post("/sessions/{session_id}/feedback")]
+pub async fn add_session_feedback(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    state: web::Data<AppState>,
+    session_id: web::Path<String>,
+    feedback: web::Json<SessionFeedback>,
+) -> Result<HttpResponse, AppError> {
+    if feedback.positive.len() > MAX_SESSION_EXAMPLES
+        || feedback.negative.len() > MAX_SESSION_EXAMPLES
+    {
+        return Err(AppError::InvalidRequest(format!(
+            "At most {} positive and {} negative examples per request",
+            MAX_SESSION_EXAMPLES, MAX_SESSION_EXAMPLES
+        )));
+    }
+
+    // Fail before the Qdrant lookups if the session is gone
+    state.sessions.examples(&session_id, tenant.id())?;
+
+    // Images of other tenants are unknown
+    let collections = state.router.collections();
+    let vector_name = state.vector_layout.image_vector();
+    let fetch = |ids| fetch_examples(&state.qdrant, collections, vector_name, ids, tenant.id());
+    let positive = fetch(&feedback.positive).await?;
+    let negative = fetch(&feedback.negative).await?;
+
+    let examples = state
+        .sessions
+        .update(&session_id, tenant.id(), |examples| {
+            examples.add(positive, negative);
+            examples.clone()
+        })?;
+    info!(
+        session_id = %session_id,
+        positive = examples.positive.len(),
+        negative = examples.negative.len(),
+        "Session feedback recorded"
+    );
+    Ok(HttpResponse::Ok().json(session_state(&session_id, &examples)))
+}
+
+/// Handler ending a search session
+#[utoipa::path(
+    delete,
+    path = "/sessions/{session_id}",
+    params(("session_id" = String, Path, description = "Session ID")),
+    responses(
+        (status = 204, description = "Session ended"),
+        (status = 404, description = "Unknown or expired session", body = ErrorResponse)
+    ),
+    tag = "Search API"
+)]
+#[delete("/sessions/{session_id}")]
+pub async fn delete_session(
+    _: Authorized<Reader>,
+    tenant: Tenant,
+    state: web::Data<AppState>,
+    session_id: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    if !state.sessions.remove(&session_id, tenant.id()) {
+        return Err(AppError::NotFound(format!(
+            "Unknown or expired session: {}",
+            session_id
+        )));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,138 @@
+//! Snapshot Handlers
+//!
+//! Back up the collections as Qdrant snapshots, restore them and report
+//! the last restore check.
+
+use super::AppState;
+use crate::error::AppError;
+use crate::middleware::{Admin, Authorized};
+use crate::models::admin::{SnapshotRestoreRequest, SnapshotRestoreResponse};
+use crate::services::{Dependency, create_snapshot, guarded, list_snapshots};
+use actix_web::{HttpResponse, get, post, web};
+use tracing::info;
+
+/// Handler taking a snapshot of every collection
+#[utoipa::path(
+    post,
+    path = "/admin/snapshots",
+    responses(
+        (status = 201, description = "One new snapshot per collection", body = [SnapshotInfo]),
+        (status = 502, description = "Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[post("/admin/snapshots")]
+pub async fn create_snapshots(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let mut snapshots = Vec::new();
+    for collection in state.router.collections() {
+        snapshots.push(
+            guarded(
+                Dependency::Qdrant,
+                create_snapshot(&state.qdrant, collection),
+            )
+            .await?,
+        );
+    }
+
+    info!(target: "audit", action = "create_snapshots", count = snapshots.len(), "Snapshots created");
+    Ok(HttpResponse::Created().json(snapshots))
+}
+
+/// Handler listing the snapshots of every collection
+#[utoipa::path(
+    get,
+    path = "/admin/snapshots",
+    responses(
+        (status = 200, description = "Snapshots, newest first", body = [SnapshotInfo]),
+        (status = 502, description = "Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[get("/admin/snapshots")]
+pub async fn snapshot_list(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let snapshots = guarded(
+        Dependency::Qdrant,
+        list_snapshots(&state.qdrant, state.router.collections()),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(snapshots))
+}
+
+/// Handler reporting the last restore check of the newest snapshots
+#[utoipa::path(
+    get,
+    path = "/admin/snapshots/verification",
+    responses(
+        (status = 200, description = "Outcome of the last backup verification run", body = BackupVerificationStatus)
+    ),
+    tag = "Admin API"
+)]
+#[get("/admin/snapshots/verification")]
+pub async fn backup_verification(_: Authorized<Admin>, state: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok().json(state.backup_verifier.status())
+}
+
+/// Handler replacing a collection with one of its snapshots
+#[utoipa::path(
+    post,
+    path = "/admin/snapshots/restore",
+    request_body = SnapshotRestoreRequest,
+    responses(
+        (status = 200, description = "Collection restored", body = SnapshotRestoreResponse),
+        (status = 400, description = "Unknown collection or restore not configured", body = ErrorResponse),
+        (status = 404, description = "No such snapshot", body = ErrorResponse),
+        (status = 502, description = "Qdrant failure", body = ErrorResponse)
+    ),
+    tag = "Admin API"
+)]
+#[post("/admin/snapshots/restore")]
+pub async fn restore_snapshot(
+    _: Authorized<Admin>,
+    state: web::Data<AppState>,
+    request: web::Json<SnapshotRestoreRequest>,
+) -> Result<HttpResponse, AppError> {
+    let request = request.into_inner();
+    let collections = state.router.collections();
+    let collection = match request.collection {
+        Some(collection) if collections.contains(&collection) => collection,
+        Some(collection) => {
+            return Err(AppError::InvalidRequest(format!(
+                "Unknown collection '{}'",
+                collection
+            )));
+        }
+        None if collections.len() == 1 => collections[0].clone(),
+        None => {
+            return Err(AppError::InvalidRequest(
+                "collection is required when COLLECTION_SHARDS > 1".to_string(),
+            ));
+        }
+    };
+
+    let available = list_snapshots(&state.qdrant, std::slice::from_ref(&collection)).await?;
+    if !available.iter().any(|s| s.name == request.snapshot) {
+        return Err(AppError::NotFound(format!(
+            "No snapshot '{}' of {}",
+            request.snapshot, collection
+        )));
+    }
+
+    info!(target: "audit", action = "restore_snapshot", collection = %collection, snapshot = %request.snapshot, "Snapshot restore started");
+    let started = std::time::Instant::now();
+    state
+        .snapshots
+        .restore(&collection, &request.snapshot, &collection)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(SnapshotRestoreResponse {
+        collection,
+        snapshot: request.snapshot,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    }))
+}
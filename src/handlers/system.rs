@@ -0,0 +1,141 @@
+//! System Handlers
+//!
+//! Service metadata and monitoring endpoints.
+
+use super::AppState;
+use super::etag::json_with_etag;
+use crate::build_info;
+use crate::models::system::{
+    DependencyHealth, EmbeddingModelInfo, HealthResponse, ServiceStatus, VersionInfo,
+};
+use crate::services::{
+    ai_dispatcher, ai_rate_limiter, all_healthy, check_dependencies, circuit_breakers,
+};
+use actix_web::{HttpRequest, HttpResponse, Responder, get, web};
+
+/// Liveness probe; always 200 while the process serves requests
+///
+/// Dependency state is included for diagnosis but never fails the probe, so
+/// an outage elsewhere does not restart the pod.
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    responses(
+        (status = 200, description = "Process is alive", body = HealthResponse)
+    ),
+    tag = "System API"
+)]
+#[get("/healthz")]
+pub async fn healthz(state: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok().json(health_response(check(&state).await))
+}
+
+/// Readiness probe; 503 while any dependency is unreachable
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    responses(
+        (status = 200, description = "All dependencies reachable", body = HealthResponse),
+        (status = 503, description = "At least one dependency is unreachable", body = HealthResponse)
+    ),
+    tag = "System API"
+)]
+#[get("/readyz")]
+pub async fn readyz(state: web::Data<AppState>) -> impl Responder {
+    let dependencies = check(&state).await;
+    if all_healthy(&dependencies) {
+        HttpResponse::Ok().json(health_response(dependencies))
+    } else {
+        HttpResponse::ServiceUnavailable().json(health_response(dependencies))
+    }
+}
+
+async fn check(state: &AppState) -> Vec<DependencyHealth> {
+    check_dependencies(
+        &state.qdrant,
+        &state.http_client,
+        &state.ai_service_url,
+        &state.cctv_api,
+    )
+    .await
+}
+
+fn health_response(dependencies: Vec<DependencyHealth>) -> HealthResponse {
+    let status = if all_healthy(&dependencies) {
+        "ok"
+    } else {
+        "degraded"
+    };
+    HealthResponse {
+        status: status.to_string(),
+        dependencies,
+    }
+}
+
+/// Handler reporting the circuit breaker state of every dependency
+#[utoipa::path(
+    get,
+    path = "/status",
+    responses(
+        (status = 200, description = "Circuit breaker state", body = ServiceStatus),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag")
+    ),
+    tag = "System API"
+)]
+#[get("/status")]
+pub async fn service_status(req: HttpRequest) -> impl Responder {
+    let breakers = circuit_breakers();
+    json_with_etag(
+        &req,
+        &ServiceStatus {
+            circuit_breakers_enabled: breakers.is_some(),
+            circuits: breakers.map(|b| b.statuses()).unwrap_or_default(),
+            ai_rate_limit: ai_rate_limiter().map(|limiter| limiter.status()),
+            ai_dispatch: ai_dispatcher().map(|dispatcher| dispatcher.status()),
+        },
+    )
+}
+
+/// Handler reporting which build this deployment is running
+#[utoipa::path(
+    get,
+    path = "/version",
+    responses(
+        (status = 200, description = "Build and model information", body = VersionInfo),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag")
+    ),
+    tag = "System API"
+)]
+#[get("/version")]
+pub async fn version(req: HttpRequest, state: web::Data<AppState>) -> impl Responder {
+    json_with_etag(
+        &req,
+        &VersionInfo {
+            version: build_info::VERSION.to_string(),
+            git_sha: build_info::GIT_SHA.to_string(),
+            build_time: build_info::build_time(),
+            features: state.features.enabled_names(),
+            embedding_model: EmbeddingModelInfo {
+                name: state.embedding_model.clone(),
+                vector_size: state.vector_size,
+                pinned: state.scheduler.embedding_models.pins(),
+            },
+        },
+    )
+}
+
+/// Handler exposing request metrics and SLO burn rates for Prometheus
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Metrics in Prometheus text format", body = String, content_type = "text/plain")
+    ),
+    tag = "System API"
+)]
+#[get("/metrics")]
+pub async fn metrics(state: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(state.metrics.render(&state.slo.statuses()))
+}
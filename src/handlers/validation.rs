@@ -0,0 +1,625 @@
+//! Request Validation
+//!
+//! JSON body extractor that checks the request fields before the handler
+//! runs, rejecting the request with every invalid field at once, and the
+//! extractor settings that report malformed requests as `ErrorResponse`.
+
+use crate::config::technical;
+use crate::error::{AppError, FieldError};
+use crate::models::alert::CreateAlertRequest;
+use crate::models::case::{AttachCaseItemRequest, CreateCaseRequest};
+use crate::models::evaluation::{CreateEvalSetRequest, EvalRunRequest};
+use crate::models::search::{
+    CorrelatedSearchRequest, HybridCalibrationRequest, LabeledQuery, RecommendRequest,
+    SearchByImageRequest, SearchRequest,
+};
+use crate::services::{MAX_SESSION_EXAMPLES, parse_iso8601_duration, parse_rfc3339_utc};
+use actix_web::dev::Payload;
+use actix_web::{Error, FromRequest, HttpRequest, HttpResponse, web};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+
+/// Field checks of a request body
+pub trait Validate {
+    /// Every invalid field; empty if the body is valid
+    fn validate(&self) -> Vec<FieldError>;
+
+    /// Fill in derived fields once the body is valid
+    fn normalize(&mut self) {}
+}
+
+/// JSON body that passed `Validate`, or a 400 listing the invalid fields
+pub struct ValidatedJson<T>(pub T);
+
+impl<T> Deref for ValidatedJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned + Validate + 'static> FromRequest for ValidatedJson<T> {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = web::Json::<T>::from_request(req, payload);
+        Box::pin(async move {
+            let body = json.await?.into_inner();
+            Ok(Self(validated(body)?))
+        })
+    }
+}
+
+/// `body` normalized if it passes `Validate`, else every invalid field
+pub fn validated<T: Validate>(mut body: T) -> Result<T, AppError> {
+    let errors = body.validate();
+    if !errors.is_empty() {
+        return Err(AppError::Validation(errors));
+    }
+    body.normalize();
+    Ok(body)
+}
+
+/// Report malformed JSON bodies, query strings and paths as `ErrorResponse`
+pub fn configure_extractors(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(
+        web::JsonConfig::default()
+            .error_handler(|e, _| AppError::Parse(format!("Invalid JSON body: {}", e)).into()),
+    )
+    .app_data(
+        web::QueryConfig::default()
+            .error_handler(|e, _| AppError::Parse(format!("Invalid query string: {}", e)).into()),
+    )
+    .app_data(
+        web::PathConfig::default()
+            .error_handler(|e, _| AppError::Parse(format!("Invalid path: {}", e)).into()),
+    )
+    .default_service(web::to(unknown_endpoint));
+}
+
+/// 404 for paths and methods without a handler
+async fn unknown_endpoint(req: HttpRequest) -> Result<HttpResponse, AppError> {
+    Err(AppError::NotFound(format!(
+        "No endpoint for {} {}",
+        req.method(),
+        req.path()
+    )))
+}
+
+impl Validate for SearchRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self.query.trim().is_empty() {
+            errors.push(FieldError::new("query", "must not be empty"));
+        }
+        if self
+            .top_k
+            .is_some_and(|k| !(1..=technical::MAX_TOP_K).contains(&k))
+        {
+            errors.push(FieldError::new(
+                "top_k",
+                format!("must be between 1 and {}", technical::MAX_TOP_K),
+            ));
+        }
+
+        validate_date_range(
+            &mut errors,
+            self.start_date.as_deref(),
+            self.end_date.as_deref(),
+        );
+
+        let relative = [
+            ("last_hours", self.last_hours.is_some()),
+            ("last_days", self.last_days.is_some()),
+            ("last", self.last.is_some()),
+        ];
+        let mut relative_fields = relative.iter().filter(|(_, set)| *set).map(|(f, _)| *f);
+        if let Some(field) = relative_fields.next() {
+            if self.start_date.is_some() || self.end_date.is_some() {
+                errors.push(FieldError::new(
+                    field,
+                    "cannot be combined with start_date or end_date",
+                ));
+            }
+            for other in relative_fields {
+                errors.push(FieldError::new(
+                    other,
+                    format!("cannot be combined with {}", field),
+                ));
+            }
+        }
+        if self.last_hours == Some(0) {
+            errors.push(FieldError::new("last_hours", "must be at least 1"));
+        }
+        if self.last_days == Some(0) {
+            errors.push(FieldError::new("last_days", "must be at least 1"));
+        }
+        if let Some(alpha) = self.hybrid_alpha {
+            if !(0.0..=1.0).contains(&alpha) {
+                errors.push(FieldError::new("hybrid_alpha", "must be between 0 and 1"));
+            } else if !self.hybrid {
+                errors.push(FieldError::new("hybrid_alpha", "requires hybrid"));
+            }
+        }
+        if let Some(last) = &self.last
+            && !parse_iso8601_duration(last).is_ok_and(|d| d > Duration::zero())
+        {
+            errors.push(FieldError::new(
+                "last",
+                "must be a positive ISO 8601 duration, e.g. PT24H or P7D",
+            ));
+        }
+        errors
+    }
+
+    /// Turn a relative range into `start_date`/`end_date` ending now
+    fn normalize(&mut self) {
+        let range = match (self.last_hours, self.last_days, self.last.as_deref()) {
+            (Some(hours), _, _) => Duration::try_hours(hours as i64),
+            (_, Some(days), _) => Duration::try_days(days as i64),
+            (_, _, Some(last)) => parse_iso8601_duration(last).ok(),
+            _ => None,
+        };
+        let Some(range) = range else {
+            return;
+        };
+        let now = Utc::now();
+        let start = now
+            .checked_sub_signed(range)
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        self.start_date = Some(start.to_rfc3339_opts(SecondsFormat::Secs, true));
+        self.end_date = Some(now.to_rfc3339_opts(SecondsFormat::Secs, true));
+    }
+}
+
+impl Validate for SearchByImageRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self
+            .top_k
+            .is_some_and(|k| !(1..=technical::MAX_TOP_K).contains(&k))
+        {
+            errors.push(FieldError::new(
+                "top_k",
+                format!("must be between 1 and {}", technical::MAX_TOP_K),
+            ));
+        }
+        validate_date_range(
+            &mut errors,
+            self.start_date.as_deref(),
+            self.end_date.as_deref(),
+        );
+        errors
+    }
+}
+
+impl Validate for RecommendRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if !(1..=MAX_SESSION_EXAMPLES).contains(&self.positive.len()) {
+            errors.push(FieldError::new(
+                "positive",
+                format!("must hold between 1 and {} image IDs", MAX_SESSION_EXAMPLES),
+            ));
+        }
+        if self.negative.len() > MAX_SESSION_EXAMPLES {
+            errors.push(FieldError::new(
+                "negative",
+                format!("must hold at most {} image IDs", MAX_SESSION_EXAMPLES),
+            ));
+        }
+        if self
+            .top_k
+            .is_some_and(|k| !(1..=technical::MAX_TOP_K).contains(&k))
+        {
+            errors.push(FieldError::new(
+                "top_k",
+                format!("must be between 1 and {}", technical::MAX_TOP_K),
+            ));
+        }
+        validate_date_range(
+            &mut errors,
+            self.start_date.as_deref(),
+            self.end_date.as_deref(),
+        );
+        errors
+    }
+}
+
+impl Validate for CorrelatedSearchRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self.query.trim().is_empty() {
+            errors.push(FieldError::new("query", "must not be empty"));
+        }
+        let mut cameras: Vec<&str> = self.camera_ids.iter().map(String::as_str).collect();
+        cameras.sort_unstable();
+        cameras.dedup();
+        if cameras.len() < 2 {
+            errors.push(FieldError::new(
+                "camera_ids",
+                "must list at least two different cameras",
+            ));
+        }
+        if self
+            .within_minutes
+            .is_some_and(|m| !(1..=technical::MAX_CORRELATION_WINDOW_MINUTES).contains(&m))
+        {
+            errors.push(FieldError::new(
+                "within_minutes",
+                format!(
+                    "must be between 1 and {}",
+                    technical::MAX_CORRELATION_WINDOW_MINUTES
+                ),
+            ));
+        }
+        if self
+            .top_k
+            .is_some_and(|k| !(1..=technical::MAX_TOP_K).contains(&k))
+        {
+            errors.push(FieldError::new(
+                "top_k",
+                format!("must be between 1 and {}", technical::MAX_TOP_K),
+            ));
+        }
+        validate_date_range(
+            &mut errors,
+            self.start_date.as_deref(),
+            self.end_date.as_deref(),
+        );
+        errors
+    }
+}
+
+impl Validate for CreateCaseRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        if self.title.trim().is_empty() {
+            return vec![FieldError::new("title", "must not be empty")];
+        }
+        Vec::new()
+    }
+}
+
+impl Validate for AttachCaseItemRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        if self.reference.trim().is_empty() {
+            return vec![FieldError::new("reference", "must not be empty")];
+        }
+        Vec::new()
+    }
+}
+
+impl Validate for CreateAlertRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self.name.trim().is_empty() {
+            errors.push(FieldError::new("name", "must not be empty"));
+        }
+        if self.query.trim().is_empty() {
+            errors.push(FieldError::new("query", "must not be empty"));
+        }
+        if self
+            .min_confidence
+            .is_some_and(|c| !(0.0..=1.0).contains(&c))
+        {
+            errors.push(FieldError::new("min_confidence", "must be between 0 and 1"));
+        }
+        if !self.min_score.is_finite() {
+            errors.push(FieldError::new("min_score", "must be a number"));
+        }
+        if self
+            .webhook_url
+            .as_deref()
+            .is_some_and(|url| !url.starts_with("http://") && !url.starts_with("https://"))
+        {
+            errors.push(FieldError::new("webhook_url", "must be an http(s) URL"));
+        }
+        errors
+    }
+}
+
+impl Validate for HybridCalibrationRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        validate_labeled_queries(
+            &mut errors,
+            &self.queries,
+            technical::MAX_CALIBRATION_QUERIES,
+        );
+        if let Some(alphas) = &self.alphas
+            && (alphas.is_empty() || alphas.iter().any(|a| !(0.0..=1.0).contains(a)))
+        {
+            errors.push(FieldError::new(
+                "alphas",
+                "must be a non-empty list of values between 0 and 1",
+            ));
+        }
+        if self
+            .top_k
+            .is_some_and(|k| !(1..=technical::MAX_TOP_K).contains(&k))
+        {
+            errors.push(FieldError::new(
+                "top_k",
+                format!("must be between 1 and {}", technical::MAX_TOP_K),
+            ));
+        }
+        errors
+    }
+}
+
+impl Validate for CreateEvalSetRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self.name.trim().is_empty() {
+            errors.push(FieldError::new("name", "must not be empty"));
+        }
+        validate_labeled_queries(&mut errors, &self.queries, technical::MAX_EVAL_QUERIES);
+        errors
+    }
+}
+
+impl Validate for EvalRunRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self
+            .top_k
+            .is_some_and(|k| !(1..=technical::MAX_TOP_K).contains(&k))
+        {
+            errors.push(FieldError::new(
+                "top_k",
+                format!("must be between 1 and {}", technical::MAX_TOP_K),
+            ));
+        }
+        // Set per query, or not a ranking of images
+        for field in ["query", "top_k", "group_by_camera", "debug", "session_id"] {
+            if self.search.as_ref().is_some_and(|s| s.contains_key(field)) {
+                errors.push(FieldError::new(
+                    &format!("search.{}", field),
+                    "is not supported in evaluation runs",
+                ));
+            }
+        }
+        errors
+    }
+}
+
+/// Check the size of a labeled query set and that every query has words
+/// and relevant images
+fn validate_labeled_queries(errors: &mut Vec<FieldError>, queries: &[LabeledQuery], max: usize) {
+    if !(1..=max).contains(&queries.len()) {
+        errors.push(FieldError::new(
+            "queries",
+            format!("must hold between 1 and {} queries", max),
+        ));
+    }
+    for (i, query) in queries.iter().enumerate() {
+        if query.query.trim().is_empty() {
+            errors.push(FieldError::new(
+                &format!("queries[{}].query", i),
+                "must not be empty",
+            ));
+        }
+        if query.relevant_ids.is_empty() {
+            errors.push(FieldError::new(
+                &format!("queries[{}].relevant_ids", i),
+                "must not be empty",
+            ));
+        }
+    }
+}
+
+/// Check that both ends are RFC 3339 and in order
+fn validate_date_range(errors: &mut Vec<FieldError>, start: Option<&str>, end: Option<&str>) {
+    let mut datetime = |field: &str, value: Option<&str>| {
+        let parsed = value.map(parse_rfc3339_utc).transpose();
+        parsed.unwrap_or_else(|_| {
+            errors.push(FieldError::new(
+                field,
+                "must be an RFC 3339 datetime, e.g. 2025-10-08T00:00:00+07:00",
+            ));
+            None
+        })
+    };
+    let start = datetime("start_date", start);
+    let end = datetime("end_date", end);
+    if let (Some(start), Some(end)) = (start, end)
+        && start > end
+    {
+        errors.push(FieldError::new("end_date", "must not be before start_date"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(body: serde_json::Value) -> Vec<String> {
+        serde_json::from_value::<SearchRequest>(body)
+            .unwrap()
+            .validate()
+            .into_iter()
+            .map(|e| e.field)
+            .collect()
+    }
+
+    #[test]
+    fn test_search_request_validation() {
+        assert!(fields(serde_json::json!({"query": "white pickup", "top_k": 10})).is_empty());
+        assert_eq!(
+            fields(serde_json::json!({"query": " ", "top_k": 0})),
+            ["query", "top_k"]
+        );
+        assert_eq!(
+            fields(serde_json::json!({"query": "truck", "start_date": "yesterday"})),
+            ["start_date"]
+        );
+        assert_eq!(
+            fields(serde_json::json!({
+                "query": "truck",
+                "start_date": "2025-10-08T12:00:00+07:00",
+                "end_date": "2025-10-08T00:00:00+07:00"
+            })),
+            ["end_date"]
+        );
+        assert_eq!(
+            fields(serde_json::json!({
+                "query": "truck",
+                "last_hours": 24,
+                "last_days": 0,
+                "end_date": "2025-10-08T00:00:00+07:00"
+            })),
+            ["last_hours", "last_days", "last_days"]
+        );
+        assert_eq!(
+            fields(serde_json::json!({"query": "truck", "last": "P1M"})),
+            ["last"]
+        );
+        assert_eq!(
+            fields(serde_json::json!({"query": "truck", "hybrid_alpha": 0.7})),
+            ["hybrid_alpha"]
+        );
+        assert!(
+            fields(serde_json::json!({"query": "truck", "hybrid": true, "hybrid_alpha": 0.7}))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_hybrid_calibration_validation() {
+        let fields = |body: serde_json::Value| -> Vec<String> {
+            serde_json::from_value::<HybridCalibrationRequest>(body)
+                .unwrap()
+                .validate()
+                .into_iter()
+                .map(|e| e.field)
+                .collect()
+        };
+        assert!(
+            fields(serde_json::json!({
+                "queries": [{"query": "white pickup", "relevant_ids": [1728394756]}]
+            }))
+            .is_empty()
+        );
+        assert_eq!(
+            fields(serde_json::json!({
+                "queries": [{"query": " ", "relevant_ids": []}],
+                "alphas": [0.5, 1.5],
+                "top_k": 0
+            })),
+            [
+                "queries[0].query",
+                "queries[0].relevant_ids",
+                "alphas",
+                "top_k"
+            ]
+        );
+        assert_eq!(fields(serde_json::json!({"queries": []})), ["queries"]);
+    }
+
+    #[test]
+    fn test_eval_run_validation() {
+        let fields = |body: serde_json::Value| -> Vec<String> {
+            serde_json::from_value::<EvalRunRequest>(body)
+                .unwrap()
+                .validate()
+                .into_iter()
+                .map(|e| e.field)
+                .collect()
+        };
+        assert!(fields(serde_json::json!({"top_k": 10, "search": {"hybrid": true}})).is_empty());
+        assert_eq!(
+            fields(serde_json::json!({"top_k": 0, "search": {"debug": true, "query": "bus"}})),
+            ["top_k", "search.query", "search.debug"]
+        );
+    }
+
+    #[test]
+    fn test_search_by_image_validation() {
+        let fields = |body: serde_json::Value| -> Vec<String> {
+            serde_json::from_value::<SearchByImageRequest>(body)
+                .unwrap()
+                .validate()
+                .into_iter()
+                .map(|e| e.field)
+                .collect()
+        };
+        assert!(
+            fields(serde_json::json!({"image_path": "cctv01/a.jpg", "top_k": 1000})).is_empty()
+        );
+        assert_eq!(
+            fields(serde_json::json!({"image_path": "cctv01/a.jpg", "top_k": 1001})),
+            ["top_k"]
+        );
+        assert_eq!(
+            fields(serde_json::json!({"image_base64": "AAAA", "top_k": 0, "end_date": "today"})),
+            ["top_k", "end_date"]
+        );
+    }
+
+    #[test]
+    fn test_recommend_validation() {
+        let fields = |body: serde_json::Value| -> Vec<String> {
+            serde_json::from_value::<RecommendRequest>(body)
+                .unwrap()
+                .validate()
+                .into_iter()
+                .map(|e| e.field)
+                .collect()
+        };
+        assert!(fields(serde_json::json!({"positive": ["12345"], "top_k": 10})).is_empty());
+        assert_eq!(
+            fields(serde_json::json!({"positive": [], "top_k": 5000})),
+            ["positive", "top_k"]
+        );
+        let many: Vec<String> = (0..=MAX_SESSION_EXAMPLES).map(|i| i.to_string()).collect();
+        assert_eq!(
+            fields(serde_json::json!({"positive": ["1"], "negative": many, "top_k": 0})),
+            ["negative", "top_k"]
+        );
+    }
+
+    #[test]
+    fn test_correlated_search_validation() {
+        let fields = |body: serde_json::Value| -> Vec<String> {
+            serde_json::from_value::<CorrelatedSearchRequest>(body)
+                .unwrap()
+                .validate()
+                .into_iter()
+                .map(|e| e.field)
+                .collect()
+        };
+        assert!(
+            fields(serde_json::json!({
+                "query": "red sedan",
+                "camera_ids": ["gate_a", "gate_b"],
+                "within_minutes": 15
+            }))
+            .is_empty()
+        );
+        assert_eq!(
+            fields(serde_json::json!({
+                "query": "red sedan",
+                "camera_ids": ["gate_a", "gate_a"],
+                "within_minutes": 0
+            })),
+            ["camera_ids", "within_minutes"]
+        );
+    }
+
+    #[test]
+    fn test_relative_range_sets_dates() {
+        let mut request: SearchRequest =
+            serde_json::from_value(serde_json::json!({"query": "truck", "last": "PT24H"})).unwrap();
+        assert!(request.validate().is_empty());
+        request.normalize();
+
+        let start = parse_rfc3339_utc(request.start_date.as_deref().unwrap()).unwrap();
+        let end = parse_rfc3339_utc(request.end_date.as_deref().unwrap()).unwrap();
+        assert_eq!(end - start, Duration::hours(24));
+    }
+}
@@ -0,0 +1,27 @@
+//! Vehicle Type Handlers
+//!
+//! Labels of the raw vehicle codes, for filter dropdowns.
+
+use super::AppState;
+use super::etag::json_with_etag;
+use crate::middleware::{Authorized, Reader};
+use actix_web::{HttpRequest, Responder, get, web};
+
+/// Handler listing the labels of the `vehicle_type` and `yolo_id` codes
+#[utoipa::path(
+    get,
+    path = "/vehicle_types",
+    responses(
+        (status = 200, description = "Mapped codes, in mapping file order", body = VehicleTypeMapping),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag")
+    ),
+    tag = "Search API"
+)]
+#[get("/vehicle_types")]
+pub async fn vehicle_types(
+    _: Authorized<Reader>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    json_with_etag(&req, state.scheduler.vehicle_types.mapping())
+}
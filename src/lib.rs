@@ -0,0 +1,7 @@
+//! rust-cctv Library
+//!
+//! Typed HTTP client for services calling this one, behind the `client`
+//! Cargo feature.
+
+#[cfg(feature = "client")]
+pub mod client;
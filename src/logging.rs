@@ -0,0 +1,104 @@
+//! Logging
+//!
+//! `tracing` subscriber setup with text or JSON output and a log level that
+//! follows configuration reloads.
+
+use crate::config::TunablesHandle;
+use crate::error::AppError;
+use std::str::FromStr;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Registry, fmt, reload};
+
+/// Log output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines
+    Text,
+    /// One JSON object per event, for log ingestion
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => Err(format!("expected text or json, got '{}'", other)),
+        }
+    }
+}
+
+/// Parse a level (`info`) or a directive list (`info,rust_cctv=debug`)
+pub fn parse_filter(level: &str) -> Result<EnvFilter, AppError> {
+    EnvFilter::try_new(level)
+        .map_err(|e| AppError::Config(format!("Invalid LOG_LEVEL '{}': {}", level, e)))
+}
+
+/// Handle to the level filter of the installed subscriber
+#[derive(Clone)]
+pub struct LogLevelHandle {
+    handle: reload::Handle<EnvFilter, Registry>,
+}
+
+impl LogLevelHandle {
+    /// Replace the active level filter
+    pub fn set(&self, level: &str) -> Result<(), AppError> {
+        self.handle
+            .reload(parse_filter(level)?)
+            .map_err(|e| AppError::Config(format!("Failed to change log level: {}", e)))
+    }
+
+    /// Apply `log_level` whenever a reload changes it
+    pub fn follow(self, tunables: &TunablesHandle) {
+        let mut tunables_rx = tunables.subscribe();
+        let mut level = tunables.current().log_level;
+
+        tokio::spawn(async move {
+            while tunables_rx.changed().await.is_ok() {
+                let new_level = tunables_rx.borrow_and_update().log_level.clone();
+                if new_level == level {
+                    continue;
+                }
+
+                match self.set(&new_level) {
+                    Ok(()) => {
+                        tracing::info!(level = %new_level, "Log level changed");
+                        level = new_level;
+                    }
+                    Err(e) => tracing::error!(error = %e, "Failed to change log level"),
+                }
+            }
+        });
+    }
+}
+
+/// Install the global subscriber
+pub fn init(level: &str, format: LogFormat) -> Result<LogLevelHandle, AppError> {
+    let (filter, handle) = reload::Layer::new(parse_filter(level)?);
+    let registry = tracing_subscriber::registry().with(filter);
+
+    match format {
+        LogFormat::Text => registry.with(fmt::layer()).try_init(),
+        LogFormat::Json => registry.with(fmt::layer().json()).try_init(),
+    }
+    .map_err(|e| AppError::Config(format!("Failed to initialize logging: {}", e)))?;
+
+    Ok(LogLevelHandle { handle })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_settings() {
+        assert_eq!("JSON".parse::<LogFormat>(), Ok(LogFormat::Json));
+        assert!("yaml".parse::<LogFormat>().is_err());
+
+        assert!(parse_filter("info,rust_cctv=debug").is_ok());
+        assert!(parse_filter("rust_cctv=loud").is_err());
+    }
+}
@@ -1,92 +1,681 @@
 //! CCTV Search Backend
 //!
 //! A high-performance REST API for vehicle image search using vector embeddings.
+//! Built without the `server` feature, it is the edge ingest agent instead.
 
-use actix_web::{App, HttpServer, web};
+// Edge builds use the ingest half of the shared modules
+#![cfg_attr(not(feature = "server"), allow(dead_code))]
+
+#[cfg(feature = "server")]
+use actix_web::{App, HttpResponse, HttpServer, middleware::from_fn, web};
 use dotenv::dotenv;
+#[cfg(feature = "server")]
 use qdrant_client::Qdrant;
+#[cfg(feature = "server")]
 use std::sync::Arc;
+#[cfg(feature = "server")]
+use tracing::{error, info, warn};
 
+#[cfg(feature = "server")]
+mod backfill;
+#[cfg(feature = "server")]
+mod bootstrap;
+mod build_info;
 mod clients;
+#[cfg(feature = "server")]
+mod collisions;
 mod config;
+mod config_file;
+#[cfg(feature = "server")]
 mod docs;
+#[cfg(feature = "edge-ingest")]
+mod edge;
+mod error;
+#[cfg(feature = "server")]
+mod features;
+#[cfg(feature = "grpc")]
+mod grpc;
+#[cfg(feature = "server")]
 mod handlers;
+mod logging;
+#[cfg(feature = "server")]
+mod middleware;
+#[cfg(feature = "server")]
+mod migrations;
 mod models;
 mod scheduler;
 mod services;
 
-use docs::{ApiDoc, SwaggerUi};
-use utoipa::OpenApi;
+#[cfg(not(any(feature = "server", feature = "edge-ingest")))]
+compile_error!("Enable the `server` feature, the `edge-ingest` feature, or both");
 
-use config::{Config, technical};
+#[cfg(feature = "server")]
+use backfill::Backfill;
+#[cfg(feature = "server")]
+use clients::cctv_client::CctvApi;
+#[cfg(feature = "server")]
+use config::{Config, TunablesHandle, technical};
+#[cfg(feature = "server")]
+use error::AppError;
+#[cfg(feature = "server")]
+use features::{Feature, FeatureRegistry};
+#[cfg(feature = "server")]
 use scheduler::{SchedulerContext, start_scheduler};
+#[cfg(feature = "server")]
+use services::{
+    AlertStore, BackupVerifier, CaseStore, EmbeddingCache, EvalSetStore, IntegrityVerifier,
+    MaintenanceMode, RequestMetrics, SchedulerRunHistory, SearchSessions, ShadowSearch,
+    ShardRebalancer, ShardRouter, SloTracker, SnapshotRestorer, UrlRewriter, VehicleTypes,
+};
 
+#[cfg(feature = "server")]
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
 
     // Load configuration
     let config = Config::from_env().expect("Failed to load configuration");
-    config.print_summary();
+    let log_level =
+        logging::init(&config.log_level, config.log_format).expect("Failed to initialize logging");
+    config.log_summary();
+
+    // Resolve optional subsystems from the build and DISABLED_FEATURES
+    let features =
+        FeatureRegistry::new(&config.disabled_features).map_err(std::io::Error::other)?;
+    features.log_summary();
+    let features = Arc::new(features);
+
+    // Fail fast while a dependency keeps failing
+    if features.is_enabled(Feature::CircuitBreaker) {
+        services::install_circuit_breakers(services::CircuitBreakers::new(
+            config.circuit_failure_threshold,
+            std::time::Duration::from_secs(config.circuit_open_secs),
+        ));
+    }
+
+    // Share the AI service between searches, ingestion and backfills
+    if let Some(rps) = config.ai_rate_limit_rps {
+        services::install_ai_rate_limiter(services::AiRateLimiter::new(
+            rps,
+            config.ai_rate_limit_burst,
+        ));
+    }
+    if let Some(max_in_flight) = config.ai_max_in_flight {
+        services::install_ai_dispatcher(services::AiDispatcher::new(
+            max_in_flight,
+            config.ai_bulk_every,
+        ));
+    }
+
+    // The edge ingest agent needs neither Qdrant nor the HTTP server
+    #[cfg(feature = "edge-ingest")]
+    if std::env::args().nth(1).as_deref() == Some("edge-ingest") {
+        return edge::run(config).await.map_err(std::io::Error::other);
+    }
 
     // Initialize Qdrant client
-    let qdrant = Qdrant::from_url(&config.qdrant_url)
-        .api_key(config.qdrant_api_key.clone())
-        .build()
-        .expect("Failed to initialize Qdrant client");
+    let qdrant =
+        services::build_qdrant_client(&config).expect("Failed to initialize Qdrant client");
 
     let qdrant = Arc::new(qdrant);
     let http_client = reqwest::Client::new();
 
-    // Setup Qdrant collection
-    setup_qdrant(&qdrant, &config.collection_name).await;
+    // One-shot commands
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("bootstrap") {
+        let options =
+            bootstrap::BootstrapOptions::from_args(&args[1..]).map_err(std::io::Error::other)?;
+        return bootstrap::run(qdrant, http_client, config, options)
+            .await
+            .map_err(std::io::Error::other);
+    }
+    if args.first().map(String::as_str) == Some("find-collisions") {
+        let options =
+            collisions::CollisionOptions::from_args(&args[1..]).map_err(std::io::Error::other)?;
+        return collisions::run(qdrant, http_client, config, options)
+            .await
+            .map_err(std::io::Error::other);
+    }
+
+    // Setup Qdrant collections (one per shard)
+    let router = Arc::new(ShardRouter::new(
+        &config.collection_name,
+        config.collection_shards,
+    ));
+
+    // Collections pinned to a legacy embedding model keep embedding with it
+    let embedding_models = Arc::new(
+        services::EmbeddingModels::load(&config, router.collections())
+            .map_err(std::io::Error::other)?,
+    );
+    for (collection, model) in embedding_models.pins() {
+        info!(collection = %collection, model = %model, "Embedding model pinned");
+    }
+
+    // Each collection stores vectors of its embedding model's size
+    for collection in router.collections() {
+        let vector_size = embedding_models.for_collection(collection).vector_size;
+        setup_qdrant(&qdrant, collection, vector_size, &config)
+            .await
+            .map_err(std::io::Error::other)?;
+    }
+
+    // Fail fast if an AI service produces vectors the collections can't store
+    for model in embedding_models.models() {
+        match services::verify_embedding_dimension(
+            &http_client,
+            &model.ai_service_url,
+            model.vector_size,
+        )
+        .await
+        {
+            Ok(()) => info!(
+                model = %model.name,
+                dimension = model.vector_size,
+                "AI service dimension OK"
+            ),
+            Err(e @ AppError::Config(_)) => return Err(std::io::Error::other(e)),
+            Err(e) => warn!(
+                model = %model.name,
+                error = %e,
+                "Could not verify the AI service dimension"
+            ),
+        }
+    }
+
+    // Shared runtime tunables, reloadable via SIGHUP or POST /admin/reload
+    let tunables = TunablesHandle::new(config.tunables());
+    #[cfg(unix)]
+    spawn_sighup_reload(tunables.clone());
+    log_level.follow(&tunables);
+
+    // Request metrics and latency SLOs exposed at /metrics
+    let metrics = Arc::new(RequestMetrics::default());
+    let slo = Arc::new(SloTracker::new(
+        config.slo_targets.clone(),
+        config.slo_window_minutes,
+    ));
+    if let Some(webhook_url) = config.slo_alert_webhook.clone() {
+        services::spawn_burn_rate_alerts(
+            slo.clone(),
+            http_client.clone(),
+            webhook_url,
+            config.slo_burn_rate_alert,
+        );
+    }
+
+    // Maintenance switch shared by the HTTP server and the scheduler
+    let maintenance = Arc::new(MaintenanceMode::default());
+
+    // Labels stored next to the raw vehicle codes and served at /vehicle_types
+    let vehicle_types =
+        Arc::new(VehicleTypes::load(&config.vehicle_types_path).map_err(std::io::Error::other)?);
+    info!(
+        path = %config.vehicle_types_path,
+        labels = vehicle_types.len(),
+        "Vehicle type labels loaded"
+    );
+
+    // Saved searches run against every batch the scheduler stores
+    let alerts = Arc::new(AlertStore::load(&config.alerts_path).map_err(std::io::Error::other)?);
+    info!(path = %config.alerts_path, alerts = alerts.list(None).len(), "Alerts loaded");
+
+    // Shared by the background scheduler and manual runs via /scheduler/trigger
+    let scheduler_ctx = SchedulerContext::new(
+        qdrant.clone(),
+        http_client.clone(),
+        config.clone(),
+        tunables.clone(),
+        maintenance.clone(),
+        metrics.clone(),
+        Arc::new(SchedulerRunHistory::default()),
+    )
+    .with_vehicle_types(vehicle_types)
+    .with_embedding_models(embedding_models)
+    .with_alerts(alerts);
 
     // Start background scheduler
-    let scheduler_ctx = SchedulerContext::new(qdrant.clone(), http_client.clone(), config.clone());
-    start_scheduler(scheduler_ctx).await;
+    let mut scheduler_task = None;
+    if features.is_enabled(Feature::Scheduler) {
+        scheduler_task = Some(start_scheduler(scheduler_ctx.clone()).await);
 
-    // Give scheduler time to initialize
-    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+        // Give scheduler time to initialize
+        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    }
+
+    // Historical backfill, resumed if the process stopped mid-job
+    let backfill =
+        Arc::new(Backfill::load(&config.backfill_state_path).map_err(std::io::Error::other)?);
+    if backfill.interrupted() && features.is_enabled(Feature::AdminApi) {
+        info!("Resuming interrupted backfill");
+        let backfill = backfill.clone();
+        let ctx = scheduler_ctx.clone();
+        tokio::spawn(async move { backfill.run(&ctx).await });
+    }
 
     // Start HTTP server
     let ai_service_url = config.ai_service_url.clone();
-    let collection_name = config.collection_name.clone();
-    let server_port = config.server_port;
-
-    HttpServer::new(move || {
-        App::new()
-            .app_data(web::Data::new(handlers::AppState {
-                qdrant: qdrant.clone(),
-                http_client: http_client.clone(),
-                ai_service_url: ai_service_url.clone(),
-                collection_name: collection_name.clone(),
-            }))
-            .service(
-                SwaggerUi::new("/swagger-ui/{_:.*}")
-                    .url("/api-docs/openapi.json", ApiDoc::openapi()),
-            )
-            .service(handlers::search_vehicles)
-            .service(handlers::insert_image)
+    let cctv_api = CctvApi::new(
+        config.cctv_api_url.clone(),
+        config.cctv_authorize_code.clone(),
+        config.cctv_user_auth.clone(),
+        config.cctv_client_id.clone(),
+    );
+    let rebalancer = Arc::new(ShardRebalancer::default());
+    let integrity = Arc::new(IntegrityVerifier::default());
+    let snapshots = Arc::new(SnapshotRestorer::new(&config).map_err(std::io::Error::other)?);
+    let backup_verifier = Arc::new(BackupVerifier::default());
+    if let Some(hours) = config.backup_verify_every_hours {
+        services::spawn_backup_verification(
+            backup_verifier.clone(),
+            qdrant.clone(),
+            snapshots.clone(),
+            router.clone(),
+            metrics.clone(),
+            config.vector_layout,
+            std::time::Duration::from_secs(hours * 3600),
+        );
+        info!(every_hours = hours, "Backup verification scheduled");
+    }
+    let sessions = Arc::new(SearchSessions::default());
+    let cases = Arc::new(CaseStore::load(&config.cases_path).map_err(std::io::Error::other)?);
+    let eval_sets =
+        Arc::new(EvalSetStore::load(&config.eval_sets_path).map_err(std::io::Error::other)?);
+    let embedding_cache = Arc::new(EmbeddingCache::new(
+        config.embedding_cache_entries,
+        std::time::Duration::from_secs(config.embedding_cache_ttl_secs),
+    ));
+
+    // Without keys the API is open to anyone who can reach the port
+    let api_keys = Arc::new(middleware::ApiKeys::load(&config).map_err(std::io::Error::other)?);
+    if api_keys.is_enabled() {
+        info!(keys = api_keys.len(), "API key authentication enabled");
+    } else {
+        warn!("API key authentication disabled: set API_KEYS or API_KEYS_FILE");
+    }
+
+    // Edge devices authenticate `/ingest/batch` with their own keys
+    let device_keys =
+        Arc::new(middleware::DeviceKeys::load(&config).map_err(std::io::Error::other)?);
+    if device_keys.is_enabled() {
+        info!(devices = device_keys.len(), "Edge device ingest enabled");
+    }
+
+    // Bearer tokens carry the caller's role; the JWKS key set is kept fresh
+    let jwt = Arc::new(
+        middleware::JwtAuth::load(&config, &http_client)
+            .await
+            .map_err(std::io::Error::other)?,
+    );
+    if jwt.is_enabled() {
+        info!("JWT authentication enabled");
+        middleware::spawn_jwks_refresh(jwt.clone(), http_client.clone());
+    }
+
+    // One state for both servers; every field is a cheap shared handle
+    let state = web::Data::new(handlers::AppState {
+        qdrant,
+        http_client,
+        ai_service_url,
+        cctv_api,
+        router,
+        rebalancer,
+        integrity,
+        snapshots,
+        backup_verifier,
+        backfill,
+        query_image_dir: config.query_image_dir.clone(),
+        embed_image_root: config.embed_image_root.clone(),
+        embedding_model: config.embedding_model.clone(),
+        vector_size: config.vector_size,
+        vector_layout: config.vector_layout,
+        tunables,
+        maintenance,
+        maintenance_allowlist: config.maintenance_allowlist.clone(),
+        api_keys,
+        jwt,
+        device_keys,
+        ingest_sequences: Arc::new(services::IngestSequences::default()),
+        client_rate_limiter: config.client_rate_limit_rps.map(|rps| {
+            Arc::new(middleware::ClientRateLimiter::new(
+                rps,
+                config.client_rate_limit_burst,
+                config.client_rate_limit_paths.clone(),
+            ))
+        }),
+        cors: Arc::new(middleware::CorsPolicy::new(&config)),
+        url_rewriter: Arc::new(UrlRewriter::new(config.image_url_rewrites.clone())),
+        cameras: scheduler_ctx.cameras.clone(),
+        features: features.clone(),
+        metrics,
+        slo,
+        sessions,
+        cases,
+        eval_sets,
+        embedding_cache,
+        shadow: Arc::new(ShadowSearch::default()),
+        scheduler: scheduler_ctx,
+    });
+
+    // With ADMIN_PORT set, operational endpoints are only reachable on that port
+    let admin_port = config.admin_port;
+    // SIGTERM/SIGINT stop the servers, which then drain open connections
+    let shutdown_timeout = std::time::Duration::from_secs(config.shutdown_timeout_secs);
+    let scheduler = state.scheduler.clone();
+    spawn_stream_close(scheduler.ingest_feed.clone());
+    let public_server = HttpServer::new({
+        let state = state.clone();
+        let features = features.clone();
+        move || {
+            App::new()
+                .app_data(state.clone())
+                .wrap(from_fn(middleware::client_rate_limit))
+                .wrap(from_fn(middleware::track_requests))
+                .wrap(from_fn(middleware::maintenance_guard))
+                .wrap(from_fn(middleware::jwt_guard))
+                .wrap(from_fn(middleware::api_key_guard))
+                .wrap(from_fn(middleware::cors))
+                .configure(handlers::configure_extractors)
+                .configure(|cfg| configure_docs(cfg, &features))
+                .configure(|cfg| configure_public(cfg, &features))
+                .configure(|cfg| {
+                    if admin_port.is_none() {
+                        configure_admin(cfg, &features);
+                    }
+                })
+        }
     })
-    .bind(("0.0.0.0", server_port))?
-    .run()
-    .await
+    .shutdown_timeout(config.shutdown_timeout_secs)
+    .bind(("0.0.0.0", config.server_port))?
+    .run();
+
+    // The gRPC API shares the state and stops after the HTTP servers
+    #[cfg(feature = "grpc")]
+    let grpc_server = match config.grpc_port {
+        Some(port) if features.is_enabled(Feature::Grpc) => {
+            Some(spawn_grpc_server(port, state.clone().into_inner())?)
+        }
+        _ => None,
+    };
+
+    let served = match admin_port {
+        None => public_server.await,
+        Some(admin_port) => {
+            let admin_server = HttpServer::new(move || {
+                App::new()
+                    .app_data(state.clone())
+                    .wrap(from_fn(middleware::track_requests))
+                    .wrap(from_fn(middleware::maintenance_guard))
+                    .wrap(from_fn(middleware::jwt_guard))
+                    .wrap(from_fn(middleware::api_key_guard))
+                    .configure(handlers::configure_extractors)
+                    .configure(|cfg| configure_admin(cfg, &features))
+            })
+            .workers(technical::ADMIN_SERVER_WORKERS)
+            .shutdown_timeout(config.shutdown_timeout_secs)
+            .bind(("0.0.0.0", admin_port))?
+            .run();
+            info!(
+                port = admin_port,
+                "Admin endpoints served on a separate port"
+            );
+
+            tokio::try_join!(public_server, admin_server).map(|_| ())
+        }
+    };
+
+    #[cfg(feature = "grpc")]
+    if let Some((stop, task)) = grpc_server {
+        let _ = stop.send(());
+        match task.await {
+            Ok(Err(e)) => error!(error = %e, "gRPC server failed"),
+            Err(e) => error!(error = %e, "gRPC server task failed"),
+            Ok(Ok(())) => {}
+        }
+    }
+
+    // Let fetch runs in flight finish their upserts before the process exits
+    info!("HTTP servers stopped, waiting for fetch runs in flight");
+    if !scheduler.gate.close(shutdown_timeout).await {
+        warn!(
+            timeout_secs = config.shutdown_timeout_secs,
+            "Fetch runs still in flight at shutdown, abandoning them"
+        );
+    }
+    if let Some(task) = scheduler_task
+        && let Err(e) = task.await
+    {
+        error!(error = %e, "Scheduler task failed");
+    }
+    info!("Shutdown complete");
+    served
+}
+
+/// Edge builds only run the ingest agent, forwarding to the central API
+#[cfg(not(feature = "server"))]
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    dotenv().ok();
+
+    let config = config::Config::from_env().expect("Failed to load configuration");
+    logging::init(&config.log_level, config.log_format).expect("Failed to initialize logging");
+    edge::run(config).await.map_err(std::io::Error::other)
+}
+
+/// Register the search, insertion, session, case, health and tool endpoints
+#[cfg(feature = "server")]
+fn configure_public(cfg: &mut web::ServiceConfig, features: &FeatureRegistry) {
+    cfg.service(handlers::search_vehicles)
+        .service(handlers::search_by_image)
+        .service(handlers::recommend)
+        .service(handlers::search_correlated)
+        .service(handlers::create_session)
+        .service(handlers::get_session)
+        .service(handlers::add_session_feedback)
+        .service(handlers::delete_session)
+        .service(handlers::create_case)
+        .service(handlers::list_cases)
+        .service(handlers::get_case)
+        .service(handlers::attach_case_item)
+        .service(handlers::create_alert)
+        .service(handlers::list_alerts)
+        .service(handlers::get_alert)
+        .service(handlers::delete_alert)
+        .service(handlers::insert_image)
+        .service(handlers::insert_images)
+        .service(handlers::ingest_batch)
+        .service(handlers::live_ingest)
+        .service(handlers::live_events)
+        .service(handlers::list_images)
+        .service(handlers::vehicle_types)
+        .service(handlers::healthz)
+        .service(handlers::readyz)
+        .service(handlers::service_status)
+        .service(handlers::scheduler_runs)
+        .service(handlers::scheduler_run)
+        .service(handlers::version);
+
+    if features.is_enabled(Feature::Tools) {
+        cfg.service(handlers::embed_text)
+            .service(handlers::embed_image);
+    }
+}
+
+/// Register the admin, maintenance, metrics and dev-only endpoints
+#[cfg(feature = "server")]
+fn configure_admin(cfg: &mut web::ServiceConfig, features: &FeatureRegistry) {
+    cfg.service(handlers::metrics);
+
+    if features.is_enabled(Feature::AdminApi) {
+        cfg.service(handlers::reload_config)
+            .service(handlers::set_maintenance)
+            .service(handlers::delete_images)
+            .service(handlers::retag_images)
+            .service(handlers::calibrate_hybrid)
+            .service(handlers::create_eval_set)
+            .service(handlers::list_eval_sets)
+            .service(handlers::get_eval_set)
+            .service(handlers::delete_eval_set)
+            .service(handlers::run_eval_set)
+            .service(handlers::trigger_fetch)
+            .service(handlers::start_backfill)
+            .service(handlers::backfill_status)
+            .service(handlers::pause_backfill)
+            .service(handlers::resume_backfill)
+            .service(handlers::flush_writes)
+            .service(handlers::collection_info)
+            .service(handlers::shard_status)
+            .service(handlers::rebalance_shards)
+            .service(handlers::integrity_status)
+            .service(handlers::verify_integrity)
+            .service(handlers::dead_letters)
+            .service(handlers::create_snapshots)
+            .service(handlers::snapshot_list)
+            .service(handlers::restore_snapshot)
+            .service(handlers::backup_verification);
+    }
+
+    configure_chaos(cfg, features);
+}
+
+/// Serve the OpenAPI document, with Swagger UI when that feature is enabled
+#[cfg(feature = "server")]
+#[cfg_attr(not(feature = "swagger-ui"), allow(unused_variables))]
+fn configure_docs(cfg: &mut web::ServiceConfig, features: &FeatureRegistry) {
+    cfg.route(
+        "/openapi.json",
+        web::get().to(|| async { HttpResponse::Ok().json(docs::openapi()) }),
+    );
+
+    #[cfg(feature = "swagger-ui")]
+    if features.is_enabled(Feature::SwaggerUi) {
+        cfg.service(
+            docs::SwaggerUi::new("/swagger-ui/{_:.*}")
+                .url("/api-docs/openapi.json", docs::openapi()),
+        );
+        return;
+    }
+
+    cfg.route(
+        "/api-docs/openapi.json",
+        web::get().to(|| async { HttpResponse::Ok().json(docs::openapi()) }),
+    );
+}
+
+/// Register the dev-only chaos endpoints when that feature is enabled
+#[cfg(feature = "server")]
+#[cfg_attr(not(feature = "chaos"), allow(unused_variables))]
+fn configure_chaos(cfg: &mut web::ServiceConfig, features: &FeatureRegistry) {
+    #[cfg(feature = "chaos")]
+    if features.is_enabled(Feature::Chaos) {
+        cfg.service(handlers::get_chaos)
+            .service(handlers::set_chaos_settings)
+            .service(handlers::clear_chaos);
+    }
 }
 
 /// Setup Qdrant collection and indices
-async fn setup_qdrant(qdrant: &Arc<Qdrant>, collection_name: &str) {
-    println!("Setting up collection...");
+///
+/// Only a collection whose vectors don't match the configuration is fatal.
+#[cfg(feature = "server")]
+async fn setup_qdrant(
+    qdrant: &Arc<Qdrant>,
+    collection_name: &str,
+    vector_size: usize,
+    config: &Config,
+) -> Result<(), AppError> {
+    info!(collection = collection_name, "Setting up collection");
 
-    match services::ensure_collection_exists(qdrant, collection_name, technical::VECTOR_SIZE).await
-    {
-        Ok(_) => println!("✅ Collection is ready"),
-        Err(e) => println!("⚠️  Warning: {}", e),
+    let settings = services::CollectionSettings {
+        vector_size,
+        ..services::CollectionSettings::new(config)
+    };
+    match services::ensure_collection_exists(qdrant, collection_name, &settings).await {
+        Ok(_) => info!(collection = collection_name, "Collection is ready"),
+        Err(e @ AppError::Config(_)) => return Err(e),
+        Err(e) => warn!(collection = collection_name, error = %e, "Collection setup failed"),
+    }
+
+    match migrations::run_pending(qdrant, collection_name).await {
+        Ok(applied) if applied.is_empty() => {
+            info!(
+                collection = collection_name,
+                "Collection schema is up to date"
+            )
+        }
+        Ok(applied) => info!(collection = collection_name, ?applied, "Applied migrations"),
+        Err(e) => warn!(collection = collection_name, error = %e, "Migrations failed"),
     }
+    Ok(())
+}
+
+/// Shutdown sender and task of the gRPC server
+#[cfg(feature = "grpc")]
+type GrpcServer = (
+    tokio::sync::oneshot::Sender<()>,
+    tokio::task::JoinHandle<Result<(), tonic::transport::Error>>,
+);
 
-    println!("Creating datetime field index...");
+/// Serve the gRPC API on `port` until the returned sender fires
+#[cfg(feature = "grpc")]
+fn spawn_grpc_server(port: u16, state: Arc<handlers::AppState>) -> std::io::Result<GrpcServer> {
+    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
+    let incoming = tonic::transport::server::TcpIncoming::new(addr, true, None)
+        .map_err(std::io::Error::other)?;
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let server = tonic::transport::Server::builder()
+        .add_service(grpc::CctvSearchService::new(state))
+        .serve_with_incoming_shutdown(incoming, async {
+            let _ = stopped.await;
+        });
+    info!(port, "gRPC API served on a separate port");
+    Ok((stop, tokio::spawn(server)))
+}
+
+/// Close `/ws` and `/events` streams on SIGINT or SIGTERM; they would
+/// otherwise hold the connection drain open until `SHUTDOWN_TIMEOUT_SECS`
+#[cfg(feature = "server")]
+fn spawn_stream_close(feed: Arc<services::IngestFeed>) {
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        feed.close();
+    });
+}
 
-    match services::create_datetime_index(qdrant, collection_name).await {
-        Ok(_) => println!("✅ Datetime field index created successfully"),
-        Err(e) => println!("⚠️  Warning: {}", e),
+/// Resolves on SIGINT or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    if let Ok(mut terminate) =
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+    {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+        return;
     }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Reload runtime tunables whenever the process receives SIGHUP
+#[cfg(all(feature = "server", unix))]
+fn spawn_sighup_reload(tunables: TunablesHandle) {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    tokio::spawn(async move {
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(s) => s,
+            Err(e) => {
+                warn!(error = %e, "SIGHUP reload disabled");
+                return;
+            }
+        };
+
+        while hangup.recv().await.is_some() {
+            match tunables.reload() {
+                Ok(_) => info!("Configuration reloaded (SIGHUP)"),
+                Err(e) => error!(error = %e, "Configuration reload failed"),
+            }
+        }
+    });
 }
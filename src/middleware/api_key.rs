@@ -0,0 +1,167 @@
+//! API Key Guard
+//!
+//! Rejects requests with 401 unless they carry one of the configured keys in
+//! the `X-API-Key` header. A key may be bound to a tenant, which confines its
+//! requests to that tenant.
+
+use crate::config::Config;
+use crate::error::{AppError, ErrorResponse};
+use crate::handlers::AppState;
+use crate::middleware::{INGEST_BATCH_PATH, PROBE_PATHS};
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpResponse, web};
+
+/// Header carrying the client's key
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+/// A configured key and the tenant it is bound to, if any
+#[derive(Debug, Clone, PartialEq)]
+struct ApiKey {
+    key: String,
+    tenant: Option<String>,
+}
+
+/// Keys accepted by the guard; with none configured every request passes
+#[derive(Debug, Default)]
+pub struct ApiKeys {
+    keys: Vec<ApiKey>,
+}
+
+impl ApiKeys {
+    /// Keys from `API_KEYS` plus those in `API_KEYS_FILE`, if set
+    pub fn load(config: &Config) -> Result<Self, AppError> {
+        let mut entries = config.api_keys.clone();
+        if let Some(path) = &config.api_keys_file {
+            let content = std::fs::read_to_string(path).map_err(|e| {
+                AppError::Config(format!("Failed to read API_KEYS_FILE '{}': {}", path, e))
+            })?;
+            entries.extend(parse_key_file(&content));
+        }
+        if config.api_keys_file.is_some() && entries.is_empty() {
+            return Err(AppError::Config(
+                "API_KEYS_FILE contains no keys".to_string(),
+            ));
+        }
+        let keys = entries
+            .iter()
+            .map(|entry| parse_key(entry, &config.tenants))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { keys })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether `key` is one of the configured keys
+    pub fn accepts(&self, key: &[u8]) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Tenant `key` is bound to, if it is a configured key bound to one
+    pub fn tenant_of(&self, key: &[u8]) -> Option<&str> {
+        self.find(key)?.tenant.as_deref()
+    }
+
+    fn find(&self, key: &[u8]) -> Option<&ApiKey> {
+        // Check every key so the time taken doesn't reveal which one matched
+        self.keys.iter().fold(None, |found, k| {
+            if constant_time_eq(k.key.as_bytes(), key) {
+                Some(k)
+            } else {
+                found
+            }
+        })
+    }
+}
+
+/// `<key>` or `<key> <tenant>`, with the tenant one of `tenants`
+fn parse_key(entry: &str, tenants: &[String]) -> Result<ApiKey, AppError> {
+    let mut parts = entry.split_whitespace();
+    let key = parts.next().unwrap_or_default().to_string();
+    let tenant = parts.next().map(str::to_string);
+    if parts.next().is_some() {
+        return Err(AppError::Config(
+            "API key entries are '<key>' or '<key> <tenant>'".to_string(),
+        ));
+    }
+    if let Some(tenant) = &tenant
+        && !tenants.contains(tenant)
+    {
+        return Err(AppError::Config(format!(
+            "An API key is bound to tenant '{}', which is not in TENANTS",
+            tenant
+        )));
+    }
+    Ok(ApiKey { key, tenant })
+}
+
+/// One key per line; blank lines and `#` comments are skipped
+fn parse_key_file(content: &str) -> impl Iterator<Item = String> + '_ {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+}
+
+/// Compare without returning early at the first differing byte
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Return 401 for every path but the probes and the device-authenticated
+/// `/ingest/batch` without a valid `X-API-Key`
+pub async fn api_key_guard(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let rejected = req.app_data::<web::Data<AppState>>().is_some_and(|state| {
+        let path = req.path();
+        if !state.api_keys.is_enabled() || PROBE_PATHS.contains(&path) || path == INGEST_BATCH_PATH
+        {
+            return false;
+        }
+        let key = req.headers().get(API_KEY_HEADER);
+        !key.is_some_and(|key| state.api_keys.accepts(key.as_bytes()))
+    });
+
+    if rejected {
+        // Built directly: `AppError::Unauthorized` would ask for a bearer token
+        let error = AppError::Unauthorized("Missing or invalid X-API-Key header".to_string());
+        let response = HttpResponse::Unauthorized().json(ErrorResponse::from(&error));
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+    next.call(req).await.map(|res| res.map_into_left_body())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_keys() {
+        let tenants = vec!["site-a".to_string()];
+        let keys = ApiKeys {
+            keys: parse_key_file("# search frontend\nkey-one\n\n  key-two  site-a \n")
+                .map(|entry| parse_key(&entry, &tenants).unwrap())
+                .collect(),
+        };
+        assert_eq!(keys.len(), 2);
+        assert!(keys.accepts(b"key-one"));
+        assert!(keys.accepts(b"key-two"));
+        assert!(!keys.accepts(b"key-on"));
+        assert!(!keys.accepts(b""));
+        assert_eq!(keys.tenant_of(b"key-one"), None);
+        assert_eq!(keys.tenant_of(b"key-two"), Some("site-a"));
+        assert!(parse_key("key-three site-b", &tenants).is_err());
+        assert!(parse_key("key-three site-a extra", &tenants).is_err());
+        assert!(!ApiKeys::default().is_enabled());
+    }
+}
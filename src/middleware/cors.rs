@@ -0,0 +1,115 @@
+//! CORS
+//!
+//! Lets browser frontends served from other domains call the public API
+//! directly: preflight requests are answered here, before the API key and JWT
+//! guards, and responses to allowed origins carry the CORS headers.
+
+use crate::config::{Config, technical};
+use crate::handlers::AppState;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::Method;
+use actix_web::http::header::{
+    ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+    ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_METHOD, HeaderValue, ORIGIN, VARY,
+};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpResponse, web};
+
+/// Allowed origins, methods and headers; with no origins every request
+/// passes without CORS headers
+#[derive(Debug, Default)]
+pub struct CorsPolicy {
+    origins: Vec<String>,
+    /// Comma-separated, as sent in `Access-Control-Allow-Methods`
+    methods: String,
+    /// Comma-separated, as sent in `Access-Control-Allow-Headers`
+    headers: String,
+}
+
+impl CorsPolicy {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            origins: config.cors_allowed_origins.clone(),
+            methods: config.cors_allowed_methods.join(", "),
+            headers: config.cors_allowed_headers.join(", "),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.origins.is_empty()
+    }
+
+    /// Whether `origin` is configured, or any origin is allowed with `*`
+    pub fn allows(&self, origin: &str) -> bool {
+        self.origins.iter().any(|o| o == "*" || o == origin)
+    }
+}
+
+/// Answer preflights from allowed origins and add CORS headers to their responses
+pub async fn cors(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let state = req
+        .app_data::<web::Data<AppState>>()
+        .filter(|state| state.cors.is_enabled())
+        .cloned();
+    let Some(state) = state else {
+        return next.call(req).await.map(|res| res.map_into_left_body());
+    };
+    // The origin is echoed back, so `*` also works for requests with credentials
+    let origin = req
+        .headers()
+        .get(ORIGIN)
+        .filter(|origin| {
+            origin
+                .to_str()
+                .is_ok_and(|origin| state.cors.allows(origin))
+        })
+        .cloned();
+    let Some(origin) = origin else {
+        return next.call(req).await.map(|res| res.map_into_left_body());
+    };
+
+    let preflight = req.method() == Method::OPTIONS
+        && req.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD);
+    if preflight {
+        let response = HttpResponse::NoContent()
+            .insert_header((ACCESS_CONTROL_ALLOW_ORIGIN, origin))
+            .insert_header((ACCESS_CONTROL_ALLOW_METHODS, state.cors.methods.as_str()))
+            .insert_header((ACCESS_CONTROL_ALLOW_HEADERS, state.cors.headers.as_str()))
+            .insert_header((ACCESS_CONTROL_MAX_AGE, technical::CORS_MAX_AGE_SECS))
+            .insert_header((VARY, "Origin"))
+            .finish();
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+
+    let mut res = next.call(req).await?;
+    let headers = res.headers_mut();
+    headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+    headers.append(VARY, HeaderValue::from_static("Origin"));
+    Ok(res.map_into_left_body())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowed_origins() {
+        let policy = CorsPolicy {
+            origins: vec!["https://dashboard.example.com".to_string()],
+            ..CorsPolicy::default()
+        };
+        assert!(policy.allows("https://dashboard.example.com"));
+        assert!(!policy.allows("https://evil.example.com"));
+
+        let any = CorsPolicy {
+            origins: vec!["*".to_string()],
+            ..CorsPolicy::default()
+        };
+        assert!(any.allows("http://localhost:3000"));
+        assert!(!CorsPolicy::default().is_enabled());
+    }
+}
@@ -0,0 +1,120 @@
+//! Edge Device Keys
+//!
+//! Per-device credentials of `POST /ingest/batch`. Edge devices send their
+//! ID in `X-Device-Id` and their own key in `X-Device-Key` instead of the
+//! API key or bearer token of the other endpoints.
+
+use crate::config::Config;
+use crate::error::AppError;
+use crate::handlers::AppState;
+use crate::middleware::constant_time_eq;
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpRequest, web};
+use std::collections::HashMap;
+use std::future::{Ready, ready};
+
+/// Path authenticated by device keys, skipped by the API key and JWT guards
+pub const INGEST_BATCH_PATH: &str = "/ingest/batch";
+/// Header carrying the device's ID
+pub const DEVICE_ID_HEADER: &str = "X-Device-Id";
+/// Header carrying the device's key
+pub const DEVICE_KEY_HEADER: &str = "X-Device-Key";
+
+/// Keys of the edge devices allowed to send batches, by device ID
+#[derive(Debug, Default)]
+pub struct DeviceKeys {
+    keys: HashMap<String, String>,
+}
+
+impl DeviceKeys {
+    /// Keys from `EDGE_DEVICE_KEYS` entries of the form `<device_id>:<key>`
+    pub fn load(config: &Config) -> Result<Self, AppError> {
+        Self::from_entries(&config.edge_device_keys)
+    }
+
+    fn from_entries(entries: &[String]) -> Result<Self, AppError> {
+        let mut keys = HashMap::new();
+        for entry in entries {
+            let (device_id, key) = entry
+                .split_once(':')
+                .map(|(id, key)| (id.trim(), key.trim()))
+                .filter(|(id, key)| !id.is_empty() && !key.is_empty())
+                .ok_or_else(|| {
+                    AppError::Config(format!(
+                        "Invalid EDGE_DEVICE_KEYS entry '{}': expected <device_id>:<key>",
+                        entry.split(':').next().unwrap_or_default()
+                    ))
+                })?;
+            if keys
+                .insert(device_id.to_string(), key.to_string())
+                .is_some()
+            {
+                return Err(AppError::Config(format!(
+                    "Device '{}' is listed twice in EDGE_DEVICE_KEYS",
+                    device_id
+                )));
+            }
+        }
+        Ok(Self { keys })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether `key` is the key of `device_id`
+    pub fn accepts(&self, device_id: &str, key: &[u8]) -> bool {
+        self.keys
+            .get(device_id)
+            .is_some_and(|expected| constant_time_eq(expected.as_bytes(), key))
+    }
+}
+
+/// Extractor of the authenticated edge device, or 401
+pub struct EdgeDevice(pub String);
+
+impl FromRequest for EdgeDevice {
+    type Error = AppError;
+    type Future = Ready<Result<Self, AppError>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let header = |name| req.headers().get(name).map(|value| value.as_bytes());
+        let device_id = header(DEVICE_ID_HEADER).and_then(|id| std::str::from_utf8(id).ok());
+        let accepted = req.app_data::<web::Data<AppState>>().is_some_and(|state| {
+            device_id
+                .zip(header(DEVICE_KEY_HEADER))
+                .is_some_and(|(id, key)| state.device_keys.accepts(id, key))
+        });
+        ready(match device_id {
+            Some(id) if accepted => Ok(Self(id.to_string())),
+            _ => Err(AppError::Unauthorized(
+                "Missing or invalid X-Device-Id/X-Device-Key headers".to_string(),
+            )),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_keys() {
+        let load = |entries: &[&str]| {
+            DeviceKeys::from_entries(&entries.iter().map(|e| e.to_string()).collect::<Vec<_>>())
+        };
+        let keys = load(&["gate_a:key-one", "gate_b: key-two"]).unwrap();
+        assert_eq!(keys.len(), 2);
+        assert!(keys.accepts("gate_a", b"key-one"));
+        assert!(keys.accepts("gate_b", b"key-two"));
+        assert!(!keys.accepts("gate_a", b"key-two"));
+        assert!(!keys.accepts("gate_c", b"key-one"));
+
+        assert!(load(&["gate_a"]).is_err());
+        assert!(load(&["gate_a:one", "gate_a:two"]).is_err());
+    }
+}
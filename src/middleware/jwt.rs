@@ -0,0 +1,410 @@
+//! JWT Bearer Authentication
+//!
+//! Validates `Authorization: Bearer` tokens signed with a shared secret or a
+//! key published at a JWKS URL, and lets handlers require a role through the
+//! `Authorized` extractor.
+
+use crate::config::{Config, technical};
+use crate::error::AppError;
+use crate::handlers::AppState;
+use crate::middleware::{INGEST_BATCH_PATH, PROBE_PATHS, check_unbound};
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::middleware::Next;
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest, ResponseError, web};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use serde::Deserialize;
+use std::fmt;
+use std::future::{Ready, ready};
+use std::marker::PhantomData;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Roles granted by the `roles` claim; each includes the ones below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Search and read stored images
+    Reader,
+    /// Also insert images
+    Writer,
+    /// Also run admin, scheduler and deletion endpoints
+    Admin,
+}
+
+impl Role {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "reader" => Some(Role::Reader),
+            "writer" => Some(Role::Writer),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Role::Reader => "reader",
+            Role::Writer => "writer",
+            Role::Admin => "admin",
+        })
+    }
+}
+
+/// Claims of a validated token, stored in the request extensions
+#[derive(Debug, Clone, Deserialize)]
+pub struct Claims {
+    /// Caller the token was issued to, used to attribute changes
+    #[serde(default)]
+    pub sub: Option<String>,
+    /// Unknown role names are ignored
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Tenant the token is confined to while `TENANTS` is set
+    #[serde(default)]
+    pub tenant: Option<String>,
+}
+
+impl Claims {
+    /// Highest role granted by the token
+    pub fn role(&self) -> Option<Role> {
+        self.roles.iter().filter_map(|r| Role::parse(r)).max()
+    }
+}
+
+/// Where token signatures are verified from
+enum Keys {
+    /// HS256 with `JWT_SECRET`
+    Secret(DecodingKey),
+    /// Asymmetric keys from `JWT_JWKS_URL`, selected by the token's `kid`
+    Jwks { url: String, keys: RwLock<JwkSet> },
+}
+
+/// Token validation settings; with neither a secret nor a JWKS URL
+/// configured every request passes
+#[derive(Default)]
+pub struct JwtAuth {
+    keys: Option<Keys>,
+    issuer: Option<String>,
+    audience: Option<String>,
+}
+
+impl JwtAuth {
+    /// Validation from `JWT_SECRET` or `JWT_JWKS_URL`, fetching the key set
+    pub async fn load(config: &Config, http_client: &reqwest::Client) -> Result<Self, AppError> {
+        let keys = match (&config.jwt_secret, &config.jwt_jwks_url) {
+            (Some(secret), _) => Some(Keys::Secret(DecodingKey::from_secret(secret.as_bytes()))),
+            (None, Some(url)) => Some(Keys::Jwks {
+                keys: RwLock::new(fetch_jwks(http_client, url).await?),
+                url: url.clone(),
+            }),
+            (None, None) => None,
+        };
+        Ok(Self {
+            keys,
+            issuer: config.jwt_issuer.clone(),
+            audience: config.jwt_audience.clone(),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.keys.is_some()
+    }
+
+    /// Check the signature, expiry and, if configured, issuer and audience
+    pub fn verify(&self, token: &str) -> Result<Claims, AppError> {
+        let invalid = |e: jsonwebtoken::errors::Error| {
+            AppError::Unauthorized(format!("Invalid bearer token: {}", e))
+        };
+        let (key, algorithm) = match &self.keys {
+            None => {
+                return Err(AppError::Unauthorized(
+                    "JWT authentication is disabled".into(),
+                ));
+            }
+            Some(Keys::Secret(key)) => (key.clone(), Algorithm::HS256),
+            Some(Keys::Jwks { keys, .. }) => {
+                let header = decode_header(token).map_err(invalid)?;
+                let kid = header.kid.ok_or_else(|| {
+                    AppError::Unauthorized("Bearer token has no key ID (kid)".to_string())
+                })?;
+                // Never accept a public key used as an HMAC secret
+                if matches!(
+                    header.alg,
+                    Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+                ) {
+                    return Err(AppError::Unauthorized(
+                        "Symmetric algorithms are not accepted with JWKS".to_string(),
+                    ));
+                }
+                let keys = keys.read().unwrap_or_else(|e| e.into_inner());
+                let jwk = keys.find(&kid).ok_or_else(|| {
+                    AppError::Unauthorized(format!("Unknown signing key '{}'", kid))
+                })?;
+                (DecodingKey::from_jwk(jwk).map_err(invalid)?, header.alg)
+            }
+        };
+
+        let mut validation = Validation::new(algorithm);
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        decode::<Claims>(token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(invalid)
+    }
+
+    /// Replace the JWKS key set, picking up rotated keys
+    async fn refresh(&self, http_client: &reqwest::Client) -> Result<(), AppError> {
+        if let Some(Keys::Jwks { url, keys }) = &self.keys {
+            let fresh = fetch_jwks(http_client, url).await?;
+            *keys.write().unwrap_or_else(|e| e.into_inner()) = fresh;
+        }
+        Ok(())
+    }
+}
+
+async fn fetch_jwks(http_client: &reqwest::Client, url: &str) -> Result<JwkSet, AppError> {
+    let fetch_error = |e: reqwest::Error| {
+        AppError::Config(format!("Failed to fetch JWT_JWKS_URL '{}': {}", url, e))
+    };
+    http_client
+        .get(url)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .map_err(fetch_error)?
+        .json()
+        .await
+        .map_err(fetch_error)
+}
+
+/// Re-fetch the JWKS key set periodically; a failed fetch keeps the old keys
+pub fn spawn_jwks_refresh(auth: Arc<JwtAuth>, http_client: reqwest::Client) {
+    if !matches!(auth.keys, Some(Keys::Jwks { .. })) {
+        return;
+    }
+    tokio::spawn(async move {
+        let period = Duration::from_secs(technical::JWKS_REFRESH_SECS);
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        loop {
+            interval.tick().await;
+            match auth.refresh(&http_client).await {
+                Ok(()) => info!("JWKS key set refreshed"),
+                Err(e) => warn!(error = %e, "JWKS refresh failed, keeping the previous keys"),
+            }
+        }
+    });
+}
+
+/// Return 401 for every path but the probes and the device-authenticated
+/// `/ingest/batch` without a valid bearer token
+pub async fn jwt_guard(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let verified = req.app_data::<web::Data<AppState>>().and_then(|state| {
+        let path = req.path();
+        if !state.jwt.is_enabled() || PROBE_PATHS.contains(&path) || path == INGEST_BATCH_PATH {
+            return None;
+        }
+        let token = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        Some(match token {
+            Some(token) => state.jwt.verify(token.trim()),
+            None => Err(AppError::Unauthorized(
+                "Missing Authorization: Bearer header".to_string(),
+            )),
+        })
+    });
+
+    match verified {
+        Some(Err(e)) => {
+            let response = e.error_response();
+            Ok(req.into_response(response).map_into_right_body())
+        }
+        Some(Ok(claims)) => {
+            req.extensions_mut().insert(claims);
+            next.call(req).await.map(|res| res.map_into_left_body())
+        }
+        None => next.call(req).await.map(|res| res.map_into_left_body()),
+    }
+}
+
+/// Role a handler requires through `Authorized`
+pub trait RequiredRole {
+    const ROLE: Role;
+}
+
+/// `reader` role marker
+pub struct Reader;
+/// `writer` role marker
+pub struct Writer;
+/// `admin` role marker
+pub struct Admin;
+
+impl RequiredRole for Reader {
+    const ROLE: Role = Role::Reader;
+}
+
+impl RequiredRole for Writer {
+    const ROLE: Role = Role::Writer;
+}
+
+impl RequiredRole for Admin {
+    const ROLE: Role = Role::Admin;
+}
+
+/// Extractor rejecting the request with 403 unless the token validated by
+/// `jwt_guard` grants role `R`; a no-op while JWT authentication is disabled
+///
+/// `admin` is also refused to requests bound to a tenant, since the admin
+/// endpoints act on every tenant's data.
+pub struct Authorized<R>(PhantomData<R>);
+
+impl<R: RequiredRole> FromRequest for Authorized<R> {
+    type Error = AppError;
+    type Future = Ready<Result<Self, AppError>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        if R::ROLE == Role::Admin
+            && let Err(e) = check_unbound(req)
+        {
+            return ready(Err(e));
+        }
+        let enabled = req
+            .app_data::<web::Data<AppState>>()
+            .is_some_and(|state| state.jwt.is_enabled());
+        if !enabled {
+            return ready(Ok(Self(PhantomData)));
+        }
+        let role = req.extensions().get::<Claims>().map(Claims::role);
+        ready(match role {
+            None => Err(AppError::Unauthorized(
+                "Missing Authorization: Bearer header".to_string(),
+            )),
+            Some(role) => check_role(role, R::ROLE).map(|_| Self(PhantomData)),
+        })
+    }
+}
+
+/// `sub` claim of the request's token; `None` while JWT authentication is
+/// disabled or the token has no subject
+pub fn token_subject(req: &HttpRequest) -> Option<String> {
+    req.extensions()
+        .get::<Claims>()
+        .and_then(|claims| claims.sub.clone())
+}
+
+/// 403 unless `granted` includes `required`
+pub fn check_role(granted: Option<Role>, required: Role) -> Result<(), AppError> {
+    if granted.is_some_and(|role| role >= required) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(format!(
+            "The '{}' role is required",
+            required
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header, encode};
+
+    fn auth(secret: &str) -> JwtAuth {
+        JwtAuth {
+            keys: Some(Keys::Secret(DecodingKey::from_secret(secret.as_bytes()))),
+            issuer: Some("sso".to_string()),
+            audience: None,
+        }
+    }
+
+    fn token(secret: &str, claims: serde_json::Value) -> String {
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_verify_and_roles() {
+        let exp = chrono::Utc::now().timestamp() + 600;
+        let auth = auth("s3cret");
+
+        let claims = auth
+            .verify(&token(
+                "s3cret",
+                serde_json::json!({"sub": "ui", "iss": "sso", "exp": exp, "roles": ["reader", "writer", "ops"]}),
+            ))
+            .unwrap();
+        assert_eq!(claims.role(), Some(Role::Writer));
+        assert!(check_role(claims.role(), Role::Reader).is_ok());
+        assert!(check_role(claims.role(), Role::Writer).is_ok());
+        assert!(check_role(claims.role(), Role::Admin).is_err());
+        assert!(check_role(None, Role::Reader).is_err());
+
+        let wrong_secret = token("other", serde_json::json!({"iss": "sso", "exp": exp}));
+        assert!(auth.verify(&wrong_secret).is_err());
+        let expired = token(
+            "s3cret",
+            serde_json::json!({"iss": "sso", "exp": exp - 7200}),
+        );
+        assert!(auth.verify(&expired).is_err());
+        let wrong_issuer = token("s3cret", serde_json::json!({"iss": "other", "exp": exp}));
+        assert!(auth.verify(&wrong_issuer).is_err());
+        assert!(!JwtAuth::default().is_enabled());
+    }
+
+    #[actix_web::test]
+    async fn test_admin_refused_to_tenant_bound_requests() {
+        use crate::middleware::API_KEY_HEADER;
+        use actix_web::http::StatusCode;
+        use actix_web::{App, HttpResponse, get, test};
+
+        #[get("/admin")]
+        async fn admin(_: Authorized<Admin>) -> HttpResponse {
+            HttpResponse::Ok().finish()
+        }
+        #[get("/reader")]
+        async fn reader(_: Authorized<Reader>) -> HttpResponse {
+            HttpResponse::Ok().finish()
+        }
+
+        let state = AppState::for_tests(&[
+            ("TENANTS", "site-a,site-b"),
+            ("API_KEYS", "k-site-a site-a,k-ops"),
+        ])
+        .await;
+        let app =
+            test::init_service(App::new().app_data(state).service(admin).service(reader)).await;
+        let status = |path: &str, key: &str| {
+            let request = test::TestRequest::get()
+                .uri(path)
+                .insert_header((API_KEY_HEADER, key))
+                .to_request();
+            test::call_service(&app, request)
+        };
+
+        assert_eq!(
+            status("/admin", "k-site-a").await.status(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(status("/reader", "k-site-a").await.status(), StatusCode::OK);
+        assert_eq!(status("/admin", "k-ops").await.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,46 @@
+//! Maintenance Guard
+//!
+//! Rejects requests with 503 while maintenance mode is active.
+
+use crate::error::AppError;
+use crate::handlers::AppState;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{Error, ResponseError, web};
+
+/// Path that must stay reachable so maintenance can be switched off again
+pub const MAINTENANCE_PATH: &str = "/admin/maintenance";
+
+/// Probe paths; blocking liveness would get the pod restarted mid-maintenance
+pub const PROBE_PATHS: [&str; 2] = ["/healthz", "/readyz"];
+
+/// Return 503 for every path outside the allowlist while in maintenance
+pub async fn maintenance_guard(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let blocked_message = req.app_data::<web::Data<AppState>>().and_then(|state| {
+        let path = req.path();
+        let allowed = path == MAINTENANCE_PATH
+            || PROBE_PATHS.contains(&path)
+            || state.maintenance_allowlist.iter().any(|p| p == path);
+        if allowed {
+            None
+        } else {
+            state.maintenance.message()
+        }
+    });
+
+    match blocked_message {
+        Some(message) => {
+            let response = AppError::Unavailable {
+                message,
+                retry_after_secs: 120,
+            }
+            .error_response();
+            Ok(req.into_response(response).map_into_right_body())
+        }
+        None => next.call(req).await.map(|res| res.map_into_left_body()),
+    }
+}
@@ -0,0 +1,41 @@
+//! Request Metrics
+//!
+//! Records latency and status of every request for `/metrics` and SLOs.
+
+use crate::handlers::AppState;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{Error, web};
+use std::time::Instant;
+
+/// Route label for requests that matched no registered route
+const UNMATCHED_PATH: &str = "unmatched";
+
+/// Time each request and record it by route pattern
+pub async fn track_requests(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let state = req.app_data::<web::Data<AppState>>().cloned();
+    let started = Instant::now();
+
+    let res = next.call(req).await?;
+
+    if let Some(state) = state {
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        // Route patterns keep the label set bounded
+        let path = res
+            .request()
+            .match_pattern()
+            .unwrap_or_else(|| UNMATCHED_PATH.to_string());
+        let status = res.status();
+
+        state.metrics.record(&path, status.as_u16(), elapsed_ms);
+        state
+            .slo
+            .record(&path, elapsed_ms, !status.is_server_error());
+    }
+
+    Ok(res)
+}
@@ -0,0 +1,21 @@
+//! HTTP Middleware
+//!
+//! Request guards and instrumentation applied in front of the API handlers.
+
+mod api_key;
+mod cors;
+mod device_keys;
+mod jwt;
+mod maintenance;
+mod metrics;
+mod rate_limit;
+mod tenant;
+
+pub use api_key::*;
+pub use cors::*;
+pub use device_keys::*;
+pub use jwt::*;
+pub use maintenance::*;
+pub use metrics::*;
+pub use rate_limit::*;
+pub use tenant::*;
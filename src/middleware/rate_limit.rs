@@ -0,0 +1,168 @@
+//! Per-Client Rate Limit
+//!
+//! Token bucket per client on the expensive endpoints, so one misbehaving
+//! dashboard cannot starve the AI service and Qdrant for everyone else.
+//! Clients are told apart by their `X-API-Key`, or their IP address without one.
+
+use crate::config::technical;
+use crate::error::AppError;
+use crate::handlers::AppState;
+use crate::middleware::API_KEY_HEADER;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{Error, ResponseError, web};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token buckets refilled at `requests_per_sec` up to `burst`, one per client
+#[derive(Debug)]
+pub struct ClientRateLimiter {
+    requests_per_sec: f64,
+    burst: u32,
+    /// Paths the limit applies to
+    paths: Vec<String>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl ClientRateLimiter {
+    pub fn new(requests_per_sec: f64, burst: u32, paths: Vec<String>) -> Self {
+        Self {
+            requests_per_sec,
+            burst: burst.max(1),
+            paths,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn applies_to(&self, path: &str) -> bool {
+        self.paths.iter().any(|p| p == path)
+    }
+
+    /// Take a token of `client`, or return how long to wait before retrying
+    pub fn try_acquire(&self, client: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= technical::MAX_RATE_LIMITED_CLIENTS && !buckets.contains_key(client) {
+            self.prune(&mut buckets, now);
+        }
+
+        let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+            tokens: self.burst as f64,
+            updated: now,
+        });
+        self.refill(bucket, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64(
+            (1.0 - bucket.tokens) / self.requests_per_sec,
+        ))
+    }
+
+    fn refill(&self, bucket: &mut Bucket, now: Instant) {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.requests_per_sec).min(self.burst as f64);
+        bucket.updated = now;
+    }
+
+    /// Forget clients whose bucket has refilled; a new bucket starts full anyway
+    fn prune(&self, buckets: &mut HashMap<String, Bucket>, now: Instant) {
+        buckets.retain(|_, bucket| {
+            self.refill(bucket, now);
+            bucket.tokens < self.burst as f64
+        });
+    }
+}
+
+/// Return 429 with `Retry-After` once a client has used up its bucket
+pub async fn client_rate_limit(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let retry_after = req.app_data::<web::Data<AppState>>().and_then(|state| {
+        let limiter = state.client_rate_limiter.as_ref()?;
+        if !limiter.applies_to(req.path()) {
+            return None;
+        }
+        let client = client_id(&req, state);
+        limiter.try_acquire(&client, Instant::now()).err()
+    });
+
+    match retry_after {
+        Some(wait) => {
+            debug!(
+                path = req.path(),
+                wait_ms = wait.as_millis() as u64,
+                "Client rate limited"
+            );
+            let response = rate_limited(wait).error_response();
+            Ok(req.into_response(response).map_into_right_body())
+        }
+        None => next.call(req).await.map(|res| res.map_into_left_body()),
+    }
+}
+
+/// Error returned to a client that has to wait `wait` before retrying
+pub fn rate_limited(wait: Duration) -> AppError {
+    AppError::RateLimited {
+        message: "Too many requests from this client".to_string(),
+        retry_after_secs: wait.as_secs_f64().ceil() as u64,
+    }
+}
+
+fn client_id(req: &ServiceRequest, state: &AppState) -> String {
+    client_key(
+        state,
+        req.headers().get(API_KEY_HEADER).map(|key| key.as_bytes()),
+        req.peer_addr().map(|addr| addr.ip()),
+    )
+}
+
+/// Bucket key of a caller: its API key if keys are checked, otherwise the IP
+/// address of its connection
+pub fn client_key(state: &AppState, api_key: Option<&[u8]>, peer: Option<IpAddr>) -> String {
+    // An unchecked key could be changed on every request to get a fresh bucket
+    if let Some(key) = api_key.filter(|_| state.api_keys.is_enabled()) {
+        return format!("key:{}", String::from_utf8_lossy(key));
+    }
+    // The peer address can't be spoofed with a header, unlike X-Forwarded-For
+    match peer {
+        Some(ip) => format!("ip:{}", ip),
+        None => "unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets_are_per_client() {
+        let limiter = ClientRateLimiter::new(2.0, 2, vec!["/search".to_string()]);
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire("ip:10.0.0.1", start).is_ok());
+        assert!(limiter.try_acquire("ip:10.0.0.1", start).is_ok());
+        let wait = limiter.try_acquire("ip:10.0.0.1", start).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(500));
+
+        // Another client still has its full burst
+        assert!(limiter.try_acquire("key:dashboard", start).is_ok());
+
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.try_acquire("ip:10.0.0.1", later).is_ok());
+
+        assert!(limiter.applies_to("/search"));
+        assert!(!limiter.applies_to("/healthz"));
+    }
+}
@@ -0,0 +1,93 @@
+//! Tenant Extractor
+//!
+//! Resolves the tenant a request is confined to while `TENANTS` is set,
+//! from the `tenant` claim of its bearer token, the tenant its API key is
+//! bound to or the `X-Tenant-ID` header, and keeps tenant-bound requests
+//! out of the admin endpoints, which act on every tenant's data.
+
+use crate::error::AppError;
+use crate::handlers::AppState;
+use crate::middleware::{API_KEY_HEADER, Claims, Role};
+use crate::services::{bound_tenant, resolve_tenant};
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpMessage, HttpRequest, web};
+use std::future::{Ready, ready};
+
+/// Header naming the tenant of a request
+pub const TENANT_HEADER: &str = "X-Tenant-ID";
+
+/// Tenant of the request; `None` while tenancy is disabled
+#[derive(Debug, Clone, Default)]
+pub struct Tenant(pub Option<String>);
+
+impl Tenant {
+    pub fn id(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+}
+
+impl FromRequest for Tenant {
+    type Error = AppError;
+    type Future = Ready<Result<Self, AppError>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let Some(state) = req.app_data::<web::Data<AppState>>() else {
+            return ready(Ok(Self(None)));
+        };
+        let extensions = req.extensions();
+        let claims = extensions.get::<Claims>();
+        // Without any authentication any caller may name its tenant
+        let header_trusted = if state.jwt.is_enabled() {
+            claims.is_some_and(|claims| claims.role() == Some(Role::Admin))
+        } else {
+            !state.api_keys.is_enabled()
+        };
+        let requested = req
+            .headers()
+            .get(TENANT_HEADER)
+            .and_then(|value| value.to_str().ok());
+        ready(
+            request_binding(req, state)
+                .and_then(|bound| {
+                    resolve_tenant(
+                        &state.scheduler.config.tenants,
+                        bound.as_deref(),
+                        requested,
+                        header_trusted,
+                    )
+                })
+                .map(Self),
+        )
+    }
+}
+
+/// 403 if tenancy is enabled and the request's token or API key is bound to
+/// a tenant
+pub fn check_unbound(req: &HttpRequest) -> Result<(), AppError> {
+    let Some(state) = req.app_data::<web::Data<AppState>>() else {
+        return Ok(());
+    };
+    if state.scheduler.config.tenants.is_empty() {
+        return Ok(());
+    }
+    match request_binding(req, state)? {
+        Some(tenant) => Err(AppError::Forbidden(format!(
+            "The request is bound to tenant '{}'; admin endpoints span every tenant",
+            tenant
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Tenant the request's token claim or API key is bound to
+fn request_binding(req: &HttpRequest, state: &AppState) -> Result<Option<String>, AppError> {
+    let extensions = req.extensions();
+    let claimed = extensions
+        .get::<Claims>()
+        .and_then(|claims| claims.tenant.as_deref());
+    let key_tenant = req
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|key| state.api_keys.tenant_of(key.as_bytes()));
+    bound_tenant(claimed, key_tenant).map(|bound| bound.map(str::to_string))
+}
@@ -0,0 +1,285 @@
+//! Vector Store Migrations
+//!
+//! Versioned schema changes for the Qdrant collection (payload indexes,
+//! payload transforms, alias moves), applied in order at startup. Applied
+//! versions are recorded in a `<collection>_migrations` history collection
+//! so each migration runs once; every step is also safe to re-run.
+
+use crate::error::AppError;
+use crate::services::{PayloadBuilder, create_field_index, point_id_to_string};
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{
+    Condition, CreateAliasBuilder, CreateCollection, Distance, FieldType, Filter, PointStruct,
+    ScrollPointsBuilder, SetPayloadPointsBuilder, UpsertPoints, VectorParams,
+};
+use tracing::{info, instrument};
+
+/// Value written by a payload backfill step
+#[allow(dead_code)]
+pub enum PayloadDefault {
+    Str(&'static str),
+    Int(i64),
+}
+
+/// A single idempotent schema change
+#[allow(dead_code)]
+pub enum MigrationStep {
+    /// Create a payload field index
+    CreateIndex {
+        field: &'static str,
+        field_type: FieldType,
+    },
+    /// Set `key` to `value` on every point where it is missing
+    BackfillPayload {
+        key: &'static str,
+        value: PayloadDefault,
+    },
+    /// Point `alias` at the configured collection
+    MoveAlias { alias: &'static str },
+}
+
+/// A versioned group of steps
+pub struct Migration {
+    pub version: u64,
+    pub name: &'static str,
+    pub steps: &'static [MigrationStep],
+}
+
+/// All migrations, in strictly increasing version order
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_payload_indexes",
+        steps: &[
+            MigrationStep::CreateIndex {
+                field: "datetime",
+                field_type: FieldType::Datetime,
+            },
+            MigrationStep::CreateIndex {
+                field: "camera_id",
+                field_type: FieldType::Keyword,
+            },
+            MigrationStep::CreateIndex {
+                field: "vehicle_class",
+                field_type: FieldType::Keyword,
+            },
+            MigrationStep::CreateIndex {
+                field: "vehicle_type",
+                field_type: FieldType::Integer,
+            },
+        ],
+    },
+    Migration {
+        version: 2,
+        name: "confidence_index",
+        steps: &[MigrationStep::CreateIndex {
+            field: "confidence",
+            field_type: FieldType::Float,
+        }],
+    },
+    Migration {
+        version: 3,
+        name: "hybrid_text_indexes",
+        steps: &[
+            MigrationStep::CreateIndex {
+                field: "filename",
+                field_type: FieldType::Text,
+            },
+            MigrationStep::CreateIndex {
+                field: "caption",
+                field_type: FieldType::Text,
+            },
+        ],
+    },
+    Migration {
+        version: 4,
+        name: "caption_terms_index",
+        steps: &[MigrationStep::CreateIndex {
+            field: "caption_terms",
+            field_type: FieldType::Text,
+        }],
+    },
+    Migration {
+        version: 5,
+        name: "tenant_index",
+        steps: &[MigrationStep::CreateIndex {
+            field: "tenant_id",
+            field_type: FieldType::Keyword,
+        }],
+    },
+];
+
+/// Name of the collection holding the migration history
+pub fn history_collection(collection_name: &str) -> String {
+    format!("{}_migrations", collection_name)
+}
+
+/// Migrations whose version is not in `applied`, in order
+pub fn pending<'a>(migrations: &'a [Migration], applied: &[u64]) -> Vec<&'a Migration> {
+    migrations
+        .iter()
+        .filter(|m| !applied.contains(&m.version))
+        .collect()
+}
+
+/// Apply all pending migrations, returning the versions applied
+///
+/// Stops at the first failing migration; it is not recorded and will be
+/// retried on the next start.
+#[instrument(skip(qdrant))]
+pub async fn run_pending(qdrant: &Qdrant, collection_name: &str) -> Result<Vec<u64>, AppError> {
+    let history = history_collection(collection_name);
+    ensure_history_collection(qdrant, &history).await?;
+
+    let applied = applied_versions(qdrant, &history).await?;
+    let mut newly_applied = Vec::new();
+
+    for migration in pending(MIGRATIONS, &applied) {
+        info!(
+            version = migration.version,
+            name = migration.name,
+            "Applying migration"
+        );
+
+        for step in migration.steps {
+            apply_step(qdrant, collection_name, step)
+                .await
+                .map_err(|e| {
+                    AppError::Qdrant(format!("Migration {} failed: {}", migration.version, e))
+                })?;
+        }
+
+        record_migration(qdrant, &history, migration).await?;
+        newly_applied.push(migration.version);
+    }
+
+    Ok(newly_applied)
+}
+
+async fn apply_step(
+    qdrant: &Qdrant,
+    collection_name: &str,
+    step: &MigrationStep,
+) -> Result<(), AppError> {
+    match step {
+        MigrationStep::CreateIndex { field, field_type } => {
+            create_field_index(qdrant, collection_name, field, *field_type).await
+        }
+        MigrationStep::BackfillPayload { key, value } => {
+            let payload = match value {
+                PayloadDefault::Str(s) => PayloadBuilder::new().string(*key, *s),
+                PayloadDefault::Int(i) => PayloadBuilder::new().integer(*key, *i),
+            }
+            .build();
+
+            let missing = Filter::must([Condition::is_empty(*key)]);
+            qdrant
+                .set_payload(
+                    SetPayloadPointsBuilder::new(collection_name, payload)
+                        .points_selector(missing)
+                        .wait(true),
+                )
+                .await
+                .map(|_| ())
+                .map_err(|e| AppError::Qdrant(format!("Failed to backfill {}: {}", key, e)))
+        }
+        MigrationStep::MoveAlias { alias } => {
+            // Deleting a missing alias fails; that is expected on first run
+            let _ = qdrant.delete_alias(*alias).await;
+            qdrant
+                .create_alias(CreateAliasBuilder::new(collection_name, *alias))
+                .await
+                .map(|_| ())
+                .map_err(|e| AppError::Qdrant(format!("Failed to move alias {}: {}", alias, e)))
+        }
+    }
+}
+
+async fn ensure_history_collection(qdrant: &Qdrant, history: &str) -> Result<(), AppError> {
+    let exists = qdrant
+        .collection_exists(history)
+        .await
+        .map_err(|e| AppError::Qdrant(format!("Failed to check migration history: {}", e)))?;
+    if exists {
+        return Ok(());
+    }
+
+    // History points carry no meaningful vector; Qdrant still requires one
+    let create = CreateCollection {
+        collection_name: history.to_string(),
+        vectors_config: Some(
+            VectorParams {
+                size: 1,
+                distance: Distance::Dot.into(),
+                ..Default::default()
+            }
+            .into(),
+        ),
+        ..Default::default()
+    };
+
+    qdrant
+        .create_collection(create)
+        .await
+        .map(|_| ())
+        .map_err(|e| AppError::Qdrant(format!("Failed to create migration history: {}", e)))
+}
+
+async fn applied_versions(qdrant: &Qdrant, history: &str) -> Result<Vec<u64>, AppError> {
+    let response = qdrant
+        .scroll(ScrollPointsBuilder::new(history).limit(10_000))
+        .await
+        .map_err(|e| AppError::Qdrant(format!("Failed to read migration history: {}", e)))?;
+
+    Ok(response
+        .result
+        .iter()
+        .filter_map(|p| p.id.as_ref())
+        .filter_map(|id| point_id_to_string(id).parse().ok())
+        .collect())
+}
+
+async fn record_migration(
+    qdrant: &Qdrant,
+    history: &str,
+    migration: &Migration,
+) -> Result<(), AppError> {
+    let payload = PayloadBuilder::new()
+        .integer("version", migration.version as i64)
+        .string("name", migration.name)
+        .string("applied_at", chrono::Utc::now().to_rfc3339())
+        .build();
+
+    let upsert = UpsertPoints {
+        collection_name: history.to_string(),
+        wait: Some(true),
+        points: vec![PointStruct::new(migration.version, vec![0.0], payload)],
+        ..Default::default()
+    };
+
+    qdrant.upsert_points(upsert).await.map(|_| ()).map_err(|e| {
+        AppError::Qdrant(format!(
+            "Failed to record migration {}: {}",
+            migration.version, e
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migration_versions_are_increasing() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+    }
+
+    #[test]
+    fn test_pending_skips_applied() {
+        let versions: Vec<u64> = pending(MIGRATIONS, &[]).iter().map(|m| m.version).collect();
+        assert_eq!(versions.len(), MIGRATIONS.len());
+
+        let all: Vec<u64> = MIGRATIONS.iter().map(|m| m.version).collect();
+        assert!(pending(MIGRATIONS, &all).is_empty());
+    }
+}
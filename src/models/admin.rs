@@ -0,0 +1,323 @@
+//! Admin Models
+//!
+//! Request/Response structures for the operational endpoints.
+
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Request to switch maintenance mode on or off
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MaintenanceRequest {
+    pub enabled: bool,
+    /// Message returned to clients while in maintenance
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Current maintenance mode state
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+    pub message: Option<String>,
+    /// Paths still served while in maintenance
+    pub allowlist: Vec<String>,
+}
+
+/// Selection of images to delete; at least one filter is required
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteImagesQuery {
+    /// Only delete images from this camera
+    pub camera_id: Option<String>,
+    /// Delete images after this time (RFC 3339)
+    pub start_date: Option<String>,
+    /// Delete images up to and including this time (RFC 3339)
+    pub end_date: Option<String>,
+    /// Only count matching images without deleting them
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Outcome of a delete by filter
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeleteImagesResponse {
+    /// Number of images deleted (or that would be deleted with `dry_run`)
+    pub deleted: u64,
+    pub dry_run: bool,
+}
+
+/// Payload patch applied to every image matching a filter; at least one
+/// filter is required
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RetagRequest {
+    /// Only patch images from this camera
+    pub camera_id: Option<String>,
+    /// Patch images after this time (RFC 3339)
+    pub start_date: Option<String>,
+    /// Patch images up to and including this time (RFC 3339)
+    pub end_date: Option<String>,
+    /// Payload fields to set, e.g. `{"camera_group": "north"}`; other fields are kept
+    #[schema(value_type = Object)]
+    pub set: serde_json::Map<String, serde_json::Value>,
+    /// Only count matching images without patching them
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Outcome of a bulk re-tag
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RetagResponse {
+    /// Number of images patched (or that would be patched with `dry_run`)
+    pub matched: u64,
+    pub dry_run: bool,
+    /// A changed `camera_id` left images in the wrong shard; run a rebalance
+    pub rebalance_required: bool,
+}
+
+/// Progress of the most recent rebalancing run
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct RebalanceStatus {
+    pub running: bool,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    /// Points examined so far
+    pub scanned: u64,
+    /// Points moved to their target shard so far
+    pub moved: u64,
+    pub error: Option<String>,
+}
+
+/// Shard layout and rebalancing progress
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ShardStatus {
+    /// Collections images are spread over, in shard order
+    pub collections: Vec<String>,
+    pub rebalance: RebalanceStatus,
+}
+
+/// Request to verify the content hash of a sample of stored images
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct IntegrityVerifyRequest {
+    /// Points sampled per collection (default 100, max 1000)
+    #[serde(default)]
+    pub sample_size: Option<u32>,
+}
+
+/// Stored image whose content no longer matches its hash
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct IntegrityMismatch {
+    pub id: String,
+    pub collection: String,
+    pub camera_id: String,
+    pub file_path: String,
+    pub stored_sha256: String,
+    pub actual_sha256: String,
+}
+
+/// Progress of the most recent integrity verification run
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct IntegrityStatus {
+    pub running: bool,
+    pub sample_size: u32,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    /// Sampled images re-hashed so far
+    pub checked: u64,
+    pub matched: u64,
+    pub mismatched: u64,
+    /// Sampled images that could not be read
+    pub unreadable: u64,
+    /// First 100 mismatches
+    pub mismatches: Vec<IntegrityMismatch>,
+    pub error: Option<String>,
+}
+
+/// Image rejected at ingest, as stored in `DEAD_LETTER_PATH`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeadLetter {
+    pub id: u64,
+    pub cctv_id: String,
+    pub filename: String,
+    pub file_path: String,
+    /// Ingest path that rejected the image: `scheduler` or `insert`
+    pub source: String,
+    pub reason: String,
+    /// RFC 3339
+    pub at: String,
+}
+
+/// Number of dead letters to list
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeadLettersQuery {
+    /// Newest entries to return (default 100, max 1000)
+    pub limit: Option<usize>,
+}
+
+/// Outcome of `POST /admin/flush`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FlushResponse {
+    /// Collections whose pending writes are now applied
+    pub collections: Vec<String>,
+    pub elapsed_ms: u64,
+}
+
+/// Request to ingest a historical date range
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BackfillRequest {
+    /// Start of the range in RFC 3339 format
+    pub start_date: String,
+    /// End of the range in RFC 3339 format
+    pub end_date: String,
+    /// Minutes of history fetched per chunk (default: 60)
+    #[serde(default)]
+    pub chunk_minutes: Option<i64>,
+    /// Only backfill this camera, even if disabled (default: every enabled camera)
+    #[serde(default)]
+    pub cctv_id: Option<String>,
+    /// Images per camera and chunk (default: 1000)
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+/// Lifecycle of a backfill job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BackfillState {
+    Running,
+    /// Stopped on request; resumable
+    Paused,
+    /// A chunk kept failing; resumable from that chunk
+    Failed,
+    Completed,
+}
+
+/// Progress of the backfill job, persisted to `BACKFILL_STATE_PATH`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BackfillStatus {
+    pub id: String,
+    pub state: BackfillState,
+    /// RFC 3339
+    pub start_date: String,
+    /// RFC 3339
+    pub end_date: String,
+    pub chunk_minutes: i64,
+    pub cctv_id: Option<String>,
+    pub limit: u32,
+    /// Start of the next chunk to fetch (RFC 3339)
+    pub cursor: String,
+    pub chunks_done: u64,
+    pub chunks_total: u64,
+    pub images_fetched: u64,
+    /// Images already stored, not embedded again
+    #[serde(default)]
+    pub skipped: u64,
+    pub inserted: u64,
+    pub failed: u64,
+    /// RFC 3339
+    pub started_at: String,
+    /// RFC 3339
+    pub updated_at: String,
+    /// Most recent chunk error, if any
+    pub last_error: Option<String>,
+}
+
+/// Snapshot of a collection, stored on the Qdrant node that created it
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SnapshotInfo {
+    pub collection: String,
+    pub name: String,
+    /// RFC 3339
+    pub created_at: Option<String>,
+    pub size_bytes: u64,
+    /// SHA-256 of the snapshot file
+    pub checksum: Option<String>,
+}
+
+/// Request to replace a collection with one of its snapshots
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SnapshotRestoreRequest {
+    /// Snapshot name, as listed by `GET /admin/snapshots`
+    pub snapshot: String,
+    /// Collection the snapshot was taken of (default: the only collection)
+    #[serde(default)]
+    pub collection: Option<String>,
+}
+
+/// Outcome of `POST /admin/snapshots/restore`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SnapshotRestoreResponse {
+    pub collection: String,
+    pub snapshot: String,
+    pub elapsed_ms: u64,
+}
+
+/// Restore check of the newest snapshot of one collection
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct SnapshotCheck {
+    pub collection: String,
+    /// Snapshot restored; `None` if the collection has none
+    pub snapshot: Option<String>,
+    /// Points in the restored copy
+    pub restored_points: u64,
+    /// Points in the live collection at the time of the check
+    pub live_points: u64,
+    /// Sampled points searched for in the restored copy by their own vector
+    pub probes: u32,
+    /// Probes found among the top hits of their own search
+    pub probes_found: u32,
+    pub passed: bool,
+    pub error: Option<String>,
+}
+
+/// Outcome of the most recent backup verification run
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct BackupVerificationStatus {
+    pub running: bool,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    /// Whether every collection passed; `None` before the first run finished
+    pub passed: Option<bool>,
+    pub checks: Vec<SnapshotCheck>,
+}
+
+/// Payload field with an index
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PayloadIndexInfo {
+    pub field: String,
+    /// Qdrant schema type, e.g. `keyword`, `datetime` or `text`
+    pub data_type: String,
+    /// Points indexed in this field
+    pub points: Option<u64>,
+}
+
+/// Vector settings of a collection
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VectorConfigInfo {
+    pub size: u64,
+    /// `cosine`, `dot`, `euclid` or `manhattan`
+    pub distance: String,
+    pub on_disk: bool,
+}
+
+/// Index health of one collection, from Qdrant's collection info
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CollectionStats {
+    pub collection: String,
+    /// `green` (ready), `yellow` (optimizing), `grey` (optimization pending) or `red`
+    pub status: String,
+    pub optimizer_ok: bool,
+    pub optimizer_error: Option<String>,
+    /// Approximate
+    pub points_count: Option<u64>,
+    /// Approximate; lags `points_count` until new segments are indexed
+    pub indexed_vectors_count: Option<u64>,
+    pub segments_count: u64,
+    /// `None` for named vector layouts
+    pub vectors: Option<VectorConfigInfo>,
+    pub shard_number: u32,
+    pub replication_factor: u32,
+    /// Sorted by field
+    pub payload_indexes: Vec<PayloadIndexInfo>,
+    pub warnings: Vec<String>,
+}
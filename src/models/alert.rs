@@ -0,0 +1,85 @@
+//! Alert Models
+//!
+//! Request/Response structures for saved searches that are run against
+//! every ingested batch, and the notifications of their matches.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Body of `POST /alerts`
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "name": "Red pickups at the north gate",
+    "query": "red pickup truck",
+    "camera_ids": ["cctv01", "cctv02"],
+    "vehicle_classes": ["pickup"],
+    "min_score": 0.3,
+    "webhook_url": "http://dispatch.local/hooks/cctv"
+}))]
+pub struct CreateAlertRequest {
+    pub name: String,
+    /// Text query, embedded once when the alert is created
+    pub query: String,
+    /// Only match images from these cameras (default: every camera)
+    #[serde(default)]
+    pub camera_ids: Option<Vec<String>>,
+    /// Only match images whose AI label has one of these classes
+    #[serde(default)]
+    pub vehicle_classes: Option<Vec<String>>,
+    /// Only match images whose AI label has at least this confidence (0 to 1)
+    #[serde(default)]
+    pub min_confidence: Option<f32>,
+    /// Minimum similarity score of a match
+    pub min_score: f32,
+    /// URL each match notification is POSTed to (default: only `GET /events`)
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+/// A saved search run against every batch stored by the scheduler
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Alert {
+    pub alert_id: String,
+    pub name: String,
+    pub query: String,
+    pub camera_ids: Option<Vec<String>>,
+    pub vehicle_classes: Option<Vec<String>>,
+    pub min_confidence: Option<f32>,
+    pub min_score: f32,
+    pub webhook_url: Option<String>,
+    /// Key of the HMAC-SHA256 signature of the deliveries to `webhook_url`;
+    /// only returned when the alert is created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_secret: Option<String>,
+    /// RFC 3339
+    pub created_at: String,
+    /// `sub` claim of the token that created it; `null` without JWT
+    pub created_by: Option<String>,
+    /// Tenant whose images it is run against; omitted without tenancy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+/// Newly stored image matching an alert
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AlertHit {
+    pub point_id: u64,
+    pub camera_id: String,
+    pub filename: String,
+    /// Capture time, RFC 3339 (UTC)
+    pub datetime: String,
+    pub vehicle_class: Option<String>,
+    pub score: f32,
+}
+
+/// Matches of one alert in one ingested batch, best first; sent to the
+/// alert's webhook and as an `alert` event of `GET /events`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AlertNotification {
+    pub alert_id: String,
+    pub name: String,
+    pub query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    pub hits: Vec<AlertHit>,
+}
@@ -0,0 +1,94 @@
+//! Case Models
+//!
+//! Request/Response structures for investigator cases and the searches,
+//! bookmarks, incidents and exports attached to them.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// What an item attached to a case refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseItemKind {
+    /// A search that was run, e.g. its query or full request
+    Search,
+    /// A stored image worth keeping, by point ID
+    Bookmark,
+    /// An incident report, e.g. its ticket number
+    Incident,
+    /// Exported images, e.g. a file name or `/images` page token
+    Export,
+}
+
+/// Body of `POST /cases`
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateCaseRequest {
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Body of `POST /cases/{case_id}/items`
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "kind": "bookmark",
+    "reference": "1234",
+    "note": "Plate readable, driver visible"
+}))]
+pub struct AttachCaseItemRequest {
+    pub kind: CaseItemKind,
+    /// What the item points at: a query, point ID, ticket number or export
+    pub reference: String,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Anything else worth keeping, e.g. the full search request
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<serde_json::Value>,
+}
+
+/// Item attached to a case
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CaseItem {
+    /// Position in the case, starting at 1
+    pub item_id: u64,
+    pub kind: CaseItemKind,
+    pub reference: String,
+    pub note: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<serde_json::Value>,
+    /// RFC 3339
+    pub added_at: String,
+    /// `sub` claim of the token that attached it; `null` without JWT
+    pub added_by: Option<String>,
+}
+
+/// A case with everything attached to it, oldest item first
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Case {
+    pub case_id: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// RFC 3339
+    pub created_at: String,
+    /// `sub` claim of the token that created it; `null` without JWT
+    pub created_by: Option<String>,
+    /// RFC 3339; when the case was created or the last item attached
+    pub updated_at: String,
+    /// Tenant that opened it; omitted without tenancy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    pub items: Vec<CaseItem>,
+}
+
+/// A case without its items, as listed by `GET /cases`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CaseSummary {
+    pub case_id: String,
+    pub title: String,
+    pub created_at: String,
+    pub created_by: Option<String>,
+    pub updated_at: String,
+    /// Number of attached items
+    pub items: usize,
+}
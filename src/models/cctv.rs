@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CctvListResponse {
@@ -10,3 +11,61 @@ pub struct CctvListResponse {
 pub struct CctvItem {
     pub cctv_id: String,
 }
+
+/// Camera entry in the local camera registry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraEntry {
+    pub cctv_id: String,
+    /// Disabled cameras are skipped by the scheduler
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Display name returned with search results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Free-text location, e.g. the road or junction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lat: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lon: Option<f64>,
+    /// IANA timezone of the `date`/`time` this camera reports, instead of
+    /// `CCTV_SOURCE_TIMEZONE`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Tenant (customer site) the camera's images are stored for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Human-readable label of a raw `vehicle_type` or `yolo_id` code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct VehicleTypeLabel {
+    pub code: u32,
+    pub label: String,
+}
+
+/// Labels of the raw vehicle codes, as read from the vehicle type mapping file
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "vehicle_types": [
+        { "code": 1, "label": "Car" },
+        { "code": 2, "label": "Pickup truck" }
+    ],
+    "yolo_ids": [
+        { "code": 2, "label": "car" },
+        { "code": 7, "label": "truck" }
+    ]
+}))]
+pub struct VehicleTypeMapping {
+    /// Labels of the CCTV API `vehicle_type` codes
+    #[serde(default)]
+    pub vehicle_types: Vec<VehicleTypeLabel>,
+    /// Labels of the detector class `yolo_id` codes
+    #[serde(default)]
+    pub yolo_ids: Vec<VehicleTypeLabel>,
+}
@@ -0,0 +1,41 @@
+//! Chaos Models
+//!
+//! Request/Response structures for the dev-only chaos endpoints.
+
+use crate::services::ChaosTarget;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Fault injection applied before each call to a dependency
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema)]
+pub struct ChaosSettings {
+    /// Extra delay added to every call
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// Probability (0.0-1.0) that a call fails
+    #[serde(default)]
+    pub failure_rate: f32,
+}
+
+impl ChaosSettings {
+    /// No injected latency or failures
+    pub const NONE: Self = Self {
+        latency_ms: 0,
+        failure_rate: 0.0,
+    };
+}
+
+/// Request to configure chaos for one dependency
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChaosRequest {
+    pub target: ChaosTarget,
+    #[serde(flatten)]
+    pub settings: ChaosSettings,
+}
+
+/// Chaos currently configured for every dependency
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChaosStatus {
+    pub ai_service: ChaosSettings,
+    pub qdrant: ChaosSettings,
+}
@@ -0,0 +1,41 @@
+//! Embedding Models
+//!
+//! Request/Response structures for the embedding passthrough endpoints.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Text to embed
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct EmbedTextRequest {
+    #[schema(example = "รถกระบะสีขาว")]
+    pub text: String,
+}
+
+/// Image to embed; exactly one of `image_path` and `image_base64` is required
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct EmbedImageRequest {
+    /// Path of an image readable by the AI service
+    #[serde(default)]
+    pub image_path: Option<String>,
+    /// Image content, base64 encoded (a `data:` URL prefix is accepted)
+    #[serde(default)]
+    pub image_base64: Option<String>,
+}
+
+/// Embedding returned by an `/embed/*` endpoint
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "embedding": [0.0123, -0.0456, 0.0789],
+    "dimensions": 1152,
+    "model": "siglip-so400m",
+    "cached": false
+}))]
+pub struct EmbeddingResponse {
+    pub embedding: Vec<f32>,
+    pub dimensions: usize,
+    /// Model identity, as reported by `GET /version`
+    pub model: String,
+    /// Served from the embedding cache without calling the AI service
+    pub cached: bool,
+}
@@ -0,0 +1,115 @@
+//! Evaluation Models
+//!
+//! Request/Response structures for labeled query sets and the reports of
+//! running them through the search pipeline.
+
+use crate::models::search::LabeledQuery;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Body of `POST /admin/eval/sets`
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "name": "Gate traffic, October",
+    "queries": [
+        {"query": "white pickup truck at the north gate", "relevant_ids": [1728394756, 1728395012]},
+        {"query": "red motorcycle", "relevant_ids": [1728401123]}
+    ]
+}))]
+pub struct CreateEvalSetRequest {
+    pub name: String,
+    pub queries: Vec<LabeledQuery>,
+}
+
+/// Labeled query set with the summaries of its runs
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EvalSet {
+    pub set_id: String,
+    pub name: String,
+    pub queries: Vec<LabeledQuery>,
+    /// RFC 3339
+    pub created_at: String,
+    /// `sub` claim of the token that uploaded it; `null` without JWT
+    pub created_by: Option<String>,
+    /// Most recent runs, oldest first
+    #[serde(default)]
+    pub runs: Vec<EvalRunSummary>,
+}
+
+/// Labeled query set without its queries and runs
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EvalSetSummary {
+    pub set_id: String,
+    pub name: String,
+    pub queries: usize,
+    pub created_at: String,
+    pub created_by: Option<String>,
+    /// Most recent run, if any
+    pub last_run: Option<EvalRunSummary>,
+}
+
+/// Body of `POST /admin/eval/sets/{set_id}/run`
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "top_k": 10,
+    "search": {"hybrid": true, "hybrid_fusion": "weighted", "hybrid_alpha": 0.6}
+}))]
+pub struct EvalRunRequest {
+    /// Cutoff of recall and reciprocal rank (default: 10)
+    #[serde(default)]
+    pub top_k: Option<u64>,
+    /// `/search` request fields applied to every query, e.g. `camera_ids`
+    /// or `hybrid`; `query` and `top_k` are set per query
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub search: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// Search latency over the queries of a run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct LatencySummary {
+    pub mean_ms: f64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+}
+
+/// Aggregate results of a run
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EvalRunSummary {
+    /// RFC 3339
+    pub started_at: String,
+    pub top_k: u64,
+    /// Embedding models in use when the run started
+    pub embedding_models: Vec<String>,
+    /// Search request fields the run applied to every query
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub search: Option<serde_json::Map<String, serde_json::Value>>,
+    /// Mean share of the relevant images within the top `top_k`
+    pub recall: f64,
+    /// Mean reciprocal rank of the first relevant image within the top `top_k`
+    pub mrr: f64,
+    pub latency: LatencySummary,
+}
+
+/// Results of one query of a run
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EvalQueryResult {
+    pub query: String,
+    pub recall: f64,
+    /// `1 / rank` of the first relevant image, `0` if none was returned
+    pub reciprocal_rank: f64,
+    pub latency_ms: u64,
+    /// Relevant images not within the top `top_k`
+    pub missed_ids: Vec<u64>,
+}
+
+/// Report of `POST /admin/eval/sets/{set_id}/run`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EvalRunReport {
+    pub set_id: String,
+    #[serde(flatten)]
+    pub summary: EvalRunSummary,
+    pub results: Vec<EvalQueryResult>,
+}
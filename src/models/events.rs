@@ -0,0 +1,19 @@
+//! Live Event Models
+//!
+//! Query options for the `GET /events` Server-Sent Events stream.
+
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// Filters of one event stream; unset filters let every event through
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventsQuery {
+    /// Only alert on images from this camera, and only report runs fetching
+    /// it or every camera
+    pub camera_id: Option<String>,
+    /// Only alert on images whose AI label has this class
+    pub vehicle_class: Option<String>,
+    /// Only send the `alert` events of this saved search
+    pub alert_id: Option<String>,
+}
@@ -0,0 +1,55 @@
+//! Export Models
+//!
+//! Request/Response structures for paging through stored image metadata.
+
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Selection and page of stored images to export
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListImagesQuery {
+    /// Only list images from this camera
+    pub camera_id: Option<String>,
+    /// Only list images after this time (RFC 3339)
+    pub start_date: Option<String>,
+    /// Only list images up to and including this time (RFC 3339)
+    pub end_date: Option<String>,
+    /// Only list images whose AI label has this class
+    pub vehicle_class: Option<String>,
+    /// Images per page (default 100, max 1000)
+    pub limit: Option<u32>,
+    /// `next_page_token` of the previous page; omit for the first page
+    pub page_token: Option<String>,
+}
+
+/// Stored image with its full payload
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "id": "1234",
+    "payload": {
+        "image": "http://example.com/images/cctv01/20250101_120000.jpg",
+        "filename": "20250101_120000.jpg",
+        "camera_id": "cctv01",
+        "datetime": "2025-01-01T12:00:00+07:00",
+        "frame": 1,
+        "vehicle_type": 2,
+        "yolo_id": 7,
+        "created_at": "2025-01-01T12:00:05+07:00"
+    }
+}))]
+pub struct StoredImage {
+    /// Qdrant point ID
+    pub id: String,
+    /// Every payload field stored for the image
+    #[schema(value_type = Object)]
+    pub payload: serde_json::Map<String, serde_json::Value>,
+}
+
+/// One page of stored images
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImagePage {
+    pub images: Vec<StoredImage>,
+    /// Pass as `page_token` to fetch the next page; `null` on the last page
+    pub next_page_token: Option<String>,
+}
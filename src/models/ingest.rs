@@ -0,0 +1,54 @@
+//! Edge Ingest Models
+//!
+//! Request/Response structures for `POST /ingest/batch`, through which
+//! edge devices send images they have already embedded.
+
+use crate::models::search::{BatchInsertFailure, CctvImageData};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Batch of embedded images from one edge device
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct EdgeIngestBatch {
+    /// Start time of the sending agent (Unix seconds); sequences restart
+    /// at 1 with each epoch
+    pub epoch: u64,
+    /// Position of the batch in its epoch, from 1; a retry resends the
+    /// same sequence
+    pub sequence: u64,
+    /// Images, each with its `embedding`
+    pub points: Vec<CctvImageData>,
+}
+
+/// Inclusive range of batch sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct SequenceRange {
+    pub from: u64,
+    pub to: u64,
+}
+
+/// What happened to an acknowledged batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IngestAckStatus {
+    /// Stored now
+    Accepted,
+    /// Stored by an earlier attempt; nothing was written
+    Duplicate,
+}
+
+/// Acknowledgement of a stored batch; the device may drop it once received
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EdgeIngestAck {
+    pub device_id: String,
+    pub epoch: u64,
+    pub sequence: u64,
+    pub status: IngestAckStatus,
+    /// Point IDs upserted (empty for a duplicate)
+    pub inserted: Vec<u64>,
+    /// Images that were not stored, e.g. sent without an embedding; a
+    /// retry won't store them either
+    pub failed: Vec<BatchInsertFailure>,
+    /// Sequences of the epoch not received yet, oldest first
+    pub missing: Vec<SequenceRange>,
+}
@@ -1,3 +1,16 @@
+pub mod admin;
+pub mod alert;
+pub mod case;
 pub mod cctv;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod embed;
+pub mod evaluation;
+pub mod events;
+pub mod export;
+pub mod ingest;
+pub mod scheduler;
 pub mod search;
+pub mod session;
+pub mod system;
 pub mod token;
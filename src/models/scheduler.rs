@@ -0,0 +1,50 @@
+//! Scheduler Models
+//!
+//! Request/Response structures for the fetch run endpoints.
+
+use crate::services::SchedulerRun;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Query options for `/scheduler/runs`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SchedulerRunsQuery {
+    /// Maximum number of runs to return (default: 20)
+    pub limit: Option<usize>,
+}
+
+/// Recent fetch runs
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SchedulerRunsResponse {
+    /// Whether the `scheduler` feature is enabled in this process
+    pub scheduler_enabled: bool,
+    /// Newest first
+    pub runs: Vec<SchedulerRun>,
+}
+
+/// Overrides for a manually triggered fetch; unset fields use the
+/// scheduler defaults
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct TriggerFetchRequest {
+    /// Fetch only this camera, even if disabled (default: every enabled camera)
+    pub cctv_id: Option<String>,
+    /// Start of the window in RFC 3339 format (default: `FETCH_EVERY_TIME` minutes before `end_date`)
+    pub start_date: Option<String>,
+    /// End of the window in RFC 3339 format (default: now)
+    pub end_date: Option<String>,
+    /// Images per camera (default: `FETCH_LIMIT`)
+    pub limit: Option<u32>,
+    /// Embed and overwrite images that are already stored instead of
+    /// skipping them, e.g. to correct their payload (default: false)
+    #[serde(default)]
+    pub reingest: bool,
+}
+
+/// A fetch run started in the background
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TriggerFetchResponse {
+    pub job_id: String,
+    /// Where the run's progress and outcome can be read
+    pub status_url: String,
+}
@@ -3,31 +3,438 @@
 //! Request/Response structures for the API and external services.
 
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
 
 // =============================================================================
 // Search API Models
 // =============================================================================
 
 /// Request for searching images with optional datetime filtering
-#[derive(Debug, Deserialize, ToSchema)]
+#[derive(Debug, Default, Deserialize, ToSchema)]
 pub struct SearchRequest {
     pub query: String,
     #[serde(default)]
     pub top_k: Option<u64>,
+    /// Drop hits whose vector similarity is below this score
+    #[serde(default)]
+    pub min_score: Option<f32>,
+    /// Start date filter in RFC 3339 format
+    pub start_date: Option<String>,
+    /// End date filter in RFC 3339 format
+    pub end_date: Option<String>,
+    /// Only images from the last this many hours, instead of
+    /// `start_date`/`end_date`
+    #[serde(default)]
+    pub last_hours: Option<u64>,
+    /// Only images from the last this many days, instead of
+    /// `start_date`/`end_date`
+    #[serde(default)]
+    pub last_days: Option<u64>,
+    /// Only images from this ISO 8601 duration before now, e.g. `PT24H` or
+    /// `P7D`, instead of `start_date`/`end_date`
+    #[serde(default)]
+    pub last: Option<String>,
+    /// Only return images from these cameras
+    #[serde(default)]
+    pub camera_ids: Option<Vec<String>>,
+    /// Only return images whose AI label is one of these classes, e.g. `truck`
+    #[serde(default)]
+    pub vehicle_classes: Option<Vec<String>>,
+    /// Only return images whose AI label confidence is at least this (0 to 1)
+    #[serde(default)]
+    pub min_confidence: Option<f32>,
+    /// Exact-match conditions on upstream extra fields, e.g.
+    /// `{"extra.lane": 2}`; an array value matches any of its items
+    #[serde(default)]
+    pub filters: Option<HashMap<String, serde_json::Value>>,
+    /// How much of each result to return: `minimal`, `standard` or `full`
+    #[serde(default)]
+    pub detail: ResultDetail,
+    /// Split the datetime range into this many sub-ranges searched in parallel
+    /// (requires both `start_date` and `end_date`)
+    #[serde(default)]
+    pub fanout_chunks: Option<u32>,
+    /// Override the configured Qdrant search parameters for this request
+    #[serde(default)]
+    pub search_params: Option<SearchParamsRequest>,
+    /// Multiply scores by 0.5 per this many hours of image age, favoring
+    /// recent sightings; `0` disables (default: `SEARCH_RECENCY_HALF_LIFE_HOURS`)
+    #[serde(default)]
+    pub recency_half_life_hours: Option<f64>,
+    /// Search session whose feedback examples steer this search
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Keep only the best hit per camera within this many seconds, so a
+    /// burst of frames of one vehicle counts as one result
+    #[serde(default)]
+    pub collapse_window_s: Option<u64>,
+    /// Treat `query` as weighted prompts, e.g. `+1.0 "pickup truck" -0.5 "delivery van"`,
+    /// and search with the combined embedding
+    #[serde(default)]
+    pub arithmetic: bool,
+    /// Also match the query words against the `filename` and `caption` fields
+    /// and combine that with vector similarity
+    #[serde(default)]
+    pub hybrid: bool,
+    /// How hybrid text matches are combined: `rrf`, `dbsf`, `weighted` or
+    /// `filter` (default: `weighted` if `hybrid_alpha` is set, else
+    /// `SEARCH_HYBRID_FUSION`)
+    #[serde(default)]
+    pub hybrid_fusion: Option<String>,
+    /// Weight of vector similarity against keyword matches in `weighted`
+    /// fusion, 0 to 1 (default: `SEARCH_HYBRID_ALPHA`)
+    #[serde(default)]
+    pub hybrid_alpha: Option<f32>,
+    /// Return the best hits of each camera, as `[CameraGroup]`, instead of a
+    /// flat list; `top_k` is then the number of cameras
+    #[serde(default)]
+    pub group_by_camera: bool,
+    /// Hits per camera in grouped mode (default: 1)
+    #[serde(default)]
+    pub group_size: Option<u32>,
+    /// Run the search with several candidate HNSW settings and report
+    /// latency vs result overlap instead of plain results
+    #[serde(default)]
+    pub debug: bool,
+    /// Compare the query with the images (`image`) or with their captions
+    /// (`caption`, requires `VECTOR_LAYOUT=named`)
+    #[serde(default)]
+    pub vector_space: VectorSpace,
+}
+
+/// Embeddings a text query is compared with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorSpace {
+    /// The image embeddings
+    #[default]
+    Image,
+    /// The caption text embeddings
+    Caption,
+}
+
+/// Qdrant search parameters that can be tuned per request
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct SearchParamsRequest {
+    /// Size of the HNSW beam; higher is more accurate but slower
+    pub hnsw_ef: Option<u64>,
+    /// Bypass the HNSW index and run an exact (brute force) search
+    pub exact: Option<bool>,
+    /// Only search segments that are already indexed (skips points still being ingested)
+    pub indexed_only: Option<bool>,
+    /// Read consistency on distributed Qdrant: `all`, `majority`, `quorum` or a node count
+    pub consistency: Option<String>,
+}
+
+/// Request for searching images similar to a given image
+///
+/// Exactly one of `image_path` and `image_base64` must be set.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SearchByImageRequest {
+    /// Path of an image under `EMBED_IMAGE_ROOT`, relative to it or absolute
+    #[serde(default)]
+    pub image_path: Option<String>,
+    /// Image content, base64 encoded (a `data:` URL prefix is accepted)
+    #[serde(default)]
+    pub image_base64: Option<String>,
+    /// Number of results (default: 5)
+    #[serde(default)]
+    pub top_k: Option<u64>,
+    /// Drop hits whose vector similarity is below this score
+    #[serde(default)]
+    pub min_score: Option<f32>,
+    /// Start date filter in RFC 3339 format
+    pub start_date: Option<String>,
+    /// End date filter in RFC 3339 format
+    pub end_date: Option<String>,
+    /// Only return images from these cameras
+    #[serde(default)]
+    pub camera_ids: Option<Vec<String>>,
+    /// Only return images whose AI label is one of these classes, e.g. `truck`
+    #[serde(default)]
+    pub vehicle_classes: Option<Vec<String>>,
+    /// Only return images whose AI label confidence is at least this (0 to 1)
+    #[serde(default)]
+    pub min_confidence: Option<f32>,
+    /// Exact-match conditions on upstream extra fields, e.g.
+    /// `{"extra.lane": 2}`; an array value matches any of its items
+    #[serde(default)]
+    pub filters: Option<HashMap<String, serde_json::Value>>,
+    /// How much of each result to return: `minimal`, `standard` or `full`
+    #[serde(default)]
+    pub detail: ResultDetail,
+    /// Multiply scores by 0.5 per this many hours of image age, favoring
+    /// recent sightings; `0` disables (default: `SEARCH_RECENCY_HALF_LIFE_HOURS`)
+    #[serde(default)]
+    pub recency_half_life_hours: Option<f64>,
+    /// Search session whose feedback examples steer this search
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Keep only the best hit per camera within this many seconds, so a
+    /// burst of frames of one vehicle counts as one result
+    #[serde(default)]
+    pub collapse_window_s: Option<u64>,
+}
+
+/// Request for images similar to stored images, by point ID
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RecommendRequest {
+    /// Images to find more of
+    pub positive: Vec<String>,
+    /// Images to steer away from
+    #[serde(default)]
+    pub negative: Vec<String>,
+    #[serde(default)]
+    pub top_k: Option<u64>,
+    /// Drop hits whose vector similarity is below this score
+    #[serde(default)]
+    pub min_score: Option<f32>,
+    /// Start date filter in RFC 3339 format
+    pub start_date: Option<String>,
+    /// End date filter in RFC 3339 format
+    pub end_date: Option<String>,
+    /// Only return images from these cameras
+    #[serde(default)]
+    pub camera_ids: Option<Vec<String>>,
+    /// Only return images whose AI label is one of these classes, e.g. `truck`
+    #[serde(default)]
+    pub vehicle_classes: Option<Vec<String>>,
+    /// Only return images whose AI label confidence is at least this (0 to 1)
+    #[serde(default)]
+    pub min_confidence: Option<f32>,
+    /// Exact-match conditions on upstream extra fields, e.g.
+    /// `{"extra.lane": 2}`; an array value matches any of its items
+    #[serde(default)]
+    pub filters: Option<HashMap<String, serde_json::Value>>,
+    /// How much of each result to return: `minimal`, `standard` or `full`
+    #[serde(default)]
+    pub detail: ResultDetail,
+}
+
+/// Request for sightings of one query on several cameras close together in time
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CorrelatedSearchRequest {
+    pub query: String,
+    /// Cameras to correlate; at least two
+    pub camera_ids: Vec<String>,
+    /// Maximum time between the two sightings of a pair (default: 30)
+    #[serde(default)]
+    pub within_minutes: Option<u32>,
+    /// Number of pairs to return (default: 5)
+    #[serde(default)]
+    pub top_k: Option<u64>,
+    /// Drop hits whose vector similarity is below this score
+    #[serde(default)]
+    pub min_score: Option<f32>,
     /// Start date filter in RFC 3339 format
     pub start_date: Option<String>,
     /// End date filter in RFC 3339 format
     pub end_date: Option<String>,
+    /// How much of each result to return: `minimal`, `standard` or `full`
+    #[serde(default)]
+    pub detail: ResultDetail,
+}
+
+/// Sightings of the query on two different cameras within the window
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CorrelatedMatch {
+    /// Mean of the two scores
+    pub score: f32,
+    /// Seconds from `first` to `second`
+    pub gap_seconds: i64,
+    /// Earlier sighting
+    pub first: SearchResult,
+    /// Later sighting, on another camera
+    pub second: SearchResult,
+}
+
+/// Fields included in each search result
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultDetail {
+    /// Only `id`, `score`, `camera_id`, `datetime` and `file_path`, for slow links
+    Minimal,
+    /// The fields below without `payload` and `score_breakdown`
+    #[default]
+    Standard,
+    /// Also every stored payload field and how the score was computed
+    Full,
+}
+
+/// How the final score of a result was computed
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScoreBreakdown {
+    /// Score from Qdrant: vector similarity, or the fused rank score in hybrid mode
+    pub base_score: f32,
+    /// Multiplier of the recency boost (`1` when not boosted)
+    pub recency_factor: f32,
 }
 
 /// Result from image search
 #[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "filename": "cctv01_2025-10-08_06-32_123.jpg",
+    "id": "12345",
+    "score": 0.89,
+    "datetime": "2025-10-08T06:32:00+07:00",
+    "camera_id": "cctv01",
+    "file_path": "https://example.com/images/cctv01_2025-10-08_06-32_123.jpg",
+    "frame": 123,
+    "vehicle_class": "pickup",
+    "confidence": 0.95,
+    "vehicle_type_label": "Pickup truck",
+    "yolo_label": "truck"
+}))]
 pub struct SearchResult {
-    pub filename: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// Qdrant point ID
     pub id: String,
     pub score: f32,
     pub datetime: String,
+    pub camera_id: String,
+    /// Display name of the camera, from the camera registry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_name: Option<String>,
+    /// Location of the camera, from the camera registry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lat: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lon: Option<f64>,
+    /// Full path or URL of the image
+    pub file_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame: Option<i64>,
+    /// AI label class, when the image was labeled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vehicle_class: Option<String>,
+    /// AI label confidence, when the image was labeled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// Label of the `vehicle_type` code, when mapped at ingest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vehicle_type_label: Option<String>,
+    /// Label of the `yolo_id` code, when mapped at ingest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub yolo_label: Option<String>,
+    /// Description of the image, sent at insert or generated at ingest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    /// Every stored payload field, with `detail: full`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Map<String, serde_json::Value>>,
+    /// With `detail: full`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score_breakdown: Option<ScoreBreakdown>,
+}
+
+/// Best hits of one camera in a grouped search
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CameraGroup {
+    pub camera_id: String,
+    /// Hits of this camera, best first
+    pub hits: Vec<SearchResult>,
+}
+
+/// Latency and recall of one candidate search setting in debug mode
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchParamTrial {
+    /// HNSW `ef` used for this trial (`None` = collection default)
+    pub hnsw_ef: Option<u64>,
+    /// Number of candidates requested from Qdrant before truncating to `top_k`
+    pub limit: u64,
+    pub latency_ms: u64,
+    /// Fraction of the exact-search top-k also returned by this trial
+    pub overlap: f32,
+}
+
+/// Response of a search executed in debug mode
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchDebugResponse {
+    /// Results of the exact (brute force) reference search
+    pub results: Vec<SearchResult>,
+    pub reference_latency_ms: u64,
+    pub trials: Vec<SearchParamTrial>,
+}
+
+/// Evaluation query with the IDs of the images relevant to it
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LabeledQuery {
+    pub query: String,
+    pub relevant_ids: Vec<u64>,
+}
+
+/// Request to sweep the hybrid weight against a labeled evaluation set
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "queries": [
+        {"query": "white pickup truck at the north gate", "relevant_ids": [1728394756, 1728395012]},
+        {"query": "red motorcycle", "relevant_ids": [1728401123]}
+    ],
+    "top_k": 10
+}))]
+pub struct HybridCalibrationRequest {
+    pub queries: Vec<LabeledQuery>,
+    /// Alphas to try (default: 0 to 1 in steps of 0.1)
+    #[serde(default)]
+    pub alphas: Option<Vec<f32>>,
+    /// Cutoff of recall and reciprocal rank (default: 10)
+    #[serde(default)]
+    pub top_k: Option<u64>,
+    /// Only search images of these cameras
+    #[serde(default)]
+    pub camera_ids: Option<Vec<String>>,
+}
+
+/// Ranking quality of the evaluation set at one alpha
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AlphaTrial {
+    pub alpha: f32,
+    /// Mean share of the relevant images within the top `top_k`
+    pub recall: f64,
+    /// Mean reciprocal rank of the first relevant image within the top `top_k`
+    pub mrr: f64,
+}
+
+/// Result of a hybrid weight calibration
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HybridCalibrationResponse {
+    /// Alpha with the highest MRR, then recall
+    pub best_alpha: f32,
+    /// `SEARCH_HYBRID_ALPHA` currently in effect
+    pub current_alpha: f32,
+    pub queries: usize,
+    pub top_k: u64,
+    pub trials: Vec<AlphaTrial>,
+}
+
+/// Image that could not be embedded in a batch insert
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchInsertFailure {
+    pub id: u64,
+    pub error: String,
+}
+
+/// Outcome of a batch insert
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchInsertResponse {
+    /// Point IDs upserted into the collection
+    pub inserted: Vec<u64>,
+    pub failed: Vec<BatchInsertFailure>,
+    /// Points that did not read back as written (only when verification ran)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification_mismatches: Option<Vec<String>>,
+}
+
+/// Query options for the insert endpoints
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct InsertOptions {
+    /// Read points back after upsert and compare payloads (default: `VERIFY_UPSERTS`)
+    pub verify: Option<bool>,
 }
 
 // =============================================================================
@@ -65,6 +472,22 @@ pub struct BatchImageEmbeddingResponse {
     pub results: Vec<BatchImageEmbeddingResult>,
 }
 
+/// Individual result in batch caption response
+#[derive(Debug, Deserialize)]
+pub struct BatchImageCaptionResult {
+    pub path: String,
+    #[serde(default)]
+    pub caption: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Response from the AI service captioning endpoint
+#[derive(Debug, Deserialize)]
+pub struct BatchImageCaptionResponse {
+    pub results: Vec<BatchImageCaptionResult>,
+}
+
 // =============================================================================
 // CCTV Metadata API Models
 // =============================================================================
@@ -89,6 +512,19 @@ pub struct CctvMetadataResponse {
 
 /// Individual CCTV image metadata
 #[derive(Debug, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "id": 12345,
+    "cctv_id": "cctv01",
+    "date": "2025-10-08",
+    "time": "06:32:00",
+    "frame": 123,
+    "vehicle_type": 2,
+    "yolo_id": 5,
+    "filename": "cctv01_2025-10-08_06-32_123.jpg",
+    "file_path": "https://example.com/images/cctv01_2025-10-08_06-32_123.jpg",
+    "ai_label": { "class_name": "pickup", "confidence": 0.95 },
+    "createdAt": "2025-10-08T06:32:15Z"
+}))]
 pub struct CctvImageData {
     pub id: u32,
     pub cctv_id: String,
@@ -100,6 +536,18 @@ pub struct CctvImageData {
     pub filename: String,
     pub file_path: String,
     pub ai_label: Option<AiLabel>,
+    /// Description of the image, stored in `caption` and, with
+    /// `VECTOR_LAYOUT=named`, embedded into the `caption_text` vector;
+    /// generated at ingest when `CAPTION_ENDPOINT` is set and it is missing
+    #[serde(default)]
+    pub caption: Option<String>,
     #[serde(rename = "createdAt", default)]
     pub created_at: Option<String>,
+    /// Image embedding computed by the sender, e.g. an edge ingest agent;
+    /// the AI service is only called for images sent without one
+    #[serde(default)]
+    pub embedding: Option<Vec<f32>>,
+    /// Unrecognized fields, stored under the `extra` payload object
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
@@ -0,0 +1,25 @@
+//! Session Models
+//!
+//! Request/Response structures for search sessions.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Images marked during an investigation, by point ID
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SessionFeedback {
+    /// Images that look like what is being searched for
+    #[serde(default)]
+    pub positive: Vec<String>,
+    /// Images to steer away from
+    #[serde(default)]
+    pub negative: Vec<String>,
+}
+
+/// Examples collected in a session
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SessionState {
+    pub session_id: String,
+    pub positive: Vec<String>,
+    pub negative: Vec<String>,
+}
@@ -0,0 +1,62 @@
+//! System Models
+//!
+//! Response structures for service metadata endpoints.
+
+use crate::services::{AiDispatchStatus, AiRateLimitStatus, CircuitStatus};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+/// Embedding model the service is configured for
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EmbeddingModelInfo {
+    pub name: String,
+    pub vector_size: usize,
+    /// Collections pinned to another model in `EMBEDDING_MODELS_PATH`, and that model
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub pinned: BTreeMap<String, String>,
+}
+
+/// Result of checking one external dependency
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DependencyHealth {
+    /// `qdrant`, `ai_service` or `cctv_api`
+    pub name: String,
+    pub healthy: bool,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Probe response with the state of every dependency
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthResponse {
+    /// `ok` when every dependency is healthy, otherwise `degraded`
+    pub status: String,
+    pub dependencies: Vec<DependencyHealth>,
+}
+
+/// Runtime state of the resilience subsystems
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ServiceStatus {
+    /// Whether the `circuit-breaker` feature is enabled
+    pub circuit_breakers_enabled: bool,
+    /// One entry per dependency; empty when circuit breakers are disabled
+    pub circuits: Vec<CircuitStatus>,
+    /// Outbound AI service rate limiter; `None` when `AI_RATE_LIMIT_RPS` is unset
+    pub ai_rate_limit: Option<AiRateLimitStatus>,
+    /// Outbound AI service dispatch queue; `None` when `AI_MAX_IN_FLIGHT` is unset
+    pub ai_dispatch: Option<AiDispatchStatus>,
+}
+
+/// Build and runtime identity of this deployment
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VersionInfo {
+    pub version: String,
+    pub git_sha: String,
+    /// Build time in RFC 3339 format
+    pub build_time: String,
+    /// Optional subsystems enabled in this process
+    pub features: Vec<String>,
+    pub embedding_model: EmbeddingModelInfo,
+}
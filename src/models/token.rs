@@ -10,14 +10,21 @@ pub struct GetTokenRequest {
 
 #[derive(Debug, Deserialize)]
 pub struct TokenData {
+    #[allow(dead_code)]
     pub token_type: String,
     pub access_token: String,
+    #[allow(dead_code)]
     pub status: bool,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct GetTokenResponse {
-    pub Code: u32,
-    pub Message: String,
-    pub Data: TokenData,
+    #[serde(rename = "Code")]
+    #[allow(dead_code)]
+    pub code: u32,
+    #[serde(rename = "Message")]
+    #[allow(dead_code)]
+    pub message: String,
+    #[serde(rename = "Data")]
+    pub data: TokenData,
 }
@@ -3,19 +3,35 @@
 //! Handles scheduled tasks for fetching and processing CCTV images.
 
 use crate::clients::cctv_client::CctvApi;
-use crate::config::Config;
-use crate::models::search::{CctvImageData, CctvMetadataRequest};
+use crate::config::{Config, TunablesHandle, technical};
+use crate::error::AppError;
+use crate::models::search::{CctvImageData, CctvMetadataRequest, VectorSpace};
 use crate::services::cctv_service::CctvService;
-use crate::services::{PayloadBuilder, api_datetime_to_rfc3339, get_image_embedding};
-use chrono::Duration;
+use crate::services::{
+    AiPriority, AlertStore, CAPTION_TERMS_FIELD, CONTENT_HASH_FIELD, CameraDirectory,
+    CameraRegistry, ChaosTarget, DeadLetterQueue, Dependency, EXTRA_FIELD, EmbeddingModel,
+    EmbeddingModels, ImageFailure, IngestFeed, IngestPath, IngestThrottle, MaintenanceMode,
+    PayloadBuilder, RequestMetrics, RunTally, RunTrigger, SchedulerRun, SchedulerRunHistory,
+    ShardRouter, TENANT_FIELD, UpsertSettings, VEHICLE_TYPE_LABEL_FIELD, VehicleTypes,
+    WebhookDispatcher, WebhookEvent, YOLO_LABEL_FIELD, alert_filter, alert_notification,
+    api_datetime_to_rfc3339, caption_embedding, caption_terms, detect_id_collisions,
+    flush_collection, generate_captions, get_image_embedding, get_text_embedding, guarded,
+    hash_images, image_caption, ingest_rate, inject, stored_image_ids, validate_embedding,
+    verify_upsert,
+};
+use chrono::{DateTime, Duration, Timelike, Utc};
 use chrono_tz::Asia::Bangkok;
-//
-use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 
 use qdrant_client::Qdrant;
-use qdrant_client::qdrant::{PointStruct, UpsertPoints};
+use qdrant_client::qdrant::{PointId, PointStruct, SearchPoints, UpsertPoints, Vectors};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use tokio::sync::{OwnedRwLockReadGuard, RwLock};
+use tokio::task::{JoinHandle, JoinSet};
 use tokio_cron_scheduler::{Job, JobScheduler};
+use tokio_util::sync::CancellationToken;
+use tracing::{Instrument, debug, error, info, info_span, instrument, warn};
 
 /// Scheduler context containing shared resources
 #[derive(Clone)]
@@ -23,11 +39,77 @@ pub struct SchedulerContext {
     pub qdrant: Arc<Qdrant>,
     pub http_client: reqwest::Client,
     pub config: Config,
+    pub tunables: TunablesHandle,
+    pub maintenance: Arc<MaintenanceMode>,
+    /// Shared with `/metrics` for upsert verification counters
+    pub metrics: Arc<RequestMetrics>,
+    /// Maps cameras to their (shard) collection
+    pub router: Arc<ShardRouter>,
     pub cctv_service: CctvService<CctvApi>,
+    /// Outcomes of scheduled runs, shared with `/scheduler/runs`
+    pub run_history: Arc<SchedulerRunHistory>,
+    /// Images rejected for a defective embedding
+    pub dead_letters: Arc<DeadLetterQueue>,
+    /// Labels stored next to the raw vehicle codes
+    pub vehicle_types: Arc<VehicleTypes>,
+    /// Registered cameras, for their names and source timezones
+    pub cameras: Arc<CameraDirectory>,
+    /// Runs in flight, closed on shutdown
+    pub gate: RunGate,
+    /// Paces upserts to the ingest rate of the current hour
+    pub ingest_throttle: Arc<IngestThrottle>,
+    /// Notifies `/ws` subscribers of stored points
+    pub ingest_feed: Arc<IngestFeed>,
+    /// Embedding model and AI service of each collection
+    pub embedding_models: Arc<EmbeddingModels>,
+    /// Saved searches run against every stored batch
+    pub alerts: Arc<AlertStore>,
+    /// Posts run summaries and alert matches to external systems
+    pub webhooks: WebhookDispatcher,
+}
+
+/// Tracks scheduled and manual runs in flight, so shutdown can stop new
+/// runs and wait for the running ones to finish their upserts
+#[derive(Debug, Clone, Default)]
+pub struct RunGate {
+    closed: CancellationToken,
+    /// Read-locked by every run in flight
+    running: Arc<RwLock<()>>,
+}
+
+impl RunGate {
+    /// Guard held for the duration of a run, or `None` once closed
+    pub async fn enter(&self) -> Option<OwnedRwLockReadGuard<()>> {
+        if self.is_closed() {
+            return None;
+        }
+        Some(self.running.clone().read_owned().await)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.is_cancelled()
+    }
+
+    /// Refuse new runs and wait up to `timeout` for those in flight;
+    /// `false` if some were still running
+    pub async fn close(&self, timeout: std::time::Duration) -> bool {
+        self.closed.cancel();
+        tokio::time::timeout(timeout, self.running.write())
+            .await
+            .is_ok()
+    }
 }
 
 impl SchedulerContext {
-    pub fn new(qdrant: Arc<Qdrant>, http_client: reqwest::Client, config: Config) -> Self {
+    pub fn new(
+        qdrant: Arc<Qdrant>,
+        http_client: reqwest::Client,
+        config: Config,
+        tunables: TunablesHandle,
+        maintenance: Arc<MaintenanceMode>,
+        metrics: Arc<RequestMetrics>,
+        run_history: Arc<SchedulerRunHistory>,
+    ) -> Self {
         // Create CCTV API client with automatic token handling
         let cctv_client = CctvApi::new(
             config.cctv_api_url.clone(),
@@ -38,197 +120,780 @@ impl SchedulerContext {
 
         // Create CCTV service
         let cctv_service = CctvService::new(cctv_client);
+        let router = Arc::new(ShardRouter::new(
+            &config.collection_name,
+            config.collection_shards,
+        ));
+        let dead_letters = Arc::new(DeadLetterQueue::new(&config.dead_letter_path));
+        let cameras = Arc::new(CameraDirectory::new(
+            &config.camera_registry_path,
+            &config.cameras,
+        ));
+        let embedding_models = Arc::new(EmbeddingModels::unpinned(&config));
+        let webhooks = WebhookDispatcher::new(http_client.clone(), &config);
 
         Self {
             qdrant,
             http_client,
             config,
+            tunables,
+            maintenance,
+            metrics,
+            router,
             cctv_service,
+            run_history,
+            dead_letters,
+            vehicle_types: Arc::default(),
+            cameras,
+            gate: RunGate::default(),
+            ingest_throttle: Arc::default(),
+            ingest_feed: Arc::default(),
+            embedding_models,
+            alerts: Arc::default(),
+            webhooks,
         }
     }
+
+    /// Store `vehicle_types` labels with ingested images
+    pub fn with_vehicle_types(mut self, vehicle_types: Arc<VehicleTypes>) -> Self {
+        self.vehicle_types = vehicle_types;
+        self
+    }
+
+    /// Embed images with the model pinned for their collection
+    pub fn with_embedding_models(mut self, embedding_models: Arc<EmbeddingModels>) -> Self {
+        self.embedding_models = embedding_models;
+        self
+    }
+
+    /// Run the saved `alerts` against every stored batch
+    pub fn with_alerts(mut self, alerts: Arc<AlertStore>) -> Self {
+        self.alerts = alerts;
+        self
+    }
 }
 
 /// Start the background scheduler for CCTV image fetching
-pub async fn start_scheduler(ctx: SchedulerContext) {
+///
+/// The fetch job is re-created whenever a reload changes `fetch_every_time`.
+/// The returned task ends once `ctx.gate` is closed and the cron scheduler
+/// has stopped.
+pub async fn start_scheduler(ctx: SchedulerContext) -> JoinHandle<()> {
     tokio::spawn(async move {
-        let sched = JobScheduler::new()
+        let mut sched = JobScheduler::new()
             .await
             .expect("Failed to create scheduler");
 
-        // Save config values before moving ctx into closure
-        let fetch_every_time = ctx.config.fetch_every_time;
+        let mut tunables_rx = ctx.tunables.subscribe();
+        let mut fetch_every_time = ctx.tunables.current().fetch_every_time;
 
-        // Build cron expression dynamically based on FETCH_EVERY_TIME
-        let cron_expr = format!("0 */{} * * * *", fetch_every_time);
-
-        let job = Job::new_async(cron_expr.as_str(), move |_uuid, _l| {
-            let ctx = ctx.clone();
-            Box::pin(async move {
-                run_fetch_task(&ctx).await;
-            })
-        })
-        .expect("Failed to create scheduled job");
-
-        sched.add(job).await.expect("Failed to add job");
+        let mut job_id = sched
+            .add(fetch_job(ctx.clone(), fetch_every_time))
+            .await
+            .expect("Failed to add job");
         sched.start().await.expect("Failed to start scheduler");
 
-        println!(
-            "✅ Background scheduler started (every {} minutes)",
-            fetch_every_time
+        info!(
+            every_minutes = fetch_every_time,
+            "Background scheduler started"
         );
 
-        // Keep scheduler running
+        // Keep scheduler running, rescheduling when the interval is reloaded
+        let closed = ctx.gate.closed.clone();
         loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+            tokio::select! {
+                _ = closed.cancelled() => break,
+                changed = tunables_rx.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                }
+            }
+            let new_every = tunables_rx.borrow_and_update().fetch_every_time;
+            if new_every == fetch_every_time {
+                continue;
+            }
+
+            if let Err(e) = sched.remove(&job_id).await {
+                error!(error = %e, "Failed to remove fetch job");
+                continue;
+            }
+            match sched.add(fetch_job(ctx.clone(), new_every)).await {
+                Ok(id) => {
+                    job_id = id;
+                    fetch_every_time = new_every;
+                    info!(every_minutes = new_every, "Scheduler rescheduled");
+                }
+                Err(e) => error!(error = %e, "Failed to reschedule fetch job"),
+            }
+        }
+
+        // No new ticks; runs in flight are awaited through the gate
+        if let Err(e) = sched.shutdown().await {
+            error!(error = %e, "Failed to shut down scheduler");
         }
-    });
+        info!("Background scheduler stopped");
+    })
+}
+
+/// Build the cron job that runs the fetch task every `every_minutes` minutes
+fn fetch_job(ctx: SchedulerContext, every_minutes: i64) -> Job {
+    // Build cron expression dynamically based on FETCH_EVERY_TIME
+    let cron_expr = format!("0 */{} * * * *", every_minutes);
+
+    Job::new_async(cron_expr.as_str(), move |_uuid, _l| {
+        let ctx = ctx.clone();
+        Box::pin(async move {
+            run_fetch_task(&ctx)
+                .instrument(info_span!("scheduler_run"))
+                .await;
+        })
+    })
+    .expect("Failed to create scheduled job")
+}
+
+/// Boundaries scheduled fetch windows are aligned to, in Bangkok local time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowAlign {
+    /// The `fetch_every_time` minutes up to the moment the run starts
+    None,
+    /// Ending on the last whole minute, so consecutive runs abut exactly
+    Minute,
+    /// Slots of `fetch_every_time` minutes counted from the start of each hour
+    Hour,
+    /// Slots of `fetch_every_time` minutes counted from midnight
+    Day,
+}
+
+/// Parse `none`, `minute`, `hour` or `day`
+pub fn parse_window_align(value: &str) -> Result<WindowAlign, AppError> {
+    match value.to_ascii_lowercase().as_str() {
+        "none" => Ok(WindowAlign::None),
+        "minute" => Ok(WindowAlign::Minute),
+        "hour" => Ok(WindowAlign::Hour),
+        "day" => Ok(WindowAlign::Day),
+        _ => Err(AppError::InvalidRequest(format!(
+            "Invalid window alignment {:?}: expected none, minute, hour or day",
+            value
+        ))),
+    }
+}
+
+/// Start and end of the last complete window of `minutes` before `now`
+///
+/// With hour or day alignment, the slot ending a period is cut short at the
+/// period boundary, so no window spans two Bangkok hours or days.
+pub fn aligned_window(
+    now: DateTime<Utc>,
+    minutes: i64,
+    align: WindowAlign,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    let minutes = minutes.max(1);
+    let every = Duration::minutes(minutes);
+    let whole_minute = now
+        - Duration::seconds(now.second() as i64)
+        - Duration::nanoseconds(now.nanosecond() as i64);
+
+    let (period, into_period) = {
+        let local = whole_minute.with_timezone(&Bangkok);
+        match align {
+            WindowAlign::None => return (now - every, now),
+            WindowAlign::Minute => return (whole_minute - every, whole_minute),
+            WindowAlign::Hour => (60, local.minute() as i64),
+            WindowAlign::Day => (24 * 60, (local.hour() * 60 + local.minute()) as i64),
+        }
+    };
+
+    let into_slot = into_period % minutes;
+    let stop = whole_minute - Duration::minutes(into_slot);
+    // The slot ending a period may be shorter if `minutes` doesn't divide it
+    let length = match period % minutes {
+        rest if rest > 0 && into_slot == into_period => rest,
+        _ => minutes,
+    };
+    (stop - Duration::minutes(length), stop)
+}
+
+/// CCTV metadata window a fetch run reads
+#[derive(Debug, Clone)]
+pub struct FetchWindow {
+    /// Bangkok local time, `%Y-%m-%d %H:%M:%S` as the CCTV API expects
+    pub date_start: String,
+    pub date_stop: String,
+    /// Fetch only this camera (even if disabled) instead of every enabled one
+    pub cctv_id: Option<String>,
+    /// Images per camera
+    pub limit: u32,
+    /// Embed and overwrite images that are already stored instead of
+    /// skipping them
+    pub reingest: bool,
+}
+
+impl FetchWindow {
+    /// The `minutes` leading up to now, for every enabled camera
+    pub fn last_minutes(minutes: i64, limit: u32) -> Self {
+        Self::aligned(minutes, WindowAlign::None, limit)
+    }
+
+    /// The last complete `minutes` window aligned per `align`, for every
+    /// enabled camera
+    pub fn aligned(minutes: i64, align: WindowAlign, limit: u32) -> Self {
+        let (start, stop) = aligned_window(Utc::now(), minutes, align);
+        Self::between(start, stop, None, limit)
+    }
+
+    pub fn between(
+        start: DateTime<Utc>,
+        stop: DateTime<Utc>,
+        cctv_id: Option<String>,
+        limit: u32,
+    ) -> Self {
+        // The CCTV API works in Thailand local time
+        let format = |dt: DateTime<Utc>| {
+            dt.with_timezone(&Bangkok)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        };
+        Self {
+            date_start: format(start),
+            date_stop: format(stop),
+            cctv_id,
+            limit,
+            reingest: false,
+        }
+    }
 }
 
-/// Run the CCTV image fetch and processing task
+/// Run the CCTV image fetch and processing task, recording its outcome
 async fn run_fetch_task(ctx: &SchedulerContext) {
-    println!("\n⏰ Running scheduled CCTV image fetch...");
-
-    // Calculate time range in Thailand timezone
-    let now = chrono::Utc::now().with_timezone(&Bangkok);
-    let date_stop = now.format("%Y-%m-%d %H:%M:%S").to_string();
-    let date_start = (now - Duration::minutes(ctx.config.fetch_every_time))
-        .format("%Y-%m-%d %H:%M:%S")
-        .to_string();
-    // Get list of all CCTV IDs
-    let cctv_ids = match ctx.cctv_service.list_cctv().await {
-        Ok(ids) => {
-            println!("📹 Found {} CCTV cameras", ids.len());
-            ids
+    let tunables = ctx.tunables.current();
+    let align = parse_window_align(&tunables.fetch_window_align).unwrap_or(WindowAlign::None);
+    let window = FetchWindow::aligned(tunables.fetch_every_time, align, tunables.fetch_limit);
+    let mut run = start_run(&window, RunTrigger::Scheduled);
+    info!(
+        run_id = %run.id,
+        date_start = %window.date_start,
+        date_stop = %window.date_stop,
+        align = ?align,
+        "Scheduled fetch window"
+    );
+
+    if ctx.maintenance.is_enabled() {
+        info!("Maintenance mode active, skipping scheduled fetch");
+        run.skip("Maintenance mode active");
+        ctx.run_history.record(run.clone());
+        ctx.webhooks.dispatch(&WebhookEvent::RunFinished(run));
+        return;
+    }
+
+    info!("Running scheduled CCTV image fetch");
+    ctx.run_history.record(run.clone());
+    execute_run(ctx, run, &window).await;
+}
+
+/// New run record for `window`
+pub fn start_run(window: &FetchWindow, trigger: RunTrigger) -> SchedulerRun {
+    SchedulerRun::start(
+        trigger,
+        window.date_start.clone(),
+        window.date_stop.clone(),
+        window.cctv_id.clone(),
+    )
+}
+
+/// Fetch and process `window`, then store the outcome of the recorded `run`
+#[instrument(skip_all, fields(run_id = %run.id, trigger = ?run.trigger))]
+pub async fn execute_run(ctx: &SchedulerContext, mut run: SchedulerRun, window: &FetchWindow) {
+    let Some(_running) = ctx.gate.enter().await else {
+        info!("Shutting down, skipping fetch run");
+        run.skip("Shutting down");
+        ctx.run_history.update(&run);
+        ctx.webhooks.dispatch(&WebhookEvent::RunFinished(run));
+        return;
+    };
+    // Scheduled and manual runs always complete, even during shutdown
+    let tally = run_fetch(ctx, window, &CancellationToken::new(), Some(&mut run)).await;
+    run.finish(tally);
+    ctx.run_history.update(&run);
+    ctx.webhooks.dispatch(&WebhookEvent::RunFinished(run));
+}
+
+/// Fetch and process images from all cameras for the last `minutes` minutes
+#[instrument(skip(ctx, cancel))]
+pub async fn run_fetch_window(
+    ctx: &SchedulerContext,
+    minutes: i64,
+    cancel: &CancellationToken,
+) -> RunTally {
+    let window = FetchWindow::last_minutes(minutes, ctx.tunables.current().fetch_limit);
+    run_fetch(ctx, &window, cancel, None).await
+}
+
+/// Fetch and process the images in `window`
+///
+/// Embedding batches run concurrently; cancelling `cancel` abandons the
+/// batches in flight (including their upserts) and marks the tally cancelled.
+/// The counters of a recorded `run` are updated as batches complete.
+pub async fn run_fetch(
+    ctx: &SchedulerContext,
+    window: &FetchWindow,
+    cancel: &CancellationToken,
+    mut run: Option<&mut SchedulerRun>,
+) -> RunTally {
+    let mut tally = RunTally::default();
+    let Some(all_images) = cancel
+        .run_until_cancelled(fetch_window_images(ctx, window, &mut tally))
+        .await
+    else {
+        tally.cancel();
+        return tally;
+    };
+    tally.fetched = all_images.len() as u64;
+    report_progress(ctx, run.as_deref_mut(), &tally);
+
+    if all_images.is_empty() {
+        warn!("No images were fetched from any CCTV");
+        return tally;
+    }
+
+    // Process all collected images
+    info!(images = tally.fetched, "Processing images");
+    let ctx = Arc::new(ctx.clone());
+    let mut images = all_images.into_iter();
+    let mut tasks = JoinSet::new();
+    loop {
+        while tasks.len() < technical::EMBEDDING_CONCURRENCY && !cancel.is_cancelled() {
+            let batch: Vec<_> = images
+                .by_ref()
+                .take(technical::EMBEDDING_BATCH_SIZE)
+                .collect();
+            if batch.is_empty() {
+                break;
+            }
+            tasks.spawn(
+                process_batch(ctx.clone(), batch, window.reingest, cancel.clone())
+                    .in_current_span(),
+            );
         }
-        Err(e) => {
-            println!("❌ Failed to get CCTV list: {}", e);
-            vec![]
+        let Some(result) = tasks.join_next().await else {
+            break;
+        };
+        match result {
+            Ok(batch_tally) => tally.merge(batch_tally),
+            Err(e) => tally.error(format!("Embedding task failed: {}", e)),
         }
+        report_progress(&ctx, run.as_deref_mut(), &tally);
+    }
+
+    if cancel.is_cancelled() {
+        tally.cancel();
+        info!(
+            inserted = tally.inserted,
+            failed = tally.failed,
+            "Fetch run cancelled"
+        );
+    } else {
+        info!(
+            skipped = tally.skipped,
+            inserted = tally.inserted,
+            failed = tally.failed,
+            "Scheduled task completed"
+        );
+    }
+    tally
+}
+
+/// Publish the counters so far of a recorded run
+fn report_progress(ctx: &SchedulerContext, run: Option<&mut SchedulerRun>, tally: &RunTally) {
+    if let Some(run) = run {
+        run.progress(tally);
+        ctx.run_history.update(run);
+    }
+}
+
+/// Embed and store one batch unless `cancel` fires first
+async fn process_batch(
+    ctx: Arc<SchedulerContext>,
+    images: Vec<CctvImageData>,
+    reingest: bool,
+    cancel: CancellationToken,
+) -> RunTally {
+    let mut tally = RunTally::default();
+    if cancel
+        .run_until_cancelled(process_images(&ctx, &images, reingest, &mut tally))
+        .await
+        .is_none()
+    {
+        tally.cancel();
+    }
+    tally
+}
+
+/// Fetch up to `window.limit` images per camera in `window`
+///
+/// Cameras that cannot be read are skipped and reported to `tally`.
+pub async fn fetch_window_images(
+    ctx: &SchedulerContext,
+    window: &FetchWindow,
+    tally: &mut RunTally,
+) -> Vec<CctvImageData> {
+    // An explicitly requested camera is fetched even if disabled
+    let (cctv_ids, registry) = match &window.cctv_id {
+        Some(cctv_id) => (vec![cctv_id.clone()], None),
+        None => (list_cameras(ctx, tally).await, load_registry(ctx)),
     };
 
     let mut all_images = Vec::new();
 
     // Fetch images from each CCTV
     for cctv_id in cctv_ids {
-        println!("📡 Fetching data from CCTV: {}", cctv_id);
+        if registry.as_ref().is_some_and(|r| !r.is_enabled(&cctv_id)) {
+            info!(cctv_id = %cctv_id, "Skipping disabled CCTV");
+            continue;
+        }
+
+        info!(cctv_id = %cctv_id, "Fetching data from CCTV");
 
         // Create request for training data
         let request = CctvMetadataRequest {
             cctv_id: cctv_id.clone(),
-            date_start: date_start.clone(),
-            date_stop: date_stop.clone(),
-            limit: ctx.config.fetch_limit,
+            date_start: window.date_start.clone(),
+            date_stop: window.date_stop.clone(),
+            limit: window.limit,
         };
 
         // Fetch images using the CCTV service
         match ctx.cctv_service.fetch_train_data(&request).await {
             Ok(images) => {
-                println!("   → Got {} images from CCTV: {}", images.len(), cctv_id);
+                info!(cctv_id = %cctv_id, images = images.len(), "Fetched images");
                 all_images.extend(images);
             }
             Err(e) => {
-                println!(
-                    "   ❌ Failed to fetch training data from CCTV {}: {}",
-                    cctv_id, e
-                );
+                error!(cctv_id = %cctv_id, error = %e, "Failed to fetch training data");
+                tally.error(format!("{}: failed to fetch training data: {}", cctv_id, e));
             }
         }
     }
 
-    // Process all collected images
-    if !all_images.is_empty() {
-        println!("📥 Processing {} total images...", all_images.len());
-        process_images(ctx, &all_images).await;
-        println!("✅ Scheduled task completed\n");
-    } else {
-        println!("⚠️  No images were fetched from any CCTV\n");
+    all_images
+}
+
+/// Camera toggles, re-read every run so edits apply without a restart
+fn load_registry(ctx: &SchedulerContext) -> Option<CameraRegistry> {
+    match CameraRegistry::load(&ctx.config.camera_registry_path, &ctx.config.cameras) {
+        Ok(r) => Some(r),
+        Err(e) => {
+            warn!(error = %e, "Camera registry unavailable");
+            None
+        }
+    }
+}
+
+/// IDs of every upstream camera; empty (and reported) if the list fails
+async fn list_cameras(ctx: &SchedulerContext, tally: &mut RunTally) -> Vec<String> {
+    match ctx.cctv_service.list_cctv().await {
+        Ok(ids) => {
+            info!(cameras = ids.len(), "Fetched CCTV list");
+            ids
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to get CCTV list");
+            tally.error(format!("Failed to get CCTV list: {}", e));
+            vec![]
+        }
     }
 }
 
 /// Process a batch of images using batch embedding
-async fn process_images(ctx: &SchedulerContext, images: &[CctvImageData]) {
+///
+/// Images are embedded by the model of their collection, one AI service
+/// call per model; see `embed_points` for retries.
+#[instrument(skip_all, fields(images = images.len()))]
+async fn process_images(
+    ctx: &SchedulerContext,
+    images: &[CctvImageData],
+    reingest: bool,
+    tally: &mut RunTally,
+) {
+    let images = skip_stored_images(ctx, images, reingest, tally).await;
     if images.is_empty() {
         return;
     }
 
-    println!(
-        "   🚀 Getting batch embeddings for {} images...",
-        images.len()
-    );
-
-    // Collect all image paths
-    let image_paths: Vec<String> = images.iter().map(|img| img.file_path.clone()).collect();
-
-    // Get batch embeddings
-    let batch_result = match get_image_embedding(
+    let hashes = if ctx.tunables.current().hash_images {
+        let image_paths: Vec<String> = images.iter().map(|img| img.file_path.clone()).collect();
+        hash_images(&ctx.http_client, &image_paths).await
+    } else {
+        HashMap::new()
+    };
+    let captions = generate_captions(
         &ctx.http_client,
         &ctx.config.ai_service_url,
-        image_paths.clone(),
+        ctx.config.caption_endpoint.as_deref(),
+        &images,
     )
-    .await
-    {
-        Ok(result) => result,
-        Err(e) => {
-            println!("   ❌ Failed to get batch embeddings: {}", e);
-            return;
+    .await;
+
+    let mut by_model: Vec<(&EmbeddingModel, Vec<&CctvImageData>)> = Vec::new();
+    for image in images {
+        let collection = ctx.router.collection_for(&image.cctv_id);
+        let model = ctx.embedding_models.for_collection(collection);
+        match by_model.iter_mut().find(|(m, _)| *m == model) {
+            Some((_, images)) => images.push(image),
+            None => by_model.push((model, vec![image])),
         }
-    };
+    }
 
-    println!(
-        "   ✅ Received {} embedding results",
-        batch_result.results.len()
-    );
+    let mut points = Vec::new();
+    for (model, images) in by_model {
+        let embedded = embed_points(ctx, model, images, &captions, &hashes, tally).await;
+        points.extend(embedded);
+    }
+    store_points(ctx, points, tally).await;
+}
 
-    // Process each result and store in Qdrant
-    for (idx, result) in batch_result.results.iter().enumerate() {
-        // Find the corresponding image data
-        let image = match images.iter().find(|img| img.file_path == result.path) {
-            Some(img) => img,
-            None => {
-                println!("   ⚠️  Could not find image data for path: {}", result.path);
+/// Embed `images` with `model` and build their points
+///
+/// Images that fail with a transient error are embedded again, up to
+/// `EMBEDDING_ATTEMPTS` times with a doubling delay; those still failing, and
+/// those rejected outright, are reported in the run's failures.
+async fn embed_points<'a>(
+    ctx: &'a SchedulerContext,
+    model: &EmbeddingModel,
+    images: Vec<&CctvImageData>,
+    captions: &HashMap<String, String>,
+    hashes: &HashMap<String, String>,
+    tally: &mut RunTally,
+) -> Vec<(&'a str, PointStruct)> {
+    let mut points = Vec::with_capacity(images.len());
+    let mut pending: Vec<(&CctvImageData, String)> = images
+        .into_iter()
+        .map(|image| (image, String::new()))
+        .collect();
+    let mut delay = std::time::Duration::from_secs(technical::EMBEDDING_RETRY_DELAY_SECS);
+    let mut attempts = 0;
+    let mut transient = true;
+
+    for attempt in 1..=technical::EMBEDDING_ATTEMPTS {
+        attempts = attempt;
+        if attempt > 1 {
+            warn!(
+                images = pending.len(),
+                attempt,
+                delay_secs = delay.as_secs(),
+                "Retrying failed embeddings"
+            );
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+            tally.retried += pending.len() as u64;
+        }
+
+        let batch: Vec<&CctvImageData> = pending.drain(..).map(|(image, _)| image).collect();
+        let paths: Vec<String> = batch.iter().map(|img| img.file_path.clone()).collect();
+        let batch_result = match get_image_embedding(
+            &ctx.http_client,
+            &model.ai_service_url,
+            paths,
+            AiPriority::Bulk,
+        )
+        .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!(error = %e, attempt, "Failed to get batch embeddings");
+                let message = format!("batch embedding failed: {}", e);
+                transient = e.is_dependency_failure() || matches!(e, AppError::Unavailable { .. });
+                pending = batch
+                    .into_iter()
+                    .map(|img| (img, message.clone()))
+                    .collect();
+                if !transient {
+                    break;
+                }
                 continue;
             }
         };
 
-        println!(
-            "   [{}/{}] Processing: {}",
-            idx + 1,
-            batch_result.results.len(),
-            image.filename
+        info!(
+            results = batch_result.results.len(),
+            attempt, "Received embedding results"
         );
 
-        // Check if this result has an error
-        if let Some(ref error) = result.error {
-            println!("      ❌ {}", error);
-            continue;
-        }
+        let mut results: HashMap<&str, _> = batch_result
+            .results
+            .iter()
+            .map(|result| (result.path.as_str(), result))
+            .collect();
+        let total = batch.len();
+        for (idx, image) in batch.into_iter().enumerate() {
+            debug!("[{}/{}] Processing: {}", idx + 1, total, image.filename);
 
-        // Check if embedding is present
-        let vector = match &result.embedding {
-            Some(v) => v.clone(),
-            None => {
-                println!("      ❌ No embedding in result");
+            let Some(result) = results.remove(image.file_path.as_str()) else {
+                warn!(filename = %image.filename, "No result for image");
+                pending.push((image, "no result for image".to_string()));
+                continue;
+            };
+
+            // Errors reported per image are retried, as are missing embeddings
+            if let Some(ref error) = result.error {
+                error!(filename = %image.filename, error = %error, "Embedding failed");
+                pending.push((image, format!("embedding failed: {}", error)));
                 continue;
             }
-        };
+            let vector = match &result.embedding {
+                Some(v) => v.clone(),
+                None => {
+                    error!(filename = %image.filename, "No embedding in result");
+                    pending.push((image, "no embedding in result".to_string()));
+                    continue;
+                }
+            };
 
-        // Build payload and store in Qdrant
-        if let Err(e) = store_image_in_qdrant(ctx, image, vector).await {
-            println!("      ❌ {}", e);
-        } else {
-            println!("      ✅ Inserted successfully");
+            // A defective embedding will not improve on retry
+            if let Err(e) = validate_embedding(
+                &vector,
+                model.vector_size,
+                "ingest",
+                &image.filename,
+                &ctx.metrics,
+            ) {
+                ctx.dead_letters
+                    .push(image, "scheduler", &e.to_string(), &ctx.metrics);
+                tally.image_failed(image_failure(image, e.to_string(), attempt, false));
+                continue;
+            }
+
+            let caption = image_caption(image, captions);
+            let caption_vector = caption_embedding(
+                ctx.config.vector_layout,
+                &ctx.http_client,
+                &model.ai_service_url,
+                caption,
+                model.vector_size,
+                &ctx.metrics,
+            )
+            .await;
+            let timezone = ctx
+                .cameras
+                .timezone(&image.cctv_id, ctx.config.cctv_source_timezone);
+            let point = image_point(
+                image,
+                ctx.config
+                    .vector_layout
+                    .point_vectors(vector, caption_vector),
+                caption,
+                hashes.get(&image.file_path),
+                &ctx.vehicle_types,
+                timezone,
+                ctx.cameras.tenant(&image.cctv_id),
+            )
+            .and_then(|mut point| {
+                ctx.config.payload_limits.enforce(
+                    &mut point.payload,
+                    &image.filename,
+                    &ctx.metrics,
+                )?;
+                Ok(point)
+            });
+            match point {
+                Ok(point) => points.push((ctx.router.collection_for(&image.cctv_id), point)),
+                Err(e) => {
+                    ctx.dead_letters
+                        .push(image, "scheduler", &e.to_string(), &ctx.metrics);
+                    tally.image_failed(image_failure(image, e.to_string(), attempt, false));
+                }
+            }
+        }
+
+        if pending.is_empty() {
+            break;
         }
     }
+
+    for (image, error) in pending {
+        tally.image_failed(image_failure(image, error, attempts, transient));
+    }
+    points
 }
 
-/// Store a single image with its embedding in Qdrant
-async fn store_image_in_qdrant(
+fn image_failure(
+    image: &CctvImageData,
+    error: String,
+    attempts: u32,
+    transient: bool,
+) -> ImageFailure {
+    ImageFailure {
+        id: image.id,
+        cctv_id: image.cctv_id.clone(),
+        filename: image.filename.clone(),
+        file_path: image.file_path.clone(),
+        error,
+        attempts,
+        transient,
+    }
+}
+
+/// Drop the images already stored by an earlier, overlapping run, unless
+/// the run re-ingests them
+///
+/// If the lookup fails every image is embedded, as before.
+async fn skip_stored_images<'a>(
     ctx: &SchedulerContext,
+    images: &'a [CctvImageData],
+    reingest: bool,
+    tally: &mut RunTally,
+) -> Vec<&'a CctvImageData> {
+    let images: Vec<&CctvImageData> = images.iter().collect();
+    let lookup = guarded(
+        Dependency::Qdrant,
+        stored_image_ids(&ctx.qdrant, &ctx.router, &images),
+    );
+    let stored = match lookup.await {
+        Ok(stored) => stored,
+        Err(e) => {
+            warn!(error = %e, "Skipped stored image check");
+            return images;
+        }
+    };
+
+    if !stored.is_empty() {
+        if reingest {
+            debug!(overwritten = stored.len(), "Re-ingesting stored images");
+        } else {
+            debug!(skipped = stored.len(), "Images already stored");
+            tally.skipped += stored.len() as u64;
+        }
+    }
+    images_to_embed(images, &stored, reingest)
+}
+
+/// `images` without those in `stored`, or all of them on a re-ingest
+fn images_to_embed<'a>(
+    images: Vec<&'a CctvImageData>,
+    stored: &HashSet<u64>,
+    reingest: bool,
+) -> Vec<&'a CctvImageData> {
+    images
+        .into_iter()
+        .filter(|image| reingest || !stored.contains(&(image.id as u64)))
+        .collect()
+}
+
+/// Build the Qdrant point for an image, its embeddings, caption, content hash
+/// and the tenant of its camera
+///
+/// The image `date`/`time` are local to `timezone` and stored in UTC.
+fn image_point(
     image: &CctvImageData,
-    vector: Vec<f32>,
-) -> Result<(), String> {
+    vectors: Vectors,
+    caption: Option<&str>,
+    content_sha256: Option<&String>,
+    vehicle_types: &VehicleTypes,
+    timezone: Tz,
+    tenant: Option<String>,
+) -> Result<PointStruct, AppError> {
     // Build payload using the builder
-    let datetime_rfc3339 = api_datetime_to_rfc3339(&image.date, &image.time);
+    let datetime_rfc3339 = api_datetime_to_rfc3339(&image.date, &image.time, timezone)?;
 
     // Use provided created_at or generate current timestamp
     let created_at = image
@@ -244,7 +909,17 @@ async fn store_image_in_qdrant(
         .integer("frame", image.frame as i64)
         .integer("vehicle_type", image.vehicle_type as i64)
         .integer("yolo_id", image.yolo_id as i64)
-        .string("created_at", &created_at);
+        .string_opt(
+            VEHICLE_TYPE_LABEL_FIELD,
+            vehicle_types.vehicle_type_label(image.vehicle_type),
+        )
+        .string_opt(YOLO_LABEL_FIELD, vehicle_types.yolo_label(image.yolo_id))
+        .string("created_at", &created_at)
+        .string_opt("caption", caption)
+        .string_opt(CAPTION_TERMS_FIELD, caption.and_then(caption_terms))
+        .string_opt(CONTENT_HASH_FIELD, content_sha256)
+        .string_opt(TENANT_FIELD, tenant)
+        .object(EXTRA_FIELD, &image.extra);
 
     // Add AI label if present
     if let Some(ai_label) = &image.ai_label {
@@ -253,22 +928,298 @@ async fn store_image_in_qdrant(
             .double("confidence", ai_label.confidence as f64);
     }
 
-    let payload_map = payload_builder.build();
+    Ok(PointStruct::new(
+        image.id as u64,
+        vectors,
+        payload_builder.build(),
+    ))
+}
+
+/// Upsert `points` (with their collection) in chunks of `UPSERT_BATCH_SIZE`
+///
+/// A failed chunk counts all of its images as failed; the other chunks are
+/// still stored.
+async fn store_points(
+    ctx: &SchedulerContext,
+    points: Vec<(&str, PointStruct)>,
+    tally: &mut RunTally,
+) {
+    let batch_size = ctx.tunables.current().upsert_batch_size;
+
+    let mut by_collection: Vec<(&str, Vec<PointStruct>)> = Vec::new();
+    for (collection_name, point) in points {
+        match by_collection
+            .iter_mut()
+            .find(|(c, _)| *c == collection_name)
+        {
+            Some((_, points)) => points.push(point),
+            None => by_collection.push((collection_name, vec![point])),
+        }
+    }
 
-    // Create and upsert point
-    let point = PointStruct::new(image.id as u64, vector, payload_map);
+    for (collection_name, points) in by_collection {
+        for chunk in points.chunks(batch_size) {
+            throttle_ingest(ctx, chunk.len()).await;
+            match upsert_chunk(ctx, collection_name, chunk).await {
+                Ok(mismatches) => {
+                    tally.inserted += (chunk.len() - mismatches.len()) as u64;
+                    tally.failed += mismatches.len() as u64;
+                    for mismatch in mismatches {
+                        tally.error(format!("Verification failed: {}", mismatch));
+                    }
+                }
+                Err(e) => {
+                    error!(collection = collection_name, points = chunk.len(), error = %e, "Failed to store images");
+                    tally.failed += chunk.len() as u64;
+                    tally.error(format!("{} images: failed to store: {}", chunk.len(), e));
+                }
+            }
+        }
+    }
+}
+
+/// Wait until `points` may be upserted at the ingest rate of the current hour
+async fn throttle_ingest(ctx: &SchedulerContext, points: usize) {
+    let hour = Utc::now()
+        .with_timezone(&ctx.config.cctv_source_timezone)
+        .hour();
+    let rate = ingest_rate(&ctx.tunables.current(), hour);
+    let waited = ctx.ingest_throttle.pace(points, rate).await;
+    if !waited.is_zero() {
+        debug!(
+            points,
+            points_per_sec = rate,
+            waited_ms = waited.as_millis() as u64,
+            "Ingest throttled"
+        );
+    }
+}
 
+/// Upsert one chunk of points, returning verification mismatches
+#[instrument(skip(ctx, points), fields(points = points.len()))]
+async fn upsert_chunk(
+    ctx: &SchedulerContext,
+    collection_name: &str,
+    points: &[PointStruct],
+) -> Result<Vec<String>, AppError> {
+    let tunables = ctx.tunables.current();
+    let settings = UpsertSettings::for_path(&tunables, IngestPath::Bulk, tunables.verify_upserts);
     let upsert = UpsertPoints {
-        collection_name: ctx.config.collection_name.clone(),
-        wait: Some(true),
-        points: vec![point],
+        collection_name: collection_name.to_string(),
+        wait: Some(settings.wait),
+        points: points.to_vec(),
+        ordering: settings.ordering,
         ..Default::default()
     };
 
-    ctx.qdrant
-        .upsert_points(upsert)
-        .await
-        .map_err(|e| format!("Failed to insert: {}", e))?;
+    guarded(Dependency::Qdrant, async {
+        inject(ChaosTarget::Qdrant).await?;
+        if let Err(e) =
+            detect_id_collisions(&ctx.qdrant, collection_name, points, &ctx.metrics).await
+        {
+            warn!(error = %e, "Skipped ID collision check");
+        }
+        ctx.qdrant
+            .upsert_points(upsert)
+            .await
+            .map_err(|e| AppError::Qdrant(format!("Failed to insert: {}", e)))
+    })
+    .await?;
+    debug!("Inserted successfully");
+    ctx.ingest_feed.publish(points);
+    if !ctx.alerts.is_empty() {
+        let ids = points.iter().filter_map(|p| p.id.clone()).collect();
+        tokio::spawn(
+            check_alerts(ctx.clone(), collection_name.to_string(), ids, settings.wait)
+                .in_current_span(),
+        );
+    }
+
+    if !tunables.verify_upserts {
+        return Ok(Vec::new());
+    }
+    verify_upsert(&ctx.qdrant, collection_name, points, &ctx.metrics).await
+}
 
-    Ok(())
+/// Search the saved alerts among the points `ids` just stored in
+/// `collection_name`, and notify their matches
+///
+/// Unwaited upserts are flushed first, so the new points are searchable.
+async fn check_alerts(
+    ctx: SchedulerContext,
+    collection_name: String,
+    ids: Vec<PointId>,
+    applied: bool,
+) {
+    if !applied && let Err(e) = flush_collection(&ctx.qdrant, &collection_name).await {
+        warn!(error = %e, "Skipped alerts, new points not applied");
+        return;
+    }
+    let model = ctx.embedding_models.for_collection(&collection_name);
+    let vector_name = ctx
+        .config
+        .vector_layout
+        .using(VectorSpace::Image)
+        .ok()
+        .flatten();
+    for (alert, vector) in ctx.alerts.watchlist(&model.name) {
+        // Alerts created before their collection's model was pinned
+        let vector = match vector {
+            Some(vector) => vector,
+            None => {
+                let embedded = get_text_embedding(
+                    &ctx.http_client,
+                    &model.ai_service_url,
+                    &alert.query,
+                    AiPriority::Bulk,
+                )
+                .await;
+                match embedded {
+                    Ok(vector) => {
+                        if let Err(e) =
+                            ctx.alerts
+                                .set_vector(&alert.alert_id, &model.name, vector.clone())
+                        {
+                            warn!(alert_id = %alert.alert_id, error = %e, "Failed to save alert");
+                        }
+                        vector
+                    }
+                    Err(e) => {
+                        warn!(alert_id = %alert.alert_id, error = %e, "Failed to embed alert");
+                        continue;
+                    }
+                }
+            }
+        };
+        let filter = match alert_filter(&alert, ids.clone()) {
+            Ok(filter) => filter,
+            Err(e) => {
+                warn!(alert_id = %alert.alert_id, error = %e, "Invalid alert filter");
+                continue;
+            }
+        };
+        let search = SearchPoints {
+            collection_name: collection_name.clone(),
+            vector,
+            vector_name: vector_name.clone(),
+            limit: ids.len() as u64,
+            with_payload: Some(true.into()),
+            filter: Some(filter),
+            score_threshold: Some(alert.min_score),
+            ..Default::default()
+        };
+        let hits = guarded(Dependency::Qdrant, async {
+            ctx.qdrant
+                .search_points(search)
+                .await
+                .map(|response| response.result)
+                .map_err(|e| AppError::Qdrant(format!("Alert search error: {}", e)))
+        })
+        .await;
+        match hits {
+            Ok(hits) if hits.is_empty() => {}
+            Ok(hits) => {
+                info!(alert_id = %alert.alert_id, hits = hits.len(), "Alert matched");
+                let notification = alert_notification(&alert, &hits);
+                ctx.alerts.publish(&notification);
+                let event = WebhookEvent::AlertMatched(notification);
+                if let Some(webhook_url) = &alert.webhook_url {
+                    ctx.webhooks.dispatch_to_alert(
+                        webhook_url,
+                        alert.webhook_secret.as_deref(),
+                        &event,
+                    );
+                }
+                ctx.webhooks.dispatch(&event);
+            }
+            Err(e) => warn!(alert_id = %alert.alert_id, error = %e, "Alert search failed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_reingest_keeps_stored_images() {
+        let image = |id: u32| -> CctvImageData {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "cctv_id": "cctv01",
+                "filename": format!("{}.jpg", id),
+                "file_path": format!("/data/{}.jpg", id),
+                "date": "2025-01-01",
+                "time": "08:00:00",
+                "frame": 0,
+                "vehicle_type": 1,
+                "yolo_id": 2
+            }))
+            .unwrap()
+        };
+        let images = [image(1), image(2), image(3)];
+        let stored = HashSet::from([1, 3]);
+        let ids = |reingest: bool| -> Vec<u32> {
+            images_to_embed(images.iter().collect(), &stored, reingest)
+                .iter()
+                .map(|image| image.id)
+                .collect()
+        };
+
+        assert_eq!(ids(false), [2]);
+        // Legacy points are overwritten, e.g. to correct their datetime
+        assert_eq!(ids(true), [1, 2, 3]);
+        assert!(!FetchWindow::last_minutes(10, 20).reingest);
+    }
+
+    #[test]
+    fn test_aligned_window() {
+        let bangkok = |d, h, m, s| {
+            Bangkok
+                .with_ymd_and_hms(2025, 6, d, h, m, s)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let now = bangkok(1, 0, 10, 7);
+
+        assert_eq!(
+            aligned_window(now, 10, WindowAlign::None),
+            (bangkok(1, 0, 0, 7), now)
+        );
+        assert_eq!(
+            aligned_window(now, 10, WindowAlign::Minute),
+            (bangkok(1, 0, 0, 0), bangkok(1, 0, 10, 0))
+        );
+        // 25-minute slots from the hour: :00, :25 and :50, cut short at :00
+        assert_eq!(
+            aligned_window(now, 25, WindowAlign::Hour),
+            (
+                bangkok(1, 0, 0, 0) - Duration::minutes(10),
+                bangkok(1, 0, 0, 0)
+            )
+        );
+        assert_eq!(
+            aligned_window(bangkok(1, 0, 30, 0), 25, WindowAlign::Hour),
+            (bangkok(1, 0, 0, 0), bangkok(1, 0, 25, 0))
+        );
+        // Daily windows run from Bangkok midnight to midnight
+        assert_eq!(
+            aligned_window(bangkok(2, 9, 0, 0), 24 * 60, WindowAlign::Day),
+            (bangkok(1, 0, 0, 0), bangkok(2, 0, 0, 0))
+        );
+        assert!(parse_window_align("Day").is_ok());
+        assert!(parse_window_align("week").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_gate_waits_for_runs_in_flight() {
+        let gate = RunGate::default();
+        let running = gate.enter().await.unwrap();
+        assert!(!gate.close(std::time::Duration::from_millis(10)).await);
+        assert!(gate.enter().await.is_none());
+
+        drop(running);
+        assert!(gate.close(std::time::Duration::from_millis(10)).await);
+    }
 }
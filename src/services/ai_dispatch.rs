@@ -0,0 +1,214 @@
+//! AI Service Dispatch
+//!
+//! Bounded number of AI service calls in flight, with queued calls admitted
+//! by priority so searches overtake scheduler and backfill batches.
+
+use crate::services::AiPriority;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::oneshot;
+use tracing::warn;
+use utoipa::ToSchema;
+
+/// Dispatcher state as reported by `/status`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AiDispatchStatus {
+    pub max_in_flight: usize,
+    pub in_flight: usize,
+    pub waiting_interactive: usize,
+    pub waiting_bulk: usize,
+    /// Interactive calls admitted in a row before a waiting bulk call goes next
+    pub bulk_every: u32,
+}
+
+#[derive(Debug, Default)]
+struct Queues {
+    in_flight: usize,
+    interactive: VecDeque<oneshot::Sender<()>>,
+    bulk: VecDeque<oneshot::Sender<()>>,
+    /// Interactive calls admitted since the last bulk call
+    interactive_streak: u32,
+}
+
+impl Queues {
+    /// Priority of the next waiter to admit, if any
+    ///
+    /// Interactive calls go first, except that a waiting bulk call is admitted
+    /// after `bulk_every` interactive ones so bulk work never starves.
+    fn next_priority(&mut self, bulk_every: u32) -> Option<AiPriority> {
+        let priority = match (self.interactive.is_empty(), self.bulk.is_empty()) {
+            (true, true) => return None,
+            (false, false) if self.interactive_streak >= bulk_every => AiPriority::Bulk,
+            (false, _) => AiPriority::Interactive,
+            (true, false) => AiPriority::Bulk,
+        };
+        match priority {
+            AiPriority::Interactive => self.interactive_streak += 1,
+            AiPriority::Bulk => self.interactive_streak = 0,
+        }
+        Some(priority)
+    }
+
+    fn queue(&mut self, priority: AiPriority) -> &mut VecDeque<oneshot::Sender<()>> {
+        match priority {
+            AiPriority::Interactive => &mut self.interactive,
+            AiPriority::Bulk => &mut self.bulk,
+        }
+    }
+}
+
+/// Admits at most `max_in_flight` AI service calls at once
+#[derive(Debug)]
+pub struct AiDispatcher {
+    max_in_flight: usize,
+    bulk_every: u32,
+    queues: Mutex<Queues>,
+}
+
+/// Slot for one AI service call; released on drop
+pub struct AiPermit {
+    dispatcher: &'static AiDispatcher,
+}
+
+impl Drop for AiPermit {
+    fn drop(&mut self) {
+        self.dispatcher.release();
+    }
+}
+
+static AI_DISPATCHER: OnceLock<AiDispatcher> = OnceLock::new();
+
+/// Install the process-wide dispatcher; without this, AI calls are not queued
+pub fn install_ai_dispatcher(dispatcher: AiDispatcher) {
+    if AI_DISPATCHER.set(dispatcher).is_err() {
+        warn!("AI dispatcher already installed");
+    }
+}
+
+/// Process-wide dispatcher, if configured
+pub fn ai_dispatcher() -> Option<&'static AiDispatcher> {
+    AI_DISPATCHER.get()
+}
+
+/// Wait for a slot of the process-wide dispatcher, if any
+pub async fn dispatch_ai_call(priority: AiPriority) -> Option<AiPermit> {
+    match ai_dispatcher() {
+        Some(dispatcher) => Some(dispatcher.acquire(priority).await),
+        None => None,
+    }
+}
+
+impl AiDispatcher {
+    pub fn new(max_in_flight: usize, bulk_every: u32) -> Self {
+        Self {
+            max_in_flight: max_in_flight.max(1),
+            bulk_every,
+            queues: Mutex::default(),
+        }
+    }
+
+    fn queues(&self) -> std::sync::MutexGuard<'_, Queues> {
+        self.queues.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Wait until the call may be sent
+    pub async fn acquire(&'static self, priority: AiPriority) -> AiPermit {
+        loop {
+            let receiver = {
+                let mut queues = self.queues();
+                if queues.in_flight < self.max_in_flight {
+                    queues.in_flight += 1;
+                    return AiPermit { dispatcher: self };
+                }
+                let (sender, receiver) = oneshot::channel();
+                queues.queue(priority).push_back(sender);
+                receiver
+            };
+
+            let mut waiter = Waiter {
+                dispatcher: self,
+                receiver,
+                admitted: false,
+            };
+            if (&mut waiter.receiver).await.is_ok() {
+                waiter.admitted = true;
+                return AiPermit { dispatcher: self };
+            }
+        }
+    }
+
+    /// Hand the released slot to the next waiter, or free it
+    fn release(&self) {
+        let mut queues = self.queues();
+        while let Some(priority) = queues.next_priority(self.bulk_every) {
+            let Some(sender) = queues.queue(priority).pop_front() else {
+                continue;
+            };
+            // A waiter that gave up no longer takes the slot
+            if sender.send(()).is_ok() {
+                return;
+            }
+        }
+        queues.in_flight -= 1;
+    }
+
+    pub fn status(&self) -> AiDispatchStatus {
+        let queues = self.queues();
+        AiDispatchStatus {
+            max_in_flight: self.max_in_flight,
+            in_flight: queues.in_flight,
+            waiting_interactive: queues.interactive.len(),
+            waiting_bulk: queues.bulk.len(),
+            bulk_every: self.bulk_every,
+        }
+    }
+}
+
+/// Queued call; releases a slot handed over after the call was abandoned
+struct Waiter {
+    dispatcher: &'static AiDispatcher,
+    receiver: oneshot::Receiver<()>,
+    admitted: bool,
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        if self.admitted {
+            return;
+        }
+        self.receiver.close();
+        if self.receiver.try_recv().is_ok() {
+            self.dispatcher.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bulk_calls_are_admitted_after_an_interactive_streak() {
+        let mut queues = Queues::default();
+        for _ in 0..3 {
+            queues.interactive.push_back(oneshot::channel().0);
+        }
+        queues.bulk.push_back(oneshot::channel().0);
+
+        let mut order = Vec::new();
+        while let Some(priority) = queues.next_priority(2) {
+            queues.queue(priority).pop_front();
+            order.push(priority);
+        }
+        assert_eq!(
+            order,
+            vec![
+                AiPriority::Interactive,
+                AiPriority::Interactive,
+                AiPriority::Bulk,
+                AiPriority::Interactive,
+            ]
+        );
+    }
+}
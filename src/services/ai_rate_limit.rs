@@ -0,0 +1,181 @@
+//! AI Service Rate Limit
+//!
+//! Process-wide token bucket on outbound AI service calls, shared by the
+//! handlers, scheduler and backfill. Part of the bucket is held back for
+//! interactive callers so bulk ingestion cannot starve searches.
+
+use crate::config::technical;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+use utoipa::ToSchema;
+
+/// Who an AI service call is made for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiPriority {
+    /// Searches and tool requests waiting on the answer
+    Interactive,
+    /// Scheduled fetches, backfills and insert endpoints
+    Bulk,
+}
+
+impl AiPriority {
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Rate limiter state as reported by `/status`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AiRateLimitStatus {
+    pub requests_per_sec: f64,
+    pub burst: u32,
+    /// Tokens only interactive calls may take
+    pub interactive_reserve: u32,
+    pub available_tokens: f64,
+    /// Interactive calls that had to wait for a token
+    pub throttled_interactive: u64,
+    /// Bulk calls that had to wait for a token
+    pub throttled_bulk: u64,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket refilled at `requests_per_sec` up to `burst` tokens
+#[derive(Debug)]
+pub struct AiRateLimiter {
+    requests_per_sec: f64,
+    burst: u32,
+    interactive_reserve: u32,
+    bucket: Mutex<Bucket>,
+    throttled: [AtomicU64; 2],
+}
+
+static AI_RATE_LIMITER: OnceLock<AiRateLimiter> = OnceLock::new();
+
+/// Install the process-wide limiter; without this, AI calls are not limited
+pub fn install_ai_rate_limiter(limiter: AiRateLimiter) {
+    if AI_RATE_LIMITER.set(limiter).is_err() {
+        warn!("AI rate limiter already installed");
+    }
+}
+
+/// Process-wide limiter, if configured
+pub fn ai_rate_limiter() -> Option<&'static AiRateLimiter> {
+    AI_RATE_LIMITER.get()
+}
+
+/// Wait for a token of the process-wide limiter, if any
+pub async fn throttle_ai_call(priority: AiPriority) {
+    if let Some(limiter) = ai_rate_limiter() {
+        limiter.acquire(priority).await;
+    }
+}
+
+impl AiRateLimiter {
+    /// Full bucket of `burst` tokens, refilled at `requests_per_sec`
+    pub fn new(requests_per_sec: f64, burst: u32) -> Self {
+        let burst = burst.max(1);
+        Self {
+            requests_per_sec,
+            burst,
+            interactive_reserve: (burst as f64 * technical::AI_RATE_INTERACTIVE_RESERVE) as u32,
+            bucket: Mutex::new(Bucket {
+                tokens: burst as f64,
+                updated: Instant::now(),
+            }),
+            throttled: Default::default(),
+        }
+    }
+
+    fn bucket(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        self.bucket.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn refill(&self, bucket: &mut Bucket, now: Instant) {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.requests_per_sec).min(self.burst as f64);
+        bucket.updated = now;
+    }
+
+    /// Take a token, or return how long to wait before trying again
+    ///
+    /// Bulk calls leave `interactive_reserve` tokens in the bucket.
+    pub fn try_acquire(&self, priority: AiPriority, now: Instant) -> Result<(), Duration> {
+        let mut bucket = self.bucket();
+        self.refill(&mut bucket, now);
+
+        let floor = match priority {
+            AiPriority::Interactive => 0.0,
+            AiPriority::Bulk => self.interactive_reserve as f64,
+        };
+        let missing = floor + 1.0 - bucket.tokens;
+        if missing <= 0.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64(missing / self.requests_per_sec))
+    }
+
+    /// Wait until a token is available for `priority` and take it
+    pub async fn acquire(&self, priority: AiPriority) {
+        let mut throttled = false;
+        while let Err(wait) = self.try_acquire(priority, Instant::now()) {
+            if !throttled {
+                throttled = true;
+                self.throttled[priority.index()].fetch_add(1, Ordering::Relaxed);
+                debug!(priority = ?priority, wait_ms = wait.as_millis() as u64, "AI call throttled");
+            }
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    pub fn status(&self) -> AiRateLimitStatus {
+        let tokens = {
+            let mut bucket = self.bucket();
+            self.refill(&mut bucket, Instant::now());
+            bucket.tokens
+        };
+        AiRateLimitStatus {
+            requests_per_sec: self.requests_per_sec,
+            burst: self.burst,
+            interactive_reserve: self.interactive_reserve,
+            available_tokens: tokens,
+            throttled_interactive: self.throttled[AiPriority::Interactive.index()]
+                .load(Ordering::Relaxed),
+            throttled_bulk: self.throttled[AiPriority::Bulk.index()].load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bulk_calls_leave_the_interactive_reserve() {
+        let limiter = AiRateLimiter::new(2.0, 4);
+        let start = Instant::now();
+
+        // Bulk calls stop at the reserve of 2 tokens
+        assert!(limiter.try_acquire(AiPriority::Bulk, start).is_ok());
+        assert!(limiter.try_acquire(AiPriority::Bulk, start).is_ok());
+        let wait = limiter.try_acquire(AiPriority::Bulk, start).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(500));
+
+        // Interactive calls may drain the bucket
+        assert!(limiter.try_acquire(AiPriority::Interactive, start).is_ok());
+        assert!(limiter.try_acquire(AiPriority::Interactive, start).is_ok());
+        assert!(limiter.try_acquire(AiPriority::Interactive, start).is_err());
+
+        // Refilled at 2 tokens per second
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.try_acquire(AiPriority::Interactive, later).is_ok());
+    }
+}
@@ -1,15 +1,41 @@
 //! AI Embedding Service
-//! 
+//!
 //! Functions to get text and image embeddings from the AI service.
 
-use crate::models::search::{EmbedResponse, BatchImageEmbeddingResponse};
+use crate::error::AppError;
+use crate::models::search::{
+    BatchImageCaptionResponse, BatchImageEmbeddingResponse, EmbedResponse,
+};
+use crate::services::{
+    AiPriority, ChaosTarget, Dependency, dispatch_ai_call, guarded, inject, throttle_ai_call,
+};
+use tracing::instrument;
 
-/// Get text embedding from AI service
+/// Get text embedding from AI service, waiting for a dispatch slot and the AI rate limit
+#[instrument(skip(client, text), fields(text_len = text.len()))]
 pub async fn get_text_embedding(
     client: &reqwest::Client,
     base_url: &str,
     text: &str,
-) -> Result<Vec<f32>, String> {
+    priority: AiPriority,
+) -> Result<Vec<f32>, AppError> {
+    let _permit = dispatch_ai_call(priority).await;
+    throttle_ai_call(priority).await;
+    guarded(
+        Dependency::AiService,
+        request_text_embedding(client, base_url, text),
+    )
+    .await
+}
+
+/// Get text embedding without the AI service circuit breaker and rate limit,
+/// for callers whose failures must not affect live traffic
+pub async fn request_text_embedding(
+    client: &reqwest::Client,
+    base_url: &str,
+    text: &str,
+) -> Result<Vec<f32>, AppError> {
+    inject(ChaosTarget::AiService).await?;
     let url = format!("{}/predict", base_url);
 
     let res = client
@@ -17,47 +43,90 @@ pub async fn get_text_embedding(
         .json(&serde_json::json!({ "text": text }))
         .send()
         .await
-        .map_err(|e| format!("Failed to connect to AI Service: {}", e))?;
+        .map_err(|e| AppError::AiService(format!("Failed to connect to AI Service: {}", e)))?;
 
     if !res.status().is_success() {
-        return Err(format!("AI Service returned error: {}", res.status()));
+        return Err(AppError::AiService(format!(
+            "AI Service returned error: {}",
+            res.status()
+        )));
     }
 
     let data: EmbedResponse = res.json().await.map_err(|e| {
-        format!(
+        AppError::AiService(format!(
             "Failed to parse AI response. Ensure Python returns 'vector' or 'embedding' key. Error: {}",
             e
-        )
+        ))
     })?;
 
     Ok(data.vector)
 }
 
+/// Embed a probe text and check that the AI service returns `expected`-dimensional vectors
+///
+/// A mismatch is a configuration error; an unreachable AI service is an AI service error.
+pub async fn verify_embedding_dimension(
+    client: &reqwest::Client,
+    base_url: &str,
+    expected: usize,
+) -> Result<(), AppError> {
+    let probe =
+        get_text_embedding(client, base_url, "dimension probe", AiPriority::Interactive).await?;
+    if probe.len() != expected {
+        return Err(AppError::Config(format!(
+            "AI service returned {}-dimensional vectors, but VECTOR_SIZE={}",
+            probe.len(),
+            expected
+        )));
+    }
+    Ok(())
+}
+
 /// Get image embedding(s) from AI service
-/// 
+///
 /// Supports both single and batch image embedding requests.
 /// Pass a single image path or multiple image paths in the vector.
-/// 
+/// A batch counts as one call against the AI rate limit.
+///
 /// # Examples
-/// 
+///
 /// Single image:
 /// ```
-/// let result = get_image_embedding(&client, &url, vec!["image.jpg".to_string()]).await?;
+/// let result = get_image_embedding(&client, &url, vec!["image.jpg".to_string()], AiPriority::Interactive).await?;
 /// ```
-/// 
+///
 /// Batch images:
 /// ```
-/// let result = get_image_embedding(&client, &url, vec!["img1.jpg".to_string(), "img2.jpg".to_string()]).await?;
+/// let result = get_image_embedding(&client, &url, vec!["img1.jpg".to_string(), "img2.jpg".to_string()], AiPriority::Bulk).await?;
 /// ```
+#[instrument(skip(client, image_paths), fields(images = image_paths.len()))]
 pub async fn get_image_embedding(
     client: &reqwest::Client,
     base_url: &str,
     image_paths: Vec<String>,
-) -> Result<BatchImageEmbeddingResponse, String> {
+    priority: AiPriority,
+) -> Result<BatchImageEmbeddingResponse, AppError> {
     if image_paths.is_empty() {
-        return Err("No image paths provided".to_string());
+        return Err(AppError::InvalidRequest(
+            "No image paths provided".to_string(),
+        ));
     }
 
+    let _permit = dispatch_ai_call(priority).await;
+    throttle_ai_call(priority).await;
+    guarded(
+        Dependency::AiService,
+        request_image_embedding(client, base_url, image_paths),
+    )
+    .await
+}
+
+async fn request_image_embedding(
+    client: &reqwest::Client,
+    base_url: &str,
+    image_paths: Vec<String>,
+) -> Result<BatchImageEmbeddingResponse, AppError> {
+    inject(ChaosTarget::AiService).await?;
     let url = format!("{}/predict", base_url);
 
     let res = client
@@ -65,16 +134,67 @@ pub async fn get_image_embedding(
         .json(&serde_json::json!({ "image_paths": image_paths }))
         .send()
         .await
-        .map_err(|e| format!("Failed to connect to AI Image Service: {}", e))?;
+        .map_err(|e| {
+            AppError::AiService(format!("Failed to connect to AI Image Service: {}", e))
+        })?;
 
     if !res.status().is_success() {
-        return Err(format!("AI Image Service returned error: {}", res.status()));
+        return Err(AppError::AiService(format!(
+            "AI Image Service returned error: {}",
+            res.status()
+        )));
     }
 
     let data: BatchImageEmbeddingResponse = res
         .json()
         .await
-        .map_err(|e| format!("Failed to parse AI image response: {}", e))?;
+        .map_err(|e| AppError::AiService(format!("Failed to parse AI image response: {}", e)))?;
 
     Ok(data)
 }
+
+/// Get captions of images from the AI service captioning `endpoint`
+///
+/// Like an image embedding batch, a batch counts as one call against the AI
+/// rate limit.
+#[instrument(skip(client, image_paths), fields(images = image_paths.len()))]
+pub async fn get_image_captions(
+    client: &reqwest::Client,
+    base_url: &str,
+    endpoint: &str,
+    image_paths: Vec<String>,
+    priority: AiPriority,
+) -> Result<BatchImageCaptionResponse, AppError> {
+    let _permit = dispatch_ai_call(priority).await;
+    throttle_ai_call(priority).await;
+    let request = request_image_captions(client, base_url, endpoint, image_paths);
+    guarded(Dependency::AiService, request).await
+}
+
+async fn request_image_captions(
+    client: &reqwest::Client,
+    base_url: &str,
+    endpoint: &str,
+    image_paths: Vec<String>,
+) -> Result<BatchImageCaptionResponse, AppError> {
+    inject(ChaosTarget::AiService).await?;
+    let url = format!("{}{}", base_url, endpoint);
+
+    let res = client
+        .post(&url)
+        .json(&serde_json::json!({ "image_paths": image_paths }))
+        .send()
+        .await
+        .map_err(|e| AppError::AiService(format!("Failed to connect to AI captioning: {}", e)))?;
+
+    if !res.status().is_success() {
+        return Err(AppError::AiService(format!(
+            "AI captioning returned error: {}",
+            res.status()
+        )));
+    }
+
+    res.json()
+        .await
+        .map_err(|e| AppError::AiService(format!("Failed to parse AI caption response: {}", e)))
+}
@@ -0,0 +1,341 @@
+//! Saved Search Alerts
+//!
+//! Persistent text queries with filters and a score threshold. After each
+//! batch the scheduler stores, the alerts are searched against only the new
+//! points, and matches are sent to the webhooks and to `GET /events`
+//! subscribers. Query embeddings are kept per embedding model, so collections
+//! pinned to another model are searched with a matching vector.
+
+use crate::error::AppError;
+use crate::models::alert::{Alert, AlertHit, AlertNotification, CreateAlertRequest};
+use crate::services::{TENANT_FIELD, extract_string, label_conditions};
+use chrono::Utc;
+use qdrant_client::qdrant::point_id::PointIdOptions;
+use qdrant_client::qdrant::{Condition, Filter, PointId, ScoredPoint};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tokio::sync::broadcast;
+
+/// Notifications a subscriber may fall behind by before it skips ahead
+const NOTIFICATION_CAPACITY: usize = 256;
+
+/// Alert as saved, with its query embedding by each model name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedAlert {
+    #[serde(flatten)]
+    alert: Alert,
+    vectors: BTreeMap<String, Vec<f32>>,
+}
+
+/// Alerts shared by the HTTP workers and the scheduler, mirrored to a JSON file
+#[derive(Debug)]
+pub struct AlertStore {
+    /// `None` keeps the alerts in memory only
+    path: Option<PathBuf>,
+    alerts: RwLock<Vec<SavedAlert>>,
+    matches: broadcast::Sender<AlertNotification>,
+}
+
+impl Default for AlertStore {
+    fn default() -> Self {
+        Self {
+            path: None,
+            alerts: RwLock::default(),
+            matches: broadcast::channel(NOTIFICATION_CAPACITY).0,
+        }
+    }
+}
+
+impl AlertStore {
+    /// Load the saved alerts from `path` (a missing file means no alerts)
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AppError> {
+        let path = path.as_ref().to_path_buf();
+        let alerts = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| {
+                AppError::Config(format!("Failed to parse alerts {}: {}", path.display(), e))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(AppError::Io(format!(
+                    "Failed to read alerts {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+        };
+        Ok(Self {
+            path: Some(path),
+            alerts: RwLock::new(alerts),
+            ..Default::default()
+        })
+    }
+
+    /// Save a new alert created by `user` of `tenant`, with its query
+    /// embedding by each model
+    pub fn create(
+        &self,
+        request: &CreateAlertRequest,
+        user: Option<String>,
+        tenant: Option<&str>,
+        vectors: BTreeMap<String, Vec<f32>>,
+    ) -> Result<Alert, AppError> {
+        let alert = Alert {
+            alert_id: format!("{:016x}", rand::random::<u64>()),
+            name: request.name.trim().to_string(),
+            query: request.query.clone(),
+            camera_ids: request.camera_ids.clone(),
+            vehicle_classes: request.vehicle_classes.clone(),
+            min_confidence: request.min_confidence,
+            min_score: request.min_score,
+            webhook_url: request.webhook_url.clone(),
+            webhook_secret: request.webhook_url.as_ref().map(|_| {
+                rand::random::<[u8; 32]>()
+                    .map(|byte| format!("{:02x}", byte))
+                    .concat()
+            }),
+            created_at: Utc::now().to_rfc3339(),
+            created_by: user,
+            tenant_id: tenant.map(str::to_string),
+        };
+        self.modify(|alerts| {
+            alerts.push(SavedAlert {
+                alert: alert.clone(),
+                vectors,
+            });
+            Ok(alert)
+        })
+    }
+
+    /// Every alert of `tenant` (all of them without tenancy), oldest first,
+    /// without webhook secrets
+    pub fn list(&self, tenant: Option<&str>) -> Vec<Alert> {
+        self.read()
+            .iter()
+            .filter(|saved| saved.alert.visible_to(tenant))
+            .map(|saved| saved.alert.without_secret())
+            .collect()
+    }
+
+    /// Alert of `tenant` without its webhook secret; those of other tenants
+    /// are unknown
+    pub fn get(&self, alert_id: &str, tenant: Option<&str>) -> Result<Alert, AppError> {
+        self.read()
+            .iter()
+            .find(|saved| saved.alert.alert_id == alert_id && saved.alert.visible_to(tenant))
+            .map(|saved| saved.alert.without_secret())
+            .ok_or_else(|| unknown_alert(alert_id))
+    }
+
+    pub fn delete(&self, alert_id: &str, tenant: Option<&str>) -> Result<(), AppError> {
+        self.modify(|alerts| {
+            let count = alerts.len();
+            alerts.retain(|saved| {
+                saved.alert.alert_id != alert_id || !saved.alert.visible_to(tenant)
+            });
+            if alerts.len() == count {
+                return Err(unknown_alert(alert_id));
+            }
+            Ok(())
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// Every alert with its query embedding by `model`, if already embedded
+    pub fn watchlist(&self, model: &str) -> Vec<(Alert, Option<Vec<f32>>)> {
+        self.read()
+            .iter()
+            .map(|saved| (saved.alert.clone(), saved.vectors.get(model).cloned()))
+            .collect()
+    }
+
+    /// Keep the query embedding of an alert by a model it wasn't embedded with yet
+    pub fn set_vector(
+        &self,
+        alert_id: &str,
+        model: &str,
+        vector: Vec<f32>,
+    ) -> Result<(), AppError> {
+        self.modify(|alerts| {
+            let saved = alerts
+                .iter_mut()
+                .find(|saved| saved.alert.alert_id == alert_id)
+                .ok_or_else(|| unknown_alert(alert_id))?;
+            saved.vectors.insert(model.to_string(), vector);
+            Ok(())
+        })
+    }
+
+    /// Notify `GET /events` subscribers of matches
+    pub fn publish(&self, notification: &AlertNotification) {
+        if self.matches.receiver_count() > 0 {
+            let _ = self.matches.send(notification.clone());
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<AlertNotification> {
+        self.matches.subscribe()
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Vec<SavedAlert>> {
+        self.alerts.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Apply `f` to a copy of the alerts and keep it only once it is saved
+    fn modify<T>(
+        &self,
+        f: impl FnOnce(&mut Vec<SavedAlert>) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let mut alerts = self.alerts.write().unwrap_or_else(|e| e.into_inner());
+        let mut updated = alerts.clone();
+        let result = f(&mut updated)?;
+        self.save(&updated)?;
+        *alerts = updated;
+        Ok(result)
+    }
+
+    /// Write the alerts to disk, atomically replacing the previous file
+    fn save(&self, alerts: &[SavedAlert]) -> Result<(), AppError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let content = serde_json::to_string_pretty(alerts)
+            .map_err(|e| AppError::Io(format!("Failed to serialize alerts: {}", e)))?;
+
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, content)
+            .and_then(|_| std::fs::rename(&tmp, path))
+            .map_err(|e| AppError::Io(format!("Failed to write alerts {}: {}", path.display(), e)))
+    }
+}
+
+impl Alert {
+    fn visible_to(&self, tenant: Option<&str>) -> bool {
+        tenant.is_none_or(|tenant| self.tenant_id.as_deref() == Some(tenant))
+    }
+
+    fn without_secret(&self) -> Alert {
+        Alert {
+            webhook_secret: None,
+            ..self.clone()
+        }
+    }
+}
+
+fn unknown_alert(alert_id: &str) -> AppError {
+    AppError::NotFound(format!("Unknown alert: {}", alert_id))
+}
+
+/// Filter restricting a search for `alert` to the points `ids`
+pub fn alert_filter(alert: &Alert, ids: Vec<PointId>) -> Result<Filter, AppError> {
+    let mut must = vec![Condition::has_id(ids)];
+    if let Some(camera_ids) = alert.camera_ids.as_ref().filter(|ids| !ids.is_empty()) {
+        must.push(Condition::matches("camera_id", camera_ids.clone()));
+    }
+    must.extend(label_conditions(
+        alert.vehicle_classes.as_deref(),
+        alert.min_confidence,
+    )?);
+    if let Some(tenant) = &alert.tenant_id {
+        must.push(Condition::matches(TENANT_FIELD, tenant.clone()));
+    }
+    Ok(Filter {
+        must,
+        ..Default::default()
+    })
+}
+
+/// Notification of the points matching `alert`
+pub fn alert_notification(alert: &Alert, points: &[ScoredPoint]) -> AlertNotification {
+    let hits = points
+        .iter()
+        .map(|point| {
+            let vehicle_class = extract_string(&point.payload, "vehicle_class");
+            AlertHit {
+                point_id: match point
+                    .id
+                    .as_ref()
+                    .and_then(|id| id.point_id_options.as_ref())
+                {
+                    Some(PointIdOptions::Num(n)) => *n,
+                    _ => 0,
+                },
+                camera_id: extract_string(&point.payload, "camera_id"),
+                filename: extract_string(&point.payload, "filename"),
+                datetime: extract_string(&point.payload, "datetime"),
+                vehicle_class: (!vehicle_class.is_empty()).then_some(vehicle_class),
+                score: point.score,
+            }
+        })
+        .collect();
+    AlertNotification {
+        alert_id: alert.alert_id.clone(),
+        name: alert.name.clone(),
+        query: alert.query.clone(),
+        tenant_id: alert.tenant_id.clone(),
+        hits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alerts_survive_reload() {
+        let path = std::env::temp_dir().join(format!("alerts-{:016x}.json", rand::random::<u64>()));
+        let store = AlertStore::load(&path).unwrap();
+        let request = CreateAlertRequest {
+            name: " Red pickups ".to_string(),
+            query: "red pickup truck".to_string(),
+            camera_ids: Some(vec!["cctv01".to_string()]),
+            vehicle_classes: None,
+            min_confidence: None,
+            min_score: 0.3,
+            webhook_url: Some("https://hooks.example.com/cctv".to_string()),
+        };
+        let vectors = BTreeMap::from([("siglip".to_string(), vec![0.5, 0.5])]);
+        let alert = store
+            .create(
+                &request,
+                Some("analyst-3".to_string()),
+                Some("site-a"),
+                vectors,
+            )
+            .unwrap();
+        assert_eq!(alert.webhook_secret.as_ref().map(String::len), Some(64));
+        store
+            .set_vector(&alert.alert_id, "clip", vec![1.0])
+            .unwrap();
+
+        let reloaded = AlertStore::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            reloaded.get(&alert.alert_id, None).unwrap().name,
+            "Red pickups"
+        );
+        assert!(reloaded.list(Some("site-b")).is_empty());
+        assert!(reloaded.get(&alert.alert_id, Some("site-b")).is_err());
+        // The secret is kept for signing but only shown on creation
+        assert_eq!(reloaded.list(None)[0].webhook_secret, None);
+        let watchlist = reloaded.watchlist("clip");
+        assert_eq!(watchlist[0].0.webhook_secret, alert.webhook_secret);
+        assert_eq!(watchlist[0].1, Some(vec![1.0]));
+        assert_eq!(reloaded.watchlist("other")[0].1, None);
+
+        let filter = alert_filter(&alert, vec![12345.into()]).unwrap();
+        assert_eq!(filter.must.len(), 3);
+
+        assert!(reloaded.delete(&alert.alert_id, Some("site-b")).is_err());
+        reloaded.delete(&alert.alert_id, Some("site-a")).unwrap();
+        assert!(reloaded.is_empty());
+        assert!(matches!(
+            reloaded.delete(&alert.alert_id, None),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
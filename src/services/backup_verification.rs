@@ -0,0 +1,248 @@
+//! Backup Verification
+//!
+//! Periodically restores the newest snapshot of every collection into a
+//! scratch collection and checks that it holds points and that its index
+//! finds them, since a snapshot that can't be restored is no backup.
+
+use crate::config::technical;
+use crate::error::AppError;
+use crate::models::admin::{BackupVerificationStatus, SnapshotCheck};
+use crate::services::{
+    RequestMetrics, ShardRouter, SnapshotRestorer, VectorLayout, dense_vector, list_snapshots,
+};
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{CountPointsBuilder, Query, QueryPointsBuilder, Sample};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{Instrument, error, info, info_span, instrument, warn};
+
+/// Verification job state shared with the admin endpoints
+#[derive(Default)]
+pub struct BackupVerifier {
+    status: RwLock<BackupVerificationStatus>,
+}
+
+impl BackupVerifier {
+    pub fn status(&self) -> BackupVerificationStatus {
+        self.status
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn update(&self, f: impl FnOnce(&mut BackupVerificationStatus)) {
+        f(&mut self.status.write().unwrap_or_else(|e| e.into_inner()));
+    }
+
+    /// Check the newest snapshot of every shard collection, probing its
+    /// image vectors
+    pub async fn run(
+        &self,
+        qdrant: &Qdrant,
+        restorer: &SnapshotRestorer,
+        router: &ShardRouter,
+        metrics: &RequestMetrics,
+        layout: VectorLayout,
+    ) {
+        self.update(|status| {
+            status.running = true;
+            status.started_at = Some(chrono::Utc::now().to_rfc3339());
+        });
+
+        let mut checks = Vec::new();
+        for collection in router.collections() {
+            let mut check = SnapshotCheck {
+                collection: collection.clone(),
+                ..Default::default()
+            };
+            let vector_name = layout.image_vector();
+            if let Err(e) = verify_collection(qdrant, restorer, vector_name, &mut check).await {
+                check.error = Some(e.to_string());
+            }
+            check.passed = check.error.is_none() && passed(&check);
+            checks.push(check);
+        }
+
+        let passed = checks.iter().all(|check| check.passed);
+        metrics.record_backup_verification(passed);
+        for check in &checks {
+            if check.passed {
+                info!(
+                    collection = %check.collection,
+                    snapshot = check.snapshot.as_deref().unwrap_or_default(),
+                    restored_points = check.restored_points,
+                    "Snapshot verified"
+                );
+            } else {
+                error!(
+                    collection = %check.collection,
+                    snapshot = check.snapshot.as_deref().unwrap_or("none"),
+                    restored_points = check.restored_points,
+                    probes_found = check.probes_found,
+                    probes = check.probes,
+                    error = check.error.as_deref().unwrap_or_default(),
+                    "Snapshot verification failed"
+                );
+            }
+        }
+
+        self.update(|status| {
+            status.running = false;
+            status.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            status.passed = Some(passed);
+            status.checks = checks;
+        });
+    }
+}
+
+/// A restored snapshot must hold points, and every probe must find itself
+fn passed(check: &SnapshotCheck) -> bool {
+    check.restored_points > 0 && check.probes > 0 && check.probes_found == check.probes
+}
+
+/// Restore the newest snapshot next to the collection, check it and drop it
+#[instrument(skip_all, fields(collection = %check.collection))]
+async fn verify_collection(
+    qdrant: &Qdrant,
+    restorer: &SnapshotRestorer,
+    vector_name: &str,
+    check: &mut SnapshotCheck,
+) -> Result<(), AppError> {
+    let collection = check.collection.clone();
+    let newest = list_snapshots(qdrant, std::slice::from_ref(&collection))
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::NotFound(format!("No snapshot of {}", collection)))?;
+    check.snapshot = Some(newest.name.clone());
+    check.live_points = count(qdrant, &collection).await?;
+
+    // A run interrupted by a restart leaves its scratch collection behind
+    let scratch = format!("{}{}", collection, technical::BACKUP_VERIFY_SUFFIX);
+    drop_scratch(qdrant, &scratch).await?;
+
+    let result = async {
+        restorer
+            .restore(&collection, &newest.name, &scratch)
+            .await?;
+        check.restored_points = count(qdrant, &scratch).await?;
+        probe(qdrant, &scratch, vector_name, check).await
+    }
+    .await;
+
+    if let Err(e) = drop_scratch(qdrant, &scratch).await {
+        warn!(collection = %scratch, error = %e, "Failed to drop the scratch collection");
+    }
+    result
+}
+
+async fn count(qdrant: &Qdrant, collection: &str) -> Result<u64, AppError> {
+    Ok(qdrant
+        .count(CountPointsBuilder::new(collection).exact(true))
+        .await
+        .map_err(|e| AppError::Qdrant(format!("Failed to count {}: {}", collection, e)))?
+        .result
+        .map(|r| r.count)
+        .unwrap_or_default())
+}
+
+/// Search for a random sample of points by their own `vector_name` vector
+async fn probe(
+    qdrant: &Qdrant,
+    collection: &str,
+    vector_name: &str,
+    check: &mut SnapshotCheck,
+) -> Result<(), AppError> {
+    let sample = qdrant
+        .query(
+            QueryPointsBuilder::new(collection)
+                .query(Query::new_sample(Sample::Random))
+                .limit(technical::BACKUP_VERIFY_PROBES as u64)
+                .with_vectors(true),
+        )
+        .await
+        .map_err(|e| AppError::Qdrant(format!("Failed to sample '{}': {}", collection, e)))?;
+
+    for point in sample.result {
+        let vector = dense_vector(point.vectors.as_ref(), vector_name);
+        let (Some(id), Some(vector)) = (point.id, vector) else {
+            continue;
+        };
+        check.probes += 1;
+        let mut query = QueryPointsBuilder::new(collection)
+            .query(vector)
+            .limit(technical::BACKUP_VERIFY_PROBE_TOP_K);
+        if !vector_name.is_empty() {
+            query = query.using(vector_name);
+        }
+        let hits = qdrant
+            .query(query)
+            .await
+            .map_err(|e| AppError::Qdrant(format!("Failed to search '{}': {}", collection, e)))?;
+        if hits.result.iter().any(|hit| hit.id.as_ref() == Some(&id)) {
+            check.probes_found += 1;
+        }
+    }
+    Ok(())
+}
+
+async fn drop_scratch(qdrant: &Qdrant, scratch: &str) -> Result<(), AppError> {
+    let exists = qdrant
+        .collection_exists(scratch)
+        .await
+        .map_err(|e| AppError::Qdrant(format!("Failed to look up {}: {}", scratch, e)))?;
+    if exists {
+        qdrant
+            .delete_collection(scratch)
+            .await
+            .map_err(|e| AppError::Qdrant(format!("Failed to drop {}: {}", scratch, e)))?;
+    }
+    Ok(())
+}
+
+/// Verify the newest snapshots every `every`, starting one period from now
+pub fn spawn_backup_verification(
+    verifier: Arc<BackupVerifier>,
+    qdrant: Arc<Qdrant>,
+    restorer: Arc<SnapshotRestorer>,
+    router: Arc<ShardRouter>,
+    metrics: Arc<RequestMetrics>,
+    layout: VectorLayout,
+    every: Duration,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + every, every);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            verifier
+                .run(&qdrant, &restorer, &router, &metrics, layout)
+                .instrument(info_span!("backup_verification"))
+                .await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_passes_only_when_every_probe_is_found() {
+        let mut check = SnapshotCheck {
+            restored_points: 1200,
+            probes: 5,
+            probes_found: 5,
+            ..Default::default()
+        };
+        assert!(passed(&check));
+
+        check.probes_found = 4;
+        assert!(!passed(&check));
+
+        // An empty restore has nothing to probe
+        let empty = SnapshotCheck::default();
+        assert!(!passed(&empty));
+    }
+}
@@ -0,0 +1,90 @@
+//! Browse Search
+//!
+//! Payload-only search used while the AI service is unavailable: the newest
+//! images matching the camera, datetime, class and keyword filters, without
+//! any similarity ranking.
+
+use crate::error::AppError;
+use crate::services::extract_string;
+use chrono::DateTime;
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{
+    Direction, Filter, OrderByBuilder, RetrievedPoint, ScoredPoint, ScrollPointsBuilder,
+};
+use tracing::instrument;
+
+/// Up to `limit` images matching `filter` across `collections`, newest first
+///
+/// The points carry a zero score, since nothing was compared to the query.
+#[instrument(skip(qdrant, collections, filter), fields(collections = collections.len()))]
+pub async fn browse_latest(
+    qdrant: &Qdrant,
+    collections: &[String],
+    filter: Option<Filter>,
+    limit: u32,
+) -> Result<Vec<ScoredPoint>, AppError> {
+    let mut points = Vec::new();
+    for collection in collections {
+        let mut scroll = ScrollPointsBuilder::new(collection)
+            .limit(limit)
+            .with_payload(true)
+            .with_vectors(false)
+            .order_by(OrderByBuilder::new("datetime").direction(Direction::Desc as i32));
+        if let Some(filter) = filter.clone() {
+            scroll = scroll.filter(filter);
+        }
+
+        let page = qdrant
+            .scroll(scroll)
+            .await
+            .map_err(|e| AppError::Qdrant(format!("Failed to browse '{}': {}", collection, e)))?;
+        points.extend(page.result.into_iter().map(unscored));
+    }
+    Ok(newest_first(points, limit as usize))
+}
+
+fn unscored(point: RetrievedPoint) -> ScoredPoint {
+    ScoredPoint {
+        id: point.id,
+        payload: point.payload,
+        score: 0.0,
+        ..Default::default()
+    }
+}
+
+/// Merge the pages of several collections, keeping the `limit` newest
+fn newest_first(mut points: Vec<ScoredPoint>, limit: usize) -> Vec<ScoredPoint> {
+    points.sort_by_cached_key(|point| {
+        let datetime = DateTime::parse_from_rfc3339(&extract_string(&point.payload, "datetime"));
+        std::cmp::Reverse(datetime.map(|dt| dt.timestamp()).ok())
+    });
+    points.truncate(limit);
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{PayloadBuilder, point_id_to_string};
+
+    #[test]
+    fn test_newest_first_across_collections() {
+        let hit = |id: u64, datetime: &str| ScoredPoint {
+            id: Some(id.into()),
+            payload: PayloadBuilder::new().string("datetime", datetime).build(),
+            ..Default::default()
+        };
+        let points = vec![
+            hit(1, "2025-10-08T06:00:00Z"),
+            hit(2, "2025-10-08T08:00:00Z"),
+            hit(3, "not a date"),
+            // 07:00 UTC, between the other two
+            hit(4, "2025-10-08T14:00:00+07:00"),
+        ];
+        let ids: Vec<String> = newest_first(points, 3)
+            .iter()
+            .filter_map(|p| p.id.as_ref().map(point_id_to_string))
+            .collect();
+        assert_eq!(ids, ["2", "4", "1"]);
+    }
+}
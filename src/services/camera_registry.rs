@@ -0,0 +1,263 @@
+//! Camera Registry
+//!
+//! Local JSON registry of known CCTV cameras and their settings, under the
+//! cameras of the configuration file.
+
+use crate::error::AppError;
+use crate::models::cctv::CameraEntry;
+use chrono_tz::Tz;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+use tracing::warn;
+
+/// Camera registry persisted as a JSON array on disk
+pub struct CameraRegistry {
+    path: PathBuf,
+    cameras: Vec<CameraEntry>,
+    /// Cameras of the configuration file; they replace registry entries of
+    /// the same ID and are never saved
+    configured: Vec<CameraEntry>,
+}
+
+impl CameraRegistry {
+    /// Load the registry from `path` (a missing file yields an empty registry)
+    /// under the `configured` cameras
+    pub fn load(path: impl AsRef<Path>, configured: &[CameraEntry]) -> Result<Self, AppError> {
+        let path = path.as_ref().to_path_buf();
+
+        let cameras = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| {
+                AppError::Config(format!(
+                    "Failed to parse camera registry {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(AppError::Io(format!(
+                    "Failed to read camera registry {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+        };
+
+        Ok(Self {
+            path,
+            cameras,
+            configured: configured.to_vec(),
+        })
+    }
+
+    /// Write the registry back to disk
+    pub fn save(&self) -> Result<(), AppError> {
+        let content = serde_json::to_string_pretty(&self.cameras)
+            .map_err(|e| AppError::Io(format!("Failed to serialize camera registry: {}", e)))?;
+
+        std::fs::write(&self.path, content).map_err(|e| {
+            AppError::Io(format!(
+                "Failed to write camera registry {}: {}",
+                self.path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Add cameras that are not registered yet, returning how many were added
+    pub fn register<I, S>(&mut self, cctv_ids: I) -> usize
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut added = 0;
+        for cctv_id in cctv_ids {
+            let cctv_id = cctv_id.into();
+            if self.get(&cctv_id).is_none() {
+                self.cameras.push(CameraEntry {
+                    cctv_id,
+                    enabled: true,
+                    name: None,
+                    location: None,
+                    lat: None,
+                    lon: None,
+                    timezone: None,
+                    tenant_id: None,
+                });
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Look up a camera by ID
+    pub fn get(&self, cctv_id: &str) -> Option<&CameraEntry> {
+        self.configured
+            .iter()
+            .chain(&self.cameras)
+            .find(|c| c.cctv_id == cctv_id)
+    }
+
+    /// Whether a camera should be ingested (unregistered cameras are enabled)
+    pub fn is_enabled(&self, cctv_id: &str) -> bool {
+        self.get(cctv_id).is_none_or(|c| c.enabled)
+    }
+
+    /// All registered and configured cameras
+    pub fn cameras(&self) -> Vec<&CameraEntry> {
+        let registered = self.cameras.iter().filter(|camera| {
+            !self
+                .configured
+                .iter()
+                .any(|configured| configured.cctv_id == camera.cctv_id)
+        });
+        self.configured.iter().chain(registered).collect()
+    }
+}
+
+/// Registered cameras by ID, for annotating search results; re-read
+/// whenever the registry file changes
+pub struct CameraDirectory {
+    path: PathBuf,
+    configured: Vec<CameraEntry>,
+    cached: RwLock<CachedCameras>,
+}
+
+#[derive(Default)]
+struct CachedCameras {
+    /// Modification time of the loaded file (`None` = missing), `None` before
+    /// the first load
+    modified: Option<Option<SystemTime>>,
+    cameras: Arc<HashMap<String, CameraEntry>>,
+}
+
+impl CameraDirectory {
+    pub fn new(path: impl AsRef<Path>, configured: &[CameraEntry]) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            configured: configured.to_vec(),
+            cached: RwLock::default(),
+        }
+    }
+
+    /// Current cameras by ID; an unreadable registry keeps the last good copy
+    pub fn cameras(&self) -> Arc<HashMap<String, CameraEntry>> {
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .ok();
+        {
+            let cached = self.cached.read().unwrap_or_else(|e| e.into_inner());
+            if cached.modified == Some(modified) {
+                return cached.cameras.clone();
+            }
+        }
+
+        let mut cached = self.cached.write().unwrap_or_else(|e| e.into_inner());
+        match CameraRegistry::load(&self.path, &self.configured) {
+            Ok(registry) => {
+                let cameras = registry.cameras();
+                for camera in &cameras {
+                    if let Some(timezone) = &camera.timezone
+                        && timezone.parse::<Tz>().is_err()
+                    {
+                        warn!(
+                            cctv_id = %camera.cctv_id,
+                            timezone = %timezone,
+                            "Unknown camera timezone, using CCTV_SOURCE_TIMEZONE"
+                        );
+                    }
+                }
+                cached.cameras = Arc::new(
+                    cameras
+                        .into_iter()
+                        .map(|camera| (camera.cctv_id.clone(), camera.clone()))
+                        .collect(),
+                );
+            }
+            Err(e) => warn!(error = %e, "Camera registry unavailable, keeping last copy"),
+        }
+        cached.modified = Some(modified);
+        cached.cameras.clone()
+    }
+
+    /// Timezone of the local times `cctv_id` reports, or `default` if the
+    /// registry sets none (or an unknown one)
+    pub fn timezone(&self, cctv_id: &str, default: Tz) -> Tz {
+        self.cameras()
+            .get(cctv_id)
+            .and_then(|camera| camera.timezone.as_deref()?.parse().ok())
+            .unwrap_or(default)
+    }
+
+    /// Tenant `cctv_id` is registered to, if any
+    pub fn tenant(&self, cctv_id: &str) -> Option<String> {
+        self.cameras().get(cctv_id)?.tenant_id.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_skips_known_cameras() {
+        let mut registry = CameraRegistry {
+            path: PathBuf::from("unused.json"),
+            cameras: Vec::new(),
+            configured: Vec::new(),
+        };
+
+        assert_eq!(registry.register(["cctv01", "cctv02"]), 2);
+        assert_eq!(registry.register(["cctv02", "cctv03"]), 1);
+        assert_eq!(registry.cameras().len(), 3);
+        assert!(registry.get("cctv03").is_some_and(|c| c.enabled));
+
+        registry.cameras[0].enabled = false;
+        assert!(!registry.is_enabled("cctv01"));
+        assert!(registry.is_enabled("unknown"));
+    }
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        let registry = CameraRegistry::load("/nonexistent/cameras.json", &[]).unwrap();
+        assert!(registry.cameras().is_empty());
+    }
+
+    #[test]
+    fn test_configured_cameras_replace_registered() {
+        let camera = |cctv_id: &str, name: &str| CameraEntry {
+            cctv_id: cctv_id.to_string(),
+            enabled: true,
+            name: Some(name.to_string()),
+            location: None,
+            lat: None,
+            lon: None,
+            timezone: None,
+            tenant_id: None,
+        };
+        let mut registry = CameraRegistry {
+            path: PathBuf::from("unused.json"),
+            cameras: vec![
+                camera("cctv01", "Registered"),
+                camera("cctv02", "Registered"),
+            ],
+            configured: vec![camera("cctv01", "Configured")],
+        };
+
+        assert_eq!(registry.cameras().len(), 2);
+        assert_eq!(
+            registry.get("cctv01").and_then(|c| c.name.as_deref()),
+            Some("Configured")
+        );
+        assert_eq!(registry.register(["cctv01", "cctv03"]), 1);
+        assert_eq!(registry.cameras.len(), 3);
+
+        let directory = CameraDirectory::new("/nonexistent/cameras.json", &registry.configured);
+        assert_eq!(
+            directory.cameras()["cctv01"].name.as_deref(),
+            Some("Configured")
+        );
+    }
+}
@@ -0,0 +1,227 @@
+//! Investigator Cases
+//!
+//! Cases collecting the searches, bookmarks, incidents and exports of an
+//! investigation, with who attached what and when. Every change rewrites
+//! the cases file, so cases survive restarts.
+
+use crate::error::AppError;
+use crate::models::case::{AttachCaseItemRequest, Case, CaseItem, CaseSummary, CreateCaseRequest};
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Cases shared by the HTTP workers, mirrored to a JSON file
+pub struct CaseStore {
+    path: PathBuf,
+    cases: RwLock<Vec<Case>>,
+}
+
+impl CaseStore {
+    /// Load the saved cases from `path` (a missing file means no cases)
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AppError> {
+        let path = path.as_ref().to_path_buf();
+
+        let cases = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| {
+                AppError::Config(format!("Failed to parse cases {}: {}", path.display(), e))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(AppError::Io(format!(
+                    "Failed to read cases {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+        };
+
+        Ok(Self {
+            path,
+            cases: RwLock::new(cases),
+        })
+    }
+
+    /// Open a new case attributed to `user` of `tenant`
+    pub fn create(
+        &self,
+        request: &CreateCaseRequest,
+        user: Option<String>,
+        tenant: Option<&str>,
+    ) -> Result<Case, AppError> {
+        let now = Utc::now().to_rfc3339();
+        let case = Case {
+            case_id: format!("{:016x}", rand::random::<u64>()),
+            title: request.title.trim().to_string(),
+            description: request.description.clone(),
+            created_at: now.clone(),
+            created_by: user,
+            updated_at: now,
+            tenant_id: tenant.map(str::to_string),
+            items: Vec::new(),
+        };
+        self.modify(|cases| {
+            cases.push(case.clone());
+            Ok(case)
+        })
+    }
+
+    /// Every case of `tenant` (all of them without tenancy) without its
+    /// items, most recently updated first
+    pub fn list(&self, tenant: Option<&str>) -> Vec<CaseSummary> {
+        let cases = self.cases.read().unwrap_or_else(|e| e.into_inner());
+        let mut summaries: Vec<CaseSummary> = cases
+            .iter()
+            .filter(|case| case.visible_to(tenant))
+            .map(|case| CaseSummary {
+                case_id: case.case_id.clone(),
+                title: case.title.clone(),
+                created_at: case.created_at.clone(),
+                created_by: case.created_by.clone(),
+                updated_at: case.updated_at.clone(),
+                items: case.items.len(),
+            })
+            .collect();
+        // RFC 3339 in UTC sorts chronologically
+        summaries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        summaries
+    }
+
+    /// Case of `tenant`; those of other tenants are unknown
+    pub fn get(&self, case_id: &str, tenant: Option<&str>) -> Result<Case, AppError> {
+        self.cases
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|case| case.case_id == case_id && case.visible_to(tenant))
+            .cloned()
+            .ok_or_else(|| unknown_case(case_id))
+    }
+
+    /// Attach an item to a case of `tenant` on behalf of `user`
+    pub fn attach(
+        &self,
+        case_id: &str,
+        request: &AttachCaseItemRequest,
+        user: Option<String>,
+        tenant: Option<&str>,
+    ) -> Result<CaseItem, AppError> {
+        self.modify(|cases| {
+            let case = cases
+                .iter_mut()
+                .find(|case| case.case_id == case_id && case.visible_to(tenant))
+                .ok_or_else(|| unknown_case(case_id))?;
+            let item = CaseItem {
+                item_id: case.items.len() as u64 + 1,
+                kind: request.kind,
+                reference: request.reference.trim().to_string(),
+                note: request.note.clone(),
+                details: request.details.clone(),
+                added_at: Utc::now().to_rfc3339(),
+                added_by: user,
+            };
+            case.updated_at = item.added_at.clone();
+            case.items.push(item.clone());
+            Ok(item)
+        })
+    }
+
+    /// Apply `f` to a copy of the cases and keep it only once it is saved
+    fn modify<T>(
+        &self,
+        f: impl FnOnce(&mut Vec<Case>) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let mut cases = self.cases.write().unwrap_or_else(|e| e.into_inner());
+        let mut updated = cases.clone();
+        let result = f(&mut updated)?;
+        self.save(&updated)?;
+        *cases = updated;
+        Ok(result)
+    }
+
+    /// Write the cases to disk, atomically replacing the previous file
+    fn save(&self, cases: &[Case]) -> Result<(), AppError> {
+        let content = serde_json::to_string_pretty(cases)
+            .map_err(|e| AppError::Io(format!("Failed to serialize cases: {}", e)))?;
+
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, content)
+            .and_then(|_| std::fs::rename(&tmp, &self.path))
+            .map_err(|e| {
+                AppError::Io(format!(
+                    "Failed to write cases {}: {}",
+                    self.path.display(),
+                    e
+                ))
+            })
+    }
+}
+
+impl Case {
+    fn visible_to(&self, tenant: Option<&str>) -> bool {
+        tenant.is_none_or(|tenant| self.tenant_id.as_deref() == Some(tenant))
+    }
+}
+
+fn unknown_case(case_id: &str) -> AppError {
+    AppError::NotFound(format!("Unknown case: {}", case_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::case::CaseItemKind;
+
+    #[test]
+    fn test_cases_survive_reload() {
+        let path = std::env::temp_dir().join(format!("cases-{:016x}.json", rand::random::<u64>()));
+        let store = CaseStore::load(&path).unwrap();
+        let case = store
+            .create(
+                &CreateCaseRequest {
+                    title: " Hit and run, route 12 ".to_string(),
+                    description: None,
+                },
+                Some("investigator-7".to_string()),
+                Some("site-a"),
+            )
+            .unwrap();
+        let item = store
+            .attach(
+                &case.case_id,
+                &AttachCaseItemRequest {
+                    kind: CaseItemKind::Bookmark,
+                    reference: "1234".to_string(),
+                    note: Some("Plate readable".to_string()),
+                    details: None,
+                },
+                Some("investigator-9".to_string()),
+                Some("site-a"),
+            )
+            .unwrap();
+        assert_eq!(item.item_id, 1);
+        assert!(matches!(
+            store.get("missing", None),
+            Err(AppError::NotFound(_))
+        ));
+        // Cases of other tenants are unknown
+        assert!(matches!(
+            store.get(&case.case_id, Some("site-b")),
+            Err(AppError::NotFound(_))
+        ));
+        assert!(store.list(Some("site-b")).is_empty());
+        assert_eq!(store.list(None).len(), 1);
+
+        let reloaded = CaseStore::load(&path)
+            .unwrap()
+            .get(&case.case_id, Some("site-a"))
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(reloaded.title, "Hit and run, route 12");
+        assert_eq!(reloaded.created_by.as_deref(), Some("investigator-7"));
+        assert_eq!(
+            reloaded.items[0].added_by.as_deref(),
+            Some("investigator-9")
+        );
+        assert_eq!(reloaded.updated_at, reloaded.items[0].added_at);
+    }
+}
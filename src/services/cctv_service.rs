@@ -1,8 +1,8 @@
-use std::io::Error;
-
 use crate::clients::cctv_client::CctvApiClient;
+use crate::error::AppError;
 use crate::models::cctv::CctvListResponse;
 use crate::models::search::{CctvImageData, CctvMetadataRequest, CctvMetadataResponse};
+use crate::services::{Dependency, guarded};
 
 pub struct CctvService<T: CctvApiClient> {
     client: T,
@@ -21,14 +21,14 @@ impl<T: CctvApiClient> CctvService<T> {
         Self { client }
     }
 
-    pub async fn list_cctv(&self) -> Result<Vec<String>, Error> {
+    pub async fn list_cctv(&self) -> Result<Vec<String>, AppError> {
+        guarded(Dependency::CctvApi, self.request_list_cctv()).await
+    }
+
+    async fn request_list_cctv(&self) -> Result<Vec<String>, AppError> {
         let url = format!("{}/video-metadata/list-cctv", self.client.base_url());
 
-        let auth_header = self
-            .client
-            .auth_header()
-            .await
-            .map_err(|e| Error::new(std::io::ErrorKind::Other, e))?;
+        let auth_header = self.client.auth_header().await.map_err(cctv_error)?;
 
         let response = self
             .client
@@ -37,12 +37,12 @@ impl<T: CctvApiClient> CctvService<T> {
             .header("Authorization", auth_header)
             .send()
             .await
-            .map_err(|e| Error::new(std::io::ErrorKind::Other, e))?;
+            .map_err(cctv_error)?;
 
         let resp = response
             .json::<CctvListResponse>()
             .await
-            .map_err(|e| Error::new(std::io::ErrorKind::Other, e))?;
+            .map_err(cctv_error)?;
 
         Ok(resp.data.into_iter().map(|c| c.cctv_id).collect())
     }
@@ -50,17 +50,20 @@ impl<T: CctvApiClient> CctvService<T> {
     pub async fn fetch_train_data(
         &self,
         request_body: &CctvMetadataRequest,
-    ) -> Result<Vec<CctvImageData>, Error> {
+    ) -> Result<Vec<CctvImageData>, AppError> {
+        guarded(Dependency::CctvApi, self.request_train_data(request_body)).await
+    }
+
+    async fn request_train_data(
+        &self,
+        request_body: &CctvMetadataRequest,
+    ) -> Result<Vec<CctvImageData>, AppError> {
         let url = format!(
             "{}/video-metadata/train-data-condition",
             self.client.base_url()
         );
 
-        let auth_header = self
-            .client
-            .auth_header()
-            .await
-            .map_err(|e| Error::new(std::io::ErrorKind::Other, e))?;
+        let auth_header = self.client.auth_header().await.map_err(cctv_error)?;
 
         let response = self
             .client
@@ -70,20 +73,21 @@ impl<T: CctvApiClient> CctvService<T> {
             .json(request_body)
             .send()
             .await
-            .map_err(|e| Error::new(std::io::ErrorKind::Other, e))?;
+            .map_err(cctv_error)?;
 
         let response_data = response
             .json::<CctvMetadataResponse>()
             .await
-            .map_err(|e| Error::new(std::io::ErrorKind::Other, e))?;
+            .map_err(cctv_error)?;
 
         if !response_data.success {
-            return Err(Error::new(
-                std::io::ErrorKind::Other,
-                "API returned success=false",
-            ));
+            return Err(AppError::CctvApi("API returned success=false".to_string()));
         }
 
         Ok(response_data.data)
     }
 }
+
+fn cctv_error(e: impl std::fmt::Display) -> AppError {
+    AppError::CctvApi(format!("CCTV API error: {}", e))
+}
@@ -0,0 +1,93 @@
+//! Chaos Injection
+//!
+//! Artificial latency and failures for external dependency calls, used to
+//! rehearse degradation behavior in staging. Only active in builds with the
+//! `chaos` Cargo feature; otherwise `inject` compiles to a no-op.
+
+use crate::error::AppError;
+
+/// External dependency a fault can be injected into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "chaos", derive(serde::Deserialize, utoipa::ToSchema))]
+#[cfg_attr(feature = "chaos", serde(rename_all = "snake_case"))]
+pub enum ChaosTarget {
+    AiService,
+    Qdrant,
+}
+
+/// Apply configured chaos for `target` before calling it
+#[cfg(not(feature = "chaos"))]
+#[inline(always)]
+pub async fn inject(_target: ChaosTarget) -> Result<(), AppError> {
+    Ok(())
+}
+
+#[cfg(feature = "chaos")]
+pub use enabled::*;
+
+#[cfg(feature = "chaos")]
+mod enabled {
+    use super::{AppError, ChaosTarget};
+    use crate::models::chaos::ChaosSettings;
+    use std::sync::RwLock;
+
+    static AI_SERVICE: RwLock<ChaosSettings> = RwLock::new(ChaosSettings::NONE);
+    static QDRANT: RwLock<ChaosSettings> = RwLock::new(ChaosSettings::NONE);
+
+    fn slot(target: ChaosTarget) -> &'static RwLock<ChaosSettings> {
+        match target {
+            ChaosTarget::AiService => &AI_SERVICE,
+            ChaosTarget::Qdrant => &QDRANT,
+        }
+    }
+
+    /// Replace the chaos settings for `target`
+    pub fn set_chaos(target: ChaosTarget, settings: ChaosSettings) {
+        *slot(target).write().unwrap_or_else(|e| e.into_inner()) = settings;
+    }
+
+    /// Current chaos settings for `target`
+    pub fn chaos_settings(target: ChaosTarget) -> ChaosSettings {
+        *slot(target).read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Apply configured chaos for `target` before calling it
+    pub async fn inject(target: ChaosTarget) -> Result<(), AppError> {
+        let settings = chaos_settings(target);
+
+        if settings.latency_ms > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(settings.latency_ms)).await;
+        }
+
+        if settings.failure_rate > 0.0 && rand::random::<f32>() < settings.failure_rate {
+            let message = format!("Chaos: injected {:?} failure", target);
+            return Err(match target {
+                ChaosTarget::AiService => AppError::AiService(message),
+                ChaosTarget::Qdrant => AppError::Qdrant(message),
+            });
+        }
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_inject_failure() {
+            set_chaos(
+                ChaosTarget::AiService,
+                ChaosSettings {
+                    latency_ms: 0,
+                    failure_rate: 1.0,
+                },
+            );
+            assert!(inject(ChaosTarget::AiService).await.is_err());
+            assert!(inject(ChaosTarget::Qdrant).await.is_ok());
+
+            set_chaos(ChaosTarget::AiService, ChaosSettings::NONE);
+            assert!(inject(ChaosTarget::AiService).await.is_ok());
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Circuit Breakers
+//!
+//! Per-dependency breakers that open after consecutive failures and fail
+//! calls fast with 503 until a cool-down passes, so requests don't pile up
+//! behind a dependency that is down.
+
+use crate::error::AppError;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+/// External dependency guarded by a breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Dependency {
+    AiService,
+    CctvApi,
+    Qdrant,
+}
+
+impl Dependency {
+    pub const ALL: [Dependency; 3] = [
+        Dependency::AiService,
+        Dependency::CctvApi,
+        Dependency::Qdrant,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Breaker state as reported by `/status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls pass through
+    Closed,
+    /// Calls are rejected until the cool-down ends
+    Open,
+    /// Cool-down over; the next result closes or re-opens the breaker
+    HalfOpen,
+}
+
+/// Snapshot of one breaker
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CircuitStatus {
+    pub dependency: Dependency,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    /// Seconds until calls are let through again (open breakers only)
+    pub retry_after_secs: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+/// Breakers for every dependency, sharing one threshold and cool-down
+#[derive(Debug)]
+pub struct CircuitBreakers {
+    failure_threshold: u32,
+    open_for: Duration,
+    breakers: [Mutex<Breaker>; 3],
+}
+
+static CIRCUIT_BREAKERS: OnceLock<CircuitBreakers> = OnceLock::new();
+
+/// Install the process-wide breakers; without this, `guarded` never rejects
+pub fn install_circuit_breakers(breakers: CircuitBreakers) {
+    if CIRCUIT_BREAKERS.set(breakers).is_err() {
+        warn!("Circuit breakers already installed");
+    }
+}
+
+/// Process-wide breakers, if the subsystem is enabled
+pub fn circuit_breakers() -> Option<&'static CircuitBreakers> {
+    CIRCUIT_BREAKERS.get()
+}
+
+/// Run `call` through the breaker for `dependency`
+///
+/// Returns `AppError::Unavailable` without calling when the breaker is open.
+pub async fn guarded<T>(
+    dependency: Dependency,
+    call: impl Future<Output = Result<T, AppError>>,
+) -> Result<T, AppError> {
+    let Some(breakers) = circuit_breakers() else {
+        return call.await;
+    };
+
+    breakers.check(dependency, Instant::now())?;
+    let result = call.await;
+    match &result {
+        Ok(_) => breakers.record_success(dependency),
+        Err(e) if e.is_dependency_failure() => breakers.record_failure(dependency, Instant::now()),
+        Err(_) => {}
+    }
+    result
+}
+
+impl CircuitBreakers {
+    /// Breakers opening after `failure_threshold` consecutive failures for `open_for`
+    pub fn new(failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            open_for,
+            breakers: Default::default(),
+        }
+    }
+
+    fn breaker(&self, dependency: Dependency) -> std::sync::MutexGuard<'_, Breaker> {
+        self.breakers[dependency.index()]
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn state(&self, breaker: &Breaker, now: Instant) -> (CircuitState, Option<Duration>) {
+        match breaker.opened_at {
+            None => (CircuitState::Closed, None),
+            Some(opened_at) => match self.open_for.checked_sub(now - opened_at) {
+                Some(remaining) if !remaining.is_zero() => (CircuitState::Open, Some(remaining)),
+                _ => (CircuitState::HalfOpen, None),
+            },
+        }
+    }
+
+    /// Reject the call while the breaker for `dependency` is open
+    pub fn check(&self, dependency: Dependency, now: Instant) -> Result<(), AppError> {
+        let breaker = self.breaker(dependency);
+        match self.state(&breaker, now) {
+            (CircuitState::Open, Some(remaining)) => Err(AppError::Unavailable {
+                message: format!("{:?} circuit is open after repeated failures", dependency),
+                retry_after_secs: ceil_secs(remaining),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn record_success(&self, dependency: Dependency) {
+        let mut breaker = self.breaker(dependency);
+        if breaker.opened_at.is_some() {
+            info!(dependency = ?dependency, "Circuit closed");
+        }
+        *breaker = Breaker::default();
+    }
+
+    /// Count a failure; opens the breaker at the threshold and re-opens a
+    /// half-open one immediately
+    pub fn record_failure(&self, dependency: Dependency, now: Instant) {
+        let mut breaker = self.breaker(dependency);
+        breaker.consecutive_failures += 1;
+        if breaker.consecutive_failures >= self.failure_threshold {
+            if breaker.opened_at.is_none() {
+                warn!(
+                    dependency = ?dependency,
+                    failures = breaker.consecutive_failures,
+                    open_secs = self.open_for.as_secs(),
+                    "Circuit opened"
+                );
+            }
+            breaker.opened_at = Some(now);
+        }
+    }
+
+    /// State of every breaker
+    pub fn statuses(&self) -> Vec<CircuitStatus> {
+        let now = Instant::now();
+        Dependency::ALL
+            .iter()
+            .map(|dependency| {
+                let breaker = self.breaker(*dependency);
+                let (state, remaining) = self.state(&breaker, now);
+                CircuitStatus {
+                    dependency: *dependency,
+                    state,
+                    consecutive_failures: breaker.consecutive_failures,
+                    retry_after_secs: remaining.map(ceil_secs),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Round up so clients never retry before the breaker half-opens
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breaker_opens_and_recovers() {
+        let breakers = CircuitBreakers::new(2, Duration::from_secs(30));
+        let start = Instant::now();
+
+        breakers.record_failure(Dependency::AiService, start);
+        assert!(breakers.check(Dependency::AiService, start).is_ok());
+
+        breakers.record_failure(Dependency::AiService, start);
+        let err = breakers.check(Dependency::AiService, start).unwrap_err();
+        assert_eq!(
+            err,
+            AppError::Unavailable {
+                message: "AiService circuit is open after repeated failures".to_string(),
+                retry_after_secs: 30,
+            }
+        );
+        assert!(breakers.check(Dependency::Qdrant, start).is_ok());
+
+        // Half-open after the cool-down: one more failure re-opens at once
+        let later = start + Duration::from_secs(31);
+        assert!(breakers.check(Dependency::AiService, later).is_ok());
+        breakers.record_failure(Dependency::AiService, later);
+        assert!(breakers.check(Dependency::AiService, later).is_err());
+
+        breakers.record_success(Dependency::AiService);
+        assert!(breakers.check(Dependency::AiService, later).is_ok());
+    }
+}
@@ -0,0 +1,149 @@
+//! Collection Stats
+//!
+//! Point counts, vector settings, payload indexes and optimizer state of a
+//! collection, as reported by Qdrant's collection info.
+
+use crate::error::AppError;
+use crate::models::admin::{CollectionStats, PayloadIndexInfo, VectorConfigInfo};
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::vectors_config::Config as VectorsConfigKind;
+use qdrant_client::qdrant::{CollectionInfo, CollectionStatus, Distance, PayloadSchemaType};
+
+/// Index health of `collection`
+pub async fn collection_stats(
+    qdrant: &Qdrant,
+    collection: &str,
+) -> Result<CollectionStats, AppError> {
+    let info = qdrant
+        .collection_info(collection)
+        .await
+        .map_err(|e| {
+            AppError::Qdrant(format!(
+                "Failed to read collection info of {}: {}",
+                collection, e
+            ))
+        })?
+        .result
+        .ok_or_else(|| AppError::Qdrant(format!("Qdrant returned no info for {}", collection)))?;
+    Ok(to_collection_stats(collection, info))
+}
+
+fn to_collection_stats(collection: &str, info: CollectionInfo) -> CollectionStats {
+    let status = CollectionStatus::try_from(info.status)
+        .map(|s| match s {
+            CollectionStatus::Green => "green",
+            CollectionStatus::Yellow => "yellow",
+            CollectionStatus::Grey => "grey",
+            CollectionStatus::Red => "red",
+            CollectionStatus::UnknownCollectionStatus => "unknown",
+        })
+        .unwrap_or("unknown");
+    let optimizer = info.optimizer_status.unwrap_or_default();
+    let params = info.config.and_then(|c| c.params).unwrap_or_default();
+
+    let vectors = match params.vectors_config.and_then(|v| v.config) {
+        Some(VectorsConfigKind::Params(vectors)) => Some(VectorConfigInfo {
+            size: vectors.size,
+            distance: Distance::try_from(vectors.distance)
+                .map(|d| d.as_str_name().to_lowercase())
+                .unwrap_or_default(),
+            on_disk: vectors.on_disk.unwrap_or(false),
+        }),
+        _ => None,
+    };
+
+    let mut payload_indexes: Vec<PayloadIndexInfo> = info
+        .payload_schema
+        .into_iter()
+        .map(|(field, schema)| PayloadIndexInfo {
+            field,
+            data_type: PayloadSchemaType::try_from(schema.data_type)
+                .map(|t| t.as_str_name().to_lowercase())
+                .unwrap_or_default(),
+            points: schema.points,
+        })
+        .collect();
+    payload_indexes.sort_by(|a, b| a.field.cmp(&b.field));
+
+    CollectionStats {
+        collection: collection.to_string(),
+        status: status.to_string(),
+        optimizer_ok: optimizer.ok,
+        optimizer_error: Some(optimizer.error).filter(|e| !e.is_empty()),
+        points_count: info.points_count,
+        indexed_vectors_count: info.indexed_vectors_count,
+        segments_count: info.segments_count,
+        vectors,
+        shard_number: params.shard_number,
+        replication_factor: params.replication_factor.unwrap_or(1),
+        payload_indexes,
+        warnings: info.warnings.into_iter().map(|w| w.message).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qdrant_client::qdrant::{
+        CollectionConfig, CollectionParams, OptimizerStatus, PayloadSchemaInfo, VectorParams,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_to_collection_stats() {
+        let schema = |data_type: PayloadSchemaType, points| PayloadSchemaInfo {
+            data_type: data_type as i32,
+            params: None,
+            points: Some(points),
+        };
+        let info = CollectionInfo {
+            status: CollectionStatus::Yellow as i32,
+            optimizer_status: Some(OptimizerStatus {
+                ok: true,
+                error: String::new(),
+            }),
+            segments_count: 6,
+            config: Some(CollectionConfig {
+                params: Some(CollectionParams {
+                    shard_number: 3,
+                    vectors_config: Some(
+                        VectorParams {
+                            size: 1152,
+                            distance: Distance::Cosine as i32,
+                            ..Default::default()
+                        }
+                        .into(),
+                    ),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            payload_schema: HashMap::from([
+                (
+                    "datetime".to_string(),
+                    schema(PayloadSchemaType::Datetime, 90),
+                ),
+                (
+                    "camera_id".to_string(),
+                    schema(PayloadSchemaType::Keyword, 100),
+                ),
+            ]),
+            points_count: Some(100),
+            indexed_vectors_count: Some(90),
+            warnings: Vec::new(),
+        };
+
+        let stats = to_collection_stats("vehicles", info);
+        assert_eq!(stats.status, "yellow");
+        assert_eq!(stats.optimizer_error, None);
+        assert_eq!(stats.replication_factor, 1);
+        let vectors = stats.vectors.unwrap();
+        assert_eq!((vectors.size, vectors.distance.as_str()), (1152, "cosine"));
+        let fields: Vec<(&str, &str)> = stats
+            .payload_indexes
+            .iter()
+            .map(|i| (i.field.as_str(), i.data_type.as_str()))
+            .collect();
+        assert_eq!(fields, [("camera_id", "keyword"), ("datetime", "datetime")]);
+    }
+}
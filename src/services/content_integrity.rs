@@ -0,0 +1,234 @@
+//! Content Integrity
+//!
+//! SHA-256 of image bytes stored at ingest, and a job re-hashing a sample of
+//! stored images to detect upstream storage corruption or reused URLs.
+
+use crate::config::technical;
+use crate::error::AppError;
+use crate::models::admin::{IntegrityMismatch, IntegrityStatus};
+use crate::services::{ShardRouter, extract_string, point_id_to_string};
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{Condition, Filter, Query, QueryPointsBuilder, Sample};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tracing::{debug, error, info, instrument, warn};
+
+/// Payload field holding the hex SHA-256 of the image bytes
+pub const CONTENT_HASH_FIELD: &str = "content_sha256";
+
+/// Hex-encoded SHA-256 of `content`
+pub fn sha256_hex(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Hash the image at `file_path`, an `http(s)://` URL or a local path
+pub async fn image_sha256(client: &reqwest::Client, file_path: &str) -> Result<String, AppError> {
+    let content = if file_path.starts_with("http://") || file_path.starts_with("https://") {
+        let response = client
+            .get(file_path)
+            .timeout(Duration::from_secs(technical::IMAGE_HASH_TIMEOUT_SECS))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AppError::Io(format!("Failed to download {}: {}", file_path, e)))?;
+        response
+            .bytes()
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to download {}: {}", file_path, e)))?
+            .to_vec()
+    } else {
+        tokio::fs::read(file_path)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to read {}: {}", file_path, e)))?
+    };
+    Ok(sha256_hex(&content))
+}
+
+/// Hashes of the images at `file_paths` that could be read, keyed by path
+///
+/// Unreadable images are skipped, so their points are stored without a hash.
+pub async fn hash_images(
+    client: &reqwest::Client,
+    file_paths: &[String],
+) -> HashMap<String, String> {
+    let mut hashes = HashMap::with_capacity(file_paths.len());
+    let mut tasks = JoinSet::new();
+    let mut pending = file_paths.iter();
+
+    loop {
+        while tasks.len() < technical::IMAGE_HASH_CONCURRENCY {
+            let Some(path) = pending.next() else { break };
+            let (client, path) = (client.clone(), path.clone());
+            tasks.spawn(async move {
+                let hash = image_sha256(&client, &path).await;
+                (path, hash)
+            });
+        }
+        let Some(joined) = tasks.join_next().await else {
+            return hashes;
+        };
+        match joined {
+            Ok((path, Ok(hash))) => {
+                hashes.insert(path, hash);
+            }
+            Ok((path, Err(e))) => debug!(path = %path, error = %e, "Image not hashed"),
+            Err(e) => warn!(error = %e, "Image hash task failed"),
+        }
+    }
+}
+
+/// Verification job state shared with the admin endpoints
+#[derive(Default)]
+pub struct IntegrityVerifier {
+    status: RwLock<IntegrityStatus>,
+}
+
+impl IntegrityVerifier {
+    pub fn status(&self) -> IntegrityStatus {
+        self.status
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Mark a run as started; returns `false` if one is already running
+    pub fn try_start(&self, sample_size: u32) -> bool {
+        let mut status = self.status.write().unwrap_or_else(|e| e.into_inner());
+        if status.running {
+            return false;
+        }
+        *status = IntegrityStatus {
+            running: true,
+            sample_size,
+            started_at: Some(chrono::Utc::now().to_rfc3339()),
+            ..Default::default()
+        };
+        true
+    }
+
+    fn update(&self, f: impl FnOnce(&mut IntegrityStatus)) {
+        f(&mut self.status.write().unwrap_or_else(|e| e.into_inner()));
+    }
+
+    /// Re-hash a random sample of hashed points from every shard collection
+    ///
+    /// Call `try_start` first.
+    pub async fn run(&self, qdrant: &Qdrant, router: &ShardRouter, client: &reqwest::Client) {
+        let sample_size = self.status().sample_size;
+        let mut result = Ok(());
+        for collection in router.collections() {
+            result = self
+                .verify_collection(qdrant, client, collection, sample_size)
+                .await;
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.update(|status| {
+            status.running = false;
+            status.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            status.error = result.err().map(|e| e.to_string());
+        });
+
+        let status = self.status();
+        match &status.error {
+            Some(e) => error!(error = %e, "Integrity verification failed"),
+            None if status.mismatched > 0 => warn!(
+                checked = status.checked,
+                mismatched = status.mismatched,
+                unreadable = status.unreadable,
+                "Integrity verification found mismatches"
+            ),
+            None => info!(
+                checked = status.checked,
+                unreadable = status.unreadable,
+                "Integrity verification finished"
+            ),
+        }
+    }
+
+    #[instrument(skip(self, qdrant, client))]
+    async fn verify_collection(
+        &self,
+        qdrant: &Qdrant,
+        client: &reqwest::Client,
+        collection: &str,
+        sample_size: u32,
+    ) -> Result<(), AppError> {
+        // Points ingested before hashing, or whose image was unreadable, have no hash
+        let hashed = Filter::must_not([Condition::is_empty(CONTENT_HASH_FIELD)]);
+        let sample = qdrant
+            .query(
+                QueryPointsBuilder::new(collection)
+                    .query(Query::new_sample(Sample::Random))
+                    .filter(hashed)
+                    .limit(sample_size as u64)
+                    .with_payload(true),
+            )
+            .await
+            .map_err(|e| AppError::Qdrant(format!("Failed to sample '{}': {}", collection, e)))?;
+
+        let points: Vec<(String, String, String, String)> = sample
+            .result
+            .into_iter()
+            .map(|p| {
+                (
+                    p.id.as_ref().map(point_id_to_string).unwrap_or_default(),
+                    extract_string(&p.payload, "camera_id"),
+                    extract_string(&p.payload, "image"),
+                    extract_string(&p.payload, CONTENT_HASH_FIELD),
+                )
+            })
+            .collect();
+        let paths: Vec<String> = points.iter().map(|(_, _, path, _)| path.clone()).collect();
+        let actual = hash_images(client, &paths).await;
+
+        self.update(|status| {
+            for (id, camera_id, file_path, stored) in points {
+                let Some(actual) = actual.get(&file_path) else {
+                    status.unreadable += 1;
+                    continue;
+                };
+                status.checked += 1;
+                if *actual == stored {
+                    status.matched += 1;
+                    continue;
+                }
+
+                status.mismatched += 1;
+                warn!(id = %id, camera_id = %camera_id, file_path = %file_path, "Image content changed");
+                if status.mismatches.len() < technical::MAX_REPORTED_MISMATCHES {
+                    status.mismatches.push(IntegrityMismatch {
+                        id,
+                        collection: collection.to_string(),
+                        camera_id,
+                        file_path,
+                        stored_sha256: stored,
+                        actual_sha256: actual.clone(),
+                    });
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Dead-Letter Queue
+//!
+//! Images that could not be stored because of a defective embedding, appended
+//! to a JSON lines file for inspection and later re-ingestion.
+
+use crate::error::AppError;
+use crate::models::admin::DeadLetter;
+use crate::models::search::CctvImageData;
+use crate::services::RequestMetrics;
+use std::io::Write;
+use std::sync::Mutex;
+use tracing::{error, warn};
+
+/// Append-only dead-letter file shared by the scheduler and insert endpoints
+pub struct DeadLetterQueue {
+    path: String,
+    /// Serializes appends so concurrent lines are never interleaved
+    lock: Mutex<()>,
+}
+
+impl DeadLetterQueue {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Record `image` as rejected from `source` for `reason`
+    ///
+    /// A failed write is logged; the image is still skipped.
+    pub fn push(
+        &self,
+        image: &CctvImageData,
+        source: &str,
+        reason: &str,
+        metrics: &RequestMetrics,
+    ) {
+        let letter = DeadLetter {
+            id: image.id as u64,
+            cctv_id: image.cctv_id.clone(),
+            filename: image.filename.clone(),
+            file_path: image.file_path.clone(),
+            source: source.to_string(),
+            reason: reason.to_string(),
+            at: chrono::Utc::now().to_rfc3339(),
+        };
+        metrics.record_dead_letter();
+        warn!(id = letter.id, filename = %letter.filename, reason, "Image dead-lettered");
+
+        if let Err(e) = self.append(&letter) {
+            error!(path = %self.path, error = %e, "Failed to write dead letter");
+        }
+    }
+
+    fn append(&self, letter: &DeadLetter) -> Result<(), AppError> {
+        let line = serde_json::to_string(letter)
+            .map_err(|e| AppError::Parse(format!("Failed to encode dead letter: {}", e)))?;
+
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| AppError::Io(format!("Failed to open {}: {}", self.path, e)))?;
+        writeln!(file, "{}", line)
+            .map_err(|e| AppError::Io(format!("Failed to write {}: {}", self.path, e)))
+    }
+
+    /// The newest `limit` dead letters, oldest first
+    ///
+    /// Lines that cannot be parsed are skipped.
+    pub fn recent(&self, limit: usize) -> Result<Vec<DeadLetter>, AppError> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(AppError::Io(format!("Failed to read {}: {}", self.path, e)));
+            }
+        };
+
+        let letters: Vec<DeadLetter> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        let skip = letters.len().saturating_sub(limit);
+        Ok(letters.into_iter().skip(skip).collect())
+    }
+}
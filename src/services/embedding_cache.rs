@@ -0,0 +1,163 @@
+//! Embedding Cache
+//!
+//! Bounded in-memory LRU cache of text and image embeddings, shared by the
+//! search and `/embed/*` handlers, so dashboards re-running saved queries and
+//! tools re-embedding the same inputs do not load the AI service. Entries
+//! expire after a TTL, so a redeployed model is picked up.
+
+use crate::error::AppError;
+use crate::services::{AiPriority, EmbeddingModel, RequestMetrics, get_text_embedding, sha256_hex};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct CacheEntry {
+    vector: Vec<f32>,
+    inserted: Instant,
+    /// Tick of the last insert or hit
+    used: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    vectors: HashMap<String, CacheEntry>,
+    /// Keys by the tick of their last use; the least recently used is evicted first
+    by_use: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl CacheEntries {
+    fn remove(&mut self, key: &str) -> Option<CacheEntry> {
+        let entry = self.vectors.remove(key)?;
+        self.by_use.remove(&entry.used);
+        Some(entry)
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+/// Embeddings keyed by their input (text or image content hash)
+#[derive(Debug)]
+pub struct EmbeddingCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: Mutex<CacheEntries>,
+}
+
+impl EmbeddingCache {
+    /// Cache of up to `capacity` embeddings, each kept for `ttl` (0 entries = disabled)
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: Mutex::default(),
+        }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, CacheEntries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Cached embedding of `key`, unless it expired
+    pub fn get(&self, key: &str) -> Option<Vec<f32>> {
+        let mut entries = self.entries();
+        let entry = entries.remove(key)?;
+        if entry.inserted.elapsed() >= self.ttl {
+            return None;
+        }
+        let used = entries.next_tick();
+        let vector = entry.vector.clone();
+        entries.by_use.insert(used, key.to_string());
+        entries
+            .vectors
+            .insert(key.to_string(), CacheEntry { used, ..entry });
+        Some(vector)
+    }
+
+    /// Store an embedding, evicting the least recently used beyond the capacity
+    pub fn insert(&self, key: String, vector: Vec<f32>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries();
+        entries.remove(&key);
+        let used = entries.next_tick();
+        entries.by_use.insert(used, key.clone());
+        entries.vectors.insert(
+            key,
+            CacheEntry {
+                vector,
+                inserted: Instant::now(),
+                used,
+            },
+        );
+        while entries.vectors.len() > self.capacity {
+            let Some((_, oldest)) = entries.by_use.pop_first() else {
+                break;
+            };
+            entries.vectors.remove(&oldest);
+        }
+    }
+
+    /// Embedding of `text` by `model`, from the cache or else its AI service, and whether it
+    /// was cached
+    pub async fn text_embedding(
+        &self,
+        client: &reqwest::Client,
+        model: &EmbeddingModel,
+        text: &str,
+        priority: AiPriority,
+        metrics: &RequestMetrics,
+    ) -> Result<(Vec<f32>, bool), AppError> {
+        let key = text_cache_key(&model.name, text);
+        if let Some(vector) = self.get(&key) {
+            metrics.record_embedding_cache(true);
+            return Ok((vector, true));
+        }
+        metrics.record_embedding_cache(false);
+        let vector = get_text_embedding(client, &model.ai_service_url, text, priority).await?;
+        self.insert(key, vector.clone());
+        Ok((vector, false))
+    }
+}
+
+/// Cache key of a text query embedded by `model`
+pub fn text_cache_key(model: &str, text: &str) -> String {
+    format!("text:{}:{}", model, text)
+}
+
+/// Cache key of an image, from the SHA-256 of its content rather than its
+/// path, so a crafted image can't take over another image's entry
+pub fn image_cache_key(content: &[u8]) -> String {
+    format!("image:{}", sha256_hex(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eviction() {
+        let cache = EmbeddingCache::new(2, Duration::from_secs(60));
+        cache.insert(text_cache_key("clip", "a"), vec![1.0]);
+        cache.insert(text_cache_key("clip", "b"), vec![2.0]);
+        // Using "a" makes "b" the least recently used
+        assert_eq!(cache.get(&text_cache_key("clip", "a")), Some(vec![1.0]));
+        cache.insert(text_cache_key("clip", "c"), vec![3.0]);
+
+        assert!(cache.get(&text_cache_key("clip", "b")).is_none());
+        assert_eq!(cache.get(&text_cache_key("clip", "a")), Some(vec![1.0]));
+        assert_eq!(cache.get(&text_cache_key("clip", "c")), Some(vec![3.0]));
+
+        // Each model has its own embedding of a text
+        assert!(cache.get(&text_cache_key("siglip", "a")).is_none());
+
+        let expired = EmbeddingCache::new(2, Duration::ZERO);
+        expired.insert(text_cache_key("clip", "a"), vec![1.0]);
+        assert!(expired.get(&text_cache_key("clip", "a")).is_none());
+    }
+}
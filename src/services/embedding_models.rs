@@ -0,0 +1,224 @@
+//! Embedding Models
+//!
+//! Registry of the embedding model each collection was embedded with, so
+//! ingest and search embed images and queries with the AI service serving
+//! that model. Collections pinned in `EMBEDDING_MODELS_PATH` keep a legacy
+//! model, and its vector size, while the others move to `EMBEDDING_MODEL`.
+
+use crate::config::Config;
+use crate::error::AppError;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use tracing::warn;
+
+/// An embedding model and the AI service serving it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddingModel {
+    pub name: String,
+    pub ai_service_url: String,
+    /// Dimension of its embeddings and of its collections' vectors
+    pub vector_size: usize,
+}
+
+/// Model entry of the registry file
+#[derive(Debug, Deserialize)]
+struct PinnedModel {
+    name: String,
+    ai_service_url: String,
+    /// Dimension of its embeddings (default: `VECTOR_SIZE`)
+    #[serde(default)]
+    vector_size: Option<usize>,
+    /// Collections embedded with this model
+    collections: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RegistryFile {
+    models: Vec<PinnedModel>,
+}
+
+/// Embedding model of every collection; unpinned collections use the default
+#[derive(Debug, Clone)]
+pub struct EmbeddingModels {
+    default: EmbeddingModel,
+    pinned: BTreeMap<String, EmbeddingModel>,
+}
+
+impl EmbeddingModels {
+    /// Every collection on `EMBEDDING_MODEL` at `AI_SERVICE_URL`
+    pub fn unpinned(config: &Config) -> Self {
+        Self {
+            default: EmbeddingModel {
+                name: config.embedding_model.clone(),
+                ai_service_url: config.ai_service_url.clone(),
+                vector_size: config.vector_size,
+            },
+            pinned: BTreeMap::new(),
+        }
+    }
+
+    /// Registry with the pins of `EMBEDDING_MODELS_PATH`, if set
+    ///
+    /// Pins of collections the service doesn't manage are logged, as they
+    /// may be meant for a shard count not deployed yet.
+    pub fn load(config: &Config, collections: &[String]) -> Result<Self, AppError> {
+        let models = Self::unpinned(config);
+        let Some(path) = &config.embedding_models_path else {
+            return Ok(models);
+        };
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            AppError::Io(format!("Failed to read embedding models {}: {}", path, e))
+        })?;
+        let models = models
+            .with_pins(&content)
+            .map_err(|e| AppError::Config(format!("Invalid embedding models {}: {}", path, e)))?;
+        for collection in models.pinned.keys() {
+            if !collections.contains(collection) {
+                warn!(collection = %collection, "Embedding model pinned for an unknown collection");
+            }
+        }
+        Ok(models)
+    }
+
+    fn with_pins(mut self, content: &str) -> Result<Self, String> {
+        let file: RegistryFile = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        for entry in file.models {
+            let model = EmbeddingModel {
+                name: entry.name,
+                ai_service_url: entry.ai_service_url.trim_end_matches('/').to_string(),
+                vector_size: entry.vector_size.unwrap_or(self.default.vector_size),
+            };
+            if model.vector_size == 0 {
+                return Err(format!("model '{}' has a vector_size of 0", model.name));
+            }
+            if model.name == self.default.name && model != self.default {
+                return Err(format!(
+                    "model '{}' is EMBEDDING_MODEL but served by another AI service or with \
+                     another vector size",
+                    model.name
+                ));
+            }
+            for collection in entry.collections {
+                if self
+                    .pinned
+                    .insert(collection.clone(), model.clone())
+                    .is_some()
+                {
+                    return Err(format!("collection '{}' is pinned twice", collection));
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Model of collections without a pin, also used by `/embed/*`
+    pub fn default_model(&self) -> &EmbeddingModel {
+        &self.default
+    }
+
+    /// Model `collection` was embedded with
+    pub fn for_collection(&self, collection: &str) -> &EmbeddingModel {
+        self.pinned.get(collection).unwrap_or(&self.default)
+    }
+
+    /// Pinned collections and the name of their model
+    pub fn pins(&self) -> BTreeMap<String, String> {
+        self.pinned
+            .iter()
+            .map(|(collection, model)| (collection.clone(), model.name.clone()))
+            .collect()
+    }
+
+    /// Every distinct model, the default first
+    pub fn models(&self) -> Vec<&EmbeddingModel> {
+        let mut models = vec![&self.default];
+        for model in self.pinned.values() {
+            if !models.contains(&model) {
+                models.push(model);
+            }
+        }
+        models
+    }
+
+    /// `collections` grouped by model, in order of first appearance
+    pub fn partition(&self, collections: &[String]) -> Vec<(&EmbeddingModel, Vec<String>)> {
+        let mut groups: Vec<(&EmbeddingModel, Vec<String>)> = Vec::new();
+        for collection in collections {
+            let model = self.for_collection(collection);
+            match groups.iter_mut().find(|(m, _)| *m == model) {
+                Some((_, group)) => group.push(collection.clone()),
+                None => groups.push((model, vec![collection.clone()])),
+            }
+        }
+        groups
+    }
+
+    /// The single model of `collections`, or 400 if they span several
+    pub fn single_model(&self, collections: &[String]) -> Result<&EmbeddingModel, AppError> {
+        match self.partition(collections).as_slice() {
+            [(model, _)] => Ok(model),
+            [] => Ok(&self.default),
+            _ => Err(AppError::InvalidRequest(
+                "The searched collections use different embedding models; restrict camera_ids \
+                 to cameras of one model"
+                    .to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_models() {
+        let models = EmbeddingModels {
+            default: EmbeddingModel {
+                name: "siglip-so400m".to_string(),
+                ai_service_url: "http://ai:8000".to_string(),
+                vector_size: 1152,
+            },
+            pinned: BTreeMap::new(),
+        };
+        let pinned = models
+            .clone()
+            .with_pins(
+                r#"{"models": [{"name": "clip-vit-b32", "ai_service_url": "http://ai-legacy:8000/",
+                    "vector_size": 512, "collections": ["vehicles_shard0", "vehicles_shard1"]}]}"#,
+            )
+            .unwrap();
+        let legacy = pinned.for_collection("vehicles_shard1");
+        assert_eq!(legacy.name, "clip-vit-b32");
+        assert_eq!(legacy.ai_service_url, "http://ai-legacy:8000");
+        assert_eq!(legacy.vector_size, 512);
+        let current = pinned.for_collection("vehicles_shard2");
+        assert_eq!(current.name, "siglip-so400m");
+        assert_eq!(current.vector_size, 1152);
+        assert_eq!(pinned.models().len(), 2);
+
+        let collections: Vec<String> = (0..3).map(|i| format!("vehicles_shard{}", i)).collect();
+        let groups = pinned.partition(&collections);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].1, ["vehicles_shard0", "vehicles_shard1"]);
+        assert!(pinned.single_model(&collections).is_err());
+        assert!(pinned.single_model(&collections[..2]).is_ok());
+
+        let twice = r#"{"models": [
+            {"name": "a", "ai_service_url": "http://a", "collections": ["vehicles"]},
+            {"name": "b", "ai_service_url": "http://b", "collections": ["vehicles"]}]}"#;
+        assert!(models.clone().with_pins(twice).is_err());
+        let clash = r#"{"models": [
+            {"name": "siglip-so400m", "ai_service_url": "http://other", "collections": []}]}"#;
+        assert!(models.clone().with_pins(clash).is_err());
+        let resized = r#"{"models": [
+            {"name": "siglip-so400m", "ai_service_url": "http://ai:8000", "vector_size": 768,
+             "collections": ["vehicles"]}]}"#;
+        assert!(models.clone().with_pins(resized).is_err());
+        // Without a vector_size a pinned model keeps VECTOR_SIZE
+        let default_size = r#"{"models": [
+            {"name": "clip", "ai_service_url": "http://a", "collections": ["vehicles"]}]}"#;
+        let default_size = models.with_pins(default_size).unwrap();
+        assert_eq!(default_size.for_collection("vehicles").vector_size, 1152);
+    }
+}
@@ -0,0 +1,122 @@
+//! Embedding Quality
+//!
+//! Detects NaN/Inf, all-zero and wrongly sized vectors from the AI service
+//! before they are stored or searched with.
+
+use crate::config::technical;
+use crate::error::AppError;
+use crate::services::RequestMetrics;
+use std::fmt;
+use tracing::warn;
+
+/// Defect found in an embedding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorIssue {
+    /// Length differs from the configured `VECTOR_SIZE`
+    WrongDimension,
+    /// Contains NaN or infinite values
+    NonFinite,
+    /// Norm below `ZERO_EMBEDDING_NORM`; cosine similarity is undefined
+    Zero,
+    /// Norm below `LOW_EMBEDDING_NORM`; usable, but suspicious
+    NearZero,
+}
+
+impl VectorIssue {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VectorIssue::WrongDimension => "wrong_dimension",
+            VectorIssue::NonFinite => "non_finite",
+            VectorIssue::Zero => "zero",
+            VectorIssue::NearZero => "near_zero",
+        }
+    }
+
+    /// Whether the vector must not be stored or searched with
+    pub fn is_rejected(&self) -> bool {
+        !matches!(self, VectorIssue::NearZero)
+    }
+}
+
+impl fmt::Display for VectorIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The defect in `vector`, if any, given the expected dimension
+pub fn vector_issue(vector: &[f32], dimension: usize) -> Option<VectorIssue> {
+    if vector.len() != dimension {
+        return Some(VectorIssue::WrongDimension);
+    }
+    if vector.iter().any(|v| !v.is_finite()) {
+        return Some(VectorIssue::NonFinite);
+    }
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm < technical::ZERO_EMBEDDING_NORM {
+        Some(VectorIssue::Zero)
+    } else if norm < technical::LOW_EMBEDDING_NORM {
+        Some(VectorIssue::NearZero)
+    } else {
+        None
+    }
+}
+
+/// Check an embedding used for `source` (`ingest` or `search`) against the
+/// configured `dimension`
+///
+/// Every issue is logged and counted; rejected vectors return an error, a
+/// wrong dimension as unprocessable so it is not mistaken for an outage.
+pub fn validate_embedding(
+    vector: &[f32],
+    dimension: usize,
+    source: &'static str,
+    subject: &str,
+    metrics: &RequestMetrics,
+) -> Result<(), AppError> {
+    let Some(issue) = vector_issue(vector, dimension) else {
+        return Ok(());
+    };
+
+    metrics.record_embedding_issue(source, issue.as_str());
+    warn!(source, subject, issue = %issue, "Suspicious embedding from AI service");
+    if issue == VectorIssue::WrongDimension {
+        return Err(AppError::Unprocessable(format!(
+            "AI service returned a {}-dimensional embedding, but the collection stores {}-dimensional vectors; check the AI service model and VECTOR_SIZE",
+            vector.len(),
+            dimension
+        )));
+    }
+    if issue.is_rejected() {
+        return Err(AppError::AiService(format!(
+            "AI service returned a {} embedding",
+            issue
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vector_issue() {
+        assert_eq!(vector_issue(&[0.6, 0.8], 2), None);
+        assert_eq!(
+            vector_issue(&[0.6, 0.8], 3),
+            Some(VectorIssue::WrongDimension)
+        );
+        assert_eq!(
+            vector_issue(&[f32::NAN, 1.0], 2),
+            Some(VectorIssue::NonFinite)
+        );
+        assert_eq!(
+            vector_issue(&[f32::INFINITY], 1),
+            Some(VectorIssue::NonFinite)
+        );
+        assert_eq!(vector_issue(&[0.0, 0.0], 2), Some(VectorIssue::Zero));
+        assert_eq!(vector_issue(&[], 0), Some(VectorIssue::Zero));
+        assert_eq!(vector_issue(&[0.001, 0.0], 2), Some(VectorIssue::NearZero));
+    }
+}
@@ -0,0 +1,253 @@
+//! Evaluation Sets
+//!
+//! Labeled query sets run through the search pipeline to measure recall@k,
+//! MRR and latency of the current configuration. Every change rewrites the
+//! evaluation sets file, so sets and their run history survive restarts.
+
+use crate::config::technical;
+use crate::error::AppError;
+use crate::models::evaluation::{
+    CreateEvalSetRequest, EvalRunSummary, EvalSet, EvalSetSummary, LatencySummary,
+};
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Evaluation sets shared by the HTTP workers, mirrored to a JSON file
+pub struct EvalSetStore {
+    path: PathBuf,
+    sets: RwLock<Vec<EvalSet>>,
+}
+
+impl EvalSetStore {
+    /// Load the saved sets from `path` (a missing file means no sets)
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AppError> {
+        let path = path.as_ref().to_path_buf();
+
+        let sets = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| {
+                AppError::Config(format!(
+                    "Failed to parse evaluation sets {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(AppError::Io(format!(
+                    "Failed to read evaluation sets {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+        };
+
+        Ok(Self {
+            path,
+            sets: RwLock::new(sets),
+        })
+    }
+
+    /// Store a new set attributed to `user`
+    pub fn create(
+        &self,
+        request: &CreateEvalSetRequest,
+        user: Option<String>,
+    ) -> Result<EvalSet, AppError> {
+        let set = EvalSet {
+            set_id: format!("{:016x}", rand::random::<u64>()),
+            name: request.name.trim().to_string(),
+            queries: request.queries.clone(),
+            created_at: Utc::now().to_rfc3339(),
+            created_by: user,
+            runs: Vec::new(),
+        };
+        self.modify(|sets| {
+            sets.push(set.clone());
+            Ok(set)
+        })
+    }
+
+    /// Every set without its queries and runs, oldest first
+    pub fn list(&self) -> Vec<EvalSetSummary> {
+        self.sets
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|set| EvalSetSummary {
+                set_id: set.set_id.clone(),
+                name: set.name.clone(),
+                queries: set.queries.len(),
+                created_at: set.created_at.clone(),
+                created_by: set.created_by.clone(),
+                last_run: set.runs.last().cloned(),
+            })
+            .collect()
+    }
+
+    pub fn get(&self, set_id: &str) -> Result<EvalSet, AppError> {
+        self.sets
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|set| set.set_id == set_id)
+            .cloned()
+            .ok_or_else(|| unknown_set(set_id))
+    }
+
+    pub fn delete(&self, set_id: &str) -> Result<(), AppError> {
+        self.modify(|sets| {
+            let index = sets
+                .iter()
+                .position(|set| set.set_id == set_id)
+                .ok_or_else(|| unknown_set(set_id))?;
+            sets.remove(index);
+            Ok(())
+        })
+    }
+
+    /// Append a run to the history of a set, keeping the most recent
+    /// `MAX_EVAL_RUNS`
+    pub fn record_run(&self, set_id: &str, run: EvalRunSummary) -> Result<(), AppError> {
+        self.modify(|sets| {
+            let set = sets
+                .iter_mut()
+                .find(|set| set.set_id == set_id)
+                .ok_or_else(|| unknown_set(set_id))?;
+            set.runs.push(run);
+            let excess = set.runs.len().saturating_sub(technical::MAX_EVAL_RUNS);
+            set.runs.drain(..excess);
+            Ok(())
+        })
+    }
+
+    /// Apply `f` to a copy of the sets and keep it only once it is saved
+    fn modify<T>(
+        &self,
+        f: impl FnOnce(&mut Vec<EvalSet>) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let mut sets = self.sets.write().unwrap_or_else(|e| e.into_inner());
+        let mut updated = sets.clone();
+        let result = f(&mut updated)?;
+        self.save(&updated)?;
+        *sets = updated;
+        Ok(result)
+    }
+
+    /// Write the sets to disk, atomically replacing the previous file
+    fn save(&self, sets: &[EvalSet]) -> Result<(), AppError> {
+        let content = serde_json::to_string_pretty(sets)
+            .map_err(|e| AppError::Io(format!("Failed to serialize evaluation sets: {}", e)))?;
+
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, content)
+            .and_then(|_| std::fs::rename(&tmp, &self.path))
+            .map_err(|e| {
+                AppError::Io(format!(
+                    "Failed to write evaluation sets {}: {}",
+                    self.path.display(),
+                    e
+                ))
+            })
+    }
+}
+
+fn unknown_set(set_id: &str) -> AppError {
+    AppError::NotFound(format!("Unknown evaluation set: {}", set_id))
+}
+
+/// Recall and reciprocal rank of a ranking, given whether each hit is
+/// relevant and how many relevant images there are
+pub fn ranking_metrics(is_relevant: impl IntoIterator<Item = bool>, relevant: usize) -> (f64, f64) {
+    let ranks: Vec<usize> = is_relevant
+        .into_iter()
+        .enumerate()
+        .filter(|(_, hit)| *hit)
+        .map(|(rank, _)| rank)
+        .collect();
+    let recall = ranks.len() as f64 / relevant.max(1) as f64;
+    let reciprocal_rank = ranks.first().map_or(0.0, |rank| 1.0 / (rank + 1) as f64);
+    (recall, reciprocal_rank)
+}
+
+/// Mean, nearest-rank percentiles and maximum of per-query latencies
+pub fn latency_summary(latencies_ms: &[u64]) -> LatencySummary {
+    if latencies_ms.is_empty() {
+        return LatencySummary::default();
+    }
+    let mut sorted = latencies_ms.to_vec();
+    sorted.sort_unstable();
+    let percentile = |p: f64| sorted[((p * sorted.len() as f64).ceil() as usize).max(1) - 1];
+    LatencySummary {
+        mean_ms: sorted.iter().sum::<u64>() as f64 / sorted.len() as f64,
+        p50_ms: percentile(0.5),
+        p95_ms: percentile(0.95),
+        max_ms: sorted[sorted.len() - 1],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::search::LabeledQuery;
+
+    #[test]
+    fn test_eval_sets_survive_reload() {
+        let path =
+            std::env::temp_dir().join(format!("eval-sets-{:016x}.json", rand::random::<u64>()));
+        let store = EvalSetStore::load(&path).unwrap();
+        let set = store
+            .create(
+                &CreateEvalSetRequest {
+                    name: " Gate traffic ".to_string(),
+                    queries: vec![LabeledQuery {
+                        query: "white pickup truck".to_string(),
+                        relevant_ids: vec![3, 7],
+                    }],
+                },
+                Some("analyst-2".to_string()),
+            )
+            .unwrap();
+        for _ in 0..technical::MAX_EVAL_RUNS + 1 {
+            let run = EvalRunSummary {
+                started_at: Utc::now().to_rfc3339(),
+                top_k: 10,
+                embedding_models: vec!["clip-vit-b32".to_string()],
+                search: None,
+                recall: 0.5,
+                mrr: 1.0,
+                latency: latency_summary(&[120]),
+            };
+            store.record_run(&set.set_id, run).unwrap();
+        }
+        assert!(matches!(store.get("missing"), Err(AppError::NotFound(_))));
+
+        let reloaded = EvalSetStore::load(&path).unwrap();
+        let listed = reloaded.list();
+        assert_eq!(listed[0].name, "Gate traffic");
+        assert_eq!(listed[0].queries, 1);
+        assert_eq!(listed[0].last_run.as_ref().unwrap().latency.p95_ms, 120);
+        assert_eq!(
+            reloaded.get(&set.set_id).unwrap().runs.len(),
+            technical::MAX_EVAL_RUNS
+        );
+        reloaded.delete(&set.set_id).unwrap();
+        assert!(EvalSetStore::load(&path).unwrap().list().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_ranking_metrics() {
+        assert_eq!(ranking_metrics([false, true, false, true], 4), (0.5, 0.5));
+        assert_eq!(ranking_metrics([false, false], 1), (0.0, 0.0));
+        assert_eq!(ranking_metrics([], 0), (0.0, 0.0));
+
+        let latency = latency_summary(&[40, 10, 30, 20]);
+        assert_eq!(latency.mean_ms, 25.0);
+        assert_eq!(
+            (latency.p50_ms, latency.p95_ms, latency.max_ms),
+            (20, 40, 40)
+        );
+        assert_eq!(latency_summary(&[]), LatencySummary::default());
+    }
+}
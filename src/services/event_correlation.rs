@@ -0,0 +1,121 @@
+//! Event Correlation
+//!
+//! Pairs hits of the same query on different cameras taken close together
+//! in time, e.g. to confirm that a vehicle passed two checkpoints.
+
+use crate::services::extract_string;
+use chrono::DateTime;
+use qdrant_client::qdrant::ScoredPoint;
+
+/// Two sightings on different cameras, `first` being the earlier one
+#[derive(Debug, Clone)]
+pub struct HitPair {
+    pub first: ScoredPoint,
+    pub second: ScoredPoint,
+    /// Mean of the two scores
+    pub score: f32,
+    pub gap_seconds: i64,
+}
+
+/// Best pairs of hits from different cameras at most `window_secs` apart
+///
+/// Pairs are ranked by their mean score, then by the shorter gap. Each hit
+/// is used in at most one pair, so one strong sighting can't fill the list.
+/// Hits without a parseable `datetime` are ignored.
+pub fn correlate_hits(points: Vec<ScoredPoint>, window_secs: i64, limit: usize) -> Vec<HitPair> {
+    let hits: Vec<(ScoredPoint, String, i64)> = points
+        .into_iter()
+        .filter_map(|point| {
+            let time = DateTime::parse_from_rfc3339(&extract_string(&point.payload, "datetime"))
+                .ok()?
+                .timestamp();
+            let camera_id = extract_string(&point.payload, "camera_id");
+            Some((point, camera_id, time))
+        })
+        .collect();
+
+    let mut candidates = Vec::new();
+    for (i, (a, camera_a, time_a)) in hits.iter().enumerate() {
+        for (j, (b, camera_b, time_b)) in hits.iter().enumerate().skip(i + 1) {
+            let gap = (time_a - time_b).abs();
+            if camera_a != camera_b && gap <= window_secs {
+                candidates.push((i, j, (a.score + b.score) / 2.0, gap));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.3.cmp(&b.3)));
+
+    let mut used = vec![false; hits.len()];
+    let mut pairs = Vec::new();
+    for (i, j, score, gap_seconds) in candidates {
+        if pairs.len() == limit {
+            break;
+        }
+        if used[i] || used[j] {
+            continue;
+        }
+        used[i] = true;
+        used[j] = true;
+        let (first, second) = if hits[i].2 <= hits[j].2 {
+            (i, j)
+        } else {
+            (j, i)
+        };
+        pairs.push(HitPair {
+            first: hits[first].0.clone(),
+            second: hits[second].0.clone(),
+            score,
+            gap_seconds,
+        });
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{PayloadBuilder, point_id_to_string};
+
+    fn hit(id: u64, score: f32, camera_id: &str, datetime: &str) -> ScoredPoint {
+        ScoredPoint {
+            id: Some(id.into()),
+            score,
+            payload: PayloadBuilder::new()
+                .string("camera_id", camera_id)
+                .string("datetime", datetime)
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_correlate_hits() {
+        let points = vec![
+            hit(1, 0.9, "gate_a", "2025-10-08T06:40:00+07:00"),
+            hit(2, 0.8, "gate_b", "2025-10-08T06:32:00+07:00"),
+            hit(3, 0.7, "gate_b", "2025-10-08T06:45:00+07:00"),
+            // Same camera as 1, never paired with it
+            hit(4, 0.95, "gate_a", "2025-10-08T06:41:00+07:00"),
+            // Too far from every other camera's hit
+            hit(5, 0.99, "gate_c", "2025-10-08T09:00:00+07:00"),
+        ];
+        let pairs: Vec<(String, String, i64)> = correlate_hits(points, 600, 10)
+            .iter()
+            .map(|pair| {
+                (
+                    point_id_to_string(pair.first.id.as_ref().unwrap()),
+                    point_id_to_string(pair.second.id.as_ref().unwrap()),
+                    pair.gap_seconds,
+                )
+            })
+            .collect();
+        // 4+2 (0.875) beats 1+2 (0.85); 1 then pairs with 3
+        assert_eq!(
+            pairs,
+            [
+                ("2".to_string(), "4".to_string(), 540),
+                ("1".to_string(), "3".to_string(), 300)
+            ]
+        );
+    }
+}
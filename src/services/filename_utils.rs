@@ -2,24 +2,102 @@
 //!
 //! Functions for datetime conversions.
 
-use chrono::{DateTime, Datelike, Timelike, Utc};
+use crate::error::AppError;
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, SecondsFormat, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
 
-/// Convert API date and time fields directly to RFC 3339 format
+/// Convert API date and time fields, local to `timezone`, to RFC 3339 in UTC
 ///
 /// Takes date in format "2025-10-02" and time in format "13:11:00"
-/// Returns RFC 3339 format: "2025-10-02T13:11:00Z"
-#[inline]
-pub fn api_datetime_to_rfc3339(date: &str, time: &str) -> String {
-    format!("{}T{}Z", date, time)
+/// Returns RFC 3339 format, e.g. "2025-10-02T06:11:00Z" for Asia/Bangkok
+pub fn api_datetime_to_rfc3339(date: &str, time: &str, timezone: Tz) -> Result<String, AppError> {
+    let local = NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M:%S")
+        .map_err(|e| {
+        AppError::Parse(format!("Invalid image date/time {} {}: {}", date, time, e))
+    })?;
+    // A repeated hour at a DST change resolves to its first occurrence
+    let datetime = timezone
+        .from_local_datetime(&local)
+        .earliest()
+        .ok_or_else(|| {
+            AppError::Parse(format!(
+                "Image time {} {} does not exist in {}",
+                date, time, timezone
+            ))
+        })?;
+    Ok(datetime
+        .with_timezone(&Utc)
+        .to_rfc3339_opts(SecondsFormat::Secs, true))
 }
 
-/// Parse RFC 3339 datetime string to Qdrant Timestamp
-pub fn rfc3339_to_timestamp(rfc3339_str: &str) -> Result<qdrant_client::qdrant::Timestamp, String> {
-    let dt = DateTime::parse_from_rfc3339(rfc3339_str)
-        .map_err(|e| format!("Failed to parse RFC 3339 datetime: {}", e))?;
+/// Parse RFC 3339 datetime string to a UTC datetime
+pub fn parse_rfc3339_utc(rfc3339_str: &str) -> Result<DateTime<Utc>, AppError> {
+    DateTime::parse_from_rfc3339(rfc3339_str)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| AppError::Parse(format!("Failed to parse RFC 3339 datetime: {}", e)))
+}
+
+/// Parse an ISO 8601 duration of weeks, days, hours, minutes and seconds,
+/// e.g. `PT24H`, `P7D` or `P1DT12H`
+///
+/// Years and months are rejected, as their length depends on the date.
+pub fn parse_iso8601_duration(value: &str) -> Result<Duration, AppError> {
+    let invalid = || AppError::Parse(format!("Invalid ISO 8601 duration {:?}", value));
+    let rest = value.strip_prefix('P').ok_or_else(invalid)?;
+    let (date_part, time_part) = match rest.split_once('T') {
+        Some((_, "")) => return Err(invalid()),
+        Some((date, time)) => (date, time),
+        None => (rest, ""),
+    };
 
-    let dt_utc = dt.with_timezone(&Utc);
+    let mut total = Duration::zero();
+    let mut components = 0;
+    for (part, units) in [(date_part, "WD"), (time_part, "HMS")] {
+        let mut number = String::new();
+        let mut allowed = units;
+        for c in part.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+            // Units must appear in order, each at most once
+            let position = allowed.find(c).ok_or_else(invalid)?;
+            allowed = &allowed[position + 1..];
+            let amount: i64 = number.parse().map_err(|_| invalid())?;
+            number.clear();
+            let unit = match c {
+                'W' => Duration::try_weeks(amount),
+                'D' => Duration::try_days(amount),
+                'H' => Duration::try_hours(amount),
+                'M' => Duration::try_minutes(amount),
+                _ => Duration::try_seconds(amount),
+            };
+            total = unit
+                .and_then(|d| total.checked_add(&d))
+                .ok_or_else(invalid)?;
+            components += 1;
+        }
+        if !number.is_empty() {
+            return Err(invalid());
+        }
+    }
+    if components == 0 {
+        return Err(invalid());
+    }
+    Ok(total)
+}
 
+/// Parse RFC 3339 datetime string to Qdrant Timestamp
+pub fn rfc3339_to_timestamp(
+    rfc3339_str: &str,
+) -> Result<qdrant_client::qdrant::Timestamp, AppError> {
+    datetime_to_timestamp(&parse_rfc3339_utc(rfc3339_str)?)
+}
+
+/// Convert a UTC datetime to Qdrant Timestamp
+pub fn datetime_to_timestamp(
+    dt_utc: &DateTime<Utc>,
+) -> Result<qdrant_client::qdrant::Timestamp, AppError> {
     qdrant_client::qdrant::Timestamp::date_time(
         dt_utc.year() as i64,
         dt_utc.month() as u8,
@@ -28,7 +106,7 @@ pub fn rfc3339_to_timestamp(rfc3339_str: &str) -> Result<qdrant_client::qdrant::
         dt_utc.minute() as u8,
         dt_utc.second() as u8,
     )
-    .map_err(|e| format!("Failed to create timestamp: {}", e))
+    .map_err(|e| AppError::Parse(format!("Failed to create timestamp: {}", e)))
 }
 
 #[cfg(test)]
@@ -37,8 +115,16 @@ mod tests {
 
     #[test]
     fn test_api_datetime_to_rfc3339() {
-        let result = api_datetime_to_rfc3339("2025-10-02", "13:11:00");
-        assert_eq!(result, "2025-10-02T13:11:00Z");
+        let result = api_datetime_to_rfc3339("2025-10-02", "13:11:00", chrono_tz::Asia::Bangkok);
+        assert_eq!(result.unwrap(), "2025-10-02T06:11:00Z");
+        let result = api_datetime_to_rfc3339("2025-10-02", "03:11:00", chrono_tz::UTC);
+        assert_eq!(result.unwrap(), "2025-10-02T03:11:00Z");
+
+        // 02:30 is skipped when New York switches to daylight saving time
+        let result =
+            api_datetime_to_rfc3339("2025-03-09", "02:30:00", chrono_tz::America::New_York);
+        assert!(result.is_err());
+        assert!(api_datetime_to_rfc3339("02/10/2025", "13:11", chrono_tz::UTC).is_err());
     }
 
     #[test]
@@ -46,4 +132,24 @@ mod tests {
         let result = rfc3339_to_timestamp("2025-10-02T13:11:00Z");
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_parse_iso8601_duration() {
+        assert_eq!(
+            parse_iso8601_duration("PT24H").unwrap(),
+            Duration::hours(24)
+        );
+        assert_eq!(parse_iso8601_duration("P7D").unwrap(), Duration::days(7));
+        assert_eq!(
+            parse_iso8601_duration("P1DT12H30M").unwrap(),
+            Duration::minutes(36 * 60 + 30)
+        );
+        assert_eq!(parse_iso8601_duration("P2W").unwrap(), Duration::days(14));
+
+        for invalid in [
+            "", "P", "PT", "24H", "P1M", "P1Y", "PT1H2", "PT1M1H", "P1DT",
+        ] {
+            assert!(parse_iso8601_duration(invalid).is_err(), "{}", invalid);
+        }
+    }
 }
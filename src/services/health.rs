@@ -0,0 +1,77 @@
+//! Dependency Health
+//!
+//! Connectivity checks against Qdrant, the AI service and the CCTV token
+//! endpoint, run concurrently for the probe endpoints.
+
+use crate::clients::cctv_client::CctvApi;
+use crate::config::technical;
+use crate::error::AppError;
+use crate::models::system::DependencyHealth;
+use qdrant_client::Qdrant;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Check every external dependency, each bounded by the health-check timeout
+pub async fn check_dependencies(
+    qdrant: &Qdrant,
+    http_client: &reqwest::Client,
+    ai_service_url: &str,
+    cctv_api: &CctvApi,
+) -> Vec<DependencyHealth> {
+    let (qdrant, ai_service, cctv_api) = tokio::join!(
+        check("qdrant", check_qdrant(qdrant)),
+        check("ai_service", check_ai_service(http_client, ai_service_url)),
+        check("cctv_api", check_cctv_token(cctv_api)),
+    );
+    vec![qdrant, ai_service, cctv_api]
+}
+
+/// Whether every dependency passed its check
+pub fn all_healthy(dependencies: &[DependencyHealth]) -> bool {
+    dependencies.iter().all(|d| d.healthy)
+}
+
+async fn check(name: &str, probe: impl Future<Output = Result<(), AppError>>) -> DependencyHealth {
+    let started = Instant::now();
+    let timeout = Duration::from_secs(technical::HEALTH_CHECK_TIMEOUT_SECS);
+    let result = match tokio::time::timeout(timeout, probe).await {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(_) => Err(format!("no response within {}s", timeout.as_secs())),
+    };
+
+    DependencyHealth {
+        name: name.to_string(),
+        healthy: result.is_ok(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: result.err(),
+    }
+}
+
+async fn check_qdrant(qdrant: &Qdrant) -> Result<(), AppError> {
+    qdrant.list_collections().await?;
+    Ok(())
+}
+
+/// Any response below 500 shows the AI service is up; it has no health route
+async fn check_ai_service(client: &reqwest::Client, base_url: &str) -> Result<(), AppError> {
+    let response = client
+        .get(base_url)
+        .send()
+        .await
+        .map_err(|e| AppError::AiService(format!("Failed to connect to AI Service: {}", e)))?;
+
+    if response.status().is_server_error() {
+        return Err(AppError::AiService(format!(
+            "AI Service returned error: {}",
+            response.status()
+        )));
+    }
+    Ok(())
+}
+
+async fn check_cctv_token(cctv_api: &CctvApi) -> Result<(), AppError> {
+    cctv_api
+        .check_token()
+        .await
+        .map_err(|e| AppError::CctvApi(format!("Token request failed: {}", e)))
+}
@@ -0,0 +1,110 @@
+//! Hybrid Calibration
+//!
+//! Finds the weight of vector similarity against keyword matches that ranks
+//! a labeled evaluation set best: the candidates of each query are fetched
+//! once and re-ranked by weighted fusion for every alpha of the sweep.
+
+use crate::models::search::AlphaTrial;
+use crate::services::{ranking_metrics, weighted_fusion};
+use qdrant_client::qdrant::ScoredPoint;
+use qdrant_client::qdrant::point_id::PointIdOptions;
+use std::collections::HashSet;
+
+/// Hybrid candidates of one evaluation query and the images relevant to it
+#[derive(Debug, Clone)]
+pub struct LabeledCandidates {
+    pub candidates: Vec<ScoredPoint>,
+    pub terms: Vec<String>,
+    pub relevant: HashSet<u64>,
+}
+
+/// Alphas swept when a calibration request names none: 0 to 1 in steps of 0.1
+pub fn default_alphas() -> Vec<f32> {
+    (0..=10).map(|step| step as f32 / 10.0).collect()
+}
+
+/// Mean recall and reciprocal rank of the top `top_k` of every query, per
+/// alpha
+pub fn sweep_alphas(
+    queries: &[LabeledCandidates],
+    alphas: &[f32],
+    top_k: usize,
+) -> Vec<AlphaTrial> {
+    alphas
+        .iter()
+        .map(|&alpha| {
+            let (mut recall, mut mrr) = (0.0, 0.0);
+            for query in queries {
+                let ranked = weighted_fusion(query.candidates.clone(), &query.terms, alpha, top_k);
+                let (query_recall, reciprocal_rank) = ranking_metrics(
+                    ranked.iter().map(|point| {
+                        numeric_id(point).is_some_and(|id| query.relevant.contains(&id))
+                    }),
+                    query.relevant.len(),
+                );
+                recall += query_recall;
+                mrr += reciprocal_rank;
+            }
+            let count = queries.len().max(1) as f64;
+            AlphaTrial {
+                alpha,
+                recall: recall / count,
+                mrr: mrr / count,
+            }
+        })
+        .collect()
+}
+
+/// Trial with the highest MRR, then recall; the earliest wins ties
+pub fn best_trial(trials: &[AlphaTrial]) -> Option<&AlphaTrial> {
+    trials.iter().reduce(|best, trial| {
+        if (trial.mrr, trial.recall) > (best.mrr, best.recall) {
+            trial
+        } else {
+            best
+        }
+    })
+}
+
+fn numeric_id(point: &ScoredPoint) -> Option<u64> {
+    match point.id.as_ref()?.point_id_options.as_ref()? {
+        PointIdOptions::Num(id) => Some(*id),
+        PointIdOptions::Uuid(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{PayloadBuilder, query_terms};
+
+    #[test]
+    fn test_sweep_alphas() {
+        let hit = |id: u64, score: f32, caption: &str| ScoredPoint {
+            id: Some(id.into()),
+            score,
+            payload: PayloadBuilder::new().string("caption", caption).build(),
+            ..Default::default()
+        };
+        // The labeled image is the best keyword match but the worst
+        // vector match
+        let query = LabeledCandidates {
+            candidates: vec![
+                hit(1, 0.9, "A red sedan"),
+                hit(2, 0.8, "A blue bus"),
+                hit(3, 0.7, "A white pickup truck"),
+            ],
+            terms: query_terms("white pickup"),
+            relevant: HashSet::from([3]),
+        };
+
+        let trials = sweep_alphas(&[query], &[1.0, 0.4, 0.0], 1);
+        assert_eq!(trials.len(), 3);
+        assert_eq!(trials[0].mrr, 0.0);
+        assert_eq!(trials[1].recall, 1.0);
+        // 0.4 and 0.0 both rank it first; the first of the two wins
+        assert_eq!(best_trial(&trials).unwrap().alpha, 0.4);
+        assert!(best_trial(&[]).is_none());
+        assert_eq!(default_alphas().len(), 11);
+    }
+}
@@ -0,0 +1,309 @@
+//! Hybrid Search
+//!
+//! Dense vector similarity combined with a full-text match of the query on
+//! the text-indexed payload fields, fused by Qdrant, weighted, or applied as
+//! a filter.
+
+use crate::error::AppError;
+use crate::services::{
+    CAPTION_TERMS_FIELD, PayloadMap, extract_string, keyword_terms, merge_by_score,
+    point_id_to_string, with_conditions,
+};
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{
+    Condition, Filter, Fusion, PrefetchQueryBuilder, Query, QueryPointsBuilder, ScoredPoint,
+    SearchPoints,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+use tracing::instrument;
+
+/// Payload fields with a full-text index matched by hybrid searches
+pub const HYBRID_TEXT_FIELDS: &[&str] = &["filename", "caption", CAPTION_TERMS_FIELD];
+
+/// How the text match is combined with vector similarity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HybridFusion {
+    /// Reciprocal rank fusion of the dense and text-matching result lists
+    Rrf,
+    /// Distribution-based score fusion of the dense and text-matching lists
+    Dbsf,
+    /// `alpha` × vector similarity, rescaled to 0–1 over the candidates,
+    /// plus (1 − `alpha`) × the share of query words matched
+    Weighted,
+    /// Only return images matching the text, ranked by vector similarity
+    Filter,
+}
+
+impl HybridFusion {
+    /// Qdrant fusion of the prefetched lists; `None` in weighted and filter
+    /// mode
+    pub fn qdrant_fusion(&self) -> Option<Fusion> {
+        match self {
+            HybridFusion::Rrf => Some(Fusion::Rrf),
+            HybridFusion::Dbsf => Some(Fusion::Dbsf),
+            HybridFusion::Weighted | HybridFusion::Filter => None,
+        }
+    }
+}
+
+/// Parse `rrf`, `dbsf`, `weighted` or `filter`
+pub fn parse_hybrid_fusion(value: &str) -> Result<HybridFusion, AppError> {
+    match value.to_ascii_lowercase().as_str() {
+        "rrf" => Ok(HybridFusion::Rrf),
+        "dbsf" => Ok(HybridFusion::Dbsf),
+        "weighted" => Ok(HybridFusion::Weighted),
+        "filter" => Ok(HybridFusion::Filter),
+        _ => Err(AppError::InvalidRequest(format!(
+            "Invalid hybrid fusion {:?}: expected rrf, dbsf, weighted or filter",
+            value
+        ))),
+    }
+}
+
+/// Condition matching any word of `text` in any of the text fields
+///
+/// Segmented Thai captions are matched by the segmented words of `text`.
+/// Returns `None` for blank text.
+pub fn text_match_condition(text: &str) -> Option<Condition> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let terms = keyword_terms(text);
+    let fields = HYBRID_TEXT_FIELDS.iter().map(|field| {
+        let text = if *field == CAPTION_TERMS_FIELD {
+            terms.as_str()
+        } else {
+            text
+        };
+        Condition::matches_text_any(*field, text)
+    });
+    Some(Filter::should(fields).into())
+}
+
+/// Run `base` per collection as a dense search and a text-matching dense
+/// search, fused by `fusion`, and return the merged top hits
+///
+/// `score_threshold` in `base` applies to the similarity of both prefetches.
+#[instrument(skip_all, fields(collections = collections.len(), fusion = ?fusion))]
+pub async fn hybrid_search(
+    qdrant: Arc<Qdrant>,
+    base: &SearchPoints,
+    collections: &[String],
+    text: &Condition,
+    fusion: Fusion,
+) -> Result<Vec<ScoredPoint>, AppError> {
+    let text_filter = with_conditions(base.filter.clone(), vec![text.clone()]);
+    let prefetch = |filter: Option<Filter>| {
+        let mut prefetch = PrefetchQueryBuilder::default()
+            .query(Query::new_nearest(base.vector.clone()))
+            .limit(base.limit);
+        if let Some(filter) = filter {
+            prefetch = prefetch.filter(filter);
+        }
+        if let Some(params) = base.params {
+            prefetch = prefetch.params(params);
+        }
+        if let Some(threshold) = base.score_threshold {
+            prefetch = prefetch.score_threshold(threshold);
+        }
+        if let Some(vector_name) = &base.vector_name {
+            prefetch = prefetch.using(vector_name);
+        }
+        prefetch
+    };
+
+    let mut tasks = JoinSet::new();
+    for collection in collections {
+        let mut request = QueryPointsBuilder::new(collection)
+            .add_prefetch(prefetch(base.filter.clone()))
+            .add_prefetch(prefetch(text_filter.clone()))
+            .query(Query::new_fusion(fusion))
+            .limit(base.limit)
+            .with_payload(true);
+        if let Some(value) = base.read_consistency.and_then(|c| c.value) {
+            request = request.read_consistency(value);
+        }
+
+        let qdrant = qdrant.clone();
+        tasks.spawn(async move { qdrant.query(request).await });
+    }
+
+    let mut partials = Vec::with_capacity(tasks.len());
+    while let Some(joined) = tasks.join_next().await {
+        let response = joined
+            .map_err(|e| AppError::Qdrant(format!("Search task failed: {}", e)))?
+            .map_err(|e| AppError::Qdrant(format!("Qdrant hybrid search error: {}", e)))?;
+        partials.push(response.result);
+    }
+
+    Ok(merge_by_score(partials, base.limit as usize))
+}
+
+/// Run `base` per collection as a dense search and a text-matching dense
+/// search, and return the hits of both, each point once, best first
+///
+/// The candidates of weighted fusion; `score_threshold` in `base` applies
+/// to both searches.
+#[instrument(skip_all, fields(collections = collections.len()))]
+pub async fn hybrid_candidates(
+    qdrant: Arc<Qdrant>,
+    base: &SearchPoints,
+    collections: &[String],
+    text: &Condition,
+) -> Result<Vec<ScoredPoint>, AppError> {
+    let text_filter = with_conditions(base.filter.clone(), vec![text.clone()]);
+    let mut tasks = JoinSet::new();
+    for collection in collections {
+        for filter in [base.filter.clone(), text_filter.clone()] {
+            let request = SearchPoints {
+                collection_name: collection.clone(),
+                filter,
+                ..base.clone()
+            };
+            let qdrant = qdrant.clone();
+            tasks.spawn(async move { qdrant.search_points(request).await });
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut candidates = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        let response = joined
+            .map_err(|e| AppError::Qdrant(format!("Search task failed: {}", e)))?
+            .map_err(|e| AppError::Qdrant(format!("Qdrant search error: {}", e)))?;
+        for point in response.result {
+            // A point in both lists has the same similarity in each
+            if seen.insert(point.id.as_ref().map(point_id_to_string)) {
+                candidates.push(point);
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(candidates)
+}
+
+/// Re-score `candidates` by weighted fusion of their vector similarity and
+/// the share of `terms` they match, and keep the best `limit`
+///
+/// `alpha` 1 ranks by similarity alone, 0 by matched words alone.
+pub fn weighted_fusion(
+    mut candidates: Vec<ScoredPoint>,
+    terms: &[String],
+    alpha: f32,
+    limit: usize,
+) -> Vec<ScoredPoint> {
+    let (min, max) = candidates
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), point| {
+            (min.min(point.score), max.max(point.score))
+        });
+    for point in &mut candidates {
+        let similarity = if max > min {
+            (point.score - min) / (max - min)
+        } else {
+            1.0
+        };
+        point.score = alpha * similarity + (1.0 - alpha) * keyword_score(&point.payload, terms);
+    }
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates.truncate(limit);
+    candidates
+}
+
+/// Distinct words of a hybrid query, normalized like the stored text
+pub fn query_terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in words(text) {
+        if !terms.contains(&word) {
+            terms.push(word);
+        }
+    }
+    terms
+}
+
+/// Share of `terms` among the words of the text fields of `payload`
+pub fn keyword_score(payload: &PayloadMap, terms: &[String]) -> f32 {
+    if terms.is_empty() {
+        return 0.0;
+    }
+    let words: HashSet<String> = HYBRID_TEXT_FIELDS
+        .iter()
+        .flat_map(|field| words(&extract_string(payload, field)))
+        .collect();
+    let matched = terms.iter().filter(|term| words.contains(*term)).count();
+    matched as f32 / terms.len() as f32
+}
+
+/// Lowercased words of `text` split at spaces and punctuation, with Thai
+/// segmented
+fn words(text: &str) -> Vec<String> {
+    keyword_terms(text)
+        .split(|c: char| c.is_whitespace() || c.is_ascii_punctuation())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hybrid_fusion() {
+        assert_eq!(parse_hybrid_fusion("RRF").unwrap(), HybridFusion::Rrf);
+        assert_eq!(parse_hybrid_fusion("dbsf").unwrap(), HybridFusion::Dbsf);
+        assert_eq!(parse_hybrid_fusion("filter").unwrap(), HybridFusion::Filter);
+        assert_eq!(
+            parse_hybrid_fusion("Weighted").unwrap(),
+            HybridFusion::Weighted
+        );
+        assert!(parse_hybrid_fusion("sum").is_err());
+    }
+
+    #[test]
+    fn test_text_match_condition() {
+        assert!(text_match_condition("  ").is_none());
+        assert!(text_match_condition("gate truck").is_some());
+        assert!(text_match_condition("รถกระบะสีขาว").is_some());
+    }
+
+    #[test]
+    fn test_weighted_fusion() {
+        use crate::services::PayloadBuilder;
+
+        let terms = query_terms("White pickup, white gate");
+        assert_eq!(terms, ["white", "pickup", "gate"]);
+
+        let hit = |id: u64, score: f32, caption: &str| ScoredPoint {
+            id: Some(id.into()),
+            score,
+            payload: PayloadBuilder::new()
+                .string("filename", format!("cctv01_2025-10-08_06-32_{}.jpg", id))
+                .string("caption", caption)
+                .build(),
+            ..Default::default()
+        };
+        let candidates = vec![
+            hit(1, 0.30, "A red sedan"),
+            hit(2, 0.25, "A white pickup at the north gate"),
+            hit(3, 0.20, "A white van"),
+        ];
+        assert_eq!(keyword_score(&candidates[1].payload, &terms), 1.0);
+        assert_eq!(keyword_score(&candidates[0].payload, &[]), 0.0);
+
+        let ids = |alpha| -> Vec<String> {
+            weighted_fusion(candidates.clone(), &terms, alpha, 2)
+                .iter()
+                .map(|p| point_id_to_string(p.id.as_ref().unwrap()))
+                .collect()
+        };
+        assert_eq!(ids(1.0), ["1", "2"]);
+        assert_eq!(ids(0.0), ["2", "3"]);
+        // Thai captions are matched by their segmented words
+        let thai = hit(4, 0.1, "รถกระบะสีขาว");
+        assert_eq!(keyword_score(&thai.payload, &query_terms("กระบะ")), 1.0);
+    }
+}
@@ -0,0 +1,136 @@
+//! Point ID Collisions
+//!
+//! Upstream image IDs are used as point IDs but are only unique per camera,
+//! so an image from one camera can overwrite another camera's point.
+
+use crate::error::AppError;
+use crate::models::search::CctvImageData;
+use crate::services::{RequestMetrics, extract_string, point_id_to_string};
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{GetPointsBuilder, PointStruct, Value};
+use std::collections::{BTreeMap, HashMap};
+use tracing::{instrument, warn};
+
+/// A point about to be overwritten by an image from a different camera
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdCollision {
+    pub id: String,
+    pub stored_camera_id: String,
+    pub new_camera_id: String,
+}
+
+/// Points whose stored camera differs from the camera being written
+///
+/// `stored` maps point IDs to the `camera_id` currently in Qdrant.
+pub fn camera_mismatches(
+    points: &[PointStruct],
+    stored: &HashMap<String, String>,
+) -> Vec<IdCollision> {
+    points
+        .iter()
+        .filter_map(|point| {
+            let id = point_id_to_string(point.id.as_ref()?);
+            let stored_camera_id = stored.get(&id)?;
+            let new_camera_id = payload_camera_id(&point.payload);
+            (*stored_camera_id != new_camera_id).then(|| IdCollision {
+                id,
+                stored_camera_id: stored_camera_id.clone(),
+                new_camera_id,
+            })
+        })
+        .collect()
+}
+
+/// Look up `points` before an upsert and warn about IDs owned by another camera
+///
+/// The upsert is not prevented; collisions are logged and counted in `metrics`.
+#[instrument(skip(qdrant, points, metrics), fields(points = points.len()))]
+pub async fn detect_id_collisions(
+    qdrant: &Qdrant,
+    collection_name: &str,
+    points: &[PointStruct],
+    metrics: &RequestMetrics,
+) -> Result<Vec<IdCollision>, AppError> {
+    let ids: Vec<_> = points.iter().filter_map(|p| p.id.clone()).collect();
+
+    let response = qdrant
+        .get_points(GetPointsBuilder::new(collection_name, ids).with_payload(true))
+        .await
+        .map_err(|e| AppError::Qdrant(format!("ID collision check failed: {}", e)))?;
+
+    let stored: HashMap<String, String> = response
+        .result
+        .into_iter()
+        .filter_map(|p| {
+            Some((
+                point_id_to_string(p.id.as_ref()?),
+                payload_camera_id(&p.payload),
+            ))
+        })
+        .collect();
+
+    let collisions = camera_mismatches(points, &stored);
+    for collision in &collisions {
+        warn!(
+            id = %collision.id,
+            stored_camera_id = %collision.stored_camera_id,
+            new_camera_id = %collision.new_camera_id,
+            collection = collection_name,
+            "Point ID already used by another camera; overwriting"
+        );
+    }
+    metrics.record_id_collisions(collisions.len() as u64);
+
+    Ok(collisions)
+}
+
+/// Upstream images grouped by ID, keeping only IDs used by several cameras
+pub fn shared_ids(images: &[CctvImageData]) -> BTreeMap<u64, Vec<&CctvImageData>> {
+    let mut by_id: BTreeMap<u64, Vec<&CctvImageData>> = BTreeMap::new();
+    for image in images {
+        by_id.entry(image.id as u64).or_default().push(image);
+    }
+
+    by_id.retain(|_, images| {
+        images
+            .iter()
+            .any(|image| image.cctv_id != images[0].cctv_id)
+    });
+    by_id
+}
+
+fn payload_camera_id(payload: &HashMap<String, Value>) -> String {
+    extract_string(payload, "camera_id")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::PayloadBuilder;
+
+    fn point(id: u64, camera_id: &str) -> PointStruct {
+        PointStruct::new(
+            id,
+            vec![0.0],
+            PayloadBuilder::new().string("camera_id", camera_id).build(),
+        )
+    }
+
+    #[test]
+    fn test_camera_mismatches() {
+        let stored = HashMap::from([
+            ("1".to_string(), "cctv01".to_string()),
+            ("2".to_string(), "cctv01".to_string()),
+        ]);
+        let points = [point(1, "cctv01"), point(2, "cctv02"), point(3, "cctv02")];
+
+        assert_eq!(
+            camera_mismatches(&points, &stored),
+            vec![IdCollision {
+                id: "2".to_string(),
+                stored_camera_id: "cctv01".to_string(),
+                new_camera_id: "cctv02".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Image Captions
+//!
+//! Captions generated at ingest by the AI service captioning endpoint, for
+//! images that arrive without one. Captioning is best effort: images it
+//! fails for are still stored, just without a caption.
+
+use crate::models::search::{BatchImageCaptionResponse, CctvImageData};
+use crate::services::{AiPriority, get_image_captions};
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Generated captions of the `images` without a caption, keyed by path
+///
+/// Nothing is generated without a captioning `endpoint`. A failed call is
+/// logged and leaves every image uncaptioned.
+pub async fn generate_captions(
+    client: &reqwest::Client,
+    base_url: &str,
+    endpoint: Option<&str>,
+    images: &[&CctvImageData],
+) -> HashMap<String, String> {
+    let Some(endpoint) = endpoint else {
+        return HashMap::new();
+    };
+    let paths: Vec<String> = images
+        .iter()
+        .filter(|image| image.caption.is_none())
+        .map(|image| image.file_path.clone())
+        .collect();
+    if paths.is_empty() {
+        return HashMap::new();
+    }
+
+    match get_image_captions(client, base_url, endpoint, paths, AiPriority::Bulk).await {
+        Ok(response) => captions_by_path(response),
+        Err(e) => {
+            warn!(error = %e, "Storing images without generated captions");
+            HashMap::new()
+        }
+    }
+}
+
+/// Caption of `image`: the one it was sent with, else a `generated` one
+pub fn image_caption<'a>(
+    image: &'a CctvImageData,
+    generated: &'a HashMap<String, String>,
+) -> Option<&'a str> {
+    image
+        .caption
+        .as_deref()
+        .or_else(|| generated.get(&image.file_path).map(String::as_str))
+}
+
+/// Non-empty captions of a caption response, keyed by path
+fn captions_by_path(response: BatchImageCaptionResponse) -> HashMap<String, String> {
+    response
+        .results
+        .into_iter()
+        .filter_map(|result| {
+            if let Some(error) = &result.error {
+                warn!(path = %result.path, error = %error, "Captioning failed");
+            }
+            let caption = result.caption?.trim().to_string();
+            (!caption.is_empty()).then_some((result.path, caption))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_captions_by_path() {
+        let response: BatchImageCaptionResponse = serde_json::from_value(serde_json::json!({
+            "results": [
+                { "path": "a.jpg", "caption": " white pickup truck at the gate " },
+                { "path": "b.jpg", "error": "unreadable image" },
+                { "path": "c.jpg", "caption": "" }
+            ]
+        }))
+        .unwrap();
+        let captions = captions_by_path(response);
+        assert_eq!(captions.len(), 1);
+        assert_eq!(captions["a.jpg"], "white pickup truck at the gate");
+    }
+}
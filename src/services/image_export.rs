@@ -0,0 +1,121 @@
+//! Image Export
+//!
+//! Paged scrolling through stored image payloads across the shard collections,
+//! resumable with an opaque page token.
+
+use crate::error::AppError;
+use crate::services::{parse_point_id, point_id_to_string};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{Filter, PointId, RetrievedPoint, ScrollPointsBuilder};
+use tracing::instrument;
+
+/// Position of the next page: a collection and the first point to read there
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageToken {
+    /// Index into the collections being exported
+    pub collection: usize,
+    /// Qdrant scroll offset; `None` starts at the beginning of the collection
+    pub offset: Option<PointId>,
+}
+
+impl PageToken {
+    pub fn encode(&self) -> String {
+        let offset = self.offset.as_ref().map(point_id_to_string);
+        URL_SAFE_NO_PAD.encode(format!(
+            "{}:{}",
+            self.collection,
+            offset.unwrap_or_default()
+        ))
+    }
+
+    pub fn decode(token: &str) -> Result<Self, AppError> {
+        let invalid = || AppError::InvalidRequest(format!("Invalid page_token {:?}", token));
+        let decoded = URL_SAFE_NO_PAD.decode(token).map_err(|_| invalid())?;
+        let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+        let (collection, offset) = decoded.split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            collection: collection.parse().map_err(|_| invalid())?,
+            offset: Some(offset).filter(|o| !o.is_empty()).map(parse_point_id),
+        })
+    }
+}
+
+/// Read up to `limit` points matching `filter`, starting at `token`, walking
+/// `collections` in order
+///
+/// Returns the points and the token of the next page, `None` after the last.
+#[instrument(skip(qdrant, collections, filter), fields(collections = collections.len()))]
+pub async fn scroll_images(
+    qdrant: &Qdrant,
+    collections: &[String],
+    filter: Option<Filter>,
+    limit: u32,
+    token: Option<PageToken>,
+) -> Result<(Vec<RetrievedPoint>, Option<PageToken>), AppError> {
+    let mut position = token.unwrap_or(PageToken {
+        collection: 0,
+        offset: None,
+    });
+    let mut points = Vec::with_capacity(limit as usize);
+
+    while let Some(collection) = collections.get(position.collection) {
+        let remaining = limit - points.len() as u32;
+        if remaining == 0 {
+            return Ok((points, Some(position)));
+        }
+
+        let mut scroll = ScrollPointsBuilder::new(collection)
+            .limit(remaining)
+            .with_payload(true)
+            .with_vectors(false);
+        if let Some(filter) = filter.clone() {
+            scroll = scroll.filter(filter);
+        }
+        if let Some(offset) = position.offset.take() {
+            scroll = scroll.offset(offset);
+        }
+
+        let page = qdrant
+            .scroll(scroll)
+            .await
+            .map_err(|e| AppError::Qdrant(format!("Failed to scroll '{}': {}", collection, e)))?;
+        points.extend(page.result);
+
+        match page.next_page_offset {
+            Some(offset) => position.offset = Some(offset),
+            None => position.collection += 1,
+        }
+    }
+
+    Ok((points, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_token_round_trip() {
+        for token in [
+            PageToken {
+                collection: 0,
+                offset: None,
+            },
+            PageToken {
+                collection: 2,
+                offset: Some(PointId::from(1234)),
+            },
+            PageToken {
+                collection: 1,
+                offset: Some(PointId::from(
+                    "5c56c793-69f3-4fbf-87e6-c4bf54c28c26".to_string(),
+                )),
+            },
+        ] {
+            assert_eq!(PageToken::decode(&token.encode()).unwrap(), token);
+        }
+        assert!(PageToken::decode("not a token").is_err());
+    }
+}
@@ -0,0 +1,139 @@
+//! Ingest Feed
+//!
+//! Broadcasts a notification for every point stored by the scheduler or the
+//! insert endpoints, streamed to live dashboards by `GET /ws`.
+
+use crate::services::{TENANT_FIELD, extract_string};
+use qdrant_client::qdrant::PointStruct;
+use qdrant_client::qdrant::point_id::PointIdOptions;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+/// Notifications a subscriber may fall behind by before it skips ahead
+const FEED_CAPACITY: usize = 1024;
+
+/// A newly stored point
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IngestNotification {
+    pub point_id: u64,
+    pub camera_id: String,
+    pub filename: String,
+    /// Capture time, RFC 3339 (UTC)
+    pub datetime: String,
+    pub vehicle_class: Option<String>,
+    /// Omitted without tenancy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+impl IngestNotification {
+    /// Notification of `point`, read from its payload
+    pub fn from_point(point: &PointStruct) -> Self {
+        let point_id = match point
+            .id
+            .as_ref()
+            .and_then(|id| id.point_id_options.as_ref())
+        {
+            Some(PointIdOptions::Num(n)) => *n,
+            _ => 0,
+        };
+        let vehicle_class = extract_string(&point.payload, "vehicle_class");
+        let tenant_id = extract_string(&point.payload, TENANT_FIELD);
+        Self {
+            point_id,
+            camera_id: extract_string(&point.payload, "camera_id"),
+            filename: extract_string(&point.payload, "filename"),
+            datetime: extract_string(&point.payload, "datetime"),
+            vehicle_class: (!vehicle_class.is_empty()).then_some(vehicle_class),
+            tenant_id: (!tenant_id.is_empty()).then_some(tenant_id),
+        }
+    }
+
+    /// Whether a subscriber of `tenant` may see the point (any without tenancy)
+    pub fn visible_to(&self, tenant: Option<&str>) -> bool {
+        tenant.is_none_or(|tenant| self.tenant_id.as_deref() == Some(tenant))
+    }
+}
+
+/// Fan-out of ingest notifications; publishing without subscribers is free
+#[derive(Debug)]
+pub struct IngestFeed {
+    sender: broadcast::Sender<IngestNotification>,
+    /// Cancelled on shutdown, so streaming subscribers close their connections
+    closing: CancellationToken,
+}
+
+impl Default for IngestFeed {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(FEED_CAPACITY).0,
+            closing: CancellationToken::new(),
+        }
+    }
+}
+
+impl IngestFeed {
+    /// Notify subscribers of stored `points`
+    pub fn publish(&self, points: &[PointStruct]) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+        for point in points {
+            let _ = self.sender.send(IngestNotification::from_point(point));
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<IngestNotification> {
+        self.sender.subscribe()
+    }
+
+    /// Connected subscribers
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Ask subscribers to disconnect, as the server is shutting down
+    pub fn close(&self) {
+        self.closing.cancel();
+    }
+
+    /// Cancelled once `close` is called
+    pub fn closing(&self) -> CancellationToken {
+        self.closing.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::PayloadBuilder;
+
+    #[test]
+    fn test_publish() {
+        let feed = IngestFeed::default();
+        let point = |id, class: Option<&str>| {
+            let payload = PayloadBuilder::new()
+                .string("camera_id", "cctv01")
+                .string("filename", "cctv01_2025-10-08_06-32_123.jpg")
+                .string("datetime", "2025-10-07T23:32:00Z")
+                .string_opt("vehicle_class", class)
+                .string(TENANT_FIELD, "site-a")
+                .build();
+            PointStruct::new(id, vec![0.5], payload)
+        };
+        feed.publish(&[point(1, None)]);
+
+        let mut receiver = feed.subscribe();
+        feed.publish(&[point(12345, Some("pickup")), point(12346, None)]);
+        let first = receiver.try_recv().unwrap();
+        assert_eq!(first.point_id, 12345);
+        assert_eq!(first.camera_id, "cctv01");
+        assert_eq!(first.datetime, "2025-10-07T23:32:00Z");
+        assert_eq!(first.vehicle_class.as_deref(), Some("pickup"));
+        assert!(first.visible_to(Some("site-a")) && !first.visible_to(Some("site-b")));
+        assert_eq!(receiver.try_recv().unwrap().vehicle_class, None);
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(feed.subscribers(), 1);
+    }
+}
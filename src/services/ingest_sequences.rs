@@ -0,0 +1,180 @@
+//! Ingest Sequences
+//!
+//! Batch sequence numbers received from each edge device on
+//! `POST /ingest/batch`. A batch is recorded once it is stored, so a retry
+//! of an acknowledged batch is answered without storing it again, and
+//! sequences skipped by a device are reported as gaps until they arrive.
+
+use crate::error::AppError;
+use crate::models::ingest::SequenceRange;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Gaps kept per device; older ones are forgotten first
+const MAX_TRACKED_GAPS: usize = 100;
+
+/// Whether a batch still has to be stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceCheck {
+    New,
+    /// Already stored and acknowledged
+    Duplicate,
+}
+
+/// Sequences of the current epoch of one device
+#[derive(Debug, Clone, Default)]
+struct DeviceSequence {
+    epoch: u64,
+    highest: u64,
+    /// Sequences below `highest` not received yet, oldest first
+    missing: Vec<SequenceRange>,
+}
+
+impl DeviceSequence {
+    fn is_missing(&self, sequence: u64) -> bool {
+        self.missing
+            .iter()
+            .any(|r| (r.from..=r.to).contains(&sequence))
+    }
+
+    /// Record `sequence`, returning the sequences it showed to be skipped
+    fn record(&mut self, sequence: u64) -> Option<SequenceRange> {
+        if sequence > self.highest {
+            let gap = (sequence > self.highest + 1).then(|| SequenceRange {
+                from: self.highest + 1,
+                to: sequence - 1,
+            });
+            self.missing.extend(gap);
+            if self.missing.len() > MAX_TRACKED_GAPS {
+                let excess = self.missing.len() - MAX_TRACKED_GAPS;
+                self.missing.drain(..excess);
+            }
+            self.highest = sequence;
+            return gap;
+        }
+        if let Some(i) = self
+            .missing
+            .iter()
+            .position(|r| (r.from..=r.to).contains(&sequence))
+        {
+            let range = self.missing.remove(i);
+            let rest = [
+                (range.from < sequence).then(|| SequenceRange {
+                    from: range.from,
+                    to: sequence - 1,
+                }),
+                (sequence < range.to).then(|| SequenceRange {
+                    from: sequence + 1,
+                    to: range.to,
+                }),
+            ];
+            for (offset, part) in rest.into_iter().flatten().enumerate() {
+                self.missing.insert(i + offset, part);
+            }
+        }
+        None
+    }
+}
+
+/// Sequence state of every device that sent a batch since startup
+#[derive(Debug, Default)]
+pub struct IngestSequences {
+    devices: Mutex<HashMap<String, DeviceSequence>>,
+}
+
+impl IngestSequences {
+    /// Whether batch `sequence` of `epoch` from `device_id` has to be stored
+    ///
+    /// Fails for an epoch older than the device's current one: the agent
+    /// that sent it has been restarted since.
+    pub fn check(
+        &self,
+        device_id: &str,
+        epoch: u64,
+        sequence: u64,
+    ) -> Result<SequenceCheck, AppError> {
+        let devices = self.devices.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(device) = devices.get(device_id) else {
+            return Ok(SequenceCheck::New);
+        };
+        if epoch < device.epoch {
+            return Err(AppError::InvalidRequest(format!(
+                "Epoch {} is older than the current epoch {} of device '{}'",
+                epoch, device.epoch, device_id
+            )));
+        }
+        if epoch == device.epoch && sequence <= device.highest && !device.is_missing(sequence) {
+            return Ok(SequenceCheck::Duplicate);
+        }
+        Ok(SequenceCheck::New)
+    }
+
+    /// Record a stored batch, returning the gap it revealed, if any
+    ///
+    /// The first batch seen from a device starts tracking at its sequence,
+    /// so a restart of this service doesn't report earlier batches as lost;
+    /// a new epoch starts over from sequence 1.
+    pub fn acknowledge(&self, device_id: &str, epoch: u64, sequence: u64) -> Option<SequenceRange> {
+        let mut devices = self.devices.lock().unwrap_or_else(|e| e.into_inner());
+        let device = devices
+            .entry(device_id.to_string())
+            .or_insert_with(|| DeviceSequence {
+                epoch,
+                highest: sequence.saturating_sub(1),
+                missing: Vec::new(),
+            });
+        if epoch > device.epoch {
+            *device = DeviceSequence {
+                epoch,
+                ..Default::default()
+            };
+        }
+        device.record(sequence)
+    }
+
+    /// Sequences of `device_id`'s current epoch not received yet
+    pub fn missing(&self, device_id: &str) -> Vec<SequenceRange> {
+        let devices = self.devices.lock().unwrap_or_else(|e| e.into_inner());
+        devices
+            .get(device_id)
+            .map(|device| device.missing.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(from: u64, to: u64) -> SequenceRange {
+        SequenceRange { from, to }
+    }
+
+    #[test]
+    fn test_sequences() {
+        let sequences = IngestSequences::default();
+        assert_eq!(sequences.check("gate_a", 1, 7).unwrap(), SequenceCheck::New);
+        assert_eq!(sequences.acknowledge("gate_a", 1, 7), None);
+        assert_eq!(
+            sequences.check("gate_a", 1, 7).unwrap(),
+            SequenceCheck::Duplicate
+        );
+
+        // 8, 10 and 11 arrive after later batches
+        assert_eq!(sequences.acknowledge("gate_a", 1, 9), Some(range(8, 8)));
+        assert_eq!(sequences.acknowledge("gate_a", 1, 12), Some(range(10, 11)));
+        assert_eq!(sequences.missing("gate_a"), [range(8, 8), range(10, 11)]);
+        assert_eq!(
+            sequences.check("gate_a", 1, 10).unwrap(),
+            SequenceCheck::New
+        );
+        sequences.acknowledge("gate_a", 1, 10);
+        sequences.acknowledge("gate_a", 1, 8);
+        assert_eq!(sequences.missing("gate_a"), [range(11, 11)]);
+
+        // A restarted agent starts a new epoch; the old one is refused
+        assert_eq!(sequences.acknowledge("gate_a", 2, 2), Some(range(1, 1)));
+        assert!(sequences.check("gate_a", 1, 13).is_err());
+        assert!(sequences.missing("gate_b").is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+//! Ingest Throttle
+//!
+//! Paces the upserts of fetch runs and backfills to a points-per-second
+//! limit that depends on the local hour, so bulk ingestion leaves Qdrant
+//! headroom for searches during busy hours and catches up at night.
+
+use crate::config::Tunables;
+use crate::error::AppError;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Local hours from `start` (inclusive) to `end` (exclusive, up to 24),
+/// wrapping past midnight when `start > end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusyHours {
+    start: u32,
+    end: u32,
+}
+
+impl BusyHours {
+    pub fn contains(self, hour: u32) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&hour)
+        } else {
+            hour >= self.start || hour < self.end
+        }
+    }
+}
+
+/// Parse busy hours such as `07-20` or `22-02`
+pub fn parse_busy_hours(value: &str) -> Result<BusyHours, AppError> {
+    let invalid = || {
+        AppError::Parse(format!(
+            "Invalid busy hours '{}': expected <start>-<end> with hours 0-24, e.g. 07-20",
+            value
+        ))
+    };
+    let (start, end) = value.trim().split_once('-').ok_or_else(invalid)?;
+    let hour = |h: &str| h.trim().parse::<u32>().ok().filter(|h| *h <= 24);
+    let (start, end) = (
+        hour(start).ok_or_else(invalid)?,
+        hour(end).ok_or_else(invalid)?,
+    );
+    if start == 24 || start == end {
+        return Err(invalid());
+    }
+    Ok(BusyHours { start, end })
+}
+
+/// Points per second bulk ingestion may upsert at local `hour` (`None` = unlimited)
+pub fn ingest_rate(tunables: &Tunables, hour: u32) -> Option<u32> {
+    let busy = tunables
+        .ingest_busy_hours
+        .as_deref()
+        .and_then(|hours| parse_busy_hours(hours).ok())
+        .is_some_and(|hours| hours.contains(hour));
+    if busy {
+        tunables.ingest_busy_points_per_sec
+    } else {
+        tunables.ingest_off_peak_points_per_sec
+    }
+}
+
+/// Process-wide pacing of bulk upserts, shared by fetch runs and backfills
+#[derive(Debug, Default)]
+pub struct IngestThrottle {
+    /// When the next upsert may start
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl IngestThrottle {
+    /// Wait until `points` may be upserted at `points_per_sec`, returning the wait
+    ///
+    /// Each call reserves the time its points take at that rate, so a burst
+    /// of upserts is spread out instead of sent at once.
+    pub async fn pace(&self, points: usize, points_per_sec: Option<u32>) -> Duration {
+        let start = {
+            let mut next_slot = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(rate) = points_per_sec else {
+                *next_slot = None;
+                return Duration::ZERO;
+            };
+            let now = Instant::now();
+            let start = next_slot.filter(|slot| *slot > now).unwrap_or(now);
+            *next_slot = Some(start + Duration::from_secs_f64(points as f64 / rate as f64));
+            start
+        };
+        let wait = start.saturating_duration_since(Instant::now());
+        tokio::time::sleep_until(start).await;
+        wait
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_busy_hours() {
+        let day = parse_busy_hours("07-20").unwrap();
+        assert!(day.contains(7) && day.contains(19));
+        assert!(!day.contains(20) && !day.contains(3));
+
+        let night = parse_busy_hours("22-02").unwrap();
+        assert!(night.contains(23) && night.contains(1));
+        assert!(!night.contains(2) && !night.contains(12));
+
+        assert!(parse_busy_hours("18-24").unwrap().contains(23));
+        assert!(parse_busy_hours("00-24").unwrap().contains(0));
+        assert!(parse_busy_hours("7").is_err());
+        assert!(parse_busy_hours("07-25").is_err());
+        assert!(parse_busy_hours("08-08").is_err());
+    }
+}
@@ -0,0 +1,58 @@
+//! Maintenance Mode
+//!
+//! Process-wide switch that pauses ingestion and rejects API traffic.
+
+use std::sync::RwLock;
+
+/// Message returned to clients when none is given
+pub const DEFAULT_MAINTENANCE_MESSAGE: &str = "Service is under maintenance, please retry later";
+
+/// Maintenance switch shared by the HTTP workers and the scheduler
+#[derive(Default)]
+pub struct MaintenanceMode {
+    /// `Some(message)` while maintenance is active
+    state: RwLock<Option<String>>,
+}
+
+impl MaintenanceMode {
+    /// Enter maintenance mode with an optional client-facing message
+    pub fn enable(&self, message: Option<String>) {
+        let message = message.unwrap_or_else(|| DEFAULT_MAINTENANCE_MESSAGE.to_string());
+        *self.state.write().unwrap_or_else(|e| e.into_inner()) = Some(message);
+    }
+
+    /// Leave maintenance mode
+    pub fn disable(&self) {
+        *self.state.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Current maintenance message, or `None` when the service is live
+    pub fn message(&self) -> Option<String> {
+        self.state.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.message().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maintenance_toggle() {
+        let mode = MaintenanceMode::default();
+        assert!(!mode.is_enabled());
+
+        mode.enable(None);
+        assert_eq!(mode.message().as_deref(), Some(DEFAULT_MAINTENANCE_MESSAGE));
+
+        mode.enable(Some("Qdrant upgrade".to_string()));
+        assert_eq!(mode.message().as_deref(), Some("Qdrant upgrade"));
+
+        mode.disable();
+        assert!(!mode.is_enabled());
+    }
+}
@@ -0,0 +1,350 @@
+//! Request Metrics
+//!
+//! Per-endpoint request counters and latency totals, rendered in the
+//! Prometheus text exposition format.
+
+use crate::services::SloStatus;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counters for one route and status code
+#[derive(Debug, Default, Clone, Copy)]
+struct EndpointStats {
+    count: u64,
+    latency_ms_sum: u64,
+}
+
+/// Request metrics shared by the HTTP workers
+#[derive(Default)]
+pub struct RequestMetrics {
+    endpoints: Mutex<BTreeMap<(String, u16), EndpointStats>>,
+    upserts_verified: AtomicU64,
+    upsert_mismatches: AtomicU64,
+    upsert_verification_errors: AtomicU64,
+    id_collisions: AtomicU64,
+    /// Defective embeddings, by source and issue
+    embedding_issues: Mutex<BTreeMap<(&'static str, &'static str), u64>>,
+    dead_letters: AtomicU64,
+    shadow_searches: AtomicU64,
+    shadow_top_hit_mismatches: AtomicU64,
+    shadow_errors: AtomicU64,
+    shadow_skipped: AtomicU64,
+    backup_verifications: AtomicU64,
+    backup_verification_failures: AtomicU64,
+    embedding_cache_hits: AtomicU64,
+    embedding_cache_misses: AtomicU64,
+    payload_truncations: AtomicU64,
+    payload_rejections: AtomicU64,
+    edge_duplicate_batches: AtomicU64,
+    edge_missing_batches: AtomicU64,
+    /// Sum of shadow result overlaps, for the mean per shadow search
+    shadow_overlap_sum: Mutex<f64>,
+}
+
+impl RequestMetrics {
+    /// Record a finished request for a route pattern
+    pub fn record(&self, path: &str, status: u16, elapsed_ms: u64) {
+        let mut endpoints = self.endpoints.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = endpoints.entry((path.to_string(), status)).or_default();
+        stats.count += 1;
+        stats.latency_ms_sum += elapsed_ms;
+    }
+
+    /// Record the outcome of an upsert read-back verification
+    pub fn record_upsert_verification(&self, checked: u64, mismatches: u64) {
+        self.upserts_verified.fetch_add(checked, Ordering::Relaxed);
+        self.upsert_mismatches
+            .fetch_add(mismatches, Ordering::Relaxed);
+    }
+
+    /// Record a read-back verification that could not be performed
+    pub fn record_upsert_verification_error(&self) {
+        self.upsert_verification_errors
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record points about to be overwritten by an image from another camera
+    pub fn record_id_collisions(&self, collisions: u64) {
+        self.id_collisions.fetch_add(collisions, Ordering::Relaxed);
+    }
+
+    /// Record a defective embedding received for `source`
+    pub fn record_embedding_issue(&self, source: &'static str, issue: &'static str) {
+        let mut issues = self
+            .embedding_issues
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        *issues.entry((source, issue)).or_default() += 1;
+    }
+
+    /// Record an image written to the dead-letter queue
+    pub fn record_dead_letter(&self) {
+        self.dead_letters.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a shadow search compared with the live results
+    pub fn record_shadow_search(&self, overlap: f64, top_hit_matches: bool) {
+        self.shadow_searches.fetch_add(1, Ordering::Relaxed);
+        if !top_hit_matches {
+            self.shadow_top_hit_mismatches
+                .fetch_add(1, Ordering::Relaxed);
+        }
+        *self
+            .shadow_overlap_sum
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) += overlap;
+    }
+
+    /// Record a shadow search that failed
+    pub fn record_shadow_error(&self) {
+        self.shadow_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a sampled shadow search dropped because too many were running
+    pub fn record_shadow_skipped(&self) {
+        self.shadow_skipped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a finished backup verification run
+    pub fn record_backup_verification(&self, passed: bool) {
+        self.backup_verifications.fetch_add(1, Ordering::Relaxed);
+        if !passed {
+            self.backup_verification_failures
+                .fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Record a text embedding lookup in the embedding cache
+    pub fn record_embedding_cache(&self, hit: bool) {
+        let counter = if hit {
+            &self.embedding_cache_hits
+        } else {
+            &self.embedding_cache_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record free-text payload fields cut to the text limit
+    pub fn record_payload_truncations(&self, fields: u64) {
+        self.payload_truncations
+            .fetch_add(fields, Ordering::Relaxed);
+    }
+
+    /// Record an image not stored because of its payload size
+    pub fn record_payload_rejection(&self) {
+        self.payload_rejections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record an edge batch resent after it was stored
+    pub fn record_edge_duplicate(&self) {
+        self.edge_duplicate_batches.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record edge batch sequences skipped by a device
+    pub fn record_edge_sequence_gap(&self, batches: u64) {
+        self.edge_missing_batches
+            .fetch_add(batches, Ordering::Relaxed);
+    }
+
+    /// Render request metrics and SLO state in Prometheus text format
+    pub fn render(&self, slos: &[SloStatus]) -> String {
+        let endpoints = self.endpoints.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+
+        out.push_str("# HELP http_requests_total Requests handled, by route and status\n");
+        out.push_str("# TYPE http_requests_total counter\n");
+        for ((path, status), stats) in endpoints.iter() {
+            let _ = writeln!(
+                out,
+                "http_requests_total{{path=\"{}\",status=\"{}\"}} {}",
+                path, status, stats.count
+            );
+        }
+
+        out.push_str(
+            "# HELP http_request_duration_ms_sum Total request latency, by route and status\n",
+        );
+        out.push_str("# TYPE http_request_duration_ms_sum counter\n");
+        for ((path, status), stats) in endpoints.iter() {
+            let _ = writeln!(
+                out,
+                "http_request_duration_ms_sum{{path=\"{}\",status=\"{}\"}} {}",
+                path, status, stats.latency_ms_sum
+            );
+        }
+
+        for (name, help, value) in [
+            (
+                "upsert_verified_points_total",
+                "Points read back after upsert",
+                &self.upserts_verified,
+            ),
+            (
+                "upsert_verification_mismatches_total",
+                "Points missing or with differing payload after upsert",
+                &self.upsert_mismatches,
+            ),
+            (
+                "upsert_verification_errors_total",
+                "Upsert read-backs that failed",
+                &self.upsert_verification_errors,
+            ),
+            (
+                "point_id_collisions_total",
+                "Upserts that overwrote a point stored for a different camera",
+                &self.id_collisions,
+            ),
+            (
+                "dead_letters_total",
+                "Images written to the dead-letter queue",
+                &self.dead_letters,
+            ),
+            (
+                "shadow_searches_total",
+                "Shadow searches compared with the live results",
+                &self.shadow_searches,
+            ),
+            (
+                "shadow_top_hit_mismatches_total",
+                "Shadow searches whose first hit differed from the live one",
+                &self.shadow_top_hit_mismatches,
+            ),
+            (
+                "shadow_search_errors_total",
+                "Shadow searches that failed",
+                &self.shadow_errors,
+            ),
+            (
+                "shadow_searches_skipped_total",
+                "Sampled shadow searches dropped because too many were running",
+                &self.shadow_skipped,
+            ),
+            (
+                "backup_verifications_total",
+                "Restore checks of the newest snapshots",
+                &self.backup_verifications,
+            ),
+            (
+                "backup_verification_failures_total",
+                "Restore checks in which a snapshot failed",
+                &self.backup_verification_failures,
+            ),
+            (
+                "embedding_cache_hits_total",
+                "Text embeddings served from the embedding cache",
+                &self.embedding_cache_hits,
+            ),
+            (
+                "embedding_cache_misses_total",
+                "Text embeddings requested from the AI service after a cache miss",
+                &self.embedding_cache_misses,
+            ),
+            (
+                "payload_fields_truncated_total",
+                "Free-text payload fields cut to PAYLOAD_MAX_TEXT_BYTES at ingest",
+                &self.payload_truncations,
+            ),
+            (
+                "payload_rejections_total",
+                "Images not stored because their payload was too large",
+                &self.payload_rejections,
+            ),
+            (
+                "edge_duplicate_batches_total",
+                "Edge batches resent after they were stored, acknowledged without storing",
+                &self.edge_duplicate_batches,
+            ),
+            (
+                "edge_missing_batches_total",
+                "Edge batch sequences skipped by a device, counted when the gap is seen",
+                &self.edge_missing_batches,
+            ),
+        ] {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, value.load(Ordering::Relaxed));
+        }
+
+        out.push_str(
+            "# HELP shadow_overlap_sum Sum of shadow/live result overlaps (0 to 1 each)\n",
+        );
+        out.push_str("# TYPE shadow_overlap_sum counter\n");
+        let _ = writeln!(
+            out,
+            "shadow_overlap_sum {}",
+            self.shadow_overlap_sum
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+        );
+
+        out.push_str(
+            "# HELP embedding_quality_issues_total Defective embeddings, by source and issue\n",
+        );
+        out.push_str("# TYPE embedding_quality_issues_total counter\n");
+        let issues = self
+            .embedding_issues
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        for ((source, issue), count) in issues.iter() {
+            let _ = writeln!(
+                out,
+                "embedding_quality_issues_total{{source=\"{}\",issue=\"{}\"}} {}",
+                source, issue, count
+            );
+        }
+
+        if slos.is_empty() {
+            return out;
+        }
+
+        out.push_str(
+            "# HELP slo_objective Target fraction of requests under the latency threshold\n",
+        );
+        out.push_str("# TYPE slo_objective gauge\n");
+        for slo in slos {
+            let _ = writeln!(
+                out,
+                "slo_objective{{path=\"{}\",threshold_ms=\"{}\"}} {}",
+                slo.target.path, slo.target.threshold_ms, slo.target.objective
+            );
+        }
+
+        out.push_str("# HELP slo_window_requests Requests in the SLO window\n");
+        out.push_str("# TYPE slo_window_requests gauge\n");
+        for slo in slos {
+            let _ = writeln!(
+                out,
+                "slo_window_requests{{path=\"{}\"}} {}",
+                slo.target.path, slo.total
+            );
+        }
+
+        out.push_str(
+            "# HELP slo_window_good_requests Requests in the SLO window that met the objective\n",
+        );
+        out.push_str("# TYPE slo_window_good_requests gauge\n");
+        for slo in slos {
+            let _ = writeln!(
+                out,
+                "slo_window_good_requests{{path=\"{}\"}} {}",
+                slo.target.path, slo.good
+            );
+        }
+
+        out.push_str(
+            "# HELP slo_burn_rate Error budget burn rate over the SLO window (1 = on budget)\n",
+        );
+        out.push_str("# TYPE slo_burn_rate gauge\n");
+        for slo in slos {
+            let _ = writeln!(
+                out,
+                "slo_burn_rate{{path=\"{}\"}} {:.4}",
+                slo.target.path, slo.burn_rate
+            );
+        }
+
+        out
+    }
+}
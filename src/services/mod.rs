@@ -1,15 +1,159 @@
 //! Services Module
 //!
-//! Re-exports all service functions for convenient access.
+//! Re-exports all service functions for convenient access. Services only
+//! the HTTP API uses (searches, exports, analytics, backups) are left out of
+//! edge ingest builds.
 
+mod ai_dispatch;
+mod ai_rate_limit;
 mod ai_service;
+mod alerts;
+#[cfg(feature = "server")]
+mod backup_verification;
+#[cfg(feature = "server")]
+mod browse_search;
+mod camera_registry;
+#[cfg(feature = "server")]
+mod cases;
 pub mod cctv_service;
+mod chaos;
+mod circuit_breaker;
+#[cfg(feature = "server")]
+mod collection_stats;
+mod content_integrity;
+mod dead_letters;
+#[cfg(feature = "server")]
+mod embedding_cache;
+mod embedding_models;
+mod embedding_quality;
+#[cfg(feature = "server")]
+mod evaluation;
+#[cfg(feature = "server")]
+mod event_correlation;
 mod filename_utils;
+#[cfg(feature = "server")]
+mod health;
+#[cfg(feature = "server")]
+mod hybrid_calibration;
+mod hybrid_search;
+mod id_collisions;
+mod image_captions;
+#[cfg(feature = "server")]
+mod image_export;
+mod ingest_feed;
+#[cfg(feature = "server")]
+mod ingest_sequences;
+mod ingest_throttle;
+mod maintenance;
+mod metrics;
 mod payload_builder;
+mod payload_limits;
 mod qdrant_service;
+#[cfg(feature = "server")]
+mod query_arithmetic;
+#[cfg(feature = "server")]
+mod query_image;
+mod recency_boost;
+#[cfg(feature = "server")]
+mod result_collapse;
+mod scheduler_runs;
+mod search_fanout;
+#[cfg(feature = "server")]
+mod search_groups;
+#[cfg(feature = "server")]
+mod search_sessions;
+#[cfg(feature = "server")]
+mod search_tuning;
+#[cfg(feature = "server")]
+mod shadow_search;
+#[cfg(feature = "server")]
+mod shard_rebalance;
+mod shard_router;
+mod slo;
+#[cfg(feature = "server")]
+mod snapshots;
+mod stored_images;
+mod tenants;
+mod thai_text;
+mod upsert_verification;
+mod url_rewrite;
+mod vector_layout;
+mod vehicle_types;
+mod webhooks;
 
 // Re-export all public items
+pub use ai_dispatch::*;
+pub use ai_rate_limit::*;
 pub use ai_service::*;
+pub use alerts::*;
+#[cfg(feature = "server")]
+pub use backup_verification::*;
+#[cfg(feature = "server")]
+pub use browse_search::*;
+pub use camera_registry::*;
+#[cfg(feature = "server")]
+pub use cases::*;
+pub use chaos::*;
+pub use circuit_breaker::*;
+#[cfg(feature = "server")]
+pub use collection_stats::*;
+pub use content_integrity::*;
+pub use dead_letters::*;
+#[cfg(feature = "server")]
+pub use embedding_cache::*;
+pub use embedding_models::*;
+pub use embedding_quality::*;
+#[cfg(feature = "server")]
+pub use evaluation::*;
+#[cfg(feature = "server")]
+pub use event_correlation::*;
 pub use filename_utils::*;
+#[cfg(feature = "server")]
+pub use health::*;
+#[cfg(feature = "server")]
+pub use hybrid_calibration::*;
+pub use hybrid_search::*;
+pub use id_collisions::*;
+pub use image_captions::*;
+#[cfg(feature = "server")]
+pub use image_export::*;
+pub use ingest_feed::*;
+#[cfg(feature = "server")]
+pub use ingest_sequences::*;
+pub use ingest_throttle::*;
+pub use maintenance::*;
+pub use metrics::*;
 pub use payload_builder::*;
+pub use payload_limits::*;
 pub use qdrant_service::*;
+#[cfg(feature = "server")]
+pub use query_arithmetic::*;
+#[cfg(feature = "server")]
+pub use query_image::*;
+pub use recency_boost::*;
+#[cfg(feature = "server")]
+pub use result_collapse::*;
+pub use scheduler_runs::*;
+pub use search_fanout::*;
+#[cfg(feature = "server")]
+pub use search_groups::*;
+#[cfg(feature = "server")]
+pub use search_sessions::*;
+#[cfg(feature = "server")]
+pub use search_tuning::*;
+#[cfg(feature = "server")]
+pub use shadow_search::*;
+#[cfg(feature = "server")]
+pub use shard_rebalance::*;
+pub use shard_router::*;
+pub use slo::*;
+#[cfg(feature = "server")]
+pub use snapshots::*;
+pub use stored_images::*;
+pub use tenants::*;
+pub use thai_text::*;
+pub use upsert_verification::*;
+pub use url_rewrite::*;
+pub use vector_layout::*;
+pub use vehicle_types::*;
+pub use webhooks::*;
@@ -2,7 +2,7 @@
 //!
 //! Utilities for building Qdrant payloads with less boilerplate.
 
-use qdrant_client::qdrant::{value::Kind, Value};
+use qdrant_client::qdrant::{Value, value::Kind};
 use std::collections::HashMap;
 
 /// Type alias for Qdrant payload map
@@ -35,7 +35,6 @@ impl PayloadBuilder {
 
     /// Insert an optional string value (skips if None)
     #[inline]
-    #[allow(dead_code)]
     pub fn string_opt(self, key: impl Into<String>, value: Option<impl Into<String>>) -> Self {
         match value {
             Some(v) => self.string(key, v),
@@ -77,6 +76,23 @@ impl PayloadBuilder {
         self
     }
 
+    /// Insert a nested object of JSON values (skips if it has no fields)
+    pub fn object<'a>(
+        mut self,
+        key: impl Into<String>,
+        fields: impl IntoIterator<Item = (&'a String, &'a serde_json::Value)>,
+    ) -> Self {
+        let fields: serde_json::Map<String, serde_json::Value> = fields
+            .into_iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        if !fields.is_empty() {
+            self.map
+                .insert(key.into(), serde_json::Value::Object(fields).into());
+        }
+        self
+    }
+
     /// Build the final payload map
     #[inline]
     pub fn build(self) -> PayloadMap {
@@ -97,6 +113,31 @@ pub fn extract_string(payload: &PayloadMap, key: &str) -> String {
         .unwrap_or_default()
 }
 
+/// Extract integer from Qdrant payload value
+#[inline]
+pub fn extract_integer(payload: &PayloadMap, key: &str) -> Option<i64> {
+    payload
+        .get(key)
+        .and_then(|v| v.kind.as_ref())
+        .and_then(|k| match k {
+            Kind::IntegerValue(i) => Some(*i),
+            _ => None,
+        })
+}
+
+/// Extract double from Qdrant payload value
+#[inline]
+pub fn extract_double(payload: &PayloadMap, key: &str) -> Option<f64> {
+    payload
+        .get(key)
+        .and_then(|v| v.kind.as_ref())
+        .and_then(|k| match k {
+            Kind::DoubleValue(d) => Some(*d),
+            Kind::IntegerValue(i) => Some(*i as f64),
+            _ => None,
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,8 +148,19 @@ mod tests {
             .string("image", "test.jpg")
             .integer("frame", 42)
             .double("confidence", 0.95)
+            .object("extra", &HashMap::from([("lane".to_string(), 2.into())]))
+            .object("empty", &HashMap::new())
             .build();
 
         assert_eq!(extract_string(&payload, "image"), "test.jpg");
+        assert_eq!(extract_integer(&payload, "frame"), Some(42));
+        assert_eq!(extract_double(&payload, "confidence"), Some(0.95));
+        assert_eq!(extract_integer(&payload, "image"), None);
+        assert_eq!(extract_double(&payload, "missing"), None);
+        assert!(matches!(
+            payload["extra"].kind,
+            Some(Kind::StructValue(ref s)) if s.fields["lane"].kind == Some(Kind::IntegerValue(2))
+        ));
+        assert!(!payload.contains_key("empty"));
     }
 }
@@ -0,0 +1,215 @@
+//! Payload Limits
+//!
+//! Size checks on the payload of ingested points. Free-text fields (the
+//! caption and strings under `extra`) longer than the text limit are
+//! truncated or rejected, and a payload still too large is rejected with a
+//! clear error instead of an opaque upsert failure.
+
+use crate::error::AppError;
+use crate::services::{CAPTION_TERMS_FIELD, EXTRA_FIELD, PayloadMap, RequestMetrics};
+use qdrant_client::qdrant::Value;
+use qdrant_client::qdrant::value::Kind;
+use tracing::warn;
+
+/// Payload fields holding free text, which may be truncated
+const TEXT_FIELDS: &[&str] = &["caption", CAPTION_TERMS_FIELD, EXTRA_FIELD];
+
+/// What happens to free-text values over the text limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OversizePolicy {
+    /// Cut them to the limit and store the image
+    #[default]
+    Truncate,
+    /// Refuse to store the image
+    Reject,
+}
+
+/// Parse an oversize policy: `truncate` or `reject`
+pub fn parse_oversize_policy(value: &str) -> Result<OversizePolicy, AppError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "truncate" => Ok(OversizePolicy::Truncate),
+        "reject" => Ok(OversizePolicy::Reject),
+        _ => Err(AppError::Parse(format!(
+            "Invalid oversize policy '{}': expected truncate or reject",
+            value
+        ))),
+    }
+}
+
+/// Size limits of a point payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PayloadLimits {
+    /// Estimated JSON size of a whole payload
+    pub max_bytes: usize,
+    /// Longest free-text value
+    pub max_text_bytes: usize,
+    pub policy: OversizePolicy,
+}
+
+impl PayloadLimits {
+    /// Apply the limits to the payload of `subject` (e.g. its filename)
+    ///
+    /// Truncated fields are logged and counted; a rejected payload returns an
+    /// unprocessable error naming the fields or the size.
+    pub fn enforce(
+        &self,
+        payload: &mut PayloadMap,
+        subject: &str,
+        metrics: &RequestMetrics,
+    ) -> Result<(), AppError> {
+        let mut oversized = Vec::new();
+        for field in TEXT_FIELDS {
+            if let Some(value) = payload.get_mut(*field) {
+                let truncate = self.policy == OversizePolicy::Truncate;
+                limit_text(value, field, self.max_text_bytes, truncate, &mut oversized);
+            }
+        }
+        if !oversized.is_empty() {
+            let fields = oversized.join(", ");
+            if self.policy == OversizePolicy::Reject {
+                metrics.record_payload_rejection();
+                return Err(AppError::Unprocessable(format!(
+                    "Payload fields over PAYLOAD_MAX_TEXT_BYTES={}: {}",
+                    self.max_text_bytes, fields
+                )));
+            }
+            metrics.record_payload_truncations(oversized.len() as u64);
+            warn!(
+                subject,
+                fields = %fields,
+                max_text_bytes = self.max_text_bytes,
+                "Truncated payload fields"
+            );
+        }
+
+        let bytes = estimate_payload_bytes(payload);
+        if bytes > self.max_bytes {
+            metrics.record_payload_rejection();
+            return Err(AppError::Unprocessable(format!(
+                "Payload is about {} bytes, over PAYLOAD_MAX_BYTES={}",
+                bytes, self.max_bytes
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Approximate size of a payload serialized as JSON
+pub fn estimate_payload_bytes(payload: &PayloadMap) -> usize {
+    2 + payload
+        .iter()
+        .map(|(key, value)| key.len() + 4 + value_bytes(value))
+        .sum::<usize>()
+}
+
+fn value_bytes(value: &Value) -> usize {
+    match &value.kind {
+        None | Some(Kind::NullValue(_)) => 4,
+        Some(Kind::BoolValue(_)) => 5,
+        Some(Kind::IntegerValue(i)) => i.to_string().len(),
+        Some(Kind::DoubleValue(d)) => d.to_string().len(),
+        Some(Kind::StringValue(s)) => s.len() + 2,
+        Some(Kind::StructValue(s)) => estimate_payload_bytes(&s.fields),
+        Some(Kind::ListValue(list)) => {
+            2 + list
+                .values
+                .iter()
+                .map(|v| value_bytes(v) + 1)
+                .sum::<usize>()
+        }
+    }
+}
+
+/// Record (and, with `truncate`, cut) the strings in `value` over `max_bytes`
+fn limit_text(
+    value: &mut Value,
+    path: &str,
+    max_bytes: usize,
+    truncate: bool,
+    oversized: &mut Vec<String>,
+) {
+    match &mut value.kind {
+        Some(Kind::StringValue(s)) if s.len() > max_bytes => {
+            oversized.push(path.to_string());
+            if truncate {
+                let mut end = max_bytes;
+                while !s.is_char_boundary(end) {
+                    end -= 1;
+                }
+                s.truncate(end);
+            }
+        }
+        Some(Kind::StructValue(s)) => {
+            for (key, field) in s.fields.iter_mut() {
+                let path = format!("{}.{}", path, key);
+                limit_text(field, &path, max_bytes, truncate, oversized);
+            }
+        }
+        Some(Kind::ListValue(list)) => {
+            for (i, item) in list.values.iter_mut().enumerate() {
+                let path = format!("{}[{}]", path, i);
+                limit_text(item, &path, max_bytes, truncate, oversized);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{PayloadBuilder, extract_string};
+
+    fn payload() -> PayloadMap {
+        let extra = serde_json::Map::from_iter([
+            ("notes".to_string(), "ทะเบียน กข 1234".into()),
+            ("lane".to_string(), 2.into()),
+        ]);
+        PayloadBuilder::new()
+            .string(
+                "image",
+                "https://example.com/images/a-very-long-file-name.jpg",
+            )
+            .string("caption", "white pickup truck")
+            .object(EXTRA_FIELD, &extra)
+            .build()
+    }
+
+    #[test]
+    fn test_enforce() {
+        let metrics = RequestMetrics::default();
+        let limits = PayloadLimits {
+            max_bytes: 1024,
+            max_text_bytes: 10,
+            policy: OversizePolicy::Truncate,
+        };
+        let mut truncated = payload();
+        limits.enforce(&mut truncated, "a.jpg", &metrics).unwrap();
+        assert_eq!(extract_string(&truncated, "caption"), "white pick");
+        // Thai characters are 3 bytes each, cut at a character boundary
+        let extra = truncated[EXTRA_FIELD].clone().into_json();
+        assert_eq!(extra["notes"], "ทะเ");
+        assert_eq!(
+            extract_string(&truncated, "image"),
+            "https://example.com/images/a-very-long-file-name.jpg"
+        );
+
+        let reject = PayloadLimits {
+            policy: OversizePolicy::Reject,
+            ..limits
+        };
+        let error = reject
+            .enforce(&mut payload(), "a.jpg", &metrics)
+            .unwrap_err();
+        assert!(error.message().contains("caption, extra.notes"));
+
+        let small = PayloadLimits {
+            max_bytes: 64,
+            ..limits
+        };
+        assert!(matches!(
+            small.enforce(&mut payload(), "a.jpg", &metrics),
+            Err(AppError::Unprocessable(_))
+        ));
+    }
+}
@@ -1,36 +1,297 @@
 //! Qdrant Service
-//! 
+//!
 //! Functions for interacting with Qdrant vector database.
 
-use qdrant_client::qdrant::{CreateCollection, CreateFieldIndexCollectionBuilder, Distance, FieldType, VectorParams};
+use crate::config::{Config, Tunables};
+use crate::error::AppError;
+use crate::services::{CAPTION_VECTOR, IMAGE_VECTOR, VectorLayout, rfc3339_to_timestamp};
 use qdrant_client::Qdrant;
+use qdrant_client::qdrant::point_id::PointIdOptions;
+use qdrant_client::qdrant::read_consistency::Value as ConsistencyValue;
+use qdrant_client::qdrant::vectors_config::Config as VectorsConfigKind;
+use qdrant_client::qdrant::{
+    CollectionParams, CreateCollection, CreateFieldIndexCollectionBuilder, Distance, FieldType,
+    VectorParams,
+};
+use qdrant_client::qdrant::{
+    Condition, DatetimeRange, DeletePointsBuilder, Filter, PointId, Range, ReadConsistency,
+    ReadConsistencyType, WriteOrdering, WriteOrderingType,
+};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{info, instrument, warn};
+
+/// Build the Qdrant gRPC client from the connection settings in `config`
+///
+/// TLS is used for `https://` URLs. A custom CA replaces the system roots
+/// for Qdrant connections only; reqwest clients use their bundled roots.
+pub fn build_qdrant_client(config: &Config) -> Result<Qdrant, AppError> {
+    if let Some(ca_cert) = &config.qdrant_ca_cert {
+        let pem = std::fs::read_to_string(ca_cert).map_err(|e| {
+            AppError::Io(format!("Failed to read QDRANT_CA_CERT {}: {}", ca_cert, e))
+        })?;
+        if !pem.contains("-----BEGIN CERTIFICATE-----") {
+            return Err(AppError::Config(format!(
+                "QDRANT_CA_CERT {} is not a PEM certificate",
+                ca_cert
+            )));
+        }
+
+        // The client loads its trust roots via rustls-native-certs, which reads SSL_CERT_FILE.
+        // SAFETY: called once at startup, before any Qdrant connection is made and
+        // before the HTTP workers and scheduler threads are started.
+        unsafe { std::env::set_var("SSL_CERT_FILE", ca_cert) };
+    }
+
+    let mut builder = Qdrant::from_url(&config.qdrant_url)
+        .api_key(config.qdrant_api_key.clone())
+        .timeout(Duration::from_secs(config.qdrant_timeout_secs))
+        .connect_timeout(Duration::from_secs(config.qdrant_connect_timeout_secs));
+    builder.set_keep_alive_while_idle(config.qdrant_keep_alive);
+
+    builder
+        .build()
+        .map_err(|e| AppError::Config(format!("Failed to initialize Qdrant client: {}", e)))
+}
+
+/// Convert PointId to String
+pub fn point_id_to_string(point_id: &PointId) -> String {
+    match &point_id.point_id_options {
+        Some(PointIdOptions::Num(n)) => n.to_string(),
+        Some(PointIdOptions::Uuid(u)) => u.clone(),
+        None => String::new(),
+    }
+}
+
+/// Parse a point ID as returned by `point_id_to_string`
+pub fn parse_point_id(id: &str) -> PointId {
+    match id.parse::<u64>() {
+        Ok(n) => PointId::from(n),
+        Err(_) => PointId::from(id.to_string()),
+    }
+}
+
+/// Parse a read consistency setting: `all`, `majority`, `quorum` or a node count
+pub fn parse_read_consistency(value: &str) -> Result<ReadConsistency, AppError> {
+    let consistency = match value.trim().to_ascii_lowercase().as_str() {
+        "all" => ConsistencyValue::Type(ReadConsistencyType::All.into()),
+        "majority" => ConsistencyValue::Type(ReadConsistencyType::Majority.into()),
+        "quorum" => ConsistencyValue::Type(ReadConsistencyType::Quorum.into()),
+        other => match other.parse::<u64>() {
+            Ok(factor) if factor > 0 => ConsistencyValue::Factor(factor),
+            _ => {
+                return Err(AppError::Parse(format!(
+                    "Invalid read consistency '{}': expected all, majority, quorum or a positive number",
+                    value
+                )));
+            }
+        },
+    };
+
+    Ok(ReadConsistency {
+        value: Some(consistency),
+    })
+}
+
+/// Parse a distance metric: `cosine`, `dot`, `euclid` or `manhattan`
+pub fn parse_distance(value: &str) -> Result<Distance, AppError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "cosine" => Ok(Distance::Cosine),
+        "dot" => Ok(Distance::Dot),
+        "euclid" | "euclidean" => Ok(Distance::Euclid),
+        "manhattan" => Ok(Distance::Manhattan),
+        _ => Err(AppError::Parse(format!(
+            "Invalid distance metric '{}': expected cosine, dot, euclid or manhattan",
+            value
+        ))),
+    }
+}
+
+/// Parse a write ordering setting: `weak`, `medium` or `strong`
+pub fn parse_write_ordering(value: &str) -> Result<WriteOrdering, AppError> {
+    let ordering = match value.trim().to_ascii_lowercase().as_str() {
+        "weak" => WriteOrderingType::Weak,
+        "medium" => WriteOrderingType::Medium,
+        "strong" => WriteOrderingType::Strong,
+        _ => {
+            return Err(AppError::Parse(format!(
+                "Invalid write ordering '{}': expected weak, medium or strong",
+                value
+            )));
+        }
+    };
+
+    Ok(WriteOrdering {
+        r#type: ordering.into(),
+    })
+}
+
+/// Ingest paths with their own upsert settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngestPath {
+    /// `/insert_image` and `/insert_images`, where callers expect to find the image right away
+    Interactive,
+    /// Scheduled, manual and backfill fetch runs
+    Bulk,
+}
+
+/// `wait` and write ordering sent with an upsert
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UpsertSettings {
+    /// Return only once the points are applied (searchable)
+    pub wait: bool,
+    /// `None` keeps the Qdrant default (`weak`)
+    pub ordering: Option<WriteOrdering>,
+}
+
+impl UpsertSettings {
+    /// Settings for `path` from the current tunables
+    ///
+    /// `verify` forces `wait`, since points are read back right after the upsert.
+    pub fn for_path(tunables: &Tunables, path: IngestPath, verify: bool) -> Self {
+        let (wait, ordering) = match path {
+            IngestPath::Interactive => {
+                (tunables.insert_upsert_wait, &tunables.insert_write_ordering)
+            }
+            IngestPath::Bulk => (tunables.bulk_upsert_wait, &tunables.bulk_write_ordering),
+        };
+        Self {
+            wait: wait || verify,
+            // Validated when the configuration is loaded
+            ordering: ordering
+                .as_deref()
+                .and_then(|o| parse_write_ordering(o).ok()),
+        }
+    }
+}
+
+/// Wait until every write sent to `collection_name` so far is applied
+///
+/// Qdrant applies updates in order, so a no-op delete with `wait` and
+/// strong ordering returns only after the earlier, unwaited upserts.
+#[instrument(skip(qdrant))]
+pub async fn flush_collection(qdrant: &Qdrant, collection_name: &str) -> Result<(), AppError> {
+    qdrant
+        .delete_points(
+            DeletePointsBuilder::new(collection_name)
+                .points(Vec::<PointId>::new())
+                .wait(true)
+                .ordering(WriteOrdering {
+                    r#type: WriteOrderingType::Strong.into(),
+                }),
+        )
+        .await
+        .map_err(|e| AppError::Qdrant(format!("Failed to flush {}: {}", collection_name, e)))?;
+
+    Ok(())
+}
+
+/// Vectors and cluster layout of the collections; unset values use the
+/// Qdrant defaults
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectionSettings {
+    pub vector_size: usize,
+    pub distance: Distance,
+    pub layout: VectorLayout,
+    pub shard_number: Option<u32>,
+    pub replication_factor: Option<u32>,
+    pub write_consistency_factor: Option<u32>,
+    pub on_disk_vectors: Option<bool>,
+    pub on_disk_payload: Option<bool>,
+}
+
+impl CollectionSettings {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            vector_size: config.vector_size,
+            distance: config.distance_metric,
+            layout: config.vector_layout,
+            shard_number: config.qdrant_shard_number,
+            replication_factor: config.qdrant_replication_factor,
+            write_consistency_factor: config.qdrant_write_consistency_factor,
+            on_disk_vectors: config.qdrant_on_disk_vectors,
+            on_disk_payload: config.qdrant_on_disk_payload,
+        }
+    }
+
+    /// Configured layout settings an existing collection does not have
+    pub fn mismatches(&self, params: &CollectionParams, vectors: &VectorParams) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        let mut check = |name: &str, configured: Option<String>, actual: String| {
+            if let Some(configured) = configured
+                && configured != actual
+            {
+                mismatches.push(format!("{} is {}, configured {}", name, actual, configured));
+            }
+        };
+        check(
+            "shard_number",
+            self.shard_number.map(|n| n.to_string()),
+            params.shard_number.to_string(),
+        );
+        check(
+            "replication_factor",
+            self.replication_factor.map(|n| n.to_string()),
+            params.replication_factor.unwrap_or(1).to_string(),
+        );
+        check(
+            "write_consistency_factor",
+            self.write_consistency_factor.map(|n| n.to_string()),
+            params.write_consistency_factor.unwrap_or(1).to_string(),
+        );
+        check(
+            "vectors on_disk",
+            self.on_disk_vectors.map(|b| b.to_string()),
+            vectors.on_disk.unwrap_or(false).to_string(),
+        );
+        check(
+            "on_disk_payload",
+            self.on_disk_payload.map(|b| b.to_string()),
+            params.on_disk_payload.to_string(),
+        );
+        mismatches
+    }
+}
 
 /// Ensure collection exists, create if not
+///
+/// An existing collection whose vectors differ in size or distance is a
+/// configuration error; other differences from `settings` are logged.
+#[instrument(skip(qdrant, settings))]
 pub async fn ensure_collection_exists(
     qdrant: &Qdrant,
     collection_name: &str,
-    vector_size: usize,
-) -> Result<(), String> {
+    settings: &CollectionSettings,
+) -> Result<(), AppError> {
     let vector_params = VectorParams {
-        size: vector_size as u64,
-        distance: Distance::Cosine.into(),
+        size: settings.vector_size as u64,
+        distance: settings.distance.into(),
+        on_disk: settings.on_disk_vectors,
         ..Default::default()
     };
 
     let create_collection = CreateCollection {
         collection_name: collection_name.to_string(),
-        vectors_config: Some(vector_params.into()),
+        vectors_config: Some(settings.layout.vectors_config(vector_params)),
+        shard_number: settings.shard_number,
+        replication_factor: settings.replication_factor,
+        write_consistency_factor: settings.write_consistency_factor,
+        on_disk_payload: settings.on_disk_payload,
         ..Default::default()
     };
 
     match qdrant.create_collection(create_collection).await {
-        Ok(_) => println!("✅ Collection '{}' created successfully", collection_name),
+        Ok(_) => info!("Collection created"),
         Err(e) => {
             let error_msg = format!("{}", e);
             if error_msg.contains("already exists") {
-                println!("✅ Collection '{}' already exists", collection_name);
+                info!("Collection already exists");
+                check_collection(qdrant, collection_name, settings).await?;
             } else {
-                return Err(format!("Failed to create collection: {}", e));
+                return Err(AppError::Qdrant(format!(
+                    "Failed to create collection: {}",
+                    e
+                )));
             }
         }
     }
@@ -38,34 +299,338 @@ pub async fn ensure_collection_exists(
     Ok(())
 }
 
-/// Create datetime field index for filtering
-pub async fn create_datetime_index(
+/// Fail if the vectors of an existing collection don't match the configuration,
+/// and warn about any other layout difference
+async fn check_collection(
     qdrant: &Qdrant,
     collection_name: &str,
-) -> Result<(), String> {
-    // Check if collection exists
-    let collections = qdrant
-        .list_collections()
+    settings: &CollectionSettings,
+) -> Result<(), AppError> {
+    let info = qdrant
+        .collection_info(collection_name)
         .await
-        .map_err(|e| format!("Failed to list collections: {}", e))?;
+        .map_err(|e| AppError::Qdrant(format!("Failed to read collection info: {}", e)))?;
+    let Some(collection_params) = info.result.and_then(|r| r.config).and_then(|c| c.params) else {
+        return Ok(());
+    };
+    let vectors = collection_params
+        .vectors_config
+        .clone()
+        .and_then(|v| v.config);
 
-    let collection_exists = collections
-        .collections
-        .iter()
-        .any(|c| c.name == collection_name);
+    let params: Vec<(&str, VectorParams)> = match (settings.layout, vectors) {
+        (VectorLayout::Single, Some(VectorsConfigKind::Params(params))) => vec![("", params)],
+        (VectorLayout::Named, Some(VectorsConfigKind::ParamsMap(mut map))) => {
+            let mut named = Vec::new();
+            for name in [IMAGE_VECTOR, CAPTION_VECTOR] {
+                let params = map.map.remove(name).ok_or_else(|| {
+                    AppError::Config(format!(
+                        "Collection '{}' has no '{}' vector, but VECTOR_LAYOUT=named",
+                        collection_name, name
+                    ))
+                })?;
+                named.push((name, params));
+            }
+            named
+        }
+        (layout, Some(_)) => {
+            return Err(AppError::Config(format!(
+                "Collection '{}' does not have the {} vector layout set by VECTOR_LAYOUT",
+                collection_name,
+                layout.as_str()
+            )));
+        }
+        (_, None) => return Ok(()),
+    };
+    let (vector_size, distance) = (settings.vector_size, settings.distance);
+    for (name, params) in &params {
+        if params.size != vector_size as u64 || params.distance != distance as i32 {
+            let vector = if name.is_empty() {
+                String::new()
+            } else {
+                format!(" '{}'", name)
+            };
+            return Err(AppError::Config(format!(
+                "Collection '{}' stores {}-dimensional {}{} vectors, but VECTOR_SIZE={} and DISTANCE_METRIC={}",
+                collection_name,
+                params.size,
+                params.distance().as_str_name(),
+                vector,
+                vector_size,
+                distance.as_str_name()
+            )));
+        }
+    }
 
-    if !collection_exists {
-        return Err(format!("Collection '{}' does not exist", collection_name));
+    // Shards and on-disk storage can't change without recreating the collection
+    for mismatch in settings.mismatches(&collection_params, &params[0].1) {
+        warn!(
+            collection = collection_name,
+            mismatch = %mismatch,
+            "Collection layout differs from the configuration"
+        );
     }
+    Ok(())
+}
 
-    // Create datetime field index
+/// Create a single payload field index (no-op if it already exists)
+#[instrument(skip(qdrant))]
+pub async fn create_field_index(
+    qdrant: &Qdrant,
+    collection_name: &str,
+    field_name: &str,
+    field_type: FieldType,
+) -> Result<(), AppError> {
     qdrant
         .create_field_index(
-            CreateFieldIndexCollectionBuilder::new(collection_name, "datetime", FieldType::Datetime)
+            CreateFieldIndexCollectionBuilder::new(collection_name, field_name, field_type)
                 .wait(true),
         )
         .await
-        .map_err(|e| format!("Failed to create datetime index: {}", e))?;
+        .map_err(|e| AppError::Qdrant(format!("Failed to create {} index: {}", field_name, e)))?;
 
     Ok(())
 }
+
+/// Build a payload filter on datetime range and camera IDs
+///
+/// Returns `None` when no condition is given.
+pub fn build_image_filter(
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+    camera_ids: Option<&[String]>,
+) -> Result<Option<Filter>, AppError> {
+    let mut must = Vec::new();
+
+    let start_date = start_date.filter(|s| !s.is_empty());
+    let end_date = end_date.filter(|s| !s.is_empty());
+
+    if start_date.is_some() || end_date.is_some() {
+        let mut datetime_range = DatetimeRange::default();
+
+        if let Some(start) = start_date {
+            datetime_range.gt = Some(
+                rfc3339_to_timestamp(start)
+                    .map_err(|e| AppError::Parse(format!("Invalid start_date format: {}", e)))?,
+            );
+        }
+
+        if let Some(end) = end_date {
+            datetime_range.lte = Some(
+                rfc3339_to_timestamp(end)
+                    .map_err(|e| AppError::Parse(format!("Invalid end_date format: {}", e)))?,
+            );
+        }
+
+        must.push(Condition::datetime_range("datetime", datetime_range));
+    }
+
+    if let Some(camera_ids) = camera_ids.filter(|ids| !ids.is_empty()) {
+        must.push(Condition::matches("camera_id", camera_ids.to_vec()));
+    }
+
+    if must.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(Filter {
+        must,
+        ..Default::default()
+    }))
+}
+
+/// Conditions on the AI label: any of `vehicle_classes`, at least `min_confidence`
+///
+/// Images without an AI label never match either condition.
+pub fn label_conditions(
+    vehicle_classes: Option<&[String]>,
+    min_confidence: Option<f32>,
+) -> Result<Vec<Condition>, AppError> {
+    let mut conditions = Vec::new();
+
+    if let Some(classes) = vehicle_classes.filter(|classes| !classes.is_empty()) {
+        conditions.push(Condition::matches("vehicle_class", classes.to_vec()));
+    }
+
+    if let Some(min_confidence) = min_confidence {
+        if !(0.0..=1.0).contains(&min_confidence) {
+            return Err(AppError::InvalidRequest(format!(
+                "min_confidence must be between 0 and 1 (got {})",
+                min_confidence
+            )));
+        }
+        conditions.push(Condition::range(
+            "confidence",
+            Range {
+                gte: Some(min_confidence as f64),
+                ..Default::default()
+            },
+        ));
+    }
+
+    Ok(conditions)
+}
+
+/// Payload object holding the upstream fields without a dedicated payload key
+pub const EXTRA_FIELD: &str = "extra";
+
+/// Exact-match conditions on `extra.*` payload keys
+///
+/// A value matches as is; an array matches any of its strings or integers.
+pub fn extra_conditions(
+    filters: Option<&HashMap<String, serde_json::Value>>,
+) -> Result<Vec<Condition>, AppError> {
+    let mut conditions = Vec::new();
+    for (key, value) in filters.into_iter().flatten() {
+        if key
+            .strip_prefix(EXTRA_FIELD)
+            .and_then(|rest| rest.strip_prefix('.'))
+            .is_none_or(str::is_empty)
+        {
+            return Err(AppError::InvalidRequest(format!(
+                "Filter key '{}' must start with '{}.'",
+                key, EXTRA_FIELD
+            )));
+        }
+        let unsupported = || {
+            AppError::InvalidRequest(format!(
+                "Filter '{}' must be a string, integer, boolean or an array of strings or integers",
+                key
+            ))
+        };
+        let condition = match value {
+            serde_json::Value::String(s) => Condition::matches(key, s.clone()),
+            serde_json::Value::Bool(b) => Condition::matches(key, *b),
+            serde_json::Value::Number(n) => {
+                Condition::matches(key, n.as_i64().ok_or_else(unsupported)?)
+            }
+            serde_json::Value::Array(items) if items.iter().all(|v| v.is_string()) => {
+                let strings: Vec<String> = items
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect();
+                Condition::matches(key, strings)
+            }
+            serde_json::Value::Array(items) => {
+                let integers = items
+                    .iter()
+                    .map(serde_json::Value::as_i64)
+                    .collect::<Option<Vec<i64>>>()
+                    .ok_or_else(unsupported)?;
+                Condition::matches(key, integers)
+            }
+            _ => return Err(unsupported()),
+        };
+        conditions.push(condition);
+    }
+    Ok(conditions)
+}
+
+/// Add `conditions` to the `must` clause of `filter`, creating it if needed
+pub fn with_conditions(filter: Option<Filter>, conditions: Vec<Condition>) -> Option<Filter> {
+    if conditions.is_empty() {
+        return filter;
+    }
+    let mut filter = filter.unwrap_or_default();
+    filter.must.extend(conditions);
+    Some(filter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_read_consistency() {
+        assert_eq!(
+            parse_read_consistency("Majority").unwrap().value,
+            Some(ConsistencyValue::Type(ReadConsistencyType::Majority.into()))
+        );
+        assert_eq!(
+            parse_read_consistency("3").unwrap().value,
+            Some(ConsistencyValue::Factor(3))
+        );
+        assert!(parse_read_consistency("0").is_err());
+        assert!(parse_read_consistency("eventual").is_err());
+    }
+
+    #[test]
+    fn test_collection_layout_mismatches() {
+        let settings = CollectionSettings {
+            vector_size: 4,
+            distance: Distance::Cosine,
+            layout: VectorLayout::Single,
+            shard_number: Some(3),
+            replication_factor: Some(2),
+            write_consistency_factor: None,
+            on_disk_vectors: Some(true),
+            on_disk_payload: None,
+        };
+        let params = CollectionParams {
+            shard_number: 3,
+            replication_factor: Some(1),
+            ..Default::default()
+        };
+        let vectors = VectorParams {
+            on_disk: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.mismatches(&params, &vectors),
+            ["replication_factor is 1, configured 2"]
+        );
+    }
+
+    #[test]
+    fn test_parse_distance() {
+        assert_eq!(parse_distance("Cosine").unwrap(), Distance::Cosine);
+        assert_eq!(parse_distance("euclid").unwrap(), Distance::Euclid);
+        assert!(parse_distance("hamming").is_err());
+    }
+
+    #[test]
+    fn test_label_conditions() {
+        let classes = vec!["truck".to_string(), "car".to_string()];
+        assert_eq!(
+            label_conditions(Some(&classes), Some(0.5)).unwrap().len(),
+            2
+        );
+        assert!(label_conditions(Some(&[]), None).unwrap().is_empty());
+        assert!(label_conditions(None, Some(1.5)).is_err());
+
+        let filter = with_conditions(None, label_conditions(None, Some(0.5)).unwrap());
+        assert_eq!(filter.map(|f| f.must.len()), Some(1));
+        assert!(with_conditions(None, Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_extra_conditions() {
+        let filters = HashMap::from([
+            ("extra.lane".to_string(), serde_json::json!(2)),
+            (
+                "extra.color".to_string(),
+                serde_json::json!(["red", "white"]),
+            ),
+        ]);
+        assert_eq!(extra_conditions(Some(&filters)).unwrap().len(), 2);
+        assert!(extra_conditions(None).unwrap().is_empty());
+
+        let invalid = |key: &str, value: serde_json::Value| {
+            extra_conditions(Some(&HashMap::from([(key.to_string(), value)]))).is_err()
+        };
+        assert!(invalid("camera_id", serde_json::json!("cctv01")));
+        assert!(invalid("extra.", serde_json::json!("x")));
+        assert!(invalid("extras.lane", serde_json::json!(1)));
+        assert!(invalid("extra.speed", serde_json::json!(42.5)));
+        assert!(invalid("extra.lane", serde_json::json!([1, "two"])));
+    }
+
+    #[test]
+    fn test_parse_write_ordering() {
+        assert_eq!(
+            parse_write_ordering(" Strong").unwrap().r#type,
+            i32::from(WriteOrderingType::Strong)
+        );
+        assert!(parse_write_ordering("eventual").is_err());
+    }
+}
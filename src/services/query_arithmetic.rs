@@ -0,0 +1,135 @@
+//! Query Arithmetic
+//!
+//! Weighted prompt expressions such as `+1.0 "pickup truck" -0.5 "delivery van"`,
+//! combined into a single query vector.
+
+use crate::config::technical;
+use crate::error::AppError;
+
+/// One term of a prompt expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedPrompt {
+    pub weight: f32,
+    pub text: String,
+}
+
+/// Parse `[weight] "text"` terms; a missing weight is `+1.0`
+pub fn parse_prompt_expression(expression: &str) -> Result<Vec<WeightedPrompt>, AppError> {
+    let invalid =
+        |reason: &str| AppError::InvalidRequest(format!("Invalid prompt expression: {}", reason));
+
+    let mut prompts = Vec::new();
+    let mut rest = expression.trim_start();
+    while !rest.is_empty() {
+        let quote = rest
+            .find('"')
+            .ok_or_else(|| invalid("expected a quoted prompt"))?;
+        let weight = match rest[..quote].trim() {
+            "" | "+" => 1.0,
+            "-" => -1.0,
+            token => token
+                .parse::<f32>()
+                .ok()
+                .filter(|w| w.is_finite())
+                .ok_or_else(|| invalid(&format!("bad weight {:?}", token)))?,
+        };
+
+        let after_quote = &rest[quote + 1..];
+        let end = after_quote
+            .find('"')
+            .ok_or_else(|| invalid("unterminated quote"))?;
+        let text = after_quote[..end].trim();
+        if text.is_empty() {
+            return Err(invalid("empty prompt"));
+        }
+
+        prompts.push(WeightedPrompt {
+            weight,
+            text: text.to_string(),
+        });
+        rest = after_quote[end + 1..].trim_start();
+    }
+
+    if prompts.is_empty() {
+        return Err(invalid("no prompts"));
+    }
+    if prompts.len() > technical::MAX_QUERY_PROMPTS {
+        return Err(invalid(&format!(
+            "at most {} prompts are allowed",
+            technical::MAX_QUERY_PROMPTS
+        )));
+    }
+    if prompts.iter().all(|p| p.weight <= 0.0) {
+        return Err(invalid("at least one weight must be positive"));
+    }
+    Ok(prompts)
+}
+
+/// Sum of `weight * vector` over `weighted`, L2-normalized
+pub fn combine_vectors(weighted: &[(f32, Vec<f32>)]) -> Result<Vec<f32>, AppError> {
+    let dimensions = weighted.first().map(|(_, v)| v.len()).unwrap_or_default();
+    let mut combined = vec![0.0_f32; dimensions];
+    for (weight, vector) in weighted {
+        if vector.len() != dimensions {
+            return Err(AppError::AiService(format!(
+                "Embedding size mismatch: {} vs {}",
+                vector.len(),
+                dimensions
+            )));
+        }
+        for (sum, value) in combined.iter_mut().zip(vector) {
+            *sum += weight * value;
+        }
+    }
+
+    let norm = combined.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm <= f32::EPSILON {
+        return Err(AppError::InvalidRequest(
+            "Prompts cancel out to an empty query vector".to_string(),
+        ));
+    }
+    Ok(combined.into_iter().map(|v| v / norm).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_prompt_expression() {
+        let prompts =
+            parse_prompt_expression(r#"+1.0 "pickup truck" -0.5 "delivery van" "red""#).unwrap();
+        assert_eq!(
+            prompts,
+            vec![
+                WeightedPrompt {
+                    weight: 1.0,
+                    text: "pickup truck".to_string()
+                },
+                WeightedPrompt {
+                    weight: -0.5,
+                    text: "delivery van".to_string()
+                },
+                WeightedPrompt {
+                    weight: 1.0,
+                    text: "red".to_string()
+                },
+            ]
+        );
+
+        assert!(parse_prompt_expression("pickup truck").is_err());
+        assert!(parse_prompt_expression(r#"x "van""#).is_err());
+        assert!(parse_prompt_expression(r#"1.0 "van"#).is_err());
+        assert!(parse_prompt_expression(r#"-1.0 "van""#).is_err());
+        assert!(parse_prompt_expression("").is_err());
+    }
+
+    #[test]
+    fn test_combine_vectors() {
+        let combined = combine_vectors(&[(1.0, vec![1.0, 1.0]), (-1.0, vec![0.0, 1.0])]).unwrap();
+        assert_eq!(combined, vec![1.0, 0.0]);
+
+        assert!(combine_vectors(&[(1.0, vec![1.0]), (-1.0, vec![1.0])]).is_err());
+        assert!(combine_vectors(&[(1.0, vec![1.0]), (1.0, vec![1.0, 0.0])]).is_err());
+    }
+}
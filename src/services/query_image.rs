@@ -0,0 +1,178 @@
+//! Query Images
+//!
+//! Stores base64 images sent to search-by-image where the AI service can read
+//! them, and reads images named by path from under a configured root.
+
+use crate::error::AppError;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncReadExt;
+
+/// Decode a base64 image, accepting an optional `data:image/...;base64,` prefix
+pub fn decode_base64_image(encoded: &str) -> Result<Vec<u8>, AppError> {
+    let data = match encoded.split_once(";base64,") {
+        Some((prefix, data)) if prefix.starts_with("data:") => data,
+        _ => encoded,
+    };
+
+    let bytes = STANDARD
+        .decode(data.trim())
+        .map_err(|e| AppError::Parse(format!("Invalid base64 image: {}", e)))?;
+    if bytes.is_empty() {
+        return Err(AppError::InvalidRequest("Empty image".to_string()));
+    }
+
+    Ok(bytes)
+}
+
+/// Image named by path, read from under an image root
+#[derive(Debug)]
+pub struct LocalImage {
+    /// Canonical path, free of `..` and symlinks
+    pub path: PathBuf,
+    pub content: Vec<u8>,
+}
+
+impl LocalImage {
+    /// Read `path` (relative to `root`, or absolute) if it resolves to a
+    /// regular file under `root` of at most `max_bytes`
+    pub async fn read(root: &str, path: &str, max_bytes: u64) -> Result<Self, AppError> {
+        let root = tokio::fs::canonicalize(root)
+            .await
+            .map_err(|e| AppError::Io(format!("Image root {} unavailable: {}", root, e)))?;
+        let path = tokio::fs::canonicalize(root.join(path))
+            .await
+            .map_err(|_| AppError::InvalidRequest(format!("Image not found: {}", path)))?;
+        if !path.starts_with(&root) {
+            return Err(AppError::Forbidden(
+                "image_path is outside the image root".to_string(),
+            ));
+        }
+
+        let file = tokio::fs::File::open(&path)
+            .await
+            .map_err(|e| AppError::InvalidRequest(format!("Image not readable: {}", e)))?;
+        let metadata = file
+            .metadata()
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to stat image: {}", e)))?;
+        if !metadata.is_file() {
+            return Err(AppError::InvalidRequest(
+                "image_path is not a regular file".to_string(),
+            ));
+        }
+        if metadata.len() > max_bytes {
+            return Err(AppError::InvalidRequest(format!(
+                "Image is larger than {} bytes",
+                max_bytes
+            )));
+        }
+
+        // The file may grow after the check; never read past the limit
+        let mut content = Vec::with_capacity(metadata.len() as usize);
+        file.take(max_bytes + 1)
+            .read_to_end(&mut content)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to read image: {}", e)))?;
+        if content.len() as u64 > max_bytes {
+            return Err(AppError::InvalidRequest(format!(
+                "Image is larger than {} bytes",
+                max_bytes
+            )));
+        }
+
+        Ok(Self { path, content })
+    }
+}
+
+/// Temporary query image file, removed when dropped
+pub struct QueryImage {
+    path: PathBuf,
+}
+
+impl QueryImage {
+    /// Decode `encoded` and write it to a uniquely named file in `dir`
+    pub async fn save(dir: &str, encoded: &str) -> Result<Self, AppError> {
+        Self::save_bytes(dir, &decode_base64_image(encoded)?).await
+    }
+
+    /// Write already decoded image content to a uniquely named file in `dir`
+    pub async fn save_bytes(dir: &str, bytes: &[u8]) -> Result<Self, AppError> {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to create {}: {}", dir, e)))?;
+
+        let filename = format!(
+            "query-{}-{:08x}.jpg",
+            chrono::Utc::now().timestamp_millis(),
+            rand::random::<u32>()
+        );
+        let path = Path::new(dir).join(filename);
+
+        tokio::fs::write(&path, bytes)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to write query image: {}", e)))?;
+
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> String {
+        self.path.to_string_lossy().into_owned()
+    }
+}
+
+impl Drop for QueryImage {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_base64_image() {
+        assert_eq!(decode_base64_image("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(
+            decode_base64_image("data:image/jpeg;base64,aGVsbG8=").unwrap(),
+            b"hello"
+        );
+        assert!(decode_base64_image("not base64!").is_err());
+        assert!(decode_base64_image("").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_local_image_confined_to_root() {
+        let base = std::env::temp_dir().join(format!("images-{:016x}", rand::random::<u64>()));
+        let root = base.join("root");
+        std::fs::create_dir_all(root.join("cctv01")).unwrap();
+        std::fs::write(root.join("cctv01/frame.jpg"), b"jpeg").unwrap();
+        std::fs::write(base.join("secret"), b"secret").unwrap();
+        let root_str = root.to_string_lossy();
+
+        let image = LocalImage::read(&root_str, "cctv01/frame.jpg", 16)
+            .await
+            .unwrap();
+        assert_eq!(image.content, b"jpeg");
+        let absolute = root.join("cctv01/frame.jpg");
+        let image = LocalImage::read(&root_str, &absolute.to_string_lossy(), 16)
+            .await
+            .unwrap();
+        assert_eq!(image.content, b"jpeg");
+
+        let escape = LocalImage::read(&root_str, "../secret", 16).await;
+        assert!(matches!(escape, Err(AppError::Forbidden(_))));
+        let outside = LocalImage::read(&root_str, &base.join("secret").to_string_lossy(), 16).await;
+        assert!(matches!(outside, Err(AppError::Forbidden(_))));
+        let directory = LocalImage::read(&root_str, "cctv01", 16).await;
+        assert!(matches!(directory, Err(AppError::InvalidRequest(_))));
+        let too_large = LocalImage::read(&root_str, "cctv01/frame.jpg", 3).await;
+        assert!(matches!(too_large, Err(AppError::InvalidRequest(_))));
+        let missing = LocalImage::read(&root_str, "cctv01/missing.jpg", 16).await;
+        assert!(matches!(missing, Err(AppError::InvalidRequest(_))));
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -0,0 +1,94 @@
+//! Recency Boost
+//!
+//! Re-ranks search hits by vector score times an exponential decay of the
+//! image's age, so fresh sightings outrank months-old near-duplicates.
+
+use crate::error::AppError;
+use crate::services::extract_string;
+use chrono::{DateTime, Utc};
+use qdrant_client::qdrant::ScoredPoint;
+
+/// Decay factor for an image `age_hours` old: 1.0 when new, 0.5 after one half-life
+pub fn recency_decay(age_hours: f64, half_life_hours: f64) -> f64 {
+    0.5_f64.powf(age_hours.max(0.0) / half_life_hours)
+}
+
+/// Effective half-life for a request: the request value, else the configured
+/// default; `0` turns the boost off
+pub fn resolve_half_life(
+    requested: Option<f64>,
+    default: Option<f64>,
+) -> Result<Option<f64>, AppError> {
+    match requested.or(default) {
+        Some(hours) if hours < 0.0 || !hours.is_finite() => Err(AppError::InvalidRequest(format!(
+            "recency_half_life_hours must be >= 0 (got {})",
+            hours
+        ))),
+        Some(0.0) => Ok(None),
+        other => Ok(other),
+    }
+}
+
+/// Scale each score by the decay of its `datetime` payload, re-sort and keep `top_k`
+///
+/// Points without a parseable `datetime` keep their vector score.
+pub fn apply_recency_boost(
+    points: Vec<ScoredPoint>,
+    half_life_hours: f64,
+    now: DateTime<Utc>,
+    top_k: usize,
+) -> Vec<ScoredPoint> {
+    let mut boosted: Vec<ScoredPoint> = points
+        .into_iter()
+        .map(|mut point| {
+            if let Ok(datetime) =
+                DateTime::parse_from_rfc3339(&extract_string(&point.payload, "datetime"))
+            {
+                let age_hours = (now - datetime.with_timezone(&Utc)).num_seconds() as f64 / 3600.0;
+                point.score *= recency_decay(age_hours, half_life_hours) as f32;
+            }
+            point
+        })
+        .collect();
+
+    boosted.sort_by(|a, b| b.score.total_cmp(&a.score));
+    boosted.truncate(top_k);
+    boosted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::PayloadBuilder;
+
+    fn hit(id: u64, score: f32, datetime: &str) -> ScoredPoint {
+        ScoredPoint {
+            id: Some(id.into()),
+            score,
+            payload: PayloadBuilder::new().string("datetime", datetime).build(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_recency_boost_prefers_fresh_hits() {
+        let now = DateTime::parse_from_rfc3339("2025-06-01T12:00:00+07:00")
+            .unwrap()
+            .with_timezone(&Utc);
+        let points = vec![
+            hit(1, 0.90, "2025-03-01T12:00:00+07:00"),
+            hit(2, 0.80, "2025-06-01T11:00:00+07:00"),
+            hit(3, 0.85, "unknown"),
+        ];
+
+        let boosted = apply_recency_boost(points, 24.0, now, 2);
+        let ids: Vec<_> = boosted.iter().map(|p| p.id.clone().unwrap()).collect();
+        assert_eq!(ids, vec![3.into(), 2.into()]);
+        assert!((boosted[1].score - 0.80 * 0.5_f32.powf(1.0 / 24.0)).abs() < 1e-6);
+
+        assert_eq!(recency_decay(48.0, 24.0), 0.25);
+        assert_eq!(resolve_half_life(Some(0.0), Some(24.0)), Ok(None));
+        assert_eq!(resolve_half_life(None, Some(24.0)), Ok(Some(24.0)));
+        assert!(resolve_half_life(Some(-1.0), None).is_err());
+    }
+}
@@ -0,0 +1,71 @@
+//! Burst Collapsing
+//!
+//! Keeps only the best hit per camera within a time window, so consecutive
+//! frames of the same passing vehicle don't fill the whole result list.
+
+use crate::services::extract_string;
+use chrono::DateTime;
+use qdrant_client::qdrant::ScoredPoint;
+use std::collections::HashMap;
+
+/// Drop hits taken within `window_secs` of a better hit from the same camera
+///
+/// `points` must be sorted by descending score. Points without a parseable
+/// `datetime` are always kept.
+pub fn collapse_bursts(points: Vec<ScoredPoint>, window_secs: u64) -> Vec<ScoredPoint> {
+    let window = window_secs as i64;
+    let mut kept_times: HashMap<String, Vec<i64>> = HashMap::new();
+    points
+        .into_iter()
+        .filter(|point| {
+            let Ok(datetime) =
+                DateTime::parse_from_rfc3339(&extract_string(&point.payload, "datetime"))
+            else {
+                return true;
+            };
+            let time = datetime.timestamp();
+            let times = kept_times
+                .entry(extract_string(&point.payload, "camera_id"))
+                .or_default();
+            if times.iter().any(|kept| (kept - time).abs() < window) {
+                return false;
+            }
+            times.push(time);
+            true
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{PayloadBuilder, point_id_to_string};
+
+    fn hit(id: u64, score: f32, camera_id: &str, datetime: &str) -> ScoredPoint {
+        ScoredPoint {
+            id: Some(id.into()),
+            score,
+            payload: PayloadBuilder::new()
+                .string("camera_id", camera_id)
+                .string("datetime", datetime)
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_collapse_bursts() {
+        let points = vec![
+            hit(1, 0.9, "cctv01", "2025-10-08T06:32:10+07:00"),
+            hit(2, 0.8, "cctv01", "2025-10-08T06:32:25+07:00"),
+            hit(3, 0.7, "cctv02", "2025-10-08T06:32:20+07:00"),
+            hit(4, 0.6, "cctv01", "2025-10-08T06:33:00+07:00"),
+            hit(5, 0.5, "cctv01", "not a date"),
+        ];
+        let ids: Vec<String> = collapse_bursts(points, 30)
+            .iter()
+            .filter_map(|p| p.id.as_ref().map(point_id_to_string))
+            .collect();
+        assert_eq!(ids, ["1", "3", "4", "5"]);
+    }
+}
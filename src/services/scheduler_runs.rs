@@ -0,0 +1,393 @@
+//! Scheduler Run History
+//!
+//! Ring buffer of recent fetch runs, scheduled or triggered by hand, so
+//! operators can check that ingestion is healthy without reading logs.
+//! Every change of a run is also broadcast to `GET /events` subscribers.
+
+use crate::config::technical;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+/// Error messages kept per run; later ones are only counted
+const MAX_RUN_ERRORS: usize = 20;
+/// Per-image failures kept per run; later ones are only counted
+const MAX_RUN_FAILURES: usize = 100;
+/// Run updates a subscriber may fall behind by before it skips ahead
+const UPDATE_CAPACITY: usize = 64;
+
+/// Overall outcome of a fetch run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// Still fetching or embedding
+    Running,
+    /// Every fetched image was stored
+    Completed,
+    /// Some images were stored, but something failed
+    Partial,
+    /// Errors occurred and nothing was stored
+    Failed,
+    /// Not run, e.g. during maintenance
+    Skipped,
+    /// Stopped before every image was processed
+    Cancelled,
+}
+
+/// What started a fetch run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunTrigger {
+    /// The cron schedule
+    Scheduled,
+    /// `POST /scheduler/trigger`
+    Manual,
+}
+
+/// Image that could not be embedded or stored in a run
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImageFailure {
+    pub id: u32,
+    pub cctv_id: String,
+    pub filename: String,
+    pub file_path: String,
+    /// Last error
+    pub error: String,
+    /// Embedding attempts made in the run
+    pub attempts: u32,
+    /// `true` if the error looked transient and the retries ran out; `false`
+    /// if the image was rejected outright, e.g. for a defective embedding
+    pub transient: bool,
+}
+
+/// Counters accumulated while a fetch run is in progress
+#[derive(Debug, Default)]
+pub struct RunTally {
+    pub fetched: u64,
+    /// Already stored, so not embedded again
+    pub skipped: u64,
+    pub inserted: u64,
+    pub failed: u64,
+    /// Images embedded again after a transient failure
+    pub retried: u64,
+    errors: Vec<String>,
+    error_count: usize,
+    failures: Vec<ImageFailure>,
+    cancelled: bool,
+}
+
+impl RunTally {
+    /// Record an error message (only the first few are kept)
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.error_count += 1;
+        if self.errors.len() < MAX_RUN_ERRORS {
+            self.errors.push(message.into());
+        }
+    }
+
+    /// Record an image that finally failed, with its error message
+    pub fn image_failed(&mut self, failure: ImageFailure) {
+        self.failed += 1;
+        self.error(format!("{}: {}", failure.filename, failure.error));
+        if self.failures.len() < MAX_RUN_FAILURES {
+            self.failures.push(failure);
+        }
+    }
+
+    /// Mark the run as stopped before it finished
+    pub fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+
+    /// Add the counters of a sub-task, e.g. one embedding batch
+    pub fn merge(&mut self, other: RunTally) {
+        self.fetched += other.fetched;
+        self.skipped += other.skipped;
+        self.inserted += other.inserted;
+        self.failed += other.failed;
+        self.retried += other.retried;
+        self.error_count += other.error_count;
+        let room = MAX_RUN_ERRORS.saturating_sub(self.errors.len());
+        self.errors.extend(other.errors.into_iter().take(room));
+        let room = MAX_RUN_FAILURES.saturating_sub(self.failures.len());
+        self.failures.extend(other.failures.into_iter().take(room));
+        self.cancelled |= other.cancelled;
+    }
+
+    pub fn first_error(&self) -> Option<&str> {
+        self.errors.first().map(String::as_str)
+    }
+
+    pub fn status(&self) -> RunStatus {
+        if self.cancelled {
+            RunStatus::Cancelled
+        } else if self.error_count == 0 {
+            RunStatus::Completed
+        } else if self.inserted > 0 {
+            RunStatus::Partial
+        } else {
+            RunStatus::Failed
+        }
+    }
+}
+
+/// One fetch run
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SchedulerRun {
+    /// Job ID, returned by `POST /scheduler/trigger` for manual runs
+    pub id: String,
+    pub trigger: RunTrigger,
+    pub status: RunStatus,
+    /// RFC 3339
+    pub started_at: String,
+    /// RFC 3339; unset while running
+    pub finished_at: Option<String>,
+    /// Start of the fetched CCTV window (Bangkok time, as sent to the CCTV API)
+    pub date_start: String,
+    /// End of the fetched CCTV window (Bangkok time, as sent to the CCTV API)
+    pub date_stop: String,
+    /// Single camera fetched; unset when every enabled camera was
+    pub cctv_id: Option<String>,
+    pub images_fetched: u64,
+    /// Images already stored by an earlier run, not embedded again
+    pub skipped: u64,
+    pub inserted: u64,
+    /// Images that could not be embedded or stored
+    pub failed: u64,
+    /// Images embedded again after a transient failure
+    pub retried: u64,
+    pub error_count: usize,
+    /// First error messages of the run
+    pub errors: Vec<String>,
+    /// First images that finally failed, to re-ingest or investigate
+    pub failures: Vec<ImageFailure>,
+}
+
+impl SchedulerRun {
+    /// A run that has just started
+    pub fn start(
+        trigger: RunTrigger,
+        date_start: String,
+        date_stop: String,
+        cctv_id: Option<String>,
+    ) -> Self {
+        Self {
+            id: format!("{:016x}", rand::random::<u64>()),
+            trigger,
+            status: RunStatus::Running,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+            date_start,
+            date_stop,
+            cctv_id,
+            images_fetched: 0,
+            skipped: 0,
+            inserted: 0,
+            failed: 0,
+            retried: 0,
+            error_count: 0,
+            errors: Vec::new(),
+            failures: Vec::new(),
+        }
+    }
+
+    /// Mark the run as not executed, e.g. during maintenance
+    pub fn skip(&mut self, reason: &str) {
+        self.status = RunStatus::Skipped;
+        self.finished_at = Some(self.started_at.clone());
+        self.errors = vec![reason.to_string()];
+    }
+
+    /// Copy the counters so far into a run still in progress
+    pub fn progress(&mut self, tally: &RunTally) {
+        self.images_fetched = tally.fetched;
+        self.skipped = tally.skipped;
+        self.inserted = tally.inserted;
+        self.failed = tally.failed;
+        self.retried = tally.retried;
+        self.error_count = tally.error_count;
+    }
+
+    /// Copy the final counters into the run
+    pub fn finish(&mut self, tally: RunTally) {
+        self.status = tally.status();
+        self.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        self.images_fetched = tally.fetched;
+        self.skipped = tally.skipped;
+        self.inserted = tally.inserted;
+        self.failed = tally.failed;
+        self.retried = tally.retried;
+        self.error_count = tally.error_count;
+        self.errors = tally.errors;
+        self.failures = tally.failures;
+    }
+}
+
+/// Most recent runs, shared by the scheduler and `/scheduler/*`
+#[derive(Debug)]
+pub struct SchedulerRunHistory {
+    runs: Mutex<VecDeque<SchedulerRun>>,
+    updates: broadcast::Sender<SchedulerRun>,
+}
+
+impl Default for SchedulerRunHistory {
+    fn default() -> Self {
+        Self {
+            runs: Mutex::default(),
+            updates: broadcast::channel(UPDATE_CAPACITY).0,
+        }
+    }
+}
+
+impl SchedulerRunHistory {
+    fn runs(&self) -> std::sync::MutexGuard<'_, VecDeque<SchedulerRun>> {
+        self.runs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn notify(&self, run: &SchedulerRun) {
+        if self.updates.receiver_count() > 0 {
+            let _ = self.updates.send(run.clone());
+        }
+    }
+
+    /// Recorded and updated runs, as they change
+    pub fn subscribe(&self) -> broadcast::Receiver<SchedulerRun> {
+        self.updates.subscribe()
+    }
+
+    /// Append a run, dropping the oldest beyond `SCHEDULER_RUN_HISTORY`
+    pub fn record(&self, run: SchedulerRun) {
+        self.notify(&run);
+        push_run(&mut self.runs(), run);
+    }
+
+    /// Record a run unless another run of the same trigger is still going
+    ///
+    /// Returns the run in progress, without recording, when there is one.
+    pub fn record_exclusive(&self, run: SchedulerRun) -> Option<SchedulerRun> {
+        let mut runs = self.runs();
+        let running = runs
+            .iter()
+            .find(|r| r.trigger == run.trigger && r.status == RunStatus::Running)
+            .cloned();
+        if running.is_none() {
+            self.notify(&run);
+            push_run(&mut runs, run);
+        }
+        running
+    }
+
+    /// Replace the stored copy of `run` (matched by ID)
+    pub fn update(&self, run: &SchedulerRun) {
+        if let Some(stored) = self.runs().iter_mut().find(|r| r.id == run.id) {
+            *stored = run.clone();
+            self.notify(run);
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<SchedulerRun> {
+        self.runs().iter().find(|r| r.id == id).cloned()
+    }
+
+    /// Up to `limit` runs, newest first
+    pub fn recent(&self, limit: usize) -> Vec<SchedulerRun> {
+        self.runs().iter().rev().take(limit).cloned().collect()
+    }
+}
+
+fn push_run(runs: &mut VecDeque<SchedulerRun>, run: SchedulerRun) {
+    if runs.len() >= technical::SCHEDULER_RUN_HISTORY {
+        runs.pop_front();
+    }
+    runs.push_back(run);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(trigger: RunTrigger) -> SchedulerRun {
+        SchedulerRun::start(trigger, "a".to_string(), "b".to_string(), None)
+    }
+
+    #[test]
+    fn test_run_status_and_history() {
+        let mut tally = RunTally {
+            fetched: 3,
+            inserted: 2,
+            failed: 1,
+            ..Default::default()
+        };
+        assert_eq!(tally.status(), RunStatus::Completed);
+        tally.error("Embedding failed");
+        assert_eq!(tally.status(), RunStatus::Partial);
+        tally.inserted = 0;
+        assert_eq!(tally.status(), RunStatus::Failed);
+
+        let mut total = RunTally::default();
+        total.merge(RunTally {
+            inserted: 4,
+            ..Default::default()
+        });
+        assert_eq!((total.inserted, total.status()), (4, RunStatus::Completed));
+        let mut cancelled = RunTally::default();
+        cancelled.cancel();
+        total.merge(cancelled);
+        assert_eq!(total.status(), RunStatus::Cancelled);
+
+        let mut failures = RunTally::default();
+        failures.image_failed(ImageFailure {
+            id: 413,
+            cctv_id: "cctv01".to_string(),
+            filename: "img_0413.jpg".to_string(),
+            file_path: "/data/cctv01/img_0413.jpg".to_string(),
+            error: "embedding failed: timeout".to_string(),
+            attempts: 3,
+            transient: true,
+        });
+        total.merge(failures);
+        assert_eq!((total.failed, total.failures.len()), (1, 1));
+        assert_eq!(
+            total.errors,
+            vec!["img_0413.jpg: embedding failed: timeout"]
+        );
+
+        let history = SchedulerRunHistory::default();
+        let mut updates = history.subscribe();
+        let mut manual = run(RunTrigger::Manual);
+        assert!(history.record_exclusive(manual.clone()).is_none());
+        let busy = history.record_exclusive(run(RunTrigger::Manual));
+        assert_eq!(busy.map(|r| r.id), Some(manual.id.clone()));
+        assert!(
+            history
+                .record_exclusive(run(RunTrigger::Scheduled))
+                .is_none()
+        );
+
+        manual.progress(&tally);
+        history.update(&manual);
+        assert_eq!(history.get(&manual.id).unwrap().status, RunStatus::Running);
+        assert_eq!(history.get(&manual.id).unwrap().images_fetched, 3);
+        manual.finish(tally);
+        history.update(&manual);
+        assert_eq!(history.get(&manual.id).unwrap().status, RunStatus::Failed);
+        // Recorded manual and scheduled runs, then both updates
+        let statuses: Vec<_> = std::iter::from_fn(|| updates.try_recv().ok())
+            .map(|r| r.status)
+            .collect();
+        assert_eq!(statuses.len(), 4);
+        assert_eq!(statuses[3], RunStatus::Failed);
+        assert!(history.record_exclusive(run(RunTrigger::Manual)).is_none());
+
+        for _ in 0..technical::SCHEDULER_RUN_HISTORY {
+            history.record(run(RunTrigger::Scheduled));
+        }
+        assert_eq!(
+            history.recent(usize::MAX).len(),
+            technical::SCHEDULER_RUN_HISTORY
+        );
+        assert!(history.get(&manual.id).is_none());
+    }
+}
@@ -0,0 +1,179 @@
+//! Search Fan-out
+//!
+//! Searches shard collections and/or datetime sub-ranges in parallel and
+//! merges the hits by score. Hits of different embedding models are merged
+//! by rank instead, as their scores aren't comparable.
+
+use crate::error::AppError;
+use crate::services::datetime_to_timestamp;
+use chrono::{DateTime, Utc};
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{Condition, DatetimeRange, ScoredPoint, SearchPoints};
+use std::sync::Arc;
+use tokio::task::JoinSet;
+use tracing::instrument;
+
+/// Split `[start, end]` into `chunks` contiguous sub-ranges of equal length
+///
+/// Each sub-range is `(exclusive start, inclusive end)`, matching the
+/// `gt`/`lte` semantics of the search datetime filter.
+pub fn split_datetime_range(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    chunks: u32,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    if chunks <= 1 || end <= start {
+        return vec![(start, end)];
+    }
+
+    let step = (end - start) / chunks as i32;
+    (0..chunks)
+        .map(|i| {
+            let chunk_start = start + step * i as i32;
+            let chunk_end = if i + 1 == chunks {
+                end
+            } else {
+                start + step * (i + 1) as i32
+            };
+            (chunk_start, chunk_end)
+        })
+        .collect()
+}
+
+/// Run `base` once per collection and sub-range in parallel and return the
+/// merged top hits
+///
+/// The sub-range condition is added on top of the filter already in `base`,
+/// so any camera or datetime conditions there still apply. An empty `ranges`
+/// searches each collection once without an extra condition.
+#[instrument(skip_all, fields(collections = collections.len(), ranges = ranges.len()))]
+pub async fn fanout_search(
+    qdrant: Arc<Qdrant>,
+    base: &SearchPoints,
+    collections: &[String],
+    ranges: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> Result<Vec<ScoredPoint>, AppError> {
+    let mut conditions = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        let range = DatetimeRange {
+            gt: Some(datetime_to_timestamp(start)?),
+            lte: Some(datetime_to_timestamp(end)?),
+            ..Default::default()
+        };
+        conditions.push(Some(Condition::datetime_range("datetime", range)));
+    }
+    if conditions.is_empty() {
+        conditions.push(None);
+    }
+
+    let mut tasks = JoinSet::new();
+    for collection in collections {
+        for condition in &conditions {
+            let mut request = base.clone();
+            request.collection_name = collection.clone();
+            if let Some(condition) = condition {
+                let mut filter = request.filter.take().unwrap_or_default();
+                filter.must.push(condition.clone());
+                request.filter = Some(filter);
+            }
+
+            let qdrant = qdrant.clone();
+            tasks.spawn(async move { qdrant.search_points(request).await });
+        }
+    }
+
+    let mut partials = Vec::with_capacity(tasks.len());
+    while let Some(joined) = tasks.join_next().await {
+        let response = joined
+            .map_err(|e| AppError::Qdrant(format!("Search task failed: {}", e)))?
+            .map_err(|e| AppError::Qdrant(format!("Qdrant search error: {}", e)))?;
+        partials.push(response.result);
+    }
+
+    Ok(merge_by_score(partials, base.limit as usize))
+}
+
+/// Merge partial result lists, keeping the `limit` highest-scoring hits
+pub fn merge_by_score(partials: Vec<Vec<ScoredPoint>>, limit: usize) -> Vec<ScoredPoint> {
+    let mut merged: Vec<ScoredPoint> = partials.into_iter().flatten().collect();
+    merged.sort_by(|a, b| b.score.total_cmp(&a.score));
+    merged.truncate(limit);
+    merged
+}
+
+/// Rank constant of reciprocal rank fusion; damps the lead of the top hits
+const RRF_K: f32 = 60.0;
+
+/// Merge result lists whose scores aren't comparable, such as those of
+/// different embedding models, by reciprocal rank fusion: each hit scores
+/// `1 / (RRF_K + rank)` within its own list, so every list's best hit ranks
+/// alike, and the `limit` best are kept
+pub fn merge_by_rank(partials: Vec<Vec<ScoredPoint>>, limit: usize) -> Vec<ScoredPoint> {
+    let fused = partials.into_iter().map(|mut partial| {
+        partial.sort_by(|a, b| b.score.total_cmp(&a.score));
+        for (rank, point) in partial.iter_mut().enumerate() {
+            point.score = 1.0 / (RRF_K + (rank + 1) as f32);
+        }
+        partial
+    });
+    merge_by_score(fused.collect(), limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_split_datetime_range() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2025, 1, 4, 0, 0, 0).unwrap();
+
+        let ranges = split_datetime_range(start, end, 3);
+        assert_eq!(ranges.len(), 3);
+        assert_eq!(ranges[0].0, start);
+        assert_eq!(ranges[0].1, ranges[1].0);
+        assert_eq!(
+            ranges[1].1,
+            Utc.with_ymd_and_hms(2025, 1, 3, 0, 0, 0).unwrap()
+        );
+        assert_eq!(ranges[2].1, end);
+
+        assert_eq!(split_datetime_range(start, end, 1), vec![(start, end)]);
+        assert_eq!(split_datetime_range(end, start, 4), vec![(end, start)]);
+    }
+
+    #[test]
+    fn test_merge_by_score() {
+        let point = |score: f32| ScoredPoint {
+            score,
+            ..Default::default()
+        };
+
+        let merged = merge_by_score(vec![vec![point(0.9), point(0.2)], vec![point(0.5)]], 2);
+        let scores: Vec<f32> = merged.iter().map(|p| p.score).collect();
+        assert_eq!(scores, vec![0.9, 0.5]);
+    }
+
+    #[test]
+    fn test_merge_by_rank() {
+        let point = |id: u64, score: f32| ScoredPoint {
+            id: Some(id.into()),
+            score,
+            ..Default::default()
+        };
+
+        // The second model scores lower across the board but its best hit
+        // still ranks next to the first model's
+        let merged = merge_by_rank(
+            vec![
+                vec![point(1, 0.9), point(2, 0.85), point(3, 0.8)],
+                vec![point(4, 0.3), point(5, 0.2)],
+            ],
+            3,
+        );
+        let ids: Vec<_> = merged.iter().map(|p| p.id.clone().unwrap()).collect();
+        assert_eq!(ids, vec![1.into(), 4.into(), 2.into()]);
+        assert_eq!(merged[0].score, merged[1].score);
+    }
+}
@@ -0,0 +1,110 @@
+//! Search Groups
+//!
+//! Searches grouped by camera, returning the best hits of each camera instead
+//! of letting one busy camera fill the whole result list.
+
+use crate::error::AppError;
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::group_id::Kind as GroupKind;
+use qdrant_client::qdrant::{GroupId, PointGroup, SearchPointGroups, SearchPoints};
+use std::sync::Arc;
+use tokio::task::JoinSet;
+use tracing::instrument;
+
+/// Payload field results are grouped by
+pub const GROUP_BY_FIELD: &str = "camera_id";
+
+/// Run `base` as a grouped search in every collection and return the
+/// `base.limit` best groups of up to `group_size` hits each
+#[instrument(skip_all, fields(collections = collections.len(), group_size))]
+pub async fn group_search(
+    qdrant: Arc<Qdrant>,
+    base: &SearchPoints,
+    collections: &[String],
+    group_size: u32,
+) -> Result<Vec<PointGroup>, AppError> {
+    let mut tasks = JoinSet::new();
+    for collection in collections {
+        let request = SearchPointGroups {
+            collection_name: collection.clone(),
+            vector: base.vector.clone(),
+            vector_name: base.vector_name.clone(),
+            filter: base.filter.clone(),
+            limit: base.limit as u32,
+            with_payload: base.with_payload.clone(),
+            params: base.params,
+            score_threshold: base.score_threshold,
+            group_by: GROUP_BY_FIELD.to_string(),
+            group_size,
+            read_consistency: base.read_consistency,
+            ..Default::default()
+        };
+
+        let qdrant = qdrant.clone();
+        tasks.spawn(async move { qdrant.search_groups(request).await });
+    }
+
+    let mut partials = Vec::with_capacity(tasks.len());
+    while let Some(joined) = tasks.join_next().await {
+        let response = joined
+            .map_err(|e| AppError::Qdrant(format!("Group search task failed: {}", e)))?
+            .map_err(|e| AppError::Qdrant(format!("Qdrant group search error: {}", e)))?;
+        partials.push(response.result.map(|r| r.groups).unwrap_or_default());
+    }
+
+    Ok(merge_groups(partials, base.limit as usize))
+}
+
+/// Merge partial group lists, keeping the `limit` groups with the best top hit
+///
+/// A camera is stored in a single shard, so groups never need combining.
+pub fn merge_groups(partials: Vec<Vec<PointGroup>>, limit: usize) -> Vec<PointGroup> {
+    let best = |group: &PointGroup| group.hits.first().map(|p| p.score).unwrap_or(f32::MIN);
+    let mut merged: Vec<PointGroup> = partials.into_iter().flatten().collect();
+    merged.sort_by(|a, b| best(b).total_cmp(&best(a)));
+    merged.truncate(limit);
+    merged
+}
+
+/// Group key as a string
+pub fn group_id_to_string(id: &GroupId) -> String {
+    match &id.kind {
+        Some(GroupKind::StringValue(s)) => s.clone(),
+        Some(GroupKind::UnsignedValue(n)) => n.to_string(),
+        Some(GroupKind::IntegerValue(n)) => n.to_string(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qdrant_client::qdrant::ScoredPoint;
+
+    #[test]
+    fn test_merge_groups() {
+        let group = |camera: &str, score: f32| PointGroup {
+            id: Some(GroupId {
+                kind: Some(GroupKind::StringValue(camera.to_string())),
+            }),
+            hits: vec![ScoredPoint {
+                score,
+                ..Default::default()
+            }],
+            lookup: None,
+        };
+
+        let merged = merge_groups(
+            vec![
+                vec![group("cctv01", 0.7), group("cctv02", 0.4)],
+                vec![group("cctv03", 0.9)],
+            ],
+            2,
+        );
+        let cameras: Vec<String> = merged
+            .iter()
+            .map(|g| g.id.as_ref().map(group_id_to_string).unwrap_or_default())
+            .collect();
+        assert_eq!(cameras, vec!["cctv03", "cctv01"]);
+    }
+}
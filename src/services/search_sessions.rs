@@ -0,0 +1,347 @@
+//! Search Sessions
+//!
+//! In-memory investigation sessions collecting positive and negative example
+//! images; searches in a session become Qdrant recommend queries.
+
+use crate::error::AppError;
+use crate::services::{
+    TENANT_FIELD, dense_vector, extract_string, merge_by_score, parse_point_id, point_id_to_string,
+};
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{
+    Condition, GetPointsBuilder, PayloadIncludeSelector, PointId, RecommendPoints, ScoredPoint,
+    SearchPoints, Vector,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+use tracing::instrument;
+
+/// Sessions unused for this long are dropped
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(2 * 60 * 60);
+/// Upper bound on live sessions; the least recently used is evicted first
+const MAX_SESSIONS: usize = 1000;
+/// Examples kept per polarity; the oldest are dropped first
+pub const MAX_SESSION_EXAMPLES: usize = 32;
+
+/// A stored image marked as relevant or irrelevant, with its embedding
+#[derive(Debug, Clone)]
+pub struct SessionExample {
+    pub id: PointId,
+    pub vector: Vec<f32>,
+}
+
+/// Examples collected in one session
+#[derive(Debug, Clone, Default)]
+pub struct SessionExamples {
+    pub positive: Vec<SessionExample>,
+    pub negative: Vec<SessionExample>,
+}
+
+impl SessionExamples {
+    pub fn is_empty(&self) -> bool {
+        self.positive.is_empty() && self.negative.is_empty()
+    }
+
+    pub fn positive_ids(&self) -> Vec<String> {
+        self.positive
+            .iter()
+            .map(|e| point_id_to_string(&e.id))
+            .collect()
+    }
+
+    pub fn negative_ids(&self) -> Vec<String> {
+        self.negative
+            .iter()
+            .map(|e| point_id_to_string(&e.id))
+            .collect()
+    }
+
+    /// Add examples, moving an image that changes polarity and keeping the
+    /// newest `MAX_SESSION_EXAMPLES` of each
+    pub fn add(&mut self, positive: Vec<SessionExample>, negative: Vec<SessionExample>) {
+        push_examples(&mut self.positive, &mut self.negative, positive);
+        push_examples(&mut self.negative, &mut self.positive, negative);
+    }
+}
+
+fn push_examples(
+    examples: &mut Vec<SessionExample>,
+    others: &mut Vec<SessionExample>,
+    new: Vec<SessionExample>,
+) {
+    for example in new {
+        examples.retain(|e| e.id != example.id);
+        others.retain(|e| e.id != example.id);
+        examples.push(example);
+    }
+    let excess = examples.len().saturating_sub(MAX_SESSION_EXAMPLES);
+    examples.drain(..excess);
+}
+
+struct Session {
+    examples: SessionExamples,
+    /// Tenant that started it; other tenants can't use it
+    tenant: Option<String>,
+    last_used: Instant,
+}
+
+/// Live search sessions, shared by the HTTP workers
+#[derive(Default)]
+pub struct SearchSessions {
+    sessions: RwLock<HashMap<String, Session>>,
+}
+
+impl SearchSessions {
+    /// Start an empty session of `tenant` and return its ID
+    pub fn create(&self, tenant: Option<&str>) -> String {
+        let id = format!("{:032x}", rand::random::<u128>());
+        let now = Instant::now();
+
+        let mut sessions = self.sessions.write().unwrap_or_else(|e| e.into_inner());
+        sessions.retain(|_, s| now.duration_since(s.last_used) < SESSION_IDLE_TIMEOUT);
+        if sessions.len() >= MAX_SESSIONS
+            && let Some(oldest) = sessions
+                .iter()
+                .min_by_key(|(_, s)| s.last_used)
+                .map(|(id, _)| id.clone())
+        {
+            sessions.remove(&oldest);
+        }
+        sessions.insert(
+            id.clone(),
+            Session {
+                examples: SessionExamples::default(),
+                tenant: tenant.map(str::to_string),
+                last_used: now,
+            },
+        );
+        id
+    }
+
+    /// Apply `f` to a live session of `tenant`, refreshing its idle timer;
+    /// sessions of other tenants are unknown
+    pub fn update<T>(
+        &self,
+        id: &str,
+        tenant: Option<&str>,
+        f: impl FnOnce(&mut SessionExamples) -> T,
+    ) -> Result<T, AppError> {
+        let mut sessions = self.sessions.write().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        match sessions.get_mut(id) {
+            Some(session) if !session.visible_to(tenant) => Err(unknown_session(id)),
+            Some(session) if now.duration_since(session.last_used) < SESSION_IDLE_TIMEOUT => {
+                session.last_used = now;
+                Ok(f(&mut session.examples))
+            }
+            _ => {
+                sessions.remove(id);
+                Err(unknown_session(id))
+            }
+        }
+    }
+
+    /// Snapshot of the examples of a session of `tenant`
+    pub fn examples(&self, id: &str, tenant: Option<&str>) -> Result<SessionExamples, AppError> {
+        self.update(id, tenant, |examples| examples.clone())
+    }
+
+    /// End a session of `tenant`; returns `false` if it did not exist
+    pub fn remove(&self, id: &str, tenant: Option<&str>) -> bool {
+        let mut sessions = self.sessions.write().unwrap_or_else(|e| e.into_inner());
+        if !sessions.get(id).is_some_and(|s| s.visible_to(tenant)) {
+            return false;
+        }
+        sessions.remove(id).is_some()
+    }
+}
+
+impl Session {
+    fn visible_to(&self, tenant: Option<&str>) -> bool {
+        tenant.is_none_or(|tenant| self.tenant.as_deref() == Some(tenant))
+    }
+}
+
+fn unknown_session(id: &str) -> AppError {
+    AppError::NotFound(format!("Unknown or expired session: {}", id))
+}
+
+/// Look up the stored `vector_name` embeddings of `ids` across `collections`
+///
+/// Fails with the IDs that were not found in any collection; images of
+/// another tenant than `tenant` count as not found.
+pub async fn fetch_examples(
+    qdrant: &Qdrant,
+    collections: &[String],
+    vector_name: &str,
+    ids: &[String],
+    tenant: Option<&str>,
+) -> Result<Vec<SessionExample>, AppError> {
+    let point_ids: Vec<PointId> = ids.iter().map(|id| parse_point_id(id)).collect();
+    let mut vectors: HashMap<String, Vec<f32>> = HashMap::new();
+
+    for collection in collections {
+        if point_ids.is_empty() || vectors.len() == ids.len() {
+            break;
+        }
+        let mut request = GetPointsBuilder::new(collection, point_ids.clone()).with_vectors(true);
+        if tenant.is_some() {
+            request = request.with_payload(PayloadIncludeSelector {
+                fields: vec![TENANT_FIELD.to_string()],
+            });
+        }
+        let response = qdrant
+            .get_points(request)
+            .await
+            .map_err(|e| AppError::Qdrant(format!("Failed to read examples: {}", e)))?;
+
+        for point in response.result {
+            if tenant.is_some_and(|tenant| extract_string(&point.payload, TENANT_FIELD) != tenant) {
+                continue;
+            }
+            let vector = dense_vector(point.vectors.as_ref(), vector_name);
+            if let (Some(id), Some(vector)) = (point.id.as_ref(), vector) {
+                vectors.insert(point_id_to_string(id), vector);
+            }
+        }
+    }
+
+    let missing: Vec<&str> = ids
+        .iter()
+        .filter(|id| !vectors.contains_key(id.as_str()))
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return Err(AppError::NotFound(format!(
+            "Unknown image IDs: {}",
+            missing.join(", ")
+        )));
+    }
+
+    Ok(ids
+        .iter()
+        .zip(point_ids)
+        .map(|(id, point_id)| SessionExample {
+            id: point_id,
+            vector: vectors.get(id).cloned().unwrap_or_default(),
+        })
+        .collect())
+}
+
+/// Run `base` as a recommend query in every collection and merge the hits
+///
+/// A non-empty query vector in `base` counts as one more positive example;
+/// the examples themselves are excluded from the results.
+#[instrument(skip_all, fields(
+    collections = collections.len(),
+    positive = examples.positive.len(),
+    negative = examples.negative.len()
+))]
+pub async fn recommend_fanout(
+    qdrant: Arc<Qdrant>,
+    base: &SearchPoints,
+    collections: &[String],
+    examples: &SessionExamples,
+) -> Result<Vec<ScoredPoint>, AppError> {
+    let seen: Vec<PointId> = examples
+        .positive
+        .iter()
+        .chain(&examples.negative)
+        .map(|e| e.id.clone())
+        .collect();
+    let mut filter = base.filter.clone().unwrap_or_default();
+    filter.must_not.push(Condition::has_id(seen));
+
+    let mut positive_vectors: Vec<Vector> = Some(&base.vector)
+        .filter(|vector| !vector.is_empty())
+        .map(|vector| Vector::from(vector.clone()))
+        .into_iter()
+        .collect();
+    positive_vectors.extend(
+        examples
+            .positive
+            .iter()
+            .map(|e| Vector::from(e.vector.clone())),
+    );
+    let negative_vectors: Vec<Vector> = examples
+        .negative
+        .iter()
+        .map(|e| Vector::from(e.vector.clone()))
+        .collect();
+
+    let mut tasks = JoinSet::new();
+    for collection in collections {
+        let request = RecommendPoints {
+            collection_name: collection.clone(),
+            positive_vectors: positive_vectors.clone(),
+            negative_vectors: negative_vectors.clone(),
+            filter: Some(filter.clone()),
+            using: base.vector_name.clone(),
+            limit: base.limit,
+            score_threshold: base.score_threshold,
+            with_payload: base.with_payload.clone(),
+            params: base.params,
+            read_consistency: base.read_consistency,
+            ..Default::default()
+        };
+
+        let qdrant = qdrant.clone();
+        tasks.spawn(async move { qdrant.recommend(request).await });
+    }
+
+    let mut partials = Vec::with_capacity(tasks.len());
+    while let Some(joined) = tasks.join_next().await {
+        let response = joined
+            .map_err(|e| AppError::Qdrant(format!("Recommend task failed: {}", e)))?
+            .map_err(|e| AppError::Qdrant(format!("Qdrant recommend error: {}", e)))?;
+        partials.push(response.result);
+    }
+
+    Ok(merge_by_score(partials, base.limit as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example(id: u64) -> SessionExample {
+        SessionExample {
+            id: id.into(),
+            vector: vec![id as f32],
+        }
+    }
+
+    #[test]
+    fn test_add_examples() {
+        let mut examples = SessionExamples::default();
+        examples.add(vec![example(1), example(2)], vec![example(3)]);
+        examples.add(vec![example(3)], vec![example(1)]);
+
+        assert_eq!(examples.positive_ids(), vec!["2", "3"]);
+        assert_eq!(examples.negative_ids(), vec!["1"]);
+
+        examples.add((100..140).map(example).collect(), Vec::new());
+        assert_eq!(examples.positive.len(), MAX_SESSION_EXAMPLES);
+        assert_eq!(examples.positive_ids().last().unwrap(), "139");
+    }
+
+    #[test]
+    fn test_unknown_session() {
+        let sessions = SearchSessions::default();
+        let id = sessions.create(Some("site-a"));
+        assert!(sessions.examples(&id, Some("site-a")).unwrap().is_empty());
+        // Sessions of other tenants are unknown
+        assert!(matches!(
+            sessions.examples(&id, Some("site-b")),
+            Err(AppError::NotFound(_))
+        ));
+        assert!(!sessions.remove(&id, Some("site-b")));
+        assert!(sessions.remove(&id, Some("site-a")));
+        assert!(matches!(
+            sessions.examples(&id, None),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+//! Search Tuning
+//!
+//! Simulates a search under several candidate Qdrant settings to help tune
+//! `hnsw_ef` and oversampling for a given collection size.
+
+use crate::error::AppError;
+use crate::models::search::SearchParamTrial;
+use crate::services::point_id_to_string;
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{ScoredPoint, SearchParams, SearchPoints};
+use std::collections::HashSet;
+use std::time::Instant;
+use tracing::instrument;
+
+/// HNSW `ef` values tried in debug mode (`None` = collection default)
+pub const HNSW_EF_CANDIDATES: &[Option<u64>] = &[None, Some(32), Some(64), Some(128), Some(256)];
+
+/// Multipliers applied to `top_k` to test oversampling
+pub const LIMIT_FACTORS: &[u64] = &[1, 2, 4];
+
+/// Outcome of a debug search: exact reference results plus candidate trials
+pub struct TuningReport {
+    pub reference: Vec<ScoredPoint>,
+    pub reference_latency_ms: u64,
+    pub trials: Vec<SearchParamTrial>,
+}
+
+/// Run `base` as an exact search, then once per candidate setting,
+/// measuring latency and overlap with the exact top-k
+#[instrument(skip_all, fields(collection = %base.collection_name))]
+pub async fn simulate_search_params(
+    qdrant: &Qdrant,
+    base: &SearchPoints,
+) -> Result<TuningReport, AppError> {
+    let top_k = base.limit;
+
+    let mut exact = base.clone();
+    exact.params = Some(SearchParams {
+        exact: Some(true),
+        ..base.params.unwrap_or_default()
+    });
+    let (reference, reference_latency_ms) = timed_search(qdrant, exact).await?;
+    let reference_ids: Vec<String> = reference.iter().map(scored_point_id).collect();
+
+    let mut trials = Vec::with_capacity(HNSW_EF_CANDIDATES.len() * LIMIT_FACTORS.len());
+    for &hnsw_ef in HNSW_EF_CANDIDATES {
+        for &factor in LIMIT_FACTORS {
+            let mut request = base.clone();
+            request.limit = top_k * factor;
+            request.params = Some(SearchParams {
+                hnsw_ef,
+                exact: Some(false),
+                ..base.params.unwrap_or_default()
+            });
+
+            let (points, latency_ms) = timed_search(qdrant, request).await?;
+            let ids: Vec<String> = points
+                .iter()
+                .take(top_k as usize)
+                .map(scored_point_id)
+                .collect();
+
+            trials.push(SearchParamTrial {
+                hnsw_ef,
+                limit: top_k * factor,
+                latency_ms,
+                overlap: result_overlap(&reference_ids, &ids),
+            });
+        }
+    }
+
+    Ok(TuningReport {
+        reference,
+        reference_latency_ms,
+        trials,
+    })
+}
+
+/// Fraction of `reference` IDs present in `candidate` (1.0 when reference is empty)
+pub fn result_overlap(reference: &[String], candidate: &[String]) -> f32 {
+    if reference.is_empty() {
+        return 1.0;
+    }
+
+    let candidate: HashSet<&String> = candidate.iter().collect();
+    let hits = reference.iter().filter(|id| candidate.contains(id)).count();
+    hits as f32 / reference.len() as f32
+}
+
+async fn timed_search(
+    qdrant: &Qdrant,
+    request: SearchPoints,
+) -> Result<(Vec<ScoredPoint>, u64), AppError> {
+    let started = Instant::now();
+    let response = qdrant
+        .search_points(request)
+        .await
+        .map_err(|e| AppError::Qdrant(format!("Qdrant search error: {}", e)))?;
+    Ok((response.result, started.elapsed().as_millis() as u64))
+}
+
+fn scored_point_id(point: &ScoredPoint) -> String {
+    point
+        .id
+        .as_ref()
+        .map(point_id_to_string)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_result_overlap() {
+        let reference = ids(&["1", "2", "3", "4"]);
+        assert_eq!(result_overlap(&reference, &ids(&["1", "2", "3", "4"])), 1.0);
+        assert_eq!(result_overlap(&reference, &ids(&["4", "9", "2"])), 0.5);
+        assert_eq!(result_overlap(&reference, &[]), 0.0);
+        assert_eq!(result_overlap(&[], &ids(&["1"])), 1.0);
+    }
+}
@@ -0,0 +1,158 @@
+//! Shadow Search
+//!
+//! Replays a sample of live searches against an alternate collection, and
+//! optionally an alternate embedding service, and logs how the results
+//! differ from the live response without affecting it.
+
+use crate::config::technical;
+use crate::error::AppError;
+use crate::services::{RequestMetrics, point_id_to_string, request_text_embedding};
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{ScoredPoint, SearchPoints};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Semaphore;
+use tracing::{debug, info, warn};
+
+/// Where shadowed searches are replayed
+#[derive(Debug, Clone)]
+pub struct ShadowTarget {
+    pub collection: String,
+    /// Re-embed the query with this AI service (`None` = reuse the live vector)
+    pub ai_service_url: Option<String>,
+}
+
+/// How a shadow result list differs from the live one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowComparison {
+    /// Shared hits over the length of the longer list (1.0 when both are empty)
+    pub overlap: f64,
+    /// Both lists have the same first hit
+    pub top_hit_matches: bool,
+}
+
+/// Compare live and shadow result IDs
+pub fn compare_results(primary: &[String], shadow: &[String]) -> ShadowComparison {
+    let longest = primary.len().max(shadow.len());
+    let overlap = if longest == 0 {
+        1.0
+    } else {
+        let primary: HashSet<&String> = primary.iter().collect();
+        shadow.iter().filter(|id| primary.contains(id)).count() as f64 / longest as f64
+    };
+    ShadowComparison {
+        overlap,
+        top_hit_matches: primary.first() == shadow.first(),
+    }
+}
+
+/// Shadow replays in flight, bounded so they cannot pile up behind a slow
+/// shadow target
+pub struct ShadowSearch {
+    permits: Arc<Semaphore>,
+}
+
+impl Default for ShadowSearch {
+    fn default() -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(technical::MAX_SHADOW_IN_FLIGHT)),
+        }
+    }
+}
+
+impl ShadowSearch {
+    /// Replay `base` against `target` in the background and compare it with
+    /// the live `primary` hits
+    ///
+    /// `query` is re-embedded when the target has its own AI service. The
+    /// replay is dropped when too many are already running.
+    #[allow(clippy::too_many_arguments)]
+    pub fn spawn(
+        &self,
+        qdrant: Arc<Qdrant>,
+        http_client: reqwest::Client,
+        metrics: Arc<RequestMetrics>,
+        target: ShadowTarget,
+        query: String,
+        mut base: SearchPoints,
+        primary: &[ScoredPoint],
+        primary_ms: i64,
+    ) {
+        let Ok(permit) = self.permits.clone().try_acquire_owned() else {
+            metrics.record_shadow_skipped();
+            debug!(target: "shadow", "Shadow search skipped, too many in flight");
+            return;
+        };
+        let primary: Vec<String> = primary.iter().map(hit_id).collect();
+
+        tokio::spawn(async move {
+            let _permit = permit;
+            let started = Instant::now();
+            let result: Result<Vec<ScoredPoint>, AppError> = async {
+                if let Some(url) = &target.ai_service_url {
+                    base.vector = request_text_embedding(&http_client, url, &query).await?;
+                }
+                base.collection_name = target.collection.clone();
+                qdrant
+                    .search_points(base)
+                    .await
+                    .map(|response| response.result)
+                    .map_err(|e| AppError::Qdrant(format!("Qdrant search error: {}", e)))
+            }
+            .await;
+            let shadow_ms = started.elapsed().as_millis() as u64;
+
+            match result {
+                Ok(points) => {
+                    let shadow: Vec<String> = points.iter().map(hit_id).collect();
+                    let comparison = compare_results(&primary, &shadow);
+                    metrics.record_shadow_search(comparison.overlap, comparison.top_hit_matches);
+                    info!(
+                        target: "shadow",
+                        collection = %target.collection,
+                        overlap = comparison.overlap,
+                        top_hit_matches = comparison.top_hit_matches,
+                        primary_ms,
+                        shadow_ms,
+                        primary_hits = ?primary,
+                        shadow_hits = ?shadow,
+                        "Shadow search compared"
+                    );
+                }
+                Err(e) => {
+                    metrics.record_shadow_error();
+                    warn!(target: "shadow", collection = %target.collection, error = %e, "Shadow search failed");
+                }
+            }
+        });
+    }
+}
+
+fn hit_id(point: &ScoredPoint) -> String {
+    point
+        .id
+        .as_ref()
+        .map(point_id_to_string)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_results() {
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+
+        let same = compare_results(&ids(&["1", "2"]), &ids(&["1", "2"]));
+        assert_eq!(same.overlap, 1.0);
+        assert!(same.top_hit_matches);
+
+        let partial = compare_results(&ids(&["1", "2", "3", "4"]), &ids(&["2", "1"]));
+        assert_eq!(partial.overlap, 0.5);
+        assert!(!partial.top_hit_matches);
+
+        assert_eq!(compare_results(&[], &[]).overlap, 1.0);
+    }
+}
@@ -0,0 +1,231 @@
+//! Shard Rebalancing
+//!
+//! Moves points whose camera now hashes to a different shard collection,
+//! e.g. after changing `COLLECTION_SHARDS` or sharding an existing collection.
+
+use crate::error::AppError;
+use crate::models::admin::RebalanceStatus;
+use crate::services::{ShardRouter, extract_string, stored_vectors};
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{
+    DeletePointsBuilder, PointId, PointStruct, PointsIdsList, RetrievedPoint, ScrollPointsBuilder,
+    UpsertPoints,
+};
+use std::sync::RwLock;
+use tracing::{error, info, instrument};
+
+/// Points read per scroll page while rebalancing
+const REBALANCE_PAGE_SIZE: u32 = 256;
+
+/// Rebalancing job state shared with the admin endpoints
+#[derive(Default)]
+pub struct ShardRebalancer {
+    status: RwLock<RebalanceStatus>,
+}
+
+impl ShardRebalancer {
+    pub fn status(&self) -> RebalanceStatus {
+        self.status
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Mark a run as started; returns `false` if one is already running
+    pub fn try_start(&self) -> bool {
+        let mut status = self.status.write().unwrap_or_else(|e| e.into_inner());
+        if status.running {
+            return false;
+        }
+        *status = RebalanceStatus {
+            running: true,
+            started_at: Some(chrono::Utc::now().to_rfc3339()),
+            ..Default::default()
+        };
+        true
+    }
+
+    fn update(&self, f: impl FnOnce(&mut RebalanceStatus)) {
+        f(&mut self.status.write().unwrap_or_else(|e| e.into_inner()));
+    }
+
+    /// Move every point to the collection its camera maps to
+    ///
+    /// Scans all shard collections plus the unsharded base collection and
+    /// shards left over from a larger shard count. Call `try_start` first.
+    pub async fn run(&self, qdrant: &Qdrant, router: &ShardRouter) {
+        let result = self.rebalance(qdrant, router).await;
+
+        self.update(|status| {
+            status.running = false;
+            status.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            status.error = result.err().map(|e| e.to_string());
+        });
+
+        let status = self.status();
+        match &status.error {
+            Some(e) => error!(error = %e, "Shard rebalancing failed"),
+            None => info!(
+                scanned = status.scanned,
+                moved = status.moved,
+                "Shard rebalancing finished"
+            ),
+        }
+    }
+
+    async fn rebalance(&self, qdrant: &Qdrant, router: &ShardRouter) -> Result<(), AppError> {
+        let existing: Vec<String> = qdrant
+            .list_collections()
+            .await
+            .map_err(|e| AppError::Qdrant(format!("Failed to list collections: {}", e)))?
+            .collections
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+
+        for source in source_collections(router, &existing) {
+            self.rebalance_collection(qdrant, router, &source).await?;
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, qdrant, router))]
+    async fn rebalance_collection(
+        &self,
+        qdrant: &Qdrant,
+        router: &ShardRouter,
+        source: &str,
+    ) -> Result<(), AppError> {
+        let mut offset: Option<PointId> = None;
+
+        loop {
+            let mut scroll = ScrollPointsBuilder::new(source)
+                .limit(REBALANCE_PAGE_SIZE)
+                .with_payload(true)
+                .with_vectors(true);
+            if let Some(offset) = offset.take() {
+                scroll = scroll.offset(offset);
+            }
+
+            let page = qdrant
+                .scroll(scroll)
+                .await
+                .map_err(|e| AppError::Qdrant(format!("Failed to scroll '{}': {}", source, e)))?;
+            let scanned = page.result.len() as u64;
+
+            // Group misplaced points by their target collection
+            let mut moves: Vec<(String, Vec<PointStruct>)> = Vec::new();
+            for point in page.result {
+                let target = router.collection_for(&extract_string(&point.payload, "camera_id"));
+                if target == source {
+                    continue;
+                }
+                let Some(point) = to_point_struct(point) else {
+                    continue;
+                };
+                match moves.iter_mut().find(|(c, _)| c == target) {
+                    Some((_, points)) => points.push(point),
+                    None => moves.push((target.to_string(), vec![point])),
+                }
+            }
+
+            let mut moved = 0;
+            for (target, points) in moves {
+                let ids: Vec<PointId> = points.iter().filter_map(|p| p.id.clone()).collect();
+                moved += ids.len() as u64;
+
+                // Copy first so a failure never loses points
+                qdrant
+                    .upsert_points(UpsertPoints {
+                        collection_name: target.clone(),
+                        wait: Some(true),
+                        points,
+                        ..Default::default()
+                    })
+                    .await
+                    .map_err(|e| {
+                        AppError::Qdrant(format!("Failed to copy points to '{}': {}", target, e))
+                    })?;
+                qdrant
+                    .delete_points(
+                        DeletePointsBuilder::new(source)
+                            .points(PointsIdsList { ids })
+                            .wait(true),
+                    )
+                    .await
+                    .map_err(|e| {
+                        AppError::Qdrant(format!(
+                            "Failed to delete moved points from '{}': {}",
+                            source, e
+                        ))
+                    })?;
+            }
+
+            self.update(|status| {
+                status.scanned += scanned;
+                status.moved += moved;
+            });
+
+            match page.next_page_offset {
+                Some(next) => offset = Some(next),
+                None => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Existing collections that may hold points in the wrong shard
+fn source_collections(router: &ShardRouter, existing: &[String]) -> Vec<String> {
+    let shard_prefix = format!("{}_shard", router.base_collection());
+
+    existing
+        .iter()
+        .filter(|name| {
+            router.collections().contains(name)
+                || name.as_str() == router.base_collection()
+                || name
+                    .strip_prefix(&shard_prefix)
+                    .is_some_and(|index| index.parse::<u32>().is_ok())
+        })
+        .cloned()
+        .collect()
+}
+
+/// Convert a scrolled point with its dense vectors back into an upsertable point
+fn to_point_struct(point: RetrievedPoint) -> Option<PointStruct> {
+    let vectors = stored_vectors(point.vectors.as_ref()?)?;
+    Some(PointStruct::new(point.id?, vectors, point.payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_collections() {
+        let router = ShardRouter::new("vehicles", 2);
+        let existing: Vec<String> = [
+            "vehicles",
+            "vehicles_migrations",
+            "vehicles_shard0",
+            "vehicles_shard0_migrations",
+            "vehicles_shard1",
+            "vehicles_shard2",
+            "other",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        assert_eq!(
+            source_collections(&router, &existing),
+            vec![
+                "vehicles",
+                "vehicles_shard0",
+                "vehicles_shard1",
+                "vehicles_shard2"
+            ]
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Shard Router
+//!
+//! Maps cameras to one of N Qdrant collections using a consistent hash ring,
+//! so changing the shard count only moves a fraction of the cameras.
+
+use std::collections::BTreeMap;
+
+/// Virtual nodes per shard on the hash ring; more nodes spread cameras more evenly
+const VIRTUAL_NODES: u32 = 64;
+
+/// Consistent-hash router from `camera_id` to collection
+#[derive(Debug, Clone)]
+pub struct ShardRouter {
+    base_collection: String,
+    collections: Vec<String>,
+    ring: BTreeMap<u64, usize>,
+}
+
+impl ShardRouter {
+    /// Router over `shards` collections derived from `base_collection`
+    ///
+    /// With a single shard the base collection is used unchanged.
+    pub fn new(base_collection: &str, shards: u32) -> Self {
+        let collections: Vec<String> = if shards <= 1 {
+            vec![base_collection.to_string()]
+        } else {
+            (0..shards)
+                .map(|i| shard_collection_name(base_collection, i))
+                .collect()
+        };
+
+        let mut ring = BTreeMap::new();
+        for (index, collection) in collections.iter().enumerate() {
+            for vnode in 0..VIRTUAL_NODES {
+                ring.insert(
+                    hash_key(format!("{}#{}", collection, vnode).as_bytes()),
+                    index,
+                );
+            }
+        }
+
+        Self {
+            base_collection: base_collection.to_string(),
+            collections,
+            ring,
+        }
+    }
+
+    /// Unsharded collection name the shard names are derived from
+    pub fn base_collection(&self) -> &str {
+        &self.base_collection
+    }
+
+    /// All collections, in shard order
+    pub fn collections(&self) -> &[String] {
+        &self.collections
+    }
+
+    /// Collection holding the images of `camera_id`
+    pub fn collection_for(&self, camera_id: &str) -> &str {
+        let hash = hash_key(camera_id.as_bytes());
+        let index = self
+            .ring
+            .range(hash..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+            .map(|(_, index)| *index)
+            .unwrap_or(0);
+        &self.collections[index]
+    }
+
+    /// Collections to search for the given cameras (all when unrestricted)
+    pub fn collections_for(&self, camera_ids: Option<&[String]>) -> Vec<String> {
+        match camera_ids.filter(|ids| !ids.is_empty()) {
+            Some(ids) => {
+                let mut collections: Vec<String> = Vec::new();
+                for id in ids {
+                    let collection = self.collection_for(id);
+                    if !collections.iter().any(|c| c == collection) {
+                        collections.push(collection.to_string());
+                    }
+                }
+                collections
+            }
+            None => self.collections.clone(),
+        }
+    }
+}
+
+/// Name of shard `index` of `base_collection`
+pub fn shard_collection_name(base_collection: &str, index: u32) -> String {
+    format!("{}_shard{}", base_collection, index)
+}
+
+/// 64-bit FNV-1a with a final avalanche step; stable across builds, unlike
+/// `DefaultHasher`. Only spreads cameras over the ring, and must not change,
+/// or existing cameras would be routed to other shards
+fn hash_key(bytes: &[u8]) -> u64 {
+    let mut hash = bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
+
+    // Similar keys (`cctv01`, `cctv02`) otherwise land close together on the ring
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ceb9fe1a85ec53);
+    hash ^ (hash >> 33)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_shard_uses_base_collection() {
+        let router = ShardRouter::new("vehicles", 1);
+        assert_eq!(router.collections(), ["vehicles"]);
+        assert_eq!(router.collection_for("cctv01"), "vehicles");
+    }
+
+    #[test]
+    fn test_adding_a_shard_moves_few_cameras() {
+        let before = ShardRouter::new("vehicles", 4);
+        let after = ShardRouter::new("vehicles", 5);
+        let cameras: Vec<String> = (0..1000).map(|i| format!("cctv{:04}", i)).collect();
+
+        let moved = cameras
+            .iter()
+            .filter(|c| before.collection_for(c) != after.collection_for(c))
+            .count();
+        // Ideal is 1/5 of the cameras; a modulo hash would move ~4/5
+        assert!(moved < 350, "moved {} of 1000", moved);
+    }
+
+    #[test]
+    fn test_collections_for_cameras() {
+        let router = ShardRouter::new("vehicles", 3);
+        assert_eq!(router.collections_for(None).len(), 3);
+
+        let ids = vec!["cctv01".to_string(), "cctv01".to_string()];
+        assert_eq!(
+            router.collections_for(Some(&ids)),
+            vec![router.collection_for("cctv01").to_string()]
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! Latency SLOs
+//!
+//! Tracks per-endpoint latency objectives (e.g. 95% of searches under 800ms)
+//! over a rolling window and computes how fast the error budget is burning.
+
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// Minimum requests in the window before a burn rate can trigger an alert
+const MIN_ALERT_REQUESTS: u64 = 20;
+
+/// Latency objective for one route, parsed from `path:threshold_ms:objective`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SloTarget {
+    /// Route pattern as registered, e.g. `/search`
+    pub path: String,
+    /// Requests slower than this (or failing) count against the budget
+    pub threshold_ms: u64,
+    /// Fraction of requests that must be good, e.g. `0.95`
+    pub objective: f64,
+}
+
+impl FromStr for SloTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid SLO target '{}' (expected path:ms:objective)", s);
+
+        let mut parts = s.rsplitn(3, ':');
+        let objective: f64 = parts
+            .next()
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(invalid)?;
+        let threshold_ms: u64 = parts
+            .next()
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(invalid)?;
+        let path = parts
+            .next()
+            .filter(|p| p.starts_with('/'))
+            .ok_or_else(invalid)?;
+
+        if !(objective > 0.0 && objective < 1.0) {
+            return Err(format!(
+                "Invalid SLO objective in '{}': must be between 0 and 1",
+                s
+            ));
+        }
+
+        Ok(Self {
+            path: path.to_string(),
+            threshold_ms,
+            objective,
+        })
+    }
+}
+
+/// Request counts for one minute of the window
+#[derive(Debug, Clone, Copy)]
+struct MinuteBucket {
+    minute: u64,
+    total: u64,
+    good: u64,
+}
+
+/// Current state of one SLO over the rolling window
+#[derive(Debug, Clone)]
+pub struct SloStatus {
+    pub target: SloTarget,
+    pub total: u64,
+    pub good: u64,
+    /// Error budget consumption relative to the sustainable rate (1.0 = on budget)
+    pub burn_rate: f64,
+}
+
+/// Rolling-window SLO tracker shared by the HTTP workers
+pub struct SloTracker {
+    targets: Vec<SloTarget>,
+    window_minutes: u64,
+    buckets: Mutex<HashMap<String, VecDeque<MinuteBucket>>>,
+}
+
+impl SloTracker {
+    pub fn new(targets: Vec<SloTarget>, window_minutes: u64) -> Self {
+        Self {
+            targets,
+            window_minutes: window_minutes.max(1),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record a finished request against the SLO for `path`, if any
+    pub fn record(&self, path: &str, elapsed_ms: u64, success: bool) {
+        self.record_at(path, elapsed_ms, success, current_minute());
+    }
+
+    fn record_at(&self, path: &str, elapsed_ms: u64, success: bool, minute: u64) {
+        let Some(target) = self.targets.iter().find(|t| t.path == path) else {
+            return;
+        };
+        let good = success && elapsed_ms <= target.threshold_ms;
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let window = buckets.entry(target.path.clone()).or_default();
+        match window.back_mut() {
+            Some(bucket) if bucket.minute == minute => {
+                bucket.total += 1;
+                bucket.good += good as u64;
+            }
+            _ => window.push_back(MinuteBucket {
+                minute,
+                total: 1,
+                good: good as u64,
+            }),
+        }
+
+        while window
+            .front()
+            .is_some_and(|b| b.minute + self.window_minutes <= minute)
+        {
+            window.pop_front();
+        }
+    }
+
+    /// Status of every configured SLO over the current window
+    pub fn statuses(&self) -> Vec<SloStatus> {
+        self.statuses_at(current_minute())
+    }
+
+    fn statuses_at(&self, minute: u64) -> Vec<SloStatus> {
+        let buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        self.targets
+            .iter()
+            .map(|target| {
+                let (total, good) = buckets
+                    .get(&target.path)
+                    .into_iter()
+                    .flatten()
+                    .filter(|b| b.minute + self.window_minutes > minute)
+                    .fold((0, 0), |(t, g), b| (t + b.total, g + b.good));
+
+                SloStatus {
+                    target: target.clone(),
+                    total,
+                    good,
+                    burn_rate: burn_rate(total, good, target.objective),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Observed error rate divided by the error rate the objective allows
+pub fn burn_rate(total: u64, good: u64, objective: f64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    let error_rate = (total - good) as f64 / total as f64;
+    error_rate / (1.0 - objective)
+}
+
+fn current_minute() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        / 60
+}
+
+/// Periodically check burn rates and POST an alert to `webhook_url` when an
+/// SLO crosses `threshold`
+///
+/// Each SLO alerts once per excursion and re-arms after dropping below the threshold.
+pub fn spawn_burn_rate_alerts(
+    tracker: std::sync::Arc<SloTracker>,
+    http_client: reqwest::Client,
+    webhook_url: String,
+    threshold: f64,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        let mut alerting: Vec<String> = Vec::new();
+
+        loop {
+            interval.tick().await;
+
+            for status in tracker.statuses() {
+                let path = &status.target.path;
+                let burning = status.total >= MIN_ALERT_REQUESTS && status.burn_rate >= threshold;
+
+                if !burning {
+                    alerting.retain(|p| p != path);
+                    continue;
+                }
+                if alerting.contains(path) {
+                    continue;
+                }
+
+                warn!(
+                    path = %path,
+                    burn_rate = status.burn_rate,
+                    good = status.good,
+                    total = status.total,
+                    "SLO error budget burning"
+                );
+                let alert = serde_json::json!({
+                    "path": path,
+                    "threshold_ms": status.target.threshold_ms,
+                    "objective": status.target.objective,
+                    "window_minutes": tracker.window_minutes,
+                    "total": status.total,
+                    "good": status.good,
+                    "burn_rate": status.burn_rate,
+                });
+                match http_client.post(&webhook_url).json(&alert).send().await {
+                    Ok(res) if res.status().is_success() => alerting.push(path.clone()),
+                    Ok(res) => warn!(status = %res.status(), "SLO alert webhook rejected alert"),
+                    Err(e) => warn!(error = %e, "Failed to send SLO alert"),
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_slo_target() {
+        let target: SloTarget = "/search:800:0.95".parse().unwrap();
+        assert_eq!(target.path, "/search");
+        assert_eq!(target.threshold_ms, 800);
+        assert_eq!(target.objective, 0.95);
+
+        assert!("/search:800".parse::<SloTarget>().is_err());
+        assert!("/search:800:1.5".parse::<SloTarget>().is_err());
+        assert!("search:800:0.95".parse::<SloTarget>().is_err());
+    }
+
+    #[test]
+    fn test_burn_rate_over_window() {
+        let tracker = SloTracker::new(vec!["/search:800:0.9".parse().unwrap()], 5);
+
+        for _ in 0..8 {
+            tracker.record_at("/search", 100, true, 100);
+        }
+        tracker.record_at("/search", 900, true, 100);
+        tracker.record_at("/search", 100, false, 101);
+        tracker.record_at("/insert_image", 5000, true, 101);
+
+        let status = &tracker.statuses_at(101)[0];
+        assert_eq!((status.total, status.good), (10, 8));
+        assert!((status.burn_rate - 2.0).abs() < 1e-9);
+
+        // Minute 100 has left the window
+        let status = &tracker.statuses_at(105)[0];
+        assert_eq!((status.total, status.good), (1, 0));
+    }
+}
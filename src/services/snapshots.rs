@@ -0,0 +1,200 @@
+//! Collection Snapshots
+//!
+//! Creates and lists Qdrant collection snapshots over gRPC and restores them
+//! through the REST recover endpoint, which has no gRPC equivalent.
+//! Snapshots are node-local: on a cluster, each one lives on the node that
+//! served the request.
+
+use crate::config::Config;
+use crate::error::AppError;
+use crate::models::admin::SnapshotInfo;
+use chrono::DateTime;
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{CreateSnapshotRequest, ListSnapshotsRequest, SnapshotDescription};
+use std::time::Duration;
+use tracing::{info, instrument};
+
+/// Restores can rewrite the whole collection; don't cut them short
+const RESTORE_TIMEOUT: Duration = Duration::from_secs(3600);
+
+fn snapshot_info(collection: &str, description: SnapshotDescription) -> SnapshotInfo {
+    SnapshotInfo {
+        collection: collection.to_string(),
+        name: description.name,
+        created_at: description
+            .creation_time
+            .and_then(|t| DateTime::from_timestamp(t.seconds, t.nanos.max(0) as u32))
+            .map(|t| t.to_rfc3339()),
+        size_bytes: description.size.max(0) as u64,
+        checksum: description.checksum,
+    }
+}
+
+/// Take a snapshot of `collection`
+#[instrument(skip(qdrant))]
+pub async fn create_snapshot(qdrant: &Qdrant, collection: &str) -> Result<SnapshotInfo, AppError> {
+    let response = qdrant
+        .create_snapshot(CreateSnapshotRequest {
+            collection_name: collection.to_string(),
+        })
+        .await
+        .map_err(|e| AppError::Qdrant(format!("Failed to snapshot {}: {}", collection, e)))?;
+    let description = response.snapshot_description.ok_or_else(|| {
+        AppError::Qdrant(format!("Qdrant returned no snapshot for {}", collection))
+    })?;
+
+    let snapshot = snapshot_info(collection, description);
+    info!(snapshot = %snapshot.name, size_bytes = snapshot.size_bytes, "Snapshot created");
+    Ok(snapshot)
+}
+
+/// Snapshots of `collections`, newest first
+pub async fn list_snapshots(
+    qdrant: &Qdrant,
+    collections: &[String],
+) -> Result<Vec<SnapshotInfo>, AppError> {
+    let mut snapshots = Vec::new();
+    for collection in collections {
+        let response = qdrant
+            .list_snapshots(ListSnapshotsRequest {
+                collection_name: collection.clone(),
+            })
+            .await
+            .map_err(|e| {
+                AppError::Qdrant(format!("Failed to list snapshots of {}: {}", collection, e))
+            })?;
+        snapshots.extend(
+            response
+                .snapshot_descriptions
+                .into_iter()
+                .map(|description| snapshot_info(collection, description)),
+        );
+    }
+    // RFC 3339 in UTC sorts chronologically
+    snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(snapshots)
+}
+
+/// REST client for snapshot recovery
+pub struct SnapshotRestorer {
+    http: reqwest::Client,
+    rest_url: Option<String>,
+    api_key: Option<String>,
+    snapshots_path: String,
+}
+
+impl SnapshotRestorer {
+    pub fn new(config: &Config) -> Result<Self, AppError> {
+        let mut builder = reqwest::Client::builder().timeout(RESTORE_TIMEOUT);
+        if let Some(ca_cert) = &config.qdrant_ca_cert {
+            let pem = std::fs::read(ca_cert).map_err(|e| {
+                AppError::Io(format!("Failed to read QDRANT_CA_CERT {}: {}", ca_cert, e))
+            })?;
+            let certificate = reqwest::Certificate::from_pem(&pem)
+                .map_err(|e| AppError::Config(format!("Invalid QDRANT_CA_CERT: {}", e)))?;
+            builder = builder.add_root_certificate(certificate);
+        }
+        let http = builder
+            .build()
+            .map_err(|e| AppError::Config(format!("Failed to build REST client: {}", e)))?;
+
+        Ok(Self {
+            http,
+            rest_url: config
+                .qdrant_rest_url
+                .as_ref()
+                .map(|url| url.trim_end_matches('/').to_string()),
+            api_key: config.qdrant_api_key.clone(),
+            snapshots_path: config.qdrant_snapshots_path.clone(),
+        })
+    }
+
+    /// Location of a snapshot file on the Qdrant node
+    fn location(&self, collection: &str, snapshot: &str) -> String {
+        format!(
+            "file://{}/{}/{}",
+            self.snapshots_path.trim_end_matches('/'),
+            collection,
+            snapshot
+        )
+    }
+
+    /// Replace `target` with snapshot `snapshot` of `collection`
+    ///
+    /// `target` is created if missing, so a snapshot can also be restored
+    /// next to the collection it was taken of.
+    #[instrument(skip(self))]
+    pub async fn restore(
+        &self,
+        collection: &str,
+        snapshot: &str,
+        target: &str,
+    ) -> Result<(), AppError> {
+        let Some(rest_url) = &self.rest_url else {
+            return Err(AppError::InvalidRequest(
+                "Restoring snapshots requires QDRANT_REST_URL".to_string(),
+            ));
+        };
+        if snapshot.contains('/') || collection.contains('/') {
+            return Err(AppError::InvalidRequest(format!(
+                "Invalid snapshot name '{}'",
+                snapshot
+            )));
+        }
+
+        let mut request = self
+            .http
+            .put(format!(
+                "{}/collections/{}/snapshots/recover?wait=true",
+                rest_url, target
+            ))
+            .json(&serde_json::json!({
+                "location": self.location(collection, snapshot),
+                "priority": "snapshot",
+            }));
+        if let Some(api_key) = &self.api_key {
+            request = request.header("api-key", api_key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AppError::Qdrant(format!("Failed to restore {}: {}", snapshot, e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::Qdrant(format!(
+                "Failed to restore {} ({}): {}",
+                snapshot, status, body
+            )));
+        }
+        info!("Snapshot restored");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_info() {
+        let snapshot = snapshot_info(
+            "vehicles",
+            SnapshotDescription {
+                name: "vehicles-1-2025-10-08.snapshot".to_string(),
+                creation_time: Some(qdrant_client::qdrant::Timestamp {
+                    seconds: 1_759_905_000,
+                    nanos: 0,
+                }),
+                size: 2048,
+                checksum: None,
+            },
+        );
+        assert_eq!(
+            snapshot.created_at.as_deref(),
+            Some("2025-10-08T06:30:00+00:00")
+        );
+        assert_eq!(snapshot.size_bytes, 2048);
+    }
+}
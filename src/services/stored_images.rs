@@ -0,0 +1,109 @@
+//! Stored Image Lookup
+//!
+//! Finds fetched images that are already in Qdrant, so overlapping fetch
+//! windows do not pay for embedding the same images again.
+
+use crate::error::AppError;
+use crate::models::search::CctvImageData;
+use crate::services::{ShardRouter, extract_string, point_id_to_string};
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{GetPointsBuilder, PayloadIncludeSelector, PointId};
+use std::collections::{HashMap, HashSet};
+use tracing::instrument;
+
+/// Camera and filename of a stored point, keyed by point ID
+type StoredImages = HashMap<String, (String, String)>;
+
+/// IDs of `images` already stored with the same camera and filename
+///
+/// A point with the same ID but another camera or file is an ID collision,
+/// not a duplicate, so that image is embedded again.
+#[instrument(skip_all, fields(images = images.len()))]
+pub async fn stored_image_ids(
+    qdrant: &Qdrant,
+    router: &ShardRouter,
+    images: &[&CctvImageData],
+) -> Result<HashSet<u64>, AppError> {
+    let mut by_collection: HashMap<&str, Vec<PointId>> = HashMap::new();
+    for image in images {
+        by_collection
+            .entry(router.collection_for(&image.cctv_id))
+            .or_default()
+            .push(PointId::from(image.id as u64));
+    }
+
+    let mut stored = StoredImages::new();
+    for (collection_name, ids) in by_collection {
+        let response = qdrant
+            .get_points(GetPointsBuilder::new(collection_name, ids).with_payload(
+                PayloadIncludeSelector {
+                    fields: vec!["camera_id".to_string(), "filename".to_string()],
+                },
+            ))
+            .await
+            .map_err(|e| AppError::Qdrant(format!("Stored image lookup failed: {}", e)))?;
+
+        stored.extend(response.result.into_iter().filter_map(|p| {
+            Some((
+                point_id_to_string(p.id.as_ref()?),
+                (
+                    extract_string(&p.payload, "camera_id"),
+                    extract_string(&p.payload, "filename"),
+                ),
+            ))
+        }));
+    }
+
+    Ok(already_stored(images, &stored))
+}
+
+fn already_stored(images: &[&CctvImageData], stored: &StoredImages) -> HashSet<u64> {
+    images
+        .iter()
+        .filter(|image| {
+            stored
+                .get(&image.id.to_string())
+                .is_some_and(|(camera_id, filename)| {
+                    *camera_id == image.cctv_id && *filename == image.filename
+                })
+        })
+        .map(|image| image.id as u64)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(id: u32, cctv_id: &str, filename: &str) -> CctvImageData {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "cctv_id": cctv_id,
+            "filename": filename,
+            "file_path": format!("/data/{}", filename),
+            "date": "2025-01-01",
+            "time": "08:00:00",
+            "frame": 0,
+            "vehicle_type": 1,
+            "yolo_id": 2
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_already_stored() {
+        let images = [
+            image(1, "cctv01", "a.jpg"),
+            image(2, "cctv01", "b.jpg"),
+            image(3, "cctv02", "c.jpg"),
+        ];
+        let refs: Vec<&CctvImageData> = images.iter().collect();
+        let stored = StoredImages::from([
+            ("1".to_string(), ("cctv01".to_string(), "a.jpg".to_string())),
+            // Same ID from another camera: a collision, not a duplicate
+            ("3".to_string(), ("cctv09".to_string(), "c.jpg".to_string())),
+        ]);
+
+        assert_eq!(already_stored(&refs, &stored), HashSet::from([1]));
+    }
+}
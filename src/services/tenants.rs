@@ -0,0 +1,149 @@
+//! Tenants
+//!
+//! One deployment can serve several customer sites. With `TENANTS` set,
+//! every stored point carries the `tenant_id` of its site, and searches,
+//! listings, inserts and live streams of a request are confined to the
+//! tenant the request resolved to.
+
+use crate::error::AppError;
+use crate::services::with_conditions;
+use qdrant_client::qdrant::{Condition, Filter};
+
+/// Payload field holding the tenant a point belongs to
+pub const TENANT_FIELD: &str = "tenant_id";
+
+/// Tenant a request is bound to by its token's `tenant` claim or its API
+/// key, or 403 if the two name different tenants
+pub fn bound_tenant<'a>(
+    claimed: Option<&'a str>,
+    key_tenant: Option<&'a str>,
+) -> Result<Option<&'a str>, AppError> {
+    match (claimed, key_tenant) {
+        (Some(claimed), Some(key_tenant)) if claimed != key_tenant => Err(AppError::Forbidden(
+            "The token and the API key are bound to different tenants".to_string(),
+        )),
+        (claimed, key_tenant) => Ok(claimed.or(key_tenant)),
+    }
+}
+
+/// Tenant of a request, from its binding (token claim or API key) or, where
+/// trusted, the `X-Tenant-ID` header
+///
+/// `None` while tenancy is disabled. Bound requests can't switch to another
+/// tenant; the header is only trusted without any authentication or for
+/// unbound tokens that grant `admin`.
+pub fn resolve_tenant(
+    tenants: &[String],
+    claimed: Option<&str>,
+    requested: Option<&str>,
+    header_trusted: bool,
+) -> Result<Option<String>, AppError> {
+    if tenants.is_empty() {
+        return Ok(None);
+    }
+    let requested = requested.map(str::trim).filter(|t| !t.is_empty());
+    let tenant = match (claimed, requested) {
+        (Some(claimed), Some(requested)) if claimed != requested => {
+            return Err(AppError::Forbidden(format!(
+                "The request is bound to another tenant than '{}'",
+                requested
+            )));
+        }
+        (Some(claimed), _) => claimed,
+        (None, Some(_)) if !header_trusted => {
+            return Err(AppError::Forbidden(
+                "Neither the token nor the API key is bound to a tenant".to_string(),
+            ));
+        }
+        (None, Some(requested)) => requested,
+        (None, None) => {
+            return Err(AppError::InvalidRequest(
+                "Missing X-Tenant-ID header".to_string(),
+            ));
+        }
+    };
+    if !tenants.iter().any(|t| t == tenant) {
+        return Err(AppError::Forbidden(format!("Unknown tenant '{}'", tenant)));
+    }
+    Ok(Some(tenant.to_string()))
+}
+
+/// `filter` restricted to the points of `tenant`, if any
+pub fn with_tenant(filter: Option<Filter>, tenant: Option<&str>) -> Option<Filter> {
+    match tenant {
+        Some(tenant) => with_conditions(
+            filter,
+            vec![Condition::matches(TENANT_FIELD, tenant.to_string())],
+        ),
+        None => filter,
+    }
+}
+
+/// Tenant an image of a camera registered to `camera_tenant` is stored
+/// with, or 403 if the request's `tenant` is another one
+pub fn image_tenant(
+    cctv_id: &str,
+    camera_tenant: Option<String>,
+    tenant: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    match (camera_tenant, tenant) {
+        (Some(camera_tenant), Some(tenant)) if camera_tenant != tenant => Err(AppError::Forbidden(
+            format!("Camera {} belongs to another tenant", cctv_id),
+        )),
+        (Some(camera_tenant), _) => Ok(Some(camera_tenant)),
+        (None, tenant) => Ok(tenant.map(str::to_string)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_tenant() {
+        let tenants = vec!["site-a".to_string(), "site-b".to_string()];
+        assert_eq!(
+            resolve_tenant(&[], None, Some("site-a"), false).unwrap(),
+            None
+        );
+        assert_eq!(
+            resolve_tenant(&tenants, Some("site-a"), None, false).unwrap(),
+            Some("site-a".to_string())
+        );
+        assert_eq!(
+            resolve_tenant(&tenants, None, Some(" site-b "), true).unwrap(),
+            Some("site-b".to_string())
+        );
+        assert!(matches!(
+            resolve_tenant(&tenants, Some("site-a"), Some("site-b"), true),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(matches!(
+            resolve_tenant(&tenants, None, Some("site-b"), false),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(matches!(
+            resolve_tenant(&tenants, None, Some("site-c"), true),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(matches!(
+            resolve_tenant(&tenants, None, None, true),
+            Err(AppError::InvalidRequest(_))
+        ));
+
+        assert_eq!(bound_tenant(None, Some("site-a")).unwrap(), Some("site-a"));
+        assert_eq!(
+            bound_tenant(Some("site-a"), Some("site-a")).unwrap(),
+            Some("site-a")
+        );
+        assert!(bound_tenant(Some("site-a"), Some("site-b")).is_err());
+
+        assert!(with_tenant(None, None).is_none());
+        assert_eq!(with_tenant(None, Some("site-a")).unwrap().must.len(), 1);
+        assert_eq!(
+            image_tenant("cctv01", None, Some("site-a")).unwrap(),
+            Some("site-a".to_string())
+        );
+        assert!(image_tenant("cctv01", Some("site-b".to_string()), Some("site-a")).is_err());
+    }
+}
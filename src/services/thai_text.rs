@@ -0,0 +1,340 @@
+//! Thai Text
+//!
+//! Thai is written without spaces between words, so the word tokenizer of
+//! the full-text index sees a whole Thai caption as one token and keyword
+//! searches for a single word miss it. Captions containing Thai are also
+//! stored normalized and segmented into space-separated words by the ICU
+//! dictionary word segmenter, and hybrid search queries are segmented the
+//! same way before matching.
+
+use icu_segmenter::WordSegmenter;
+
+/// Payload field holding the segmented words of a Thai caption
+pub const CAPTION_TERMS_FIELD: &str = "caption_terms";
+
+thread_local! {
+    /// ICU's Thai dictionary, so words it doesn't know stay whole
+    static SEGMENTER: WordSegmenter = WordSegmenter::new_dictionary();
+}
+
+/// Words likely to appear in captions of traffic cameras
+///
+/// A word ICU keeps whole is split further when it is made up entirely of
+/// these, so each part can be searched for on its own: "สีน้ำเงิน" is stored
+/// as "สี น้ำเงิน" and found by "น้ำเงิน".
+const THAI_WORDS: &[&str] = &[
+    // Vehicles
+    "รถ",
+    "เก๋ง",
+    "กระบะ",
+    "ตู้",
+    "บรรทุก",
+    "พ่วง",
+    "บัส",
+    "โดยสาร",
+    "แท็กซี่",
+    "จักรยานยนต์",
+    "จักรยาน",
+    "มอเตอร์ไซค์",
+    "สามล้อ",
+    "ตุ๊กตุ๊ก",
+    "ตำรวจ",
+    "พยาบาล",
+    "ดับเพลิง",
+    "ขยะ",
+    "คัน",
+    "ล้อ",
+    "ป้าย",
+    "ทะเบียน",
+    "หลังคา",
+    "กระจก",
+    "ไฟ",
+    "ท้าย",
+    "ยนต์",
+    "เมล์",
+    // Colors
+    "สี",
+    "ขาว",
+    "ดำ",
+    "แดง",
+    "เทา",
+    "เงิน",
+    "น้ำเงิน",
+    "ฟ้า",
+    "เขียว",
+    "เหลือง",
+    "ส้ม",
+    "น้ำตาล",
+    "ชมพู",
+    "ม่วง",
+    "ทอง",
+    "เข้ม",
+    "อ่อน",
+    // People
+    "คน",
+    "ผู้ชาย",
+    "ผู้หญิง",
+    "ชาย",
+    "หญิง",
+    "เด็ก",
+    "เดินเท้า",
+    "หมวก",
+    "เสื้อ",
+    // Places
+    "ถนน",
+    "ทาง",
+    "ทางม้าลาย",
+    "แยก",
+    "สี่แยก",
+    "สามแยก",
+    "ประตู",
+    "ลาน",
+    "ซอย",
+    "สะพาน",
+    "ปั๊ม",
+    "น้ำมัน",
+    "อาคาร",
+    "ด่าน",
+    "เลน",
+    "ฟุตบาท",
+    "ทางเท้า",
+    // Positions and directions
+    "หน้า",
+    "หลัง",
+    "ข้าง",
+    "ซ้าย",
+    "ขวา",
+    "บน",
+    "ใต้",
+    "ใน",
+    "นอก",
+    "ใกล้",
+    "ไกล",
+    "กลาง",
+    "ตรง",
+    // Actions
+    "จอด",
+    "ขับ",
+    "ขี่",
+    "วิ่ง",
+    "เดิน",
+    "กลับ",
+    "เลี้ยว",
+    "เข้า",
+    "ออก",
+    "ผ่าน",
+    "ข้าม",
+    "หยุด",
+    "ชน",
+    "กำลัง",
+    // Time and weather
+    "กลางวัน",
+    "กลางคืน",
+    "ฝน",
+    "ตก",
+    "ตอน",
+    "เช้า",
+    "เย็น",
+    "หมอก",
+    "มืด",
+    "สว่าง",
+    // Descriptions
+    "ใหญ่",
+    "เล็ก",
+    "ใหม่",
+    "เก่า",
+    "หนึ่ง",
+    "สอง",
+    "สาม",
+    "สี่",
+    "ห้า",
+    "หลาย",
+    "มี",
+    "ที่",
+    "และ",
+    "กับ",
+    "ของ",
+    "บาง",
+];
+
+/// Normalized caption words to index, only for captions containing Thai
+///
+/// Captions without Thai are already split into words by the full-text
+/// index of `caption`.
+pub fn caption_terms(caption: &str) -> Option<String> {
+    let terms = keyword_terms(caption);
+    (caption.chars().any(is_thai) && !terms.is_empty()).then_some(terms)
+}
+
+/// `text` normalized, with Thai runs segmented into space-separated words
+pub fn keyword_terms(text: &str) -> String {
+    let normalized = normalize_thai(text);
+    let mut words = Vec::new();
+    for token in normalized.split_whitespace() {
+        let mut rest = token;
+        while let Some(first) = rest.chars().next() {
+            let thai = is_thai(first);
+            let end = rest
+                .find(|c: char| is_thai(c) != thai)
+                .unwrap_or(rest.len());
+            let (run, next) = rest.split_at(end);
+            if thai {
+                words.extend(segment_thai(run));
+            } else {
+                words.push(run.to_string());
+            }
+            rest = next;
+        }
+    }
+    words.join(" ")
+}
+
+/// Fix the common Thai typing variants, turn Thai digits into ASCII and
+/// lowercase the rest
+///
+/// Zero-width spaces and ๆ mark word boundaries and become spaces.
+fn normalize_thai(text: &str) -> String {
+    let mut chars: Vec<char> = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        let c = match c {
+            '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{FEFF}' | 'ๆ' | 'ฯ' => ' ',
+            '๐'..='๙' => char::from(b'0' + (c as u32 - '๐' as u32) as u8),
+            _ => c,
+        };
+        match (chars.last().copied(), c) {
+            // Nikhahit and sara aa typed for sara am
+            (Some('\u{0E4D}'), 'า') => {
+                chars.pop();
+                chars.push('ำ');
+            }
+            // The same mark typed twice
+            (Some(previous), c) if previous == c && is_combining(c) => {}
+            // Tone mark typed before the upper vowel it sits on
+            (Some(previous), c) if is_tone_mark(previous) && is_upper_vowel(c) => {
+                chars.pop();
+                chars.push(c);
+                chars.push(previous);
+            }
+            _ => chars.extend(c.to_lowercase()),
+        }
+    }
+    chars.into_iter().collect()
+}
+
+/// Words of a run of Thai characters, with known compounds split
+fn segment_thai(run: &str) -> Vec<String> {
+    // Never break inside a character cluster, which ICU occasionally does
+    let boundaries: Vec<usize> = SEGMENTER.with(|segmenter| {
+        segmenter
+            .segment_str(run)
+            .filter(|&i| i == 0 || i == run.len() || starts_cluster(&run[..i], &run[i..]))
+            .collect()
+    });
+    boundaries
+        .windows(2)
+        .map(|bounds| &run[bounds[0]..bounds[1]])
+        .flat_map(|word| match known_parts(word) {
+            Some(parts) => parts.into_iter().map(str::to_string).collect(),
+            None => vec![word.to_string()],
+        })
+        .collect()
+}
+
+/// `word` as a sequence of dictionary words, preferring longer ones; `None`
+/// if some part isn't one
+fn known_parts(word: &str) -> Option<Vec<&str>> {
+    if word.is_empty() {
+        return Some(Vec::new());
+    }
+    let mut candidates: Vec<&str> = THAI_WORDS
+        .iter()
+        .copied()
+        .filter(|known| word.starts_with(known) && ends_cluster(&word[known.len()..]))
+        .collect();
+    candidates.sort_by_key(|known| std::cmp::Reverse(known.len()));
+    candidates.into_iter().find_map(|known| {
+        let mut parts = known_parts(&word[known.len()..])?;
+        parts.insert(0, known);
+        Some(parts)
+    })
+}
+
+/// Whether a character cluster starts at `rest`, right after `before`
+fn starts_cluster(before: &str, rest: &str) -> bool {
+    ends_cluster(rest)
+        && before
+            .chars()
+            .next_back()
+            .is_none_or(|c| !is_leading_vowel(c))
+}
+
+/// Whether a word may end right before `rest`, i.e. `rest` doesn't continue
+/// the last character cluster
+fn ends_cluster(rest: &str) -> bool {
+    rest.chars().next().is_none_or(|c| !is_following(c))
+}
+
+fn is_thai(c: char) -> bool {
+    ('\u{0E01}'..='\u{0E5B}').contains(&c)
+}
+
+fn is_leading_vowel(c: char) -> bool {
+    ('\u{0E40}'..='\u{0E44}').contains(&c)
+}
+
+fn is_upper_vowel(c: char) -> bool {
+    c == '\u{0E31}' || ('\u{0E34}'..='\u{0E37}').contains(&c)
+}
+
+fn is_tone_mark(c: char) -> bool {
+    ('\u{0E48}'..='\u{0E4B}').contains(&c)
+}
+
+/// Vowel signs, tone marks and diacritics written above or below a consonant
+fn is_combining(c: char) -> bool {
+    c == '\u{0E31}'
+        || ('\u{0E34}'..='\u{0E3A}').contains(&c)
+        || ('\u{0E47}'..='\u{0E4E}').contains(&c)
+}
+
+/// Characters that never start a cluster
+fn is_following(c: char) -> bool {
+    is_combining(c) || matches!(c, 'ะ' | 'า' | 'ำ' | 'ๅ')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_terms() {
+        assert_eq!(
+            keyword_terms("รถกระบะสีขาวจอดหน้าประตู"),
+            "รถ กระบะ สี ขาว จอด หน้า ประตู"
+        );
+        // Unknown words stay whole, Latin words are lowercased
+        assert_eq!(
+            keyword_terms("รถเก๋งToyotaสีดำที่สุขุมวิท"),
+            "รถ เก๋ง toyota สี ดำ ที่ สุขุมวิท"
+        );
+        // "สีน้ำเงิน" typed with nikhahit + sara aa, and a zero-width space
+        assert_eq!(
+            keyword_terms("รถตู้สีน\u{0E49}\u{0E4D}าเงิน\u{200B}๒คัน"),
+            "รถ ตู้ สี น้ำเงิน 2 คัน"
+        );
+        // "ชน" is not cut out of "ชนิด", which would leave a dangling vowel
+        assert_eq!(keyword_terms("ชนิดรถ"), "ชนิด รถ");
+        // Words missing from the caption dictionary are not cut into known
+        // ones: "มีด" (knife) is not "มี ด", "สีลม" (Silom) not "สี ลม"
+        assert_eq!(keyword_terms("มีด"), "มีด");
+        assert_eq!(keyword_terms("สีลม"), "สีลม");
+        assert_eq!(keyword_terms("ชายถือมีดที่สีลม"), "ชาย ถือ มีด ที่ สีลม");
+
+        assert_eq!(caption_terms("white pickup truck"), None);
+        assert_eq!(caption_terms("ๆ"), None);
+        assert_eq!(
+            caption_terms("มอเตอร์ไซค์ 2 คัน").as_deref(),
+            Some("มอเตอร์ไซค์ 2 คัน")
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! Upsert Verification
+//!
+//! Optional read-back of freshly upserted points to catch silent write
+//! issues (e.g. during Qdrant resharding).
+
+use crate::error::AppError;
+use crate::services::{RequestMetrics, point_id_to_string};
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::read_consistency::Value as ConsistencyValue;
+use qdrant_client::qdrant::{GetPointsBuilder, PointStruct, ReadConsistencyType, Value};
+use std::collections::HashMap;
+use tracing::{instrument, warn};
+
+/// Payload keys whose stored value differs from the expected one
+pub fn payload_mismatches(
+    expected: &HashMap<String, Value>,
+    actual: &HashMap<String, Value>,
+) -> Vec<String> {
+    let mut keys: Vec<String> = expected
+        .iter()
+        .filter(|(key, value)| actual.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    keys.sort();
+    keys
+}
+
+/// Read `points` back from all replicas and compare their payloads
+///
+/// Returns one description per mismatching point. The outcome is logged and
+/// recorded in `metrics`.
+#[instrument(skip(qdrant, points, metrics), fields(points = points.len()))]
+pub async fn verify_upsert(
+    qdrant: &Qdrant,
+    collection_name: &str,
+    points: &[PointStruct],
+    metrics: &RequestMetrics,
+) -> Result<Vec<String>, AppError> {
+    let ids: Vec<_> = points.iter().filter_map(|p| p.id.clone()).collect();
+
+    let response = qdrant
+        .get_points(
+            GetPointsBuilder::new(collection_name, ids)
+                .with_payload(true)
+                .read_consistency(ConsistencyValue::Type(ReadConsistencyType::All.into())),
+        )
+        .await
+        .map_err(|e| {
+            metrics.record_upsert_verification_error();
+            AppError::Qdrant(format!("Upsert verification read-back failed: {}", e))
+        })?;
+
+    let stored: HashMap<String, HashMap<String, Value>> = response
+        .result
+        .into_iter()
+        .filter_map(|p| Some((point_id_to_string(p.id.as_ref()?), p.payload)))
+        .collect();
+
+    let mut mismatches = Vec::new();
+    for point in points {
+        let Some(id) = point.id.as_ref().map(point_id_to_string) else {
+            continue;
+        };
+        match stored.get(&id) {
+            None => mismatches.push(format!("point {}: missing after upsert", id)),
+            Some(payload) => {
+                let fields = payload_mismatches(&point.payload, payload);
+                if !fields.is_empty() {
+                    mismatches.push(format!(
+                        "point {}: fields differ: {}",
+                        id,
+                        fields.join(", ")
+                    ));
+                }
+            }
+        }
+    }
+
+    for mismatch in &mismatches {
+        warn!(collection = collection_name, mismatch = %mismatch, "Upsert verification mismatch");
+    }
+    metrics.record_upsert_verification(points.len() as u64, mismatches.len() as u64);
+
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::PayloadBuilder;
+
+    #[test]
+    fn test_payload_mismatches() {
+        let expected: HashMap<String, Value> = PayloadBuilder::new()
+            .string("camera_id", "cctv01")
+            .integer("frame", 12)
+            .build();
+        let mut actual = expected.clone();
+        assert!(payload_mismatches(&expected, &actual).is_empty());
+
+        // Extra stored fields are fine, changed or missing ones are not
+        actual.insert("extra".to_string(), Value::from(true));
+        actual.insert("frame".to_string(), Value::from(13));
+        actual.remove("camera_id");
+        assert_eq!(
+            payload_mismatches(&expected, &actual),
+            vec!["camera_id", "frame"]
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Image URL Rewriting
+//!
+//! Maps the stored `file_path` of an image, which may point at a host only
+//! reachable inside the camera network, to a URL the frontend can load.
+
+use std::borrow::Cow;
+use std::str::FromStr;
+
+/// One rewrite rule, parsed from `from=>to`
+#[derive(Debug, Clone, PartialEq)]
+pub enum UrlRewriteRule {
+    /// Replace a leading `from`, e.g. `http://10.0.0.5:8080/images/=>https://cdn.example.com/cctv/`
+    Prefix { from: String, to: String },
+    /// Replace the host (and port) of the URL, e.g. `host:10.0.0.5:8080=>images.example.com`
+    Host { from: String, to: String },
+}
+
+impl FromStr for UrlRewriteRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid image URL rewrite '{}' (expected prefix=>replacement or host:name=>name)",
+                s
+            )
+        };
+
+        let (from, to) = s.split_once("=>").ok_or_else(invalid)?;
+        let (from, to) = (from.trim(), to.trim());
+        match from.strip_prefix("host:") {
+            Some(host) if !host.is_empty() && !to.is_empty() && !to.contains('/') => {
+                Ok(UrlRewriteRule::Host {
+                    from: host.to_string(),
+                    to: to.to_string(),
+                })
+            }
+            Some(_) => Err(invalid()),
+            None if !from.is_empty() => Ok(UrlRewriteRule::Prefix {
+                from: from.to_string(),
+                to: to.to_string(),
+            }),
+            None => Err(invalid()),
+        }
+    }
+}
+
+impl UrlRewriteRule {
+    fn apply(&self, url: &str) -> Option<String> {
+        match self {
+            UrlRewriteRule::Prefix { from, to } => url
+                .strip_prefix(from.as_str())
+                .map(|rest| format!("{}{}", to, rest)),
+            UrlRewriteRule::Host { from, to } => {
+                let (scheme, rest) = url.split_once("://")?;
+                let host_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+                (rest[..host_end].eq_ignore_ascii_case(from))
+                    .then(|| format!("{}://{}{}", scheme, to, &rest[host_end..]))
+            }
+        }
+    }
+}
+
+/// Rules applied in order; the first matching rule wins
+#[derive(Debug, Default)]
+pub struct UrlRewriter {
+    rules: Vec<UrlRewriteRule>,
+}
+
+impl UrlRewriter {
+    pub fn new(rules: Vec<UrlRewriteRule>) -> Self {
+        Self { rules }
+    }
+
+    /// `url` as served to clients; unchanged when no rule matches
+    pub fn rewrite<'a>(&self, url: &'a str) -> Cow<'a, str> {
+        self.rules
+            .iter()
+            .find_map(|rule| rule.apply(url))
+            .map_or(Cow::Borrowed(url), Cow::Owned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_rules() {
+        let rewriter = UrlRewriter::new(vec![
+            "http://10.0.0.5:8080/images/=>https://cdn.example.com/cctv/"
+                .parse()
+                .unwrap(),
+            "host:10.0.0.6=>images.example.com".parse().unwrap(),
+        ]);
+        assert_eq!(
+            rewriter.rewrite("http://10.0.0.5:8080/images/cctv01/a.jpg"),
+            "https://cdn.example.com/cctv/cctv01/a.jpg"
+        );
+        assert_eq!(
+            rewriter.rewrite("http://10.0.0.6/cctv02/b.jpg?size=full"),
+            "http://images.example.com/cctv02/b.jpg?size=full"
+        );
+        // A longer host is not a match
+        assert_eq!(
+            rewriter.rewrite("http://10.0.0.66/c.jpg"),
+            "http://10.0.0.66/c.jpg"
+        );
+        assert_eq!(rewriter.rewrite("/data/d.jpg"), "/data/d.jpg");
+
+        assert!("no-arrow".parse::<UrlRewriteRule>().is_err());
+        assert!("host:=>cdn".parse::<UrlRewriteRule>().is_err());
+        assert!("host:10.0.0.6=>cdn/path".parse::<UrlRewriteRule>().is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! Vector Layout
+//!
+//! How embeddings are stored on a point: a single unnamed image vector, or
+//! named `clip_image` and `caption_text` vectors so the same images can be
+//! searched by what they show and by their captions.
+
+use crate::error::AppError;
+use crate::models::search::VectorSpace;
+use crate::services::{AiPriority, RequestMetrics, get_text_embedding, validate_embedding};
+use qdrant_client::qdrant::vector_output::Vector as VectorOutput;
+use qdrant_client::qdrant::vectors_output::VectorsOptions;
+use qdrant_client::qdrant::{
+    Vector, VectorParams, VectorParamsMap, Vectors, VectorsConfig, VectorsOutput,
+};
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Named vector holding the image embedding
+pub const IMAGE_VECTOR: &str = "clip_image";
+/// Named vector holding the caption text embedding
+pub const CAPTION_VECTOR: &str = "caption_text";
+
+/// Vectors of the points in the collections
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VectorLayout {
+    /// One unnamed vector with the image embedding
+    #[default]
+    Single,
+    /// `clip_image` and, for images with a caption, `caption_text`
+    Named,
+}
+
+/// Parse a vector layout: `single` or `named`
+pub fn parse_vector_layout(value: &str) -> Result<VectorLayout, AppError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "single" => Ok(VectorLayout::Single),
+        "named" => Ok(VectorLayout::Named),
+        _ => Err(AppError::Parse(format!(
+            "Invalid vector layout '{}': expected single or named",
+            value
+        ))),
+    }
+}
+
+impl VectorLayout {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VectorLayout::Single => "single",
+            VectorLayout::Named => "named",
+        }
+    }
+
+    /// Name of the image vector; `""` is the unnamed vector
+    pub fn image_vector(self) -> &'static str {
+        match self {
+            VectorLayout::Single => "",
+            VectorLayout::Named => IMAGE_VECTOR,
+        }
+    }
+
+    /// Name of the vector holding `space`; `""` is the unnamed vector
+    pub fn vector_name(self, space: VectorSpace) -> Result<&'static str, AppError> {
+        match (self, space) {
+            (layout, VectorSpace::Image) => Ok(layout.image_vector()),
+            (VectorLayout::Single, VectorSpace::Caption) => Err(AppError::InvalidRequest(
+                "Caption search requires VECTOR_LAYOUT=named".to_string(),
+            )),
+            (VectorLayout::Named, VectorSpace::Caption) => Ok(CAPTION_VECTOR),
+        }
+    }
+
+    /// `vector_name`/`using` of a search in `space`; `None` for the unnamed vector
+    pub fn using(self, space: VectorSpace) -> Result<Option<String>, AppError> {
+        let name = self.vector_name(space)?;
+        Ok(Some(name.to_string()).filter(|name| !name.is_empty()))
+    }
+
+    /// Vectors of a new point; the single layout has no room for a caption
+    pub fn point_vectors(self, image: Vec<f32>, caption: Option<Vec<f32>>) -> Vectors {
+        match self {
+            VectorLayout::Single => image.into(),
+            VectorLayout::Named => {
+                let mut vectors = HashMap::from([(IMAGE_VECTOR.to_string(), image)]);
+                if let Some(caption) = caption {
+                    vectors.insert(CAPTION_VECTOR.to_string(), caption);
+                }
+                vectors.into()
+            }
+        }
+    }
+
+    /// Vectors of a new collection, each with `params`
+    pub fn vectors_config(self, params: VectorParams) -> VectorsConfig {
+        match self {
+            VectorLayout::Single => params.into(),
+            VectorLayout::Named => VectorsConfig {
+                config: Some(
+                    VectorParamsMap {
+                        map: HashMap::from([
+                            (IMAGE_VECTOR.to_string(), params),
+                            (CAPTION_VECTOR.to_string(), params),
+                        ]),
+                    }
+                    .into(),
+                ),
+            },
+        }
+    }
+}
+
+/// Caption embedding of an image to store next to its image embedding
+///
+/// `None` without a caption, in the single layout, or when the caption
+/// can't be embedded; the image is then stored without a caption vector.
+pub async fn caption_embedding(
+    layout: VectorLayout,
+    client: &reqwest::Client,
+    ai_service_url: &str,
+    caption: Option<&str>,
+    dimension: usize,
+    metrics: &RequestMetrics,
+) -> Option<Vec<f32>> {
+    let caption = caption.map(str::trim).filter(|c| !c.is_empty())?;
+    if layout == VectorLayout::Single {
+        return None;
+    }
+    let embedded = get_text_embedding(client, ai_service_url, caption, AiPriority::Bulk)
+        .await
+        .and_then(|vector| {
+            validate_embedding(&vector, dimension, "ingest", caption, metrics).map(|_| vector)
+        });
+    match embedded {
+        Ok(vector) => Some(vector),
+        Err(e) => {
+            warn!(error = %e, "Storing image without a caption vector");
+            None
+        }
+    }
+}
+
+/// Dense vector `name` of a stored point (`""` for the unnamed vector)
+pub fn dense_vector(vectors: Option<&VectorsOutput>, name: &str) -> Option<Vec<f32>> {
+    match vectors?.get_vector_by_name(name)? {
+        VectorOutput::Dense(dense) => Some(dense.data),
+        _ => None,
+    }
+}
+
+/// Every dense vector of a stored point, for upserting it elsewhere
+pub fn stored_vectors(vectors: &VectorsOutput) -> Option<Vectors> {
+    match vectors.vectors_options.as_ref()? {
+        VectorsOptions::Vector(_) => Some(dense_vector(Some(vectors), "")?.into()),
+        VectorsOptions::Vectors(named) => {
+            let dense: HashMap<String, Vector> = named
+                .vectors
+                .iter()
+                .filter_map(|(name, vector)| match vector.clone().into_vector() {
+                    VectorOutput::Dense(dense) => Some((name.clone(), dense.data.into())),
+                    _ => None,
+                })
+                .collect();
+            Some(dense.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qdrant_client::qdrant::vectors::VectorsOptions as PointVectors;
+
+    #[test]
+    fn test_vector_names() {
+        let named = VectorLayout::Named;
+        assert_eq!(
+            named.vector_name(VectorSpace::Caption).unwrap(),
+            CAPTION_VECTOR
+        );
+        assert_eq!(
+            VectorLayout::Single.using(VectorSpace::Image).unwrap(),
+            None
+        );
+        assert!(matches!(
+            VectorLayout::Single.vector_name(VectorSpace::Caption),
+            Err(AppError::InvalidRequest(_))
+        ));
+
+        let vectors = named.point_vectors(vec![1.0], None);
+        let Some(PointVectors::Vectors(vectors)) = vectors.vectors_options else {
+            panic!("expected named vectors");
+        };
+        assert_eq!(vectors.vectors.len(), 1);
+        assert!(vectors.vectors.contains_key(IMAGE_VECTOR));
+    }
+}
@@ -0,0 +1,94 @@
+//! Vehicle Type Labels
+//!
+//! Local JSON mapping from the raw `vehicle_type` and `yolo_id` codes sent by
+//! the CCTV API to human-readable labels.
+
+use crate::error::AppError;
+use crate::models::cctv::{VehicleTypeLabel, VehicleTypeMapping};
+use std::path::Path;
+
+/// Payload field holding the label of `vehicle_type`
+pub const VEHICLE_TYPE_LABEL_FIELD: &str = "vehicle_type_label";
+/// Payload field holding the label of `yolo_id`
+pub const YOLO_LABEL_FIELD: &str = "yolo_label";
+
+/// Code labels loaded once at startup
+#[derive(Debug, Default)]
+pub struct VehicleTypes {
+    mapping: VehicleTypeMapping,
+}
+
+impl VehicleTypes {
+    /// Load the mapping from `path` (a missing file yields no labels)
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AppError> {
+        let path = path.as_ref();
+
+        let mapping = match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| {
+                AppError::Config(format!(
+                    "Failed to parse vehicle type mapping {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => VehicleTypeMapping::default(),
+            Err(e) => {
+                return Err(AppError::Io(format!(
+                    "Failed to read vehicle type mapping {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+        };
+
+        Ok(Self { mapping })
+    }
+
+    /// Label of a `vehicle_type` code, if mapped
+    pub fn vehicle_type_label(&self, code: u32) -> Option<&str> {
+        find_label(&self.mapping.vehicle_types, code)
+    }
+
+    /// Label of a `yolo_id` code, if mapped
+    pub fn yolo_label(&self, code: u32) -> Option<&str> {
+        find_label(&self.mapping.yolo_ids, code)
+    }
+
+    /// The whole mapping, in file order
+    pub fn mapping(&self) -> &VehicleTypeMapping {
+        &self.mapping
+    }
+
+    pub fn len(&self) -> usize {
+        self.mapping.vehicle_types.len() + self.mapping.yolo_ids.len()
+    }
+}
+
+fn find_label(labels: &[VehicleTypeLabel], code: u32) -> Option<&str> {
+    labels
+        .iter()
+        .find(|l| l.code == code)
+        .map(|l| l.label.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels() {
+        let types = VehicleTypes {
+            mapping: serde_json::from_str(
+                r#"{"vehicle_types": [{"code": 2, "label": "Pickup truck"}]}"#,
+            )
+            .unwrap(),
+        };
+        assert_eq!(types.vehicle_type_label(2), Some("Pickup truck"));
+        assert_eq!(types.vehicle_type_label(3), None);
+        assert_eq!(types.yolo_label(2), None);
+        assert_eq!(types.len(), 1);
+
+        let missing = VehicleTypes::load("/nonexistent/vehicle_types.json").unwrap();
+        assert_eq!(missing.len(), 0);
+    }
+}
@@ -0,0 +1,387 @@
+//! Webhooks
+//!
+//! Delivers ingest events to external systems: the summary of each finished
+//! fetch run and the matches of saved search alerts are POSTed as JSON to
+//! `WEBHOOK_URL`, and alert matches also to the alert's own webhook.
+//! Deliveries are signed with HMAC-SHA256, keyed by `WEBHOOK_SECRET` or the
+//! alert's own secret, and retried with exponential backoff in the
+//! background. Alert webhooks are set by API clients, so they may only reach
+//! public addresses unless their host is in `WEBHOOK_ALLOWED_HOSTS`.
+
+use crate::config::{Config, technical};
+use crate::models::alert::AlertNotification;
+use crate::services::SchedulerRun;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{StatusCode, Url, redirect};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Header naming the event of a delivery
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+/// Header with the Unix time a delivery attempt was signed at
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+/// Header with `sha256=<hex HMAC of "<timestamp>.<body>">`
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+/// Body of a webhook delivery: `{"event": ..., "data": ...}`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", content = "data")]
+pub enum WebhookEvent {
+    /// A scheduled or manual fetch run finished or was skipped
+    #[serde(rename = "run.finished")]
+    RunFinished(SchedulerRun),
+    /// A stored batch matched a saved search
+    #[serde(rename = "alert.matched")]
+    AlertMatched(AlertNotification),
+}
+
+impl WebhookEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::RunFinished(_) => "run.finished",
+            WebhookEvent::AlertMatched(_) => "alert.matched",
+        }
+    }
+}
+
+/// Sends webhook events without blocking the caller
+#[derive(Debug, Clone, Default)]
+pub struct WebhookDispatcher {
+    http_client: reqwest::Client,
+    url: Option<String>,
+    secret: Option<String>,
+    /// Hosts alert webhooks may reach on private addresses
+    allowed_hosts: Vec<String>,
+    max_retries: u32,
+    retry_backoff: Duration,
+}
+
+impl WebhookDispatcher {
+    pub fn new(http_client: reqwest::Client, config: &Config) -> Self {
+        Self {
+            http_client,
+            url: config.webhook_url.clone(),
+            secret: config.webhook_secret.clone(),
+            allowed_hosts: config.webhook_allowed_hosts.clone(),
+            max_retries: config.webhook_max_retries,
+            retry_backoff: Duration::from_millis(config.webhook_retry_backoff_ms),
+        }
+    }
+
+    /// Send `event` to `WEBHOOK_URL`, if set
+    pub fn dispatch(&self, event: &WebhookEvent) {
+        if let Some(url) = &self.url {
+            self.send(url, self.secret.clone(), false, event);
+        }
+    }
+
+    /// Send `event` to the webhook of an alert, signed with its `secret`
+    pub fn dispatch_to_alert(&self, url: &str, secret: Option<&str>, event: &WebhookEvent) {
+        self.send(url, secret.map(str::to_string), true, event);
+    }
+
+    /// Send `event` to `url` in the background, only to public addresses if
+    /// `guarded`; failures are only logged
+    fn send(&self, url: &str, secret: Option<String>, guarded: bool, event: &WebhookEvent) {
+        let body = match serde_json::to_vec(event) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!(event = event.name(), error = %e, "Failed to serialize webhook event");
+                return;
+            }
+        };
+        let dispatcher = self.clone();
+        let url = url.to_string();
+        let event = event.name();
+        tokio::spawn(async move {
+            let client = if guarded {
+                match dispatcher.guarded_client(&url).await {
+                    Ok(client) => client,
+                    Err(reason) => {
+                        warn!(event, url, reason, "Refused webhook target");
+                        return;
+                    }
+                }
+            } else {
+                dispatcher.http_client.clone()
+            };
+            dispatcher
+                .deliver(&client, &url, secret.as_deref(), event, body)
+                .await
+        });
+    }
+
+    /// Client reaching `url` only at the public addresses its host resolves
+    /// to now, so a later DNS answer can't point it elsewhere, and without
+    /// following redirects
+    async fn guarded_client(&self, url: &str) -> Result<reqwest::Client, &'static str> {
+        let host = check_alert_target(url, &self.allowed_hosts)?;
+        let builder = reqwest::Client::builder().redirect(redirect::Policy::none());
+        let builder = match host {
+            AlertTarget::Allowed => return Ok(self.http_client.clone()),
+            AlertTarget::Address => builder,
+            AlertTarget::Domain(domain, port) => {
+                let addrs: Vec<SocketAddr> = tokio::net::lookup_host((domain.as_str(), port))
+                    .await
+                    .map_err(|_| "host does not resolve")?
+                    .collect();
+                if addrs.is_empty() || !addrs.iter().all(|addr| is_public(addr.ip())) {
+                    return Err(PRIVATE_TARGET);
+                }
+                builder.resolve_to_addrs(&domain, &addrs)
+            }
+        };
+        builder
+            .build()
+            .map_err(|_| "failed to build an HTTP client")
+    }
+
+    /// POST `body` to `url`, retrying 5xx, 429 and connection failures
+    async fn deliver(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        secret: Option<&str>,
+        event: &str,
+        body: Vec<u8>,
+    ) {
+        for attempt in 0..=self.max_retries {
+            if attempt > 0 {
+                let delay = self
+                    .retry_backoff
+                    .saturating_mul(2u32.saturating_pow(attempt - 1));
+                tokio::time::sleep(delay).await;
+            }
+
+            let timestamp = chrono::Utc::now().timestamp().to_string();
+            let mut request = client
+                .post(url)
+                .timeout(Duration::from_secs(technical::WEBHOOK_TIMEOUT_SECS))
+                .header(CONTENT_TYPE, "application/json")
+                .header(EVENT_HEADER, event)
+                .header(TIMESTAMP_HEADER, &timestamp)
+                .body(body.clone());
+            if let Some(secret) = secret {
+                request = request.header(SIGNATURE_HEADER, signature(secret, &timestamp, &body));
+            }
+
+            match request.send().await {
+                Ok(res) if res.status().is_success() => {
+                    debug!(event, attempt, "Webhook delivered");
+                    return;
+                }
+                Ok(res) if is_retryable(res.status()) => {
+                    warn!(event, attempt, status = %res.status(), "Webhook delivery failed");
+                }
+                Ok(res) => {
+                    warn!(event, status = %res.status(), "Webhook rejected delivery");
+                    return;
+                }
+                Err(e) => warn!(event, attempt, error = %e, "Webhook delivery failed"),
+            }
+        }
+        warn!(
+            event,
+            attempts = self.max_retries + 1,
+            "Giving up on webhook delivery"
+        );
+    }
+}
+
+const PRIVATE_TARGET: &str = "must not target a private, loopback or link-local address";
+
+/// Where an alert webhook may be delivered
+#[derive(Debug, PartialEq)]
+enum AlertTarget {
+    /// A host of `WEBHOOK_ALLOWED_HOSTS`, reached like `WEBHOOK_URL`
+    Allowed,
+    /// A public IP address
+    Address,
+    /// A name whose addresses are checked when delivering, and its port
+    Domain(String, u16),
+}
+
+/// Why an alert may not send its matches to `url`, if it may not
+pub fn alert_target_error(url: &str, allowed_hosts: &[String]) -> Option<&'static str> {
+    check_alert_target(url, allowed_hosts).err()
+}
+
+fn check_alert_target(url: &str, allowed_hosts: &[String]) -> Result<AlertTarget, &'static str> {
+    let url = Url::parse(url).map_err(|_| "must be an http(s) URL")?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("must be an http(s) URL");
+    }
+    let host = url.host_str().ok_or("must name a host")?;
+    if allowed_hosts
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(host))
+    {
+        return Ok(AlertTarget::Allowed);
+    }
+    // The URL parser has already turned numeric forms like 2130706433 into
+    // the address they stand for
+    if let Ok(ip) = host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        return if is_public(ip) {
+            Ok(AlertTarget::Address)
+        } else {
+            Err(PRIVATE_TARGET)
+        };
+    }
+    if host == "localhost" || host.ends_with(".localhost") {
+        return Err(PRIVATE_TARGET);
+    }
+    let port = url.port_or_known_default().unwrap_or(80);
+    Ok(AlertTarget::Domain(host.to_string(), port))
+}
+
+/// Whether `ip` is a globally routable unicast address
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // 0.0.0.0/8, carrier-grade NAT 100.64.0.0/10 and reserved 240.0.0.0/4
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local fc00::/7 and link-local fe80::/10
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80)
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// `sha256=<hex>` HMAC-SHA256 of `<timestamp>.<body>` keyed by `secret`
+pub fn signature(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut message = Vec::with_capacity(timestamp.len() + 1 + body.len());
+    message.extend_from_slice(timestamp.as_bytes());
+    message.push(b'.');
+    message.extend_from_slice(body);
+    let mac: String = hmac_sha256(secret.as_bytes(), &message)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", mac)
+}
+
+/// HMAC-SHA256 (RFC 2104)
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; 64];
+    if key.len() > block.len() {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let inner = Sha256::new()
+        .chain_update(block.map(|b| b ^ 0x36))
+        .chain_update(message)
+        .finalize();
+    Sha256::new()
+        .chain_update(block.map(|b| b ^ 0x5c))
+        .chain_update(inner)
+        .finalize()
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature() {
+        let hex = |mac: [u8; 32]| -> String { mac.map(|b| format!("{:02x}", b)).concat() };
+        // RFC 4231, test case 2
+        assert_eq!(
+            hex(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // RFC 4231, test case 6: a key longer than the block is hashed first
+        assert_eq!(
+            hex(hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+        assert_ne!(
+            signature("Jefe", "1760000000", b"{}"),
+            signature("Jefe", "1760000001", b"{}")
+        );
+
+        let event = WebhookEvent::AlertMatched(AlertNotification {
+            alert_id: "7d3a9c1e5f2b8046".to_string(),
+            name: "Red pickups".to_string(),
+            query: "red pickup truck".to_string(),
+            tenant_id: None,
+            hits: Vec::new(),
+        });
+        let body = serde_json::to_value(&event).unwrap();
+        assert_eq!(body["event"], event.name());
+        assert_eq!(body["data"]["alert_id"], "7d3a9c1e5f2b8046");
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn test_alert_targets() {
+        let allowed = vec!["dispatch.local".to_string()];
+        let target = |url: &str| check_alert_target(url, &allowed);
+
+        assert_eq!(
+            target("https://hooks.example.com/cctv"),
+            Ok(AlertTarget::Domain("hooks.example.com".to_string(), 443))
+        );
+        assert_eq!(target("http://203.0.113.7:8080/"), Err(PRIVATE_TARGET));
+        assert_eq!(target("http://8.8.8.8/hook"), Ok(AlertTarget::Address));
+        assert_eq!(
+            target("http://dispatch.local/hooks/cctv"),
+            Ok(AlertTarget::Allowed)
+        );
+        for url in [
+            "http://127.0.0.1/",
+            "http://localhost:6333/collections",
+            "http://10.0.0.5/",
+            "http://192.168.1.1/",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://100.64.0.1/",
+            "http://0.0.0.0/",
+            "http://[::1]/",
+            "http://[fd00::1]/",
+            "http://[fe80::1]/",
+            "http://[::ffff:127.0.0.1]/",
+            // Numeric forms are parsed into the address they stand for
+            "http://2130706433/",
+            "http://0x7f.1/",
+        ] {
+            assert_eq!(target(url), Err(PRIVATE_TARGET), "{}", url);
+        }
+        assert!(alert_target_error("ftp://example.com/", &allowed).is_some());
+        assert!(alert_target_error("not a url", &allowed).is_some());
+    }
+}